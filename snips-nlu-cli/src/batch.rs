@@ -0,0 +1,229 @@
+//! Parses utterances in batch, for instance to label transcripts offline:
+//!
+//! ```bash
+//! snips-nlu-batch path/to/trained_engine utterances.jsonl --threads 8 > results.jsonl
+//! ```
+//!
+//! Each line of the input is either a JSON string, or a JSON object with an `input` field along
+//! with optional `intents_filter` and `id` fields. The results are written in the order of the
+//! input lines, in the versioned JSON format of the parsing results with the `id` of the
+//! utterance, or as an object with an `error` field when the line cannot be parsed.
+
+extern crate clap;
+#[macro_use]
+extern crate serde_json;
+extern crate snips_nlu_lib;
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process;
+use std::sync::mpsc::{channel, sync_channel};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use clap::{App, Arg};
+use serde_json::Value;
+use snips_nlu_lib::{result_to_json, Error, Result, ResultExt, SnipsNluEngine};
+
+/// Number of lines read in advance per worker thread
+const LINES_BUFFER_SIZE: usize = 64;
+
+fn main() {
+    let matches = App::new("snips-nlu-batch")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Parses JSON-lines utterances with a trained Snips NLU engine")
+        .arg(
+            Arg::with_name("MODEL")
+                .help("Directory or zip archive of the trained engine")
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("INPUT")
+                .help("JSON-lines file of utterances, the standard input being used if absent"),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .long("threads")
+                .takes_value(true)
+                .default_value("4")
+                .help("Number of threads parsing the utterances"),
+        )
+        .get_matches();
+
+    let result = matches
+        .value_of("threads")
+        .unwrap()
+        .parse()
+        .chain_err(|| "Invalid number of threads")
+        .and_then(|threads| {
+            run(
+                matches.value_of("MODEL").unwrap(),
+                matches.value_of("INPUT"),
+                threads,
+            )
+        });
+    if let Err(error) = result {
+        eprintln!("Error: {}", error);
+        for cause in error.iter().skip(1) {
+            eprintln!("Caused by: {}", cause);
+        }
+        process::exit(1);
+    }
+}
+
+fn run(model_path: &str, input_path: Option<&str>, threads: usize) -> Result<()> {
+    let engine = SnipsNluEngine::from_model_path(model_path)
+        .chain_err(|| format!("Cannot load the engine from {:?}", model_path))?;
+    let engine = Arc::new(engine);
+    let reader: Box<BufRead + Send> = match input_path {
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).chain_err(|| format!("Cannot open {:?}", path))?,
+        )),
+        None => Box::new(BufReader::new(io::stdin())),
+    };
+
+    let (line_sender, line_receiver) =
+        sync_channel::<(usize, String)>(threads * LINES_BUFFER_SIZE);
+    let line_receiver = Arc::new(Mutex::new(line_receiver));
+    let (output_sender, output_receiver) = channel::<(usize, String)>();
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let engine = engine.clone();
+            let line_receiver = line_receiver.clone();
+            let output_sender = output_sender.clone();
+            thread::spawn(move || loop {
+                let message = match line_receiver.lock() {
+                    Ok(line_receiver) => line_receiver.recv(),
+                    Err(_) => break,
+                };
+                let (index, line) = match message {
+                    Ok(message) => message,
+                    Err(_) => break,
+                };
+                if output_sender.send((index, process_line(&engine, &line))).is_err() {
+                    break;
+                }
+            })
+        })
+        .collect();
+    drop(output_sender);
+
+    let reader_thread = thread::spawn(move || -> Result<()> {
+        let mut index = 0;
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line_sender.send((index, line)).is_err() {
+                break;
+            }
+            index += 1;
+        }
+        Ok(())
+    });
+
+    // The results are buffered until the results of all the previous lines are written
+    let stdout = io::stdout();
+    let mut writer = BufWriter::new(stdout.lock());
+    let mut pending_outputs = BTreeMap::new();
+    let mut next_index = 0;
+    for (index, output) in output_receiver {
+        pending_outputs.insert(index, output);
+        while let Some(output) = pending_outputs.remove(&next_index) {
+            writeln!(writer, "{}", output)?;
+            next_index += 1;
+        }
+    }
+    writer.flush()?;
+
+    reader_thread
+        .join()
+        .map_err(|_| Error::from("The reading thread panicked"))??;
+    for worker in workers {
+        worker
+            .join()
+            .map_err(|_| Error::from("A parsing thread panicked"))?;
+    }
+    Ok(())
+}
+
+/// Returns the JSON output of the line, which is an error object when the line is invalid
+fn process_line(engine: &SnipsNluEngine, line: &str) -> String {
+    parse_line(engine, line)
+        .unwrap_or_else(|e| json!({ "line": line, "error": e.to_string() }))
+        .to_string()
+}
+
+fn parse_line(engine: &SnipsNluEngine, line: &str) -> Result<Value> {
+    let (input, intents_filter, id) = match serde_json::from_str(line)? {
+        Value::String(input) => (input, None, None),
+        Value::Object(mut utterance) => {
+            let input = match utterance.remove("input") {
+                Some(Value::String(input)) => input,
+                _ => return Err("Missing \"input\" field".into()),
+            };
+            let intents_filter: Option<Vec<String>> = match utterance.remove("intents_filter") {
+                Some(intents_filter) => Some(serde_json::from_value(intents_filter)?),
+                None => None,
+            };
+            (input, intents_filter, utterance.remove("id"))
+        }
+        _ => return Err("Expected a string or an object".into()),
+    };
+    let result = engine.parse(&input, intents_filter.as_ref().map(|i| &**i))?;
+    let mut output = result_to_json(&result)?;
+    if let (Some(id), Some(output)) = (id, output.as_object_mut()) {
+        output.insert("id".to_string(), id);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_lib::SnipsNluEngineBuilder;
+
+    fn test_engine() -> SnipsNluEngine {
+        SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me a coffee"])
+            .intent("MakeTea", &["make me a tea"])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn process_line_should_parse_utterances() {
+        // Given
+        let engine = test_engine();
+
+        // When
+        let string_output = process_line(&engine, r#""make me a tea""#);
+        let object_output = process_line(
+            &engine,
+            r#"{"input": "make me a coffee", "intents_filter": ["MakeCoffee"], "id": 3}"#,
+        );
+
+        // Then
+        let string_output: Value = serde_json::from_str(&string_output).unwrap();
+        let object_output: Value = serde_json::from_str(&object_output).unwrap();
+        assert_eq!("MakeTea", string_output["intent"]["intentName"]);
+        assert_eq!("MakeCoffee", object_output["intent"]["intentName"]);
+        assert_eq!(3, object_output["id"]);
+        assert_eq!(1, object_output["resultFormatVersion"]);
+    }
+
+    #[test]
+    fn process_line_should_report_invalid_lines() {
+        // Given
+        let engine = test_engine();
+
+        // When
+        let output = process_line(&engine, r#"{"text": "make me a tea"}"#);
+
+        // Then
+        let output: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!("Missing \"input\" field", output["error"]);
+    }
+}
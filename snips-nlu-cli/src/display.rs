@@ -0,0 +1,94 @@
+use ansi_term::Colour;
+use serde_json;
+use snips_nlu_ontology::{IntentParserResult, Slot};
+
+const SLOT_COLOURS: [Colour; 5] = [
+    Colour::Green,
+    Colour::Cyan,
+    Colour::Yellow,
+    Colour::Purple,
+    Colour::Blue,
+];
+
+/// Formats the parsing result with the intent, the input whose slots are colored, and the
+/// resolved values of the slots
+pub fn format_result(result: &IntentParserResult) -> String {
+    let mut lines = vec![];
+    match result.intent {
+        Some(ref intent) => lines.push(format!(
+            "{} ({:.3})",
+            Colour::White.bold().paint(intent.intent_name.as_str()),
+            intent.probability
+        )),
+        None => lines.push(Colour::Red.paint("No intent found").to_string()),
+    }
+    let slots = result.slots.as_ref().map(|slots| &**slots).unwrap_or(&[]);
+    lines.push(highlight_slots(&result.input, slots));
+    for (index, slot) in slots.iter().enumerate() {
+        let value = serde_json::to_string(&slot.value).unwrap_or_else(|_| slot.raw_value.clone());
+        lines.push(format!(
+            "  {} ({}) = {}",
+            slot_colour(index).paint(slot.slot_name.as_str()),
+            slot.entity,
+            value
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Colors the span of each slot in the input
+pub fn highlight_slots(input: &str, slots: &[Slot]) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut ranges: Vec<_> = slots
+        .iter()
+        .enumerate()
+        .filter_map(|(index, slot)| slot.range.clone().map(|range| (index, range)))
+        .collect();
+    ranges.sort_by_key(|&(_, ref range)| range.start);
+    let mut highlighted = String::new();
+    let mut current_index = 0;
+    for (slot_index, range) in ranges {
+        if range.start < current_index || range.end > chars.len() {
+            continue;
+        }
+        highlighted.extend(&chars[current_index..range.start]);
+        let slot_text: String = chars[range.clone()].iter().collect();
+        highlighted.push_str(&slot_colour(slot_index).underline().paint(slot_text).to_string());
+        current_index = range.end;
+    }
+    highlighted.extend(&chars[current_index..]);
+    highlighted
+}
+
+fn slot_colour(index: usize) -> Colour {
+    SLOT_COLOURS[index % SLOT_COLOURS.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_ontology::SlotValue;
+
+    fn coffee_slot() -> Slot {
+        Slot {
+            raw_value: "iced lattes".to_string(),
+            value: SlotValue::Custom("iced latte".to_string().into()),
+            range: Some(11..22),
+            entity: "coffee_type".to_string(),
+            slot_name: "coffee_type".to_string(),
+        }
+    }
+
+    #[test]
+    fn highlight_slots_should_keep_text() {
+        // Given
+        let input = "get me two iced lattes";
+
+        // When
+        let highlighted = highlight_slots(input, &[coffee_slot()]);
+
+        // Then
+        let expected = format!("get me two {}", Colour::Green.underline().paint("iced lattes"));
+        assert_eq!(expected, highlighted);
+    }
+}
@@ -0,0 +1,141 @@
+//! Command line tool to debug trained engines:
+//!
+//! ```bash
+//! snips-nlu parse path/to/trained_engine "Make me two cups of coffee"
+//! snips-nlu tag path/to/trained_engine "Make me two cups of coffee"
+//! snips-nlu repl path/to/trained_engine
+//! ```
+
+extern crate ansi_term;
+extern crate clap;
+extern crate serde_json;
+extern crate snips_nlu_lib;
+extern crate snips_nlu_ontology;
+
+mod display;
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use snips_nlu_lib::tagging::bio_tags;
+use snips_nlu_lib::{Result, ResultExt, SnipsNluEngine};
+
+use display::format_result;
+
+fn main() {
+    let model_arg = Arg::with_name("MODEL")
+        .help("Directory or zip archive of the trained engine")
+        .required(true);
+    let input_arg = Arg::with_name("INPUT").required(true);
+    let intents_filter_arg = Arg::with_name("intents_filter")
+        .long("intents-filter")
+        .takes_value(true)
+        .use_delimiter(true)
+        .help("Comma separated intents among which the intent is searched");
+    let matches = App::new("snips-nlu")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Parses inputs with a trained Snips NLU engine")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("parse")
+                .about("Prints the intent and the slots of the input")
+                .arg(model_arg.clone())
+                .arg(input_arg.clone())
+                .arg(intents_filter_arg.clone())
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Prints the result as JSON"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("tag")
+                .about("Prints the tokens of the input with their BIO tag")
+                .arg(model_arg.clone())
+                .arg(input_arg)
+                .arg(intents_filter_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("repl")
+                .about("Parses the lines read on the standard input, loading the engine once")
+                .arg(model_arg)
+                .arg(intents_filter_arg),
+        )
+        .get_matches();
+
+    if let Err(error) = run(&matches) {
+        eprintln!("Error: {}", error);
+        for cause in error.iter().skip(1) {
+            eprintln!("Caused by: {}", cause);
+        }
+        process::exit(1);
+    }
+}
+
+fn run(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        ("parse", Some(matches)) => {
+            let engine = load_engine(matches.value_of("MODEL").unwrap())?;
+            let intents_filter = intents_filter(matches);
+            let input = matches.value_of("INPUT").unwrap();
+            let result = engine.parse(input, intents_filter.as_ref().map(|i| &**i))?;
+            if matches.is_present("json") {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("{}", format_result(&result));
+            }
+        }
+        ("tag", Some(matches)) => {
+            let engine = load_engine(matches.value_of("MODEL").unwrap())?;
+            let intents_filter = intents_filter(matches);
+            let input = matches.value_of("INPUT").unwrap();
+            let result = engine.parse(input, intents_filter.as_ref().map(|i| &**i))?;
+            let tokens = engine.tokenize(input)?;
+            let tags = bio_tags(&tokens, result.slots.as_ref().map(|s| &**s).unwrap_or(&[]));
+            for (token, tag) in tokens.iter().zip(tags) {
+                println!("{}\t{}", token.value, tag);
+            }
+        }
+        ("repl", Some(matches)) => {
+            let engine = load_engine(matches.value_of("MODEL").unwrap())?;
+            let intents_filter = intents_filter(matches);
+            repl(&engine, intents_filter.as_ref().map(|i| &**i))?;
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+fn repl(engine: &SnipsNluEngine, intents_filter: Option<&[String]>) -> Result<()> {
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(()),
+        };
+        let input = line.trim();
+        if input.is_empty() {
+            continue;
+        }
+        match engine.parse(input, intents_filter) {
+            Ok(result) => println!("{}", format_result(&result)),
+            Err(error) => eprintln!("Error: {}", error),
+        }
+    }
+}
+
+fn intents_filter(matches: &ArgMatches) -> Option<Vec<String>> {
+    matches
+        .values_of("intents_filter")
+        .map(|intents| intents.map(|intent| intent.to_string()).collect())
+}
+
+fn load_engine<P: AsRef<Path>>(model_path: P) -> Result<SnipsNluEngine> {
+    SnipsNluEngine::from_model_path(&model_path)
+        .chain_err(|| format!("Cannot load the engine from {:?}", model_path.as_ref()))
+}
@@ -0,0 +1,8 @@
+#!/bin/sh -e
+
+# Regenerates libsnips_nlu.h from the sources of snips-nlu-ffi, which must be done, and the header
+# committed, whenever the C API changes. Requires cbindgen: `cargo install cbindgen`
+
+cd "$(dirname "$0")/.."
+
+cbindgen --config cbindgen.toml --crate snips-nlu-ffi --output c/libsnips_nlu.h
@@ -1,38 +1,114 @@
+/* Generated by cbindgen from the sources of snips-nlu-ffi with c/generate_header.sh, do not edit it manually */
+
 #ifndef LIBSNIPS_NLU_H_
 #define LIBSNIPS_NLU_H_
 
-#ifdef __cplusplus
-extern "C" {
-#endif
-
-typedef enum CPrecision {
-    APPROXIMATE = 0,
-    EXACT = 1,
-} CPrecision;
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
 
 typedef enum CGrain {
-    YEAR = 0,
-    QUARTER = 1,
-    MONTH = 2,
-    WEEK = 3,
-    DAY = 4,
-    HOUR = 5,
-    MINUTE = 6,
-    SECOND = 7,
+  YEAR = 0,
+  QUARTER = 1,
+  MONTH = 2,
+  WEEK = 3,
+  DAY = 4,
+  HOUR = 5,
+  MINUTE = 6,
+  SECOND = 7,
 } CGrain;
 
+typedef enum CPrecision {
+  APPROXIMATE = 0,
+  EXACT = 1,
+} CPrecision;
+
 typedef enum CSlotValueType {
-    CUSTOM = 1,
-    NUMBER = 2,
-    ORDINAL = 3,
-    INSTANTTIME = 4,
-    TIMEINTERVAL = 5,
-    AMOUNTOFMONEY = 6,
-    TEMPERATURE = 7,
-    DURATION = 8,
-    PERCENTAGE = 9,
+  CUSTOM = 1,
+  NUMBER = 2,
+  ORDINAL = 3,
+  INSTANTTIME = 4,
+  TIMEINTERVAL = 5,
+  AMOUNTOFMONEY = 6,
+  TEMPERATURE = 7,
+  DURATION = 8,
+  PERCENTAGE = 9,
 } CSlotValueType;
 
+/**
+ * Kind of the error of the last failed call, see `nlu_engine_get_last_error_code`, which the
+ * Swift wrapper reports as the code of its `NSError`
+ */
+typedef enum NLUERRORCODE {
+  /**
+   * No call failed on the thread
+   */
+  NLU_ERROR_NONE = 0,
+  NLU_ERROR_UNKNOWN = 1,
+  /**
+   * An argument is not a valid UTF-8 string
+   */
+  NLU_ERROR_INVALID_ARGUMENT = 2,
+  /**
+   * The files of the model can't be read
+   */
+  NLU_ERROR_MODEL_LOADING = 3,
+  NLU_ERROR_INCOMPATIBLE_MODEL_VERSION = 4,
+  /**
+   * A file of the model does not match its checksum
+   */
+  NLU_ERROR_CORRUPTED_MODEL = 5,
+  NLU_ERROR_UNKNOWN_INTENT = 6,
+  NLU_ERROR_INPUT_TOO_LONG = 7,
+  NLU_ERROR_WARM_UP_FAILED = 8,
+} NLUERRORCODE;
+
+typedef enum NLURESULT {
+  KO = 0,
+  OK = 1,
+} NLURESULT;
+
+/**
+ * Engine behind the pointers of the C API, which is opaque to the C code
+ */
+typedef struct Opaque Opaque;
+
+typedef struct CIntentClassifierResult {
+  const char *intent_name;
+  float probability;
+} CIntentClassifierResult;
+
+typedef struct CSlotValue {
+  CSlotValueType value_type;
+  /**
+   * Points to either a char *const, a CNumberValue, a COrdinalValue,
+   * a CInstantTimeValue, a CTimeIntervalValue, a CAmountOfMoneyValue,
+   * a CTemperatureValue, a CDurationValue or a CPercentageValue depending on value_type
+   */
+  const void *value;
+} CSlotValue;
+
+typedef struct CSlot {
+  const char *raw_value;
+  CSlotValue value;
+  int range_start;
+  int range_end;
+  const char *entity;
+  const char *slot_name;
+} CSlot;
+
+typedef struct CSlotList {
+  const CSlot *slots;
+  int size;
+} CSlotList;
+
+typedef struct CIntentParserResult {
+  const char *input;
+  const CIntentClassifierResult *intent;
+  const CSlotList *slots;
+} CIntentParserResult;
+
 typedef double CNumberValue;
 
 typedef double CPercentageValue;
@@ -40,101 +116,100 @@ typedef double CPercentageValue;
 typedef long COrdinalValue;
 
 typedef struct CInstantTimeValue {
-    char *const value;
-    CGrain grain;
-    CPrecision precision;
+  const char *value;
+  CGrain grain;
+  CPrecision precision;
 } CInstantTimeValue;
 
 typedef struct CTimeIntervalValue {
-    char *const from;
-    char *const to;
+  const char *from;
+  const char *to;
 } CTimeIntervalValue;
 
 typedef struct CAmountOfMoneyValue {
-    float value;
-    CPrecision precision;
-    char *const unit;
+  float value;
+  CPrecision precision;
+  const char *unit;
 } CAmountOfMoneyValue;
 
 typedef struct CTemperatureValue {
-    float value;
-    char *const unit;
+  float value;
+  const char *unit;
 } CTemperatureValue;
 
 typedef struct CDurationValue {
-    long years;
-    long quarters;
-    long months;
-    long weeks;
-    long days;
-    long hours;
-    long minutes;
-    long seconds;
-    CPrecision precision;
+  long years;
+  long quarters;
+  long months;
+  long weeks;
+  long days;
+  long hours;
+  long minutes;
+  long seconds;
+  CPrecision precision;
 } CDurationValue;
 
-typedef struct CSlotValue {
-    CSlotValueType value_type;
-    /**
-      * Points to either a char *const, a CNumberValue, a COrdinalValue,
-      * a CInstantTimeValue, a CTimeIntervalValue, a CAmountOfMoneyValue,
-      * a CTemperatureValue or a CDurationValue depending on value_type
-      */
-    void *const value;
-} CSlotValue;
-
-typedef struct CSlot {
-    char *const raw_value;
-    CSlotValue value;
-    int range_start;
-    int range_end;
-    char *const entity;
-    char *const slot_name;
-} CSlot;
-
-typedef struct CIntentClassifierResult {
-    char *const intent_name;
-    float probability;
-} CIntentClassifierResult;
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
 
-typedef struct CSlotList {
-    CSlot *const slots;
-    int size;
-} CSlotList;
+NLURESULT nlu_engine_create_from_dir(const char *root_dir, const Opaque **client);
 
-typedef struct CIntentParserResult{
-    char *const input;
-    CIntentClassifierResult *const intent;
-    CSlotList *const slots;
-} CIntentParserResult;
+NLURESULT nlu_engine_create_from_zip(const unsigned char *zip,
+                                     unsigned int zip_size,
+                                     const Opaque **client);
 
-typedef struct Opaque Opaque;
+NLURESULT nlu_engine_run_parse(const Opaque *client,
+                               const char *input,
+                               const CIntentParserResult **result);
 
-typedef enum NLURESULT {
-	KO = 0,
-	OK = 1,
-} NLURESULT;
+NLURESULT nlu_engine_run_parse_into_json(const Opaque *client,
+                                         const char *input,
+                                         const char **result_json);
 
-NLURESULT nlu_engine_create_from_dir(char const* root_dir, Opaque** client);
+/**
+ * Returns the intents of the input as {"resultFormatVersion": 1, "intents": [...]}
+ */
+NLURESULT nlu_engine_run_get_intents_into_json(const Opaque *client,
+                                               const char *input,
+                                               const char **result_json);
 
-NLURESULT nlu_engine_create_from_zip(unsigned char const* zip, unsigned int zip_size, Opaque** client);
+/**
+ * Returns the slots of the input for the intent as {"resultFormatVersion": 1, "slots": [...]}
+ */
+NLURESULT nlu_engine_run_get_slots_into_json(const Opaque *client,
+                                             const char *input,
+                                             const char *intent,
+                                             const char **result_json);
 
-NLURESULT nlu_engine_run_parse(Opaque const* client, char const* input, CIntentParserResult** result);
+/**
+ * Tags the tokens of the input with the slots found in it, as a JSON list of objects with the
+ * value and the char range of each token, along with the name and the confidence of its slot
+ */
+NLURESULT nlu_engine_run_tag_into_json(const Opaque *client,
+                                       const char *input,
+                                       const char **result_json);
 
-NLURESULT nlu_engine_run_parse_into_json(Opaque const* client, char const* input, char** result_json);
+/**
+ * Returns the error of the last failed call made on the calling thread
+ */
+NLURESULT nlu_engine_get_last_error(const char **error);
 
-NLURESULT nlu_engine_destroy_string(char* string);
+/**
+ * Returns the code of the error of the last failed call made on the calling thread
+ */
+NLURESULT nlu_engine_get_last_error_code(NLUERRORCODE *code);
 
-NLURESULT nlu_engine_destroy_client(Opaque* client);
+NLURESULT nlu_engine_destroy_string(char *string);
 
-NLURESULT nlu_engine_destroy_result(CIntentParserResult* result);
+NLURESULT nlu_engine_destroy_client(Opaque *client);
 
-NLURESULT nlu_engine_get_last_error(char **error);
+NLURESULT nlu_engine_destroy_result(CIntentParserResult *result);
 
-NLURESULT nlu_engine_get_model_version(char **version);
+NLURESULT nlu_engine_get_model_version(const char **version);
 
 #ifdef __cplusplus
-}
-#endif
+} // extern "C"
+#endif // __cplusplus
 
-#endif // !LIBSNIPS_NLU_H_
+#endif /* LIBSNIPS_NLU_H_ */
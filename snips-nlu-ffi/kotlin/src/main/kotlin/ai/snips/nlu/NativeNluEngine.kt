@@ -0,0 +1,64 @@
+package ai.snips.nlu
+
+import java.io.Closeable
+import java.io.File
+import java.io.InputStream
+import java.util.concurrent.locks.ReentrantReadWriteLock
+import kotlin.concurrent.read
+import kotlin.concurrent.write
+
+/**
+ * Engine calling the JNI entry points of the native library, built with its `jni` feature, which
+ * avoids the JNA dependency of [NluEngine] in Android apps
+ *
+ * The native engine is thread safe, so the calls of several JVM threads run concurrently, and
+ * closing the engine waits for the running calls. The engine can't be used once closed.
+ */
+class NativeNluEngine private constructor(private val handle: Long) : Closeable {
+
+    companion object {
+        init {
+            System.loadLibrary("snips_nlu_ffi")
+        }
+
+        @JvmStatic private external fun createFromDir(engineDir: String): Long
+        @JvmStatic private external fun createFromZip(zip: ByteArray): Long
+        @JvmStatic private external fun parse(handle: Long, input: String): String
+        @JvmStatic private external fun tag(handle: Long, input: String): String
+        @JvmStatic private external fun destroy(handle: Long)
+    }
+
+    constructor(engineDir: File) : this(createFromDir(engineDir.absolutePath))
+
+    /**
+     * Loads the engine from a stream of a zipped model, such as `context.assets.open(assetPath)`
+     * for a model shipped in the assets of an Android app, the stream being closed afterwards
+     */
+    constructor(zipStream: InputStream) : this(createFromZip(zipStream.use { it.readBytes() }))
+
+    private val lock = ReentrantReadWriteLock()
+    private var closed = false
+
+    override fun close() {
+        lock.write {
+            if (!closed) {
+                closed = true
+                destroy(handle)
+            }
+        }
+    }
+
+    private fun <T> withHandle(block: (Long) -> T): T = lock.read {
+        if (closed) throw IllegalStateException("The engine is closed")
+        block(handle)
+    }
+
+    fun parseIntoJson(input: String): String = withHandle { parse(it, input) }
+
+    /**
+     * Returns the tokens of the input tagged with the slots found in it, as a JSON list of objects
+     * with the value and the char range of each token, along with the name and the confidence of
+     * its slot
+     */
+    fun tagIntoJson(input: String): String = withHandle { tag(it, input) }
+}
@@ -10,8 +10,18 @@ import com.sun.jna.Pointer
 import com.sun.jna.ptr.PointerByReference
 import java.io.Closeable
 import java.io.File
+import java.io.InputStream
+import java.util.concurrent.locks.ReentrantReadWriteLock
+import kotlin.concurrent.read
+import kotlin.concurrent.write
 import ai.snips.nlu.NluEngine.SnipsNluClientLibrary.Companion.INSTANCE as LIB
 
+/**
+ * Engine backed by the native library, which can be used from several JVM threads at once
+ *
+ * The native engine runs one call at a time, and closing the engine waits for the running calls.
+ * The engine can't be used once closed.
+ */
 class NluEngine private constructor(clientBuilder: () -> Pointer) : Closeable {
 
     companion object {
@@ -47,28 +57,68 @@ class NluEngine private constructor(clientBuilder: () -> Pointer) : Closeable {
                      }.value
                  })
 
+    /**
+     * Loads the engine from a stream of a zipped model, such as `context.assets.open(assetPath)`
+     * for a model shipped in the assets of an Android app, the stream being closed afterwards
+     */
+    constructor(zipStream: InputStream) : this(zipStream.use { it.readBytes() })
+
 
     val client: Pointer = clientBuilder()
+    private val lock = ReentrantReadWriteLock()
+    private var closed = false
 
     override fun close() {
-        LIB.nlu_engine_destroy_client(client)
+        lock.write {
+            if (!closed) {
+                closed = true
+                LIB.nlu_engine_destroy_client(client)
+            }
+        }
     }
 
-    fun parse(input: String): IntentParserResult =
-            CIntentParserResult(PointerByReference().apply {
-                parseError(LIB.nlu_engine_run_parse(client, input.toPointer(), this))
-            }.value).let {
-                it.toIntentParserResult().apply {
-                    // we don't want jna to try and sync this struct after the call as we're destroying it
-                    // /!\ removing that will make the app crash semi randomly...
-                    it.autoRead = false
-                    LIB.nlu_engine_destroy_result(it)
-                }
+    private fun <T> withClient(block: (Pointer) -> T): T = lock.read {
+        if (closed) throw IllegalStateException("The engine is closed")
+        block(client)
+    }
+
+    fun parse(input: String): IntentParserResult = withClient { client ->
+        CIntentParserResult(PointerByReference().apply {
+            parseError(LIB.nlu_engine_run_parse(client, input.toPointer(), this))
+        }.value).let {
+            it.toIntentParserResult().apply {
+                // we don't want jna to try and sync this struct after the call as we're destroying it
+                // /!\ removing that will make the app crash semi randomly...
+                it.autoRead = false
+                LIB.nlu_engine_destroy_result(it)
             }
+        }
+    }
+
+    fun parseIntoJson(input: String): String = withClient { client ->
+        readJson { LIB.nlu_engine_run_parse_into_json(client, input.toPointer(), it) }
+    }
+
+    fun getIntentsIntoJson(input: String): String = withClient { client ->
+        readJson { LIB.nlu_engine_run_get_intents_into_json(client, input.toPointer(), it) }
+    }
+
+    fun getSlotsIntoJson(input: String, intent: String): String = withClient { client ->
+        readJson { LIB.nlu_engine_run_get_slots_into_json(client, input.toPointer(), intent.toPointer(), it) }
+    }
+
+    /**
+     * Returns the tokens of the input tagged with the slots found in it, as a JSON list of objects
+     * with the value and the char range of each token, along with the name and the confidence of
+     * its slot
+     */
+    fun tagIntoJson(input: String): String = withClient { client ->
+        readJson { LIB.nlu_engine_run_tag_into_json(client, input.toPointer(), it) }
+    }
 
-    fun parseIntoJson(input: String): String =
+    private fun readJson(call: (PointerByReference) -> Int): String =
             PointerByReference().apply {
-                parseError(LIB.nlu_engine_run_parse_into_json(client, input.toPointer(), this))
+                parseError(call(this))
             }.value.let {
                 it.readString().apply {
                     LIB.nlu_engine_destroy_string(it)
@@ -85,6 +135,9 @@ class NluEngine private constructor(clientBuilder: () -> Pointer) : Closeable {
         fun nlu_engine_create_from_zip(data: ByteArray, data_size: Int, pointer: PointerByReference): Int
         fun nlu_engine_run_parse(client: Pointer, input: Pointer, result: PointerByReference): Int
         fun nlu_engine_run_parse_into_json(client: Pointer, input: Pointer, result: PointerByReference): Int
+        fun nlu_engine_run_get_intents_into_json(client: Pointer, input: Pointer, result: PointerByReference): Int
+        fun nlu_engine_run_get_slots_into_json(client: Pointer, input: Pointer, intent: Pointer, result: PointerByReference): Int
+        fun nlu_engine_run_tag_into_json(client: Pointer, input: Pointer, result: PointerByReference): Int
         fun nlu_engine_get_last_error(error: PointerByReference): Int
         fun nlu_engine_destroy_client(client: Pointer): Int
         fun nlu_engine_destroy_result(result: CIntentParserResult): Int
@@ -3,6 +3,8 @@ package ai.snips.nlu
 import com.google.common.truth.Truth.assertThat
 import org.junit.Test
 import java.io.File
+import java.util.Collections
+import kotlin.concurrent.thread
 
 
 class NluEngineTest {
@@ -38,6 +40,16 @@ class NluEngineTest {
         }
     }
 
+    @Test
+    fun createFromZipStreamWorks() {
+        NluEngine(File("../../data/tests/zip_files/sample_config.zip").inputStream()).use {
+            it.parse("make me two cups of hot tea").apply {
+                assertThat(intent).isNotNull()
+                assertThat(intent!!.intentName).isEqualTo("MakeTea")
+            }
+        }
+    }
+
     @Test
     fun parseIntoJsonWorks() {
         NluEngine(File("../../data/tests/configurations")).use {
@@ -51,6 +63,63 @@ class NluEngineTest {
         }
     }
 
+    @Test
+    fun getIntentsIntoJsonWorks() {
+        NluEngine(File("../../data/tests/configurations")).use {
+            it.getIntentsIntoJson("make me two cups of hot tea").apply {
+                assertThat(this).contains("MakeTea")
+            }
+        }
+    }
+
+    @Test
+    fun getSlotsIntoJsonWorks() {
+        NluEngine(File("../../data/tests/configurations")).use {
+            it.getSlotsIntoJson("make me two cups of hot tea", "MakeTea").apply {
+                assertThat(this).contains("beverage_temperature")
+                assertThat(this).contains("number_of_cups")
+            }
+        }
+    }
+
+    @Test
+    fun tagIntoJsonWorks() {
+        NluEngine(File("../../data/tests/configurations")).use {
+            it.tagIntoJson("make me two cups of hot tea").apply {
+                assertThat(this).contains("\"value\":\"two\"")
+                assertThat(this).contains("number_of_cups")
+            }
+        }
+    }
+
+    @Test
+    fun parseWorksFromSeveralThreads() {
+        NluEngine(File("../../data/tests/configurations")).use { engine ->
+            val intentNames = Collections.synchronizedList(mutableListOf<String?>())
+            val threads = (1..4).map {
+                thread { intentNames.add(engine.parse("make me two cups of hot tea").intent?.intentName) }
+            }
+            threads.forEach { it.join() }
+
+            assertThat(intentNames).containsExactly("MakeTea", "MakeTea", "MakeTea", "MakeTea")
+        }
+    }
+
+    @Test(expected = IllegalStateException::class)
+    fun parseFailsOnceClosed() {
+        val engine = NluEngine(File("../../data/tests/configurations"))
+        engine.close()
+        engine.close()
+        engine.parse("make me two cups of hot tea")
+    }
+
+    @Test(expected = RuntimeException::class)
+    fun getSlotsIntoJsonFailsWithUnknownIntent() {
+        NluEngine(File("../../data/tests/configurations")).use {
+            it.getSlotsIntoJson("make me two cups of hot tea", "MakePizza")
+        }
+    }
+
     @Test
     fun funkyCharsArePreserved() {
         NluEngine(File("../../data/tests/configurations")).use {
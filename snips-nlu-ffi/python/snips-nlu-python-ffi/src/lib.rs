@@ -20,6 +20,9 @@ macro_rules! export_c_symbol {
 export_c_symbol!(ffi_nlu_engine_create_from_dir, fn nlu_engine_create_from_dir(root_dir: *const libc::c_char, client: *mut *const Opaque) -> NLURESULT);
 export_c_symbol!(ffi_nlu_engine_create_from_zip, fn nlu_engine_create_from_zip(zip: *const libc::c_uchar, zip_size: libc::c_uint, client: *mut *const Opaque) -> NLURESULT);
 export_c_symbol!(ffi_nlu_engine_run_parse_into_json, fn nlu_engine_run_parse_into_json(client: *const Opaque, input: *const libc::c_char, result_json: *mut *const libc::c_char) -> NLURESULT);
+export_c_symbol!(ffi_nlu_engine_run_get_intents_into_json, fn nlu_engine_run_get_intents_into_json(client: *const Opaque, input: *const libc::c_char, result_json: *mut *const libc::c_char) -> NLURESULT);
+export_c_symbol!(ffi_nlu_engine_run_get_slots_into_json, fn nlu_engine_run_get_slots_into_json(client: *const Opaque, input: *const libc::c_char, intent: *const libc::c_char, result_json: *mut *const libc::c_char) -> NLURESULT);
+export_c_symbol!(ffi_nlu_engine_run_tag_into_json, fn nlu_engine_run_tag_into_json(client: *const Opaque, input: *const libc::c_char, result_json: *mut *const libc::c_char) -> NLURESULT);
 export_c_symbol!(ffi_nlu_engine_get_last_error, fn nlu_engine_get_last_error(error: *mut *const libc::c_char) -> NLURESULT);
 export_c_symbol!(ffi_nlu_engine_destroy_string, fn nlu_engine_destroy_string(string: *mut libc::c_char) -> NLURESULT);
 export_c_symbol!(ffi_nlu_engine_destroy_client, fn nlu_engine_destroy_client(client: *mut Opaque) -> NLURESULT);
@@ -46,3 +46,43 @@ class NLUEngine(object):
             result = string_at(ptr)
 
         return json.loads(result.decode("utf-8"))
+
+    def get_intents(self, query):
+        """Returns the intents of the query by decreasing probability"""
+        with string_pointer(c_char_p()) as ptr:
+            exit_code = lib.nlu_engine_run_get_intents_into_json(
+                self._engine, query.encode("utf-8"), byref(ptr))
+            _check_exit_code(exit_code)
+            result = string_at(ptr)
+
+        return json.loads(result.decode("utf-8"))["intents"]
+
+    def get_slots(self, query, intent):
+        """Returns the slots of the query for the given intent"""
+        with string_pointer(c_char_p()) as ptr:
+            exit_code = lib.nlu_engine_run_get_slots_into_json(
+                self._engine, query.encode("utf-8"), intent.encode("utf-8"),
+                byref(ptr))
+            _check_exit_code(exit_code)
+            result = string_at(ptr)
+
+        return json.loads(result.decode("utf-8"))["slots"]
+
+    def tag(self, query):
+        """Returns the tokens of the query along with the name and the
+        confidence of the slot each token is tagged with, if any"""
+        with string_pointer(c_char_p()) as ptr:
+            exit_code = lib.nlu_engine_run_tag_into_json(
+                self._engine, query.encode("utf-8"), byref(ptr))
+            _check_exit_code(exit_code)
+            result = string_at(ptr)
+
+        return json.loads(result.decode("utf-8"))
+
+
+def _check_exit_code(exit_code):
+    if exit_code != 1:
+        with string_pointer(c_char_p()) as ptr:
+            lib.nlu_engine_get_last_error(byref(ptr))
+            error = string_at(ptr).decode("utf-8")
+        raise ValueError(error)
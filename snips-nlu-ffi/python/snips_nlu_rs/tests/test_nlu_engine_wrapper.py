@@ -18,6 +18,7 @@ class TestNLUEngineWrapper(unittest.TestCase):
 
         # Then
         self.assertEqual("MakeCoffee", res["intent"]["intentName"])
+        self.assertEqual(1, res["resultFormatVersion"])
 
     def test_should_load_from_zip_and_parse(self):
         # Given
@@ -28,3 +29,47 @@ class TestNLUEngineWrapper(unittest.TestCase):
 
         # Then
         self.assertEqual("MakeCoffee", res["intent"]["intentName"])
+
+    def test_should_get_intents(self):
+        # Given
+        engine = NLUEngine(data_path=SAMPLE_ASSISTANT_DIR)
+
+        # When
+        res = engine.get_intents("Make me two cups of coffee please")
+
+        # Then
+        self.assertEqual("MakeCoffee", res[0]["intentName"])
+
+    def test_should_get_slots(self):
+        # Given
+        engine = NLUEngine(data_path=SAMPLE_ASSISTANT_DIR)
+
+        # When
+        slots = engine.get_slots("Make me two cups of coffee please",
+                                 "MakeCoffee")
+
+        # Then
+        self.assertEqual(1, len(slots))
+        self.assertEqual("number_of_cups", slots[0]["slotName"])
+        self.assertEqual("two", slots[0]["rawValue"])
+
+    def test_get_slots_should_fail_with_unknown_intent(self):
+        # Given
+        engine = NLUEngine(data_path=SAMPLE_ASSISTANT_DIR)
+
+        # When / Then
+        with self.assertRaises(ValueError):
+            engine.get_slots("Make me two cups of coffee please", "MakePizza")
+
+    def test_should_tag(self):
+        # Given
+        engine = NLUEngine(data_path=SAMPLE_ASSISTANT_DIR)
+
+        # When
+        tokens = engine.tag("Make me two cups of coffee please")
+
+        # Then
+        self.assertEqual("two", tokens[2]["value"])
+        self.assertEqual({"start": 8, "end": 11}, tokens[2]["range"])
+        self.assertEqual("number_of_cups", tokens[2]["slotName"])
+        self.assertIsNone(tokens[0]["slotName"])
@@ -0,0 +1,127 @@
+//! JNI entry points of the `NativeNluEngine` class of the Kotlin binding, built with the `jni`
+//! feature, so that Android apps can embed the engine without going through JNA
+//!
+//! The engine is `Send` and `Sync`, so the handle returned when it is created is used from any
+//! JVM thread, concurrently and without locking, the Kotlin class only preventing the engine from
+//! being destroyed while it is in use. The `JNIEnv` of a call is only used on the JVM thread which
+//! makes the call, and which the JVM has already attached. The engine never calls back into the
+//! JVM, so the threads of its parser pool never need to be attached.
+
+use std::io::Cursor;
+use std::ptr;
+
+use jni::objects::{JClass, JString};
+use jni::sys::{jbyteArray, jlong, jstring};
+use jni::JNIEnv;
+use serde_json;
+
+use snips_nlu_lib::{result_to_json_string, FileBasedConfiguration, SnipsNluEngine,
+                    ZipBasedConfiguration};
+
+use errors::*;
+
+#[no_mangle]
+pub extern "system" fn Java_ai_snips_nlu_NativeNluEngine_createFromDir(
+    env: JNIEnv,
+    _class: JClass,
+    engine_dir: JString,
+) -> jlong {
+    let handle = get_string(&env, engine_dir).and_then(|engine_dir| {
+        let configuration = FileBasedConfiguration::new(engine_dir, false)?;
+        Ok(into_handle(SnipsNluEngine::new(configuration)?))
+    });
+    unwrap_or_throw(&env, handle, 0)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_ai_snips_nlu_NativeNluEngine_createFromZip(
+    env: JNIEnv,
+    _class: JClass,
+    zip: jbyteArray,
+) -> jlong {
+    let handle = env.convert_byte_array(zip)
+        .map_err(jni_error)
+        .and_then(|zip| {
+            let configuration = ZipBasedConfiguration::new(Cursor::new(zip), false)?;
+            Ok(into_handle(SnipsNluEngine::new(configuration)?))
+        });
+    unwrap_or_throw(&env, handle, 0)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_ai_snips_nlu_NativeNluEngine_parse(
+    env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    input: JString,
+) -> jstring {
+    let json = get_string(&env, input).and_then(|input| {
+        let result = from_handle(handle)?.parse(&input, None)?;
+        Ok(result_to_json_string(&result)?)
+    });
+    new_string_or_throw(&env, json)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_ai_snips_nlu_NativeNluEngine_tag(
+    env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    input: JString,
+) -> jstring {
+    let json = get_string(&env, input).and_then(|input| {
+        let tagged_tokens = from_handle(handle)?.tag(&input, None)?;
+        Ok(serde_json::to_string(&tagged_tokens)?)
+    });
+    new_string_or_throw(&env, json)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_ai_snips_nlu_NativeNluEngine_destroy(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) {
+    if handle != 0 {
+        unsafe { Box::from_raw(handle as *mut SnipsNluEngine) };
+    }
+}
+
+fn into_handle(engine: SnipsNluEngine) -> jlong {
+    Box::into_raw(Box::new(engine)) as jlong
+}
+
+fn from_handle<'a>(handle: jlong) -> Result<&'a SnipsNluEngine> {
+    if handle == 0 {
+        bail!("The engine is not created");
+    }
+    Ok(unsafe { &*(handle as *const SnipsNluEngine) })
+}
+
+fn get_string(env: &JNIEnv, string: JString) -> Result<String> {
+    Ok(env.get_string(string).map_err(jni_error)?.into())
+}
+
+fn new_string_or_throw(env: &JNIEnv, string: Result<String>) -> jstring {
+    let string = string.and_then(|string| {
+        Ok(env.new_string(string).map_err(jni_error)?.into_inner())
+    });
+    unwrap_or_throw(env, string, ptr::null_mut())
+}
+
+/// Returns the value of the result, or throws its error as a `RuntimeException` once the native
+/// call returns, in which case the returned default value is ignored by the JVM
+fn unwrap_or_throw<T>(env: &JNIEnv, result: Result<T>, default: T) -> T {
+    result.unwrap_or_else(|error| {
+        use error_chain::ChainedError;
+        let message = error.display_chain().to_string();
+        if env.throw_new("java/lang/RuntimeException", message).is_err() {
+            eprintln!("Failed to throw the error of the engine: {}", error);
+        }
+        default
+    })
+}
+
+fn jni_error(error: ::jni::errors::Error) -> Error {
+    format!("JNI error: {}", error).into()
+}
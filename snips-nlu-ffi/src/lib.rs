@@ -1,22 +1,35 @@
 #[macro_use]
 extern crate error_chain;
-#[macro_use]
-extern crate lazy_static;
+#[cfg(feature = "jni")]
+extern crate jni;
 extern crate libc;
+#[cfg(feature = "python")]
+extern crate pyo3;
 extern crate serde_json;
 extern crate snips_nlu_lib;
 extern crate snips_nlu_ontology_ffi;
 
+use std::cell::{Cell, RefCell};
 use std::ffi::{CStr, CString};
 use std::sync::Mutex;
 use std::slice;
 use std::io::Cursor;
 
-use snips_nlu_lib::{FileBasedConfiguration, SnipsNluEngine, ZipBasedConfiguration};
+use snips_nlu_lib::{intents_to_json, result_to_json_string, slots_to_json,
+                    FileBasedConfiguration, SnipsNluEngine, ZipBasedConfiguration};
 use snips_nlu_ontology_ffi::CIntentParserResult;
 
-lazy_static! {
-    static ref LAST_ERROR: Mutex<String> = Mutex::new("".to_string());
+#[cfg(feature = "jni")]
+mod jvm;
+#[cfg(feature = "python")]
+mod python;
+
+thread_local! {
+    /// Error of the last failed call made on the thread, so that the error read by a thread is not
+    /// overwritten by a call failing concurrently on another thread, such as another JVM thread
+    static LAST_ERROR: RefCell<String> = RefCell::new("".to_string());
+    /// Code of the error of the last failed call made on the thread
+    static LAST_ERROR_CODE: Cell<NLUERRORCODE> = Cell::new(NLUERRORCODE::NLU_ERROR_NONE);
 }
 
 mod errors {
@@ -44,7 +57,7 @@ mod errors {
 
 use errors::*;
 
-#[repr(C)]
+/// Engine behind the pointers of the C API, which is opaque to the C code
 pub struct Opaque(std::sync::Mutex<SnipsNluEngine>);
 
 #[repr(C)]
@@ -54,6 +67,53 @@ pub enum NLURESULT {
     OK = 1,
 }
 
+/// Kind of the error of the last failed call, see `nlu_engine_get_last_error_code`, which the
+/// Swift wrapper reports as the code of its `NSError`
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NLUERRORCODE {
+    /// No call failed on the thread
+    NLU_ERROR_NONE = 0,
+    NLU_ERROR_UNKNOWN = 1,
+    /// An argument is not a valid UTF-8 string
+    NLU_ERROR_INVALID_ARGUMENT = 2,
+    /// The files of the model can't be read
+    NLU_ERROR_MODEL_LOADING = 3,
+    NLU_ERROR_INCOMPATIBLE_MODEL_VERSION = 4,
+    /// A file of the model does not match its checksum
+    NLU_ERROR_CORRUPTED_MODEL = 5,
+    NLU_ERROR_UNKNOWN_INTENT = 6,
+    NLU_ERROR_INPUT_TOO_LONG = 7,
+    NLU_ERROR_WARM_UP_FAILED = 8,
+}
+
+impl<'a> From<&'a Error> for NLUERRORCODE {
+    fn from(error: &'a Error) -> Self {
+        use snips_nlu_lib::ErrorKind as LibErrorKind;
+        match *error.kind() {
+            ErrorKind::Utf8Error(_) | ErrorKind::NulError(_) => {
+                NLUERRORCODE::NLU_ERROR_INVALID_ARGUMENT
+            }
+            ErrorKind::Io(_) => NLUERRORCODE::NLU_ERROR_MODEL_LOADING,
+            ErrorKind::SnipsNluLib(ref kind) => match *kind {
+                LibErrorKind::ConfigLoad(_) | LibErrorKind::Io(_) | LibErrorKind::Zip(_) => {
+                    NLUERRORCODE::NLU_ERROR_MODEL_LOADING
+                }
+                LibErrorKind::WrongModelVersion(_) | LibErrorKind::IncompatibleModelVersion(..) => {
+                    NLUERRORCODE::NLU_ERROR_INCOMPATIBLE_MODEL_VERSION
+                }
+                LibErrorKind::ChecksumMismatch(_) => NLUERRORCODE::NLU_ERROR_CORRUPTED_MODEL,
+                LibErrorKind::UnknownIntents(_) => NLUERRORCODE::NLU_ERROR_UNKNOWN_INTENT,
+                LibErrorKind::InputTooLong(..) => NLUERRORCODE::NLU_ERROR_INPUT_TOO_LONG,
+                LibErrorKind::WarmUpFailed(_) => NLUERRORCODE::NLU_ERROR_WARM_UP_FAILED,
+                _ => NLUERRORCODE::NLU_ERROR_UNKNOWN,
+            },
+            _ => NLUERRORCODE::NLU_ERROR_UNKNOWN,
+        }
+    }
+}
+
 macro_rules! wrap {
     ($e:expr) => { match $e {
         Ok(_) => { NLURESULT::OK }
@@ -61,10 +121,8 @@ macro_rules! wrap {
             use error_chain::ChainedError;
             let msg = e.display_chain().to_string();
             eprintln!("{}", msg);
-            match LAST_ERROR.lock() {
-                Ok(mut guard) => *guard = msg,
-                Err(_) => () /* curl up and cry */
-            }
+            LAST_ERROR.with(|last_error| *last_error.borrow_mut() = msg);
+            LAST_ERROR_CODE.with(|last_error_code| last_error_code.set((&e).into()));
             NLURESULT::KO
         }
     }}
@@ -118,15 +176,59 @@ pub extern "C" fn nlu_engine_run_parse_into_json(
     wrap!(run_parse_into_json(client, input, result_json))
 }
 
+/// Returns the intents of the input as {"resultFormatVersion": 1, "intents": [...]}
+#[no_mangle]
+pub extern "C" fn nlu_engine_run_get_intents_into_json(
+    client: *const Opaque,
+    input: *const libc::c_char,
+    result_json: *mut *const libc::c_char,
+) -> NLURESULT {
+    wrap!(run_get_intents_into_json(client, input, result_json))
+}
+
+/// Returns the slots of the input for the intent as {"resultFormatVersion": 1, "slots": [...]}
+#[no_mangle]
+pub extern "C" fn nlu_engine_run_get_slots_into_json(
+    client: *const Opaque,
+    input: *const libc::c_char,
+    intent: *const libc::c_char,
+    result_json: *mut *const libc::c_char,
+) -> NLURESULT {
+    wrap!(run_get_slots_into_json(client, input, intent, result_json))
+}
+
+/// Tags the tokens of the input with the slots found in it, as a JSON list of objects with the
+/// value and the char range of each token, along with the name and the confidence of its slot
+#[no_mangle]
+pub extern "C" fn nlu_engine_run_tag_into_json(
+    client: *const Opaque,
+    input: *const libc::c_char,
+    result_json: *mut *const libc::c_char,
+) -> NLURESULT {
+    wrap!(run_tag_into_json(client, input, result_json))
+}
+
+/// Returns the error of the last failed call made on the calling thread
 #[no_mangle]
 pub extern "C" fn nlu_engine_get_last_error(error: *mut *const libc::c_char) -> NLURESULT {
     wrap!(get_last_error(error))
 }
 
+/// Returns the code of the error of the last failed call made on the calling thread
+#[no_mangle]
+pub extern "C" fn nlu_engine_get_last_error_code(code: *mut NLUERRORCODE) -> NLURESULT {
+    let last_error_code = LAST_ERROR_CODE.with(|last_error_code| last_error_code.get());
+    unsafe { *code = last_error_code };
+    NLURESULT::OK
+}
+
 #[no_mangle]
 pub extern "C" fn nlu_engine_destroy_string(string: *mut libc::c_char) -> NLURESULT {
-    unsafe {
-        let _: CString = CString::from_raw(string);
+    // The string is null when the function which should have set it failed
+    if !string.is_null() {
+        unsafe {
+            let _: CString = CString::from_raw(string);
+        }
     }
 
     NLURESULT::OK
@@ -208,11 +310,52 @@ fn run_parse_into_json(
 
     let results = intent_parser.parse(input, None)?;
 
-    point_to_string(result_json, serde_json::to_string(&results)?)
+    point_to_string(result_json, result_to_json_string(&results)?)
+}
+
+fn run_get_intents_into_json(
+    client: *const Opaque,
+    input: *const libc::c_char,
+    result_json: *mut *const libc::c_char,
+) -> Result<()> {
+    let input = get_str!(input);
+    let intent_parser = get_intent_parser!(client);
+
+    let results = intent_parser.get_intents(input, None)?;
+
+    point_to_string(result_json, serde_json::to_string(&intents_to_json(&results)?)?)
+}
+
+fn run_get_slots_into_json(
+    client: *const Opaque,
+    input: *const libc::c_char,
+    intent: *const libc::c_char,
+    result_json: *mut *const libc::c_char,
+) -> Result<()> {
+    let input = get_str!(input);
+    let intent = get_str!(intent);
+    let intent_parser = get_intent_parser!(client);
+
+    let slots = intent_parser.get_slots(input, intent)?;
+
+    point_to_string(result_json, serde_json::to_string(&slots_to_json(&slots)?)?)
+}
+
+fn run_tag_into_json(
+    client: *const Opaque,
+    input: *const libc::c_char,
+    result_json: *mut *const libc::c_char,
+) -> Result<()> {
+    let input = get_str!(input);
+    let intent_parser = get_intent_parser!(client);
+
+    let tagged_tokens = intent_parser.tag(input, None)?;
+
+    point_to_string(result_json, serde_json::to_string(&tagged_tokens)?)
 }
 
 fn get_last_error(error: *mut *const libc::c_char) -> Result<()> {
-    point_to_string(error, LAST_ERROR.lock()?.clone())
+    point_to_string(error, LAST_ERROR.with(|last_error| last_error.borrow().clone()))
 }
 
 fn get_model_version(version: *mut *const libc::c_char) -> Result<()> {
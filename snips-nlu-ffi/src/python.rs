@@ -0,0 +1,88 @@
+//! Python extension module exposing the engine as a native class, built with the `python`
+//! feature, whose results are the dicts of the Python library rather than JSON strings
+//!
+//! The engine is parsed without holding the GIL, so that Python threads can parse concurrently.
+
+use pyo3::exceptions::ValueError;
+use pyo3::prelude::*;
+use serde_json;
+
+use snips_nlu_lib::{intents_to_json, result_to_json_string, slots_to_json,
+                    FileBasedConfiguration, SnipsNluEngine};
+
+use errors::*;
+
+#[pyclass(name = SnipsNLUEngine)]
+pub struct PySnipsNluEngine {
+    engine: SnipsNluEngine,
+}
+
+#[pymethods]
+impl PySnipsNluEngine {
+    #[new]
+    fn new(engine_dir: &str) -> PyResult<Self> {
+        let engine = load_engine(engine_dir).map_err(to_py_err)?;
+        Ok(Self { engine })
+    }
+
+    /// Returns the intent and the slots of the input
+    fn parse(&self, py: Python, input: &str) -> PyResult<PyObject> {
+        let engine = &self.engine;
+        let json = py.allow_threads(|| -> Result<String> {
+            Ok(result_to_json_string(&engine.parse(input, None)?)?)
+        });
+        json_loads(py, &json.map_err(to_py_err)?)
+    }
+
+    /// Returns the intents of the input by decreasing probability
+    fn get_intents(&self, py: Python, input: &str) -> PyResult<PyObject> {
+        let engine = &self.engine;
+        let json = py.allow_threads(|| -> Result<String> {
+            let intents = intents_to_json(&engine.get_intents(input, None)?)?;
+            Ok(serde_json::to_string(&intents["intents"])?)
+        });
+        json_loads(py, &json.map_err(to_py_err)?)
+    }
+
+    /// Returns the slots of the input for the given intent
+    fn get_slots(&self, py: Python, input: &str, intent: &str) -> PyResult<PyObject> {
+        let engine = &self.engine;
+        let json = py.allow_threads(|| -> Result<String> {
+            let slots = slots_to_json(&engine.get_slots(input, intent)?)?;
+            Ok(serde_json::to_string(&slots["slots"])?)
+        });
+        json_loads(py, &json.map_err(to_py_err)?)
+    }
+
+    /// Returns the tokens of the input along with the name and the confidence of the slot each
+    /// token is tagged with, if any
+    fn tag(&self, py: Python, input: &str) -> PyResult<PyObject> {
+        let engine = &self.engine;
+        let json = py.allow_threads(|| -> Result<String> {
+            Ok(serde_json::to_string(&engine.tag(input, None)?)?)
+        });
+        json_loads(py, &json.map_err(to_py_err)?)
+    }
+}
+
+#[pymodule]
+fn snips_nlu_ffi(_py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_class::<PySnipsNluEngine>()?;
+    Ok(())
+}
+
+fn load_engine(engine_dir: &str) -> Result<SnipsNluEngine> {
+    let configuration = FileBasedConfiguration::new(engine_dir, false)?;
+    Ok(SnipsNluEngine::new(configuration)?)
+}
+
+/// Converts a JSON result to the Python objects of the Python library, dicts and lists
+fn json_loads(py: Python, json: &str) -> PyResult<PyObject> {
+    let value = py.import("json")?.call1("loads", (json,))?;
+    Ok(value.to_object(py))
+}
+
+fn to_py_err(error: Error) -> PyErr {
+    use error_chain::ChainedError;
+    PyErr::new::<ValueError, _>(error.display_chain().to_string())
+}
@@ -9,16 +9,50 @@
 import Foundation
 import Clibsnips_nlu
 
+/// Code of the `NSError` of the engine, one for each kind of error of the C API
+public enum NluEngineErrorCode: Int {
+    case unknown = 1
+    case invalidArgument = 2
+    case modelLoading = 3
+    case incompatibleModelVersion = 4
+    case corruptedModel = 5
+    case unknownIntent = 6
+    case inputTooLong = 7
+    case warmUpFailed = 8
+    /// Result of the C API which the wrapper fails to convert
+    case internalError = 100
+}
+
 public struct NluEngineError: Error {
     public let message: String
+    public let code: NluEngineErrorCode
+
+    init(message: String, code: NluEngineErrorCode = .internalError) {
+        self.message = message
+        self.code = code
+    }
 
     static var getLast: NluEngineError {
-        let buffer = UnsafeMutablePointer<UnsafeMutablePointer<Int8>?>.allocate(capacity: 1024)
-        nlu_engine_get_last_error(buffer)
-        return NluEngineError(message: String(cString: buffer.pointee!))
+        var cMessage: UnsafePointer<Int8>? = nil
+        nlu_engine_get_last_error(&cMessage)
+        defer { nlu_engine_destroy_string(UnsafeMutablePointer(mutating: cMessage)) }
+        var cCode = NLU_ERROR_UNKNOWN
+        nlu_engine_get_last_error_code(&cCode)
+        let code = NluEngineErrorCode(rawValue: Int(cCode.rawValue)) ?? .unknown
+        return NluEngineError(message: cMessage.map { String(cString: $0) } ?? "", code: code)
     }
 }
 
+extension NluEngineError: CustomNSError, LocalizedError {
+    public static var errorDomain: String { return "ai.snips.nlu" }
+
+    public var errorCode: Int { return code.rawValue }
+
+    public var errorUserInfo: [String: Any] { return [NSLocalizedDescriptionKey: message] }
+
+    public var errorDescription: String? { return message }
+}
+
 public struct IntentParserResult {
     public let input: String
     public let intent: IntentClassifierResult?
@@ -247,6 +281,15 @@ public struct Slot {
         self.entity = String(cString: cSlot.entity)
         self.slotName = String(cString: cSlot.slot_name)
     }
+
+    /// Range of the slot in the UTF-16 code units of the input, which is the range expected by
+    /// NSString and NSAttributedString, `range` counting unicode scalars
+    public func nsRange(in input: String) -> NSRange? {
+        let scalars = input.unicodeScalars
+        guard let start = scalars.index(scalars.startIndex, offsetBy: range.lowerBound, limitedBy: scalars.endIndex),
+              let end = scalars.index(start, offsetBy: range.count, limitedBy: scalars.endIndex) else { return nil }
+        return NSRange(start..<end, in: input)
+    }
 }
 
 public class NluEngine {
@@ -270,9 +313,9 @@ public class NluEngine {
     }
 
     public func parse(string: String) throws -> IntentParserResult {
-        var cResult: UnsafeMutablePointer<CIntentParserResult>? = nil;
+        var cResult: UnsafePointer<CIntentParserResult>? = nil;
         guard nlu_engine_run_parse(self.client, string, &cResult) == OK else { throw NluEngineError.getLast }
-        defer { nlu_engine_destroy_result(cResult) }
+        defer { nlu_engine_destroy_result(UnsafeMutablePointer(mutating: cResult)) }
         guard let result = cResult?.pointee else { throw NluEngineError(message: "Can't retrieve result")}
         return try IntentParserResult(cResult: result)
     }
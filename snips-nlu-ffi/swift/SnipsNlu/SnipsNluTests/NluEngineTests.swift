@@ -27,4 +27,26 @@ class NluEngineTests: XCTestCase {
 
         XCTAssertNotNil(nluEngine)
     }
+
+    func testErrorCode() {
+        let directoryURL = URL(fileURLWithPath: "/nonexistent/configurations")
+
+        XCTAssertThrowsError(try NluEngine(assistantDirectoryURL: directoryURL)) { error in
+            XCTAssertEqual(NluEngineErrorCode.modelLoading.rawValue, (error as NSError).code)
+            XCTAssertEqual("ai.snips.nlu", (error as NSError).domain)
+        }
+    }
+
+    func testSlotNSRange() {
+        let directoryURL = Bundle(for: type(of: self)).url(forResource: "configurations", withExtension: nil)!
+        let nluEngine = try! NluEngine(assistantDirectoryURL: directoryURL)
+        let input = "🙂 make me two cups of coffee"
+
+        let result = try! nluEngine.parse(string: input)
+
+        XCTAssertEqual(1, result.slots.count)
+        XCTAssertEqual(10..<13, result.slots[0].range)
+        XCTAssertEqual(NSRange(location: 11, length: 3), result.slots[0].nsRange(in: input))
+        XCTAssertEqual("two", (input as NSString).substring(with: result.slots[0].nsRange(in: input)!))
+    }
 }
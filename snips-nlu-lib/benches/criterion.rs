@@ -0,0 +1,89 @@
+//! Benchmark suite run on the fixture models of the repository, whose results are compared
+//! between releases:
+//!
+//! ```bash
+//! cargo bench --features bench --bench criterion
+//! ```
+
+#[macro_use]
+extern crate criterion;
+extern crate snips_nlu_lib;
+extern crate snips_nlu_ontology;
+
+use criterion::Criterion;
+use snips_nlu_lib::{file_path, SnipsNluEngine};
+use snips_nlu_ontology::{BuiltinEntityParser, Language};
+
+const MODEL_DIRECTORY: &str = "tests/configurations";
+const MODEL_ZIP: &str = "tests/zip_files/sample_config.zip";
+const UTTERANCE_CHUNK: &str = "make me two cups of hot tea please";
+const UTTERANCE_LENGTHS: [usize; 4] = [1, 3, 10, 30];
+
+const BUILTIN_ENTITIES_SENTENCES: [(Language, &str); 6] = [
+    (Language::EN, "two cups of tea tomorrow at 8am for 3 dollars at 80 degrees"),
+    (Language::FR, "deux tasses de thé demain à 8h pour 3 euros à 80 degrés"),
+    (Language::DE, "zwei Tassen Tee morgen um 8 Uhr für 3 Euro bei 80 Grad"),
+    (Language::ES, "dos tazas de té mañana a las 8 por 3 euros a 80 grados"),
+    (Language::JA, "明日の午前8時に3ドルでお茶を2杯"),
+    (Language::KO, "내일 오전 8시에 3달러로 차 두 잔"),
+];
+
+fn load_engine() -> SnipsNluEngine {
+    SnipsNluEngine::from_path(file_path(MODEL_DIRECTORY)).unwrap()
+}
+
+/// Repeats the chunk of utterance, so that the latency can be measured on longer inputs
+fn utterance(nb_chunks: usize) -> String {
+    vec![UTTERANCE_CHUNK; nb_chunks].join(" ")
+}
+
+fn engine_loading(c: &mut Criterion) {
+    c.bench_function("load engine from directory", |b| b.iter(load_engine));
+    c.bench_function("load engine from zip", |b| {
+        b.iter(|| SnipsNluEngine::from_zip_path(file_path(MODEL_ZIP)).unwrap())
+    });
+}
+
+fn parsing(c: &mut Criterion) {
+    let engine = load_engine();
+    engine.warm_up().unwrap();
+    c.bench_function_over_inputs(
+        "parse",
+        move |b, &&nb_chunks| {
+            let input = utterance(nb_chunks);
+            b.iter(|| engine.parse(&input, None).unwrap())
+        },
+        &UTTERANCE_LENGTHS,
+    );
+}
+
+fn tagging(c: &mut Criterion) {
+    let engine = load_engine();
+    engine.warm_up().unwrap();
+    c.bench_function_over_inputs(
+        "tag",
+        move |b, &&nb_chunks| {
+            let input = utterance(nb_chunks);
+            b.iter(|| engine.get_slots(&input, "MakeTea").unwrap())
+        },
+        &UTTERANCE_LENGTHS,
+    );
+}
+
+fn builtin_entities_extraction(c: &mut Criterion) {
+    for &(language, sentence) in &BUILTIN_ENTITIES_SENTENCES {
+        let language_code = language.to_string();
+        let parser = BuiltinEntityParser::get(language);
+        // The first extraction, which is slower, is not measured
+        parser.extract_entities(sentence, None);
+        c.bench_function(
+            &format!("extract builtin entities {}", language_code),
+            move |b| b.iter(|| parser.extract_entities(sentence, None)),
+        );
+    }
+}
+
+criterion_group!(load, engine_loading);
+criterion_group!(run, parsing, tagging, builtin_entities_extraction);
+
+criterion_main!(load, run);
@@ -0,0 +1,784 @@
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "parallel")]
+pub use rayon::ThreadPool;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use configurations::ArbitrationStrategy;
+use errors::*;
+#[cfg(feature = "parallel")]
+use instrumentation::Span;
+use intent_parser::IntentParser;
+use nlu_utils::range::ranges_overlap;
+use parse_context::ParseContext;
+use utils::decreasing_order;
+use snips_nlu_ontology::{IntentClassifierResult, Slot};
+
+/// Thread pool of which no value exists when the crate is built without the `parallel` feature,
+/// for instance for WebAssembly, the parsers being then always run one after the other
+#[cfg(not(feature = "parallel"))]
+pub enum ThreadPool {}
+
+/// Intent selected by the arbitration, along with the indexes of the parsers whose slots must be
+/// used, by order of priority
+#[derive(Debug, PartialEq)]
+pub struct ArbitrationResult {
+    pub intent: IntentClassifierResult,
+    pub parser_indexes: Vec<usize>,
+}
+
+/// Runs the intent parsers on the input and selects the intent according to the arbitration
+/// strategy
+///
+/// The intents found by a parser with a probability below their threshold are ignored, so that
+/// the next parsers get a chance to classify the input.
+///
+/// When a thread pool is given, the parsers are run concurrently on it, the result being the same
+/// as when they are run in sequence.
+pub fn arbitrate(
+    parsers: &[Box<IntentParser>],
+    strategy: &ArbitrationStrategy,
+    input: &str,
+    intents: Option<&HashSet<String>>,
+    context: &ParseContext,
+    intent_thresholds: &HashMap<String, f32>,
+    thread_pool: Option<&ThreadPool>,
+) -> Result<Option<ArbitrationResult>> {
+    match *strategy {
+        ArbitrationStrategy::FirstMatch => {
+            if thread_pool.is_some() {
+                // All the parsers are run, even though only the first match is used
+                let results = run_parsers(parsers, &all_indexes(parsers), thread_pool, |parser| {
+                    parser.get_intent(input, intents, context)
+                });
+                first_match(results.into_iter(), intent_thresholds)
+            } else {
+                let results = parsers
+                    .iter()
+                    .map(|parser| parser.get_intent(input, intents, context));
+                first_match(results, intent_thresholds)
+            }
+        }
+        ArbitrationStrategy::BestConfidence => {
+            let results = get_all_intents(
+                parsers,
+                input,
+                intents,
+                context,
+                intent_thresholds,
+                thread_pool,
+            )?;
+            Ok(results
+                .into_iter()
+                .min_by(|a, b| decreasing_order(a.1.probability, b.1.probability))
+                .map(|(parser_index, intent)| ArbitrationResult {
+                    intent,
+                    parser_indexes: vec![parser_index],
+                }))
+        }
+        ArbitrationStrategy::WeightedVote { ref weights } => {
+            let results = get_all_intents(
+                parsers,
+                input,
+                intents,
+                context,
+                intent_thresholds,
+                thread_pool,
+            )?;
+            Ok(weighted_vote(parsers.len(), weights, results).map(|mut result| {
+                result.parser_indexes.truncate(1);
+                result
+            }))
+        }
+        ArbitrationStrategy::Ensemble { ref weights } => {
+            let results = get_all_distributions(
+                parsers,
+                input,
+                intents,
+                context,
+                intent_thresholds,
+                thread_pool,
+            )?;
+            Ok(weighted_vote(parsers.len(), weights, results))
+        }
+    }
+}
+
+/// Runs the function on the parsers with the given indexes, concurrently when a thread pool is
+/// given, the results being returned in the order of the indexes
+pub fn run_parsers<T, F>(
+    parsers: &[Box<IntentParser>],
+    parser_indexes: &[usize],
+    thread_pool: Option<&ThreadPool>,
+    f: F,
+) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&IntentParser) -> T + Sync,
+{
+    let run_parser = |parser_index: &usize| {
+        let span = pipeline_span!("intent_parser", parser = *parser_index);
+        let _entered = span.enter();
+        f(&*parsers[*parser_index])
+    };
+    #[cfg(feature = "parallel")]
+    {
+        if let Some(thread_pool) = thread_pool {
+            // The spans of the parsers are attached to the span of the caller
+            let span = Span::current();
+            return thread_pool.install(|| {
+                parser_indexes
+                    .par_iter()
+                    .map(|parser_index| {
+                        let _entered = span.enter();
+                        run_parser(parser_index)
+                    })
+                    .collect()
+            });
+        }
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        if let Some(thread_pool) = thread_pool {
+            match *thread_pool {}
+        }
+    }
+    parser_indexes.iter().map(run_parser).collect()
+}
+
+fn all_indexes(parsers: &[Box<IntentParser>]) -> Vec<usize> {
+    (0..parsers.len()).collect()
+}
+
+/// Selects the first intent meeting its threshold among the results of the parsers, the errors
+/// of the parsers after that one being ignored
+fn first_match<I>(
+    results: I,
+    intent_thresholds: &HashMap<String, f32>,
+) -> Result<Option<ArbitrationResult>>
+where
+    I: Iterator<Item = Result<Option<IntentClassifierResult>>>,
+{
+    for (parser_index, result) in results.enumerate() {
+        if let Some(classification_result) = result? {
+            if !meets_threshold(&classification_result, intent_thresholds) {
+                continue;
+            }
+            return Ok(Some(ArbitrationResult {
+                intent: classification_result,
+                parser_indexes: vec![parser_index],
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Ranks the intents found by the parsers by decreasing probability, the probabilities being
+/// combined according to the arbitration strategy
+pub fn rank_intents(
+    parsers: &[Box<IntentParser>],
+    strategy: &ArbitrationStrategy,
+    input: &str,
+    intents: Option<&HashSet<String>>,
+    context: &ParseContext,
+    intent_thresholds: &HashMap<String, f32>,
+) -> Result<Vec<IntentClassifierResult>> {
+    let mut ranking: Vec<IntentClassifierResult> = match *strategy {
+        ArbitrationStrategy::FirstMatch => {
+            let mut first_distribution = vec![];
+            for parser in parsers {
+                first_distribution = parser
+                    .get_intents(input, intents, context)?
+                    .into_iter()
+                    .filter(|result| meets_threshold(result, intent_thresholds))
+                    .collect();
+                if !first_distribution.is_empty() {
+                    break;
+                }
+            }
+            first_distribution
+        }
+        ArbitrationStrategy::BestConfidence => {
+            let mut best_results: Vec<IntentClassifierResult> = vec![];
+            let results = get_all_distributions(
+                parsers,
+                input,
+                intents,
+                context,
+                intent_thresholds,
+                None,
+            )?;
+            for (_, result) in results {
+                if let Some(best_result) = best_results
+                    .iter_mut()
+                    .find(|best_result| best_result.intent_name == result.intent_name)
+                {
+                    if result.probability > best_result.probability {
+                        best_result.probability = result.probability;
+                    }
+                    continue;
+                }
+                best_results.push(result);
+            }
+            best_results
+        }
+        ArbitrationStrategy::WeightedVote { ref weights }
+        | ArbitrationStrategy::Ensemble { ref weights } => {
+            let results = get_all_distributions(
+                parsers,
+                input,
+                intents,
+                context,
+                intent_thresholds,
+                None,
+            )?;
+            let total_weight = total_weight(parsers.len(), weights);
+            accumulate_votes(weights, &results)
+                .into_iter()
+                .map(|(intent_name, vote)| IntentClassifierResult {
+                    intent_name,
+                    probability: if total_weight > 0. {
+                        vote / total_weight
+                    } else {
+                        0.
+                    },
+                })
+                .collect()
+        }
+    };
+    ranking.sort_by(|a, b| decreasing_order(a.probability, b.probability));
+    Ok(ranking)
+}
+
+fn meets_threshold(
+    result: &IntentClassifierResult,
+    intent_thresholds: &HashMap<String, f32>,
+) -> bool {
+    intent_thresholds
+        .get(&result.intent_name)
+        .map(|threshold| result.probability >= *threshold)
+        .unwrap_or(true)
+}
+
+fn parser_weight(weights: &[f32], parser_index: usize) -> f32 {
+    *weights.get(parser_index).unwrap_or(&1.0)
+}
+
+fn total_weight(nb_parsers: usize, weights: &[f32]) -> f32 {
+    (0..nb_parsers)
+        .map(|parser_index| parser_weight(weights, parser_index))
+        .sum()
+}
+
+/// Accumulates the weighted probabilities of the parsers per intent, in order of first appearance
+fn accumulate_votes(
+    weights: &[f32],
+    results: &[(usize, IntentClassifierResult)],
+) -> Vec<(String, f32)> {
+    let mut votes: Vec<(String, f32)> = vec![];
+    for &(parser_index, ref result) in results {
+        let vote = parser_weight(weights, parser_index) * result.probability;
+        if let Some(intent_votes) = votes
+            .iter_mut()
+            .find(|intent_votes| intent_votes.0 == result.intent_name)
+        {
+            intent_votes.1 += vote;
+            continue;
+        }
+        votes.push((result.intent_name.clone(), vote));
+    }
+    votes
+}
+
+/// Each parser votes for the intents it found with their probabilities multiplied by its weight,
+/// the parsers which voted for the selected intent being sorted by decreasing contribution
+fn weighted_vote(
+    nb_parsers: usize,
+    weights: &[f32],
+    results: Vec<(usize, IntentClassifierResult)>,
+) -> Option<ArbitrationResult> {
+    let total_weight = total_weight(nb_parsers, weights);
+    let votes = accumulate_votes(weights, &results);
+    // The first intent with the highest vote is selected
+    let best_intent = votes
+        .into_iter()
+        .min_by(|a, b| decreasing_order(a.1, b.1));
+
+    best_intent.map(|(intent_name, vote)| {
+        let mut contributions = results
+            .into_iter()
+            .filter(|&(_, ref result)| result.intent_name == intent_name)
+            .map(|(parser_index, result)| {
+                (
+                    parser_index,
+                    parser_weight(weights, parser_index) * result.probability,
+                )
+            })
+            .collect::<Vec<_>>();
+        contributions.sort_by(|a, b| decreasing_order(a.1, b.1));
+        let probability = if total_weight > 0. {
+            vote / total_weight
+        } else {
+            0.
+        };
+        ArbitrationResult {
+            intent: IntentClassifierResult {
+                intent_name,
+                probability,
+            },
+            parser_indexes: contributions
+                .into_iter()
+                .map(|(parser_index, _)| parser_index)
+                .collect(),
+        }
+    })
+}
+
+/// Merges the slots extracted by several parsers, given by order of priority, a slot being
+/// dropped when it overlaps a slot of a parser with a higher priority
+pub fn merge_slots(slots_by_priority: Vec<Vec<Slot>>) -> Vec<Slot> {
+    let mut merged_slots: Vec<Slot> = vec![];
+    for slots in slots_by_priority {
+        let new_slots = slots
+            .into_iter()
+            .filter(|slot| {
+                !merged_slots
+                    .iter()
+                    .any(|merged_slot| slots_conflict(merged_slot, slot))
+            })
+            .collect::<Vec<_>>();
+        merged_slots.extend(new_slots);
+    }
+    merged_slots
+}
+
+fn slots_conflict(slot: &Slot, other_slot: &Slot) -> bool {
+    match (slot.range.as_ref(), other_slot.range.as_ref()) {
+        (Some(range), Some(other_range)) => ranges_overlap(range, other_range),
+        _ => slot.slot_name == other_slot.slot_name && slot.raw_value == other_slot.raw_value,
+    }
+}
+
+fn get_all_intents(
+    parsers: &[Box<IntentParser>],
+    input: &str,
+    intents: Option<&HashSet<String>>,
+    context: &ParseContext,
+    intent_thresholds: &HashMap<String, f32>,
+    thread_pool: Option<&ThreadPool>,
+) -> Result<Vec<(usize, IntentClassifierResult)>> {
+    let parsers_results = run_parsers(parsers, &all_indexes(parsers), thread_pool, |parser| {
+        parser.get_intent(input, intents, context)
+    });
+    let mut results = vec![];
+    for (parser_index, parser_result) in parsers_results.into_iter().enumerate() {
+        if let Some(classification_result) = parser_result? {
+            if meets_threshold(&classification_result, intent_thresholds) {
+                results.push((parser_index, classification_result));
+            }
+        }
+    }
+    Ok(results)
+}
+
+fn get_all_distributions(
+    parsers: &[Box<IntentParser>],
+    input: &str,
+    intents: Option<&HashSet<String>>,
+    context: &ParseContext,
+    intent_thresholds: &HashMap<String, f32>,
+    thread_pool: Option<&ThreadPool>,
+) -> Result<Vec<(usize, IntentClassifierResult)>> {
+    let distributions = run_parsers(parsers, &all_indexes(parsers), thread_pool, |parser| {
+        parser.get_intents(input, intents, context)
+    });
+    let mut results = vec![];
+    for (parser_index, distribution) in distributions.into_iter().enumerate() {
+        for classification_result in distribution? {
+            if meets_threshold(&classification_result, intent_thresholds) {
+                results.push((parser_index, classification_result));
+            }
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "parallel")]
+    use rayon::ThreadPoolBuilder;
+    use testutils::epsilon_eq;
+
+    struct ConstantIntentParser {
+        result: Option<IntentClassifierResult>,
+    }
+
+    impl IntentParser for ConstantIntentParser {
+        fn get_intent(
+            &self,
+            _input: &str,
+            _intents: Option<&HashSet<String>>,
+            _context: &ParseContext,
+        ) -> Result<Option<IntentClassifierResult>> {
+            Ok(self.result.clone())
+        }
+
+        fn get_slots(
+            &self,
+            _input: &str,
+            _intent_name: &str,
+            _context: &ParseContext,
+        ) -> Result<Vec<Slot>> {
+            Ok(vec![])
+        }
+    }
+
+    fn constant_parser(intent: Option<(&str, f32)>) -> Box<IntentParser> {
+        Box::new(ConstantIntentParser {
+            result: intent.map(|(intent_name, probability)| IntentClassifierResult {
+                intent_name: intent_name.to_string(),
+                probability,
+            }),
+        })
+    }
+
+    fn test_parsers() -> Vec<Box<IntentParser>> {
+        vec![
+            constant_parser(None),
+            constant_parser(Some(("MakeTea", 0.6))),
+            constant_parser(Some(("MakeCoffee", 0.7))),
+            constant_parser(Some(("MakeTea", 0.3))),
+        ]
+    }
+
+    #[test]
+    fn arbitrate_works_with_first_match() {
+        // When
+        let result = arbitrate(
+            &test_parsers(),
+            &ArbitrationStrategy::FirstMatch,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &HashMap::new(),
+            None,
+        ).unwrap()
+            .unwrap();
+
+        // Then
+        assert_eq!(vec![1], result.parser_indexes);
+        assert_eq!("MakeTea", result.intent.intent_name);
+    }
+
+    #[test]
+    fn arbitrate_works_with_best_confidence() {
+        // When
+        let result = arbitrate(
+            &test_parsers(),
+            &ArbitrationStrategy::BestConfidence,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &HashMap::new(),
+            None,
+        ).unwrap()
+            .unwrap();
+
+        // Then
+        assert_eq!(vec![2], result.parser_indexes);
+        assert_eq!("MakeCoffee", result.intent.intent_name);
+    }
+
+    #[test]
+    fn arbitrate_works_with_weighted_vote() {
+        // Given
+        let strategy = ArbitrationStrategy::WeightedVote {
+            weights: vec![1.0, 1.0, 1.0, 1.0],
+        };
+
+        // When
+        let result = arbitrate(
+            &test_parsers(),
+            &strategy,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &HashMap::new(),
+            None,
+        ).unwrap()
+            .unwrap();
+
+        // Then
+        assert_eq!(vec![1], result.parser_indexes);
+        assert_eq!("MakeTea", result.intent.intent_name);
+        assert!(epsilon_eq(0.225, result.intent.probability, 1e-6));
+    }
+
+    #[test]
+    fn arbitrate_works_with_weighted_vote_and_heavy_parser() {
+        // Given
+        let strategy = ArbitrationStrategy::WeightedVote {
+            weights: vec![1.0, 1.0, 2.0],
+        };
+
+        // When
+        let result = arbitrate(
+            &test_parsers(),
+            &strategy,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &HashMap::new(),
+            None,
+        ).unwrap()
+            .unwrap();
+
+        // Then
+        assert_eq!(vec![2], result.parser_indexes);
+        assert_eq!("MakeCoffee", result.intent.intent_name);
+        assert!(epsilon_eq(0.28, result.intent.probability, 1e-6));
+    }
+
+    #[test]
+    fn arbitrate_works_with_ensemble() {
+        // Given
+        let strategy = ArbitrationStrategy::Ensemble {
+            weights: vec![1.0, 1.0, 1.0, 3.0],
+        };
+
+        // When
+        let result = arbitrate(
+            &test_parsers(),
+            &strategy,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &HashMap::new(),
+            None,
+        ).unwrap()
+            .unwrap();
+
+        // Then
+        assert_eq!(vec![3, 1], result.parser_indexes);
+        assert_eq!("MakeTea", result.intent.intent_name);
+        assert!(epsilon_eq(0.25, result.intent.probability, 1e-6));
+    }
+
+    #[test]
+    fn arbitrate_should_skip_intents_below_threshold() {
+        // Given
+        let intent_thresholds = hashmap!["MakeTea".to_string() => 0.65];
+
+        // When
+        let result = arbitrate(
+            &test_parsers(),
+            &ArbitrationStrategy::FirstMatch,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &intent_thresholds,
+            None,
+        ).unwrap()
+            .unwrap();
+
+        // Then
+        assert_eq!(vec![2], result.parser_indexes);
+        assert_eq!("MakeCoffee", result.intent.intent_name);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn arbitrate_should_give_same_results_with_thread_pool() {
+        // Given
+        let thread_pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+        let strategies = vec![
+            ArbitrationStrategy::FirstMatch,
+            ArbitrationStrategy::BestConfidence,
+            ArbitrationStrategy::WeightedVote {
+                weights: vec![1.0, 1.0, 2.0],
+            },
+            ArbitrationStrategy::Ensemble {
+                weights: vec![1.0, 1.0, 1.0, 3.0],
+            },
+        ];
+        let parsers = test_parsers();
+
+        for strategy in strategies {
+            // When
+            let result = arbitrate(
+                &parsers,
+                &strategy,
+                "hello",
+                None,
+                &ParseContext::default(),
+                &HashMap::new(),
+                None,
+            );
+            let parallel_result = arbitrate(
+                &parsers,
+                &strategy,
+                "hello",
+                None,
+                &ParseContext::default(),
+                &HashMap::new(),
+                Some(&thread_pool),
+            );
+
+            // Then
+            assert_eq!(result.unwrap(), parallel_result.unwrap());
+        }
+    }
+
+    #[test]
+    fn rank_intents_works_with_weighted_vote() {
+        // Given
+        let strategy = ArbitrationStrategy::WeightedVote {
+            weights: vec![1.0, 1.0, 1.0, 1.0],
+        };
+
+        // When
+        let ranking = rank_intents(
+            &test_parsers(),
+            &strategy,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &HashMap::new(),
+        ).unwrap();
+
+        // Then
+        assert_eq!(2, ranking.len());
+        assert_eq!("MakeTea", ranking[0].intent_name);
+        assert!(epsilon_eq(0.225, ranking[0].probability, 1e-6));
+        assert_eq!("MakeCoffee", ranking[1].intent_name);
+        assert!(epsilon_eq(0.175, ranking[1].probability, 1e-6));
+    }
+
+    #[test]
+    fn rank_intents_works_with_best_confidence() {
+        // When
+        let ranking = rank_intents(
+            &test_parsers(),
+            &ArbitrationStrategy::BestConfidence,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &HashMap::new(),
+        ).unwrap();
+
+        // Then
+        let expected_ranking = vec![
+            IntentClassifierResult {
+                intent_name: "MakeCoffee".to_string(),
+                probability: 0.7,
+            },
+            IntentClassifierResult {
+                intent_name: "MakeTea".to_string(),
+                probability: 0.6,
+            },
+        ];
+        assert_eq!(expected_ranking, ranking);
+    }
+
+    #[test]
+    fn arbitrate_should_not_select_nan_probabilities() {
+        // Given
+        let parsers = vec![
+            constant_parser(Some(("MakeTea", ::std::f32::NAN))),
+            constant_parser(Some(("MakeCoffee", 0.7))),
+        ];
+
+        // When
+        let result = arbitrate(
+            &parsers,
+            &ArbitrationStrategy::BestConfidence,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &HashMap::new(),
+            None,
+        ).unwrap()
+            .unwrap();
+        let ranking = rank_intents(
+            &parsers,
+            &ArbitrationStrategy::BestConfidence,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &HashMap::new(),
+        ).unwrap();
+
+        // Then
+        assert_eq!(vec![1], result.parser_indexes);
+        assert_eq!("MakeCoffee", ranking[0].intent_name);
+    }
+
+    #[test]
+    fn arbitrate_should_return_none_when_no_parser_matches() {
+        // Given
+        let parsers = vec![constant_parser(None)];
+
+        // When
+        let result = arbitrate(
+            &parsers,
+            &ArbitrationStrategy::BestConfidence,
+            "hello",
+            None,
+            &ParseContext::default(),
+            &HashMap::new(),
+            None,
+        );
+
+        // Then
+        assert_eq!(None, result.unwrap());
+    }
+
+    #[test]
+    fn merge_slots_works() {
+        // Given
+        let rule_based_slots = vec![
+            Slot::new_custom(
+                "hot".to_string(),
+                9..12,
+                "Temperature".to_string(),
+                "beverage_temperature".to_string(),
+            ),
+        ];
+        let probabilistic_slots = vec![
+            Slot::new_custom(
+                "hot tea".to_string(),
+                9..16,
+                "Beverage".to_string(),
+                "beverage".to_string(),
+            ),
+            Slot::new_custom(
+                "two".to_string(),
+                2..5,
+                "snips/number".to_string(),
+                "number_of_cups".to_string(),
+            ),
+        ];
+
+        // When
+        let merged_slots = merge_slots(vec![rule_based_slots, probabilistic_slots]);
+
+        // Then
+        let expected_slots = vec![
+            Slot::new_custom(
+                "hot".to_string(),
+                9..12,
+                "Temperature".to_string(),
+                "beverage_temperature".to_string(),
+            ),
+            Slot::new_custom(
+                "two".to_string(),
+                2..5,
+                "snips/number".to_string(),
+                "number_of_cups".to_string(),
+            ),
+        ];
+        assert_eq!(expected_slots, merged_slots);
+    }
+}
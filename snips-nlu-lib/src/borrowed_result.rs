@@ -0,0 +1,145 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use snips_nlu_ontology::{IntentClassifierResult, IntentParserResult, Slot, SlotValue};
+
+use utils::substring_with_char_range;
+
+/// Parsing result borrowing the input, see `SnipsNluEngine::parse_borrowed`
+///
+/// The input of the result is the parsed input itself, or a slice of it when the input is
+/// truncated, and the raw values of the slots are slices of the input. It is serialized like
+/// `IntentParserResult`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BorrowedParserResult<'a> {
+    pub input: &'a str,
+    pub intent: Option<IntentClassifierResult>,
+    pub slots: Option<Vec<BorrowedSlot<'a>>>,
+}
+
+/// Slot whose raw value is a slice of the input whenever the slot has a range, which is the case of
+/// all the slots found in the input
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BorrowedSlot<'a> {
+    pub raw_value: Cow<'a, str>,
+    pub value: SlotValue,
+    pub range: Option<Range<usize>>,
+    pub entity: String,
+    pub slot_name: String,
+}
+
+impl<'a> BorrowedParserResult<'a> {
+    /// Builds the result of the parsing of `input`, borrowing the raw values of the slots from it
+    pub fn new(
+        input: &'a str,
+        intent: Option<IntentClassifierResult>,
+        slots: Option<Vec<Slot>>,
+    ) -> Self {
+        Self {
+            input,
+            intent,
+            slots: slots.map(|slots| {
+                slots
+                    .into_iter()
+                    .map(|slot| BorrowedSlot::new(input, slot))
+                    .collect()
+            }),
+        }
+    }
+
+    pub fn into_owned(self) -> IntentParserResult {
+        IntentParserResult {
+            input: self.input.to_string(),
+            intent: self.intent,
+            slots: self.slots
+                .map(|slots| slots.into_iter().map(BorrowedSlot::into_owned).collect()),
+        }
+    }
+}
+
+impl<'a> BorrowedSlot<'a> {
+    fn new(input: &'a str, slot: Slot) -> Self {
+        let raw_value = match slot.range {
+            Some(ref range) if substring_with_char_range(input, range) == slot.raw_value => {
+                Cow::Borrowed(substring_with_char_range(input, range))
+            }
+            _ => Cow::Owned(slot.raw_value),
+        };
+        Self {
+            raw_value,
+            value: slot.value,
+            range: slot.range,
+            entity: slot.entity,
+            slot_name: slot.slot_name,
+        }
+    }
+
+    pub fn into_owned(self) -> Slot {
+        Slot {
+            raw_value: self.raw_value.into_owned(),
+            value: self.value,
+            range: self.range,
+            entity: self.entity,
+            slot_name: self.slot_name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_ontology::NumberValue;
+
+    fn test_result(input: &str) -> IntentParserResult {
+        IntentParserResult {
+            input: input.to_string(),
+            intent: Some(IntentClassifierResult {
+                intent_name: "MakeCoffee".to_string(),
+                probability: 0.8,
+            }),
+            slots: Some(vec![
+                Slot {
+                    raw_value: "deux".to_string(),
+                    value: SlotValue::Number(NumberValue { value: 2.0 }),
+                    range: Some(0..4),
+                    entity: "snips/number".to_string(),
+                    slot_name: "number_of_cups".to_string(),
+                },
+                Slot {
+                    raw_value: "cafe".to_string(),
+                    value: SlotValue::Custom("coffee".to_string().into()),
+                    range: None,
+                    entity: "beverage".to_string(),
+                    slot_name: "beverage".to_string(),
+                },
+            ]),
+        }
+    }
+
+    #[test]
+    fn borrowed_result_should_borrow_raw_values_from_the_input() {
+        // Given
+        let input = "deux cafés s'il vous plaît";
+
+        // When
+        let result = test_result(input);
+        let borrowed_result = BorrowedParserResult::new(input, result.intent, result.slots);
+
+        // Then
+        let slots = borrowed_result.slots.as_ref().unwrap();
+        match slots[0].raw_value {
+            Cow::Borrowed(raw_value) => assert_eq!("deux", raw_value),
+            Cow::Owned(_) => panic!("Expected a raw value borrowed from the input"),
+        }
+        match slots[1].raw_value {
+            Cow::Borrowed(_) => panic!("Expected an owned raw value"),
+            Cow::Owned(ref raw_value) => assert_eq!("cafe", raw_value),
+        }
+        assert_eq!(
+            ::serde_json::to_value(&test_result(input)).unwrap(),
+            ::serde_json::to_value(&borrowed_result).unwrap()
+        );
+        assert_eq!(test_result(input), borrowed_result.into_owned());
+    }
+}
@@ -0,0 +1,304 @@
+use std::mem;
+use std::sync::Arc;
+
+use memory_footprint::HeapSize;
+use nlu_utils::range::ranges_overlap;
+use snips_nlu_ontology::{BuiltinEntity, BuiltinEntityKind, BuiltinEntityParser, Language};
+
+/// Builtin entity parser backed by the Rustling parsers of one or several languages
+///
+/// When several languages are used, the entities extracted in each language are merged, the
+/// longest entity being kept when entities extracted in different languages overlap, and the
+/// entity of the first language when they have the same length. Fallback
+/// languages are then tried in order, their entities being kept only where no entity was found
+/// before.
+pub struct MultilingualBuiltinEntityParser {
+    parsers: Vec<Arc<BuiltinEntityParser>>,
+    fallback_parsers: Vec<Arc<BuiltinEntityParser>>,
+}
+
+impl MultilingualBuiltinEntityParser {
+    pub fn new(languages: &[Language]) -> Self {
+        Self {
+            parsers: get_parsers(languages),
+            fallback_parsers: vec![],
+        }
+    }
+
+    /// Sets the languages tried in order on the parts of the sentences where no entity is found
+    /// in the languages of the parser
+    pub fn with_fallback_languages(mut self, languages: &[Language]) -> Self {
+        self.fallback_parsers = get_parsers(languages);
+        self
+    }
+
+    pub fn extract_entities(
+        &self,
+        sentence: &str,
+        filter_entity_kinds: Option<&[BuiltinEntityKind]>,
+    ) -> Vec<BuiltinEntity> {
+        let span = pipeline_span!("builtin_entity_extraction");
+        let _entered = span.enter();
+        let mut entities = if self.parsers.is_empty() {
+            vec![]
+        } else if self.parsers.len() == 1 {
+            self.parsers[0].extract_entities(sentence, filter_entity_kinds)
+        } else {
+            let all_entities = self.parsers
+                .iter()
+                .flat_map(|parser| parser.extract_entities(sentence, filter_entity_kinds));
+            merge_entities(all_entities)
+        };
+        for fallback_parser in &self.fallback_parsers {
+            for entity in fallback_parser.extract_entities(sentence, filter_entity_kinds) {
+                let is_new_span = entities
+                    .iter()
+                    .all(|existing| !ranges_overlap(&existing.range, &entity.range));
+                if is_new_span {
+                    entities.push(entity);
+                }
+            }
+        }
+        entities.sort_by_key(|entity| entity.range.start);
+        entities
+    }
+}
+
+/// The Rustling parsers are shared across engines by `snips-nlu-ontology`, which doesn't expose
+/// their size, so that only the references to them are counted
+impl HeapSize for MultilingualBuiltinEntityParser {
+    fn heap_size(&self) -> usize {
+        (self.parsers.capacity() + self.fallback_parsers.capacity())
+            * mem::size_of::<Arc<BuiltinEntityParser>>()
+    }
+}
+
+#[cfg(feature = "builtin_entities")]
+fn get_parsers(languages: &[Language]) -> Vec<Arc<BuiltinEntityParser>> {
+    languages
+        .iter()
+        .map(|language| BuiltinEntityParser::get(*language))
+        .collect()
+}
+
+/// Without the `builtin_entities` feature, no Rustling parser is loaded and no builtin entity is
+/// ever extracted, which keeps the engine light enough for WebAssembly
+#[cfg(not(feature = "builtin_entities"))]
+fn get_parsers(_languages: &[Language]) -> Vec<Arc<BuiltinEntityParser>> {
+    vec![]
+}
+
+/// Returns an input in which the builtin entity parser of the language extracts an entity of the
+/// given kind, used to check the parsers when the engine is warmed up
+pub fn warm_up_input(kind: BuiltinEntityKind, language: Language) -> Option<&'static str> {
+    use self::BuiltinEntityKind::*;
+    use snips_nlu_ontology::Language::*;
+    match (kind, language) {
+        (AmountOfMoney, EN) => Some("ten dollars"),
+        (Duration, EN) => Some("for three hours"),
+        (Number, EN) => Some("forty two"),
+        (Ordinal, EN) => Some("the third one"),
+        (Temperature, EN) => Some("twenty degrees"),
+        (Time, EN) => Some("tomorrow at eight am"),
+        (AmountOfMoney, FR) => Some("dix euros"),
+        (Duration, FR) => Some("pendant trois heures"),
+        (Number, FR) => Some("quarante deux"),
+        (Ordinal, FR) => Some("le troisième"),
+        (Temperature, FR) => Some("vingt degrés"),
+        (Time, FR) => Some("demain à huit heures"),
+        (AmountOfMoney, DE) => Some("zehn Euro"),
+        (Duration, DE) => Some("für drei Stunden"),
+        (Number, DE) => Some("zweiundvierzig"),
+        (Ordinal, DE) => Some("der dritte"),
+        (Temperature, DE) => Some("zwanzig Grad"),
+        (Time, DE) => Some("morgen um acht Uhr"),
+        (AmountOfMoney, ES) => Some("diez euros"),
+        (Duration, ES) => Some("durante tres horas"),
+        (Number, ES) => Some("cuarenta y dos"),
+        (Ordinal, ES) => Some("el tercero"),
+        (Temperature, ES) => Some("veinte grados"),
+        (Time, ES) => Some("mañana a las ocho"),
+        _ => None,
+    }
+}
+
+/// Merges the entities of all the languages, the overlaps being resolved once all the entities
+/// are known so that an entity overlapping several shorter ones replaces all of them
+fn merge_entities<I>(entities: I) -> Vec<BuiltinEntity>
+where
+    I: Iterator<Item = BuiltinEntity>,
+{
+    let mut entities: Vec<BuiltinEntity> = entities.collect();
+    // The sort is stable, so that the order of the languages breaks the ties
+    entities.sort_by(|a, b| b.range.len().cmp(&a.range.len()));
+    let mut merged: Vec<BuiltinEntity> = vec![];
+    for entity in entities {
+        let is_conflicting = merged
+            .iter()
+            .any(|existing| ranges_overlap(&existing.range, &entity.range));
+        if !is_conflicting {
+            merged.push(entity);
+        }
+    }
+    merged.sort_by_key(|entity| entity.range.start);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_ontology::{NumberValue, SlotValue};
+
+    #[test]
+    fn merge_entities_works() {
+        // Given
+        let entities = vec![
+            BuiltinEntity {
+                value: "two".to_string(),
+                range: 0..3,
+                entity: SlotValue::Number(NumberValue { value: 2.0 }),
+                entity_kind: BuiltinEntityKind::Number,
+            },
+            BuiltinEntity {
+                value: "10".to_string(),
+                range: 12..14,
+                entity: SlotValue::Number(NumberValue { value: 10.0 }),
+                entity_kind: BuiltinEntityKind::Number,
+            },
+            BuiltinEntity {
+                value: "10 20".to_string(),
+                range: 12..17,
+                entity: SlotValue::Number(NumberValue { value: 1020.0 }),
+                entity_kind: BuiltinEntityKind::Number,
+            },
+            BuiltinEntity {
+                value: "tw".to_string(),
+                range: 1..3,
+                entity: SlotValue::Number(NumberValue { value: 3.0 }),
+                entity_kind: BuiltinEntityKind::Number,
+            },
+        ];
+
+        // When
+        let merged_entities = merge_entities(entities.into_iter());
+
+        // Then
+        let expected_entities = vec![
+            BuiltinEntity {
+                value: "two".to_string(),
+                range: 0..3,
+                entity: SlotValue::Number(NumberValue { value: 2.0 }),
+                entity_kind: BuiltinEntityKind::Number,
+            },
+            BuiltinEntity {
+                value: "10 20".to_string(),
+                range: 12..17,
+                entity: SlotValue::Number(NumberValue { value: 1020.0 }),
+                entity_kind: BuiltinEntityKind::Number,
+            },
+        ];
+        assert_eq!(expected_entities, merged_entities);
+    }
+
+    #[test]
+    fn merge_entities_should_drop_all_the_entities_overlapping_a_longer_one() {
+        // Given
+        let number = |value: &str, range: ::std::ops::Range<usize>| BuiltinEntity {
+            value: value.to_string(),
+            range,
+            entity: SlotValue::Number(NumberValue { value: 1.0 }),
+            entity_kind: BuiltinEntityKind::Number,
+        };
+        let entities = vec![
+            number("one", 0..3),
+            number("two", 8..11),
+            number("and", 4..7),
+            number("one and two", 0..11),
+        ];
+
+        // When
+        let merged_entities = merge_entities(entities.into_iter());
+
+        // Then
+        assert_eq!(vec![number("one and two", 0..11)], merged_entities);
+    }
+
+    #[test]
+    fn extract_entities_works_with_several_languages() {
+        // Given
+        let parser = MultilingualBuiltinEntityParser::new(&[Language::EN, Language::FR]);
+
+        // When
+        let entities =
+            parser.extract_entities("two cups and trois tasses", Some(&[BuiltinEntityKind::Number]));
+
+        // Then
+        let values = entities.into_iter().map(|e| e.entity).collect::<Vec<_>>();
+        let expected_values = vec![
+            SlotValue::Number(NumberValue { value: 2.0 }),
+            SlotValue::Number(NumberValue { value: 3.0 }),
+        ];
+        assert_eq!(expected_values, values);
+    }
+
+    #[test]
+    fn extract_entities_should_use_fallback_languages_where_nothing_is_found() {
+        // Given
+        let parser = MultilingualBuiltinEntityParser::new(&[Language::FR])
+            .with_fallback_languages(&[Language::EN]);
+
+        // When
+        let entities = parser.extract_entities(
+            "trois tasses and two cups",
+            Some(&[BuiltinEntityKind::Number]),
+        );
+
+        // Then
+        let values = entities.into_iter().map(|e| e.entity).collect::<Vec<_>>();
+        let expected_values = vec![
+            SlotValue::Number(NumberValue { value: 3.0 }),
+            SlotValue::Number(NumberValue { value: 2.0 }),
+        ];
+        assert_eq!(expected_values, values);
+    }
+
+    #[cfg(not(feature = "builtin_entities"))]
+    #[cfg(feature = "builtin_entities")]
+    #[test]
+    fn warm_up_inputs_should_contain_an_entity_of_their_kind() {
+        // Given
+        let kinds = [
+            BuiltinEntityKind::AmountOfMoney,
+            BuiltinEntityKind::Duration,
+            BuiltinEntityKind::Number,
+            BuiltinEntityKind::Ordinal,
+            BuiltinEntityKind::Temperature,
+            BuiltinEntityKind::Time,
+        ];
+        let parser = MultilingualBuiltinEntityParser::new(&[Language::EN]);
+
+        // When
+        let kinds_without_entity = kinds
+            .iter()
+            .filter(|kind| {
+                let input = warm_up_input(**kind, Language::EN).unwrap();
+                parser.extract_entities(input, Some(&[**kind])).is_empty()
+            })
+            .collect::<Vec<_>>();
+
+        // Then
+        assert!(kinds_without_entity.is_empty());
+    }
+
+    #[test]
+    fn extract_entities_should_find_nothing_without_feature() {
+        // Given
+        let parser = MultilingualBuiltinEntityParser::new(&[Language::EN]);
+
+        // When
+        let entities = parser.extract_entities("two cups", None);
+
+        // Then
+        assert!(entities.is_empty());
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, Weak};
+
+use configurations::WeightsQuantization;
+use memory_footprint::HeapSize;
+
+/// Number of hashes of interned strings from which the hashes of the dropped strings are removed
+const MIN_SWEPT_HASHES: usize = 1024;
+
+lazy_static! {
+    static ref INTERNED_STRINGS: Mutex<InternedStrings> = Mutex::new(InternedStrings::default());
+}
+
+/// Strings shared by the models, grouped by hash so that the strings themselves are not retained
+/// once the models using them are dropped
+#[derive(Default)]
+struct InternedStrings {
+    buckets: HashMap<u64, Vec<Weak<str>>>,
+    /// Number of hashes left after the last removal of the hashes of dropped strings, which
+    /// happens whenever their number doubles so that reloading engines doesn't accumulate them
+    nb_swept_hashes: usize,
+}
+
+/// Vector of model weights, stored with a reduced precision when quantized
+#[derive(Debug, Clone, PartialEq)]
+pub enum Weights<T> {
+    Full(Vec<T>),
+    /// Half precision floats
+    F16(Vec<u16>),
+    /// Integers multiplied by the scale of their row, so that the rows of small weights keep
+    /// their precision
+    Int8 { values: Vec<i8>, scales: Vec<f64> },
+}
+
+impl<T: Copy + Into<f64>> Weights<T> {
+    pub fn len(&self) -> usize {
+        match *self {
+            Weights::Full(ref values) => values.len(),
+            Weights::F16(ref values) => values.len(),
+            Weights::Int8 { ref values, .. } => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the weight at `index`, which belongs to the row `row`
+    pub fn get(&self, row: usize, index: usize) -> f64 {
+        match *self {
+            Weights::Full(ref values) => values[index].into(),
+            Weights::F16(ref values) => f64::from(f16_to_f32(values[index])),
+            Weights::Int8 {
+                ref values,
+                ref scales,
+            } => f64::from(values[index]) * scales[row],
+        }
+    }
+
+    /// Returns the weights stored with the precision of `quantization`, the weights of row `i`
+    /// being the ones between `row_offsets[i]` and `row_offsets[i + 1]`
+    pub fn quantize(self, quantization: WeightsQuantization, row_offsets: &[usize]) -> Self {
+        let values = match self {
+            Weights::Full(values) => values.into_iter().map(|value| value.into()).collect(),
+            // Weights are not quantized twice
+            quantized_weights => return quantized_weights,
+        };
+        quantize(values, quantization, row_offsets)
+    }
+}
+
+fn quantize<T>(
+    values: Vec<f64>,
+    quantization: WeightsQuantization,
+    row_offsets: &[usize],
+) -> Weights<T> {
+    match quantization {
+        WeightsQuantization::F16 => {
+            Weights::F16(values.into_iter().map(|value| f32_to_f16(value as f32)).collect())
+        }
+        WeightsQuantization::Int8 => {
+            let scales = row_offsets
+                .windows(2)
+                .map(|row| {
+                    let max_abs_value = values[row[0]..row[1]]
+                        .iter()
+                        .fold(0.0, |max, value: &f64| max.max(value.abs()));
+                    if max_abs_value > 0.0 {
+                        max_abs_value / 127.0
+                    } else {
+                        1.0
+                    }
+                })
+                .collect::<Vec<f64>>();
+            let mut quantized_values = Vec::with_capacity(values.len());
+            for (row, scale) in row_offsets.windows(2).zip(scales.iter()) {
+                quantized_values.extend(
+                    values[row[0]..row[1]]
+                        .iter()
+                        .map(|value| (value / scale).round().max(-127.0).min(127.0) as i8),
+                );
+            }
+            Weights::Int8 {
+                values: quantized_values,
+                scales,
+            }
+        }
+    }
+}
+
+impl<T: HeapSize> HeapSize for Weights<T> {
+    fn heap_size(&self) -> usize {
+        match *self {
+            Weights::Full(ref values) => values.heap_size(),
+            Weights::F16(ref values) => values.heap_size(),
+            Weights::Int8 {
+                ref values,
+                ref scales,
+            } => values.heap_size() + scales.heap_size(),
+        }
+    }
+}
+
+/// Returns a shared copy of `value`, so that the strings repeated across models are stored once
+pub fn intern(value: &str) -> Arc<str> {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    let hash = hasher.finish();
+    let mut interned_strings = INTERNED_STRINGS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if interned_strings.buckets.len() > 2 * interned_strings.nb_swept_hashes.max(MIN_SWEPT_HASHES)
+    {
+        interned_strings.buckets.retain(|_, bucket| {
+            bucket.retain(|string| string.upgrade().is_some());
+            !bucket.is_empty()
+        });
+        interned_strings.nb_swept_hashes = interned_strings.buckets.len();
+    }
+    let bucket = interned_strings.buckets.entry(hash).or_insert_with(Vec::new);
+    bucket.retain(|string| string.upgrade().is_some());
+    if let Some(string) = bucket
+        .iter()
+        .filter_map(|string| string.upgrade())
+        .find(|string| &**string == value)
+    {
+        return string;
+    }
+    let string: Arc<str> = Arc::from(value);
+    bucket.push(Arc::downgrade(&string));
+    string
+}
+
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+    if exponent == 0xff {
+        // Infinity or NaN
+        return sign | 0x7c00 | if mantissa != 0 { 0x0200 } else { 0 };
+    }
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        // Subnormal half precision float
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - half_exponent) as u32;
+        let mut half_mantissa = mantissa >> shift;
+        if mantissa & (1 << (shift - 1)) != 0 {
+            half_mantissa += 1;
+        }
+        return sign | half_mantissa as u16;
+    }
+    let mut half = u32::from(sign) | ((half_exponent as u32) << 10) | (mantissa >> 13);
+    // Rounding can carry over to the exponent, which gives the right result
+    if mantissa & 0x1000 != 0 {
+        half += 1;
+    }
+    half as u16
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = u32::from(half & 0x8000) << 16;
+    let exponent = u32::from((half >> 10) & 0x1f);
+    let mantissa = u32::from(half & 0x03ff);
+    if exponent == 0 {
+        let value = mantissa as f32 * 2f32.powi(-24);
+        return if sign != 0 { -value } else { value };
+    }
+    let bits = if exponent == 0x1f {
+        sign | 0x7f80_0000 | (mantissa << 13)
+    } else {
+        sign | ((exponent + 127 - 15) << 23) | (mantissa << 13)
+    };
+    f32::from_bits(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f16_conversion_works() {
+        // Given
+        let values = vec![0.0, 1.0, -2.5, 0.1, 65504.0, 1e-6, -1e9];
+
+        // When
+        let converted_values = values
+            .iter()
+            .map(|value| f16_to_f32(f32_to_f16(*value)))
+            .collect::<Vec<_>>();
+
+        // Then
+        assert_eq!(0.0, converted_values[0]);
+        assert_eq!(1.0, converted_values[1]);
+        assert_eq!(-2.5, converted_values[2]);
+        assert!((converted_values[3] - 0.1).abs() < 1e-4);
+        assert_eq!(65504.0, converted_values[4]);
+        assert!((converted_values[5] - 1e-6).abs() < 1e-7);
+        assert_eq!(::std::f32::NEG_INFINITY, converted_values[6]);
+    }
+
+    #[test]
+    fn quantize_works() {
+        // Given
+        let weights = Weights::Full(vec![0.5_f64, -1.27, 0.0, 0.013]);
+        let row_offsets = [0, weights.len()];
+
+        // When
+        let f16_weights = weights
+            .clone()
+            .quantize(WeightsQuantization::F16, &row_offsets);
+        let int8_weights = weights
+            .clone()
+            .quantize(WeightsQuantization::Int8, &row_offsets);
+
+        // Then
+        for index in 0..weights.len() {
+            assert!((weights.get(0, index) - f16_weights.get(0, index)).abs() < 1e-3);
+            assert!((weights.get(0, index) - int8_weights.get(0, index)).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn int8_quantization_should_scale_each_row() {
+        // Given
+        let weights = Weights::Full(vec![100.0_f64, -50.0, 0.02, -0.01]);
+        let row_offsets = [0, 2, 4];
+
+        // When
+        let int8_weights = weights
+            .clone()
+            .quantize(WeightsQuantization::Int8, &row_offsets);
+
+        // Then
+        let rows = [0, 0, 1, 1];
+        for (index, &row) in rows.iter().enumerate() {
+            let weight = weights.get(row, index);
+            assert!((weight - int8_weights.get(row, index)).abs() <= weight.abs() / 100.0);
+        }
+    }
+
+    #[test]
+    fn intern_should_share_strings() {
+        // When
+        let string = intern("word:coffee");
+        let same_string = intern(&format!("word:{}", "coffee"));
+        let other_string = intern("word:tea");
+
+        // Then
+        assert!(Arc::ptr_eq(&string, &same_string));
+        assert!(!Arc::ptr_eq(&string, &other_string));
+    }
+}
@@ -1,16 +1,27 @@
 use std::io::{Read, Seek};
+#[cfg(feature = "encryption")]
+use std::io::Cursor;
 use std::sync::{Arc, Mutex};
 use std::path;
 use std::fs;
 
 use errors::*;
-use configurations::{ModelVersionConfiguration, NluEngineConfiguration,
+use configurations::checksums::{verify_checksums, CHECKSUMS_FILENAME};
+#[cfg(feature = "encryption")]
+use configurations::encryption::decrypt;
+use configurations::{configuration_value, ModelMigrationRegistry, NluEngineConfiguration,
                      NluEngineConfigurationConvertible};
+use resource_reader::ResourceReader;
 
 const NLU_CONFIGURATION_FILENAME: &str = "trained_assistant.json";
+/// Keys of the intent classifier configurations which hold the path of a resource file
+const RESOURCE_PATH_KEYS: &[&str] = &["word_embeddings_path", "model_path"];
+/// Key of the slot filler configurations which holds the path of the CRF model file
+const CRF_MODEL_FILE_KEY: &str = "crf_model_file";
 
 pub struct FileBasedConfiguration {
     nlu_configuration: NluEngineConfiguration,
+    resource_reader: ResourceReader,
 }
 
 impl FileBasedConfiguration {
@@ -18,30 +29,165 @@ impl FileBasedConfiguration {
         root_dir: P,
         bypass_model_version_check: bool,
     ) -> Result<Self> {
-        let path = root_dir.as_ref().join(NLU_CONFIGURATION_FILENAME);
-
-        if !bypass_model_version_check {
-            let config_file =
-                fs::File::open(&path).chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", path)))?;
-            let nlu_configuration_with_version_only: ModelVersionConfiguration =
-                ::serde_json::from_reader(config_file)
-                    .chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", path)))?;
-            if nlu_configuration_with_version_only.model_version
-                != ::SnipsNluEngine::model_version()
-            {
-                bail!(ErrorKind::WrongModelVersion(
-                    nlu_configuration_with_version_only.model_version
-                ));
+        Self::new_with_migrations(
+            root_dir,
+            bypass_model_version_check,
+            &ModelMigrationRegistry::new(),
+        )
+    }
+
+    /// Loads the configuration, models trained with older versions being converted with the
+    /// registered migrations
+    pub fn new_with_migrations<P: AsRef<path::Path>>(
+        root_dir: P,
+        bypass_model_version_check: bool,
+        migrations: &ModelMigrationRegistry,
+    ) -> Result<Self> {
+        Self::from_dir(
+            root_dir.as_ref(),
+            ResourceReader::default(),
+            bypass_model_version_check,
+            migrations,
+        )
+    }
+
+    /// Loads a model whose files, the configuration as well as the resource files, have been
+    /// encrypted with AES-256-GCM, the nonce being stored before the ciphertext
+    #[cfg(feature = "encryption")]
+    pub fn new_encrypted<P: AsRef<path::Path>>(
+        root_dir: P,
+        key: &[u8],
+        bypass_model_version_check: bool,
+    ) -> Result<Self> {
+        Self::from_dir(
+            root_dir.as_ref(),
+            ResourceReader::encrypted(key),
+            bypass_model_version_check,
+            &ModelMigrationRegistry::new(),
+        )
+    }
+
+    fn from_dir(
+        root_dir: &path::Path,
+        resource_reader: ResourceReader,
+        bypass_model_version_check: bool,
+        migrations: &ModelMigrationRegistry,
+    ) -> Result<Self> {
+        verify_directory_checksums(root_dir)?;
+        let config_bytes = resource_reader.read(root_dir.join(NLU_CONFIGURATION_FILENAME))?;
+        let mut configuration = Self::from_bytes(
+            root_dir,
+            &config_bytes,
+            bypass_model_version_check,
+            migrations,
+        )?;
+        configuration.resource_reader = resource_reader;
+        Ok(configuration)
+    }
+
+    fn from_bytes(
+        root_dir: &path::Path,
+        config_bytes: &[u8],
+        bypass_model_version_check: bool,
+        migrations: &ModelMigrationRegistry,
+    ) -> Result<Self> {
+        let path = root_dir.join(NLU_CONFIGURATION_FILENAME);
+        let json_configuration = configuration_value(config_bytes)
+            .chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", path)))?;
+        let mut nlu_configuration = load_configuration(
+            json_configuration,
+            bypass_model_version_check,
+            migrations,
+            format!("{:?}", path),
+        )?;
+        resolve_resource_paths(&mut nlu_configuration.intent_parsers, root_dir)?;
+        for entity in nlu_configuration.dataset_metadata.entities.values_mut() {
+            if let Some(utterances_file) = entity.utterances_file.as_mut() {
+                *utterances_file = resolve_resource_path(utterances_file, root_dir)?;
             }
         }
 
-        let config_file =
-            fs::File::open(&path).chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", path)))?;
-        let nlu_configuration = ::serde_json::from_reader(config_file)
-            .chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", path)))?;
+        Ok(Self {
+            nlu_configuration,
+            resource_reader: ResourceReader::default(),
+        })
+    }
+}
 
-        Ok(Self { nlu_configuration })
+/// Checks the files of the model directory when it contains a checksums manifest
+fn verify_directory_checksums(root_dir: &path::Path) -> Result<()> {
+    let manifest_path = root_dir.join(CHECKSUMS_FILENAME);
+    if !manifest_path.is_file() {
+        return Ok(());
+    }
+    let manifest = fs::read(&manifest_path)
+        .chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", manifest_path)))?;
+    verify_checksums(&manifest, |file_name| Ok(fs::read(root_dir.join(file_name))?))
+}
+
+/// Checks the model version of the configuration, migrating it if needed, and deserializes it
+fn load_configuration(
+    json_configuration: ::serde_json::Value,
+    bypass_model_version_check: bool,
+    migrations: &ModelMigrationRegistry,
+    config_path: String,
+) -> Result<NluEngineConfiguration> {
+    let json_configuration = if bypass_model_version_check {
+        json_configuration
+    } else {
+        migrations.migrate(json_configuration)?
+    };
+    ::serde_json::from_value(json_configuration).chain_err(|| ErrorKind::ConfigLoad(config_path))
+}
+
+/// Resolves the paths of the resource files used by the intent classifiers and the slot fillers,
+/// relative paths being relative to the model directory, and checks that the files exist
+fn resolve_resource_paths(
+    intent_parsers: &mut [::serde_json::Value],
+    root_dir: &path::Path,
+) -> Result<()> {
+    for parser_config in intent_parsers.iter_mut() {
+        if let Some(classifier_config) = parser_config.get_mut("intent_classifier") {
+            for key in RESOURCE_PATH_KEYS {
+                resolve_resource_path_value(classifier_config, key, root_dir)?;
+            }
+        }
+        if let Some(slot_fillers) = parser_config
+            .get_mut("slot_fillers")
+            .and_then(|slot_fillers| slot_fillers.as_object_mut())
+        {
+            for slot_filler_config in slot_fillers.values_mut() {
+                resolve_resource_path_value(slot_filler_config, CRF_MODEL_FILE_KEY, root_dir)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn resolve_resource_path_value(
+    config: &mut ::serde_json::Value,
+    key: &str,
+    root_dir: &path::Path,
+) -> Result<()> {
+    if let Some(path_value) = config.get_mut(key) {
+        if path_value.is_null() {
+            return Ok(());
+        }
+        let resolved_path = match path_value.as_str() {
+            Some(resource_path) => resolve_resource_path(resource_path, root_dir)?,
+            None => bail!("Resource path '{}' is not a string", key),
+        };
+        *path_value = ::serde_json::Value::String(resolved_path);
     }
+    Ok(())
+}
+
+fn resolve_resource_path(resource_path: &str, root_dir: &path::Path) -> Result<String> {
+    let resource_path = root_dir.join(resource_path);
+    if !resource_path.is_file() {
+        bail!("Missing resource file: {:?}", resource_path);
+    }
+    Ok(resource_path.to_string_lossy().into_owned())
 }
 
 impl NluEngineConfigurationConvertible for FileBasedConfiguration {
@@ -52,6 +198,10 @@ impl NluEngineConfigurationConvertible for FileBasedConfiguration {
     fn into_nlu_engine_configuration(self) -> NluEngineConfiguration {
         self.nlu_configuration
     }
+
+    fn resource_reader(&self) -> ResourceReader {
+        self.resource_reader.clone()
+    }
 }
 
 pub struct ZipBasedConfiguration {
@@ -60,12 +210,30 @@ pub struct ZipBasedConfiguration {
 
 impl ZipBasedConfiguration {
     pub fn new<R>(reader: R, bypass_model_version_check: bool) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        Self::new_with_migrations(
+            reader,
+            bypass_model_version_check,
+            &ModelMigrationRegistry::new(),
+        )
+    }
+
+    /// Loads the configuration, models trained with older versions being converted with the
+    /// registered migrations
+    pub fn new_with_migrations<R>(
+        reader: R,
+        bypass_model_version_check: bool,
+        migrations: &ModelMigrationRegistry,
+    ) -> Result<Self>
     where
         R: Read + Seek,
     {
         let zip =
             ::zip::ZipArchive::new(reader).chain_err(|| "Could not load ZipBasedConfiguration")?;
         let mutex = Arc::new(Mutex::new(zip));
+        Self::verify_checksums(&mutex)?;
 
         let nlu_conf_bytes = Self::read_bytes(&mutex, NLU_CONFIGURATION_FILENAME)
             .or_else(|_| {
@@ -74,25 +242,48 @@ impl ZipBasedConfiguration {
             })
             .chain_err(|| ErrorKind::ConfigLoad(NLU_CONFIGURATION_FILENAME.into()))?;
 
-        if !bypass_model_version_check {
-            let nlu_configuration_with_version_only: ModelVersionConfiguration =
-                ::serde_json::from_slice(&nlu_conf_bytes)
-                    .chain_err(|| ErrorKind::ConfigLoad(NLU_CONFIGURATION_FILENAME.into()))?;
-            if nlu_configuration_with_version_only.model_version
-                != ::SnipsNluEngine::model_version()
-            {
-                bail!(ErrorKind::WrongModelVersion(
-                    nlu_configuration_with_version_only.model_version
-                ));
-            }
-        }
-
-        let nlu_configuration = ::serde_json::from_slice(&nlu_conf_bytes)
+        let json_configuration = configuration_value(&nlu_conf_bytes)
             .chain_err(|| ErrorKind::ConfigLoad(NLU_CONFIGURATION_FILENAME.into()))?;
+        let nlu_configuration = load_configuration(
+            json_configuration,
+            bypass_model_version_check,
+            migrations,
+            NLU_CONFIGURATION_FILENAME.into(),
+        )?;
 
         Ok(Self { nlu_configuration })
     }
 
+    /// Loads a zip archive which has been encrypted with AES-256-GCM, the nonce being stored
+    /// before the ciphertext
+    #[cfg(feature = "encryption")]
+    pub fn new_encrypted<R: Read>(
+        mut reader: R,
+        key: &[u8],
+        bypass_model_version_check: bool,
+    ) -> Result<Self> {
+        let mut encrypted_bytes = vec![];
+        reader.read_to_end(&mut encrypted_bytes)?;
+        let zip_bytes = decrypt(&encrypted_bytes, key)?;
+        Self::new(Cursor::new(zip_bytes), bypass_model_version_check)
+    }
+
+    /// Checks the files of the archive when it contains a checksums manifest
+    fn verify_checksums<R>(zip: &Mutex<::zip::read::ZipArchive<R>>) -> Result<()>
+    where
+        R: Read + Seek,
+    {
+        for prefix in &["", "assistant/"] {
+            let manifest_name = format!("{}{}", prefix, CHECKSUMS_FILENAME);
+            if let Ok(manifest) = Self::read_bytes(zip, &manifest_name) {
+                return verify_checksums(&manifest, |file_name| {
+                    Self::read_bytes(zip, &format!("{}{}", prefix, file_name))
+                });
+            }
+        }
+        Ok(())
+    }
+
     fn read_bytes<R>(zip: &Mutex<::zip::read::ZipArchive<R>>, name: &str) -> Result<Vec<u8>>
     where
         R: Read + Seek,
@@ -120,6 +311,7 @@ impl NluEngineConfigurationConvertible for ZipBasedConfiguration {
 mod tests {
     use std::fs;
     use super::*;
+    use configurations::ConfigurationFormat;
     use utils::file_path;
 
     #[test]
@@ -141,4 +333,120 @@ mod tests {
 
         assert_eq!(Ok("ok"), nlu_config_formatted);
     }
+
+    #[test]
+    fn file_based_assistant_should_migrate_cbor_configuration() {
+        // Given
+        let root_dir = file_path("tests/configurations");
+        let mut configuration: NluEngineConfiguration =
+            ::testutils::parse_json("tests/configurations/trained_assistant.json");
+        configuration.model_version = "0.12.0".to_string();
+        let mut config_bytes = vec![];
+        configuration
+            .to_writer(&mut config_bytes, ConfigurationFormat::Cbor)
+            .unwrap();
+        let mut migrations = ModelMigrationRegistry::new();
+        migrations.register(
+            "0.12.0",
+            ::SnipsNluEngine::model_version(),
+            |configuration: ::serde_json::Value| Ok(configuration),
+        );
+
+        // When
+        let migrated_configuration =
+            FileBasedConfiguration::from_bytes(&root_dir, &config_bytes, false, &migrations);
+        let unmigrated_configuration = FileBasedConfiguration::from_bytes(
+            &root_dir,
+            &config_bytes,
+            false,
+            &ModelMigrationRegistry::new(),
+        );
+
+        // Then
+        assert_eq!(
+            Some(::SnipsNluEngine::model_version()),
+            migrated_configuration
+                .ok()
+                .map(|configuration| configuration.nlu_configuration.model_version)
+                .as_ref()
+                .map(|version| &**version)
+        );
+        assert!(unmigrated_configuration.is_err());
+    }
+
+    #[test]
+    fn resolve_resource_paths_works() {
+        // Given
+        let root_dir = file_path("tests/configurations");
+        let mut intent_parsers: Vec<::serde_json::Value> = vec![
+            ::serde_json::from_str(r#"{"unit_name": "deterministic_intent_parser"}"#).unwrap(),
+            ::serde_json::from_str(
+                r#"{"intent_classifier": {"word_embeddings_path": "trained_assistant.json"}}"#,
+            ).unwrap(),
+            ::serde_json::from_str(
+                r#"{"slot_fillers": {"MakeTea": {"crf_model_file": "trained_assistant.json"}}}"#,
+            ).unwrap(),
+        ];
+        let mut missing_intent_parsers: Vec<::serde_json::Value> = vec![
+            ::serde_json::from_str(r#"{"intent_classifier": {"model_path": "missing.onnx"}}"#)
+                .unwrap(),
+        ];
+
+        // When
+        let result = resolve_resource_paths(&mut intent_parsers, &root_dir);
+        let missing_result = resolve_resource_paths(&mut missing_intent_parsers, &root_dir);
+
+        // Then
+        assert!(result.is_ok());
+        let expected_path = root_dir.join("trained_assistant.json");
+        assert_eq!(
+            Some(&*expected_path.to_string_lossy()),
+            intent_parsers[1]["intent_classifier"]["word_embeddings_path"].as_str()
+        );
+        assert_eq!(
+            Some(&*expected_path.to_string_lossy()),
+            intent_parsers[2]["slot_fillers"]["MakeTea"]["crf_model_file"].as_str()
+        );
+        assert!(missing_result.is_err());
+    }
+
+    #[test]
+    fn resolve_resource_paths_should_resolve_model_path() {
+        // Given
+        let root_dir = file_path("tests/onnx_model");
+        let mut intent_parsers: Vec<::serde_json::Value> = vec![
+            ::serde_json::from_str(
+                r#"{"intent_classifier": {"model_path": "intent_classifier.onnx"}}"#,
+            ).unwrap(),
+        ];
+
+        // When
+        let result = resolve_resource_paths(&mut intent_parsers, &root_dir);
+
+        // Then
+        assert!(result.is_ok());
+        let expected_path = root_dir.join("intent_classifier.onnx");
+        assert_eq!(
+            Some(&*expected_path.to_string_lossy()),
+            intent_parsers[0]["intent_classifier"]["model_path"].as_str()
+        );
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypted_zip_based_assistant_works() {
+        // Given
+        let key = [42; 32];
+        let zip_bytes = fs::read(file_path("tests/zip_files/sample_config.zip")).unwrap();
+        let encrypted_bytes = ::configurations::encryption::encrypt(&zip_bytes, &key, &[1; 12]);
+
+        // When
+        let configuration = ZipBasedConfiguration::new_encrypted(&*encrypted_bytes, &key, false);
+        let configuration_with_wrong_key =
+            ZipBasedConfiguration::new_encrypted(&*encrypted_bytes, &[0; 32], false);
+
+        // Then
+        assert!(configuration.is_ok());
+        assert!(configuration_with_wrong_key.is_err());
+    }
 }
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+use sha2::{Digest, Sha256};
+
+use errors::*;
+
+/// Optional manifest of a model, mapping the paths of its files, relative to the model root, to
+/// their hex encoded SHA-256 checksum
+pub const CHECKSUMS_FILENAME: &str = "checksums.json";
+
+/// Checks the files listed in a checksums manifest, `read_file` returning the content of a file
+/// given its path relative to the model root
+pub fn verify_checksums<F>(manifest: &[u8], mut read_file: F) -> Result<()>
+where
+    F: FnMut(&str) -> Result<Vec<u8>>,
+{
+    let checksums: HashMap<String, String> = ::serde_json::from_slice(manifest)
+        .chain_err(|| ErrorKind::ConfigLoad(CHECKSUMS_FILENAME.into()))?;
+    let mut file_names = checksums.keys().collect::<Vec<_>>();
+    file_names.sort();
+    for file_name in file_names {
+        check_relative_path(file_name)?;
+        let bytes = read_file(file_name).chain_err(|| ErrorKind::ConfigLoad(file_name.clone()))?;
+        if sha256_hex(&bytes) != checksums[file_name].to_lowercase() {
+            bail!(ErrorKind::ChecksumMismatch(file_name.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Rejects the paths which could point outside of the model root, that is absolute paths and
+/// paths with `..` components
+fn check_relative_path(file_name: &str) -> Result<()> {
+    let is_inside_root = Path::new(file_name)
+        .components()
+        .all(|component| match component {
+            Component::Normal(_) | Component::CurDir => true,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => false,
+        });
+    if file_name.is_empty() || !is_inside_root {
+        bail!(
+            "Invalid path in {}, paths must be relative to the model root: {:?}",
+            CHECKSUMS_FILENAME,
+            file_name
+        );
+    }
+    Ok(())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_test_file(file_name: &str) -> Result<Vec<u8>> {
+        match file_name {
+            "hello.txt" => Ok(b"hello".to_vec()),
+            _ => bail!("Missing file"),
+        }
+    }
+
+    #[test]
+    fn verify_checksums_works() {
+        // Given
+        let manifest = br#"{
+            "hello.txt": "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824"
+        }"#;
+
+        // When
+        let result = verify_checksums(manifest, read_test_file);
+
+        // Then
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn verify_checksums_should_fail_with_corrupted_file() {
+        // Given
+        let manifest = br#"{
+            "hello.txt": "0000000000000000000000000000000000000000000000000000000000000000"
+        }"#;
+
+        // When
+        let result = verify_checksums(manifest, read_test_file);
+
+        // Then
+        match *result.unwrap_err().kind() {
+            ErrorKind::ChecksumMismatch(ref file_name) => assert_eq!("hello.txt", file_name),
+            ref kind => panic!("Expected a checksum mismatch, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn verify_checksums_should_fail_with_missing_file() {
+        // Given
+        let manifest = br#"{"missing.txt": "0000"}"#;
+
+        // When
+        let result = verify_checksums(manifest, read_test_file);
+
+        // Then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn verify_checksums_should_reject_paths_outside_of_model() {
+        // Given
+        let manifests: Vec<&[u8]> = vec![
+            br#"{"../hello.txt": "0000"}"#,
+            br#"{"resources/../../hello.txt": "0000"}"#,
+            br#"{"/etc/hello.txt": "0000"}"#,
+        ];
+
+        for manifest in manifests {
+            // When
+            let mut read_files = vec![];
+            let result = verify_checksums(manifest, |file_name| {
+                read_files.push(file_name.to_string());
+                read_test_file(file_name)
+            });
+
+            // Then
+            assert!(result.is_err());
+            assert!(read_files.is_empty());
+        }
+    }
+}
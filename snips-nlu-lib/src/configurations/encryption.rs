@@ -0,0 +1,55 @@
+use aes_gcm::Aes256Gcm;
+use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::aead::generic_array::GenericArray;
+
+use errors::*;
+
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+
+/// Decrypts data encrypted with AES-256-GCM, made of the 12 bytes nonce followed by the
+/// ciphertext and its authentication tag
+pub fn decrypt(data: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+    if key.len() != KEY_SIZE {
+        bail!("Decryption key must be {} bytes long", KEY_SIZE);
+    }
+    if data.len() < NONCE_SIZE {
+        bail!("Encrypted data is too short");
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_SIZE);
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(key));
+    cipher
+        .decrypt(GenericArray::from_slice(nonce), ciphertext)
+        .map_err(|_| "Cannot decrypt the model, the key is wrong or the data is corrupted".into())
+}
+
+#[cfg(test)]
+pub fn encrypt(data: &[u8], key: &[u8], nonce: &[u8]) -> Vec<u8> {
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(key));
+    let ciphertext = cipher
+        .encrypt(GenericArray::from_slice(nonce), data)
+        .unwrap();
+    let mut encrypted_data = nonce.to_vec();
+    encrypted_data.extend(ciphertext);
+    encrypted_data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decrypt_works() {
+        // Given
+        let key = [7; KEY_SIZE];
+        let encrypted_data = encrypt(b"trained assistant", &key, &[3; NONCE_SIZE]);
+
+        // When
+        let data = decrypt(&encrypted_data, &key);
+        let data_with_wrong_key = decrypt(&encrypted_data, &[8; KEY_SIZE]);
+
+        // Then
+        assert_eq!(b"trained assistant".to_vec(), data.unwrap());
+        assert!(data_with_wrong_key.is_err());
+    }
+}
@@ -1,14 +1,51 @@
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct IntentClassifierConfiguration {
     pub featurizer: Option<FeaturizerConfiguration>,
     pub intercept: Option<Vec<f32>>,
     pub coeffs: Option<Vec<Vec<f32>>>,
     pub intent_list: Vec<Option<String>>,
+    /// Calibration functions of the intents, in the order of the intent list
+    pub calibration: Option<Vec<CalibrationConfiguration>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum CalibrationConfiguration {
+    /// Platt scaling, which maps a probability `p` to `1 / (1 + exp(a * p + b))`
+    Platt { a: f32, b: f32 },
+    /// Isotonic regression, linearly interpolated between the thresholds
+    Isotonic {
+        x_thresholds: Vec<f32>,
+        y_thresholds: Vec<f32>,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EmbeddingIntentClassifierConfiguration {
+    pub language_code: String,
+    /// Path of the text file containing the pretrained word vectors
+    pub word_embeddings_path: String,
+    pub intercept: Vec<f32>,
+    pub coeffs: Vec<Vec<f32>>,
+    pub intent_list: Vec<Option<String>>,
+    /// Calibration functions of the intents, in the order of the intent list
+    pub calibration: Option<Vec<CalibrationConfiguration>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OnnxIntentClassifierConfiguration {
+    /// Path of the ONNX model, which takes the output of the featurizer as input and returns the
+    /// probabilities of the intents
+    pub model_path: String,
+    pub featurizer: FeaturizerConfiguration,
+    pub intent_list: Vec<Option<String>>,
+    /// Calibration functions of the intents, in the order of the intent list
+    pub calibration: Option<Vec<CalibrationConfiguration>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FeaturizerConfiguration {
     pub language_code: String,
     pub tfidf_vectorizer: TfIdfVectorizerConfiguration,
@@ -17,12 +54,12 @@ pub struct FeaturizerConfiguration {
     pub entity_utterances_to_feature_names: HashMap<String, Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FeaturizerConfigConfiguration {
     pub sublinear_tf: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TfIdfVectorizerConfiguration {
     pub idf_diag: Vec<f32>,
     pub vocab: HashMap<String, usize>,
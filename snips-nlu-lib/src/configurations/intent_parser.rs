@@ -1,17 +1,45 @@
 use std::collections::HashMap;
 
-use configurations::{IntentClassifierConfiguration, SlotFillerConfiguration};
+use configurations::{SlotFillerConfigConfiguration, SlotFillerConfiguration};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DeterministicParserConfiguration {
     pub language_code: String,
     pub patterns: HashMap<String, Vec<String>>,
     pub group_names_to_slot_names: HashMap<String, String>,
     pub slot_names_to_entities: HashMap<String, String>,
+    /// Patterns written with the rule syntax, compiled to regexes in addition to `patterns`
+    pub rules: Option<HashMap<String, Vec<String>>>,
+    /// Tags every builtin entity kind of the language in the inputs, instead of only the kinds
+    /// referenced by the slots of each intent
+    #[serde(default)]
+    pub tag_all_builtin_entities: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ExactMatchParserConfiguration {
+    pub language_code: String,
+    pub utterances: HashMap<String, Vec<Vec<UtteranceChunk>>>,
+    pub keywords: Option<HashMap<String, Vec<Vec<String>>>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UtteranceChunk {
+    pub text: String,
+    pub entity: Option<String>,
+    pub slot_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ProbabilisticParserConfiguration {
-    pub intent_classifier: IntentClassifierConfiguration,
+    /// Configuration of the intent classifier, whose backend is selected with its `unit_name`
+    pub intent_classifier: ::serde_json::Value,
     pub slot_fillers: HashMap<String, SlotFillerConfiguration>,
+    pub config: Option<ProbabilisticParserConfigConfiguration>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ProbabilisticParserConfigConfiguration {
+    /// Default settings of the slot fillers, which can be overridden for each intent
+    pub slot_filler_config: Option<SlotFillerConfigConfiguration>,
 }
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use configurations::ModelVersionConfiguration;
+use errors::*;
+
+/// Converts the JSON configuration of a model to the format of a more recent model version
+pub trait ModelMigration: Send + Sync {
+    fn migrate(&self, configuration: ::serde_json::Value) -> Result<::serde_json::Value>;
+}
+
+impl<F> ModelMigration for F
+where
+    F: Fn(::serde_json::Value) -> Result<::serde_json::Value> + Send + Sync,
+{
+    fn migrate(&self, configuration: ::serde_json::Value) -> Result<::serde_json::Value> {
+        self(configuration)
+    }
+}
+
+/// Migrations of the model formats, indexed by the model version they apply to
+#[derive(Default)]
+pub struct ModelMigrationRegistry {
+    migrations: HashMap<String, (String, Box<ModelMigration>)>,
+}
+
+impl ModelMigrationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a migration converting models of version `from_version` to `to_version`
+    pub fn register<M>(&mut self, from_version: &str, to_version: &str, migration: M)
+    where
+        M: ModelMigration + 'static,
+    {
+        self.migrations.insert(
+            from_version.to_string(),
+            (to_version.to_string(), Box::new(migration)),
+        );
+    }
+
+    /// Applies the migrations needed to bring the configuration to the current model version
+    pub fn migrate(&self, mut configuration: ::serde_json::Value) -> Result<::serde_json::Value> {
+        let expected_version = ::SnipsNluEngine::model_version();
+        let found_version = model_version(&configuration)?;
+        let mut version = found_version.clone();
+        let mut nb_migrations = 0;
+        while version != expected_version {
+            let &(ref to_version, ref migration) = match self.migrations.get(&version) {
+                Some(migration) if nb_migrations < self.migrations.len() => migration,
+                _ => bail!(ErrorKind::IncompatibleModelVersion(
+                    found_version,
+                    expected_version.to_string()
+                )),
+            };
+            configuration = migration
+                .migrate(configuration)
+                .chain_err(|| format!("Cannot migrate model from version {}", version))?;
+            configuration["model_version"] = ::serde_json::Value::String(to_version.clone());
+            version = to_version.clone();
+            nb_migrations += 1;
+        }
+        Ok(configuration)
+    }
+}
+
+fn model_version(configuration: &::serde_json::Value) -> Result<String> {
+    let version_configuration = ModelVersionConfiguration::deserialize(configuration)
+        .chain_err(|| "Model version is not properly defined")?;
+    Ok(version_configuration.model_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename_language(mut configuration: ::serde_json::Value) -> Result<::serde_json::Value> {
+        let language = ::std::mem::replace(&mut configuration["language"], json_null());
+        configuration["language_code"] = language;
+        Ok(configuration)
+    }
+
+    fn json_null() -> ::serde_json::Value {
+        ::serde_json::Value::Null
+    }
+
+    #[test]
+    fn migrate_should_apply_migrations_in_sequence() {
+        // Given
+        let mut registry = ModelMigrationRegistry::new();
+        registry.register("0.11.0", "0.12.0", rename_language);
+        registry.register(
+            "0.12.0",
+            ::SnipsNluEngine::model_version(),
+            |configuration: ::serde_json::Value| Ok(configuration),
+        );
+        let configuration =
+            ::serde_json::from_str(r#"{"model_version": "0.11.0", "language": "en"}"#).unwrap();
+
+        // When
+        let migrated_configuration = registry.migrate(configuration).unwrap();
+
+        // Then
+        assert_eq!(
+            Some(::SnipsNluEngine::model_version()),
+            migrated_configuration["model_version"].as_str()
+        );
+        assert_eq!(Some("en"), migrated_configuration["language_code"].as_str());
+    }
+
+    #[test]
+    fn migrate_should_fail_with_unknown_model_version() {
+        // Given
+        let registry = ModelMigrationRegistry::new();
+        let configuration = ::serde_json::from_str(r#"{"model_version": "0.1.0"}"#).unwrap();
+
+        // When
+        let result = registry.migrate(configuration);
+
+        // Then
+        match result {
+            Err(Error(ErrorKind::IncompatibleModelVersion(ref found, ref expected), _)) => {
+                assert_eq!("0.1.0", found);
+                assert_eq!(::SnipsNluEngine::model_version(), expected);
+            }
+            _ => panic!("Expected an IncompatibleModelVersion error"),
+        }
+    }
+}
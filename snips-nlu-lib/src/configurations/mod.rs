@@ -1,11 +1,20 @@
 pub mod assistant;
+pub mod checksums;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 pub mod intent_classifier;
 pub mod intent_parser;
+pub mod migration;
+pub mod model_format;
 pub mod nlu_engine;
 pub mod slot_filler;
+pub mod validation;
 
 pub use self::assistant::*;
 pub use self::intent_classifier::*;
 pub use self::intent_parser::*;
+pub use self::migration::*;
+pub use self::model_format::*;
 pub use self::nlu_engine::*;
 pub use self::slot_filler::*;
+pub use self::validation::*;
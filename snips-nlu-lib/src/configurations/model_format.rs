@@ -0,0 +1,156 @@
+use std::io::{Read, Write};
+
+use configurations::NluEngineConfiguration;
+use errors::*;
+
+/// Header written before the serialized configuration, followed by the format identifier
+const MAGIC: &[u8] = b"SNIPSNLU";
+
+/// Formats in which the configuration of an engine can be persisted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigurationFormat {
+    Json,
+    /// Compact binary format which is much faster to load than JSON
+    Cbor,
+}
+
+impl ConfigurationFormat {
+    fn identifier(&self) -> u8 {
+        match *self {
+            ConfigurationFormat::Json => 0,
+            ConfigurationFormat::Cbor => 1,
+        }
+    }
+
+    fn from_identifier(identifier: u8) -> Result<Self> {
+        match identifier {
+            0 => Ok(ConfigurationFormat::Json),
+            1 => Ok(ConfigurationFormat::Cbor),
+            _ => bail!("Unknown configuration format identifier: {}", identifier),
+        }
+    }
+}
+
+/// Returns the format of a serialized configuration along with its content, configurations
+/// without header being plain JSON
+fn split_header(bytes: &[u8]) -> Result<(ConfigurationFormat, &[u8])> {
+    if !bytes.starts_with(MAGIC) {
+        return Ok((ConfigurationFormat::Json, bytes));
+    }
+    let format_identifier = *bytes
+        .get(MAGIC.len())
+        .ok_or_else(|| "Configuration format identifier is missing")?;
+    let format = ConfigurationFormat::from_identifier(format_identifier)?;
+    Ok((format, &bytes[MAGIC.len() + 1..]))
+}
+
+/// Decodes a configuration written by `to_writer`, or a plain JSON configuration, as a JSON
+/// value, so that it can be migrated before being deserialized
+pub fn configuration_value(bytes: &[u8]) -> Result<::serde_json::Value> {
+    match split_header(bytes)? {
+        (ConfigurationFormat::Json, content) => Ok(::serde_json::from_slice(content)?),
+        (ConfigurationFormat::Cbor, content) => Ok(::serde_cbor::from_slice(content)?),
+    }
+}
+
+impl NluEngineConfiguration {
+    /// Reads a configuration written by `to_writer`, plain JSON configurations being also
+    /// accepted
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        match split_header(&bytes)? {
+            (ConfigurationFormat::Json, content) => Ok(::serde_json::from_slice(content)?),
+            (ConfigurationFormat::Cbor, content) => Ok(::serde_cbor::from_slice(content)?),
+        }
+    }
+
+    /// Writes the configuration in the given format, preceded by a header identifying it
+    pub fn to_writer<W: Write>(&self, mut writer: W, format: ConfigurationFormat) -> Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[format.identifier()])?;
+        match format {
+            ConfigurationFormat::Json => ::serde_json::to_writer(writer, self)?,
+            ConfigurationFormat::Cbor => ::serde_cbor::to_writer(&mut writer, self)?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutils::parse_json;
+
+    fn test_configuration() -> NluEngineConfiguration {
+        parse_json("tests/configurations/trained_assistant.json")
+    }
+
+    fn round_trip(format: ConfigurationFormat) -> NluEngineConfiguration {
+        let mut bytes = vec![];
+        test_configuration().to_writer(&mut bytes, format).unwrap();
+        NluEngineConfiguration::from_reader(&*bytes).unwrap()
+    }
+
+    #[test]
+    fn cbor_configuration_should_round_trip() {
+        // When
+        let configuration = round_trip(ConfigurationFormat::Cbor);
+
+        // Then
+        let expected_configuration = test_configuration();
+        assert_eq!(expected_configuration.model_version, configuration.model_version);
+        assert_eq!(expected_configuration.intent_parsers, configuration.intent_parsers);
+    }
+
+    #[test]
+    fn json_configuration_should_round_trip() {
+        // When
+        let configuration = round_trip(ConfigurationFormat::Json);
+
+        // Then
+        let expected_configuration = test_configuration();
+        assert_eq!(expected_configuration.intent_parsers, configuration.intent_parsers);
+    }
+
+    #[test]
+    fn from_reader_should_accept_plain_json() {
+        // Given
+        let bytes = ::serde_json::to_vec(&test_configuration()).unwrap();
+
+        // When
+        let configuration = NluEngineConfiguration::from_reader(&*bytes);
+
+        // Then
+        assert!(configuration.is_ok());
+    }
+
+    #[test]
+    fn configuration_value_should_decode_cbor_configuration() {
+        // Given
+        let mut bytes = vec![];
+        test_configuration()
+            .to_writer(&mut bytes, ConfigurationFormat::Cbor)
+            .unwrap();
+
+        // When
+        let value = configuration_value(&bytes).unwrap();
+
+        // Then
+        let expected_value = ::serde_json::to_value(test_configuration()).unwrap();
+        assert_eq!(expected_value["model_version"], value["model_version"]);
+        assert_eq!(expected_value["intent_parsers"], value["intent_parsers"]);
+    }
+
+    #[test]
+    fn from_reader_should_fail_with_unknown_format() {
+        // Given
+        let bytes = b"SNIPSNLU\x07{}";
+
+        // When
+        let configuration = NluEngineConfiguration::from_reader(&bytes[..]);
+
+        // Then
+        assert!(configuration.is_err());
+    }
+}
@@ -1,8 +1,16 @@
 use std::collections::HashMap;
 
+use resource_reader::ResourceReader;
+use units::UnitSystem;
+
 pub trait NluEngineConfigurationConvertible {
     fn nlu_engine_configuration(&self) -> &NluEngineConfiguration;
     fn into_nlu_engine_configuration(self) -> NluEngineConfiguration;
+
+    /// Returns the reader of the resource files which the configuration refers to
+    fn resource_reader(&self) -> ResourceReader {
+        ResourceReader::default()
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -10,25 +18,172 @@ pub struct ModelVersionConfiguration {
     pub model_version: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct NluEngineConfiguration {
     pub dataset_metadata: DatasetMetadata,
     pub intent_parsers: Vec<::serde_json::Value>,
+    /// Configurations of intent parsers provided through an `IntentParserRegistry`, which are
+    /// tried after the trained intent parsers
+    pub custom_parsers: Option<Vec<::serde_json::Value>>,
+    /// Strategy used to combine the results of the intent parsers, `first_match` by default. The
+    /// probability of the returned intent is the combined score when the strategy is a vote.
+    pub arbitration_strategy: Option<ArbitrationStrategy>,
+    /// Factor applied to the probability of the None intent in the probabilistic intent parser,
+    /// values above 1 reducing false accepts and values below 1 reducing false rejects
+    pub none_sensitivity: Option<f32>,
+    /// Minimum probabilities of the intents, an intent found by a parser with a lower probability
+    /// being ignored so that the input falls through to the next parser
+    pub intent_thresholds: Option<HashMap<String, f32>>,
+    /// Name of the tokenizer provided through a `TokenizerRegistry` which is used by all the
+    /// intent parsers, such as the builtin `cjk` tokenizer, the tokenizer of `snips-nlu-utils`
+    /// being used by default
+    pub tokenizer: Option<String>,
+    /// Whether the emojis glued to words are split into tokens of their own, `false` by default
+    /// as it changes the tokenization of the models trained without it
+    pub split_emojis: Option<bool>,
+    /// Unicode normalization applied to the inputs before they are parsed, like a
+    /// `normalize_unicode` step preceding the `preprocessing` steps
+    pub unicode_normalization: Option<UnicodeNormalization>,
+    /// Steps applied in order to the inputs before they are parsed, the ranges and raw values of
+    /// the slots referring to the original inputs
+    pub preprocessing: Option<Vec<PreprocessingStep>>,
+    /// Whether the contractions of the language are expanded in the inputs, like an
+    /// `expand_contractions` step following the `preprocessing` steps
+    pub expand_contractions: Option<bool>,
+    /// Spell correction of the inputs, like a `correct_spelling` step following the
+    /// `preprocessing` steps and the expansion of the contractions
+    pub spell_correction: Option<SpellCorrectionConfiguration>,
+    /// Textual aliases of emojis, like a `replace_emojis` step applied after all the other steps
+    pub emoji_aliases: Option<HashMap<String, String>>,
+    /// Maximum number of chars of the inputs, along with the handling of longer inputs, which
+    /// protects the intent parsers against very long inputs
+    pub input_length_limit: Option<InputLengthLimit>,
+    /// Slots of each intent which, when missing from an input parsed in a dialogue, are filled
+    /// with the slots of the same name found in the previous turns, see `DialogueContext`
+    pub carry_over_slots: Option<HashMap<String, Vec<String>>>,
+    /// Unit system in which the temperatures of the builtin slots are returned, the units spoken
+    /// by the user being kept by default
+    pub unit_system: Option<UnitSystem>,
     pub model_version: String,
     pub training_package_version: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArbitrationStrategy {
+    /// The first intent parser, in the configuration order, which finds an intent is used
+    FirstMatch,
+    /// The intent found with the highest probability is used
+    BestConfidence,
+    /// Each parser votes for the intent it found with its probability multiplied by its weight,
+    /// parsers without weight having a weight of 1
+    WeightedVote { weights: Vec<f32> },
+    /// The intent is selected as with `WeightedVote`, except that parsers vote with the
+    /// probabilities of all the intents rather than only their best one, and the slots of all
+    /// the parsers which voted for it are merged, the slots of the parsers which contributed the
+    /// most to the vote taking precedence over overlapping slots
+    Ensemble { weights: Vec<f32> },
+}
+
+impl Default for ArbitrationStrategy {
+    fn default() -> Self {
+        ArbitrationStrategy::FirstMatch
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightsQuantization {
+    /// Weights are stored as half precision floats
+    F16,
+    /// Weights are stored as 8 bits integers, scaled by the largest weight of each row,
+    /// i.e. of each class of a logistic regression and of each attribute or previous label of a CRF
+    Int8,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnicodeNormalization {
+    /// Canonical composition, letters followed by combining accents being composed
+    Nfc,
+    /// Compatibility composition, which also replaces ligatures, full-width forms and other
+    /// compatibility characters by their usual equivalents
+    Nfkc,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreprocessingStep {
+    /// Unicode normalization of the input
+    NormalizeUnicode(UnicodeNormalization),
+    Lowercase,
+    /// Removes the accents and other diacritics
+    StripAccents,
+    /// Replaces sequences of whitespaces by a single space and trims the input
+    CollapseWhitespace,
+    /// Replaces the matches of a regex, the replacement possibly referring to the groups of the
+    /// regex such as `$1`
+    RegexReplacement { pattern: String, replacement: String },
+    /// Expands the contractions of the language, such as "what's" or "j'aime", which requires
+    /// the intent parsers to be trained on expanded utterances
+    ExpandContractions,
+    /// Corrects the spelling of the words against the words of the intent parsers and the
+    /// values of the custom entities, the builtin entities being left unchanged
+    CorrectSpelling(SpellCorrectionConfiguration),
+    /// Replaces the emojis by textual aliases, such as `:thumbsup:` for 👍, so that they can be
+    /// used as values of custom entities
+    ReplaceEmojis(HashMap<String, String>),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct InputLengthLimit {
+    pub max_length: usize,
+    pub policy: InputLengthPolicy,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InputLengthPolicy {
+    /// Longer inputs are rejected with an `InputTooLong` error
+    Reject,
+    /// Longer inputs are truncated after their last token which ends within the limit
+    Truncate,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SpellCorrectionConfiguration {
+    /// Maximum number of inserted, deleted, substituted or swapped chars between a word and its
+    /// correction
+    pub max_edit_distance: usize,
+    /// Minimum number of chars of the corrected words, shorter words being left unchanged
+    pub min_word_length: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DatasetMetadata {
     pub language_code: String,
+    /// Languages in which builtin entities are extracted in addition to the model language
+    pub additional_builtin_entities_languages: Option<Vec<String>>,
+    /// Languages tried in order, for builtin entities, on the parts of the inputs where no builtin
+    /// entity is found in the model language and the additional languages
+    pub fallback_builtin_entities_languages: Option<Vec<String>>,
     pub entities: HashMap<String, Entity>,
     pub slot_name_mappings: HashMap<String, HashMap<String, String>>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Entity {
     pub automatically_extensible: bool,
+    /// Gazetteer of the entity, empty when it is stored in `utterances_file`
+    #[serde(default)]
     pub utterances: HashMap<String, String>,
+    /// Path of the JSON file storing the gazetteer, which is memory-mapped and loaded the first
+    /// time the entity is used
+    pub utterances_file: Option<String>,
+    /// Number of values of the gazetteer, synonyms included, which is counted when the engine is
+    /// loaded for the gazetteers stored inline, and which the models must provide along with
+    /// `utterances_file` for it to be known without loading the file
+    pub nb_values: Option<usize>,
 }
 
 impl NluEngineConfigurationConvertible for NluEngineConfiguration {
@@ -1,22 +1,106 @@
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SlotFillerConfiguration {
     pub language_code: String,
     pub intent: String,
     pub slot_name_mapping: HashMap<String, String>,
+    /// Base64 encoded CRF model, empty when the model is stored in `crf_model_file`
+    #[serde(default)]
     pub crf_model_data: String,
-    pub config: SlotFillerConfigConfiguration,
+    /// Path of the file storing the CRF model, which is memory-mapped and loaded the first time
+    /// the slot filler is used
+    pub crf_model_file: Option<String>,
+    /// Settings of this slot filler, the `slot_filler_config` of the probabilistic parser being
+    /// used when they are not defined
+    pub config: Option<SlotFillerConfigConfiguration>,
+    /// Decodes the CRF labels with a beam search enforcing constraints on the slots, instead of
+    /// the Viterbi algorithm
+    pub beam_search: Option<BeamSearchConfiguration>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BeamSearchConfiguration {
+    pub beam_size: usize,
+    pub constraints: Vec<SlotConstraint>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SlotConstraint {
+    /// At most `max_count` slots named `slot_name` can be extracted from an utterance
+    MaxCount { slot_name: String, max_count: usize },
+    /// Slot `slot_name` can only be extracted along with slot `required_slot_name`
+    Requires {
+        slot_name: String,
+        required_slot_name: String,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SlotFillerConfigConfiguration {
-    pub tagging_scheme: u8,
+    pub tagging_scheme: TaggingSchemeConfiguration,
     pub exhaustive_permutations_threshold: usize,
     pub feature_factory_configs: Vec<FeatureFactory>,
+    /// Resolution of the builtin entities overlapping the custom slots tagged by the CRF
+    #[serde(default)]
+    pub overlapping_slots_policy: OverlappingSlotsPolicy,
+    /// Assignment of the builtin entities to the slots when their entity is used by several
+    /// slots of the intent
+    #[serde(default)]
+    pub builtin_slots_disambiguation: BuiltinSlotsDisambiguation,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlappingSlotsPolicy {
+    /// The custom slot is kept and the builtin entity discarded
+    PreferCustom,
+    /// The longest of the custom slot and the builtin entity is kept, the custom slot being kept
+    /// when they have the same length
+    LongestSpan,
+    /// The builtin entity is kept when tagging it as one of its slots gives a more probable
+    /// sequence of tags than the custom slot
+    HighestConfidence,
+}
+
+impl Default for OverlappingSlotsPolicy {
+    fn default() -> Self {
+        OverlappingSlotsPolicy::PreferCustom
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BuiltinSlotsDisambiguation {
+    /// The entities are assigned to the slots, or left out of them, so that the sequence of tags
+    /// is the most probable
+    MostProbable,
+    /// The entities are assigned to the slots by order of appearance, the slots being taken in
+    /// the order of `slot_names` followed by the slots which are not listed, and the entities in
+    /// excess being left out of the slots
+    Positional { slot_names: Vec<String> },
+    /// The entities are left out of the slots rather than assigned to one of them by guessing,
+    /// and are reported in `ParseExplanation::ambiguous_builtin_entities`
+    Ambiguous,
+}
+
+impl Default for BuiltinSlotsDisambiguation {
+    fn default() -> Self {
+        BuiltinSlotsDisambiguation::MostProbable
+    }
+}
+
+/// Tagging scheme of the CRF labels, given either by its identifier (0 for IO, 1 for BIO and 2
+/// for BILOU) or by its name, as in the models trained by recent versions of the Python library
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TaggingSchemeConfiguration {
+    Identifier(u8),
+    Name(String),
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FeatureFactory {
     pub factory_name: String,
     pub offsets: Vec<i32>,
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use configurations::NluEngineConfiguration;
+use snips_nlu_ontology::{BuiltinEntityKind, Language};
+
+/// Problem found in a configuration by `NluEngineConfiguration::validate`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigurationDiagnostic {
+    /// The language of the configuration is not supported
+    UnsupportedLanguage { language_code: String },
+    /// The entity of a slot is neither a custom entity of the dataset nor a builtin entity
+    UnknownSlotEntity {
+        intent_name: String,
+        slot_name: String,
+        entity_name: String,
+    },
+    /// An intent of the slot name mappings is not handled by any intent parser
+    UnknownIntent { intent_name: String },
+    /// A custom entity has no utterance
+    EmptyEntity { entity_name: String },
+}
+
+impl fmt::Display for ConfigurationDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigurationDiagnostic::UnsupportedLanguage { ref language_code } => {
+                write!(f, "Unsupported language: '{}'", language_code)
+            }
+            ConfigurationDiagnostic::UnknownSlotEntity {
+                ref intent_name,
+                ref slot_name,
+                ref entity_name,
+            } => write!(
+                f,
+                "Unknown entity '{}' of slot '{}' in intent '{}'",
+                entity_name, slot_name, intent_name
+            ),
+            ConfigurationDiagnostic::UnknownIntent { ref intent_name } => {
+                write!(f, "Intent '{}' is not handled by any intent parser", intent_name)
+            }
+            ConfigurationDiagnostic::EmptyEntity { ref entity_name } => {
+                write!(f, "Entity '{}' has no utterance", entity_name)
+            }
+        }
+    }
+}
+
+impl NluEngineConfiguration {
+    /// Checks the consistency of the configuration, returning all the problems found rather than
+    /// failing on the first one when the engine is built
+    pub fn validate(&self) -> Vec<ConfigurationDiagnostic> {
+        let mut diagnostics = vec![];
+        let metadata = &self.dataset_metadata;
+        if Language::from_str(&metadata.language_code).is_err() {
+            diagnostics.push(ConfigurationDiagnostic::UnsupportedLanguage {
+                language_code: metadata.language_code.clone(),
+            });
+        }
+
+        let mut entity_names = metadata.entities.keys().collect::<Vec<_>>();
+        entity_names.sort();
+        for entity_name in entity_names {
+            let entity = &metadata.entities[entity_name];
+            if entity.utterances.is_empty() && entity.utterances_file.is_none() {
+                diagnostics.push(ConfigurationDiagnostic::EmptyEntity {
+                    entity_name: entity_name.clone(),
+                });
+            }
+        }
+
+        let parsers_intents = self.intent_parsers
+            .iter()
+            .map(get_parser_intents)
+            .collect::<Option<Vec<_>>>();
+        let mut intent_names = metadata.slot_name_mappings.keys().collect::<Vec<_>>();
+        intent_names.sort();
+        for intent_name in intent_names {
+            if let Some(parsers_intents) = parsers_intents.as_ref() {
+                // Intents handled by custom parsers can't be checked
+                if self.custom_parsers.is_none()
+                    && !parsers_intents
+                        .iter()
+                        .any(|intents| intents.contains(intent_name))
+                {
+                    diagnostics.push(ConfigurationDiagnostic::UnknownIntent {
+                        intent_name: intent_name.clone(),
+                    });
+                }
+            }
+            let slot_name_mapping = &metadata.slot_name_mappings[intent_name];
+            let mut slot_names = slot_name_mapping.keys().collect::<Vec<_>>();
+            slot_names.sort();
+            for slot_name in slot_names {
+                let entity_name = &slot_name_mapping[slot_name];
+                if BuiltinEntityKind::from_identifier(entity_name).is_err()
+                    && !metadata.entities.contains_key(entity_name)
+                {
+                    diagnostics.push(ConfigurationDiagnostic::UnknownSlotEntity {
+                        intent_name: intent_name.clone(),
+                        slot_name: slot_name.clone(),
+                        entity_name: entity_name.clone(),
+                    });
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+/// Returns the intents handled by a trained intent parser, or `None` when they can't be found in
+/// its configuration
+fn get_parser_intents(parser_config: &::serde_json::Value) -> Option<HashSet<String>> {
+    let intents_keys: &[&str] = match parser_config["unit_name"].as_str() {
+        Some("deterministic_intent_parser") => &["patterns", "rules"],
+        Some("exact_match_intent_parser") => &["utterances"],
+        Some("probabilistic_intent_parser") => &["slot_fillers"],
+        _ => return None,
+    };
+    let mut intents = HashSet::new();
+    for key in intents_keys {
+        if let Some(intents_map) = parser_config[*key].as_object() {
+            intents.extend(intents_map.keys().cloned());
+        }
+    }
+    Some(intents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use configurations::Entity;
+    use testutils::parse_json;
+
+    #[test]
+    fn validate_should_not_find_problems_in_valid_configuration() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+
+        // When
+        let diagnostics = configuration.validate();
+
+        // Then
+        assert_eq!(Vec::<ConfigurationDiagnostic>::new(), diagnostics);
+    }
+
+    #[test]
+    fn validate_should_find_problems() {
+        // Given
+        let mut configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        configuration.dataset_metadata.language_code = "xx".to_string();
+        configuration.dataset_metadata.entities.insert(
+            "Size".to_string(),
+            Entity {
+                automatically_extensible: false,
+                utterances: hashmap!{},
+                utterances_file: None,
+                nb_values: None,
+            },
+        );
+        configuration.dataset_metadata.slot_name_mappings.insert(
+            "MakeSoup".to_string(),
+            hashmap! { "soup_type".to_string() => "SoupType".to_string() },
+        );
+
+        // When
+        let diagnostics = configuration.validate();
+
+        // Then
+        let expected_diagnostics = vec![
+            ConfigurationDiagnostic::UnsupportedLanguage {
+                language_code: "xx".to_string(),
+            },
+            ConfigurationDiagnostic::EmptyEntity {
+                entity_name: "Size".to_string(),
+            },
+            ConfigurationDiagnostic::UnknownIntent {
+                intent_name: "MakeSoup".to_string(),
+            },
+            ConfigurationDiagnostic::UnknownSlotEntity {
+                intent_name: "MakeSoup".to_string(),
+                slot_name: "soup_type".to_string(),
+                entity_name: "SoupType".to_string(),
+            },
+        ];
+        assert_eq!(expected_diagnostics, diagnostics);
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use snips_nlu_ontology::Slot;
+
+/// Slots filled in the previous turns of a dialogue, which the caller threads between the parses
+/// of the turns, see `SnipsNluEngine::parse_in_context`
+///
+/// The context remembers the last slot of each slot name, so that a follow-up input such as
+/// "and in the bedroom?" inherits the carry-over slots of its intent from the previous turns.
+#[derive(Debug, Clone, Default)]
+pub struct DialogueContext {
+    /// Last slot of each slot name, along with the turn in which it was filled
+    slots: HashMap<String, (Slot, usize)>,
+    turn: usize,
+    max_turns: Option<usize>,
+}
+
+impl DialogueContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a context which forgets the slots which were not filled in the last `max_turns`
+    /// turns
+    pub fn with_max_turns(max_turns: usize) -> Self {
+        Self {
+            max_turns: Some(max_turns),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the last slot filled with this slot name, if any
+    pub fn slot(&self, slot_name: &str) -> Option<&Slot> {
+        self.slots.get(slot_name).map(|&(ref slot, _)| slot)
+    }
+
+    /// Records the slots of a new turn of the dialogue
+    pub fn add_turn(&mut self, slots: &[Slot]) {
+        self.turn += 1;
+        for slot in slots {
+            self.slots
+                .insert(slot.slot_name.clone(), (slot.clone(), self.turn));
+        }
+        if let Some(max_turns) = self.max_turns {
+            let turn = self.turn;
+            self.slots
+                .retain(|_, &mut (_, slot_turn)| turn - slot_turn < max_turns);
+        }
+    }
+
+    /// Forgets the slots of the previous turns, for instance when a new dialogue starts
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.turn = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(raw_value: &str, slot_name: &str) -> Slot {
+        Slot::new_custom(
+            raw_value.to_string(),
+            0..raw_value.chars().count(),
+            slot_name.to_string(),
+            slot_name.to_string(),
+        )
+    }
+
+    #[test]
+    fn add_turn_should_remember_last_slots() {
+        // Given
+        let mut context = DialogueContext::new();
+
+        // When
+        context.add_turn(&[slot("off", "action"), slot("kitchen", "room")]);
+        context.add_turn(&[slot("bedroom", "room")]);
+        context.add_turn(&[]);
+
+        // Then
+        assert_eq!(Some(&slot("off", "action")), context.slot("action"));
+        assert_eq!(Some(&slot("bedroom", "room")), context.slot("room"));
+        assert_eq!(None, context.slot("brightness"));
+    }
+
+    #[test]
+    fn add_turn_should_forget_old_slots() {
+        // Given
+        let mut context = DialogueContext::with_max_turns(2);
+
+        // When
+        context.add_turn(&[slot("off", "action"), slot("kitchen", "room")]);
+        context.add_turn(&[slot("bedroom", "room")]);
+        context.add_turn(&[]);
+
+        // Then
+        assert_eq!(None, context.slot("action"));
+        assert_eq!(Some(&slot("bedroom", "room")), context.slot("room"));
+    }
+
+    #[test]
+    fn clear_should_restart_the_turns() {
+        // Given
+        let mut context = DialogueContext::with_max_turns(2);
+        context.add_turn(&[slot("off", "action")]);
+        context.add_turn(&[slot("kitchen", "room")]);
+
+        // When
+        context.clear();
+        context.add_turn(&[slot("on", "action")]);
+
+        // Then
+        assert_eq!(None, context.slot("room"));
+        assert_eq!(Some(&slot("on", "action")), context.slot("action"));
+        assert_eq!(1, context.turn);
+    }
+}
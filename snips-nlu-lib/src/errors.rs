@@ -5,15 +5,14 @@ error_chain! {
 
     foreign_links {
         Io(::std::io::Error);
-        NdArray(::ndarray::ShapeError);
         Csv(::csv::Error);
         Zip(::zip::result::ZipError);
         Regex(::regex::Error);
-        Crfsuite(::crfsuite::Error);
         Base64(::base64::DecodeError);
         Utf8(::std::string::FromUtf8Error);
         PackedResources(::resources_packed::Error);
         SerdeJson(::serde_json::Error);
+        SerdeCbor(::serde_cbor::error::Error);
     }
 
     errors {
@@ -22,10 +21,36 @@ error_chain! {
             display("Unable to read file `{}`", path)
         }
 
+        /// Deprecated, model versions mismatches are reported as `IncompatibleModelVersion`
         WrongModelVersion(model_version: String) {
             description("Model version doesn't match")
             display("Given model version {} doesn't match. Expected model version {}", model_version, ::SnipsNluEngine::model_version())
         }
+
+        IncompatibleModelVersion(found: String, expected: String) {
+            description("Model version is not compatible")
+            display("Given model version {} is not compatible. Expected model version {}", found, expected)
+        }
+
+        ChecksumMismatch(file_name: String) {
+            description("Model file is corrupted")
+            display("Checksum of file `{}` does not match the model manifest", file_name)
+        }
+
+        WarmUpFailed(failures: Vec<String>) {
+            description("Engine warm-up failed")
+            display("Engine warm-up failed: {}", failures.join(", "))
+        }
+
+        UnknownIntents(intent_names: Vec<String>) {
+            description("Unknown intents in the intents filter")
+            display("Unknown intents in the intents filter: {}", intent_names.join(", "))
+        }
+
+        InputTooLong(length: usize, max_length: usize) {
+            description("Input is too long")
+            display("Input of {} chars exceeds the maximum length of {} chars", length, max_length)
+        }
     }
 }
 
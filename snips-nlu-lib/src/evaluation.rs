@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+
+use errors::*;
+use nlu_engine::SnipsNluEngine;
+use training::{Dataset, DatasetUtterance};
+
+/// Counts of a classification, from which its precision, recall and F1 score are derived
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Metrics {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+}
+
+impl Metrics {
+    pub fn precision(&self) -> f32 {
+        ratio(self.true_positives, self.true_positives + self.false_positives)
+    }
+
+    pub fn recall(&self) -> f32 {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+
+    pub fn f1(&self) -> f32 {
+        let precision = self.precision();
+        let recall = self.recall();
+        if precision + recall == 0. {
+            0.
+        } else {
+            2. * precision * recall / (precision + recall)
+        }
+    }
+}
+
+/// Metrics of an intent, the slots being only evaluated on the utterances whose intent is
+/// correctly found
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IntentMetrics {
+    pub intent: Metrics,
+    /// Metrics of the slots, indexed by slot name
+    pub slots: HashMap<String, Metrics>,
+}
+
+/// Number of utterances of each intent classified in each intent, the last row and column
+/// standing for the None intent
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfusionMatrix {
+    pub intents: Vec<Option<String>>,
+    /// Rows are indexed by the expected intent and columns by the predicted intent
+    pub matrix: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluationMetrics {
+    pub nb_utterances: usize,
+    /// Ratio of utterances whose intent is correctly found
+    pub intent_accuracy: f32,
+    /// Metrics of the intents, indexed by intent name
+    pub intents: HashMap<String, IntentMetrics>,
+    pub confusion_matrix: ConfusionMatrix,
+}
+
+/// Computes the metrics of engines trained natively on `nb_folds` splits of the dataset, each
+/// engine being evaluated on the utterances left out of its training split
+pub fn compute_cross_val_metrics(dataset: &Dataset, nb_folds: usize) -> Result<EvaluationMetrics> {
+    compute_cross_val_metrics_with_engine(dataset, nb_folds, |train_dataset| {
+        SnipsNluEngine::new(train_dataset.train()?)
+    })
+}
+
+/// Computes the cross-validation metrics of the engines built by `build_engine` from the
+/// training splits of the dataset
+///
+/// The utterances of each intent are assigned to the folds in turn, so that the intents keep the
+/// same proportions in all the folds.
+pub fn compute_cross_val_metrics_with_engine<F>(
+    dataset: &Dataset,
+    nb_folds: usize,
+    build_engine: F,
+) -> Result<EvaluationMetrics>
+where
+    F: Fn(&Dataset) -> Result<SnipsNluEngine>,
+{
+    if nb_folds < 2 {
+        bail!("Cross-validation requires at least 2 folds, found {}", nb_folds);
+    }
+    let intent_names = dataset.intent_names();
+    let mut evaluator = Evaluator::new(&intent_names);
+    for fold in 0..nb_folds {
+        let mut train_dataset = dataset.clone();
+        let mut test_utterances: Vec<(&String, &DatasetUtterance)> = vec![];
+        for intent_name in &intent_names {
+            let utterances = &dataset.intents[*intent_name].utterances;
+            let (train_utterances, fold_utterances): (Vec<_>, Vec<_>) = utterances
+                .iter()
+                .enumerate()
+                .partition(|&(index, _)| index % nb_folds != fold);
+            if let Some(intent) = train_dataset.intents.get_mut(*intent_name) {
+                intent.utterances = train_utterances
+                    .into_iter()
+                    .map(|(_, utterance)| utterance.clone())
+                    .collect();
+            }
+            test_utterances.extend(
+                fold_utterances
+                    .into_iter()
+                    .map(|(_, utterance)| (*intent_name, utterance)),
+            );
+        }
+        if test_utterances.is_empty() {
+            continue;
+        }
+        let engine = build_engine(&train_dataset)?;
+        for (intent_name, utterance) in test_utterances {
+            evaluator.evaluate(&engine, intent_name, utterance)?;
+        }
+    }
+    Ok(evaluator.metrics())
+}
+
+struct Evaluator {
+    intent_list: Vec<Option<String>>,
+    confusion_matrix: Vec<Vec<usize>>,
+    intents: HashMap<String, IntentMetrics>,
+}
+
+impl Evaluator {
+    fn new(intent_names: &[&String]) -> Self {
+        let mut intent_list: Vec<Option<String>> = intent_names
+            .iter()
+            .map(|intent_name| Some(intent_name.to_string()))
+            .collect();
+        intent_list.push(None);
+        let nb_intents = intent_list.len();
+        Self {
+            intent_list,
+            confusion_matrix: vec![vec![0; nb_intents]; nb_intents],
+            intents: intent_names
+                .iter()
+                .map(|intent_name| (intent_name.to_string(), IntentMetrics::default()))
+                .collect(),
+        }
+    }
+
+    fn intent_index(&self, intent_name: Option<&String>) -> usize {
+        self.intent_list
+            .iter()
+            .position(|intent| intent.as_ref() == intent_name)
+            .unwrap_or(self.intent_list.len() - 1)
+    }
+
+    fn evaluate(
+        &mut self,
+        engine: &SnipsNluEngine,
+        intent_name: &str,
+        utterance: &DatasetUtterance,
+    ) -> Result<()> {
+        let result = engine.parse(&utterance.text(), None)?;
+        let predicted_intent = result.intent.map(|intent| intent.intent_name);
+        let expected_index = self.intent_index(Some(&intent_name.to_string()));
+        let predicted_index = self.intent_index(predicted_intent.as_ref());
+        self.confusion_matrix[expected_index][predicted_index] += 1;
+
+        if expected_index == predicted_index {
+            let intent_metrics = self.intents.get_mut(intent_name).unwrap();
+            intent_metrics.intent.true_positives += 1;
+            let mut expected_slots: Vec<(&String, &String)> = utterance
+                .data
+                .iter()
+                .filter_map(|chunk| chunk.slot_name.as_ref().map(|name| (name, &chunk.text)))
+                .collect();
+            for slot in result.slots.unwrap_or_else(Vec::new) {
+                let position = expected_slots.iter().position(|&(slot_name, value)| {
+                    *slot_name == slot.slot_name && *value == slot.raw_value
+                });
+                let slot_metrics = intent_metrics
+                    .slots
+                    .entry(slot.slot_name.clone())
+                    .or_insert_with(Metrics::default);
+                if let Some(position) = position {
+                    expected_slots.remove(position);
+                    slot_metrics.true_positives += 1;
+                } else {
+                    slot_metrics.false_positives += 1;
+                }
+            }
+            for (slot_name, _) in expected_slots {
+                intent_metrics
+                    .slots
+                    .entry(slot_name.clone())
+                    .or_insert_with(Metrics::default)
+                    .false_negatives += 1;
+            }
+        } else {
+            self.intents
+                .get_mut(intent_name)
+                .unwrap()
+                .intent
+                .false_negatives += 1;
+            if let Some(predicted_intent) = predicted_intent {
+                if let Some(predicted_metrics) = self.intents.get_mut(&predicted_intent) {
+                    predicted_metrics.intent.false_positives += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn metrics(self) -> EvaluationMetrics {
+        let nb_utterances: usize = self.confusion_matrix
+            .iter()
+            .map(|row| row.iter().sum::<usize>())
+            .sum();
+        let nb_correct_utterances: usize = (0..self.intent_list.len())
+            .map(|index| self.confusion_matrix[index][index])
+            .sum();
+        EvaluationMetrics {
+            nb_utterances,
+            intent_accuracy: ratio(nb_correct_utterances, nb_utterances),
+            intents: self.intents,
+            confusion_matrix: ConfusionMatrix {
+                intents: self.intent_list,
+                matrix: self.confusion_matrix,
+            },
+        }
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f32 {
+    if denominator == 0 {
+        0.
+    } else {
+        numerator as f32 / denominator as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+    use testutils::epsilon_eq;
+    use utils::file_path;
+
+    #[test]
+    fn metrics_works() {
+        // Given
+        let metrics = Metrics {
+            true_positives: 6,
+            false_positives: 2,
+            false_negatives: 4,
+        };
+
+        // When
+        let precision = metrics.precision();
+        let recall = metrics.recall();
+        let f1 = metrics.f1();
+
+        // Then
+        assert!(epsilon_eq(0.75, precision, 1e-6));
+        assert!(epsilon_eq(0.6, recall, 1e-6));
+        assert!(epsilon_eq(2. / 3., f1, 1e-6));
+    }
+
+    #[test]
+    fn compute_cross_val_metrics_works() {
+        // Given
+        let path = file_path("tests/datasets/beverage_dataset.json");
+        let dataset = Dataset::from_reader(File::open(path).unwrap()).unwrap();
+
+        // When
+        let metrics = compute_cross_val_metrics(&dataset, 5).unwrap();
+
+        // Then
+        assert_eq!(10, metrics.nb_utterances);
+        assert_eq!(
+            vec![
+                Some("MakeCoffee".to_string()),
+                Some("MakeTea".to_string()),
+                None,
+            ],
+            metrics.confusion_matrix.intents
+        );
+        let nb_classified_utterances: usize = metrics
+            .confusion_matrix
+            .matrix
+            .iter()
+            .map(|row| row.iter().sum::<usize>())
+            .sum();
+        assert_eq!(10, nb_classified_utterances);
+        assert_eq!(vec![0, 0, 0], metrics.confusion_matrix.matrix[2]);
+        assert!(metrics.intent_accuracy > 0.5);
+    }
+
+    #[test]
+    fn compute_cross_val_metrics_should_fail_with_a_single_fold() {
+        // Given
+        let path = file_path("tests/datasets/beverage_dataset.json");
+        let dataset = Dataset::from_reader(File::open(path).unwrap()).unwrap();
+
+        // When
+        let result = compute_cross_val_metrics(&dataset, 1);
+
+        // Then
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! Spans of the parsing pipeline, emitted with the `tracing` crate when the `tracing` feature is
+//! enabled
+//!
+//! Without the feature, `pipeline_span!` returns a no-op `Span` and its fields are not even
+//! evaluated, so that the instrumentation costs nothing.
+
+#[cfg(feature = "tracing")]
+pub use tracing::Span;
+
+/// Creates a span of the pipeline, with the syntax of `tracing::info_span!`
+#[cfg(feature = "tracing")]
+macro_rules! pipeline_span {
+    ($($args:tt)+) => {
+        ::tracing::info_span!($($args)+)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! pipeline_span {
+    ($($args:tt)+) => {
+        ::instrumentation::Span
+    };
+}
+
+/// Hash of the input identifying it in the spans, so that the inputs, which may be sensitive,
+/// are not written in the traces
+#[cfg(feature = "tracing")]
+pub fn input_hash(input: &str) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    input.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(not(feature = "tracing"))]
+#[derive(Clone, Debug)]
+pub struct Span;
+
+#[cfg(not(feature = "tracing"))]
+pub struct Entered;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    pub fn current() -> Self {
+        Span
+    }
+
+    pub fn enter(&self) -> Entered {
+        Entered
+    }
+
+    pub fn record<Q: ?Sized, V>(&self, _field: &Q, _value: V) -> &Self {
+        self
+    }
+}
@@ -0,0 +1,182 @@
+use configurations::CalibrationConfiguration;
+use errors::*;
+
+/// Calibrates the probabilities of the intents with one function per intent, the calibrated
+/// probabilities being normalized afterwards
+pub struct Calibration {
+    functions: Vec<CalibrationFunction>,
+}
+
+enum CalibrationFunction {
+    Platt {
+        a: f32,
+        b: f32,
+    },
+    Isotonic {
+        x_thresholds: Vec<f32>,
+        y_thresholds: Vec<f32>,
+    },
+}
+
+impl Calibration {
+    pub fn new(configs: Vec<CalibrationConfiguration>, nb_intents: usize) -> Result<Self> {
+        if configs.len() != nb_intents {
+            bail!(
+                "Expected {} calibration functions but got {}",
+                nb_intents,
+                configs.len()
+            );
+        }
+        let functions = configs
+            .into_iter()
+            .map(CalibrationFunction::new)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { functions })
+    }
+
+    pub fn calibrate(&self, probabilities: &[f32]) -> Vec<f32> {
+        let calibrated_probabilities: Vec<f32> = self.functions
+            .iter()
+            .zip(probabilities.iter())
+            .map(|(function, probability)| function.apply(*probability))
+            .collect();
+        let sum: f32 = calibrated_probabilities.iter().sum();
+        if sum > 0. {
+            calibrated_probabilities
+                .into_iter()
+                .map(|probability| probability / sum)
+                .collect()
+        } else {
+            calibrated_probabilities
+        }
+    }
+}
+
+impl CalibrationFunction {
+    fn new(config: CalibrationConfiguration) -> Result<Self> {
+        match config {
+            CalibrationConfiguration::Platt { a, b } => Ok(CalibrationFunction::Platt { a, b }),
+            CalibrationConfiguration::Isotonic {
+                x_thresholds,
+                y_thresholds,
+            } => {
+                if x_thresholds.is_empty() || x_thresholds.len() != y_thresholds.len() {
+                    bail!("Isotonic calibration thresholds are not properly defined");
+                }
+                if x_thresholds.windows(2).any(|x| x[0] > x[1]) {
+                    bail!("Isotonic calibration thresholds must be sorted");
+                }
+                Ok(CalibrationFunction::Isotonic {
+                    x_thresholds,
+                    y_thresholds,
+                })
+            }
+        }
+    }
+
+    fn apply(&self, probability: f32) -> f32 {
+        match *self {
+            CalibrationFunction::Platt { a, b } => 1. / (1. + (a * probability + b).exp()),
+            CalibrationFunction::Isotonic {
+                ref x_thresholds,
+                ref y_thresholds,
+            } => {
+                let last_index = x_thresholds.len() - 1;
+                if probability <= x_thresholds[0] {
+                    return y_thresholds[0];
+                }
+                if probability >= x_thresholds[last_index] {
+                    return y_thresholds[last_index];
+                }
+                let upper_index = x_thresholds
+                    .iter()
+                    .position(|x| *x >= probability)
+                    .unwrap_or(last_index);
+                let (x0, x1) = (x_thresholds[upper_index - 1], x_thresholds[upper_index]);
+                let (y0, y1) = (y_thresholds[upper_index - 1], y_thresholds[upper_index]);
+                if x1 == x0 {
+                    y1
+                } else {
+                    y0 + (probability - x0) * (y1 - y0) / (x1 - x0)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutils::{assert_epsilon_eq_vec, epsilon_eq};
+
+    #[test]
+    fn platt_calibration_works() {
+        // Given
+        let function = CalibrationFunction::new(CalibrationConfiguration::Platt {
+            a: -4.0,
+            b: 2.0,
+        }).unwrap();
+
+        // When
+        let calibrated_probability = function.apply(0.5);
+
+        // Then
+        assert!(epsilon_eq(0.5, calibrated_probability, 1e-6));
+    }
+
+    #[test]
+    fn isotonic_calibration_works() {
+        // Given
+        let function = CalibrationFunction::new(CalibrationConfiguration::Isotonic {
+            x_thresholds: vec![0.2, 0.4, 0.8],
+            y_thresholds: vec![0.1, 0.3, 0.9],
+        }).unwrap();
+
+        // When
+        let calibrated_probabilities = vec![
+            function.apply(0.1),
+            function.apply(0.3),
+            function.apply(0.6),
+            function.apply(0.9),
+        ];
+
+        // Then
+        assert_epsilon_eq_vec(&[0.1, 0.2, 0.6, 0.9], &calibrated_probabilities, 1e-6);
+    }
+
+    #[test]
+    fn isotonic_calibration_should_fail_with_unsorted_thresholds() {
+        // When
+        let function = CalibrationFunction::new(CalibrationConfiguration::Isotonic {
+            x_thresholds: vec![0.4, 0.2],
+            y_thresholds: vec![0.1, 0.3],
+        });
+
+        // Then
+        assert!(function.is_err());
+    }
+
+    #[test]
+    fn calibrate_should_normalize_probabilities() {
+        // Given
+        let calibration = Calibration::new(
+            vec![
+                CalibrationConfiguration::Isotonic {
+                    x_thresholds: vec![0.0, 1.0],
+                    y_thresholds: vec![0.0, 0.5],
+                },
+                CalibrationConfiguration::Isotonic {
+                    x_thresholds: vec![0.0, 1.0],
+                    y_thresholds: vec![0.0, 1.0],
+                },
+            ],
+            2,
+        ).unwrap();
+
+        // When
+        let calibrated_probabilities = calibration.calibrate(&[0.8, 0.2]);
+
+        // Then
+        assert_epsilon_eq_vec(&[2. / 3., 1. / 3.], &calibrated_probabilities, 1e-6);
+    }
+}
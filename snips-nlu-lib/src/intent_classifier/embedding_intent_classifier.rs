@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use itertools::Itertools;
+
+use configurations::EmbeddingIntentClassifierConfiguration;
+use errors::*;
+use intent_classifier::IntentClassifier;
+use intent_classifier::calibration::Calibration;
+use intent_classifier::intent_selection::{intents_distribution, intern_intent_list,
+                                           select_intent};
+use memory_footprint::HeapSize;
+use nlu_utils::string::normalize;
+use resource_reader::ResourceReader;
+use resources::word_embeddings::WordEmbeddings;
+use snips_nlu_ontology::IntentClassifierResult;
+use symbols::Symbol;
+use tokenizer::Tokenizer;
+
+/// Intent classifier which averages the pretrained vectors of the input words and feeds the
+/// result to a linear layer followed by a softmax
+pub struct EmbeddingIntentClassifier {
+    tokenizer: Arc<Tokenizer>,
+    word_embeddings: WordEmbeddings,
+    intercept: Vec<f32>,
+    coeffs: Vec<Vec<f32>>,
+    intent_list: Vec<Option<Symbol>>,
+    calibration: Option<Calibration>,
+    none_sensitivity: f32,
+}
+
+impl EmbeddingIntentClassifier {
+    pub fn new(
+        config: EmbeddingIntentClassifierConfiguration,
+        none_sensitivity: f32,
+        tokenizer: Arc<Tokenizer>,
+        resource_reader: &ResourceReader,
+    ) -> Result<Self> {
+        let word_embeddings =
+            WordEmbeddings::from_reader(resource_reader.open(&config.word_embeddings_path)?)?;
+        let nb_intents = config.intent_list.len();
+        let calibration = if let Some(calibration_configs) = config.calibration {
+            Some(Calibration::new(calibration_configs, nb_intents)?)
+        } else {
+            None
+        };
+        let mut classifier = Self::with_word_embeddings(
+            tokenizer,
+            word_embeddings,
+            config.intercept,
+            config.coeffs,
+            config.intent_list,
+        )?;
+        classifier.calibration = calibration;
+        classifier.none_sensitivity = none_sensitivity;
+        Ok(classifier)
+    }
+
+    fn with_word_embeddings(
+        tokenizer: Arc<Tokenizer>,
+        word_embeddings: WordEmbeddings,
+        intercept: Vec<f32>,
+        coeffs: Vec<Vec<f32>>,
+        intent_list: Vec<Option<String>>,
+    ) -> Result<Self> {
+        if intercept.len() != intent_list.len() || coeffs.len() != intent_list.len() {
+            bail!("Linear layer doesn't match the number of intents");
+        }
+        if coeffs
+            .iter()
+            .any(|class_coeffs| class_coeffs.len() != word_embeddings.dimension())
+        {
+            bail!("Linear layer doesn't match the dimension of the word embeddings");
+        }
+        Ok(Self {
+            tokenizer,
+            word_embeddings,
+            intercept,
+            coeffs,
+            intent_list: intern_intent_list(intent_list),
+            calibration: None,
+            none_sensitivity: 1.0,
+        })
+    }
+
+    fn features(&self, input: &str) -> Vec<f32> {
+        let words = self.tokenizer
+            .tokenize_light(input)
+            .iter()
+            .map(|token| normalize(token))
+            .collect_vec();
+        self.word_embeddings.average_vector(&words)
+    }
+
+    fn probabilities(&self, input: &str) -> Vec<f32> {
+        let features = self.features(input);
+        let scores = self.intercept
+            .iter()
+            .zip(self.coeffs.iter())
+            .map(|(intercept, class_coeffs)| {
+                class_coeffs
+                    .iter()
+                    .zip(features.iter())
+                    .fold(*intercept, |acc, (coeff, feature)| acc + coeff * feature)
+            })
+            .collect_vec();
+        let probabilities = softmax(&scores);
+        if let Some(calibration) = self.calibration.as_ref() {
+            calibration.calibrate(&probabilities)
+        } else {
+            probabilities
+        }
+    }
+}
+
+impl IntentClassifier for EmbeddingIntentClassifier {
+    fn get_intent(
+        &self,
+        input: &str,
+        intents_filter: Option<&HashSet<String>>,
+    ) -> Result<Option<IntentClassifierResult>> {
+        if input.is_empty() || self.intent_list.is_empty() {
+            return Ok(None);
+        }
+
+        let probabilities = self.probabilities(input);
+        Ok(select_intent(
+            &self.intent_list,
+            &probabilities,
+            intents_filter,
+            self.none_sensitivity,
+        ))
+    }
+
+    fn get_intents(
+        &self,
+        input: &str,
+        intents_filter: Option<&HashSet<String>>,
+    ) -> Result<Vec<IntentClassifierResult>> {
+        if input.is_empty() || self.intent_list.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let probabilities = self.probabilities(input);
+        Ok(intents_distribution(
+            &self.intent_list,
+            &probabilities,
+            intents_filter,
+        ))
+    }
+
+    fn get_features(&self, input: &str) -> Result<Option<Vec<f32>>> {
+        Ok(Some(self.features(input)))
+    }
+
+    /// The word embeddings are a shared resource, which is not counted
+    fn heap_size(&self) -> usize {
+        self.intent_list.heap_size() + self.intercept.heap_size() + self.coeffs.heap_size()
+    }
+}
+
+fn softmax(scores: &[f32]) -> Vec<f32> {
+    let max_score = scores.iter().cloned().fold(::std::f32::NEG_INFINITY, f32::max);
+    let exponentials = scores
+        .iter()
+        .map(|score| (score - max_score).exp())
+        .collect_vec();
+    let sum: f32 = exponentials.iter().sum();
+    exponentials.into_iter().map(|e| e / sum).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_ontology::Language;
+    use testutils::{assert_epsilon_eq_vec, epsilon_eq};
+    use tokenizer::default_tokenizer;
+
+    fn test_classifier() -> EmbeddingIntentClassifier {
+        let data = "coffee 1.0 0.0\nlatte 0.8 0.2\ntea 0.0 1.0\nplease 0.1 0.1\n";
+        let word_embeddings = WordEmbeddings::from_reader(data.as_bytes()).unwrap();
+        EmbeddingIntentClassifier::with_word_embeddings(
+            default_tokenizer(Language::EN),
+            word_embeddings,
+            vec![0.0, 0.0, 0.5],
+            vec![vec![4.0, -2.0], vec![-2.0, 4.0], vec![-1.0, -1.0]],
+            vec![
+                Some("MakeCoffee".to_string()),
+                Some("MakeTea".to_string()),
+                None,
+            ],
+        ).unwrap()
+    }
+
+    #[test]
+    fn get_intent_works() {
+        // Given
+        let classifier = test_classifier();
+
+        // When
+        let intent = classifier.get_intent("a Latte please", None).unwrap();
+
+        // Then
+        let intent = intent.unwrap();
+        assert_eq!("MakeCoffee", intent.intent_name);
+        assert!(intent.probability > 0.5);
+    }
+
+    #[test]
+    fn get_intent_works_with_filter() {
+        // Given
+        let classifier = test_classifier();
+        let intents_filter = hashset!["MakeTea".to_string()];
+
+        // When
+        let intent = classifier
+            .get_intent("a coffee please", Some(&intents_filter))
+            .unwrap();
+
+        // Then
+        assert_eq!("MakeTea", intent.unwrap().intent_name);
+    }
+
+    #[test]
+    fn get_intent_should_return_none_for_unknown_words() {
+        // Given
+        let classifier = test_classifier();
+
+        // When
+        let intent = classifier.get_intent("hello world", None).unwrap();
+
+        // Then
+        assert_eq!(None, intent);
+    }
+
+    #[test]
+    fn softmax_works() {
+        // When
+        let probabilities = softmax(&[1.0, 2.0, 3.0]);
+
+        // Then
+        assert_epsilon_eq_vec(&[0.09003057, 0.24472847, 0.66524096], &probabilities, 1e-6);
+        assert!(epsilon_eq(1.0, probabilities.iter().sum(), 1e-6));
+    }
+}
@@ -1,31 +1,46 @@
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use itertools::Itertools;
-use ndarray::prelude::*;
 
 use configurations::FeaturizerConfiguration;
 use errors::*;
-use language::{FromLanguage, LanguageConfig};
-use nlu_utils::token::{compute_all_ngrams, tokenize_light};
+use language::LanguageConfig;
+use memory_footprint::HeapSize;
+use ngrams::NgramTrie;
+use nlu_utils::token::compute_all_ngrams;
 use nlu_utils::string::normalize;
-use nlu_utils::language::Language as NluUtilsLanguage;
 use resources::word_clusterer::{StaticMapWordClusterer, WordClusterer};
 use resources::stemmer::{StaticMapStemmer, Stemmer};
+use tokenizer::{default_tokenizer, Tokenizer};
 
 pub struct Featurizer {
     best_features: Vec<usize>,
     vocabulary: HashMap<String, usize>,
     idf_diag: Vec<f32>,
     sublinear: bool,
-    language_config: LanguageConfig,
     word_clusterer: Option<StaticMapWordClusterer>,
     stemmer: Option<StaticMapStemmer>,
-    entity_utterances_to_feature_names: HashMap<String, Vec<String>>,
+    /// Entity features of the entity utterances, normalized beforehand
+    entity_utterances_to_feature_names: NgramTrie<Vec<String>>,
+    tokenizer: Arc<Tokenizer>,
+}
+
+/// The word clusters and the stemmer are shared resources, which are not counted
+impl HeapSize for Featurizer {
+    fn heap_size(&self) -> usize {
+        self.best_features.heap_size() + self.vocabulary.heap_size() + self.idf_diag.heap_size()
+    }
 }
 
 impl Featurizer {
     pub fn new(config: FeaturizerConfiguration) -> Self {
+        let language_config = LanguageConfig::from_str(&config.language_code).unwrap();
+        Self::new_with_tokenizer(config, default_tokenizer(language_config.language))
+    }
+
+    pub fn new_with_tokenizer(config: FeaturizerConfiguration, tokenizer: Arc<Tokenizer>) -> Self {
         let best_features = config.best_features;
         let vocabulary = config.tfidf_vectorizer.vocab;
         let idf_diag = config.tfidf_vectorizer.idf_diag;
@@ -38,21 +53,29 @@ impl Featurizer {
             })
             .unwrap_or(None);
         let stemmer = StaticMapStemmer::new(language_config.language).ok();
-        let entity_utterances_to_feature_names = config.entity_utterances_to_feature_names;
+        let entity_utterances_to_feature_names = config
+            .entity_utterances_to_feature_names
+            .into_iter()
+            .map(|(utterance, feature_names)| {
+                let normalized_feature_names =
+                    feature_names.iter().map(|name| normalize(name)).collect();
+                (utterance, normalized_feature_names)
+            })
+            .collect();
 
         Self {
             best_features,
             vocabulary,
             idf_diag,
             sublinear: config.config.sublinear_tf,
-            language_config,
             word_clusterer,
             stemmer,
             entity_utterances_to_feature_names,
+            tokenizer,
         }
     }
 
-    pub fn transform(&self, input: &str) -> Result<Array1<f32>> {
+    pub fn transform(&self, input: &str) -> Result<Vec<f32>> {
         let preprocessed_tokens = self.preprocess_query(input);
         let vocabulary_size = self.vocabulary.values().max().unwrap() + 1;
 
@@ -78,16 +101,46 @@ impl Featurizer {
 
         tfidf = tfidf.iter().map(|c| *c / safe_l2_norm).collect_vec();
 
-        let selected_features =
-            Array::from_iter((0..self.best_features.len()).map(|fi| tfidf[self.best_features[fi]]));
+        let selected_features = self.best_features
+            .iter()
+            .map(|feature_index| tfidf[*feature_index])
+            .collect();
         Ok(selected_features)
     }
 
-    fn preprocess_query(&self, query: &str) -> Vec<String> {
-        let tokens = tokenize_light(
-            query,
-            NluUtilsLanguage::from_language(self.language_config.language),
-        );
+    /// Returns the name of the feature at `index` in the vectors returned by `transform`
+    pub fn feature_name(&self, index: usize) -> Option<&str> {
+        let vocabulary_index = *self.best_features.get(index)?;
+        self.vocabulary
+            .iter()
+            .find(|&(_, word_index)| *word_index == vocabulary_index)
+            .map(|(word, _)| &**word)
+    }
+
+    /// Returns the words of the tf-idf vocabulary and of the entity utterances, which are left
+    /// out when the inputs are stemmed since they are then stems rather than words
+    pub fn vocabulary(&self) -> Vec<String> {
+        if self.stemmer.is_some() {
+            return vec![];
+        }
+        let feature_names: HashSet<&str> = self.entity_utterances_to_feature_names
+            .values()
+            .into_iter()
+            .flat_map(|names| names.iter().map(|name| &**name))
+            .collect();
+        self.vocabulary
+            .keys()
+            .map(|word| &**word)
+            .filter(|word| !feature_names.contains(word))
+            .chain(self.entity_utterances_to_feature_names.tokens())
+            .filter(|word| word.chars().all(char::is_alphabetic))
+            .map(|word| word.to_string())
+            .unique()
+            .collect()
+    }
+
+    pub(crate) fn preprocess_query(&self, query: &str) -> Vec<String> {
+        let tokens = self.tokenizer.tokenize_light(query);
         let mut processed_tokens: Vec<String> = if let Some(ref stemmer) = self.stemmer {
             tokens.iter().map(|t| stemmer.stem(&normalize(t))).collect()
         } else {
@@ -119,7 +172,7 @@ fn get_word_cluster_features<C: WordClusterer>(
 fn get_dataset_entities_features<S: Stemmer>(
     query_tokens: &[String],
     stemmer: Option<&S>,
-    entity_utterances_to_feature_names: &HashMap<String, Vec<String>>,
+    entity_utterances_to_feature_names: &NgramTrie<Vec<String>>,
 ) -> Vec<String> {
     let normalized_tokens: Vec<String> = query_tokens.iter().map(|t| normalize(t)).collect();
     let normalized_stemmed_tokens = stemmer.map_or(normalized_tokens.clone(), |stemmer| {
@@ -128,12 +181,10 @@ fn get_dataset_entities_features<S: Stemmer>(
             .map(|t| stem(&t, stemmer))
             .collect()
     });
-    let tokens_ref = normalized_stemmed_tokens.iter().map(|t| &**t).collect_vec();
-    compute_all_ngrams(&*tokens_ref, tokens_ref.len())
+    entity_utterances_to_feature_names
+        .find_all(&normalized_stemmed_tokens)
         .into_iter()
-        .filter_map(|ngrams| entity_utterances_to_feature_names.get(&ngrams.0))
-        .flat_map(|features| features)
-        .map(|s| normalize(s))
+        .flat_map(|(_, features)| features.iter().cloned())
         .collect()
 }
 
@@ -145,7 +196,7 @@ fn stem<S: Stemmer>(input: &str, stemmer: &S) -> String {
 mod tests {
     use super::{get_dataset_entities_features, get_word_cluster_features, Featurizer};
 
-    use testutils::assert_epsilon_eq_array1;
+    use testutils::assert_epsilon_eq_vec;
     use resources::word_clusterer::WordClusterer;
     use resources::stemmer::Stemmer;
     use nlu_utils::language::Language;
@@ -232,7 +283,7 @@ mod tests {
         let features = featurizer.transform(input).unwrap();
 
         // Then
-        let expected_features = array![
+        let expected_features = vec![
             0.0,
             0.40887040136658365,
             0.5661321160803057,
@@ -242,7 +293,48 @@ mod tests {
             0.30854541380686823,
             0.4900427160462025
         ];
-        assert_epsilon_eq_array1(&features, &expected_features, 1e-6);
+        assert_epsilon_eq_vec(&features, &expected_features, 1e-6);
+    }
+
+    #[test]
+    fn vocabulary_should_leave_out_feature_names() {
+        // Given
+        let vocab = hashmap![
+            "make".to_string() => 0,
+            "coffee".to_string() => 1,
+            "42".to_string() => 2,
+            "featureentitybeverage".to_string() => 3
+        ];
+        let entity_utterances_to_feature_names = hashmap![
+            "hot chocolate".to_string() => vec!["featureentitybeverage".to_string()]
+        ];
+        let featurizer_config = FeaturizerConfiguration {
+            language_code: "en".to_string(),
+            tfidf_vectorizer: TfIdfVectorizerConfiguration {
+                idf_diag: vec![1.0, 1.0, 1.0, 1.0],
+                vocab,
+            },
+            config: FeaturizerConfigConfiguration {
+                sublinear_tf: false,
+            },
+            best_features: vec![0, 1, 2, 3],
+            entity_utterances_to_feature_names,
+        };
+        let mut featurizer = Featurizer::new(featurizer_config);
+        featurizer.stemmer = None;
+
+        // When
+        let mut vocabulary = featurizer.vocabulary();
+        vocabulary.sort();
+
+        // Then
+        let expected_vocabulary = vec![
+            "chocolate".to_string(),
+            "coffee".to_string(),
+            "hot".to_string(),
+            "make".to_string(),
+        ];
+        assert_eq!(expected_vocabulary, vocabulary);
     }
 
     #[test]
@@ -266,10 +358,14 @@ mod tests {
         // Given
         let language = Language::EN;
         let query_tokens = tokenize_light("Hëllo this bïrd is a beautiful Bïrd", language);
-        let entity_utterances_to_feature_names = hashmap![
-            "bir".to_string() => vec!["featureentityAnimal".to_string()],
-            "hell this".to_string() => vec!["featureentityWord".to_string(), "featureentityGreeting".to_string()]
-        ];
+        let entity_utterances_to_feature_names = vec![
+            ("bir".to_string(), vec!["featureentityanimal".to_string()]),
+            (
+                "hell this".to_string(),
+                vec!["featureentityword".to_string(), "featureentitygreeting".to_string()],
+            ),
+        ].into_iter()
+            .collect();
         let stemmer = TestStemmer {};
 
         // When
@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+
+use snips_nlu_ontology::IntentClassifierResult;
+
+use symbols::Symbol;
+use utils::decreasing_order;
+
+/// Interns the names of the intent list, the None intent being represented by `None`
+pub fn intern_intent_list(intent_list: Vec<Option<String>>) -> Vec<Option<Symbol>> {
+    intent_list
+        .into_iter()
+        .map(|opt_intent| opt_intent.map(Symbol::from))
+        .collect()
+}
+
+/// Selects the most likely intent among the ones allowed by the filter, the None intent being
+/// represented by `None` in the intent list
+///
+/// The probability of the None intent is multiplied by the none sensitivity before the
+/// comparison: a sensitivity above 1 favors rejecting inputs, one below 1 favors accepting them.
+pub fn select_intent(
+    intent_list: &[Option<Symbol>],
+    probabilities: &[f32],
+    intents_filter: Option<&HashSet<String>>,
+    none_sensitivity: f32,
+) -> Option<IntentClassifierResult> {
+    let best_intent = intent_list
+        .iter()
+        .zip(probabilities.iter())
+        .filter(|&(opt_intent, _)| {
+            opt_intent
+                .as_ref()
+                .and_then(|intent| intents_filter.map(|intents| intents.contains(&**intent)))
+                .unwrap_or(true)
+        })
+        .map(|(opt_intent, probability)| {
+            let score = if opt_intent.is_none() {
+                probability * none_sensitivity
+            } else {
+                *probability
+            };
+            (opt_intent, *probability, score)
+        })
+        .min_by(|a, b| decreasing_order(a.2, b.2));
+
+    best_intent.and_then(|(opt_intent, probability, _)| {
+        opt_intent
+            .as_ref()
+            .map(|intent_name| IntentClassifierResult {
+                intent_name: intent_name.to_string(),
+                probability,
+            })
+    })
+}
+
+/// Returns the intents allowed by the filter along with their probabilities, by decreasing
+/// probability, the None intent being left out
+pub fn intents_distribution(
+    intent_list: &[Option<Symbol>],
+    probabilities: &[f32],
+    intents_filter: Option<&HashSet<String>>,
+) -> Vec<IntentClassifierResult> {
+    let mut distribution = intent_list
+        .iter()
+        .zip(probabilities.iter())
+        .filter_map(|(opt_intent, probability)| {
+            opt_intent.as_ref().map(|intent_name| IntentClassifierResult {
+                intent_name: intent_name.to_string(),
+                probability: *probability,
+            })
+        })
+        .filter(|result| {
+            intents_filter
+                .map(|intents| intents.contains(&result.intent_name))
+                .unwrap_or(true)
+        })
+        .collect::<Vec<_>>();
+    distribution.sort_by(|a, b| decreasing_order(a.probability, b.probability));
+    distribution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_intent_list() -> Vec<Option<Symbol>> {
+        vec![
+            Some("MakeCoffee".into()),
+            Some("MakeTea".into()),
+            None,
+        ]
+    }
+
+    #[test]
+    fn select_intent_works() {
+        // When
+        let intent = select_intent(&test_intent_list(), &[0.3, 0.45, 0.25], None, 1.0);
+
+        // Then
+        let expected_intent = Some(IntentClassifierResult {
+            intent_name: "MakeTea".to_string(),
+            probability: 0.45,
+        });
+        assert_eq!(expected_intent, intent);
+    }
+
+    #[test]
+    fn select_intent_works_with_filter() {
+        // Given
+        let intents_filter = hashset!["MakeCoffee".to_string()];
+
+        // When
+        let intent = select_intent(
+            &test_intent_list(),
+            &[0.3, 0.45, 0.25],
+            Some(&intents_filter),
+            1.0,
+        );
+
+        // Then
+        let expected_intent = Some(IntentClassifierResult {
+            intent_name: "MakeCoffee".to_string(),
+            probability: 0.3,
+        });
+        assert_eq!(expected_intent, intent);
+    }
+
+    #[test]
+    fn select_intent_should_reject_input_with_high_none_sensitivity() {
+        // When
+        let intent = select_intent(&test_intent_list(), &[0.3, 0.45, 0.25], None, 2.0);
+
+        // Then
+        assert_eq!(None, intent);
+    }
+
+    #[test]
+    fn select_intent_should_accept_input_with_low_none_sensitivity() {
+        // When
+        let intent = select_intent(&test_intent_list(), &[0.2, 0.3, 0.5], None, 0.5);
+
+        // Then
+        let expected_intent = Some(IntentClassifierResult {
+            intent_name: "MakeTea".to_string(),
+            probability: 0.3,
+        });
+        assert_eq!(expected_intent, intent);
+    }
+
+    #[test]
+    fn intents_distribution_works() {
+        // Given
+        let intents_filter = hashset!["MakeCoffee".to_string(), "MakeTea".to_string()];
+
+        // When
+        let distribution = intents_distribution(
+            &test_intent_list(),
+            &[0.2, 0.3, 0.5],
+            Some(&intents_filter),
+        );
+
+        // Then
+        let expected_distribution = vec![
+            IntentClassifierResult {
+                intent_name: "MakeTea".to_string(),
+                probability: 0.3,
+            },
+            IntentClassifierResult {
+                intent_name: "MakeCoffee".to_string(),
+                probability: 0.2,
+            },
+        ];
+        assert_eq!(expected_distribution, distribution);
+    }
+
+    #[test]
+    fn intents_distribution_should_put_nan_probabilities_last() {
+        // When
+        let distribution =
+            intents_distribution(&test_intent_list(), &[::std::f32::NAN, 0.3, 0.5], None);
+
+        // Then
+        let intent_names = distribution
+            .iter()
+            .map(|result| &*result.intent_name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["MakeTea", "MakeCoffee"], intent_names);
+    }
+
+    #[test]
+    fn select_intent_should_ignore_nan_probabilities() {
+        // When
+        let intent = select_intent(&test_intent_list(), &[0.3, ::std::f32::NAN, 0.25], None, 1.0);
+
+        // Then
+        let expected_intent = Some(IntentClassifierResult {
+            intent_name: "MakeCoffee".to_string(),
+            probability: 0.3,
+        });
+        assert_eq!(expected_intent, intent);
+    }
+}
@@ -1,43 +1,91 @@
+use std::cmp::Ordering;
 use std::collections::HashSet;
-
-use itertools::Itertools;
-use ndarray::prelude::*;
+use std::sync::Arc;
 
 use errors::*;
 use intent_classifier::{Featurizer, IntentClassifier};
+use intent_classifier::calibration::Calibration;
+use intent_classifier::intent_selection::{intents_distribution, intern_intent_list,
+                                           select_intent};
 use intent_classifier::logreg::MulticlassLogisticRegression;
 use snips_nlu_ontology::IntentClassifierResult;
-use configurations::IntentClassifierConfiguration;
+use configurations::{IntentClassifierConfiguration, WeightsQuantization};
+use memory_footprint::HeapSize;
+use symbols::Symbol;
+use tokenizer::Tokenizer;
+
+/// Number of features returned by `get_weighted_features`
+const NB_WEIGHTED_FEATURES: usize = 5;
 
 pub struct LogRegIntentClassifier {
-    intent_list: Vec<Option<String>>,
+    intent_list: Vec<Option<Symbol>>,
     featurizer: Option<Featurizer>,
     logreg: Option<MulticlassLogisticRegression>,
+    calibration: Option<Calibration>,
+    none_sensitivity: f32,
 }
 
 impl LogRegIntentClassifier {
-    pub fn new(config: IntentClassifierConfiguration) -> Result<Self> {
-        let featurizer = config.featurizer.map(Featurizer::new);
+    pub fn new(
+        config: IntentClassifierConfiguration,
+        none_sensitivity: f32,
+        weights_quantization: Option<WeightsQuantization>,
+        tokenizer: Arc<Tokenizer>,
+    ) -> Result<Self> {
+        let featurizer = config
+            .featurizer
+            .map(|featurizer_config| Featurizer::new_with_tokenizer(featurizer_config, tokenizer));
         let logreg = if let (Some(intercept), Some(coeffs)) = (config.intercept, config.coeffs) {
-            let arr_intercept = Array::from_vec(intercept);
-            let nb_classes = arr_intercept.dim();
-            let nb_features = coeffs[0].len();
-            // Note: the deserialized coeffs matrix is transposed
-            let arr_weights =
-                Array::from_shape_fn((nb_features, nb_classes), |(i, j)| coeffs[j][i]);
-            MulticlassLogisticRegression::new(arr_intercept, arr_weights).map(Some)
+            MulticlassLogisticRegression::new(intercept, coeffs).map(Some)
         } else {
             Ok(None)
         }?;
+        let logreg = match (logreg, weights_quantization) {
+            (Some(logreg), Some(quantization)) => Some(logreg.quantize(quantization)),
+            (logreg, _) => logreg,
+        };
+        let nb_intents = config.intent_list.len();
+        let calibration = if let Some(calibration_configs) = config.calibration {
+            Some(Calibration::new(calibration_configs, nb_intents)?)
+        } else {
+            None
+        };
 
         Ok(Self {
-            intent_list: config.intent_list,
+            intent_list: intern_intent_list(config.intent_list),
             featurizer,
             logreg,
+            calibration,
+            none_sensitivity,
         })
     }
 }
 
+impl LogRegIntentClassifier {
+    /// Returns the probabilities of the intents of the intent list, if they can be computed
+    fn probabilities(&self, input: &str) -> Result<Option<Vec<f32>>> {
+        if input.is_empty() || self.intent_list.is_empty() {
+            return Ok(None);
+        }
+
+        if self.intent_list.len() == 1 {
+            return Ok(Some(vec![1.0]));
+        }
+
+        if let (Some(featurizer), Some(logreg)) = (self.featurizer.as_ref(), self.logreg.as_ref()) {
+            let features = featurizer.transform(input)?;
+            let probabilities = logreg.run(&features)?;
+            if let Some(calibration) = self.calibration.as_ref() {
+                Ok(Some(calibration.calibrate(&probabilities)))
+            } else {
+                Ok(Some(probabilities))
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
+
 impl IntentClassifier for LogRegIntentClassifier {
     fn get_intent(
         &self,
@@ -52,47 +100,85 @@ impl IntentClassifier for LogRegIntentClassifier {
             return Ok(self.intent_list[0]
                 .as_ref()
                 .map(|intent_name| IntentClassifierResult {
-                    intent_name: intent_name.clone(),
+                    intent_name: intent_name.to_string(),
                     probability: 1.0,
                 }));
         }
 
-        if let (Some(featurizer), Some(logreg)) = (self.featurizer.as_ref(), self.logreg.as_ref()) {
-            let features = featurizer.transform(input)?;
-            let probabilities = logreg.run(&features.view())?;
-
-            let mut intents_proba: Vec<(&Option<String>, &f32)> = self.intent_list
-                .iter()
-                .zip(probabilities.into_iter())
-                .collect_vec();
-
-            // Sort intents by decreasing probabilities
-            intents_proba.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap());
-
-            let mut filtered_intents = intents_proba.into_iter().filter(|&(opt_intent, _)| {
-                if let Some(intent) = opt_intent.as_ref() {
-                    intents_filter
-                        .map(|intents| intents.contains(intent))
-                        .unwrap_or(true)
-                } else {
-                    true
+        Ok(self.probabilities(input)?.and_then(|probabilities| {
+            select_intent(
+                &self.intent_list,
+                &probabilities,
+                intents_filter,
+                self.none_sensitivity,
+            )
+        }))
+    }
+
+    fn get_intents(
+        &self,
+        input: &str,
+        intents_filter: Option<&HashSet<String>>,
+    ) -> Result<Vec<IntentClassifierResult>> {
+        Ok(self.probabilities(input)?
+            .map(|probabilities| {
+                intents_distribution(&self.intent_list, &probabilities, intents_filter)
+            })
+            .unwrap_or_else(Vec::new))
+    }
+
+    fn get_features(&self, input: &str) -> Result<Option<Vec<f32>>> {
+        self.featurizer
+            .as_ref()
+            .map(|featurizer| featurizer.transform(input))
+            .map_or(Ok(None), |features| features.map(Some))
+    }
+
+    fn vocabulary(&self) -> Vec<String> {
+        self.featurizer
+            .as_ref()
+            .map(|featurizer| featurizer.vocabulary())
+            .unwrap_or_else(Vec::new)
+    }
+
+    fn get_weighted_features(
+        &self,
+        input: &str,
+        intent_name: &str,
+    ) -> Result<Option<Vec<(String, f32)>>> {
+        let class_index = self.intent_list
+            .iter()
+            .position(|intent| intent.as_ref().map_or(false, |intent| &**intent == intent_name));
+        let (featurizer, logreg, class_index) =
+            match (self.featurizer.as_ref(), self.logreg.as_ref(), class_index) {
+                (Some(featurizer), Some(logreg), Some(class_index)) => {
+                    (featurizer, logreg, class_index)
                 }
-            });
-
-            filtered_intents
-                .next()
-                .map(|(opt_intent, proba)| {
-                    Ok(opt_intent
-                        .clone()
-                        .map(|intent_name| IntentClassifierResult {
-                            intent_name: intent_name.clone(),
-                            probability: *proba,
-                        }))
-                })
-                .unwrap_or(Ok(None))
-        } else {
-            Ok(None)
-        }
+                _ => return Ok(None),
+            };
+        let features = featurizer.transform(input)?;
+        let mut weighted_features: Vec<(String, f32)> = logreg
+            .contributions(&features, class_index)?
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, contribution)| contribution > 0.)
+            .filter_map(|(index, contribution)| {
+                featurizer
+                    .feature_name(index)
+                    .map(|name| (name.to_string(), contribution))
+            })
+            .collect();
+        weighted_features.sort_by(|&(_, contribution), &(_, other_contribution)| {
+            other_contribution
+                .partial_cmp(&contribution)
+                .unwrap_or(Ordering::Equal)
+        });
+        weighted_features.truncate(NB_WEIGHTED_FEATURES);
+        Ok(Some(weighted_features))
+    }
+
+    fn heap_size(&self) -> usize {
+        self.intent_list.heap_size() + self.featurizer.heap_size() + self.logreg.heap_size()
     }
 }
 
@@ -344,9 +430,9 @@ mod tests {
 
         let tfidf_vectorizer = TfIdfVectorizerConfiguration { idf_diag, vocab };
 
-        let intent_list: Vec<Option<String>> = vec![
-            Some("MakeCoffee".to_string()),
-            Some("MakeTea".to_string()),
+        let intent_list: Vec<Option<Symbol>> = vec![
+            Some("MakeCoffee".into()),
+            Some("MakeTea".into()),
             None,
         ];
 
@@ -364,7 +450,7 @@ mod tests {
 
         let featurizer = Featurizer::new(config);
 
-        let intercept = array![
+        let intercept = vec![
             -0.6769558144299883,
             -0.6587242944035958,
             0.22680835693804338
@@ -460,12 +546,17 @@ mod tests {
             ],
         ];
 
-        let coeffs: Array2<f32> = Array::from_shape_fn((27, 3), |(i, j)| coeffs_vec[j][i]);
+        let coeffs: Vec<Vec<f32>> = coeffs_vec
+            .iter()
+            .map(|class_coeffs| class_coeffs.to_vec())
+            .collect();
         let logreg = MulticlassLogisticRegression::new(intercept, coeffs).unwrap();
         LogRegIntentClassifier {
             featurizer: Some(featurizer),
             intent_list,
             logreg: Some(logreg),
+            calibration: None,
+            none_sensitivity: 1.0,
         }
     }
 
@@ -487,6 +578,58 @@ mod tests {
         assert_eq!(expected_result.probability, actual_result.probability);
     }
 
+    #[test]
+    fn get_weighted_features_works() {
+        // Given
+        let classifier = get_sample_log_reg_classifier();
+
+        // When
+        let weighted_features = classifier
+            .get_weighted_features("Make me two cups of tea", "MakeTea")
+            .unwrap()
+            .unwrap();
+        let unknown_intent_features = classifier
+            .get_weighted_features("Make me two cups of tea", "MakePizza")
+            .unwrap();
+
+        // Then
+        assert!(!weighted_features.is_empty());
+        assert!(weighted_features.len() <= NB_WEIGHTED_FEATURES);
+        assert!(
+            weighted_features
+                .windows(2)
+                .all(|pair| pair[0].1 >= pair[1].1 && pair[1].1 > 0.)
+        );
+        assert!(weighted_features.iter().any(|&(ref name, _)| name == "tea"));
+        assert_eq!(None, unknown_intent_features);
+    }
+
+    #[test]
+    fn get_intents_works() {
+        // Given
+        let classifier = get_sample_log_reg_classifier();
+
+        // When
+        let intents = classifier
+            .get_intents("Make me two cups of tea", None)
+            .unwrap();
+
+        // Then
+        let nb_named_intents = classifier
+            .intent_list
+            .iter()
+            .filter(|intent| intent.is_some())
+            .count();
+        assert_eq!(nb_named_intents, intents.len());
+        assert_eq!("MakeTea", &intents[0].intent_name);
+        assert_eq!(0.48829985, intents[0].probability);
+        assert!(
+            intents
+                .windows(2)
+                .all(|pair| pair[0].probability >= pair[1].probability)
+        );
+    }
+
     #[test]
     fn should_filter_intents() {
         // Given
@@ -1,25 +1,33 @@
-use ndarray::prelude::*;
+use compression::Weights;
+use configurations::WeightsQuantization;
 use errors::*;
+use memory_footprint::HeapSize;
 
 /// The multiclass probability estimates are derived from binary (one-vs.-rest)
 /// estimates by simple normalization
 pub struct MulticlassLogisticRegression {
-    /// matrix with shape (f, c)
-    /// ------------------------
+    /// intercept of each class
+    intercept: Vec<f32>,
+    /// weights of each class
+    /// ---------------------
     ///
-    /// - f = number of features
-    /// - c = number of classes
-    weights: Array2<f32>,
+    /// There is one vector of weights per class, having one weight per feature
+    weights: Vec<Weights<f32>>,
+}
+
+impl HeapSize for MulticlassLogisticRegression {
+    fn heap_size(&self) -> usize {
+        self.intercept.heap_size() + self.weights.heap_size()
+    }
 }
 
 impl MulticlassLogisticRegression {
     fn nb_features(&self) -> usize {
-        // without intercept
-        self.weights.dim().0 - 1
+        self.weights.first().map(|w| w.len()).unwrap_or(0)
     }
 
     fn nb_classes(&self) -> usize {
-        self.weights.dim().1
+        self.intercept.len()
     }
 
     fn is_binary(&self) -> bool {
@@ -28,31 +36,96 @@ impl MulticlassLogisticRegression {
 }
 
 impl MulticlassLogisticRegression {
-    pub fn new(intercept: Array1<f32>, weights: Array2<f32>) -> Result<Self> {
-        let nb_classes = intercept.dim();
-        let reshaped_intercept = intercept.into_shape((1, nb_classes))?;
-        let weights_with_intercept = stack![Axis(0), reshaped_intercept, weights];
-        Ok(Self {
-            weights: weights_with_intercept,
-        })
+    pub fn new(intercept: Vec<f32>, weights: Vec<Vec<f32>>) -> Result<Self> {
+        if intercept.len() != weights.len() {
+            bail!(
+                "Intercept has {} classes while weights have {} classes",
+                intercept.len(),
+                weights.len()
+            );
+        }
+        let nb_features = weights.first().map(|w| w.len()).unwrap_or(0);
+        if weights.iter().any(|w| w.len() != nb_features) {
+            bail!("All classes must have the same number of weights");
+        }
+        let weights = weights.into_iter().map(Weights::Full).collect();
+        Ok(Self { intercept, weights })
     }
 
-    pub fn run(&self, features: &ArrayView1<f32>) -> Result<Array1<f32>> {
-        let reshaped_features = features.into_shape((1, self.nb_features()))?;
-        let reshaped_features = stack![Axis(1), array![[1.]], reshaped_features];
-        let mut result = reshaped_features
-            .dot(&self.weights)
-            .into_shape(self.nb_classes())?;
-        result.mapv_inplace(logit);
+    /// Returns the regression with quantized weights, the weights of each class being scaled
+    /// separately
+    pub fn quantize(self, quantization: WeightsQuantization) -> Self {
+        let row_offsets = [0, self.nb_features()];
+        Self {
+            intercept: self.intercept,
+            weights: self.weights
+                .into_iter()
+                .map(|class_weights| class_weights.quantize(quantization, &row_offsets))
+                .collect(),
+        }
+    }
+
+    pub fn run(&self, features: &[f32]) -> Result<Vec<f32>> {
+        if features.len() != self.nb_features() {
+            bail!(
+                "Expected {} features but got {}",
+                self.nb_features(),
+                features.len()
+            );
+        }
+        let mut result: Vec<f32> = self.intercept
+            .iter()
+            .zip(self.weights.iter())
+            .map(|(intercept, class_weights)| {
+                let score = features
+                    .iter()
+                    .enumerate()
+                    .fold(*intercept, |acc, (index, feature)| {
+                        acc + class_weights.get(0, index) as f32 * feature
+                    });
+                logit(score)
+            })
+            .collect();
         if self.is_binary() {
-            return Ok(arr1(&[1.0 - result[0], result[0]]));
+            return Ok(vec![1.0 - result[0], result[0]]);
+        }
+        let divider: f32 = result.iter().sum();
+        for probability in result.iter_mut() {
+            *probability /= divider;
         }
-        let divider = result.scalar_sum();
-        result /= divider;
         Ok(result)
     }
 }
 
+impl MulticlassLogisticRegression {
+    /// Returns the contribution of each feature to the score of the class, whose index is the
+    /// one of its probability in the output of `run`
+    pub fn contributions(&self, features: &[f32], class_index: usize) -> Result<Vec<f32>> {
+        if features.len() != self.nb_features() {
+            bail!(
+                "Expected {} features but got {}",
+                self.nb_features(),
+                features.len()
+            );
+        }
+        // The weights of a binary regression are the ones of the second class
+        let (weights_index, sign) = match (self.is_binary(), class_index) {
+            (true, 0) => (0, -1.),
+            (true, 1) => (0, 1.),
+            (true, _) => bail!("Unknown class index: {}", class_index),
+            (false, _) => (class_index, 1.),
+        };
+        let class_weights = self.weights
+            .get(weights_index)
+            .ok_or_else(|| format!("Unknown class index: {}", class_index))?;
+        Ok(features
+            .iter()
+            .enumerate()
+            .map(|(index, feature)| sign * class_weights.get(0, index) as f32 * feature)
+            .collect())
+    }
+}
+
 fn logit(x: f32) -> f32 {
     1. / (1. + (-x).exp())
 }
@@ -60,44 +133,102 @@ fn logit(x: f32) -> f32 {
 #[cfg(test)]
 mod tests {
     use super::MulticlassLogisticRegression;
-    use testutils::assert_epsilon_eq_array1;
+    use configurations::WeightsQuantization;
+    use testutils::assert_epsilon_eq_vec;
 
     #[test]
     fn multiclass_logistic_regression_works() {
         // Given
-        let intercept = array![0.98, 0.32, -0.76];
-        let weights = array![
-            [2.5, -0.6, 0.5],
-            [1.2, 2.2, -2.7],
-            [1.5, 0.1, -3.2],
-            [-0.9, -2.4, 1.8]
+        let intercept = vec![0.98, 0.32, -0.76];
+        let weights = vec![
+            vec![2.5, 1.2, 1.5, -0.9],
+            vec![-0.6, 2.2, 0.1, -2.4],
+            vec![0.5, -2.7, -3.2, 1.8],
         ];
 
-        let features = array![0.4, -2.3, 1.9, 1.3];
+        let features = vec![0.4, -2.3, 1.9, 1.3];
         let regression = MulticlassLogisticRegression::new(intercept, weights).unwrap();
 
         // When
-        let predictions = regression.run(&features.view()).unwrap();
+        let predictions = regression.run(&features).unwrap();
 
         // Then
-        let expected_predictions = array![0.4493038, 0.0002318, 0.5504642];
-        assert_epsilon_eq_array1(&predictions, &expected_predictions, 1e-06);
+        let expected_predictions = vec![0.4493038, 0.0002318, 0.5504642];
+        assert_epsilon_eq_vec(&predictions, &expected_predictions, 1e-06);
     }
 
     #[test]
     fn multiclass_logistic_regression_works_when_binary() {
         // Given
-        let intercept = array![0.98];
-        let weights = array![[2.5], [1.2], [1.5], [-0.9]];
+        let intercept = vec![0.98];
+        let weights = vec![vec![2.5, 1.2, 1.5, -0.9]];
+
+        let features = vec![0.4, -2.3, 1.9, 1.3];
+        let regression = MulticlassLogisticRegression::new(intercept, weights).unwrap();
+
+        // When
+        let predictions = regression.run(&features).unwrap();
+
+        // Then
+        let expected_predictions = vec![0.2890504, 0.7109495];
+        assert_epsilon_eq_vec(&predictions, &expected_predictions, 1e-06);
+    }
+
+    #[test]
+    fn contributions_works() {
+        // Given
+        let intercept = vec![0.98, 0.32];
+        let weights = vec![vec![2.5, 1.2, -1.5], vec![-0.5, 2.0, 0.5]];
+        let features = vec![0.4, 0.0, 2.0];
+        let regression = MulticlassLogisticRegression::new(intercept, weights).unwrap();
+        let binary_regression =
+            MulticlassLogisticRegression::new(vec![0.98], vec![vec![2.5, 1.2, -1.5]]).unwrap();
+
+        // When
+        let contributions = regression.contributions(&features, 1).unwrap();
+        let binary_contributions = binary_regression.contributions(&features, 0).unwrap();
+
+        // Then
+        assert_epsilon_eq_vec(&contributions, &vec![-0.2, 0.0, 1.0], 1e-06);
+        assert_epsilon_eq_vec(&binary_contributions, &vec![-1.0, 0.0, 3.0], 1e-06);
+    }
+
+    #[test]
+    fn multiclass_logistic_regression_should_fail_with_wrong_number_of_features() {
+        // Given
+        let intercept = vec![0.98];
+        let weights = vec![vec![2.5, 1.2, 1.5, -0.9]];
 
-        let features = array![0.4, -2.3, 1.9, 1.3];
+        let features = vec![0.4, -2.3];
         let regression = MulticlassLogisticRegression::new(intercept, weights).unwrap();
 
         // When
-        let predictions = regression.run(&features.view()).unwrap();
+        let predictions = regression.run(&features);
+
+        // Then
+        assert!(predictions.is_err());
+    }
+
+    #[test]
+    fn quantized_multiclass_logistic_regression_works() {
+        // Given
+        let intercept = vec![0.98, 0.32, -0.76];
+        let weights = vec![
+            vec![2.5, 1.2, 1.5, -0.9],
+            vec![-0.6, 2.2, 0.1, -2.4],
+            vec![0.5, -2.7, -3.2, 1.8],
+        ];
+
+        let features = vec![0.4, -2.3, 1.9, 1.3];
+        let regression = MulticlassLogisticRegression::new(intercept, weights)
+            .unwrap()
+            .quantize(WeightsQuantization::F16);
+
+        // When
+        let predictions = regression.run(&features).unwrap();
 
         // Then
-        let expected_predictions = array![0.2890504, 0.7109495];
-        assert_epsilon_eq_array1(&predictions, &expected_predictions, 1e-06);
+        let expected_predictions = vec![0.4493038, 0.0002318, 0.5504642];
+        assert_epsilon_eq_vec(&predictions, &expected_predictions, 1e-02);
     }
 }
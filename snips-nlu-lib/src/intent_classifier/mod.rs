@@ -1,13 +1,21 @@
+mod calibration;
+mod embedding_intent_classifier;
 mod featurizer;
+mod intent_selection;
 mod log_reg_intent_classifier;
 mod logreg;
+#[cfg(feature = "onnx")]
+mod onnx_intent_classifier;
 
 use std::collections::HashSet;
 use snips_nlu_ontology::IntentClassifierResult;
 use errors::*;
 
+pub use self::embedding_intent_classifier::EmbeddingIntentClassifier;
 pub use self::log_reg_intent_classifier::LogRegIntentClassifier;
 pub use self::featurizer::Featurizer;
+#[cfg(feature = "onnx")]
+pub use self::onnx_intent_classifier::OnnxIntentClassifier;
 
 pub trait IntentClassifier: Send + Sync {
     fn get_intent(
@@ -15,4 +23,36 @@ pub trait IntentClassifier: Send + Sync {
         input: &str,
         intents_filter: Option<&HashSet<String>>,
     ) -> Result<Option<IntentClassifierResult>>;
+
+    /// Returns the probabilities of the intents allowed by the filter, by decreasing probability,
+    /// the None intent being left out
+    fn get_intents(
+        &self,
+        input: &str,
+        intents_filter: Option<&HashSet<String>>,
+    ) -> Result<Vec<IntentClassifierResult>>;
+
+    /// Returns the input vector of the classifier, if any
+    fn get_features(&self, input: &str) -> Result<Option<Vec<f32>>>;
+
+    /// Returns the features of the input which contributed the most to the score of the intent,
+    /// as (name, contribution) pairs by decreasing contribution, for classifiers which can tell
+    fn get_weighted_features(
+        &self,
+        _input: &str,
+        _intent_name: &str,
+    ) -> Result<Option<Vec<(String, f32)>>> {
+        Ok(None)
+    }
+
+    /// Returns the words known by the classifier, against which the inputs are spell-corrected
+    fn vocabulary(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Returns the estimated number of bytes taken by the weights and the vocabulary of the
+    /// classifier
+    fn heap_size(&self) -> usize {
+        0
+    }
 }
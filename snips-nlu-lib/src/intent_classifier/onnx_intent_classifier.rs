@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use tract_onnx::prelude::*;
+
+use configurations::OnnxIntentClassifierConfiguration;
+use errors::*;
+use intent_classifier::{Featurizer, IntentClassifier};
+use intent_classifier::calibration::Calibration;
+use intent_classifier::intent_selection::{intents_distribution, intern_intent_list,
+                                           select_intent};
+use resource_reader::ResourceReader;
+use snips_nlu_ontology::IntentClassifierResult;
+use symbols::Symbol;
+use tokenizer::Tokenizer;
+
+type OnnxModel = SimplePlan<TypedFact, Box<TypedOp>, TypedModel>;
+
+/// Intent classifier running a neural network exported to the ONNX format, which maps the output
+/// of the featurizer to the probabilities of the intents
+pub struct OnnxIntentClassifier {
+    featurizer: Featurizer,
+    model: OnnxModel,
+    intent_list: Vec<Option<Symbol>>,
+    calibration: Option<Calibration>,
+    none_sensitivity: f32,
+}
+
+impl OnnxIntentClassifier {
+    pub fn new(
+        config: OnnxIntentClassifierConfiguration,
+        none_sensitivity: f32,
+        tokenizer: Arc<Tokenizer>,
+        resource_reader: &ResourceReader,
+    ) -> Result<Self> {
+        let mut model_reader = resource_reader.open(&config.model_path)?;
+        let model = tract_onnx::onnx()
+            .model_for_read(&mut model_reader)
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(|e| format!("Cannot load ONNX model {}: {}", config.model_path, e))?;
+        let nb_intents = config.intent_list.len();
+        let calibration = if let Some(calibration_configs) = config.calibration {
+            Some(Calibration::new(calibration_configs, nb_intents)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            featurizer: Featurizer::new_with_tokenizer(config.featurizer, tokenizer),
+            model,
+            intent_list: intern_intent_list(config.intent_list),
+            calibration,
+            none_sensitivity,
+        })
+    }
+
+    fn probabilities(&self, input: &str) -> Result<Vec<f32>> {
+        let features = self.featurizer.transform(input)?;
+        let input_tensor = Tensor::from_shape(&[1, features.len()], &features)
+            .map_err(|e| format!("Cannot build ONNX model input: {}", e))?;
+        let outputs = self.model
+            .run(TVec::from(vec![input_tensor]))
+            .map_err(|e| format!("Cannot run ONNX model: {}", e))?;
+        let probabilities = outputs
+            .get(0)
+            .ok_or_else(|| "ONNX model has no output")?
+            .as_slice::<f32>()
+            .map_err(|e| format!("Invalid ONNX model output: {}", e))?
+            .to_vec();
+        if probabilities.len() != self.intent_list.len() {
+            bail!("ONNX model output doesn't match the number of intents");
+        }
+        if let Some(calibration) = self.calibration.as_ref() {
+            Ok(calibration.calibrate(&probabilities))
+        } else {
+            Ok(probabilities)
+        }
+    }
+}
+
+impl IntentClassifier for OnnxIntentClassifier {
+    fn get_intent(
+        &self,
+        input: &str,
+        intents_filter: Option<&HashSet<String>>,
+    ) -> Result<Option<IntentClassifierResult>> {
+        if input.is_empty() || self.intent_list.is_empty() {
+            return Ok(None);
+        }
+
+        let probabilities = self.probabilities(input)?;
+        Ok(select_intent(
+            &self.intent_list,
+            &probabilities,
+            intents_filter,
+            self.none_sensitivity,
+        ))
+    }
+
+    fn get_intents(
+        &self,
+        input: &str,
+        intents_filter: Option<&HashSet<String>>,
+    ) -> Result<Vec<IntentClassifierResult>> {
+        if input.is_empty() || self.intent_list.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let probabilities = self.probabilities(input)?;
+        Ok(intents_distribution(
+            &self.intent_list,
+            &probabilities,
+            intents_filter,
+        ))
+    }
+
+    fn get_features(&self, input: &str) -> Result<Option<Vec<f32>>> {
+        Ok(Some(self.featurizer.transform(input)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use configurations::{FeaturizerConfigConfiguration, FeaturizerConfiguration,
+                         TfIdfVectorizerConfiguration};
+    use snips_nlu_ontology::Language;
+    use tokenizer::default_tokenizer;
+    use utils::file_path;
+
+    fn get_sample_onnx_classifier() -> OnnxIntentClassifier {
+        let featurizer = FeaturizerConfiguration {
+            language_code: "en".to_string(),
+            tfidf_vectorizer: TfIdfVectorizerConfiguration {
+                idf_diag: vec![1.0, 1.0],
+                vocab: hashmap!["coffee".to_string() => 0, "tea".to_string() => 1],
+            },
+            config: FeaturizerConfigConfiguration { sublinear_tf: false },
+            best_features: vec![0, 1],
+            entity_utterances_to_feature_names: hashmap![],
+        };
+        let config = OnnxIntentClassifierConfiguration {
+            model_path: file_path("tests/onnx_model/intent_classifier.onnx")
+                .to_string_lossy()
+                .into_owned(),
+            featurizer,
+            intent_list: vec![
+                Some("MakeCoffee".to_string()),
+                Some("MakeTea".to_string()),
+                None,
+            ],
+            calibration: None,
+        };
+        OnnxIntentClassifier::new(
+            config,
+            1.0,
+            default_tokenizer(Language::EN),
+            &ResourceReader::default(),
+        ).unwrap()
+    }
+
+    #[test]
+    fn get_intent_works() {
+        // Given
+        let classifier = get_sample_onnx_classifier();
+
+        // When
+        let coffee_intent = classifier.get_intent("a coffee please", None).unwrap();
+        let none_intent = classifier.get_intent("hello", None).unwrap();
+
+        // Then
+        assert_eq!(
+            Some("MakeCoffee"),
+            coffee_intent.as_ref().map(|res| &*res.intent_name)
+        );
+        assert_eq!(None, none_intent);
+    }
+
+    #[test]
+    fn get_intents_works() {
+        // Given
+        let classifier = get_sample_onnx_classifier();
+
+        // When
+        let intents = classifier.get_intents("some tea", None).unwrap();
+
+        // Then
+        let intent_names = intents
+            .iter()
+            .map(|res| &*res.intent_name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["MakeTea", "MakeCoffee"], intent_names);
+    }
+}
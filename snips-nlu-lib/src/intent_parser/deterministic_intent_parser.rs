@@ -1,61 +1,176 @@
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::ops::Range;
 use std::str::FromStr;
 use std::sync::Arc;
 
 use itertools::Itertools;
-use regex::{Regex, RegexBuilder};
+use regex::Regex;
 
+use builtin_entity_parser::MultilingualBuiltinEntityParser;
 use errors::*;
-use intent_parser::IntentParser;
+use intent_parser::{IntentExplanation, IntentParser};
+use intent_parser::regex_cache::LazyRegexes;
+use intent_parser::rules::compile_rule;
+use memory_footprint::{HeapSize, MemoryFootprint};
+use parse_context::ParseContext;
 use configurations::DeterministicParserConfiguration;
-use language::FromLanguage;
 use nlu_utils::language::Language as NluUtilsLanguage;
 use nlu_utils::range::ranges_overlap;
-use nlu_utils::string::{convert_to_char_range, substring_with_char_range, suffix_from_char_index};
-use nlu_utils::token::{tokenize, tokenize_light};
+use nlu_utils::string::convert_to_char_range;
+use nlu_utils::token::tokenize_light;
 use slot_utils::*;
-use snips_nlu_ontology::{BuiltinEntityKind, BuiltinEntityParser, IntentClassifierResult, Language,
+use symbols::Symbol;
+use snips_nlu_ontology::{BuiltinEntity, BuiltinEntityKind, IntentClassifierResult, Language,
                          Slot};
+use tokenizer::{default_tokenizer, Tokenizer};
+use utils::{align_on_grapheme_boundaries, grapheme_boundaries, substring_with_char_range,
+            suffix_from_char_index};
+
+lazy_static! {
+    /// Parts of the patterns which are not literal words: group names, escaped chars and
+    /// placeholders of builtin entities
+    static ref PATTERN_SYNTAX_REGEX: Regex = Regex::new(r"\(\?P<\w+>|\\.|%[A-Z0-9]+%").unwrap();
+    static ref PATTERN_WORD_REGEX: Regex = Regex::new(r"[^\W\d_]+").unwrap();
+    static ref GROUP_NAME_REGEX: Regex = Regex::new(r"\(\?P<(\w+)>").unwrap();
+}
 
 pub struct DeterministicIntentParser {
-    regexes_per_intent: HashMap<String, Vec<Regex>>,
-    group_names_to_slot_names: HashMap<String, String>,
-    slot_names_to_entities: HashMap<String, String>,
-    builtin_entity_parser: Option<Arc<BuiltinEntityParser>>,
-    language: Language,
+    regexes_per_intent: HashMap<Symbol, LazyRegexes>,
+    group_names_to_slot_names: HashMap<String, Symbol>,
+    slot_names_to_entities: HashMap<Symbol, Symbol>,
+    /// Builtin entity kinds tagged in the inputs for each intent, sorted by identifier, `None`
+    /// when all the kinds are tagged
+    builtin_entity_scopes: Option<HashMap<Symbol, Vec<BuiltinEntityKind>>>,
+    /// Builtin entity kinds of all the scopes, which are extracted at once from the inputs and
+    /// then filtered by intent
+    scoped_builtin_entity_kinds: Vec<BuiltinEntityKind>,
+    builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+    tokenizer: Arc<Tokenizer>,
 }
 
 impl DeterministicIntentParser {
-    pub fn new(configuration: DeterministicParserConfiguration) -> Result<Self> {
-        let builtin_entity_parser = Language::from_str(&configuration.language_code)
-            .ok()
-            .map(BuiltinEntityParser::get);
+    pub fn new(
+        configuration: DeterministicParserConfiguration,
+        builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+    ) -> Result<Self> {
         let language = Language::from_str(&configuration.language_code)?;
+        Self::new_with_tokenizer(configuration, builtin_entity_parser, default_tokenizer(language))
+    }
+
+    pub fn new_with_tokenizer(
+        configuration: DeterministicParserConfiguration,
+        builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+        tokenizer: Arc<Tokenizer>,
+    ) -> Result<Self> {
+        let mut patterns = configuration.patterns;
+        let mut group_names_to_slot_names = configuration.group_names_to_slot_names;
+        for (intent, rules) in configuration.rules.unwrap_or_else(HashMap::new) {
+            for rule in rules {
+                let pattern = compile_rule(
+                    &rule,
+                    &configuration.slot_names_to_entities,
+                    &mut group_names_to_slot_names,
+                )?;
+                patterns
+                    .entry(intent.clone())
+                    .or_insert_with(Vec::new)
+                    .push(pattern);
+            }
+        }
+
+        let builtin_entity_scopes: Option<HashMap<Symbol, Vec<BuiltinEntityKind>>> =
+            if configuration.tag_all_builtin_entities {
+                None
+            } else {
+                Some(
+                    patterns
+                        .iter()
+                        .map(|(intent, intent_patterns)| {
+                            let kinds = intent_patterns
+                                .iter()
+                                .flat_map(|pattern| GROUP_NAME_REGEX.captures_iter(pattern))
+                                .filter_map(|caps| group_names_to_slot_names.get(&caps[1]))
+                                .filter_map(|slot_name| {
+                                    configuration.slot_names_to_entities.get(slot_name)
+                                })
+                                .flat_map(|entity| BuiltinEntityKind::from_identifier(entity).ok());
+                            (intent.as_str().into(), sorted_kinds(kinds))
+                        })
+                        .collect(),
+                )
+            };
+        let scoped_builtin_entity_kinds = builtin_entity_scopes
+            .as_ref()
+            .map(|scopes| sorted_kinds(scopes.values().flat_map(|kinds| kinds.iter().cloned())))
+            .unwrap_or_else(Vec::new);
+
+        let regexes_per_intent = patterns
+            .into_iter()
+            .map(|(intent, patterns)| Ok((intent.into(), LazyRegexes::new(patterns)?)))
+            .collect::<Result<_>>()?;
 
         Ok(DeterministicIntentParser {
-            regexes_per_intent: compile_regexes_per_intent(configuration.patterns)?,
-            group_names_to_slot_names: configuration.group_names_to_slot_names,
-            slot_names_to_entities: configuration.slot_names_to_entities,
+            regexes_per_intent,
+            group_names_to_slot_names: group_names_to_slot_names
+                .into_iter()
+                .map(|(group_name, slot_name)| (group_name, slot_name.into()))
+                .collect(),
+            slot_names_to_entities: configuration
+                .slot_names_to_entities
+                .into_iter()
+                .map(|(slot_name, entity)| (slot_name.into(), entity.into()))
+                .collect(),
+            builtin_entity_scopes,
+            scoped_builtin_entity_kinds,
             builtin_entity_parser,
-            language,
+            tokenizer,
         })
     }
-}
 
-fn compile_regexes_per_intent(
-    patterns: HashMap<String, Vec<String>>,
-) -> Result<HashMap<String, Vec<Regex>>> {
-    patterns
-        .into_iter()
-        .map(|(intent, patterns)| {
-            let regexes: Result<_> = patterns
-                .into_iter()
-                .map(|p| Ok(RegexBuilder::new(&p).case_insensitive(true).build()?))
-                .collect();
-            Ok((intent, regexes?))
+    /// Builtin entity kinds to tag in the inputs parsed with the patterns of the intent
+    fn builtin_entity_scope(&self, intent_name: &str) -> Option<&[BuiltinEntityKind]> {
+        self.builtin_entity_scopes.as_ref().map(|scopes| {
+            scopes
+                .get(intent_name)
+                .map(|kinds| &**kinds)
+                .unwrap_or(&[])
         })
-        .collect()
+    }
+
+    fn format_input<'a>(
+        &self,
+        input: &'a str,
+        scope: Option<&[BuiltinEntityKind]>,
+        context: &ParseContext,
+    ) -> (HashMap<Range<usize>, Range<usize>>, Cow<'a, str>) {
+        let builtin_entity_parser =
+            context.builtin_entity_parser(self.builtin_entity_parser.as_ref());
+        let builtin_entity_parser = match builtin_entity_parser {
+            Some(parser) if scope.map_or(true, |kinds| !kinds.is_empty()) => parser,
+            _ => return (HashMap::new(), Cow::Borrowed(input)),
+        };
+        // The entities of all the scopes are extracted once, the context keeping them for the
+        // other intents
+        let filter_entity_kinds = self.builtin_entity_scopes
+            .as_ref()
+            .map(|_| &*self.scoped_builtin_entity_kinds);
+        let builtin_entities = context
+            .extract_builtin_entities(builtin_entity_parser, input, filter_entity_kinds)
+            .into_iter()
+            .filter(|entity| scope.map_or(true, |kinds| kinds.contains(&entity.entity_kind)))
+            .collect();
+        replace_builtin_entities(input, builtin_entities)
+    }
+}
+
+/// Sorts the builtin entity kinds by identifier and removes the duplicates, so that scopes with
+/// the same kinds are equal
+fn sorted_kinds<I: Iterator<Item = BuiltinEntityKind>>(kinds: I) -> Vec<BuiltinEntityKind> {
+    let mut kinds = kinds.collect_vec();
+    kinds.sort_by_key(|kind| kind.identifier());
+    kinds.dedup();
+    kinds
 }
 
 impl IntentParser for DeterministicIntentParser {
@@ -63,46 +178,49 @@ impl IntentParser for DeterministicIntentParser {
         &self,
         input: &str,
         intents: Option<&HashSet<String>>,
+        context: &ParseContext,
     ) -> Result<Option<IntentClassifierResult>> {
-        let formatted_input =
-            if let Some(builtin_entity_parser) = self.builtin_entity_parser.as_ref() {
-                replace_builtin_entities(input, &*builtin_entity_parser).1
+        let candidate_intents = self.regexes_per_intent.iter().filter(|&(intent, _)| {
+            if let Some(intent_set) = intents {
+                intent_set.contains(&**intent)
             } else {
-                input.to_string()
-            };
-        Ok(self.regexes_per_intent
-            .iter()
-            .filter(|&(intent, _)| {
-                if let Some(intent_set) = intents {
-                    intent_set.contains(intent)
-                } else {
-                    true
-                }
-            })
-            .find(|&(_, regexes)| regexes.iter().any(|r| r.is_match(&formatted_input)))
-            .map(|(intent_name, _)| IntentClassifierResult {
-                intent_name: intent_name.to_string(),
-                probability: 1.0,
-            }))
+                true
+            }
+        });
+        // Intents tagging the same builtin entity kinds share the formatted input
+        let mut formatted_inputs: HashMap<Option<&[BuiltinEntityKind]>, Cow<str>> =
+            HashMap::new();
+        for (intent_name, regexes) in candidate_intents {
+            let scope = self.builtin_entity_scope(intent_name);
+            let formatted_input = formatted_inputs
+                .entry(scope)
+                .or_insert_with(|| self.format_input(input, scope, context).1);
+            if regexes.get()?.is_match(&*formatted_input) {
+                return Ok(Some(IntentClassifierResult {
+                    intent_name: intent_name.to_string(),
+                    probability: 1.0,
+                }));
+            }
+        }
+        Ok(None)
     }
 
-    fn get_slots(&self, input: &str, intent_name: &str) -> Result<Vec<Slot>> {
-        let regexes = self.regexes_per_intent
+    fn get_slots(
+        &self,
+        input: &str,
+        intent_name: &str,
+        context: &ParseContext,
+    ) -> Result<Vec<Slot>> {
+        let compiled_patterns = self.regexes_per_intent
             .get(intent_name)
-            .ok_or_else(|| format!("intent {:?} not found", intent_name))?;
+            .ok_or_else(|| format!("intent {:?} not found", intent_name))?
+            .get()?;
 
         let (ranges_mapping, formatted_input) =
-            if let Some(builtin_entity_parser) = self.builtin_entity_parser.as_ref() {
-                replace_builtin_entities(input, &*builtin_entity_parser)
-            } else {
-                (
-                    HashMap::<Range<usize>, Range<usize>>::new(),
-                    input.to_string(),
-                )
-            };
+            self.format_input(input, self.builtin_entity_scope(intent_name), context);
 
         let mut result = vec![];
-        for regex in regexes {
+        for regex in compiled_patterns.matching_regexes(&formatted_input) {
             for caps in regex.captures_iter(&formatted_input) {
                 if caps.len() == 0 {
                     continue;
@@ -122,20 +240,20 @@ impl IntentParser for DeterministicIntentParser {
                         let matched_range = convert_to_char_range(&formatted_input, &byte_range);
                         let (value, range) = if let Some(rng) = ranges_mapping.get(&matched_range) {
                             (
-                                substring_with_char_range(input.to_string(), rng),
+                                substring_with_char_range(input, rng).to_string(),
                                 rng.clone(),
                             )
                         } else {
                             (a_match.as_str().into(), matched_range)
                         };
-                        let slot_name = self.group_names_to_slot_names[group_name].to_string();
-                        let entity = self.slot_names_to_entities[&slot_name].to_string();
+                        let slot_name = &self.group_names_to_slot_names[group_name];
+                        let entity = self.slot_names_to_entities[slot_name].to_string();
 
                         InternalSlot {
                             value,
                             char_range: range,
                             entity,
-                            slot_name,
+                            slot_name: slot_name.to_string(),
                         }
                     })
                     .foreach(|slot| {
@@ -144,8 +262,10 @@ impl IntentParser for DeterministicIntentParser {
                 break;
             }
         }
-        let deduplicated_slots = deduplicate_overlapping_slots(result, self.language);
-        if let Some(builtin_entity_parser) = self.builtin_entity_parser.as_ref() {
+        let deduplicated_slots = deduplicate_overlapping_slots(result, &*self.tokenizer);
+        let builtin_entity_parser =
+            context.builtin_entity_parser(self.builtin_entity_parser.as_ref());
+        if let Some(builtin_entity_parser) = builtin_entity_parser {
             let filter_entity_kinds = self.slot_names_to_entities
                 .values()
                 .flat_map(|entity_name| BuiltinEntityKind::from_identifier(entity_name).ok())
@@ -154,7 +274,7 @@ impl IntentParser for DeterministicIntentParser {
             Ok(resolve_builtin_slots(
                 input,
                 deduplicated_slots,
-                &*builtin_entity_parser,
+                builtin_entity_parser,
                 Some(&*filter_entity_kinds),
             ))
         } else {
@@ -170,14 +290,59 @@ impl IntentParser for DeterministicIntentParser {
                 .collect())
         }
     }
+
+    fn explain_intent(
+        &self,
+        input: &str,
+        intent_name: &str,
+        context: &ParseContext,
+    ) -> Result<Option<IntentExplanation>> {
+        let compiled_patterns = if let Some(regexes) = self.regexes_per_intent.get(intent_name) {
+            regexes.get()?
+        } else {
+            return Ok(None);
+        };
+        let scope = self.builtin_entity_scope(intent_name);
+        let formatted_input = self.format_input(input, scope, context).1;
+        Ok(compiled_patterns
+            .matching_regexes(&formatted_input)
+            .first()
+            .map(|regex| IntentExplanation::MatchedPattern(regex.as_str().to_string())))
+    }
+
+    fn vocabulary(&self) -> Vec<String> {
+        self.regexes_per_intent
+            .values()
+            .flat_map(|regexes| regexes.patterns().iter())
+            .flat_map(|pattern| pattern_words(pattern))
+            .unique()
+            .collect()
+    }
+
+    fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint {
+            rule_patterns: self.regexes_per_intent.heap_size()
+                + self.group_names_to_slot_names.heap_size()
+                + self.slot_names_to_entities.heap_size(),
+            ..MemoryFootprint::default()
+        }
+    }
+}
+
+/// Returns the words matched literally by a pattern, in lowercase
+fn pattern_words(pattern: &str) -> Vec<String> {
+    let literal_text = PATTERN_SYNTAX_REGEX.replace_all(pattern, " ");
+    PATTERN_WORD_REGEX
+        .find_iter(&literal_text)
+        .map(|word| word.as_str().to_lowercase())
+        .collect()
 }
 
 fn deduplicate_overlapping_slots(
     slots: Vec<InternalSlot>,
-    language: Language,
+    tokenizer: &Tokenizer,
 ) -> Vec<InternalSlot> {
     let mut deduped: Vec<InternalSlot> = Vec::with_capacity(slots.len());
-    let language = NluUtilsLanguage::from_language(language);
 
     for slot in slots {
         let conflicting_slot_index = deduped
@@ -185,12 +350,12 @@ fn deduplicate_overlapping_slots(
             .position(|existing_slot| ranges_overlap(&slot.char_range, &existing_slot.char_range));
 
         if let Some(index) = conflicting_slot_index {
-            fn extract_counts(v: &InternalSlot, l: NluUtilsLanguage) -> (usize, usize) {
-                (tokenize(&v.value, l).len(), v.value.chars().count())
+            fn extract_counts(v: &InternalSlot, tokenizer: &Tokenizer) -> (usize, usize) {
+                (tokenizer.tokenize(&v.value).len(), v.value.chars().count())
             }
             let (existing_token_count, existing_char_count) =
-                extract_counts(&deduped[index], language);
-            let (token_count, char_count) = extract_counts(&slot, language);
+                extract_counts(&deduped[index], tokenizer);
+            let (token_count, char_count) = extract_counts(&slot, tokenizer);
 
             if token_count > existing_token_count
                 || (token_count == existing_token_count && char_count > existing_char_count)
@@ -207,40 +372,41 @@ fn deduplicate_overlapping_slots(
 
 fn replace_builtin_entities(
     text: &str,
-    parser: &BuiltinEntityParser,
-) -> (HashMap<Range<usize>, Range<usize>>, String) {
-    let builtin_entities = parser.extract_entities(text, None);
+    builtin_entities: Vec<BuiltinEntity>,
+) -> (HashMap<Range<usize>, Range<usize>>, Cow<str>) {
     if builtin_entities.is_empty() {
-        return (HashMap::new(), text.to_string());
+        return (HashMap::new(), Cow::Borrowed(text));
     }
 
     let mut range_mapping: HashMap<Range<usize>, Range<usize>> = HashMap::new();
-    let mut processed_text = "".to_string();
+    let mut processed_text = String::with_capacity(text.len());
     let mut offset = 0;
     let mut current_ix = 0;
+    let boundaries = grapheme_boundaries(text);
 
     for entity in builtin_entities {
-        let range_start = (entity.range.start as i16 + offset) as usize;
-        let prefix_text =
-            substring_with_char_range(text.to_string(), &(current_ix..entity.range.start));
+        // Entities are replaced along with the whole grapheme clusters they overlap
+        let entity_range = align_on_grapheme_boundaries(&boundaries, &entity.range);
+        if entity_range.start < current_ix {
+            continue;
+        }
+        let range_start = (entity_range.start as i16 + offset) as usize;
+        let prefix_text = substring_with_char_range(text, &(current_ix..entity_range.start));
         let entity_text = get_builtin_entity_name(entity.entity_kind.identifier());
-        processed_text = format!("{}{}{}", processed_text, prefix_text, entity_text);
-        offset += entity_text.chars().count() as i16 - entity.range.clone().count() as i16;
-        let range_end = (entity.range.end as i16 + offset) as usize;
+        processed_text.push_str(prefix_text);
+        processed_text.push_str(&entity_text);
+        offset += entity_text.chars().count() as i16 - entity_range.clone().count() as i16;
+        let range_end = (entity_range.end as i16 + offset) as usize;
         let new_range = range_start..range_end;
-        current_ix = entity.range.end;
-        range_mapping.insert(new_range, entity.range);
+        current_ix = entity_range.end;
+        range_mapping.insert(new_range, entity_range);
     }
 
-    processed_text = format!(
-        "{}{}",
-        processed_text,
-        suffix_from_char_index(text.to_string(), current_ix)
-    );
-    (range_mapping, processed_text)
+    processed_text.push_str(suffix_from_char_index(text, current_ix));
+    (range_mapping, Cow::Owned(processed_text))
 }
 
-fn get_builtin_entity_name(entity_label: &str) -> String {
+pub fn get_builtin_entity_name(entity_label: &str) -> String {
     // Here we don't need language specific tokenization, we just want to generate a feature name, that's why we use EN
     let normalized_entity_label = tokenize_light(entity_label, NluUtilsLanguage::EN)
         .join("")
@@ -253,11 +419,13 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
     use std::iter::FromIterator;
-    use snips_nlu_ontology::{AmountOfMoneyValue, BuiltinEntityParser, IntentClassifierResult,
-                             Language, Precision, Slot, SlotValue};
+    use snips_nlu_ontology::{AmountOfMoneyValue, IntentClassifierResult, Language, Precision,
+                             Slot, SlotValue};
+    use builtin_entity_parser::MultilingualBuiltinEntityParser;
     use configurations::DeterministicParserConfiguration;
     use intent_parser::IntentParser;
     use slot_utils::InternalSlot;
+    use tokenizer::DefaultTokenizer;
 
     fn test_configuration() -> DeterministicParserConfiguration {
         DeterministicParserConfiguration {
@@ -292,17 +460,25 @@ mod tests {
                 "dummy_slot_name2".to_string() => "dummy_entity_2".to_string(),
                 "dummy_slot_name4".to_string() => "snips/amountOfMoney".to_string(),
             ],
+            rules: None,
+            tag_all_builtin_entities: false,
         }
     }
 
+    fn test_builtin_entity_parser() -> Option<Arc<MultilingualBuiltinEntityParser>> {
+        Some(Arc::new(MultilingualBuiltinEntityParser::new(&[Language::EN])))
+    }
+
     #[test]
     fn should_get_intent() {
         // Given
-        let parser = DeterministicIntentParser::new(test_configuration()).unwrap();
+        let parser =
+            DeterministicIntentParser::new(test_configuration(), test_builtin_entity_parser())
+                .unwrap();
         let text = "this is a dummy_a query with another dummy_c";
 
         // When
-        let intent = parser.get_intent(text, None).unwrap().unwrap();
+        let intent = parser.get_intent(text, None, &ParseContext::default()).unwrap().unwrap();
 
         // Then
         let expected_intent = IntentClassifierResult {
@@ -313,14 +489,38 @@ mod tests {
         assert_eq!(intent, expected_intent);
     }
 
+    #[test]
+    fn explain_intent_should_return_matched_pattern() {
+        // Given
+        let parser =
+            DeterministicIntentParser::new(test_configuration(), test_builtin_entity_parser())
+                .unwrap();
+        let text = "Send 10 dollars to John";
+
+        // When
+        let context = ParseContext::default();
+        let explanation = parser.explain_intent(text, "dummy_intent_3", &context).unwrap();
+        let unknown_intent_explanation =
+            parser.explain_intent(text, "dummy_intent_2", &context).unwrap();
+
+        // Then
+        let expected_explanation = Some(IntentExplanation::MatchedPattern(
+            r"^Send (?P<group_6>%SNIPSAMOUNTOFMONEY%) to john$".to_string(),
+        ));
+        assert_eq!(expected_explanation, explanation);
+        assert_eq!(None, unknown_intent_explanation);
+    }
+
     #[test]
     fn should_get_intent_with_builtin_entity() {
         // Given
-        let parser = DeterministicIntentParser::new(test_configuration()).unwrap();
+        let parser =
+            DeterministicIntentParser::new(test_configuration(), test_builtin_entity_parser())
+                .unwrap();
         let text = "Send 10 dollars to John";
 
         // When
-        let intent = parser.get_intent(text, None).unwrap();
+        let intent = parser.get_intent(text, None, &ParseContext::default()).unwrap();
 
         // Then
         let expected_intent = Some(IntentClassifierResult {
@@ -334,11 +534,13 @@ mod tests {
     #[test]
     fn should_get_slots() {
         // Given
-        let parser = DeterministicIntentParser::new(test_configuration()).unwrap();
+        let parser =
+            DeterministicIntentParser::new(test_configuration(), test_builtin_entity_parser())
+                .unwrap();
         let text = "this is a dummy_a query with another dummy_c";
 
         // When
-        let slots = parser.get_slots(text, "dummy_intent_1").unwrap();
+        let slots = parser.get_slots(text, "dummy_intent_1", &ParseContext::default()).unwrap();
 
         // Then
         let expected_slots = vec![
@@ -361,11 +563,13 @@ mod tests {
     #[test]
     fn should_get_slots_with_non_ascii_chars() {
         // Given
-        let parser = DeterministicIntentParser::new(test_configuration()).unwrap();
+        let parser =
+            DeterministicIntentParser::new(test_configuration(), test_builtin_entity_parser())
+                .unwrap();
         let text = "This is another über dummy_cc query!";
 
         // When
-        let slots = parser.get_slots(text, "dummy_intent_1").unwrap();
+        let slots = parser.get_slots(text, "dummy_intent_1", &ParseContext::default()).unwrap();
 
         // Then
         let expected_slots = vec![
@@ -382,11 +586,13 @@ mod tests {
     #[test]
     fn should_get_slots_with_builtin_entity() {
         // Given
-        let parser = DeterministicIntentParser::new(test_configuration()).unwrap();
+        let parser =
+            DeterministicIntentParser::new(test_configuration(), test_builtin_entity_parser())
+                .unwrap();
         let text = "Send 10 dollars to John";
 
         // When
-        let slots = parser.get_slots(text, "dummy_intent_3").unwrap();
+        let slots = parser.get_slots(text, "dummy_intent_3", &ParseContext::default()).unwrap();
 
         // Then
         let expected_slots = vec![
@@ -405,10 +611,95 @@ mod tests {
         assert_eq!(slots, expected_slots);
     }
 
+    #[test]
+    fn should_get_intent_and_slots_with_rules() {
+        // Given
+        let mut configuration = test_configuration();
+        configuration.slot_names_to_entities.insert(
+            "beverage_temperature".to_string(),
+            "Temperature".to_string(),
+        );
+        configuration.rules = Some(hashmap![
+            "MakeTea".to_string() => vec![
+                "[please] make me *2 {beverage_temperature} tea [please]".to_string()
+            ],
+        ]);
+        let parser = DeterministicIntentParser::new(configuration, None).unwrap();
+        let text = "Please make me some hot tea";
+
+        // When
+        let intent = parser.get_intent(text, None, &ParseContext::default()).unwrap();
+        let slots = parser.get_slots(text, "MakeTea", &ParseContext::default()).unwrap();
+
+        // Then
+        let expected_intent = Some(IntentClassifierResult {
+            intent_name: "MakeTea".to_string(),
+            probability: 1.0,
+        });
+        let expected_slots = vec![
+            Slot::new_custom(
+                "hot".to_string(),
+                20..23,
+                "Temperature".to_string(),
+                "beverage_temperature".to_string(),
+            ),
+        ];
+        assert_eq!(expected_intent, intent);
+        assert_eq!(expected_slots, slots);
+    }
+
+    #[test]
+    fn should_only_tag_builtin_entities_of_the_intent_slots() {
+        // Given
+        let mut configuration = test_configuration();
+        configuration.patterns.insert(
+            "BookRooms".to_string(),
+            vec![
+                r"^Book (?P<group_7>%SNIPSNUMBER%) rooms for tomorrow$".to_string(),
+                r"^Book (?P<group_7>%SNIPSNUMBER%) rooms$".to_string(),
+            ],
+        );
+        configuration
+            .group_names_to_slot_names
+            .insert("group_7".to_string(), "number_of_rooms".to_string());
+        configuration
+            .slot_names_to_entities
+            .insert("number_of_rooms".to_string(), "snips/number".to_string());
+        let mut tag_all_configuration = configuration.clone();
+        tag_all_configuration.tag_all_builtin_entities = true;
+        let parser =
+            DeterministicIntentParser::new(configuration, test_builtin_entity_parser()).unwrap();
+        let tag_all_parser =
+            DeterministicIntentParser::new(tag_all_configuration, test_builtin_entity_parser())
+                .unwrap();
+        let text = "Book 3 rooms for tomorrow";
+
+        // When
+        let context = ParseContext::default();
+        let intent = parser.get_intent(text, None, &context).unwrap();
+        let tag_all_intent = tag_all_parser.get_intent(text, None, &context).unwrap();
+
+        // Then
+        let expected_intent = Some(IntentClassifierResult {
+            intent_name: "BookRooms".to_string(),
+            probability: 1.0,
+        });
+        assert_eq!(expected_intent, intent);
+        assert_eq!(None, tag_all_intent);
+        assert_eq!(
+            Some(&[BuiltinEntityKind::Number][..]),
+            parser.builtin_entity_scope("BookRooms")
+        );
+        assert_eq!(
+            vec![BuiltinEntityKind::AmountOfMoney, BuiltinEntityKind::Number],
+            parser.scoped_builtin_entity_kinds
+        );
+    }
+
     #[test]
     fn should_deduplicate_overlapping_slots() {
         // Given
-        let language = Language::EN;
+        let tokenizer = DefaultTokenizer::new(Language::EN);
         let slots = vec![
             InternalSlot {
                 value: "non_overlapping1".to_string(),
@@ -443,7 +734,7 @@ mod tests {
         ];
 
         // When
-        let deduplicated_slots = deduplicate_overlapping_slots(slots, language);
+        let deduplicated_slots = deduplicate_overlapping_slots(slots, &tokenizer);
 
         // Then
         let expected_slots = vec![
@@ -473,10 +764,11 @@ mod tests {
     fn should_replace_builtin_entities() {
         // Given
         let text = "Meeting this evening or tomorrow at 11am !";
-        let parser = BuiltinEntityParser::get(Language::EN);
+        let parser = MultilingualBuiltinEntityParser::new(&[Language::EN]);
+        let builtin_entities = parser.extract_entities(text, None);
 
         // When
-        let (range_mapping, formatted_text) = replace_builtin_entities(text, &*parser);
+        let (range_mapping, formatted_text) = replace_builtin_entities(text, builtin_entities);
 
         // Then
         let expected_mapping =
@@ -484,7 +776,7 @@ mod tests {
 
         let expected_text = "Meeting %SNIPSDATETIME% or %SNIPSDATETIME% !";
         assert_eq!(expected_mapping, range_mapping);
-        assert_eq!(expected_text, &formatted_text);
+        assert_eq!(expected_text, &*formatted_text);
     }
 
     #[test]
@@ -498,4 +790,19 @@ mod tests {
         // Then
         assert_eq!("%SNIPSDATETIME%", &formatted_label)
     }
+
+    #[test]
+    fn pattern_words_works() {
+        // Given
+        let pattern = concat!(
+            r"^\s*Make(?:^\s*|\s+)(?P<group_1>%SNIPSNUMBER%)\s+cups?\s+of",
+            r"\s+(?P<group_2>hot|cold)\s+tea\?\s*$"
+        );
+
+        // When
+        let words = pattern_words(pattern);
+
+        // Then
+        assert_eq!(vec!["make", "cups", "of", "hot", "cold", "tea"], words);
+    }
 }
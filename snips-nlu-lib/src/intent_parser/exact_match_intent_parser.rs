@@ -0,0 +1,395 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use itertools::Itertools;
+
+use builtin_entity_parser::MultilingualBuiltinEntityParser;
+use configurations::{ExactMatchParserConfiguration, UtteranceChunk};
+use errors::*;
+use intent_parser::IntentParser;
+use memory_footprint::{HeapSize, MemoryFootprint};
+use nlu_utils::string::normalize;
+use nlu_utils::token::Token;
+use parse_context::ParseContext;
+use slot_utils::*;
+use snips_nlu_ontology::{BuiltinEntityKind, IntentClassifierResult, Language, Slot};
+use tokenizer::{default_tokenizer, Tokenizer};
+
+/// Intent parser which recognizes the utterances seen during training verbatim, modulo case,
+/// accents and punctuation, as well as inputs containing one of the configured keyword sets
+pub struct ExactMatchIntentParser {
+    utterances: HashMap<String, ExactMatchUtterance>,
+    keywords: Vec<(String, HashSet<String>)>,
+    builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+    tokenizer: Arc<Tokenizer>,
+}
+
+struct ExactMatchUtterance {
+    intent: String,
+    slots: Vec<UtteranceSlot>,
+}
+
+struct UtteranceSlot {
+    tokens_range: Range<usize>,
+    entity: String,
+    slot_name: String,
+}
+
+impl HeapSize for ExactMatchUtterance {
+    fn heap_size(&self) -> usize {
+        self.intent.heap_size() + self.slots.heap_size()
+    }
+}
+
+impl HeapSize for UtteranceSlot {
+    fn heap_size(&self) -> usize {
+        self.entity.heap_size() + self.slot_name.heap_size()
+    }
+}
+
+impl ExactMatchIntentParser {
+    pub fn new(
+        configuration: ExactMatchParserConfiguration,
+        builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+    ) -> Result<Self> {
+        let language = Language::from_str(&configuration.language_code)?;
+        Self::new_with_tokenizer(configuration, builtin_entity_parser, default_tokenizer(language))
+    }
+
+    pub fn new_with_tokenizer(
+        configuration: ExactMatchParserConfiguration,
+        builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+        tokenizer: Arc<Tokenizer>,
+    ) -> Result<Self> {
+        let mut utterances: HashMap<String, ExactMatchUtterance> = HashMap::new();
+        let mut ambiguous_utterances: HashSet<String> = HashSet::new();
+        for (intent, intent_utterances) in configuration.utterances {
+            for chunks in intent_utterances {
+                let (key, slots) = index_utterance(&chunks, &*tokenizer)?;
+                let is_ambiguous = utterances
+                    .get(&key)
+                    .map(|existing| existing.intent != intent)
+                    .unwrap_or(false);
+                if is_ambiguous {
+                    ambiguous_utterances.insert(key);
+                } else {
+                    utterances.insert(
+                        key,
+                        ExactMatchUtterance {
+                            intent: intent.clone(),
+                            slots,
+                        },
+                    );
+                }
+            }
+        }
+        // Utterances shared by several intents can't be matched reliably
+        for key in ambiguous_utterances {
+            utterances.remove(&key);
+        }
+
+        let keywords = configuration
+            .keywords
+            .unwrap_or_else(HashMap::new)
+            .into_iter()
+            .flat_map(|(intent, keyword_sets)| {
+                keyword_sets.into_iter().map(move |keyword_set| {
+                    let normalized_keywords: HashSet<String> =
+                        keyword_set.iter().map(|k| normalize(k)).collect();
+                    (intent.clone(), normalized_keywords)
+                })
+            })
+            .sorted_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(Self {
+            utterances,
+            keywords,
+            builtin_entity_parser,
+            tokenizer,
+        })
+    }
+
+    fn match_utterance(&self, tokens: &[Token]) -> Option<&ExactMatchUtterance> {
+        let key = tokens.iter().map(|t| normalize(&t.value)).join(" ");
+        self.utterances.get(&key)
+    }
+
+    fn match_keywords(&self, tokens: &[Token], intents: Option<&HashSet<String>>) -> Option<&str> {
+        let normalized_tokens: HashSet<String> =
+            tokens.iter().map(|t| normalize(&t.value)).collect();
+        self.keywords
+            .iter()
+            .filter(|&&(ref intent, _)| intents.map(|i| i.contains(intent)).unwrap_or(true))
+            .find(|&&(_, ref keyword_set)| keyword_set.is_subset(&normalized_tokens))
+            .map(|&(ref intent, _)| &**intent)
+    }
+}
+
+fn index_utterance(
+    chunks: &[UtteranceChunk],
+    tokenizer: &Tokenizer,
+) -> Result<(String, Vec<UtteranceSlot>)> {
+    let mut normalized_tokens: Vec<String> = vec![];
+    let mut slots: Vec<UtteranceSlot> = vec![];
+    for chunk in chunks {
+        let chunk_tokens = tokenizer.tokenize(&chunk.text);
+        let start = normalized_tokens.len();
+        normalized_tokens.extend(chunk_tokens.iter().map(|t| normalize(&t.value)));
+        if let (Some(entity), Some(slot_name)) = (chunk.entity.as_ref(), chunk.slot_name.as_ref())
+        {
+            if chunk_tokens.is_empty() {
+                bail!("Slot '{}' has no value in exact match utterance", slot_name);
+            }
+            slots.push(UtteranceSlot {
+                tokens_range: start..normalized_tokens.len(),
+                entity: entity.to_string(),
+                slot_name: slot_name.to_string(),
+            });
+        }
+    }
+    Ok((normalized_tokens.join(" "), slots))
+}
+
+impl IntentParser for ExactMatchIntentParser {
+    fn get_intent(
+        &self,
+        input: &str,
+        intents: Option<&HashSet<String>>,
+        _context: &ParseContext,
+    ) -> Result<Option<IntentClassifierResult>> {
+        let tokens = self.tokenizer.tokenize(input);
+        let utterance_intent = self.match_utterance(&tokens)
+            .map(|utterance| &*utterance.intent)
+            .filter(|intent| intents.map(|i| i.contains(*intent)).unwrap_or(true));
+        Ok(utterance_intent
+            .or_else(|| self.match_keywords(&tokens, intents))
+            .map(|intent_name| IntentClassifierResult {
+                intent_name: intent_name.to_string(),
+                probability: 1.0,
+            }))
+    }
+
+    fn get_slots(
+        &self,
+        input: &str,
+        intent_name: &str,
+        context: &ParseContext,
+    ) -> Result<Vec<Slot>> {
+        let tokens = self.tokenizer.tokenize(input);
+        let utterance = if let Some(utterance) = self.match_utterance(&tokens) {
+            utterance
+        } else {
+            return Ok(vec![]);
+        };
+        if utterance.intent != intent_name {
+            return Ok(vec![]);
+        }
+
+        let internal_slots = utterance
+            .slots
+            .iter()
+            .map(|slot| {
+                let first_token = &tokens[slot.tokens_range.start];
+                let last_token = &tokens[slot.tokens_range.end - 1];
+                InternalSlot {
+                    value: input[first_token.range.start..last_token.range.end].to_string(),
+                    char_range: first_token.char_range.start..last_token.char_range.end,
+                    entity: slot.entity.clone(),
+                    slot_name: slot.slot_name.clone(),
+                }
+            })
+            .collect_vec();
+
+        let builtin_entity_parser =
+            context.builtin_entity_parser(self.builtin_entity_parser.as_ref());
+        if let Some(builtin_entity_parser) = builtin_entity_parser {
+            let filter_entity_kinds = internal_slots
+                .iter()
+                .flat_map(|slot| BuiltinEntityKind::from_identifier(&slot.entity).ok())
+                .unique()
+                .collect_vec();
+            Ok(resolve_builtin_slots(
+                input,
+                internal_slots,
+                builtin_entity_parser,
+                Some(&*filter_entity_kinds),
+            ))
+        } else {
+            Ok(internal_slots
+                .into_iter()
+                .filter(|s| BuiltinEntityKind::from_identifier(&s.entity).is_err())
+                .map(convert_to_custom_slot)
+                .collect())
+        }
+    }
+
+    fn vocabulary(&self) -> Vec<String> {
+        let keywords = self.keywords
+            .iter()
+            .flat_map(|&(_, ref keyword_set)| keyword_set.iter().map(|keyword| &**keyword));
+        self.utterances
+            .keys()
+            .flat_map(|utterance| utterance.split(' '))
+            .chain(keywords)
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_string())
+            .unique()
+            .collect()
+    }
+
+    fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint {
+            rule_patterns: self.utterances.heap_size() + self.keywords.heap_size(),
+            ..MemoryFootprint::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use configurations::{ExactMatchParserConfiguration, UtteranceChunk};
+    use snips_nlu_ontology::{NumberValue, SlotValue};
+
+    fn text_chunk(text: &str) -> UtteranceChunk {
+        UtteranceChunk {
+            text: text.to_string(),
+            entity: None,
+            slot_name: None,
+        }
+    }
+
+    fn slot_chunk(text: &str, entity: &str, slot_name: &str) -> UtteranceChunk {
+        UtteranceChunk {
+            text: text.to_string(),
+            entity: Some(entity.to_string()),
+            slot_name: Some(slot_name.to_string()),
+        }
+    }
+
+    fn test_configuration() -> ExactMatchParserConfiguration {
+        ExactMatchParserConfiguration {
+            language_code: "en".to_string(),
+            utterances: hashmap![
+                "MakeCoffee".to_string() => vec![
+                    vec![
+                        text_chunk("Make me "),
+                        slot_chunk("two", "snips/number", "number_of_cups"),
+                        text_chunk(" cups of coffee"),
+                    ],
+                    vec![text_chunk("I need some caffeine")],
+                ],
+                "MakeTea".to_string() => vec![
+                    vec![
+                        text_chunk("I want a "),
+                        slot_chunk("hot", "Temperature", "beverage_temperature"),
+                        text_chunk(" tea"),
+                    ],
+                    vec![text_chunk("I need some caffeine")],
+                ],
+            ],
+            keywords: Some(hashmap![
+                "MakeTea".to_string() => vec![vec!["earl".to_string(), "grey".to_string()]],
+            ]),
+        }
+    }
+
+    fn test_parser() -> ExactMatchIntentParser {
+        let builtin_entity_parser =
+            Some(Arc::new(MultilingualBuiltinEntityParser::new(&[Language::EN])));
+        ExactMatchIntentParser::new(test_configuration(), builtin_entity_parser).unwrap()
+    }
+
+    #[test]
+    fn should_get_intent_of_training_utterance() {
+        // Given
+        let parser = test_parser();
+
+        // When
+        let intent = parser
+            .get_intent("make me TWO cups of coffee!", None, &ParseContext::default())
+            .unwrap();
+
+        // Then
+        let expected_intent = Some(IntentClassifierResult {
+            intent_name: "MakeCoffee".to_string(),
+            probability: 1.0,
+        });
+        assert_eq!(expected_intent, intent);
+    }
+
+    #[test]
+    fn should_get_intent_with_keywords() {
+        // Given
+        let parser = test_parser();
+
+        // When
+        let context = ParseContext::default();
+        let intent = parser.get_intent("an Earl Grey please", None, &context).unwrap();
+        let filtered_intent = parser
+            .get_intent(
+                "an Earl Grey please",
+                Some(&hashset!["MakeCoffee".to_string()]),
+                &context,
+            )
+            .unwrap();
+
+        // Then
+        let expected_intent = Some(IntentClassifierResult {
+            intent_name: "MakeTea".to_string(),
+            probability: 1.0,
+        });
+        assert_eq!(expected_intent, intent);
+        assert_eq!(None, filtered_intent);
+    }
+
+    #[test]
+    fn should_not_get_intent_of_ambiguous_utterance() {
+        // Given
+        let parser = test_parser();
+
+        // When
+        let intent = parser
+            .get_intent("I need some caffeine", None, &ParseContext::default())
+            .unwrap();
+
+        // Then
+        assert_eq!(None, intent);
+    }
+
+    #[test]
+    fn should_get_slots() {
+        // Given
+        let parser = test_parser();
+
+        // When
+        let context = ParseContext::default();
+        let coffee_slots = parser
+            .get_slots("make me two cups of coffee", "MakeCoffee", &context)
+            .unwrap();
+        let tea_slots = parser.get_slots("I want a hot tea", "MakeTea", &context).unwrap();
+
+        // Then
+        let expected_coffee_slots = vec![
+            Slot {
+                raw_value: "two".to_string(),
+                value: SlotValue::Number(NumberValue { value: 2.0 }),
+                range: Some(8..11),
+                entity: "snips/number".to_string(),
+                slot_name: "number_of_cups".to_string(),
+            },
+        ];
+        let expected_tea_slots = vec![
+            Slot::new_custom(
+                "hot".to_string(),
+                9..12,
+                "Temperature".to_string(),
+                "beverage_temperature".to_string(),
+            ),
+        ];
+        assert_eq!(expected_coffee_slots, coffee_slots);
+        assert_eq!(expected_tea_slots, tea_slots);
+    }
+}
@@ -1,19 +1,105 @@
 pub mod deterministic_intent_parser;
+pub mod exact_match_intent_parser;
 pub mod probabilistic_intent_parser;
+pub mod regex_cache;
+pub mod registry;
+pub mod rules;
 
 use std::collections::HashSet;
 
 use errors::*;
+use memory_footprint::MemoryFootprint;
+use parse_context::ParseContext;
 use snips_nlu_ontology::{IntentClassifierResult, Slot};
 
 pub use self::deterministic_intent_parser::DeterministicIntentParser;
+pub use self::exact_match_intent_parser::ExactMatchIntentParser;
 pub use self::probabilistic_intent_parser::ProbabilisticIntentParser;
+pub use self::registry::{IntentParserFactory, IntentParserRegistry};
 
+/// Parser of the intent and the slots of the inputs
+///
+/// The context of the parse tells, among others, in which languages the builtin entities of the
+/// input are extracted.
 pub trait IntentParser: Send + Sync {
     fn get_intent(
         &self,
         input: &str,
         intents: Option<&HashSet<String>>,
+        context: &ParseContext,
     ) -> Result<Option<IntentClassifierResult>>;
-    fn get_slots(&self, input: &str, intent_name: &str) -> Result<Vec<Slot>>;
+    fn get_slots(
+        &self,
+        input: &str,
+        intent_name: &str,
+        context: &ParseContext,
+    ) -> Result<Vec<Slot>>;
+
+    /// Returns the probabilities of the intents allowed by the filter, by decreasing probability
+    ///
+    /// Parsers which can't compute a probability distribution only return their best intent.
+    fn get_intents(
+        &self,
+        input: &str,
+        intents: Option<&HashSet<String>>,
+        context: &ParseContext,
+    ) -> Result<Vec<IntentClassifierResult>> {
+        Ok(self.get_intent(input, intents, context)?.into_iter().collect())
+    }
+
+    /// Returns the features computed on the input, for parsers relying on features
+    fn get_features(
+        &self,
+        _input: &str,
+        _intent_name: &str,
+        _context: &ParseContext,
+    ) -> Result<Option<ParserFeatures>> {
+        Ok(None)
+    }
+
+    /// Explains why the parser found the intent in the input, for parsers which can tell
+    fn explain_intent(
+        &self,
+        _input: &str,
+        _intent_name: &str,
+        _context: &ParseContext,
+    ) -> Result<Option<IntentExplanation>> {
+        Ok(None)
+    }
+
+    /// Returns the words known by the parser, against which the inputs are spell-corrected
+    fn vocabulary(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Returns the estimated memory footprint of the models of the parser
+    fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint::default()
+    }
+}
+
+/// Features computed by a parser on an input, to help understand its predictions
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserFeatures {
+    /// Input vector of the intent classifier
+    pub intent_classifier_features: Option<Vec<f32>>,
+    /// Features of each token computed by the slot filler of the intent
+    pub slot_filler_features: Option<Vec<TokenFeatures>>,
+}
+
+/// Reason why a parser found an intent in an input
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntentExplanation {
+    /// Pattern of the intent, compiled to a regex, which matched the input
+    MatchedPattern(String),
+    /// Features of the input which contributed the most to the score of the intent, as
+    /// (name, contribution) pairs by decreasing contribution
+    WeightedFeatures(Vec<(String, f32)>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenFeatures {
+    pub token: String,
+    /// (name, value) pairs of the features
+    pub features: Vec<(String, String)>,
 }
@@ -1,12 +1,23 @@
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
+use std::sync::Arc;
 
-use configurations::ProbabilisticParserConfiguration;
+use builtin_entity_parser::MultilingualBuiltinEntityParser;
+use configurations::{ProbabilisticParserConfiguration, SlotFillerConfigConfiguration,
+                     SlotFillerConfiguration, WeightsQuantization};
 use errors::*;
-use intent_classifier::{IntentClassifier, LogRegIntentClassifier};
-use intent_parser::IntentParser;
-use slot_filler::{CRFSlotFiller, SlotFiller};
+use intent_classifier::{EmbeddingIntentClassifier, IntentClassifier, LogRegIntentClassifier};
+#[cfg(feature = "onnx")]
+use intent_classifier::OnnxIntentClassifier;
+use intent_parser::{IntentExplanation, IntentParser, ParserFeatures};
+use memory_footprint::MemoryFootprint;
+use parse_context::ParseContext;
+use resource_reader::ResourceReader;
+#[cfg(feature = "crf")]
+use slot_filler::CRFSlotFiller;
+use slot_filler::{FeatureExtractorRegistry, SlotFiller};
 use snips_nlu_ontology::{IntentClassifierResult, Slot};
+use tokenizer::Tokenizer;
 
 pub struct ProbabilisticIntentParser {
     intent_classifier: Box<IntentClassifier>,
@@ -14,20 +25,47 @@ pub struct ProbabilisticIntentParser {
 }
 
 impl ProbabilisticIntentParser {
-    pub fn new(config: ProbabilisticParserConfiguration) -> Result<Self> {
+    pub fn new(
+        config: ProbabilisticParserConfiguration,
+        builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+        feature_registry: &FeatureExtractorRegistry,
+        none_sensitivity: f32,
+        weights_quantization: Option<WeightsQuantization>,
+        tokenizer: Arc<Tokenizer>,
+        resource_reader: &ResourceReader,
+    ) -> Result<Self> {
+        let default_slot_filler_config = config
+            .config
+            .and_then(|parser_config| parser_config.slot_filler_config);
         let slot_fillers_vec: Result<Vec<_>> = config
             .slot_fillers
             .into_iter()
             .map(|(intent_name, slot_filler_config)| {
+                let slot_filler_config = with_default_slot_filler_config(
+                    slot_filler_config,
+                    default_slot_filler_config.as_ref(),
+                );
                 Ok((
                     intent_name,
-                    Box::new(CRFSlotFiller::new(slot_filler_config)?) as _,
+                    build_slot_filler(
+                        slot_filler_config,
+                        builtin_entity_parser.clone(),
+                        feature_registry,
+                        weights_quantization,
+                        tokenizer.clone(),
+                        resource_reader,
+                    )?,
                 ))
             })
             .collect();
         let slot_fillers = HashMap::from_iter(slot_fillers_vec?);
-        let intent_classifier =
-            Box::new(LogRegIntentClassifier::new(config.intent_classifier)?) as _;
+        let intent_classifier = build_intent_classifier(
+            config.intent_classifier,
+            none_sensitivity,
+            weights_quantization,
+            tokenizer,
+            resource_reader,
+        )?;
 
         Ok(ProbabilisticIntentParser {
             intent_classifier,
@@ -36,19 +74,269 @@ impl ProbabilisticIntentParser {
     }
 }
 
+fn with_default_slot_filler_config(
+    mut slot_filler_config: SlotFillerConfiguration,
+    default_config: Option<&SlotFillerConfigConfiguration>,
+) -> SlotFillerConfiguration {
+    if slot_filler_config.config.is_none() {
+        slot_filler_config.config = default_config.cloned();
+    }
+    slot_filler_config
+}
+
+#[cfg(feature = "crf")]
+fn build_slot_filler(
+    config: SlotFillerConfiguration,
+    builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+    feature_registry: &FeatureExtractorRegistry,
+    weights_quantization: Option<WeightsQuantization>,
+    tokenizer: Arc<Tokenizer>,
+    resource_reader: &ResourceReader,
+) -> Result<Box<SlotFiller>> {
+    Ok(Box::new(CRFSlotFiller::new(
+        config,
+        builtin_entity_parser,
+        feature_registry,
+        weights_quantization,
+        tokenizer,
+        resource_reader,
+    )?) as _)
+}
+
+#[cfg(not(feature = "crf"))]
+fn build_slot_filler(
+    _config: SlotFillerConfiguration,
+    _builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+    _feature_registry: &FeatureExtractorRegistry,
+    _weights_quantization: Option<WeightsQuantization>,
+    _tokenizer: Arc<Tokenizer>,
+    _resource_reader: &ResourceReader,
+) -> Result<Box<SlotFiller>> {
+    Err("CRF slot fillers require the 'crf' feature".into())
+}
+
+fn build_intent_classifier(
+    config: ::serde_json::Value,
+    none_sensitivity: f32,
+    weights_quantization: Option<WeightsQuantization>,
+    tokenizer: Arc<Tokenizer>,
+    resource_reader: &ResourceReader,
+) -> Result<Box<IntentClassifier>> {
+    match config["unit_name"].as_str() {
+        Some("embedding_intent_classifier") => {
+            let config = ::serde_json::from_value(config)?;
+            Ok(Box::new(EmbeddingIntentClassifier::new(
+                config,
+                none_sensitivity,
+                tokenizer,
+                resource_reader,
+            )?) as _)
+        }
+        Some("log_reg_intent_classifier") => {
+            let config = ::serde_json::from_value(config)?;
+            Ok(Box::new(LogRegIntentClassifier::new(
+                config,
+                none_sensitivity,
+                weights_quantization,
+                tokenizer,
+            )?) as _)
+        }
+        #[cfg(feature = "onnx")]
+        Some("onnx_intent_classifier") => {
+            let config = ::serde_json::from_value(config)?;
+            Ok(Box::new(OnnxIntentClassifier::new(
+                config,
+                none_sensitivity,
+                tokenizer,
+                resource_reader,
+            )?) as _)
+        }
+        #[cfg(not(feature = "onnx"))]
+        Some("onnx_intent_classifier") => {
+            Err("ONNX intent classifiers require the 'onnx' feature".into())
+        }
+        Some(_) => Err("Unknown intent classifier unit name".into()),
+        None => Err("Intent classifier unit name is not properly defined".into()),
+    }
+}
+
 impl IntentParser for ProbabilisticIntentParser {
     fn get_intent(
         &self,
         input: &str,
         intents: Option<&HashSet<String>>,
+        _context: &ParseContext,
     ) -> Result<Option<IntentClassifierResult>> {
         self.intent_classifier.get_intent(input, intents)
     }
 
-    fn get_slots(&self, input: &str, intent_name: &str) -> Result<Vec<Slot>> {
+    fn get_intents(
+        &self,
+        input: &str,
+        intents: Option<&HashSet<String>>,
+        _context: &ParseContext,
+    ) -> Result<Vec<IntentClassifierResult>> {
+        self.intent_classifier.get_intents(input, intents)
+    }
+
+    fn get_slots(
+        &self,
+        input: &str,
+        intent_name: &str,
+        context: &ParseContext,
+    ) -> Result<Vec<Slot>> {
         self.slot_fillers
             .get(intent_name)
             .ok_or_else(|| format!("intent {:?} not found in slot fillers", intent_name))?
-            .get_slots(input)
+            .get_slots(input, context)
+    }
+
+    fn get_features(
+        &self,
+        input: &str,
+        intent_name: &str,
+        context: &ParseContext,
+    ) -> Result<Option<ParserFeatures>> {
+        let intent_classifier_features = self.intent_classifier.get_features(input)?;
+        let slot_filler_features = if let Some(slot_filler) = self.slot_fillers.get(intent_name) {
+            Some(slot_filler.get_features(input, context)?)
+        } else {
+            None
+        };
+        Ok(Some(ParserFeatures {
+            intent_classifier_features,
+            slot_filler_features,
+        }))
+    }
+
+    fn explain_intent(
+        &self,
+        input: &str,
+        intent_name: &str,
+        _context: &ParseContext,
+    ) -> Result<Option<IntentExplanation>> {
+        Ok(self.intent_classifier
+            .get_weighted_features(input, intent_name)?
+            .map(IntentExplanation::WeightedFeatures))
+    }
+
+    fn vocabulary(&self) -> Vec<String> {
+        self.intent_classifier.vocabulary()
+    }
+
+    fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint {
+            intent_classifiers: self.intent_classifier.heap_size(),
+            crf_weights: self.slot_fillers
+                .values()
+                .map(|slot_filler| slot_filler.heap_size())
+                .sum(),
+            ..MemoryFootprint::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use configurations::{BuiltinSlotsDisambiguation, OverlappingSlotsPolicy,
+                         TaggingSchemeConfiguration};
+
+    fn slot_filler_config(
+        config: Option<SlotFillerConfigConfiguration>,
+    ) -> SlotFillerConfiguration {
+        SlotFillerConfiguration {
+            language_code: "en".to_string(),
+            intent: "MakeCoffee".to_string(),
+            slot_name_mapping: hashmap![],
+            crf_model_data: "".to_string(),
+            crf_model_file: None,
+            config,
+            beam_search: None,
+        }
+    }
+
+    fn config_with_tagging_scheme(tagging_scheme: u8) -> SlotFillerConfigConfiguration {
+        SlotFillerConfigConfiguration {
+            tagging_scheme: TaggingSchemeConfiguration::Identifier(tagging_scheme),
+            exhaustive_permutations_threshold: 64,
+            feature_factory_configs: vec![],
+            overlapping_slots_policy: OverlappingSlotsPolicy::default(),
+            builtin_slots_disambiguation: BuiltinSlotsDisambiguation::default(),
+        }
+    }
+
+    #[test]
+    fn with_default_slot_filler_config_should_keep_intent_config() {
+        // Given
+        let intent_config = slot_filler_config(Some(config_with_tagging_scheme(2)));
+        let default_config = config_with_tagging_scheme(1);
+
+        // When
+        let resolved_config = with_default_slot_filler_config(intent_config, Some(&default_config));
+
+        // Then
+        assert_eq!(
+            TaggingSchemeConfiguration::Identifier(2),
+            resolved_config.config.unwrap().tagging_scheme
+        );
+    }
+
+    #[test]
+    fn with_default_slot_filler_config_should_use_default_config() {
+        // Given
+        let intent_config = slot_filler_config(None);
+        let default_config = config_with_tagging_scheme(1);
+
+        // When
+        let resolved_config = with_default_slot_filler_config(intent_config, Some(&default_config));
+
+        // Then
+        assert_eq!(
+            TaggingSchemeConfiguration::Identifier(1),
+            resolved_config.config.unwrap().tagging_scheme
+        );
+    }
+
+    #[cfg(not(feature = "onnx"))]
+    #[test]
+    fn build_intent_classifier_should_fail_with_onnx_classifier_without_feature() {
+        use snips_nlu_ontology::Language;
+        use tokenizer::default_tokenizer;
+
+        // Given
+        let config = ::serde_json::from_str(r#"{"unit_name": "onnx_intent_classifier"}"#).unwrap();
+
+        // When
+        let intent_classifier = build_intent_classifier(
+            config,
+            1.0,
+            None,
+            default_tokenizer(Language::EN),
+            &ResourceReader::default(),
+        );
+
+        // Then
+        assert!(intent_classifier.is_err());
+    }
+
+    #[cfg(not(feature = "crf"))]
+    #[test]
+    fn build_slot_filler_should_fail_without_feature() {
+        use snips_nlu_ontology::Language;
+        use tokenizer::default_tokenizer;
+
+        // When
+        let slot_filler = build_slot_filler(
+            slot_filler_config(None),
+            None,
+            &FeatureExtractorRegistry::new(),
+            None,
+            default_tokenizer(Language::EN),
+            &ResourceReader::default(),
+        );
+
+        // Then
+        assert!(slot_filler.is_err());
     }
 }
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+
+use once_cell::sync::OnceCell;
+use regex::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
+use regex_syntax::ParserBuilder;
+
+use errors::*;
+use memory_footprint::HeapSize;
+
+lazy_static! {
+    static ref REGEX_CACHE: Mutex<HashMap<String, Weak<Regex>>> = Mutex::new(HashMap::new());
+}
+
+/// Returns the case insensitive regex compiled from `pattern`
+///
+/// Compiled regexes are cached for as long as they are in use, so that engines sharing the same
+/// patterns within a process also share their compiled programs.
+pub fn get_regex(pattern: &str) -> Result<Arc<Regex>> {
+    let mut cache = REGEX_CACHE
+        .lock()
+        .map_err(|_| "Regex cache lock is poisoned")?;
+    if let Some(regex) = cache.get(pattern).and_then(|regex| regex.upgrade()) {
+        return Ok(regex);
+    }
+    let regex = Arc::new(RegexBuilder::new(pattern).case_insensitive(true).build()?);
+    cache.retain(|_, cached_regex| cached_regex.upgrade().is_some());
+    cache.insert(pattern.to_string(), Arc::downgrade(&regex));
+    Ok(regex)
+}
+
+/// Patterns of an intent, compiled into a `RegexSet` used to find the matching patterns
+/// quickly, along with the individual regexes used to extract the captures
+pub struct CompiledPatterns {
+    set: RegexSet,
+    regexes: Vec<Arc<Regex>>,
+}
+
+impl CompiledPatterns {
+    fn new(patterns: &[String]) -> Result<Self> {
+        let set = RegexSetBuilder::new(patterns)
+            .case_insensitive(true)
+            .build()?;
+        let regexes = patterns
+            .iter()
+            .map(|pattern| get_regex(pattern))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { set, regexes })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.set.is_match(text)
+    }
+
+    /// Returns the regexes matching `text`, in the order of the patterns
+    pub fn matching_regexes(&self, text: &str) -> Vec<&Regex> {
+        self.set
+            .matches(text)
+            .into_iter()
+            .map(|index| &*self.regexes[index])
+            .collect()
+    }
+}
+
+/// Patterns of an intent, compiled the first time they are needed
+///
+/// The compiled patterns are kept for the lifetime of the parser, reading them once compiled
+/// taking no lock.
+pub struct LazyRegexes {
+    patterns: Vec<String>,
+    compiled_patterns: OnceCell<CompiledPatterns>,
+}
+
+/// Only the patterns are counted, the size of the compiled regexes being unknown
+impl HeapSize for LazyRegexes {
+    fn heap_size(&self) -> usize {
+        self.patterns.heap_size()
+    }
+}
+
+impl LazyRegexes {
+    /// Checks the syntax of the patterns, so that invalid patterns are reported when the model
+    /// is loaded rather than when the patterns are first used
+    pub fn new(patterns: Vec<String>) -> Result<Self> {
+        for pattern in &patterns {
+            ParserBuilder::new()
+                .case_insensitive(true)
+                .build()
+                .parse(pattern)
+                .map_err(|error| format!("Invalid pattern {:?}: {}", pattern, error))?;
+        }
+        Ok(Self {
+            patterns,
+            compiled_patterns: OnceCell::new(),
+        })
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub fn get(&self) -> Result<&CompiledPatterns> {
+        self.compiled_patterns
+            .get_or_try_init(|| CompiledPatterns::new(&self.patterns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_regex_should_share_compiled_regexes() {
+        // Given
+        let pattern = r"^share (?P<group_0>this|that) regex$";
+
+        // When
+        let regex_1 = get_regex(pattern).unwrap();
+        let regex_2 = get_regex(pattern).unwrap();
+
+        // Then
+        assert!(Arc::ptr_eq(&regex_1, &regex_2));
+        assert!(regex_1.is_match("Share THIS regex"));
+    }
+
+    #[test]
+    fn compiled_patterns_should_return_matching_regexes() {
+        // Given
+        let patterns = vec![
+            r"^make me (?P<group_0>\w+) tea$".to_string(),
+            r"^make me a coffee$".to_string(),
+            r"^make me .+$".to_string(),
+        ];
+        let compiled_patterns = CompiledPatterns::new(&patterns).unwrap();
+
+        // When
+        let matching_regexes = compiled_patterns.matching_regexes("Make me hot tea");
+
+        // Then
+        let matching_patterns: Vec<&str> = matching_regexes.iter().map(|r| r.as_str()).collect();
+        assert!(compiled_patterns.is_match("make me a coffee"));
+        assert!(!compiled_patterns.is_match("make me"));
+        assert_eq!(vec![&*patterns[0], &*patterns[2]], matching_patterns);
+    }
+
+    #[test]
+    fn lazy_regexes_should_compile_on_first_use() {
+        // Given
+        let lazy_regexes = LazyRegexes::new(vec![r"^make me (?P<group_0>\w+) tea$".to_string()])
+            .unwrap();
+        assert!(lazy_regexes.compiled_patterns.get().is_none());
+
+        // When
+        let regexes = lazy_regexes.get().unwrap();
+
+        // Then
+        assert!(regexes.is_match("make me green tea"));
+        assert!(lazy_regexes.compiled_patterns.get().is_some());
+    }
+
+    #[test]
+    fn lazy_regexes_should_reject_invalid_patterns() {
+        // When
+        let lazy_regexes = LazyRegexes::new(vec!["^invalid (regex$".to_string()]);
+
+        // Then
+        assert!(lazy_regexes.is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use errors::*;
+use intent_parser::IntentParser;
+
+/// Builds an intent parser out of its JSON configuration
+pub trait IntentParserFactory: Send + Sync {
+    fn create(&self, configuration: ::serde_json::Value) -> Result<Box<IntentParser>>;
+}
+
+impl<F> IntentParserFactory for F
+where
+    F: Fn(::serde_json::Value) -> Result<Box<IntentParser>> + Send + Sync,
+{
+    fn create(&self, configuration: ::serde_json::Value) -> Result<Box<IntentParser>> {
+        self(configuration)
+    }
+}
+
+/// Factories of the custom intent parsers that the engine can instantiate, indexed by the
+/// `unit_name` used in the `custom_parsers` section of the configuration
+#[derive(Default)]
+pub struct IntentParserRegistry {
+    factories: HashMap<String, Box<IntentParserFactory>>,
+}
+
+impl IntentParserRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<F>(&mut self, unit_name: &str, factory: F)
+    where
+        F: IntentParserFactory + 'static,
+    {
+        self.factories
+            .insert(unit_name.to_string(), Box::new(factory));
+    }
+
+    pub fn create(&self, configuration: ::serde_json::Value) -> Result<Box<IntentParser>> {
+        let factory = {
+            let unit_name = configuration["unit_name"]
+                .as_str()
+                .ok_or_else(|| "Custom intent parser unit name is not properly defined")?;
+            self.factories
+                .get(unit_name)
+                .ok_or_else(|| format!("Unknown custom intent parser unit name: {}", unit_name))?
+        };
+        factory.create(configuration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use parse_context::ParseContext;
+    use snips_nlu_ontology::{IntentClassifierResult, Slot};
+
+    struct KeywordIntentParser {
+        keyword: String,
+        intent_name: String,
+    }
+
+    impl IntentParser for KeywordIntentParser {
+        fn get_intent(
+            &self,
+            input: &str,
+            _intents: Option<&HashSet<String>>,
+            _context: &ParseContext,
+        ) -> Result<Option<IntentClassifierResult>> {
+            if input.contains(&*self.keyword) {
+                Ok(Some(IntentClassifierResult {
+                    intent_name: self.intent_name.clone(),
+                    probability: 1.0,
+                }))
+            } else {
+                Ok(None)
+            }
+        }
+
+        fn get_slots(
+            &self,
+            _input: &str,
+            _intent_name: &str,
+            _context: &ParseContext,
+        ) -> Result<Vec<Slot>> {
+            Ok(vec![])
+        }
+    }
+
+    fn create_keyword_parser(configuration: ::serde_json::Value) -> Result<Box<IntentParser>> {
+        Ok(Box::new(KeywordIntentParser {
+            keyword: configuration["keyword"]
+                .as_str()
+                .ok_or_else(|| "missing keyword")?
+                .to_string(),
+            intent_name: configuration["intent"]
+                .as_str()
+                .ok_or_else(|| "missing intent")?
+                .to_string(),
+        }))
+    }
+
+    #[test]
+    fn registry_should_create_registered_parser() {
+        // Given
+        let mut registry = IntentParserRegistry::new();
+        registry.register("keyword_intent_parser", create_keyword_parser);
+        let configuration = ::serde_json::from_str(
+            r#"{"unit_name": "keyword_intent_parser", "keyword": "coffee", "intent": "MakeCoffee"}"#,
+        ).unwrap();
+
+        // When
+        let parser = registry.create(configuration).unwrap();
+        let result = parser
+            .get_intent("a coffee please", None, &ParseContext::default())
+            .unwrap();
+
+        // Then
+        let expected_result = Some(IntentClassifierResult {
+            intent_name: "MakeCoffee".to_string(),
+            probability: 1.0,
+        });
+        assert_eq!(expected_result, result);
+    }
+
+    #[test]
+    fn registry_should_fail_with_unknown_parser() {
+        // Given
+        let registry = IntentParserRegistry::new();
+        let configuration = ::serde_json::from_str(r#"{"unit_name": "unknown_parser"}"#).unwrap();
+
+        // When
+        let result = registry.create(configuration);
+
+        // Then
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use regex::escape;
+
+use errors::*;
+use intent_parser::deterministic_intent_parser::get_builtin_entity_name;
+use snips_nlu_ontology::BuiltinEntityKind;
+
+const WORD_SEPARATOR: &str = r"(?:^\s*|\s+)";
+
+/// Element of a rule, rules being written with the following syntax:
+/// - words are matched case-insensitively and are separated by whitespaces
+/// - `[...]` is an optional group of elements
+/// - `*N` matches up to N arbitrary words
+/// - `{slot_name}` matches the value of a slot
+#[derive(Debug, PartialEq)]
+enum RuleItem {
+    Word(String),
+    Optional(Vec<RuleItem>),
+    Wildcard(usize),
+    Slot(String),
+}
+
+/// Compiles a rule into a regex pattern, registering the names of the groups capturing slots
+pub fn compile_rule(
+    rule: &str,
+    slot_names_to_entities: &HashMap<String, String>,
+    group_names_to_slot_names: &mut HashMap<String, String>,
+) -> Result<String> {
+    let items = parse_items(&mut rule.chars().peekable(), false)
+        .chain_err(|| format!("Invalid rule: '{}'", rule))?;
+    let pattern = items_to_regex(&items, slot_names_to_entities, group_names_to_slot_names)?;
+    Ok(format!(r"^{}\s*$", pattern))
+}
+
+fn parse_items(chars: &mut Peekable<Chars>, is_optional_group: bool) -> Result<Vec<RuleItem>> {
+    let mut items = vec![];
+    loop {
+        match chars.peek().cloned() {
+            None => {
+                if is_optional_group {
+                    bail!("Optional group is not closed");
+                }
+                return Ok(items);
+            }
+            Some(c) if c.is_whitespace() => {
+                chars.next();
+            }
+            Some('[') => {
+                chars.next();
+                items.push(RuleItem::Optional(parse_items(chars, true)?));
+            }
+            Some(']') => {
+                chars.next();
+                if !is_optional_group {
+                    bail!("Unexpected end of optional group");
+                }
+                return Ok(items);
+            }
+            Some('{') => {
+                chars.next();
+                let slot_name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                let slot_name = slot_name.trim();
+                if slot_name.is_empty() {
+                    bail!("Slot name is missing");
+                }
+                items.push(RuleItem::Slot(slot_name.to_string()));
+            }
+            Some('*') => {
+                chars.next();
+                let mut max_nb_words = String::new();
+                while let Some(c) = chars.peek().cloned() {
+                    if !c.is_digit(10) {
+                        break;
+                    }
+                    max_nb_words.push(c);
+                    chars.next();
+                }
+                let max_nb_words = max_nb_words
+                    .parse()
+                    .chain_err(|| "Wildcard must be followed by a maximum number of words")?;
+                items.push(RuleItem::Wildcard(max_nb_words));
+            }
+            Some(_) => {
+                let mut word = String::new();
+                while let Some(c) = chars.peek().cloned() {
+                    if c.is_whitespace() || c == '[' || c == ']' || c == '{' || c == '}' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                items.push(RuleItem::Word(word));
+            }
+        }
+    }
+}
+
+fn items_to_regex(
+    items: &[RuleItem],
+    slot_names_to_entities: &HashMap<String, String>,
+    group_names_to_slot_names: &mut HashMap<String, String>,
+) -> Result<String> {
+    let mut pattern = String::new();
+    for item in items {
+        match *item {
+            RuleItem::Word(ref word) => {
+                pattern.push_str(&format!("{}{}", WORD_SEPARATOR, escape(word)));
+            }
+            RuleItem::Optional(ref optional_items) => {
+                let optional_pattern = items_to_regex(
+                    optional_items,
+                    slot_names_to_entities,
+                    group_names_to_slot_names,
+                )?;
+                pattern.push_str(&format!("(?:{})?", optional_pattern));
+            }
+            RuleItem::Wildcard(max_nb_words) => {
+                pattern.push_str(&format!(r"(?:{}\S+){{0,{}}}", WORD_SEPARATOR, max_nb_words));
+            }
+            RuleItem::Slot(ref slot_name) => {
+                let entity = slot_names_to_entities
+                    .get(slot_name)
+                    .ok_or_else(|| format!("Unknown slot in rule: {}", slot_name))?;
+                let value_pattern = if BuiltinEntityKind::from_identifier(entity).is_ok() {
+                    escape(&get_builtin_entity_name(entity))
+                } else {
+                    ".+?".to_string()
+                };
+                let group_name = new_group_name(group_names_to_slot_names);
+                group_names_to_slot_names.insert(group_name.clone(), slot_name.clone());
+                pattern.push_str(&format!(
+                    "{}(?P<{}>{})",
+                    WORD_SEPARATOR, group_name, value_pattern
+                ));
+            }
+        }
+    }
+    Ok(pattern)
+}
+
+fn new_group_name(group_names_to_slot_names: &HashMap<String, String>) -> String {
+    (group_names_to_slot_names.len()..)
+        .map(|index| format!("rule_group_{}", index))
+        .find(|group_name| !group_names_to_slot_names.contains_key(group_name))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::RegexBuilder;
+
+    fn compile_test_rule(rule: &str) -> (::regex::Regex, HashMap<String, String>) {
+        let slot_names_to_entities = hashmap![
+            "beverage_temperature".to_string() => "Temperature".to_string(),
+            "number_of_cups".to_string() => "snips/number".to_string(),
+        ];
+        let mut group_names_to_slot_names = HashMap::new();
+        let pattern = compile_rule(
+            rule,
+            &slot_names_to_entities,
+            &mut group_names_to_slot_names,
+        ).unwrap();
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        (regex, group_names_to_slot_names)
+    }
+
+    #[test]
+    fn parse_items_works() {
+        // When
+        let items = parse_items(&mut "make [me a] *2 {slot} tea".chars().peekable(), false);
+
+        // Then
+        let expected_items = vec![
+            RuleItem::Word("make".to_string()),
+            RuleItem::Optional(vec![
+                RuleItem::Word("me".to_string()),
+                RuleItem::Word("a".to_string()),
+            ]),
+            RuleItem::Wildcard(2),
+            RuleItem::Slot("slot".to_string()),
+            RuleItem::Word("tea".to_string()),
+        ];
+        assert_eq!(expected_items, items.unwrap());
+    }
+
+    #[test]
+    fn parse_items_should_fail_with_unclosed_optional_group() {
+        // When
+        let items = parse_items(&mut "make [me a tea".chars().peekable(), false);
+
+        // Then
+        assert!(items.is_err());
+    }
+
+    #[test]
+    fn compiled_rule_should_match_optional_groups() {
+        // Given
+        let (regex, _) = compile_test_rule("[please] make me [a cup of] tea [please]");
+
+        // Then
+        assert!(regex.is_match("make me tea"));
+        assert!(regex.is_match("Please make me a cup of tea"));
+        assert!(regex.is_match("make me tea please"));
+        assert!(!regex.is_match("make me a tea"));
+    }
+
+    #[test]
+    fn compiled_rule_should_match_bounded_wildcards() {
+        // Given
+        let (regex, _) = compile_test_rule("make me *2 tea");
+
+        // Then
+        assert!(regex.is_match("make me tea"));
+        assert!(regex.is_match("make me some green tea"));
+        assert!(!regex.is_match("make me some nice green tea"));
+    }
+
+    #[test]
+    fn compiled_rule_should_capture_slots() {
+        // Given
+        let (regex, group_names_to_slot_names) =
+            compile_test_rule("make me {number_of_cups} *1 {beverage_temperature} tea");
+
+        // When
+        let captures = regex.captures("make me hot tea");
+        let captures_with_wildcard = regex.captures("make me %SNIPSNUMBER% very hot tea");
+
+        // Then
+        let expected_group_names_to_slot_names = hashmap![
+            "rule_group_0".to_string() => "number_of_cups".to_string(),
+            "rule_group_1".to_string() => "beverage_temperature".to_string(),
+        ];
+        assert_eq!(expected_group_names_to_slot_names, group_names_to_slot_names);
+        assert!(captures.is_none());
+        let captures_with_wildcard = captures_with_wildcard.unwrap();
+        assert_eq!("%SNIPSNUMBER%", &captures_with_wildcard["rule_group_0"]);
+        assert_eq!("hot", &captures_with_wildcard["rule_group_1"]);
+    }
+
+    #[test]
+    fn compile_rule_should_fail_with_unknown_slot() {
+        // When
+        let pattern = compile_rule("make me {beverage}", &HashMap::new(), &mut HashMap::new());
+
+        // Then
+        assert!(pattern.is_err());
+    }
+}
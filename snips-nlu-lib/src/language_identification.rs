@@ -0,0 +1,130 @@
+use nlu_utils::language::Language as NluUtilsLanguage;
+use nlu_utils::string::normalize;
+use nlu_utils::token::tokenize_light;
+use resources::gazetteer::{Gazetteer, StaticMapGazetteer};
+use snips_nlu_ontology::Language;
+use tokenizer::Script;
+
+/// Identifies the language of the inputs, so that they can be routed to the engines of their
+/// language
+pub trait LanguageIdentifier: Send + Sync {
+    /// Returns the language of the input among the candidate languages, or `None` when it
+    /// cannot be identified
+    fn identify(&self, input: &str, candidates: &[Language]) -> Option<Language>;
+}
+
+/// Identifies Japanese and Korean with the script of the inputs, and the other languages with
+/// the number of their stop words and frequent words found in the inputs
+pub struct DefaultLanguageIdentifier {
+    gazetteers: Vec<(Language, Vec<StaticMapGazetteer>)>,
+}
+
+impl DefaultLanguageIdentifier {
+    /// Creates an identifier of the given languages, loading their word lists
+    pub fn new(languages: &[Language]) -> Self {
+        let gazetteers = languages
+            .iter()
+            .map(|&language| {
+                let gazetteers = ["stop_words", "top_10000_words"]
+                    .iter()
+                    .filter_map(|name| StaticMapGazetteer::new(name, language, false).ok())
+                    .collect();
+                (language, gazetteers)
+            })
+            .collect();
+        Self { gazetteers }
+    }
+
+    fn words_score(&self, words: &[String], language: Language) -> usize {
+        self.gazetteers
+            .iter()
+            .filter(|&&(gazetteer_language, _)| gazetteer_language == language)
+            .flat_map(|&(_, ref gazetteers)| gazetteers)
+            .map(|gazetteer| {
+                words
+                    .iter()
+                    .filter(|word| gazetteer.contains(word.as_str()))
+                    .count()
+            })
+            .sum()
+    }
+}
+
+impl LanguageIdentifier for DefaultLanguageIdentifier {
+    fn identify(&self, input: &str, candidates: &[Language]) -> Option<Language> {
+        if let Some(language) = identify_with_script(input) {
+            if candidates.contains(&language) {
+                return Some(language);
+            }
+        }
+        let words: Vec<String> = tokenize_light(input, NluUtilsLanguage::EN)
+            .iter()
+            .map(|word| normalize(word))
+            .collect();
+        let mut best_language: Option<(Language, usize)> = None;
+        for &language in candidates {
+            let score = self.words_score(&words, language);
+            if score > 0 && best_language.map_or(true, |(_, best_score)| score > best_score) {
+                best_language = Some((language, score));
+            }
+        }
+        best_language.map(|(language, _)| language)
+    }
+}
+
+/// Korean being the only language written with hangul and Japanese the only one written with
+/// kana or han, among the supported languages, these scripts identify their language
+fn identify_with_script(input: &str) -> Option<Language> {
+    let mut is_japanese = false;
+    for c in input.chars() {
+        match Script::of(c) {
+            Script::Hangul => return Some(Language::KO),
+            Script::Hiragana | Script::Katakana | Script::Han => is_japanese = true,
+            _ => {}
+        }
+    }
+    if is_japanese {
+        Some(Language::JA)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identify_should_use_frequent_words() {
+        // Given
+        let languages = [Language::EN, Language::FR, Language::DE];
+        let identifier = DefaultLanguageIdentifier::new(&languages);
+
+        // When
+        let english = identifier.identify("what is the weather like today", &languages);
+        let french = identifier.identify("quel temps fait-il aujourd'hui", &languages);
+        let unknown = identifier.identify("1234", &languages);
+
+        // Then
+        assert_eq!(Some(Language::EN), english);
+        assert_eq!(Some(Language::FR), french);
+        assert_eq!(None, unknown);
+    }
+
+    #[test]
+    fn identify_should_use_scripts() {
+        // Given
+        let languages = [Language::EN, Language::JA, Language::KO];
+        let identifier = DefaultLanguageIdentifier::new(&languages);
+
+        // When
+        let japanese = identifier.identify("コーヒーをください", &languages);
+        let korean = identifier.identify("커피 주세요", &languages);
+        let not_candidate = identifier.identify("커피 주세요", &[Language::EN]);
+
+        // Then
+        assert_eq!(Some(Language::JA), japanese);
+        assert_eq!(Some(Language::KO), korean);
+        assert_eq!(None, not_candidate);
+    }
+}
@@ -0,0 +1,177 @@
+use std::fs;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use memmap::Mmap;
+use once_cell::sync::OnceCell;
+use serde::de::DeserializeOwned;
+
+use errors::*;
+use resource_reader::ResourceReader;
+
+/// Section of a model, such as CRF weights or entity gazetteers, which is either already loaded
+/// or stored in its own file
+pub enum LazySection<T> {
+    Loaded(Arc<T>),
+    Mapped(MappedSection<T>),
+}
+
+/// Section stored in a file which is memory-mapped and deserialized the first time the section
+/// is used, so that the sections which are never used don't take any resident memory
+///
+/// The mapping is released once the section is deserialized, the section then being read
+/// without locking.
+pub struct MappedSection<T> {
+    path: PathBuf,
+    /// Size of the file when the model was loaded, which must not change afterwards
+    size: u64,
+    reader: ResourceReader,
+    loader: Box<Fn(&[u8]) -> Result<T> + Send + Sync>,
+    value: OnceCell<Arc<T>>,
+}
+
+impl<T> LazySection<T> {
+    pub fn loaded(value: T) -> Self {
+        LazySection::Loaded(Arc::new(value))
+    }
+
+    /// Creates a section whose content is built by `loader` out of the bytes of the file at
+    /// `path`, read with `reader`, the file being checked right away
+    pub fn mapped<P, F>(path: P, reader: &ResourceReader, loader: F) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        F: Fn(&[u8]) -> Result<T> + Send + Sync + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+        let metadata =
+            fs::metadata(&path).chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", path)))?;
+        if !metadata.is_file() || metadata.len() == 0 {
+            bail!(ErrorKind::ConfigLoad(format!("{:?}", path)));
+        }
+        Ok(LazySection::Mapped(MappedSection {
+            path,
+            size: metadata.len(),
+            reader: reader.clone(),
+            loader: Box::new(loader),
+            value: OnceCell::new(),
+        }))
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        match *self {
+            LazySection::Loaded(_) => true,
+            LazySection::Mapped(ref section) => section.value.get().is_some(),
+        }
+    }
+
+    /// Returns the content of the section, loading it if needed
+    pub fn get(&self) -> Result<Arc<T>> {
+        match *self {
+            LazySection::Loaded(ref value) => Ok(value.clone()),
+            LazySection::Mapped(ref section) => section.get(),
+        }
+    }
+}
+
+impl<T: DeserializeOwned + 'static> LazySection<T> {
+    /// Creates a section stored as JSON in the file at `path`
+    pub fn from_json_file<P: AsRef<Path>>(path: P, reader: &ResourceReader) -> Result<Self> {
+        Self::mapped(path, reader, |bytes| Ok(::serde_json::from_slice(bytes)?))
+    }
+}
+
+impl<T> MappedSection<T> {
+    fn get(&self) -> Result<Arc<T>> {
+        self.value
+            .get_or_try_init(|| {
+                self.load()
+                    .map(Arc::new)
+                    .chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", self.path)))
+            })
+            .map(|value| value.clone())
+    }
+
+    fn check_size(&self, size: u64) -> Result<()> {
+        if size != self.size {
+            bail!("Section file {:?} was modified after the model was loaded", self.path);
+        }
+        Ok(())
+    }
+
+    /// Encrypted files are decrypted in memory instead of being mapped
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load(&self) -> Result<T> {
+        if self.reader.is_encrypted() {
+            self.check_size(fs::metadata(&self.path)?.len())?;
+            return (self.loader)(&self.reader.read(&self.path)?);
+        }
+        let file = File::open(&self.path)?;
+        // The file must not be modified while it is mapped, which holds for model files
+        let bytes = unsafe { Mmap::map(&file) }?;
+        self.check_size(bytes.len() as u64)?;
+        (self.loader)(&bytes[..])
+    }
+
+    /// Files cannot be memory-mapped in WebAssembly, they are read instead
+    #[cfg(target_arch = "wasm32")]
+    fn load(&self) -> Result<T> {
+        self.check_size(fs::metadata(&self.path)?.len())?;
+        (self.loader)(&self.reader.read(&self.path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::file_path;
+
+    #[test]
+    fn mapped_section_is_loaded_on_first_use() {
+        // Given
+        let path = file_path("tests/configurations/trained_assistant.json");
+        let section: LazySection<::serde_json::Value> =
+            LazySection::from_json_file(path, &ResourceReader::default()).unwrap();
+        assert!(!section.is_loaded());
+
+        // When
+        let value = section.get();
+
+        // Then
+        assert_eq!(Some("0.13.0"), value.unwrap()["model_version"].as_str());
+        assert!(section.is_loaded());
+    }
+
+    #[test]
+    fn mapped_section_with_missing_file_fails() {
+        // Given
+        let path = file_path("tests/configurations/missing.json");
+
+        // When
+        let section: Result<LazySection<::serde_json::Value>> =
+            LazySection::from_json_file(path, &ResourceReader::default());
+
+        // Then
+        assert!(section.is_err());
+    }
+
+    #[test]
+    fn mapped_section_with_modified_file_fails() {
+        // Given
+        let path = file_path("tests/configurations/trained_assistant.json");
+        let mut section: LazySection<::serde_json::Value> =
+            LazySection::from_json_file(path, &ResourceReader::default()).unwrap();
+        if let LazySection::Mapped(ref mut mapped_section) = section {
+            mapped_section.size += 1;
+        }
+
+        // When
+        let value = section.get();
+
+        // Then
+        assert!(value.is_err());
+        assert!(!section.is_loaded());
+    }
+}
@@ -1,22 +1,38 @@
 #![recursion_limit = "128"]
 
+#[cfg(feature = "encryption")]
+extern crate aes_gcm;
 extern crate base64;
-extern crate crfsuite;
 extern crate csv;
 extern crate dinghy_test;
 #[macro_use]
 extern crate error_chain;
 extern crate itertools;
 #[macro_use]
-extern crate ndarray;
+extern crate lazy_static;
+#[cfg(not(target_arch = "wasm32"))]
+extern crate memmap;
+extern crate once_cell;
+#[cfg(feature = "parallel")]
+extern crate rayon;
 extern crate regex;
+extern crate regex_syntax;
 extern crate serde;
+extern crate serde_cbor;
 #[macro_use]
 extern crate serde_derive;
+#[cfg_attr(test, macro_use)]
 extern crate serde_json;
+extern crate sha2;
 extern crate snips_nlu_ontology;
 extern crate snips_nlu_resources_packed as resources_packed;
 extern crate snips_nlu_utils as nlu_utils;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "onnx")]
+extern crate tract_onnx;
+extern crate unicode_normalization;
+extern crate unicode_segmentation;
 extern crate yolo;
 extern crate zip;
 
@@ -25,21 +41,74 @@ extern crate zip;
 extern crate maplit;
 
 pub mod errors;
+#[macro_use]
+mod instrumentation;
+mod arbitration;
+mod borrowed_result;
+mod builtin_entity_parser;
+mod compression;
 mod configurations;
+mod dialogue_context;
+mod evaluation;
 mod intent_classifier;
 mod intent_parser;
 mod resources;
 mod nlu_engine;
+mod nlu_engine_builder;
+mod nlu_router;
+mod preprocessing;
+mod reloadable_nlu_engine;
+mod schema;
 mod slot_utils;
 mod slot_filler;
+mod symbols;
+pub mod tagging;
+mod tokenizer;
+mod training;
+mod units;
 mod utils;
 mod language;
+mod language_identification;
+mod lazy_section;
+mod memory_footprint;
+mod metrics;
+mod ngrams;
+mod parse_context;
+mod resource_reader;
 #[cfg(test)]
 mod testutils;
 
 pub use errors::*;
-pub use nlu_engine::SnipsNluEngine;
-pub use configurations::{FileBasedConfiguration, NluEngineConfiguration,
-                         NluEngineConfigurationConvertible, ZipBasedConfiguration};
-pub use nlu_utils::token::{compute_all_ngrams, tokenize_light};
+pub use borrowed_result::{BorrowedParserResult, BorrowedSlot};
+pub use dialogue_context::DialogueContext;
+pub use evaluation::{compute_cross_val_metrics, compute_cross_val_metrics_with_engine,
+                     ConfusionMatrix, EvaluationMetrics, IntentMetrics, Metrics};
+pub use memory_footprint::MemoryFootprint;
+pub use metrics::MetricsSink;
+pub use nlu_engine::{EntityDescription, ParseExplanation, ParseOptions, SlotDescription,
+                     SlotResolution, SnipsNluEngine};
+pub use nlu_engine_builder::SnipsNluEngineBuilder;
+pub use language_identification::{DefaultLanguageIdentifier, LanguageIdentifier};
+pub use nlu_router::{NluRouter, RoutedParserResult};
+pub use parse_context::{AmbiguousBuiltinEntity, ParseContext};
+pub use reloadable_nlu_engine::ReloadableNluEngine;
+pub use schema::{intents_to_json, result_to_json, result_to_json_string,
+                 result_to_python_json_string, slots_to_json, RESULT_FORMAT_VERSION};
+pub use intent_parser::{IntentExplanation, IntentParser, IntentParserFactory, IntentParserRegistry,
+                        ParserFeatures, TokenFeatures};
+pub use configurations::{ArbitrationStrategy, ConfigurationDiagnostic, ConfigurationFormat,
+                         FileBasedConfiguration, ModelMigration, ModelMigrationRegistry,
+                         NluEngineConfiguration, NluEngineConfigurationConvertible,
+                         PreprocessingStep, SpellCorrectionConfiguration, UnicodeNormalization,
+                         UtteranceChunk, WeightsQuantization, ZipBasedConfiguration};
+pub use nlu_utils::token::{compute_all_ngrams, tokenize_light, Token};
+pub use training::{add_utterances, train_engine, AugmentationConfig, Dataset, DatasetDiagnostic,
+                   DatasetUtterance, EntityData, EntityValue, IntentData};
+pub use slot_filler::{FeatureExtractor, FeatureExtractorFactory, FeatureExtractorRegistry};
+pub use tokenizer::{CjkTokenizer, DefaultTokenizer, EmojiSplittingTokenizer, Tokenizer,
+                    TokenizerFactory, TokenizerRegistry, CJK_TOKENIZER};
+pub use resources::shared::load_resources;
+#[cfg(feature = "encryption")]
+pub use resources::shared::load_encrypted_resources;
+pub use units::UnitSystem;
 pub use utils::file_path; // This is used by benches
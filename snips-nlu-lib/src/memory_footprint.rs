@@ -0,0 +1,241 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::mem;
+use std::ops::{Add, AddAssign};
+use std::sync::Arc;
+
+/// Estimated memory footprint of the models of an engine, in bytes, by component
+///
+/// The estimates only count the data of the models: the allocator overhead is left out, as well
+/// as the sections of a model which are stored in their own files and have not been loaded yet.
+/// The Rustling parsers of the builtin entities are not counted either, as they are built and
+/// shared across engines by `snips-nlu-ontology`, which doesn't expose their size. The
+/// allocations shared by several components, such as the interned names, are counted once.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemoryFootprint {
+    /// Gazetteers of the custom entities
+    pub entities: usize,
+    /// Patterns and utterances of the rule-based intent parsers
+    pub rule_patterns: usize,
+    /// Weights and vocabularies of the intent classifiers
+    pub intent_classifiers: usize,
+    /// Weights of the CRF slot fillers
+    pub crf_weights: usize,
+    /// Builtin entity parser of the engine, without its Rustling parsers
+    pub builtin_entities: usize,
+    /// Vocabularies of the spell correctors and data of the other preprocessing steps
+    pub preprocessing: usize,
+    /// Configuration kept by the engines built with `new_updatable`, estimated by the length of
+    /// its JSON serialization
+    pub configuration: usize,
+}
+
+impl MemoryFootprint {
+    pub fn total(&self) -> usize {
+        self.entities
+            + self.rule_patterns
+            + self.intent_classifiers
+            + self.crf_weights
+            + self.builtin_entities
+            + self.preprocessing
+            + self.configuration
+    }
+
+    /// Computes a footprint with `measure`, each shared allocation being counted by the first of
+    /// its owners only
+    pub(crate) fn measure<F: FnOnce() -> Self>(measure: F) -> Self {
+        let previous_allocations =
+            COUNTED_ALLOCATIONS.with(|counted| counted.replace(Some(HashSet::new())));
+        let footprint = measure();
+        COUNTED_ALLOCATIONS.with(|counted| counted.replace(previous_allocations));
+        footprint
+    }
+}
+
+impl Add for MemoryFootprint {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            entities: self.entities + other.entities,
+            rule_patterns: self.rule_patterns + other.rule_patterns,
+            intent_classifiers: self.intent_classifiers + other.intent_classifiers,
+            crf_weights: self.crf_weights + other.crf_weights,
+            builtin_entities: self.builtin_entities + other.builtin_entities,
+            preprocessing: self.preprocessing + other.preprocessing,
+            configuration: self.configuration + other.configuration,
+        }
+    }
+}
+
+impl AddAssign for MemoryFootprint {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+thread_local! {
+    /// Addresses of the shared allocations already counted by the `MemoryFootprint::measure`
+    /// running on the thread, if any
+    static COUNTED_ALLOCATIONS: RefCell<Option<HashSet<usize>>> = RefCell::new(None);
+}
+
+/// Number of bytes allocated on the heap by a value
+pub trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+/// Size of the allocation of a shared value, including its reference counts, which is zero when
+/// the allocation was already counted by the running measure
+fn shared_heap_size<T: ?Sized, F: FnOnce() -> usize>(value: &Arc<T>, value_size: F) -> usize {
+    let address = &**value as *const T as *const u8 as usize;
+    let is_counted = COUNTED_ALLOCATIONS.with(|counted| match *counted.borrow_mut() {
+        Some(ref mut counted) => !counted.insert(address),
+        None => false,
+    });
+    if is_counted {
+        0
+    } else {
+        2 * mem::size_of::<usize>() + value_size()
+    }
+}
+
+macro_rules! impl_heap_size_for_values {
+    ($($value_type:ty),*) => {
+        $(
+            impl HeapSize for $value_type {
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+impl_heap_size_for_values!(bool, char, i8, u8, u16, u32, u64, usize, f32, f64);
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for Arc<str> {
+    fn heap_size(&self) -> usize {
+        shared_heap_size(self, || self.len())
+    }
+}
+
+impl<T: HeapSize> HeapSize for Arc<T> {
+    fn heap_size(&self) -> usize {
+        shared_heap_size(self, || mem::size_of::<T>() + (**self).heap_size())
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, |value| value.heap_size())
+    }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * mem::size_of::<T>()
+            + self.iter().map(|value| value.heap_size()).sum::<usize>()
+    }
+}
+
+/// Each entry of a hash table also stores the hash of its key
+impl<K: HeapSize + Eq + Hash, V: HeapSize> HeapSize for HashMap<K, V> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * (mem::size_of::<K>() + mem::size_of::<V>() + mem::size_of::<u64>())
+            + self.iter()
+                .map(|(key, value)| key.heap_size() + value.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<T: HeapSize + Eq + Hash> HeapSize for HashSet<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * (mem::size_of::<T>() + mem::size_of::<u64>())
+            + self.iter().map(|value| value.heap_size()).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heap_size_works() {
+        // Given
+        let mut values = Vec::with_capacity(2);
+        values.push(Some("coffee".to_string()));
+        values.push(None);
+
+        // When
+        let heap_size = values.heap_size();
+
+        // Then
+        let expected_heap_size = 2 * mem::size_of::<Option<String>>() + "coffee".len();
+        assert_eq!(expected_heap_size, heap_size);
+    }
+
+    #[test]
+    fn memory_footprints_should_add_up() {
+        // Given
+        let footprint = MemoryFootprint {
+            entities: 10,
+            rule_patterns: 20,
+            intent_classifiers: 0,
+            crf_weights: 5,
+            builtin_entities: 0,
+            preprocessing: 1000,
+            configuration: 0,
+        };
+        let other_footprint = MemoryFootprint {
+            entities: 1,
+            rule_patterns: 0,
+            intent_classifiers: 100,
+            crf_weights: 5,
+            builtin_entities: 2,
+            preprocessing: 0,
+            configuration: 0,
+        };
+
+        // When
+        let mut total_footprint = footprint;
+        total_footprint += other_footprint;
+
+        // Then
+        assert_eq!(11, total_footprint.entities);
+        assert_eq!(10, total_footprint.crf_weights);
+        assert_eq!(1143, total_footprint.total());
+    }
+
+    #[test]
+    fn measure_should_count_shared_allocations_once() {
+        // Given
+        let name: Arc<str> = Arc::from("coffee");
+        let names = vec![name.clone(), name.clone()];
+
+        // When
+        let footprint = MemoryFootprint::measure(|| MemoryFootprint {
+            entities: names.heap_size(),
+            ..MemoryFootprint::default()
+        });
+
+        // Then
+        let names_size = 2 * mem::size_of::<Arc<str>>();
+        let name_size = 2 * mem::size_of::<usize>() + "coffee".len();
+        assert_eq!(names_size + name_size, footprint.entities);
+        assert_eq!(names_size + 2 * name_size, names.heap_size());
+    }
+}
@@ -0,0 +1,15 @@
+use std::time::Duration;
+
+/// Receives the metrics of the parses of an engine, see `SnipsNluEngine::set_metrics_sink`
+///
+/// A sink typically feeds the counters and histograms of a monitoring system: the number of
+/// parses, the hits of each intent, the rate of parses without intent and the latency. It is
+/// called synchronously by the thread parsing the input, so it must not block.
+pub trait MetricsSink: Send + Sync {
+    /// Called after each successful parse, with the intent found, `None` when no intent was
+    /// found, and the latency of the parse
+    fn record_parse(&self, intent_name: Option<&str>, latency: Duration);
+
+    /// Called after each failed parse, with its latency
+    fn record_parse_error(&self, _latency: Duration) {}
+}
@@ -0,0 +1,185 @@
+use std::cmp::max;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+use std::ops::Range;
+
+use memory_footprint::HeapSize;
+
+/// Trie of ngrams whose edges are tokens
+///
+/// The ngrams of a sequence of tokens are looked up by walking the trie from each token, which
+/// avoids building the string of every ngram of the sequence and stops as soon as no ngram of the
+/// trie continues with the next token.
+pub struct NgramTrie<V> {
+    nodes: Vec<TrieNode<V>>,
+    max_depth: usize,
+}
+
+struct TrieNode<V> {
+    children: HashMap<String, usize>,
+    value: Option<V>,
+}
+
+impl<V> TrieNode<V> {
+    fn new() -> Self {
+        Self {
+            children: HashMap::new(),
+            value: None,
+        }
+    }
+}
+
+impl<V> NgramTrie<V> {
+    /// Inserts an ngram whose tokens are separated by spaces, replacing the value of the ngram
+    /// if it is already in the trie
+    fn insert(&mut self, ngram: &str, value: V) {
+        let mut node_index = 0;
+        let mut depth = 0;
+        for token in ngram.split(' ') {
+            depth += 1;
+            let child_index = self.nodes[node_index].children.get(token).cloned();
+            node_index = if let Some(child_index) = child_index {
+                child_index
+            } else {
+                let child_index = self.nodes.len();
+                self.nodes.push(TrieNode::new());
+                self.nodes[node_index]
+                    .children
+                    .insert(token.to_string(), child_index);
+                child_index
+            };
+        }
+        self.nodes[node_index].value = Some(value);
+        self.max_depth = max(self.max_depth, depth);
+    }
+
+    /// Number of tokens of the longest ngram of the trie
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Returns the ngrams of the trie which start at the token at index `start`, as the exclusive
+    /// end of the ngram along with its value, shortest ngrams first
+    pub fn matches_from<'a, T: AsRef<str>>(
+        &'a self,
+        tokens: &[T],
+        start: usize,
+    ) -> Vec<(usize, &'a V)> {
+        let mut matches = vec![];
+        let mut node_index = 0;
+        for (index, token) in tokens.iter().enumerate().skip(start) {
+            node_index = if let Some(child_index) =
+                self.nodes[node_index].children.get(token.as_ref())
+            {
+                *child_index
+            } else {
+                break;
+            };
+            if let Some(ref value) = self.nodes[node_index].value {
+                matches.push((index + 1, value));
+            }
+        }
+        matches
+    }
+
+    /// Returns the tokens of the ngrams of the trie
+    pub fn tokens(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .flat_map(|node| node.children.keys().map(|token| &**token))
+            .collect()
+    }
+
+    /// Returns the values of the ngrams of the trie
+    pub fn values(&self) -> Vec<&V> {
+        self.nodes
+            .iter()
+            .filter_map(|node| node.value.as_ref())
+            .collect()
+    }
+
+    /// Returns the ngrams of the tokens which are in the trie, as their token range along with
+    /// their value, ordered by start and then by length like `compute_all_ngrams`
+    pub fn find_all<'a, T: AsRef<str>>(&'a self, tokens: &[T]) -> Vec<(Range<usize>, &'a V)> {
+        (0..tokens.len())
+            .flat_map(|start| {
+                self.matches_from(tokens, start)
+                    .into_iter()
+                    .map(move |(end, value)| (start..end, value))
+            })
+            .collect()
+    }
+}
+
+impl<V: HeapSize> HeapSize for NgramTrie<V> {
+    fn heap_size(&self) -> usize {
+        self.nodes.heap_size()
+    }
+}
+
+impl<V: HeapSize> HeapSize for TrieNode<V> {
+    fn heap_size(&self) -> usize {
+        self.children.heap_size() + self.value.heap_size()
+    }
+}
+
+impl<V> FromIterator<(String, V)> for NgramTrie<V> {
+    fn from_iter<I: IntoIterator<Item = (String, V)>>(ngrams: I) -> Self {
+        let mut trie = Self {
+            nodes: vec![TrieNode::new()],
+            max_depth: 0,
+        };
+        for (ngram, value) in ngrams {
+            trie.insert(&ngram, value);
+        }
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trie() -> NgramTrie<usize> {
+        vec![
+            ("bird".to_string(), 1),
+            ("blue bird".to_string(), 2),
+            ("beautiful blue bird".to_string(), 3),
+            ("blue".to_string(), 4),
+        ].into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn matches_from_works() {
+        // Given
+        let trie = trie();
+        let tokens = vec!["a", "blue", "bird", "flies"];
+
+        // When
+        let matches = trie.matches_from(&tokens, 1);
+
+        // Then
+        assert_eq!(vec![(2, &4), (3, &2)], matches);
+        assert_eq!(3, trie.max_depth());
+    }
+
+    #[test]
+    fn find_all_works() {
+        // Given
+        let trie = trie();
+        let tokens = vec![
+            "this".to_string(),
+            "beautiful".to_string(),
+            "blue".to_string(),
+            "bird".to_string(),
+        ];
+
+        // When
+        let matches = trie.find_all(&tokens);
+
+        // Then
+        let expected_matches = vec![(1..4, &3), (2..3, &4), (2..4, &2), (3..4, &1)];
+        assert_eq!(expected_matches, matches);
+    }
+}
@@ -1,263 +1,2990 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek};
 use std::iter::FromIterator;
+use std::mem;
+use std::ops::Range;
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Instant;
 
 use itertools::Itertools;
+#[cfg(feature = "parallel")]
+use rayon::ThreadPoolBuilder;
 
+use arbitration::{arbitrate, merge_slots, rank_intents, run_parsers, ThreadPool};
+use borrowed_result::BorrowedParserResult;
+use dialogue_context::DialogueContext;
 use errors::*;
-use configurations::{DatasetMetadata, Entity, NluEngineConfigurationConvertible};
-use language::FromLanguage;
-use nlu_utils::language::Language as NluUtilsLanguage;
-use nlu_utils::token::{compute_all_ngrams, tokenize};
-use nlu_utils::string::{normalize, substring_with_char_range};
-use intent_parser::{DeterministicIntentParser, IntentParser, ProbabilisticIntentParser};
-use snips_nlu_ontology::{BuiltinEntityKind, BuiltinEntityParser, IntentParserResult, Language,
+use builtin_entity_parser::{warm_up_input, MultilingualBuiltinEntityParser};
+use configurations::{ArbitrationStrategy, DatasetMetadata, Entity, FileBasedConfiguration,
+                     InputLengthLimit, InputLengthPolicy, NluEngineConfiguration,
+                     NluEngineConfigurationConvertible, PreprocessingStep,
+                     SpellCorrectionConfiguration, WeightsQuantization, ZipBasedConfiguration};
+use lazy_section::LazySection;
+use memory_footprint::{HeapSize, MemoryFootprint};
+use metrics::MetricsSink;
+use ngrams::NgramTrie;
+use nlu_utils::token::Token;
+use nlu_utils::string::normalize;
+use parse_context::{AmbiguousBuiltinEntity, ParseContext};
+use preprocessing::{PreprocessedInput, PreprocessingPipeline, SpellCorrector};
+use resource_reader::ResourceReader;
+use intent_parser::{DeterministicIntentParser, ExactMatchIntentParser, IntentExplanation,
+                    IntentParser, IntentParserRegistry, ParserFeatures,
+                    ProbabilisticIntentParser};
+use slot_filler::FeatureExtractorRegistry;
+use tagging::{resolution_confidence, tag_result, TaggedToken};
+use tokenizer::{Tokenizer, TokenizerRegistry};
+use training::{add_utterances, train_engine, DatasetUtterance};
+use snips_nlu_ontology::{BuiltinEntityKind, IntentClassifierResult, IntentParserResult, Language,
                          Slot, SlotValue};
+use units::{convert_slot_value, UnitSystem};
+use utils::{char_range_to_grapheme_range, grapheme_aligned_range, is_blank,
+            substring_with_char_range};
 
-const MODEL_VERSION: &str = "0.13.0";
+pub const MODEL_VERSION: &str = "0.13.0";
+const WARM_UP_INPUT: &str = "42";
+const DEFAULT_NONE_SENSITIVITY: f32 = 1.0;
+/// Number of threads of the pool on which the intent parsers are run concurrently
+#[cfg(feature = "parallel")]
+const PARSER_THREADS: usize = 4;
+
+#[cfg(feature = "parallel")]
+lazy_static! {
+    /// Thread pool shared by the engines, see `ParseOptions::parallel_parsers`, which is `None`
+    /// when threads cannot be spawned on the platform
+    static ref PARSER_THREAD_POOL: Option<ThreadPool> = ThreadPoolBuilder::new()
+        .num_threads(PARSER_THREADS)
+        .thread_name(|index| format!("snips-nlu-parser-{}", index))
+        .build()
+        .ok();
+}
+
+#[cfg(not(feature = "parallel"))]
+static PARSER_THREAD_POOL: Option<ThreadPool> = None;
+
+/// Options of a parse, see `SnipsNluEngine::parse_with_options`
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Intents among which the intent of the input is searched, all the intents being used when
+    /// `None`
+    pub intents_filter: Option<Vec<String>>,
+    /// Languages in which the builtin entities of the input are extracted instead of the languages
+    /// of the model, for instance when dates are said in French to an English model, the entities
+    /// found in several languages being merged
+    pub builtin_entities_languages: Option<Vec<Language>>,
+    /// Runs the intent parsers concurrently on an internal thread pool instead of one after the
+    /// other, which cuts the latency when several parsers are needed to parse the input, the
+    /// result being the same
+    ///
+    /// The option is ignored when the crate is built without the `parallel` feature.
+    pub parallel_parsers: bool,
+    /// Gives the ranges of the slots in grapheme clusters, that is in user-perceived characters
+    /// such as letters with combining accents or emoji sequences, instead of chars
+    pub grapheme_ranges: bool,
+}
+
+/// Result of a parse borrowing the parsed input, which is converted to an `IntentParserResult`
+/// or to a `BorrowedParserResult`
+struct ParsedInput<'a> {
+    /// Parsed input, which is a prefix of the input when the input is truncated
+    input: &'a str,
+    intent: Option<IntentClassifierResult>,
+    slots: Option<Vec<Slot>>,
+}
+
+impl<'a> ParsedInput<'a> {
+    fn into_result(self) -> IntentParserResult {
+        IntentParserResult {
+            input: self.input.to_string(),
+            intent: self.intent,
+            slots: self.slots,
+        }
+    }
+
+    fn into_borrowed(self) -> BorrowedParserResult<'a> {
+        BorrowedParserResult::new(self.input, self.intent, self.slots)
+    }
+}
+
+/// Explanation of a parsing result, see `SnipsNluEngine::parse_with_explanation`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseExplanation {
+    /// Index, in the configuration, of the intent parser whose intent was selected
+    pub parser_index: Option<usize>,
+    /// Reason why the parser found the intent, for parsers which can tell
+    pub intent: Option<IntentExplanation>,
+    /// Resolution of each slot of the result, in the order of the slots
+    pub slots: Vec<SlotResolution>,
+    /// Whether the input was truncated to the maximum input length, the result referring to the
+    /// truncated input
+    pub input_truncated: bool,
+    /// Builtin entities which could fill several slots of the intent and were left out of the
+    /// slots, see `BuiltinSlotsDisambiguation::Ambiguous`, their char ranges referring to the input
+    pub ambiguous_builtin_entities: Vec<AmbiguousBuiltinEntity>,
+}
+
+/// Slot of an intent of the model, see `SnipsNluEngine::intent_slots`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotDescription<'a> {
+    pub slot_name: &'a str,
+    pub entity: &'a str,
+}
+
+/// Entity used by the slots of the model, see `SnipsNluEngine::entities`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityDescription<'a> {
+    pub name: &'a str,
+    /// Whether the entity is a builtin entity, such as `snips/number`
+    pub builtin: bool,
+    /// Whether the values which are not in the gazetteer are accepted, `false` for builtin
+    /// entities
+    pub automatically_extensible: bool,
+    /// Number of values of the gazetteer, synonyms included, `None` for builtin entities and for
+    /// the gazetteers stored in their own file by models which don't provide it
+    pub nb_values: Option<usize>,
+}
+
+/// How the value of a slot was resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotResolution {
+    /// Slot of a builtin entity, whose value is the one of the builtin entity parser
+    Builtin,
+    /// Slot whose value is in the gazetteer of its custom entity, and which takes the reference
+    /// value of the gazetteer
+    Gazetteer,
+    /// Slot whose value is not in the gazetteer of its custom entity, and which is kept as is as
+    /// the entity is automatically extensible
+    AutomaticallyExtensible,
+}
 
 pub struct SnipsNluEngine {
     dataset_metadata: DatasetMetadata,
+    /// Gazetteers of the custom entities, indexed by entity name
+    entity_utterances: HashMap<String, LazySection<EntityGazetteer>>,
     parsers: Vec<Box<IntentParser>>,
-    builtin_entity_parser: Option<Arc<BuiltinEntityParser>>,
+    arbitration_strategy: ArbitrationStrategy,
+    builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+    tokenizer: Arc<Tokenizer>,
+    language: Language,
+    preprocessing_pipeline: PreprocessingPipeline,
+    input_length_limit: Option<InputLengthLimit>,
+    unit_system: Option<UnitSystem>,
+    metrics_sink: Option<Arc<MetricsSink>>,
+    intent_thresholds: HashMap<String, f32>,
+    carry_over_slots: HashMap<String, Vec<String>>,
+    trained_model_version: String,
+    /// Configuration of the engine, only kept by the engines built with `new_updatable`
+    configuration: Option<NluEngineConfiguration>,
 }
 
 impl SnipsNluEngine {
     pub fn new<T: NluEngineConfigurationConvertible + 'static>(configuration: T) -> Result<Self> {
+        Self::new_with_registry(configuration, &IntentParserRegistry::new())
+    }
+
+    /// Builds an engine which keeps its configuration, so that it can be updated with
+    /// `add_utterances` or persisted with `into_configuration`, at the cost of the memory used
+    /// by the configuration
+    pub fn new_updatable<T: NluEngineConfigurationConvertible + 'static>(
+        configuration: T,
+    ) -> Result<Self> {
+        let resource_reader = configuration.resource_reader();
         let nlu_config = configuration.into_nlu_engine_configuration();
-        let parsers = nlu_config
+        let mut engine = Self::build(
+            nlu_config.clone(),
+            &resource_reader,
+            &IntentParserRegistry::new(),
+            &FeatureExtractorRegistry::new(),
+            &TokenizerRegistry::new(),
+            None,
+        )?;
+        engine.configuration = Some(nlu_config);
+        Ok(engine)
+    }
+
+    /// Builds an engine whose CRF and logistic regression weights are quantized, which reduces
+    /// its memory usage at the cost of a small loss of accuracy
+    pub fn new_with_quantization<T: NluEngineConfigurationConvertible + 'static>(
+        configuration: T,
+        quantization: WeightsQuantization,
+    ) -> Result<Self> {
+        let resource_reader = configuration.resource_reader();
+        Self::build(
+            configuration.into_nlu_engine_configuration(),
+            &resource_reader,
+            &IntentParserRegistry::new(),
+            &FeatureExtractorRegistry::new(),
+            &TokenizerRegistry::new(),
+            Some(quantization),
+        )
+    }
+
+    pub fn new_with_registry<T: NluEngineConfigurationConvertible + 'static>(
+        configuration: T,
+        registry: &IntentParserRegistry,
+    ) -> Result<Self> {
+        Self::new_with_registries(configuration, registry, &FeatureExtractorRegistry::new())
+    }
+
+    /// Builds the engine from the directory of a trained model, which contains the
+    /// `trained_assistant.json` configuration along with the resource files it refers to
+    pub fn from_path<P: AsRef<Path>>(root_dir: P) -> Result<Self> {
+        if !root_dir.as_ref().is_dir() {
+            bail!(ErrorKind::ConfigLoad(format!("{:?}", root_dir.as_ref())));
+        }
+        Self::new(FileBasedConfiguration::new(root_dir, false)?)
+    }
+
+    /// Builds the engine from the zip archive of a trained model, as produced by the training
+    /// command line of the Python library
+    pub fn from_zip<R: Read + Seek>(reader: R) -> Result<Self> {
+        Self::new(ZipBasedConfiguration::new(reader, false)?)
+    }
+
+    /// Builds the engine from the zip archive of a trained model located at `path`
+    pub fn from_zip_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(&path)
+            .chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", path.as_ref())))?;
+        Self::from_zip(file)
+    }
+
+    /// Builds the engine from a trained model located at `path`, which is either the directory of
+    /// the model or its zip archive
+    pub fn from_model_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        if path.as_ref().is_dir() {
+            Self::from_path(path)
+        } else {
+            Self::from_zip_path(path)
+        }
+    }
+
+    /// Trains the engine from a dataset in the JSON format used by the Python library
+    pub fn from_dataset<R: Read>(dataset: R) -> Result<Self> {
+        Self::new(train_engine(dataset)?)
+    }
+
+    /// Builds the engine from the directory of a trained model whose configuration file has been
+    /// encrypted with AES-256-GCM using `key`
+    #[cfg(feature = "encryption")]
+    pub fn from_encrypted_path<P: AsRef<Path>>(root_dir: P, key: &[u8]) -> Result<Self> {
+        if !root_dir.as_ref().is_dir() {
+            bail!(ErrorKind::ConfigLoad(format!("{:?}", root_dir.as_ref())));
+        }
+        Self::new(FileBasedConfiguration::new_encrypted(root_dir, key, false)?)
+    }
+
+    /// Builds the engine from the zip archive of a trained model which has been encrypted with
+    /// AES-256-GCM using `key`
+    #[cfg(feature = "encryption")]
+    pub fn from_encrypted_zip<R: Read>(reader: R, key: &[u8]) -> Result<Self> {
+        Self::new(ZipBasedConfiguration::new_encrypted(reader, key, false)?)
+    }
+
+    /// Builds the engine with custom intent parsers as well as custom slot filling features
+    pub fn new_with_registries<T: NluEngineConfigurationConvertible + 'static>(
+        configuration: T,
+        registry: &IntentParserRegistry,
+        feature_registry: &FeatureExtractorRegistry,
+    ) -> Result<Self> {
+        Self::new_with_tokenizer_registry(
+            configuration,
+            registry,
+            feature_registry,
+            &TokenizerRegistry::new(),
+        )
+    }
+
+    /// Builds the engine with custom intent parsers, custom slot filling features and the
+    /// tokenizers which can be selected in the `tokenizer` field of the configuration
+    pub fn new_with_tokenizer_registry<T: NluEngineConfigurationConvertible + 'static>(
+        configuration: T,
+        registry: &IntentParserRegistry,
+        feature_registry: &FeatureExtractorRegistry,
+        tokenizer_registry: &TokenizerRegistry,
+    ) -> Result<Self> {
+        let resource_reader = configuration.resource_reader();
+        Self::build(
+            configuration.into_nlu_engine_configuration(),
+            &resource_reader,
+            registry,
+            feature_registry,
+            tokenizer_registry,
+            None,
+        )
+    }
+
+    fn build(
+        nlu_config: NluEngineConfiguration,
+        resource_reader: &ResourceReader,
+        registry: &IntentParserRegistry,
+        feature_registry: &FeatureExtractorRegistry,
+        tokenizer_registry: &TokenizerRegistry,
+        weights_quantization: Option<WeightsQuantization>,
+    ) -> Result<Self> {
+        let builtin_entity_parser = get_builtin_entity_parser(&nlu_config.dataset_metadata)?;
+        let language = Language::from_str(&nlu_config.dataset_metadata.language_code)?;
+        let tokenizer = tokenizer_registry.create_with_emoji_splitting(
+            nlu_config.tokenizer.as_ref().map(|name| &**name),
+            language,
+            nlu_config.split_emojis.unwrap_or(false),
+        )?;
+        let preprocessing_steps = get_preprocessing_steps(&nlu_config);
+        let none_sensitivity = nlu_config
+            .none_sensitivity
+            .unwrap_or(DEFAULT_NONE_SENSITIVITY);
+        if none_sensitivity < 0. {
+            bail!("The none sensitivity must be positive");
+        }
+        let mut parsers = nlu_config
             .intent_parsers
             .into_iter()
             .map(|value| match value["unit_name"].as_str() {
                 Some("deterministic_intent_parser") => {
                     let config = ::serde_json::from_value(value)?;
-                    Ok(Box::new(DeterministicIntentParser::new(config)?) as _)
+                    Ok(Box::new(DeterministicIntentParser::new_with_tokenizer(
+                        config,
+                        builtin_entity_parser.clone(),
+                        tokenizer.clone(),
+                    )?) as _)
+                }
+                Some("exact_match_intent_parser") => {
+                    let config = ::serde_json::from_value(value)?;
+                    Ok(Box::new(ExactMatchIntentParser::new_with_tokenizer(
+                        config,
+                        builtin_entity_parser.clone(),
+                        tokenizer.clone(),
+                    )?) as _)
                 }
                 Some("probabilistic_intent_parser") => {
                     let config = ::serde_json::from_value(value)?;
-                    Ok(Box::new(ProbabilisticIntentParser::new(config)?) as _)
+                    Ok(Box::new(ProbabilisticIntentParser::new(
+                        config,
+                        builtin_entity_parser.clone(),
+                        feature_registry,
+                        none_sensitivity,
+                        weights_quantization,
+                        tokenizer.clone(),
+                        resource_reader,
+                    )?) as _)
                 }
                 Some(_) => Err("Unknown intent parser unit name".into()),
                 None => Err("Intent parser unit name is not properly defined".into()),
             })
-            .collect::<Result<Vec<_>>>()?;
+            .collect::<Result<Vec<Box<IntentParser>>>>()?;
+
+        if let Some(custom_parsers) = nlu_config.custom_parsers {
+            for custom_parser_config in custom_parsers {
+                parsers.push(registry.create(custom_parser_config)?);
+            }
+        }
+
+        let intent_thresholds = nlu_config.intent_thresholds.unwrap_or_else(HashMap::new);
+        for threshold in intent_thresholds.values() {
+            check_intent_threshold(*threshold)?;
+        }
+
+        let carry_over_slots = nlu_config.carry_over_slots.unwrap_or_else(HashMap::new);
+        check_carry_over_slots(&carry_over_slots, &nlu_config.dataset_metadata)?;
+
+        let mut dataset_metadata = nlu_config.dataset_metadata;
+        let entity_utterances = dataset_metadata
+            .entities
+            .iter_mut()
+            .map(|(entity_name, entity)| {
+                let utterances = if let Some(utterances_file) = entity.utterances_file.as_ref() {
+                    LazySection::mapped(utterances_file, resource_reader, |bytes| {
+                        Ok(EntityGazetteer::new(::serde_json::from_slice(bytes)?))
+                    })?
+                } else {
+                    let utterances = mem::replace(&mut entity.utterances, HashMap::new());
+                    entity.nb_values = Some(utterances.len());
+                    LazySection::loaded(EntityGazetteer::new(utterances))
+                };
+                Ok((entity_name.to_string(), utterances))
+            })
+            .collect::<Result<_>>()?;
+
+        let preprocessing_pipeline =
+            PreprocessingPipeline::new(&preprocessing_steps, language, |config| {
+                build_spell_corrector(config, &parsers, &entity_utterances, &*tokenizer)
+            })?;
+
+        Ok(SnipsNluEngine {
+            dataset_metadata,
+            entity_utterances,
+            parsers,
+            arbitration_strategy: nlu_config.arbitration_strategy.unwrap_or_default(),
+            builtin_entity_parser,
+            tokenizer,
+            language,
+            preprocessing_pipeline,
+            input_length_limit: nlu_config.input_length_limit,
+            unit_system: nlu_config.unit_system,
+            metrics_sink: None,
+            intent_thresholds,
+            carry_over_slots,
+            trained_model_version: nlu_config.model_version,
+            configuration: None,
+        })
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Returns the names of the intents of the model, sorted alphabetically
+    pub fn intents(&self) -> Vec<&str> {
+        self.dataset_metadata
+            .slot_name_mappings
+            .keys()
+            .map(|intent_name| &**intent_name)
+            .sorted()
+    }
+
+    /// Returns the slots of an intent, sorted by slot name
+    pub fn intent_slots(&self, intent_name: &str) -> Result<Vec<SlotDescription>> {
+        let slot_names_to_entities = self.dataset_metadata
+            .slot_name_mappings
+            .get(intent_name)
+            .ok_or_else(|| format!("Unknown intent: '{}'", intent_name))?;
+        Ok(slot_names_to_entities
+            .iter()
+            .map(|(slot_name, entity)| SlotDescription {
+                slot_name,
+                entity,
+            })
+            .sorted_by(|slot, other_slot| slot.slot_name.cmp(other_slot.slot_name)))
+    }
+
+    /// Returns the custom entities of the model, along with the builtin entities used by its
+    /// slots, sorted by name
+    pub fn entities(&self) -> Vec<EntityDescription> {
+        let mut entities = vec![];
+        for (entity_name, entity) in &self.dataset_metadata.entities {
+            entities.push(EntityDescription {
+                name: entity_name,
+                builtin: false,
+                automatically_extensible: entity.automatically_extensible,
+                nb_values: entity.nb_values,
+            });
+        }
+        let builtin_entities = self.dataset_metadata
+            .slot_name_mappings
+            .values()
+            .flat_map(|slot_names_to_entities| slot_names_to_entities.values())
+            .filter(|entity_name| !self.dataset_metadata.entities.contains_key(*entity_name))
+            .unique();
+        for entity_name in builtin_entities {
+            entities.push(EntityDescription {
+                name: entity_name,
+                builtin: true,
+                automatically_extensible: false,
+                nb_values: None,
+            });
+        }
+        entities.sort_by(|entity, other_entity| entity.name.cmp(other_entity.name));
+        entities
+    }
+
+    /// Returns the version of the model format with which the model was trained, while
+    /// `model_version` returns the version supported by the library
+    pub fn trained_model_version(&self) -> &str {
+        &self.trained_model_version
+    }
+
+    /// Returns the estimated memory footprint of the models of the engine, by component, see
+    /// `MemoryFootprint`
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint::measure(|| {
+            let mut footprint = self.parsers
+                .iter()
+                .fold(MemoryFootprint::default(), |footprint, parser| {
+                    footprint + parser.memory_footprint()
+                });
+            footprint.entities += self.entity_utterances
+                .values()
+                .filter(|utterances| utterances.is_loaded())
+                .filter_map(|utterances| utterances.get().ok())
+                .map(|utterances| utterances.heap_size())
+                .sum::<usize>();
+            footprint.builtin_entities = self.builtin_entity_parser.heap_size();
+            footprint.preprocessing = self.preprocessing_pipeline.heap_size();
+            footprint.configuration = self.configuration
+                .as_ref()
+                .and_then(|configuration| ::serde_json::to_vec(configuration).ok())
+                .map_or(0, |bytes| bytes.len());
+            footprint
+        })
+    }
+
+    /// Returns the configuration of the engine, including the changes made at runtime, which can
+    /// be serialized to persist the engine
+    ///
+    /// This fails unless the engine was built with `new_updatable`.
+    pub fn into_configuration(self) -> Result<NluEngineConfiguration> {
+        let mut configuration = self.configuration.ok_or_else(|| {
+            "The configuration of the engine is not kept, build it with `new_updatable`"
+        })?;
+        configuration.intent_thresholds = if self.intent_thresholds.is_empty() {
+            None
+        } else {
+            Some(self.intent_thresholds)
+        };
+        configuration.unit_system = self.unit_system;
+        Ok(configuration)
+    }
+
+    /// Returns the engine updated with new training utterances of an intent, without retraining
+    /// it, the classifier being fine-tuned on these utterances when `fine_tune_classifier` is set
+    ///
+    /// This fails unless the engine was built with `new_updatable`. The updated engine is built
+    /// without registries, engines using custom intent parsers or features must be rebuilt from
+    /// the configuration returned by `add_utterances`.
+    pub fn add_utterances(
+        self,
+        intent_name: &str,
+        utterances: &[DatasetUtterance],
+        fine_tune_classifier: bool,
+    ) -> Result<Self> {
+        let metrics_sink = self.metrics_sink.clone();
+        let configuration = add_utterances(
+            self.into_configuration()?,
+            intent_name,
+            utterances,
+            fine_tune_classifier,
+        )?;
+        let mut engine = Self::new_updatable(configuration)?;
+        engine.set_metrics_sink(metrics_sink);
+        Ok(engine)
+    }
+
+    /// Sets the minimum probability of an intent, or removes it when `None` is passed
+    pub fn set_intent_threshold(
+        &mut self,
+        intent_name: &str,
+        threshold: Option<f32>,
+    ) -> Result<()> {
+        if let Some(threshold) = threshold {
+            check_intent_threshold(threshold)?;
+            self.intent_thresholds
+                .insert(intent_name.to_string(), threshold);
+        } else {
+            self.intent_thresholds.remove(intent_name);
+        }
+        Ok(())
+    }
+
+    /// Sets the unit system in which temperature values of builtin slots are returned, the units
+    /// spoken by the user being kept when `None` is passed, which overrides the `unit_system` of
+    /// the configuration
+    pub fn set_unit_system(&mut self, unit_system: Option<UnitSystem>) {
+        self.unit_system = unit_system;
+    }
+
+    /// Sets the sink receiving the metrics of the parses, or removes it when `None` is passed
+    ///
+    /// The latency of the parses is only measured when a sink is set, as measuring time is not
+    /// supported on all targets, such as WebAssembly.
+    pub fn set_metrics_sink(&mut self, metrics_sink: Option<Arc<MetricsSink>>) {
+        self.metrics_sink = metrics_sink;
+    }
+
+    /// Parses the input, empty inputs and inputs only made of whitespaces and control characters
+    /// giving a result without intent and slots without being passed to the intent parsers
+    ///
+    /// Inputs longer than the `input_length_limit` of the configuration are either rejected or
+    /// truncated, in which case the input of the result is the truncated input.
+    pub fn parse(
+        &self,
+        input: &str,
+        intents_filter: Option<&[String]>,
+    ) -> Result<IntentParserResult> {
+        self.parse_on_thread_pool(input, intents_filter, &ParseContext::default(), None)
+    }
+
+    /// Parses an input of a dialogue like `parse`, the carry-over slots of the intent which are
+    /// missing from the input being filled with the slots of the previous turns of the context
+    ///
+    /// The carried slots have no range, as they are not part of the input. Only the slots found in
+    /// the input are recorded in the context for the next turns, the carried slots keeping the turn
+    /// in which they were filled so that they expire after the `max_turns` of the context.
+    pub fn parse_in_context(
+        &self,
+        input: &str,
+        intents_filter: Option<&[String]>,
+        context: &mut DialogueContext,
+    ) -> Result<IntentParserResult> {
+        let mut result = self.parse(input, intents_filter)?;
+        let carried_slots = if let Some(intent) = result.intent.as_ref() {
+            let slots = result.slots.as_ref().map(|slots| &**slots).unwrap_or(&[]);
+            self.carried_slots(&intent.intent_name, slots, context)
+        } else {
+            vec![]
+        };
+        context.add_turn(result.slots.as_ref().map(|slots| &**slots).unwrap_or(&[]));
+        if !carried_slots.is_empty() {
+            result
+                .slots
+                .get_or_insert_with(Vec::new)
+                .extend(carried_slots);
+        }
+        Ok(result)
+    }
+
+    /// Returns the slots of the context which fill the missing carry-over slots of the intent
+    fn carried_slots(
+        &self,
+        intent_name: &str,
+        slots: &[Slot],
+        context: &DialogueContext,
+    ) -> Vec<Slot> {
+        let (carry_over_slots, slot_name_mapping) = match (
+            self.carry_over_slots.get(intent_name),
+            self.dataset_metadata.slot_name_mappings.get(intent_name),
+        ) {
+            (Some(carry_over_slots), Some(slot_name_mapping)) => {
+                (carry_over_slots, slot_name_mapping)
+            }
+            _ => return vec![],
+        };
+        carry_over_slots
+            .iter()
+            .filter(|slot_name| slots.iter().all(|slot| &slot.slot_name != *slot_name))
+            .filter_map(|slot_name| context.slot(slot_name))
+            .filter(|slot| slot_name_mapping.get(&slot.slot_name) == Some(&slot.entity))
+            .map(|slot| Slot {
+                range: None,
+                ..slot.clone()
+            })
+            .collect()
+    }
+
+    /// Parses the input like `parse`, along with an explanation of the result: the intent parser
+    /// whose intent was selected and why, and how each slot was resolved
+    pub fn parse_with_explanation(
+        &self,
+        input: &str,
+        intents_filter: Option<&[String]>,
+    ) -> Result<(IntentParserResult, ParseExplanation)> {
+        self.parse_and_explain(input, intents_filter, &ParseContext::default(), None, true)
+            .map(|(parsed_input, explanation)| (parsed_input.into_result(), explanation))
+    }
+
+    /// Parses the input like `parse`, but returns a result which borrows the input instead of
+    /// copying it, the raw values of the slots being slices of the input
+    pub fn parse_borrowed<'a>(
+        &self,
+        input: &'a str,
+        intents_filter: Option<&[String]>,
+    ) -> Result<BorrowedParserResult<'a>> {
+        self.parse_and_explain(input, intents_filter, &ParseContext::default(), None, false)
+            .map(|(parsed_input, _)| parsed_input.into_borrowed())
+    }
+
+    /// Tags the tokens of the input with the slots found in it, along with the confidence of
+    /// these slots, see `tagging`
+    pub fn tag(&self, input: &str, intents_filter: Option<&[String]>) -> Result<Vec<TaggedToken>> {
+        let (parsed_input, explanation) = self.parse_and_explain(
+            input,
+            intents_filter,
+            &ParseContext::default(),
+            None,
+            false,
+        )?;
+        let result = parsed_input.into_result();
+        Ok(tag_result(self.tokenize(input)?, &result, &explanation))
+    }
+
+    fn parse_on_thread_pool(
+        &self,
+        input: &str,
+        intents_filter: Option<&[String]>,
+        context: &ParseContext,
+        thread_pool: Option<&ThreadPool>,
+    ) -> Result<IntentParserResult> {
+        self.parse_and_explain(input, intents_filter, context, thread_pool, false)
+            .map(|(parsed_input, _)| parsed_input.into_result())
+    }
+
+    /// Parses the input, the intent being explained only when `explain_intent` is set as it takes
+    /// another pass of the intent parser
+    fn parse_and_explain<'a>(
+        &self,
+        input: &'a str,
+        intents_filter: Option<&[String]>,
+        context: &ParseContext,
+        thread_pool: Option<&ThreadPool>,
+        explain_intent: bool,
+    ) -> Result<(ParsedInput<'a>, ParseExplanation)> {
+        let metrics_sink = if let Some(metrics_sink) = self.metrics_sink.as_ref() {
+            metrics_sink
+        } else {
+            return self.parse_without_metrics(
+                input,
+                intents_filter,
+                context,
+                thread_pool,
+                explain_intent,
+            );
+        };
+        let start = Instant::now();
+        let result =
+            self.parse_without_metrics(input, intents_filter, context, thread_pool, explain_intent);
+        match result {
+            Ok((ref result, _)) => metrics_sink.record_parse(
+                result
+                    .intent
+                    .as_ref()
+                    .map(|intent| &*intent.intent_name),
+                start.elapsed(),
+            ),
+            Err(_) => metrics_sink.record_parse_error(start.elapsed()),
+        }
+        result
+    }
+
+    fn parse_without_metrics<'a>(
+        &self,
+        input: &'a str,
+        intents_filter: Option<&[String]>,
+        context: &ParseContext,
+        thread_pool: Option<&ThreadPool>,
+        explain_intent: bool,
+    ) -> Result<(ParsedInput<'a>, ParseExplanation)> {
+        self.check_intents_filter(intents_filter)?;
+        let limited_input = self.limit_input_length(input)?;
+        let input_truncated = limited_input.len() < input.len();
+        let input = limited_input;
+        let empty_result = ParsedInput {
+            input,
+            intent: None,
+            slots: None,
+        };
+        let empty_explanation = ParseExplanation {
+            input_truncated,
+            ..ParseExplanation::default()
+        };
+        if self.parsers.is_empty() || is_blank(input) {
+            return Ok((empty_result, empty_explanation));
+        }
+        let span = pipeline_span!(
+            "parse",
+            input_hash = ::instrumentation::input_hash(input),
+            intent = ::tracing::field::Empty
+        );
+        let _entered = span.enter();
+        let set_intents: Option<HashSet<String>> = intents_filter
+            .map(|intent_list| HashSet::from_iter(intent_list.iter().map(|name| name.to_string())));
+
+        let preprocessed_input = self.preprocess(input, context);
+        let text = preprocessed_input.text();
+        let arbitration_result = {
+            let span = pipeline_span!("intent_classification");
+            let _entered = span.enter();
+            arbitrate(
+                &self.parsers,
+                &self.arbitration_strategy,
+                text,
+                set_intents.as_ref(),
+                context,
+                &self.intent_thresholds,
+                thread_pool,
+            )?
+        };
+        if let Some(arbitration_result) = arbitration_result {
+            let classification_result = arbitration_result.intent;
+            span.record("intent", &*classification_result.intent_name);
+            let (valid_slots, slot_resolutions) = self.extract_resolved_slots(
+                &preprocessed_input,
+                &classification_result.intent_name,
+                &arbitration_result.parser_indexes,
+                context,
+                thread_pool,
+            )?
+                .into_iter()
+                .unzip();
+            let ambiguous_builtin_entities = context
+                .take_ambiguous_builtin_entities()
+                .into_iter()
+                .map(|mut ambiguous_entity| {
+                    let range = preprocessed_input.original_range(&ambiguous_entity.entity.range);
+                    ambiguous_entity.entity.value =
+                        substring_with_char_range(input, &range).to_string();
+                    ambiguous_entity.entity.range = range;
+                    ambiguous_entity
+                })
+                .collect();
+            let parser_index = arbitration_result.parser_indexes.first().cloned();
+            let intent_explanation = match parser_index {
+                Some(parser_index) if explain_intent => self.parsers[parser_index]
+                    .explain_intent(text, &classification_result.intent_name, context)?,
+                _ => None,
+            };
+            let explanation = ParseExplanation {
+                parser_index,
+                intent: intent_explanation,
+                slots: slot_resolutions,
+                input_truncated,
+                ambiguous_builtin_entities,
+            };
+
+            let result = ParsedInput {
+                input,
+                intent: Some(classification_result),
+                slots: Some(valid_slots),
+            };
+            return Ok((result, explanation));
+        }
+        Ok((empty_result, empty_explanation))
+    }
+
+    /// Fails with an `UnknownIntents` error when the filter contains intents which are not
+    /// intents of the model, so that misspelled intents are not silently ignored
+    fn check_intents_filter(&self, intents_filter: Option<&[String]>) -> Result<()> {
+        let unknown_intents = intents_filter
+            .unwrap_or(&[])
+            .iter()
+            .filter(|intent_name| {
+                !self.dataset_metadata
+                    .slot_name_mappings
+                    .contains_key(*intent_name)
+            })
+            .cloned()
+            .collect_vec();
+        if !unknown_intents.is_empty() {
+            bail!(ErrorKind::UnknownIntents(unknown_intents));
+        }
+        Ok(())
+    }
+
+    /// Returns the input to parse, which is the input truncated after its last token ending
+    /// within the maximum length when it is longer and the limit policy allows it
+    fn limit_input_length<'a>(&self, input: &'a str) -> Result<&'a str> {
+        let limit = if let Some(limit) = self.input_length_limit {
+            limit
+        } else {
+            return Ok(input);
+        };
+        let length = input.chars().count();
+        if length <= limit.max_length {
+            return Ok(input);
+        }
+        match limit.policy {
+            InputLengthPolicy::Reject => bail!(ErrorKind::InputTooLong(length, limit.max_length)),
+            InputLengthPolicy::Truncate => {
+                // The char following the limit is kept so that a token cut by the limit is not
+                // mistaken for a complete one
+                let prefix = substring_with_char_range(input, &(0..limit.max_length + 1));
+                let end = self.tokenizer
+                    .tokenize(prefix)
+                    .into_iter()
+                    .map(|token| token.char_range.end)
+                    .filter(|end| *end <= limit.max_length)
+                    .max()
+                    .unwrap_or(0);
+                // A grapheme cluster ending past the limit is dropped rather than split
+                let end = grapheme_aligned_range(input, &(end..end)).start;
+                Ok(substring_with_char_range(input, &(0..end)))
+            }
+        }
+    }
+
+    /// Returns the slots of the input for the given intent, whatever the intent of the input
+    pub fn get_slots(&self, input: &str, intent_name: &str) -> Result<Vec<Slot>> {
+        if !self.dataset_metadata
+            .slot_name_mappings
+            .contains_key(intent_name)
+        {
+            bail!("Unknown intent: '{}'", intent_name);
+        }
+        let input = self.limit_input_length(input)?;
+        if is_blank(input) {
+            return Ok(vec![]);
+        }
+        let context = ParseContext::default();
+        let parser_indexes = (0..self.parsers.len()).collect_vec();
+        let preprocessed_input = self.preprocess(input, &context);
+        self.extract_slots(&preprocessed_input, intent_name, &parser_indexes, &context, None)
+    }
+
+    /// Extracts the slots with the given parsers, by order of priority, the custom slots being
+    /// resolved and the slots being mapped to the original input
+    fn extract_slots(
+        &self,
+        preprocessed_input: &PreprocessedInput,
+        intent_name: &str,
+        parser_indexes: &[usize],
+        context: &ParseContext,
+        thread_pool: Option<&ThreadPool>,
+    ) -> Result<Vec<Slot>> {
+        let resolved_slots = self.extract_resolved_slots(
+            preprocessed_input,
+            intent_name,
+            parser_indexes,
+            context,
+            thread_pool,
+        )?;
+        Ok(resolved_slots.into_iter().map(|(slot, _)| slot).collect())
+    }
+
+    /// Extracts the slots like `extract_slots`, along with the way they were resolved
+    fn extract_resolved_slots(
+        &self,
+        preprocessed_input: &PreprocessedInput,
+        intent_name: &str,
+        parser_indexes: &[usize],
+        context: &ParseContext,
+        thread_pool: Option<&ThreadPool>,
+    ) -> Result<Vec<(Slot, SlotResolution)>> {
+        let text = preprocessed_input.text();
+        let slots_by_priority = {
+            let span = pipeline_span!("slot_filling", intent = intent_name);
+            let _entered = span.enter();
+            run_parsers(&self.parsers, parser_indexes, thread_pool, |parser| {
+                parser.get_slots(text, intent_name, context)
+            }).into_iter()
+                .collect::<Result<Vec<_>>>()?
+        };
+        let span = pipeline_span!("slot_resolution", intent = intent_name);
+        let _entered = span.enter();
+        let mut valid_slots = vec![];
+        for slot in merge_slots(slots_by_priority) {
+            let preprocessed_raw_value = slot.raw_value.clone();
+            let slot = preprocessed_input.restore_slot(slot);
+            if let Some((slot, resolution)) =
+                self.resolve_custom_slot(slot, &preprocessed_raw_value)?
+            {
+                valid_slots.push((self.convert_slot_units(slot), resolution));
+            }
+        }
+        Ok(deduplicate_slots(valid_slots))
+    }
+
+    /// Parses the input like `parse`, with the options of the parse, see `ParseOptions`
+    ///
+    /// When the options set languages for the builtin entities, the builtin entities are
+    /// extracted in these languages by all the processing units, instead of the languages of the
+    /// model.
+    pub fn parse_with_options(
+        &self,
+        input: &str,
+        options: &ParseOptions,
+    ) -> Result<IntentParserResult> {
+        let intents_filter = options.intents_filter.as_ref().map(|intents| &**intents);
+        let thread_pool = if options.parallel_parsers && self.parsers.len() > 1 {
+            PARSER_THREAD_POOL.as_ref()
+        } else {
+            None
+        };
+        let context = options
+            .builtin_entities_languages
+            .as_ref()
+            .map(|languages| ParseContext::with_builtin_entities_languages(languages))
+            .unwrap_or_default();
+        let result = self.parse_on_thread_pool(input, intents_filter, &context, thread_pool)?;
+        if options.grapheme_ranges {
+            Ok(convert_to_grapheme_ranges(result))
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Returns the intents found in the input by decreasing probability, the probabilities given
+    /// by the intent parsers being combined according to the arbitration strategy
+    ///
+    /// Blank inputs, like in `parse`, have no intent, and long inputs are limited like in `parse`.
+    pub fn get_intents(
+        &self,
+        input: &str,
+        intents_filter: Option<&[String]>,
+    ) -> Result<Vec<IntentClassifierResult>> {
+        self.check_intents_filter(intents_filter)?;
+        let input = self.limit_input_length(input)?;
+        if is_blank(input) {
+            return Ok(vec![]);
+        }
+        let set_intents: Option<HashSet<String>> = intents_filter
+            .map(|intent_list| HashSet::from_iter(intent_list.iter().map(|name| name.to_string())));
+        let context = ParseContext::default();
+        rank_intents(
+            &self.parsers,
+            &self.arbitration_strategy,
+            self.preprocess(input, &context).text(),
+            set_intents.as_ref(),
+            &context,
+            &self.intent_thresholds,
+        )
+    }
+
+    /// Returns the features computed on the input by each intent parser relying on features,
+    /// the slot filler features being the ones of the given intent
+    ///
+    /// Blank inputs, like in `parse`, have no features, and long inputs are limited like in
+    /// `parse`.
+    pub fn get_features(&self, input: &str, intent_name: &str) -> Result<Vec<ParserFeatures>> {
+        let input = self.limit_input_length(input)?;
+        if is_blank(input) {
+            return Ok(vec![]);
+        }
+        let context = ParseContext::default();
+        let preprocessed_input = self.preprocess(input, &context);
+        let mut features = vec![];
+        for parser in &self.parsers {
+            if let Some(parser_features) =
+                parser.get_features(preprocessed_input.text(), intent_name, &context)?
+            {
+                features.push(parser_features);
+            }
+        }
+        Ok(features)
+    }
+
+    /// Returns the tokens of the input as the intent parsers see them, after the preprocessing of
+    /// the input, their byte and char ranges referring to the original input
+    ///
+    /// Blank inputs, like in `parse`, have no tokens, and long inputs are limited like in `parse`,
+    /// the tokens of a truncated input being the tokens of the parsed input.
+    pub fn tokenize(&self, input: &str) -> Result<Vec<Token>> {
+        let input = self.limit_input_length(input)?;
+        if is_blank(input) {
+            return Ok(vec![]);
+        }
+        let preprocessed_input = self.preprocess(input, &ParseContext::default());
+        let tokens = self.tokenizer.tokenize(preprocessed_input.text());
+        Ok(preprocessed_input.restore_tokens(tokens))
+    }
+
+    /// Runs a canned input through the builtin entity parser, for each builtin entity kind used in
+    /// the model, and through each intent parser, so that lazily initialized resources are loaded
+    /// and failures are reported before the engine is used on actual inputs
+    ///
+    /// The input of each builtin entity kind contains an entity of this kind, see
+    /// `builtin_entity_parser::warm_up_input`, and a kind which extracts nothing from it is
+    /// reported as a failure. Kinds without input in the language of the engine are only loaded.
+    /// The gazetteers of the custom entities stored in files are read, which builds their tries.
+    pub fn warm_up(&self) -> Result<()> {
+        let mut failures: Vec<String> = vec![];
+
+        if let Some(builtin_entity_parser) = self.builtin_entity_parser.as_ref() {
+            let builtin_entity_kinds = self.dataset_metadata
+                .slot_name_mappings
+                .values()
+                .flat_map(|mapping| mapping.values())
+                .flat_map(|entity| BuiltinEntityKind::from_identifier(entity).ok())
+                .unique()
+                .collect_vec();
+            for kind in builtin_entity_kinds {
+                let input = warm_up_input(kind, self.language).unwrap_or(WARM_UP_INPUT);
+                let entities = builtin_entity_parser.extract_entities(input, Some(&[kind]));
+                let is_checked = cfg!(feature = "builtin_entities")
+                    && warm_up_input(kind, self.language).is_some();
+                if is_checked && entities.is_empty() {
+                    failures.push(format!(
+                        "builtin entity {}: nothing extracted from {:?}",
+                        kind.identifier(),
+                        input
+                    ));
+                }
+            }
+        }
+
+        for (entity_name, gazetteer) in &self.entity_utterances {
+            if let Err(error) = gazetteer.get() {
+                failures.push(format!("gazetteer of entity {}: {}", entity_name, error));
+            }
+        }
+
+        let context = ParseContext::default();
+        for (parser_index, parser) in self.parsers.iter().enumerate() {
+            match parser.get_intent(WARM_UP_INPUT, None, &context) {
+                Ok(Some(classification_result)) => {
+                    let intent_name = &classification_result.intent_name;
+                    if let Err(error) = parser.get_slots(WARM_UP_INPUT, intent_name, &context) {
+                        failures.push(format!("parser #{} slots: {}", parser_index, error));
+                    }
+                }
+                Ok(None) => (),
+                Err(error) => failures.push(format!("parser #{} intent: {}", parser_index, error)),
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            bail!(ErrorKind::WarmUpFailed(failures))
+        }
+    }
+
+    // TODO: Expose directly a static variable
+    pub fn model_version() -> &'static str {
+        MODEL_VERSION
+    }
+}
+
+impl SnipsNluEngine {
+    /// Extracts the value of a slot from the whole input, the range of the slot being the char
+    /// range of its value in the input
+    ///
+    /// The input is preprocessed as in `parse`, the raw value and the range of the slot referring
+    /// to the original input.
+    pub fn extract_slot(
+        &self,
+        input: String,
+        intent_name: &str,
+        slot_name: &str,
+    ) -> Result<Option<Slot>> {
+        let entity_name = self.dataset_metadata
+            .slot_name_mappings
+            .get(intent_name)
+            .ok_or_else(|| format!("Unknown intent: {}", intent_name))?
+            .get(slot_name)
+            .ok_or_else(|| format!("Unknown slot: {}", &slot_name))?;
+        let input = self.limit_input_length(&input)?;
+        if is_blank(input) {
+            return Ok(None);
+        }
+        let preprocessed_input = self.preprocess(input, &ParseContext::default());
+        let input = preprocessed_input.text().to_string();
+
+        let slot = if let Some(custom_entity) = self.dataset_metadata.entities.get(entity_name) {
+            let gazetteer = self.get_entity_gazetteer(entity_name)?;
+            extract_custom_slot(
+                input,
+                entity_name.to_string(),
+                slot_name.to_string(),
+                custom_entity,
+                &gazetteer,
+                &*self.tokenizer,
+            )
+        } else if let Some(builtin_entity_parser) = self.builtin_entity_parser.clone() {
+            extract_builtin_slot(
+                input,
+                entity_name.to_string(),
+                slot_name.to_string(),
+                &builtin_entity_parser,
+            )?
+        } else {
+            None
+        };
+        Ok(slot.map(|slot| self.convert_slot_units(preprocessed_input.restore_slot(slot))))
+    }
+
+    /// Replaces the value of a custom slot by its reference value, custom slots whose value is
+    /// unknown being discarded when their entity is not automatically extensible
+    ///
+    /// The value is looked up with the raw value of the original input first, and then with the
+    /// raw value of the preprocessed input, so that values with contractions as well as
+    /// normalized or corrected values are resolved.
+    fn resolve_custom_slot(
+        &self,
+        slot: Slot,
+        preprocessed_raw_value: &str,
+    ) -> Result<Option<(Slot, SlotResolution)>> {
+        let entity = if let Some(entity) = self.dataset_metadata.entities.get(&slot.entity) {
+            entity
+        } else {
+            return Ok(Some((slot, SlotResolution::Builtin)));
+        };
+        let gazetteer = self.get_entity_gazetteer(&slot.entity)?;
+        let reference_value = gazetteer
+            .utterances
+            .get(&slot.raw_value)
+            .or_else(|| gazetteer.utterances.get(preprocessed_raw_value));
+        Ok(if let Some(reference_value) = reference_value {
+            let value = SlotValue::Custom(reference_value.to_string().into());
+            Some((slot.with_slot_value(value), SlotResolution::Gazetteer))
+        } else if entity.automatically_extensible {
+            Some((slot, SlotResolution::AutomaticallyExtensible))
+        } else {
+            None
+        })
+    }
+
+    fn preprocess(&self, input: &str, context: &ParseContext) -> PreprocessedInput {
+        let span = pipeline_span!("preprocessing");
+        let _entered = span.enter();
+        // The entities are the ones which the deterministic parser tags in the input, and they
+        // are reused when the spell correction doesn't change the input
+        let builtin_entity_parser =
+            context.builtin_entity_parser(self.builtin_entity_parser.as_ref());
+        self.preprocessing_pipeline
+            .apply(PreprocessedInput::new(input), |text| {
+                builtin_entity_parser
+                    .map(|parser| {
+                        context
+                            .extract_builtin_entities(parser, text, None)
+                            .into_iter()
+                            .map(|entity| entity.range)
+                            .collect()
+                    })
+                    .unwrap_or_else(Vec::new)
+            })
+    }
+
+    fn get_entity_gazetteer(&self, entity_name: &str) -> Result<Arc<EntityGazetteer>> {
+        self.entity_utterances
+            .get(entity_name)
+            .ok_or_else(|| format!("Unknown entity: {}", entity_name))?
+            .get()
+    }
+
+    fn convert_slot_units(&self, slot: Slot) -> Slot {
+        if let Some(unit_system) = self.unit_system {
+            let value = convert_slot_value(slot.value.clone(), unit_system);
+            slot.with_slot_value(value)
+        } else {
+            slot
+        }
+    }
+}
+
+/// Gazetteer of a custom entity, mapping its normalized values to their reference values
+///
+/// The values are also stored in a token trie, built along with the gazetteer, so that the
+/// values found in an input are looked up in a time proportional to the length of the input
+/// rather than to the size of the gazetteer. The gazetteers of the model are built with the
+/// engine, or when their file is first read for the gazetteers stored in files, which
+/// `SnipsNluEngine::warm_up` does.
+struct EntityGazetteer {
+    utterances: HashMap<String, String>,
+    trie: NgramTrie<String>,
+}
+
+impl EntityGazetteer {
+    fn new(utterances: HashMap<String, String>) -> Self {
+        let trie = utterances
+            .iter()
+            .map(|(utterance, reference_value)| (utterance.clone(), reference_value.clone()))
+            .collect();
+        Self { utterances, trie }
+    }
+}
+
+impl HeapSize for EntityGazetteer {
+    fn heap_size(&self) -> usize {
+        self.utterances.heap_size() + self.trie.heap_size()
+    }
+}
+
+fn check_intent_threshold(threshold: f32) -> Result<()> {
+    if threshold.is_nan() || threshold < 0. || threshold > 1. {
+        bail!("Intent thresholds must be between 0 and 1, found {}", threshold);
+    }
+    Ok(())
+}
+
+fn check_carry_over_slots(
+    carry_over_slots: &HashMap<String, Vec<String>>,
+    dataset_metadata: &DatasetMetadata,
+) -> Result<()> {
+    for (intent_name, slot_names) in carry_over_slots {
+        let slot_name_mapping =
+            if let Some(mapping) = dataset_metadata.slot_name_mappings.get(intent_name) {
+                mapping
+            } else {
+                bail!(ErrorKind::UnknownIntents(vec![intent_name.clone()]));
+            };
+        for slot_name in slot_names {
+            if !slot_name_mapping.contains_key(slot_name) {
+                bail!("Unknown carry-over slot '{}' of intent '{}'", slot_name, intent_name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the preprocessing steps of the configuration, the Unicode normalization, the expansion
+/// of the contractions, the spell correction and the emoji aliases of the configuration being
+/// inserted at their place around the steps of the `preprocessing` section
+fn get_preprocessing_steps(nlu_config: &NluEngineConfiguration) -> Vec<PreprocessingStep> {
+    let mut steps = vec![];
+    if let Some(form) = nlu_config.unicode_normalization {
+        steps.push(PreprocessingStep::NormalizeUnicode(form));
+    }
+    steps.extend(nlu_config.preprocessing.iter().flat_map(|steps| steps.iter().cloned()));
+    if nlu_config.expand_contractions.unwrap_or(false) {
+        steps.push(PreprocessingStep::ExpandContractions);
+    }
+    if let Some(config) = nlu_config.spell_correction.as_ref() {
+        steps.push(PreprocessingStep::CorrectSpelling(config.clone()));
+    }
+    if let Some(aliases) = nlu_config.emoji_aliases.as_ref() {
+        if !aliases.is_empty() {
+            steps.push(PreprocessingStep::ReplaceEmojis(aliases.clone()));
+        }
+    }
+    steps
+}
+
+/// Builds the spell corrector from the words of the intent parsers and of the values of the
+/// custom entities
+///
+/// The gazetteers stored in their own files are not loaded for that purpose, their values being
+/// known through the vocabulary of the intent classifier when it doesn't stem the inputs.
+fn build_spell_corrector(
+    config: &SpellCorrectionConfiguration,
+    parsers: &[Box<IntentParser>],
+    entity_utterances: &HashMap<String, LazySection<EntityGazetteer>>,
+    tokenizer: &Tokenizer,
+) -> Result<SpellCorrector> {
+    let mut vocabulary: Vec<String> = parsers
+        .iter()
+        .flat_map(|parser| parser.vocabulary())
+        .collect();
+    for utterances in entity_utterances.values().filter(|utterances| utterances.is_loaded()) {
+        for (utterance, reference_value) in utterances.get()?.utterances.iter() {
+            vocabulary.extend(tokenizer.tokenize_light(utterance));
+            vocabulary.extend(tokenizer.tokenize_light(reference_value));
+        }
+    }
+    Ok(SpellCorrector::new(vocabulary, config))
+}
+
+fn get_builtin_entity_parser(
+    dataset_metadata: &DatasetMetadata,
+) -> Result<Option<Arc<MultilingualBuiltinEntityParser>>> {
+    let language = if let Ok(language) = Language::from_str(&dataset_metadata.language_code) {
+        language
+    } else {
+        return Ok(None);
+    };
+    let mut languages = vec![language];
+    if let Some(additional_languages) = dataset_metadata
+        .additional_builtin_entities_languages
+        .as_ref()
+    {
+        for language_code in additional_languages {
+            let additional_language = Language::from_str(language_code)?;
+            if !languages.contains(&additional_language) {
+                languages.push(additional_language);
+            }
+        }
+    }
+    let mut fallback_languages = vec![];
+    if let Some(fallback_language_codes) = dataset_metadata
+        .fallback_builtin_entities_languages
+        .as_ref()
+    {
+        for language_code in fallback_language_codes {
+            let fallback_language = Language::from_str(language_code)?;
+            if !languages.contains(&fallback_language)
+                && !fallback_languages.contains(&fallback_language)
+            {
+                fallback_languages.push(fallback_language);
+            }
+        }
+    }
+    Ok(Some(Arc::new(
+        MultilingualBuiltinEntityParser::new(&languages)
+            .with_fallback_languages(&fallback_languages),
+    )))
+}
+
+fn extract_custom_slot(
+    input: String,
+    entity_name: String,
+    slot_name: String,
+    custom_entity: &Entity,
+    gazetteer: &EntityGazetteer,
+    tokenizer: &Tokenizer,
+) -> Option<Slot> {
+    let tokens = tokenizer.tokenize(&input);
+    let normalized_tokens = tokens.iter().map(|token| normalize(&token.value)).collect_vec();
+    // The matches are ordered by start, so the first of the longest matches is kept
+    let mut longest_match: Option<(Range<usize>, &String)> = None;
+    for (token_range, reference_value) in gazetteer.trie.find_all(&normalized_tokens) {
+        if longest_match
+            .as_ref()
+            .map_or(true, |&(ref longest_range, _)| token_range.len() > longest_range.len())
+        {
+            longest_match = Some((token_range, reference_value));
+        }
+    }
+
+    if let Some((token_range, reference_value)) = longest_match {
+        let range = grapheme_aligned_range(
+            &input,
+            &(tokens[token_range.start].char_range.start
+                ..tokens[token_range.end - 1].char_range.end),
+        );
+        Some(Slot {
+            raw_value: substring_with_char_range(&input, &range).to_string(),
+            value: SlotValue::Custom(reference_value.to_string().into()),
+            range: Some(range),
+            entity: entity_name,
+            slot_name,
+        })
+    } else if custom_entity.automatically_extensible {
+        let range = 0..input.chars().count();
+        Some(Slot {
+            raw_value: input.clone(),
+            value: SlotValue::Custom(input.into()),
+            range: Some(range),
+            entity: entity_name,
+            slot_name,
+        })
+    } else {
+        None
+    }
+}
+
+/// Merges the equivalent slots, which can be found by both the builtin entities and the custom
+/// entities tagging, the most confident slot being kept: the slot with the highest
+/// `resolution_confidence`, then the slot with the widest range, then the slot found first
+///
+/// A slot replaces all the kept slots it is equivalent to, see `slots_are_equivalent`.
+fn deduplicate_slots(slots: Vec<(Slot, SlotResolution)>) -> Vec<(Slot, SlotResolution)> {
+    let confidence = |&(ref slot, resolution): &(Slot, SlotResolution)| {
+        (
+            resolution_confidence(slot, resolution),
+            slot.range.as_ref().map_or(0, |range| range.end - range.start),
+        )
+    };
+    let mut deduplicated_slots: Vec<(Slot, SlotResolution)> = vec![];
+    for resolved_slot in slots {
+        let duplicate_indexes = deduplicated_slots
+            .iter()
+            .enumerate()
+            .filter(|&(_, kept_slot)| slots_are_equivalent(kept_slot, &resolved_slot))
+            .map(|(index, _)| index)
+            .collect_vec();
+        let first_index = if let Some(index) = duplicate_indexes.first() {
+            *index
+        } else {
+            deduplicated_slots.push(resolved_slot);
+            continue;
+        };
+        // The duplicates are removed from the last one, so that the first of the equally
+        // confident slots is kept
+        let mut most_confident_slot = resolved_slot;
+        for index in duplicate_indexes.into_iter().rev() {
+            let kept_slot = deduplicated_slots.remove(index);
+            if confidence(&kept_slot) >= confidence(&most_confident_slot) {
+                most_confident_slot = kept_slot;
+            }
+        }
+        deduplicated_slots.insert(first_index, most_confident_slot);
+    }
+    deduplicated_slots
+}
+
+/// Slots of the same entity are equivalent when their ranges are equal or contained in one
+/// another, and a builtin slot is equivalent to a slot whose value is in a gazetteer when they
+/// have the same range
+fn slots_are_equivalent(
+    &(ref slot, resolution): &(Slot, SlotResolution),
+    &(ref other_slot, other_resolution): &(Slot, SlotResolution),
+) -> bool {
+    match (resolution, other_resolution) {
+        (SlotResolution::Builtin, SlotResolution::Gazetteer)
+        | (SlotResolution::Gazetteer, SlotResolution::Builtin) => {
+            return slot.range.is_some() && slot.range == other_slot.range;
+        }
+        _ => {}
+    }
+    if slot.entity != other_slot.entity {
+        return false;
+    }
+    match (slot.range.as_ref(), other_slot.range.as_ref()) {
+        (Some(range), Some(other_range)) => {
+            (range.start <= other_range.start && other_range.end <= range.end)
+                || (other_range.start <= range.start && range.end <= other_range.end)
+        }
+        _ => slot.slot_name == other_slot.slot_name && slot.raw_value == other_slot.raw_value,
+    }
+}
+
+/// Converts the char ranges of the slots of the result to grapheme cluster ranges
+fn convert_to_grapheme_ranges(result: IntentParserResult) -> IntentParserResult {
+    let slots = {
+        let input = &result.input;
+        result.slots.map(|slots| {
+            slots
+                .into_iter()
+                .map(|slot| Slot {
+                    range: slot.range
+                        .map(|range| char_range_to_grapheme_range(input, &range)),
+                    ..slot
+                })
+                .collect()
+        })
+    };
+    IntentParserResult { slots, ..result }
+}
+
+fn extract_builtin_slot(
+    input: String,
+    entity_name: String,
+    slot_name: String,
+    builtin_entity_parser: &MultilingualBuiltinEntityParser,
+) -> Result<Option<Slot>> {
+    let builtin_entity_kind = BuiltinEntityKind::from_identifier(&entity_name)?;
+    Ok(builtin_entity_parser
+        .extract_entities(&input, Some(&[builtin_entity_kind]))
+        .first()
+        .map(|rustlin_entity| {
+            let range = grapheme_aligned_range(&input, &rustlin_entity.range);
+            Slot {
+                raw_value: substring_with_char_range(&input, &range).to_string(),
+                value: rustlin_entity.entity.clone(),
+                range: Some(range),
+                entity: entity_name,
+                slot_name,
+            }
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use configurations::UnicodeNormalization;
+    use nlu_engine_builder::SnipsNluEngineBuilder;
+    use snips_nlu_ontology::{IntentClassifierResult, NumberValue};
+    use testutils::{epsilon_eq, parse_json};
+    use tokenizer::DefaultTokenizer;
+    use utils::file_path;
+
+    #[test]
+    fn parse_works() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let result = nlu_engine
+            .parse("Make me two cups of coffee please", None)
+            .unwrap();
+
+        // Then
+        let expected_entity_value = SlotValue::Number(NumberValue { value: 2.0 });
+        let expected_result = IntentParserResult {
+            input: "Make me two cups of coffee please".to_string(),
+            intent: Some(IntentClassifierResult {
+                intent_name: "MakeCoffee".to_string(),
+                probability: 0.6838855,
+            }),
+            slots: Some(vec![
+                Slot {
+                    raw_value: "two".to_string(),
+                    value: expected_entity_value,
+                    range: Some(8..11),
+                    entity: "snips/number".to_string(),
+                    slot_name: "number_of_cups".to_string(),
+                },
+            ]),
+        };
+
+        assert_eq!(expected_result, result)
+    }
+
+    #[test]
+    fn parse_should_return_empty_result_for_blank_inputs() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let inputs = vec!["", "   ", "\t\n", "\u{0}\u{7} "];
+
+        for input in inputs {
+            // When
+            let result = nlu_engine.parse(input, None).unwrap();
+            let slots = nlu_engine.get_slots(input, "MakeCoffee").unwrap();
+            let slot = nlu_engine
+                .extract_slot(input.to_string(), "MakeCoffee", "number_of_cups")
+                .unwrap();
+
+            // Then
+            let expected_result = IntentParserResult {
+                input: input.to_string(),
+                intent: None,
+                slots: None,
+            };
+            assert_eq!(expected_result, result);
+            assert!(slots.is_empty());
+            assert_eq!(None, slot);
+        }
+    }
+
+    #[test]
+    fn get_intents_should_return_no_intent_for_blank_inputs() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let inputs = vec!["", "   ", "\t\n", "\u{0}\u{7} "];
+
+        for input in inputs {
+            // When
+            let intents = nlu_engine.get_intents(input, None).unwrap();
+
+            // Then
+            assert!(intents.is_empty());
+        }
+    }
+
+    #[test]
+    fn get_features_should_return_no_features_for_blank_inputs() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let inputs = vec!["", "   ", "\t\n", "\u{0}\u{7} "];
+
+        for input in inputs {
+            // When
+            let features = nlu_engine.get_features(input, "MakeCoffee").unwrap();
+
+            // Then
+            assert!(features.is_empty());
+        }
+    }
+
+    #[test]
+    fn tokenize_should_return_no_tokens_for_blank_inputs() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let inputs = vec!["", "   ", "\t\n", "\u{0}\u{7} "];
+
+        for input in inputs {
+            // When
+            let tokens = nlu_engine.tokenize(input).unwrap();
+
+            // Then
+            assert!(tokens.is_empty());
+        }
+    }
+
+    #[test]
+    fn from_path_works() {
+        // When
+        let nlu_engine = SnipsNluEngine::from_path(file_path("tests/configurations"));
+        let missing_nlu_engine = SnipsNluEngine::from_path(file_path("tests/missing_model"));
+
+        // Then
+        assert!(nlu_engine.is_ok());
+        assert!(missing_nlu_engine.is_err());
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn from_path_should_load_onnx_model_relative_to_model_directory() {
+        // Given
+        let nlu_engine = SnipsNluEngine::from_path(file_path("tests/onnx_model")).unwrap();
+
+        // When
+        let intents = nlu_engine.get_intents("a coffee please", None).unwrap();
+
+        // Then
+        assert_eq!("MakeCoffee", intents[0].intent_name);
+    }
+
+    #[test]
+    fn from_zip_path_works() {
+        // When
+        let nlu_engine =
+            SnipsNluEngine::from_zip_path(file_path("tests/zip_files/sample_config.zip"));
+        let missing_nlu_engine =
+            SnipsNluEngine::from_zip_path(file_path("tests/zip_files/missing.zip"));
+
+        // Then
+        assert!(nlu_engine.is_ok());
+        assert!(missing_nlu_engine.is_err());
+    }
+
+    #[test]
+    fn from_model_path_works() {
+        // When
+        let directory_nlu_engine =
+            SnipsNluEngine::from_model_path(file_path("tests/configurations"));
+        let zip_nlu_engine =
+            SnipsNluEngine::from_model_path(file_path("tests/zip_files/sample_config.zip"));
+        let missing_nlu_engine = SnipsNluEngine::from_model_path(file_path("tests/missing_model"));
+
+        // Then
+        assert!(directory_nlu_engine.is_ok());
+        assert!(zip_nlu_engine.is_ok());
+        assert!(missing_nlu_engine.is_err());
+    }
+
+    #[test]
+    fn parse_should_ignore_intent_below_threshold() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let mut nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        nlu_engine
+            .set_intent_threshold("MakeCoffee", Some(0.9))
+            .unwrap();
+
+        // When
+        let result = nlu_engine
+            .parse("Make me two cups of coffee please", None)
+            .unwrap();
+
+        // Then
+        assert_eq!(None, result.intent);
+        assert!(nlu_engine.set_intent_threshold("MakeTea", Some(1.5)).is_err());
+        assert!(nlu_engine
+            .set_intent_threshold("MakeTea", Some(::std::f32::NAN))
+            .is_err());
+    }
+
+    #[test]
+    fn new_with_quantization_should_parse_like_unquantized_engine() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration.clone()).unwrap();
+        let input = "Make me two cups of coffee please";
+        let expected_result = nlu_engine.parse(input, None).unwrap();
+
+        for quantization in vec![WeightsQuantization::F16, WeightsQuantization::Int8] {
+            // When
+            let quantized_engine =
+                SnipsNluEngine::new_with_quantization(configuration.clone(), quantization)
+                    .unwrap();
+            let result = quantized_engine.parse(input, None).unwrap();
+
+            // Then
+            assert_eq!(
+                expected_result.intent.as_ref().map(|intent| &intent.intent_name),
+                result.intent.as_ref().map(|intent| &intent.intent_name)
+            );
+            assert_eq!(expected_result.slots, result.slots);
+        }
+    }
+
+    #[test]
+    fn into_configuration_should_round_trip() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let mut nlu_engine = SnipsNluEngine::new_updatable(configuration).unwrap();
+        nlu_engine
+            .set_intent_threshold("MakeTea", Some(0.5))
+            .unwrap();
+
+        // When
+        let serialized_configuration =
+            ::serde_json::to_string(&nlu_engine.into_configuration().unwrap()).unwrap();
+        let deserialized_configuration: NluEngineConfiguration =
+            ::serde_json::from_str(&serialized_configuration).unwrap();
+        let thresholds = deserialized_configuration.intent_thresholds.clone();
+        let reloaded_nlu_engine = SnipsNluEngine::new(deserialized_configuration).unwrap();
+        let result = reloaded_nlu_engine
+            .parse("Make me two cups of coffee please", None)
+            .unwrap();
+
+        // Then
+        assert_eq!(Some(hashmap!["MakeTea".to_string() => 0.5]), thresholds);
+        assert_eq!(
+            Some("MakeCoffee".to_string()),
+            result.intent.map(|intent| intent.intent_name)
+        );
+    }
+
+    #[test]
+    fn into_configuration_should_fail_when_configuration_is_not_kept() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let result = nlu_engine.into_configuration();
+
+        // Then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn get_intents_works() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let intents = nlu_engine
+            .get_intents("Make me two cups of coffee please", None)
+            .unwrap();
+        let filtered_intents = nlu_engine
+            .get_intents(
+                "Make me two cups of coffee please",
+                Some(&["MakeTea".to_string()]),
+            )
+            .unwrap();
+
+        // Then
+        let expected_top_intent = IntentClassifierResult {
+            intent_name: "MakeCoffee".to_string(),
+            probability: 0.6838855,
+        };
+        assert_eq!(expected_top_intent, intents[0]);
+        assert!(intents.len() > 1);
+        assert_eq!(1, filtered_intents.len());
+        assert_eq!("MakeTea", filtered_intents[0].intent_name);
+    }
+
+    #[test]
+    fn get_features_works() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let features = nlu_engine
+            .get_features("Make me two cups of coffee", "MakeCoffee")
+            .unwrap();
+
+        // Then
+        assert_eq!(1, features.len());
+        assert!(features[0].intent_classifier_features.is_some());
+        let tokens = features[0]
+            .slot_filler_features
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|token_features| &*token_features.token)
+            .collect_vec();
+        assert_eq!(vec!["Make", "me", "two", "cups", "of", "coffee"], tokens);
+    }
+
+    #[test]
+    fn tokenize_works() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let tokens = nlu_engine.tokenize("Make me two cups").unwrap();
+
+        // Then
+        let expected_tokens = vec![
+            Token::new("Make".to_string(), 0..4, 0..4),
+            Token::new("me".to_string(), 5..7, 5..7),
+            Token::new("two".to_string(), 8..11, 8..11),
+            Token::new("cups".to_string(), 12..16, 12..16),
+        ];
+        assert_eq!(expected_tokens, tokens);
+    }
+
+    #[test]
+    fn tokenize_should_map_preprocessed_tokens_to_the_input() {
+        // Given
+        let mut configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        configuration.unicode_normalization = Some(UnicodeNormalization::Nfc);
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let tokens = nlu_engine.tokenize("un cafe\u{301} noir").unwrap();
+
+        // Then
+        let expected_tokens = vec![
+            Token::new("un".to_string(), 0..2, 0..2),
+            Token::new("café".to_string(), 3..9, 3..8),
+            Token::new("noir".to_string(), 10..14, 9..13),
+        ];
+        assert_eq!(expected_tokens, tokens);
+    }
+
+    #[test]
+    fn warm_up_works() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let result = nlu_engine.warm_up();
+
+        // Then
+        assert!(result.is_ok());
+    }
+
+    /// Tokenizer which finds no token, so that no value of the gazetteers can be matched
+    struct EmptyTokenizer;
+
+    impl Tokenizer for EmptyTokenizer {
+        fn tokenize(&self, _input: &str) -> Vec<Token> {
+            vec![]
+        }
+    }
+
+    #[test]
+    fn should_use_tokenizer_of_configuration() {
+        // Given
+        let mut configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        configuration.tokenizer = Some("empty".to_string());
+        let mut tokenizer_registry = TokenizerRegistry::new();
+        tokenizer_registry.register("empty", |_: Language| -> Result<Box<Tokenizer>> {
+            Ok(Box::new(EmptyTokenizer))
+        });
+        let nlu_engine = SnipsNluEngine::new_with_tokenizer_registry(
+            configuration,
+            &IntentParserRegistry::new(),
+            &FeatureExtractorRegistry::new(),
+            &tokenizer_registry,
+        ).unwrap();
+
+        // When
+        let slot = nlu_engine
+            .extract_slot("boiling".to_string(), "MakeTea", "beverage_temperature")
+            .unwrap()
+            .unwrap();
+
+        // Then
+        assert_eq!(SlotValue::Custom("boiling".to_string().into()), slot.value);
+        assert_eq!(Some(0..7), slot.range);
+    }
+
+    /// Splits the words joined with underscores
+    struct UnderscoreTokenizer {
+        tokenizer: DefaultTokenizer,
+    }
+
+    impl Tokenizer for UnderscoreTokenizer {
+        fn tokenize(&self, input: &str) -> Vec<Token> {
+            self.tokenizer.tokenize(&input.replace("_", " "))
+        }
+    }
+
+    #[test]
+    fn parse_should_use_tokenizer_of_configuration() {
+        // Given
+        let mut configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let default_nlu_engine = SnipsNluEngine::new(configuration.clone()).unwrap();
+        configuration.tokenizer = Some("underscore".to_string());
+        let mut tokenizer_registry = TokenizerRegistry::new();
+        tokenizer_registry.register("underscore", |language: Language| -> Result<Box<Tokenizer>> {
+            Ok(Box::new(UnderscoreTokenizer {
+                tokenizer: DefaultTokenizer::new(language),
+            }))
+        });
+        let nlu_engine = SnipsNluEngine::new_with_tokenizer_registry(
+            configuration,
+            &IntentParserRegistry::new(),
+            &FeatureExtractorRegistry::new(),
+            &tokenizer_registry,
+        ).unwrap();
+        let input = "make_me_two_cups_of_coffee";
+
+        // When
+        let result = nlu_engine.parse(input, None).unwrap();
+        let slot_filler_tokens = |engine: &SnipsNluEngine| -> Vec<String> {
+            engine
+                .get_features(input, "MakeCoffee")
+                .unwrap()
+                .into_iter()
+                .filter_map(|features| features.slot_filler_features)
+                .flat_map(|token_features| token_features.into_iter().map(|f| f.token))
+                .collect()
+        };
+
+        // Then
+        assert_eq!(Some("MakeCoffee"), result.intent.as_ref().map(|i| &*i.intent_name));
+        assert_eq!(
+            vec!["make", "me", "two", "cups", "of", "coffee"],
+            slot_filler_tokens(&nlu_engine)
+        );
+        assert_eq!(vec![input], slot_filler_tokens(&default_nlu_engine));
+    }
+
+    #[test]
+    fn parse_should_fail_with_unknown_intents_in_filter() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let intents_filter = vec!["MakeCoffee".to_string(), "MakeCofee".to_string()];
+
+        // When
+        let result = nlu_engine.parse("Make me two cups of coffee please", Some(&intents_filter));
+        let intents = nlu_engine.get_intents("Make me a coffee", Some(&intents_filter));
+
+        // Then
+        match *result.unwrap_err().kind() {
+            ErrorKind::UnknownIntents(ref intent_names) => {
+                assert_eq!(vec!["MakeCofee".to_string()], *intent_names)
+            }
+            ref kind => panic!("Expected unknown intents, got {:?}", kind),
+        }
+        assert!(intents.is_err());
+    }
+
+    #[test]
+    fn parse_should_reject_long_inputs() {
+        // Given
+        let mut configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        configuration.input_length_limit = Some(InputLengthLimit {
+            max_length: 13,
+            policy: InputLengthPolicy::Reject,
+        });
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let result = nlu_engine.parse("Make me two cups of coffee please", None);
+
+        // Then
+        match *result.unwrap_err().kind() {
+            ErrorKind::InputTooLong(length, max_length) => {
+                assert_eq!((33, 13), (length, max_length))
+            }
+            ref kind => panic!("Expected an input too long, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn should_limit_long_inputs_of_all_entry_points() {
+        // Given
+        let mut configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        configuration.input_length_limit = Some(InputLengthLimit {
+            max_length: 13,
+            policy: InputLengthPolicy::Reject,
+        });
+        let nlu_engine = SnipsNluEngine::new(configuration.clone()).unwrap();
+        configuration.input_length_limit = Some(InputLengthLimit {
+            max_length: 13,
+            policy: InputLengthPolicy::Truncate,
+        });
+        let truncating_nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let input = "Make me two cups of coffee please";
+
+        // When
+        let intents = nlu_engine.get_intents(input, None);
+        let features = nlu_engine.get_features(input, "MakeCoffee");
+        let tokens = nlu_engine.tokenize(input);
+        let truncated_tokens = truncating_nlu_engine.tokenize(input).unwrap();
+
+        // Then
+        assert!(intents.is_err());
+        assert!(features.is_err());
+        assert!(tokens.is_err());
+        let truncated_values = truncated_tokens
+            .into_iter()
+            .map(|token| token.value)
+            .collect_vec();
+        assert_eq!(vec!["Make", "me", "two"], truncated_values);
+    }
+
+    #[test]
+    fn parse_should_truncate_long_inputs_at_token_boundary() {
+        // Given
+        let mut configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        configuration.input_length_limit = Some(InputLengthLimit {
+            max_length: 13,
+            policy: InputLengthPolicy::Truncate,
+        });
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let (result, explanation) = nlu_engine
+            .parse_with_explanation("Make me two cups of coffee please", None)
+            .unwrap();
+        let (short_result, short_explanation) = nlu_engine
+            .parse_with_explanation("Make me two", None)
+            .unwrap();
+
+        // Then
+        assert_eq!("Make me two", result.input);
+        assert!(explanation.input_truncated);
+        assert_eq!(short_result, result);
+        assert!(!short_explanation.input_truncated);
+    }
+
+    #[test]
+    fn parse_should_truncate_long_inputs_within_the_limit() {
+        // Given
+        let mut configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        configuration.input_length_limit = Some(InputLengthLimit {
+            max_length: 4,
+            policy: InputLengthPolicy::Truncate,
+        });
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let input = "cafe\u{301} please";
+
+        // When
+        let result = nlu_engine.parse(input, None).unwrap();
+
+        // Then
+        let length = result.input.chars().count();
+        assert!(length <= 4);
+        assert_eq!(0..length, grapheme_aligned_range(input, &(0..length)));
+    }
+
+    #[test]
+    fn parse_borrowed_should_borrow_the_input() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let input = "Make me two cups of coffee please";
+
+        // When
+        let result = nlu_engine.parse(input, None).unwrap();
+        let borrowed_result = nlu_engine.parse_borrowed(input, None).unwrap();
+
+        // Then
+        assert_eq!(input.as_ptr(), borrowed_result.input.as_ptr());
+        let slots = borrowed_result.slots.as_ref().unwrap();
+        assert!(!slots.is_empty());
+        for slot in slots {
+            match slot.raw_value {
+                Cow::Borrowed(raw_value) => {
+                    let offset = raw_value.as_ptr() as usize - input.as_ptr() as usize;
+                    assert!(offset + raw_value.len() <= input.len());
+                }
+                Cow::Owned(_) => panic!("Expected a raw value borrowed from the input"),
+            }
+        }
+        assert_eq!(result, borrowed_result.into_owned());
+    }
+
+    #[test]
+    fn should_fail_with_unknown_tokenizer() {
+        // Given
+        let mut configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        configuration.tokenizer = Some("empty".to_string());
+
+        // When
+        let nlu_engine = SnipsNluEngine::new(configuration);
+
+        // Then
+        assert!(nlu_engine.is_err());
+    }
+
+    #[test]
+    fn parse_should_normalize_unicode() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me a {coffee_type} [please]"])
+            .slot("MakeCoffee", "coffee_type", "coffee_type")
+            .entity("coffee_type", &["café", "latte"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.unicode_normalization = Some(UnicodeNormalization::Nfc);
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let result = nlu_engine.parse("make me a cafe\u{301} please", None).unwrap();
+
+        // Then
+        let expected_slots = vec![
+            Slot {
+                raw_value: "cafe\u{301}".to_string(),
+                value: SlotValue::Custom("café".to_string().into()),
+                range: Some(10..15),
+                entity: "coffee_type".to_string(),
+                slot_name: "coffee_type".to_string(),
+            },
+        ];
+        assert_eq!("make me a cafe\u{301} please", result.input);
+        assert_eq!(Some(expected_slots), result.slots);
+    }
+
+    #[test]
+    fn extract_slot_should_preprocess_input() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me a {coffee_type} [please]"])
+            .slot("MakeCoffee", "coffee_type", "coffee_type")
+            .entity("coffee_type", &["café", "latte"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.unicode_normalization = Some(UnicodeNormalization::Nfc);
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let slot = nlu_engine
+            .extract_slot("a cafe\u{301}".to_string(), "MakeCoffee", "coffee_type")
+            .unwrap();
+
+        // Then
+        let expected_slot = Slot {
+            raw_value: "cafe\u{301}".to_string(),
+            value: SlotValue::Custom("café".to_string().into()),
+            range: Some(2..7),
+            entity: "coffee_type".to_string(),
+            slot_name: "coffee_type".to_string(),
+        };
+        assert_eq!(Some(expected_slot), slot);
+    }
+
+    #[test]
+    fn parse_should_replace_emojis_by_their_aliases() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent("React", &["answer with {reaction}"])
+            .slot("React", "reaction", "reaction")
+            .entity("reaction", &[":thumbsup:", ":heart:"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.emoji_aliases = Some(hashmap! {
+            "👍".to_string() => ":thumbsup:".to_string(),
+            "❤".to_string() => ":heart:".to_string(),
+        });
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let result = nlu_engine.parse("answer with ❤\u{FE0F}", None).unwrap();
+
+        // Then
+        let expected_slots = vec![
+            Slot {
+                raw_value: "❤\u{FE0F}".to_string(),
+                value: SlotValue::Custom(":heart:".to_string().into()),
+                range: Some(12..14),
+                entity: "reaction".to_string(),
+                slot_name: "reaction".to_string(),
+            },
+        ];
+        assert_eq!(Some("React".to_string()), result.intent.map(|intent| intent.intent_name));
+        assert_eq!(Some(expected_slots), result.slots);
+    }
+
+    #[test]
+    fn parse_should_expand_contractions() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .language("fr")
+            .intent("Aimer", &["je aime le {boisson}"])
+            .slot("Aimer", "boisson", "boisson")
+            .entity("boisson", &["thé", "café"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.expand_contractions = Some(true);
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let result = nlu_engine.parse("j'aime le thé", None).unwrap();
+
+        // Then
+        let expected_slots = vec![
+            Slot {
+                raw_value: "thé".to_string(),
+                value: SlotValue::Custom("thé".to_string().into()),
+                range: Some(10..13),
+                entity: "boisson".to_string(),
+                slot_name: "boisson".to_string(),
+            },
+        ];
+        assert_eq!(Some("Aimer".to_string()), result.intent.map(|intent| intent.intent_name));
+        assert_eq!(Some(expected_slots), result.slots);
+    }
+
+    #[test]
+    fn parse_should_resolve_slots_with_contractions() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent("PlaySong", &["play {song}"])
+            .slot("PlaySong", "song", "song")
+            .entity("song", &["don't stop", "let it be"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.expand_contractions = Some(true);
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let result = nlu_engine.parse("play don't stop", None).unwrap();
+
+        // Then
+        let expected_slots = vec![
+            Slot {
+                raw_value: "don't stop".to_string(),
+                value: SlotValue::Custom("don't stop".to_string().into()),
+                range: Some(5..15),
+                entity: "song".to_string(),
+                slot_name: "song".to_string(),
+            },
+        ];
+        assert_eq!(Some("PlaySong".to_string()), result.intent.map(|intent| intent.intent_name));
+        assert_eq!(Some(expected_slots), result.slots);
+    }
+
+    #[test]
+    fn parse_should_correct_spelling() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me a {coffee_type} [please]"])
+            .slot("MakeCoffee", "coffee_type", "coffee_type")
+            .entity("coffee_type", &["latte", "espresso"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.spell_correction = Some(SpellCorrectionConfiguration {
+            max_edit_distance: 1,
+            min_word_length: 4,
+        });
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let result = nlu_engine.parse("maek me a expresso plese", None).unwrap();
+
+        // Then
+        let expected_slots = vec![
+            Slot {
+                raw_value: "expresso".to_string(),
+                value: SlotValue::Custom("espresso".to_string().into()),
+                range: Some(10..18),
+                entity: "coffee_type".to_string(),
+                slot_name: "coffee_type".to_string(),
+            },
+        ];
+        assert_eq!(
+            Some("MakeCoffee".to_string()),
+            result.intent.map(|intent| intent.intent_name)
+        );
+        assert_eq!(Some(expected_slots), result.slots);
+    }
+
+    #[test]
+    fn parse_should_apply_preprocessing_steps() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me a {coffee_type} [please]"])
+            .slot("MakeCoffee", "coffee_type", "coffee_type")
+            .entity("coffee_type", &["cafe", "latte"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.preprocessing = Some(vec![
+            PreprocessingStep::Lowercase,
+            PreprocessingStep::StripAccents,
+            PreprocessingStep::CollapseWhitespace,
+            PreprocessingStep::RegexReplacement {
+                pattern: r"\bpls\b".to_string(),
+                replacement: "please".to_string(),
+            },
+        ]);
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+
+        // When
+        let result = nlu_engine.parse("  Make me a  CAFÉ pls", None).unwrap();
+
+        // Then
+        let expected_slots = vec![
+            Slot {
+                raw_value: "CAFÉ".to_string(),
+                value: SlotValue::Custom("cafe".to_string().into()),
+                range: Some(13..17),
+                entity: "coffee_type".to_string(),
+                slot_name: "coffee_type".to_string(),
+            },
+        ];
+        assert_eq!(
+            Some("MakeCoffee".to_string()),
+            result.intent.map(|intent| intent.intent_name)
+        );
+        assert_eq!(Some(expected_slots), result.slots);
+    }
+
+    #[test]
+    fn intents_works() {
+        // Given
+        let nlu_engine = SnipsNluEngineBuilder::new()
+            .intent("MakeTea", &["make me a tea"])
+            .intent("MakeCoffee", &["make me a coffee"])
+            .build()
+            .unwrap();
+
+        // When
+        let intents = nlu_engine.intents();
+
+        // Then
+        assert_eq!(vec!["MakeCoffee", "MakeTea"], intents);
+    }
+
+    #[test]
+    fn parse_in_context_should_carry_over_slots() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent(
+                "SetLights",
+                &["turn {action} the lights in the {room}", "and in the {room}"],
+            )
+            .slot("SetLights", "action", "action")
+            .slot("SetLights", "room", "room")
+            .entity("action", &["on", "off"], false)
+            .entity("room", &["kitchen", "bedroom"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.carry_over_slots = Some(hashmap![
+            "SetLights".to_string() => vec!["action".to_string()],
+        ]);
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let mut context = DialogueContext::new();
+
+        // When
+        nlu_engine
+            .parse_in_context("turn off the lights in the kitchen", None, &mut context)
+            .unwrap();
+        let result = nlu_engine
+            .parse_in_context("and in the bedroom", None, &mut context)
+            .unwrap();
+        let result_without_context = nlu_engine.parse("and in the bedroom", None).unwrap();
+
+        // Then
+        let expected_slots = vec![
+            Slot::new_custom(
+                "bedroom".to_string(),
+                11..18,
+                "room".to_string(),
+                "room".to_string(),
+            ),
+            Slot {
+                raw_value: "off".to_string(),
+                value: SlotValue::Custom("off".to_string().into()),
+                range: None,
+                entity: "action".to_string(),
+                slot_name: "action".to_string(),
+            },
+        ];
+        assert_eq!(Some(5..8), context.slot("action").and_then(|slot| slot.range.clone()));
+        assert_eq!(Some(expected_slots), result.slots);
+        assert_eq!(1, result_without_context.slots.unwrap().len());
+    }
+
+    #[test]
+    fn parse_in_context_should_not_refresh_carried_slots() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent(
+                "SetLights",
+                &["turn {action} the lights in the {room}", "and in the {room}"],
+            )
+            .slot("SetLights", "action", "action")
+            .slot("SetLights", "room", "room")
+            .entity("action", &["on", "off"], false)
+            .entity("room", &["kitchen", "bedroom"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.carry_over_slots = Some(hashmap![
+            "SetLights".to_string() => vec!["action".to_string()],
+        ]);
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let mut context = DialogueContext::with_max_turns(1);
+
+        // When
+        nlu_engine
+            .parse_in_context("turn off the lights in the kitchen", None, &mut context)
+            .unwrap();
+        let carried_result = nlu_engine
+            .parse_in_context("and in the bedroom", None, &mut context)
+            .unwrap();
+        let expired_result = nlu_engine
+            .parse_in_context("and in the kitchen", None, &mut context)
+            .unwrap();
+
+        // Then
+        assert_eq!(2, carried_result.slots.unwrap().len());
+        assert_eq!(1, expired_result.slots.unwrap().len());
+        assert_eq!(None, context.slot("action"));
+    }
 
-        let builtin_entity_parser = Language::from_str(&nlu_config.dataset_metadata.language_code)
-            .ok()
-            .map(BuiltinEntityParser::get);
+    #[test]
+    fn new_should_fail_with_unknown_carry_over_slots() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent("SetLights", &["turn on the lights in the {room}"])
+            .slot("SetLights", "room", "room")
+            .entity("room", &["kitchen", "bedroom"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.carry_over_slots = Some(hashmap![
+            "SetLights".to_string() => vec!["brightness".to_string()],
+        ]);
 
-        Ok(SnipsNluEngine {
-            dataset_metadata: nlu_config.dataset_metadata,
-            parsers,
-            builtin_entity_parser,
-        })
+        // When
+        let nlu_engine = SnipsNluEngine::new(configuration);
+
+        // Then
+        assert!(nlu_engine.is_err());
     }
 
-    pub fn parse(
-        &self,
-        input: &str,
-        intents_filter: Option<&[String]>,
-    ) -> Result<IntentParserResult> {
-        if self.parsers.is_empty() {
-            return Ok(IntentParserResult {
-                input: input.to_string(),
-                intent: None,
-                slots: None,
-            });
-        }
-        let set_intents: Option<HashSet<String>> = intents_filter
-            .map(|intent_list| HashSet::from_iter(intent_list.iter().map(|name| name.to_string())));
+    #[test]
+    fn should_convert_temperatures_to_unit_system_of_configuration() {
+        // Given
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent("SetTemperature", &["set the heating to {temperature}"])
+            .slot("SetTemperature", "temperature", "snips/temperature")
+            .build_configuration()
+            .unwrap();
+        configuration.unit_system = Some(UnitSystem::Imperial);
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
 
-        for parser in &self.parsers {
-            let classification_result = parser.get_intent(input, set_intents.as_ref())?;
-            if let Some(classification_result) = classification_result {
-                let valid_slots = parser
-                    .get_slots(input, &classification_result.intent_name)?
-                    .into_iter()
-                    .filter_map(|slot| {
-                        if let Some(entity) = self.dataset_metadata.entities.get(&slot.entity) {
-                            entity
-                                .utterances
-                                .get(&slot.raw_value)
-                                .map(|reference_value| {
-                                    Some(slot.clone().with_slot_value(SlotValue::Custom(
-                                        reference_value.to_string().into(),
-                                    )))
-                                })
-                                .unwrap_or(if entity.automatically_extensible {
-                                    Some(slot)
-                                } else {
-                                    None
-                                })
-                        } else {
-                            Some(slot)
-                        }
-                    })
-                    .collect();
+        // When
+        let slot = nlu_engine
+            .extract_slot(
+                "twenty degrees celsius".to_string(),
+                "SetTemperature",
+                "temperature",
+            )
+            .unwrap()
+            .unwrap();
 
-                return Ok(IntentParserResult {
-                    input: input.to_string(),
-                    intent: Some(classification_result),
-                    slots: Some(valid_slots),
-                });
-            }
+        // Then
+        if let SlotValue::Temperature(temperature) = slot.value {
+            assert!(epsilon_eq(68., temperature.value, 1e-4));
+            assert_eq!(Some("fahrenheit".to_string()), temperature.unit);
+        } else {
+            panic!("Expected a temperature value")
         }
-        Ok(IntentParserResult {
-            input: input.to_string(),
-            intent: None,
-            slots: None,
-        })
     }
 
-    // TODO: Expose directly a static variable
-    pub fn model_version() -> &'static str {
-        MODEL_VERSION
-    }
-}
+    #[test]
+    fn extract_slot_should_return_slot_ranges() {
+        // Given
+        let nlu_engine = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me {number_of_cups} {coffee_type}"])
+            .slot("MakeCoffee", "number_of_cups", "snips/number")
+            .slot("MakeCoffee", "coffee_type", "coffee_type")
+            .entity("coffee_type", &["latte", "espresso"], false)
+            .build()
+            .unwrap();
 
-impl SnipsNluEngine {
-    pub fn extract_slot(
-        &self,
-        input: String,
-        intent_name: &str,
-        slot_name: &str,
-    ) -> Result<Option<Slot>> {
-        let entity_name = self.dataset_metadata
-            .slot_name_mappings
-            .get(intent_name)
-            .ok_or_else(|| format!("Unknown intent: {}", intent_name))?
-            .get(slot_name)
-            .ok_or_else(|| format!("Unknown slot: {}", &slot_name))?;
+        // When
+        let builtin_slot = nlu_engine
+            .extract_slot("make me three".to_string(), "MakeCoffee", "number_of_cups")
+            .unwrap()
+            .unwrap();
+        let custom_slot = nlu_engine
+            .extract_slot("a hot latte please".to_string(), "MakeCoffee", "coffee_type")
+            .unwrap()
+            .unwrap();
 
-        let slot = if let Some(custom_entity) = self.dataset_metadata.entities.get(entity_name) {
-            let language = Language::from_str(&self.dataset_metadata.language_code)?;
-            extract_custom_slot(
-                input,
-                entity_name.to_string(),
-                slot_name.to_string(),
-                custom_entity,
-                language,
-            )
-        } else if let Some(builtin_entity_parser) = self.builtin_entity_parser.clone() {
-            extract_builtin_slot(
-                input,
-                entity_name.to_string(),
-                slot_name.to_string(),
-                &builtin_entity_parser,
-            )?
-        } else {
-            None
-        };
-        Ok(slot)
+        // Then
+        assert_eq!(Some(8..13), builtin_slot.range);
+        assert_eq!("three", builtin_slot.raw_value);
+        assert_eq!(Some(6..11), custom_slot.range);
+        assert_eq!("latte", custom_slot.raw_value);
     }
-}
 
-fn extract_custom_slot(
-    input: String,
-    entity_name: String,
-    slot_name: String,
-    custom_entity: &Entity,
-    language: Language,
-) -> Option<Slot> {
-    let tokens = tokenize(&input, NluUtilsLanguage::from_language(language));
-    let token_values_ref = tokens.iter().map(|v| &*v.value).collect_vec();
-    let mut ngrams = compute_all_ngrams(&*token_values_ref, tokens.len());
-    ngrams.sort_by_key(|&(_, ref indexes)| -(indexes.len() as i16));
-
-    ngrams
-        .into_iter()
-        .find(|&(ref ngram, _)| custom_entity.utterances.contains_key(&normalize(ngram)))
-        .map(|(ngram, _)| {
-            Some(Slot {
-                raw_value: ngram.clone(),
-                value: SlotValue::Custom(
-                    custom_entity.utterances[&normalize(&ngram)]
-                        .to_string()
-                        .into(),
-                ),
-                range: None,
-                entity: entity_name.clone(),
-                slot_name: slot_name.clone(),
-            })
-        })
-        .unwrap_or(if custom_entity.automatically_extensible {
-            Some(Slot {
-                raw_value: input.clone(),
-                value: SlotValue::Custom(input.into()),
-                range: None,
-                entity: entity_name,
-                slot_name,
-            })
-        } else {
-            None
-        })
-}
+    #[test]
+    fn introspection_works() {
+        // Given
+        let nlu_engine = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me {number_of_cups} {coffee_type}"])
+            .slot("MakeCoffee", "number_of_cups", "snips/number")
+            .slot("MakeCoffee", "coffee_type", "coffee_type")
+            .entity("coffee_type", &["latte", "espresso"], false)
+            .build()
+            .unwrap();
 
-fn extract_builtin_slot(
-    input: String,
-    entity_name: String,
-    slot_name: String,
-    builtin_entity_parser: &BuiltinEntityParser,
-) -> Result<Option<Slot>> {
-    let builtin_entity_kind = BuiltinEntityKind::from_identifier(&entity_name)?;
-    Ok(builtin_entity_parser
-        .extract_entities(&input, Some(&[builtin_entity_kind]))
-        .first()
-        .map(|rustlin_entity| Slot {
-            raw_value: substring_with_char_range(input, &rustlin_entity.range),
-            value: rustlin_entity.entity.clone(),
-            range: None,
-            entity: entity_name,
-            slot_name,
-        }))
-}
+        // When
+        let slots = nlu_engine.intent_slots("MakeCoffee").unwrap();
+        let unknown_intent_slots = nlu_engine.intent_slots("MakeTea");
+        let entities = nlu_engine.entities();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use snips_nlu_ontology::{IntentClassifierResult, NumberValue};
-    use configurations::NluEngineConfiguration;
-    use testutils::parse_json;
+        // Then
+        let expected_slots = vec![
+            SlotDescription {
+                slot_name: "coffee_type",
+                entity: "coffee_type",
+            },
+            SlotDescription {
+                slot_name: "number_of_cups",
+                entity: "snips/number",
+            },
+        ];
+        let expected_entities = vec![
+            EntityDescription {
+                name: "coffee_type",
+                builtin: false,
+                automatically_extensible: false,
+                nb_values: Some(2),
+            },
+            EntityDescription {
+                name: "snips/number",
+                builtin: true,
+                automatically_extensible: false,
+                nb_values: None,
+            },
+        ];
+        assert_eq!(expected_slots, slots);
+        assert!(unknown_intent_slots.is_err());
+        assert_eq!(expected_entities, entities);
+        assert_eq!(Language::EN, nlu_engine.language());
+        assert_eq!(MODEL_VERSION, nlu_engine.trained_model_version());
+    }
 
     #[test]
-    fn parse_works() {
+    fn memory_footprint_works() {
         // Given
         let configuration: NluEngineConfiguration =
             parse_json("tests/configurations/trained_assistant.json");
         let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
 
+        // When
+        let footprint = nlu_engine.memory_footprint();
+
+        // Then
+        assert!(footprint.rule_patterns > 0);
+        assert!(footprint.intent_classifiers > 0);
+        assert!(footprint.crf_weights > 0);
+        assert!(footprint.builtin_entities > 0);
+        assert_eq!(0, footprint.configuration);
+        assert_eq!(
+            footprint.entities
+                + footprint.rule_patterns
+                + footprint.intent_classifiers
+                + footprint.crf_weights
+                + footprint.builtin_entities
+                + footprint.preprocessing,
+            footprint.total()
+        );
+    }
+
+    #[test]
+    fn memory_footprint_should_count_kept_configuration_and_spell_corrector() {
+        // Given
+        let mut configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        configuration.spell_correction = Some(SpellCorrectionConfiguration {
+            max_edit_distance: 1,
+            min_word_length: 4,
+        });
+        let nlu_engine = SnipsNluEngine::new_updatable(configuration.clone()).unwrap();
+
+        // When
+        let footprint = nlu_engine.memory_footprint();
+
+        // Then
+        let configuration_size = ::serde_json::to_vec(&configuration).unwrap().len();
+        assert_eq!(configuration_size, footprint.configuration);
+        assert!(footprint.preprocessing > 0);
+    }
+
+    #[test]
+    fn get_slots_works() {
+        // Given
+        let nlu_engine = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me a {coffee_type}"])
+            .intent("MakeTea", &["make me a tea"])
+            .slot("MakeCoffee", "coffee_type", "coffee_type")
+            .entity("coffee_type", &["latte", "cappuccino"], false)
+            .build()
+            .unwrap();
+
+        // When
+        let slots = nlu_engine.get_slots("make me a latte", "MakeCoffee").unwrap();
+        let unknown_intent_slots = nlu_engine.get_slots("make me a latte", "MakePizza");
+
+        // Then
+        let expected_slots = vec![
+            Slot {
+                raw_value: "latte".to_string(),
+                value: SlotValue::Custom("latte".to_string().into()),
+                range: Some(10..15),
+                entity: "coffee_type".to_string(),
+                slot_name: "coffee_type".to_string(),
+            },
+        ];
+        assert_eq!(expected_slots, slots);
+        assert!(unknown_intent_slots.is_err());
+    }
+
+    #[test]
+    fn parse_with_options_should_extract_builtin_entities_in_given_languages() {
+        // Given
+        let nlu_engine = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me {number_of_cups} coffees"])
+            .slot("MakeCoffee", "number_of_cups", "snips/number")
+            .build()
+            .unwrap();
+        let options = ParseOptions {
+            intents_filter: None,
+            builtin_entities_languages: Some(vec![Language::EN, Language::FR]),
+            parallel_parsers: false,
+            grapheme_ranges: false,
+        };
+
         // When
         let result = nlu_engine
-            .parse("Make me two cups of coffee please", None)
+            .parse_with_options("make me trois coffees", &options)
             .unwrap();
+        let result_without_options = nlu_engine.parse("make me trois coffees", None).unwrap();
 
         // Then
-        let expected_entity_value = SlotValue::Number(NumberValue { value: 2.0 });
-        let expected_result = IntentParserResult {
-            input: "Make me two cups of coffee please".to_string(),
+        let expected_slots = vec![
+            Slot {
+                raw_value: "trois".to_string(),
+                value: SlotValue::Number(NumberValue { value: 3.0 }),
+                range: Some(8..13),
+                entity: "snips/number".to_string(),
+                slot_name: "number_of_cups".to_string(),
+            },
+        ];
+        assert_eq!(Some(expected_slots), result.slots);
+        assert_eq!(None, result_without_options.intent);
+    }
+
+    #[test]
+    fn parse_with_explanation_works() {
+        // Given
+        let nlu_engine = SnipsNluEngineBuilder::new()
+            .intent(
+                "MakeCoffee",
+                &["make me {number_of_cups} {coffee_type} for {recipient}"],
+            )
+            .slot("MakeCoffee", "number_of_cups", "snips/number")
+            .slot("MakeCoffee", "coffee_type", "coffee_type")
+            .slot("MakeCoffee", "recipient", "recipient")
+            .entity("coffee_type", &["latte", "espresso"], false)
+            .entity("recipient", &["alice"], true)
+            .build()
+            .unwrap();
+        let input = "make me two latte for bob";
+
+        // When
+        let (result, explanation) = nlu_engine.parse_with_explanation(input, None).unwrap();
+
+        // Then
+        assert_eq!(nlu_engine.parse(input, None).unwrap(), result);
+        assert_eq!(Some(0), explanation.parser_index);
+        match explanation.intent {
+            Some(IntentExplanation::MatchedPattern(_)) => {}
+            other => panic!("Expected a matched pattern, found {:?}", other),
+        }
+        let slot_resolutions: HashMap<String, SlotResolution> = result
+            .slots
+            .unwrap()
+            .into_iter()
+            .map(|slot| slot.slot_name)
+            .zip(explanation.slots.into_iter())
+            .collect();
+        let expected_slot_resolutions = hashmap! {
+            "number_of_cups".to_string() => SlotResolution::Builtin,
+            "coffee_type".to_string() => SlotResolution::Gazetteer,
+            "recipient".to_string() => SlotResolution::AutomaticallyExtensible,
+        };
+        assert_eq!(expected_slot_resolutions, slot_resolutions);
+    }
+
+    #[test]
+    fn tag_works() {
+        // Given
+        let nlu_engine = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me {number_of_cups} {coffee_type} for {recipient}"])
+            .slot("MakeCoffee", "number_of_cups", "snips/number")
+            .slot("MakeCoffee", "coffee_type", "coffee_type")
+            .slot("MakeCoffee", "recipient", "recipient")
+            .entity("coffee_type", &["latte", "espresso"], false)
+            .entity("recipient", &["alice"], true)
+            .build()
+            .unwrap();
+
+        // When
+        let tagged_tokens = nlu_engine.tag("make me two latte for bob", None).unwrap();
+
+        // Then
+        let tagged_slots: Vec<(&str, Option<&str>, Option<f32>)> = tagged_tokens
+            .iter()
+            .map(|token| {
+                let slot_name = token.slot_name.as_ref().map(|name| &**name);
+                (&*token.value, slot_name, token.confidence)
+            })
+            .collect();
+        let expected_tagged_slots = vec![
+            ("make", None, None),
+            ("me", None, None),
+            ("two", Some("number_of_cups"), Some(1.0)),
+            ("latte", Some("coffee_type"), Some(1.0)),
+            ("for", None, None),
+            ("bob", Some("recipient"), Some(0.5)),
+        ];
+        assert_eq!(expected_tagged_slots, tagged_slots);
+    }
+
+    #[test]
+    fn parse_should_merge_equivalent_slots() {
+        // Given
+        struct FixedSlotsIntentParser {
+            slots: Vec<Slot>,
+        }
+
+        impl IntentParser for FixedSlotsIntentParser {
+            fn get_intent(
+                &self,
+                _input: &str,
+                _intents: Option<&HashSet<String>>,
+                _context: &ParseContext,
+            ) -> Result<Option<IntentClassifierResult>> {
+                Ok(Some(IntentClassifierResult {
+                    intent_name: "MakeTea".to_string(),
+                    probability: 1.0,
+                }))
+            }
+
+            fn get_slots(
+                &self,
+                _input: &str,
+                _intent_name: &str,
+                _context: &ParseContext,
+            ) -> Result<Vec<Slot>> {
+                Ok(self.slots.clone())
+            }
+        }
+
+        let custom_slot = |raw_value: &str, range: Range<usize>, entity: &str| Slot {
+            raw_value: raw_value.to_string(),
+            value: SlotValue::Custom(raw_value.to_string().into()),
+            range: Some(range),
+            entity: entity.to_string(),
+            slot_name: entity.to_string(),
+        };
+        let quantity_slot = Slot {
+            raw_value: "two".to_string(),
+            value: SlotValue::Number(NumberValue { value: 2.0 }),
+            range: Some(0..3),
+            entity: "snips/number".to_string(),
+            slot_name: "quantity".to_string(),
+        };
+        // Overlapping slots which a parser could tag, such as the builtin entities added to the
+        // custom slots of a slot filler
+        let tagged_slots = vec![
+            quantity_slot.clone(),
+            custom_slot("tea", 10..13, "beverage"),
+            custom_slot("green", 4..9, "beverage"),
+            custom_slot("two", 0..3, "size"),
+            custom_slot("green tea", 4..13, "beverage"),
+        ];
+        let mut configuration = SnipsNluEngineBuilder::new()
+            .intent("MakeTea", &["make me a {beverage}"])
+            .slot("MakeTea", "beverage", "beverage")
+            .slot("MakeTea", "size", "size")
+            .slot("MakeTea", "quantity", "snips/number")
+            .entity("beverage", &["green tea"], true)
+            .entity("size", &["two"], false)
+            .build_configuration()
+            .unwrap();
+        configuration.custom_parsers = Some(vec![json!({"unit_name": "fixed_slots_parser"})]);
+        let mut registry = IntentParserRegistry::new();
+        registry.register(
+            "fixed_slots_parser",
+            move |_: ::serde_json::Value| -> Result<Box<IntentParser>> {
+                Ok(Box::new(FixedSlotsIntentParser {
+                    slots: tagged_slots.clone(),
+                }))
+            },
+        );
+        let nlu_engine = SnipsNluEngine::new_with_registry(configuration, &registry).unwrap();
+
+        // When
+        let result = nlu_engine.parse("two green tea", None).unwrap();
+
+        // Then
+        let expected_slots = vec![quantity_slot, custom_slot("green tea", 4..13, "beverage")];
+        assert_eq!(Some(expected_slots), result.slots);
+    }
+
+    #[test]
+    fn convert_to_grapheme_ranges_works() {
+        // Given
+        let input = "\u{1f469}\u{200d}\u{1f4bb} un cafe\u{301}";
+        let result = IntentParserResult {
+            input: input.to_string(),
             intent: Some(IntentClassifierResult {
                 intent_name: "MakeCoffee".to_string(),
-                probability: 0.6838855,
+                probability: 0.8,
             }),
-            slots: Some(vec![
-                Slot {
-                    raw_value: "two".to_string(),
-                    value: expected_entity_value,
-                    range: Some(8..11),
-                    entity: "snips/number".to_string(),
-                    slot_name: "number_of_cups".to_string(),
-                },
-            ]),
+            slots: Some(vec![Slot {
+                raw_value: "cafe\u{301}".to_string(),
+                value: SlotValue::Custom("café".to_string().into()),
+                range: Some(7..12),
+                entity: "beverage".to_string(),
+                slot_name: "beverage".to_string(),
+            }]),
         };
 
-        assert_eq!(expected_result, result)
+        // When
+        let converted_result = convert_to_grapheme_ranges(result);
+
+        // Then
+        let slots = converted_result.slots.unwrap();
+        assert_eq!(Some(5..9), slots[0].range);
+        assert_eq!("cafe\u{301}", slots[0].raw_value);
+    }
+
+    #[test]
+    fn parse_with_options_should_give_same_results_with_parallel_parsers() {
+        // Given
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        let nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let options = ParseOptions {
+            intents_filter: None,
+            builtin_entities_languages: None,
+            parallel_parsers: true,
+            grapheme_ranges: false,
+        };
+
+        for input in &["Make me two cups of coffee please", "I want 3 teas at 80 degrees"] {
+            // When
+            let result = nlu_engine.parse(input, None).unwrap();
+            let parallel_result = nlu_engine.parse_with_options(input, &options).unwrap();
+
+            // Then
+            assert_eq!(result, parallel_result);
+        }
+    }
+
+    #[test]
+    fn metrics_sink_should_record_parses() {
+        // Given
+        struct TestMetricsSink {
+            intents: Mutex<Vec<Option<String>>>,
+        }
+
+        impl MetricsSink for TestMetricsSink {
+            fn record_parse(&self, intent_name: Option<&str>, _latency: Duration) {
+                self.intents
+                    .lock()
+                    .unwrap()
+                    .push(intent_name.map(|name| name.to_string()));
+            }
+        }
+
+        let configuration = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me a coffee"])
+            .build_configuration()
+            .unwrap();
+        let mut nlu_engine = SnipsNluEngine::new(configuration).unwrap();
+        let metrics_sink = Arc::new(TestMetricsSink {
+            intents: Mutex::new(vec![]),
+        });
+        nlu_engine.set_metrics_sink(Some(metrics_sink.clone()));
+
+        // When
+        nlu_engine.parse("make me a coffee", None).unwrap();
+        nlu_engine.parse("hello world", None).unwrap();
+
+        // Then
+        let expected_intents = vec![Some("MakeCoffee".to_string()), None];
+        assert_eq!(expected_intents, *metrics_sink.intents.lock().unwrap());
     }
 
     #[test]
     fn should_extract_custom_slot_when_tagged() {
         // Given
-        let language = Language::EN;
+        let tokenizer = DefaultTokenizer::new(Language::EN);
         let input = "hello a b c d world".to_string();
         let entity_name = "entity".to_string();
         let slot_name = "slot".to_string();
@@ -268,17 +2995,26 @@ mod tests {
                 "a b".to_string() => "value1".to_string(),
                 "b c d".to_string() => "value2".to_string(),
             },
+            utterances_file: None,
+            nb_values: None,
         };
+        let gazetteer = EntityGazetteer::new(custom_entity.utterances.clone());
 
         // When
-        let extracted_slot =
-            extract_custom_slot(input, entity_name, slot_name, &custom_entity, language);
+        let extracted_slot = extract_custom_slot(
+            input,
+            entity_name,
+            slot_name,
+            &custom_entity,
+            &gazetteer,
+            &tokenizer,
+        );
 
         // Then
         let expected_slot = Some(Slot {
             raw_value: "b c d".to_string(),
             value: SlotValue::Custom("value2".to_string().into()),
-            range: None,
+            range: Some(8..13),
             entity: "entity".to_string(),
             slot_name: "slot".to_string(),
         });
@@ -288,24 +3024,32 @@ mod tests {
     #[test]
     fn should_extract_custom_slot_when_not_tagged() {
         // Given
-        let language = Language::EN;
+        let tokenizer = DefaultTokenizer::new(Language::EN);
         let input = "hello world".to_string();
         let entity_name = "entity".to_string();
         let slot_name = "slot".to_string();
         let custom_entity = Entity {
             automatically_extensible: true,
             utterances: hashmap!{},
+            utterances_file: None,
+            nb_values: None,
         };
 
         // When
-        let extracted_slot =
-            extract_custom_slot(input, entity_name, slot_name, &custom_entity, language);
+        let extracted_slot = extract_custom_slot(
+            input,
+            entity_name,
+            slot_name,
+            &custom_entity,
+            &EntityGazetteer::new(custom_entity.utterances.clone()),
+            &tokenizer,
+        );
 
         // Then
         let expected_slot = Some(Slot {
             raw_value: "hello world".to_string(),
             value: SlotValue::Custom("hello world".to_string().into()),
-            range: None,
+            range: Some(0..11),
             entity: "entity".to_string(),
             slot_name: "slot".to_string(),
         });
@@ -315,18 +3059,26 @@ mod tests {
     #[test]
     fn should_not_extract_custom_slot_when_not_extensible() {
         // Given
-        let language = Language::EN;
+        let tokenizer = DefaultTokenizer::new(Language::EN);
         let input = "hello world".to_string();
         let entity_name = "entity".to_string();
         let slot_name = "slot".to_string();
         let custom_entity = Entity {
             automatically_extensible: false,
             utterances: hashmap!{},
+            utterances_file: None,
+            nb_values: None,
         };
 
         // When
-        let extracted_slot =
-            extract_custom_slot(input, entity_name, slot_name, &custom_entity, language);
+        let extracted_slot = extract_custom_slot(
+            input,
+            entity_name,
+            slot_name,
+            &custom_entity,
+            &EntityGazetteer::new(custom_entity.utterances.clone()),
+            &tokenizer,
+        );
 
         // Then
         let expected_slot = None;
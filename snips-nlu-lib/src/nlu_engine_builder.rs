@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use configurations::{DatasetMetadata, DeterministicParserConfiguration, Entity,
+                     NluEngineConfiguration};
+use errors::*;
+use nlu_engine::{SnipsNluEngine, MODEL_VERSION};
+use nlu_utils::string::normalize;
+use snips_nlu_ontology::{BuiltinEntityKind, Language};
+
+/// Builds a rule-based engine from code, without training a model
+///
+/// The patterns of the intents are written with the rule syntax of the deterministic intent
+/// parser, for instance `"make me [a] {coffee_type} [please]"`, and each slot used in the
+/// patterns of an intent must be declared along with its entity.
+#[derive(Debug, Clone)]
+pub struct SnipsNluEngineBuilder {
+    language_code: String,
+    rules: HashMap<String, Vec<String>>,
+    slot_name_mappings: HashMap<String, HashMap<String, String>>,
+    entities: HashMap<String, Entity>,
+}
+
+impl Default for SnipsNluEngineBuilder {
+    fn default() -> Self {
+        Self {
+            language_code: "en".to_string(),
+            rules: HashMap::new(),
+            slot_name_mappings: HashMap::new(),
+            entities: HashMap::new(),
+        }
+    }
+}
+
+impl SnipsNluEngineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the language of the engine, English being used by default
+    pub fn language(mut self, language_code: &str) -> Self {
+        self.language_code = language_code.to_string();
+        self
+    }
+
+    /// Adds patterns to an intent
+    pub fn intent(mut self, intent_name: &str, patterns: &[&str]) -> Self {
+        self.rules
+            .entry(intent_name.to_string())
+            .or_insert_with(Vec::new)
+            .extend(patterns.iter().map(|pattern| pattern.to_string()));
+        self.slot_name_mappings
+            .entry(intent_name.to_string())
+            .or_insert_with(HashMap::new);
+        self
+    }
+
+    /// Declares a slot of an intent, whose entity is either a custom entity or a builtin entity
+    /// such as `snips/number`
+    pub fn slot(mut self, intent_name: &str, slot_name: &str, entity_name: &str) -> Self {
+        self.slot_name_mappings
+            .entry(intent_name.to_string())
+            .or_insert_with(HashMap::new)
+            .insert(slot_name.to_string(), entity_name.to_string());
+        self
+    }
+
+    /// Adds values to a custom entity, the slots with other values being discarded unless the
+    /// entity is automatically extensible
+    pub fn entity(
+        mut self,
+        entity_name: &str,
+        values: &[&str],
+        automatically_extensible: bool,
+    ) -> Self {
+        let entity = self.entities
+            .entry(entity_name.to_string())
+            .or_insert_with(|| Entity {
+                automatically_extensible,
+                utterances: HashMap::new(),
+                utterances_file: None,
+                nb_values: None,
+            });
+        entity.automatically_extensible = automatically_extensible;
+        for value in values {
+            entity.utterances.insert(value.to_string(), value.to_string());
+            entity.utterances.insert(normalize(value), value.to_string());
+        }
+        self
+    }
+
+    pub fn build(self) -> Result<SnipsNluEngine> {
+        SnipsNluEngine::new(self.build_configuration()?)
+    }
+
+    /// Returns the configuration of the engine, which can be serialized to persist it
+    pub fn build_configuration(self) -> Result<NluEngineConfiguration> {
+        Language::from_str(&self.language_code)?;
+        let mut slot_names_to_entities: HashMap<String, String> = HashMap::new();
+        for (intent_name, slot_name_mapping) in &self.slot_name_mappings {
+            if !self.rules.contains_key(intent_name) {
+                bail!("Slots are declared for intent '{}' which has no pattern", intent_name);
+            }
+            for (slot_name, entity_name) in slot_name_mapping {
+                if BuiltinEntityKind::from_identifier(entity_name).is_err()
+                    && !self.entities.contains_key(entity_name)
+                {
+                    bail!("Unknown entity '{}' of slot '{}'", entity_name, slot_name);
+                }
+                if let Some(other_entity_name) =
+                    slot_names_to_entities.insert(slot_name.clone(), entity_name.clone())
+                {
+                    if other_entity_name != *entity_name {
+                        bail!(
+                            "Slot '{}' is declared with entities '{}' and '{}'",
+                            slot_name,
+                            other_entity_name,
+                            entity_name
+                        );
+                    }
+                }
+            }
+        }
+
+        let parser_configuration = DeterministicParserConfiguration {
+            language_code: self.language_code.clone(),
+            patterns: HashMap::new(),
+            group_names_to_slot_names: HashMap::new(),
+            slot_names_to_entities,
+            rules: Some(self.rules),
+            tag_all_builtin_entities: false,
+        };
+        let mut parser_value = ::serde_json::to_value(parser_configuration)?;
+        if let ::serde_json::Value::Object(ref mut parser_map) = parser_value {
+            parser_map.insert(
+                "unit_name".to_string(),
+                ::serde_json::Value::String("deterministic_intent_parser".to_string()),
+            );
+        }
+
+        Ok(NluEngineConfiguration {
+            dataset_metadata: DatasetMetadata {
+                language_code: self.language_code,
+                additional_builtin_entities_languages: None,
+                fallback_builtin_entities_languages: None,
+                entities: self.entities,
+                slot_name_mappings: self.slot_name_mappings,
+            },
+            intent_parsers: vec![parser_value],
+            custom_parsers: None,
+            arbitration_strategy: None,
+            none_sensitivity: None,
+            intent_thresholds: None,
+            tokenizer: None,
+            split_emojis: None,
+            unicode_normalization: None,
+            preprocessing: None,
+            expand_contractions: None,
+            spell_correction: None,
+            emoji_aliases: None,
+            input_length_limit: None,
+            carry_over_slots: None,
+            unit_system: None,
+            model_version: MODEL_VERSION.to_string(),
+            training_package_version: env!("CARGO_PKG_VERSION").to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_ontology::SlotValue;
+
+    fn coffee_engine_builder() -> SnipsNluEngineBuilder {
+        SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me [a] {coffee_type} [please]"])
+            .slot("MakeCoffee", "coffee_type", "coffee_type")
+            .entity("coffee_type", &["latte", "espresso"], false)
+    }
+
+    #[test]
+    fn built_engine_works() {
+        // Given
+        let engine = coffee_engine_builder().build().unwrap();
+
+        // When
+        let result = engine.parse("Make me a latte please", None).unwrap();
+        let unknown_value_result = engine.parse("make me a tea", None).unwrap();
+
+        // Then
+        assert_eq!("MakeCoffee", result.intent.unwrap().intent_name);
+        let slots = result.slots.unwrap();
+        assert_eq!(1, slots.len());
+        assert_eq!("coffee_type", slots[0].slot_name);
+        assert_eq!(SlotValue::Custom("latte".to_string().into()), slots[0].value);
+        assert_eq!("MakeCoffee", unknown_value_result.intent.unwrap().intent_name);
+        assert_eq!(Some(vec![]), unknown_value_result.slots);
+    }
+
+    #[test]
+    fn build_should_fail_with_unknown_entity() {
+        // Given
+        let builder = coffee_engine_builder().slot("MakeCoffee", "size", "coffee_size");
+
+        // When
+        let engine = builder.build();
+
+        // Then
+        assert!(engine.is_err());
+    }
+
+    #[test]
+    fn build_should_fail_with_unknown_language() {
+        // Given
+        let builder = coffee_engine_builder().language("xx");
+
+        // When
+        let engine = builder.build();
+
+        // Then
+        assert!(engine.is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use std::sync::Arc;
+use std::thread;
+
+use itertools::Itertools;
+
+use errors::*;
+use language_identification::LanguageIdentifier;
+use nlu_engine::SnipsNluEngine;
+use snips_nlu_ontology::{IntentParserResult, Language};
+
+/// Result of a parse by a router, along with the identifier of the assistant which produced it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutedParserResult {
+    /// Identifier of the assistant whose result was selected, `None` when no assistant found an
+    /// intent
+    pub assistant_id: Option<String>,
+    /// Language identified in the input, `None` when the router has no language identifier or
+    /// when the language could not be identified
+    pub language: Option<Language>,
+    pub result: IntentParserResult,
+}
+
+/// Routes inputs to the engines of several assistants
+///
+/// Each input is parsed by all the engines and the intent found with the highest probability is
+/// returned, the assistant which was added first being selected in case of a tie. When the router
+/// has a language identifier, inputs whose language is identified are only parsed by the engines
+/// of this language. The intents filter may contain the intents of any assistant, each engine
+/// being passed the intents it knows.
+#[derive(Default)]
+pub struct NluRouter {
+    engines: Vec<(String, Arc<SnipsNluEngine>)>,
+    parallel: bool,
+    language_identifier: Option<Box<LanguageIdentifier>>,
+}
+
+impl NluRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the inputs with all the engines in parallel, each engine running in its own thread
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Identifies the language of the inputs among the languages of the engines, so that inputs
+    /// are only parsed by the engines of their language
+    pub fn language_identifier(mut self, language_identifier: Box<LanguageIdentifier>) -> Self {
+        self.language_identifier = Some(language_identifier);
+        self
+    }
+
+    /// Adds the engine of an assistant, replacing the engine previously added with the same
+    /// identifier if any
+    pub fn add_engine(&mut self, assistant_id: &str, engine: Arc<SnipsNluEngine>) {
+        if let Some(index) = self.engine_index(assistant_id) {
+            self.engines[index].1 = engine;
+        } else {
+            self.engines.push((assistant_id.to_string(), engine));
+        }
+    }
+
+    /// Removes the engine of an assistant, returning it if it was found
+    pub fn remove_engine(&mut self, assistant_id: &str) -> Option<Arc<SnipsNluEngine>> {
+        self.engine_index(assistant_id)
+            .map(|index| self.engines.remove(index).1)
+    }
+
+    pub fn engine(&self, assistant_id: &str) -> Option<Arc<SnipsNluEngine>> {
+        self.engine_index(assistant_id)
+            .map(|index| self.engines[index].1.clone())
+    }
+
+    pub fn assistant_ids(&self) -> Vec<String> {
+        self.engines.iter().map(|&(ref id, _)| id.clone()).collect()
+    }
+
+    pub fn parse(
+        &self,
+        input: &str,
+        intents_filter: Option<&[String]>,
+    ) -> Result<RoutedParserResult> {
+        if let Some(intents_filter) = intents_filter {
+            let unknown_intents = intents_filter
+                .iter()
+                .filter(|intent_name| {
+                    !self.engines
+                        .iter()
+                        .any(|&(_, ref engine)| engine.intents().contains(&&***intent_name))
+                })
+                .cloned()
+                .collect_vec();
+            if !unknown_intents.is_empty() {
+                bail!(ErrorKind::UnknownIntents(unknown_intents));
+            }
+        }
+        let language = self.identify_language(input);
+        let engines = self.engines
+            .iter()
+            .filter(|&&(_, ref engine)| language.map_or(true, |l| engine.language() == l))
+            .collect_vec();
+        let intents_filters = engines
+            .iter()
+            .map(|&&(_, ref engine)| engine_intents_filter(engine, intents_filter))
+            .collect_vec();
+        let results = if self.parallel {
+            parse_in_parallel(&engines, input, intents_filters)?
+        } else {
+            engines
+                .iter()
+                .zip(intents_filters.iter())
+                .map(|(&&(_, ref engine), intents_filter)| {
+                    engine.parse(input, intents_filter.as_ref().map(|intents| &**intents))
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+        Ok(select_best_result(input, &engines, language, results))
+    }
+
+    fn identify_language(&self, input: &str) -> Option<Language> {
+        let language_identifier = self.language_identifier.as_ref()?;
+        let mut languages = vec![];
+        for &(_, ref engine) in &self.engines {
+            if !languages.contains(&engine.language()) {
+                languages.push(engine.language());
+            }
+        }
+        language_identifier.identify(input, &languages)
+    }
+
+    fn engine_index(&self, assistant_id: &str) -> Option<usize> {
+        self.engines.iter().position(|&(ref id, _)| id == assistant_id)
+    }
+}
+
+/// Restricts the intents filter to the intents of the engine
+fn engine_intents_filter(
+    engine: &SnipsNluEngine,
+    intents_filter: Option<&[String]>,
+) -> Option<Vec<String>> {
+    intents_filter.map(|intents| {
+        let engine_intents = engine.intents();
+        intents
+            .iter()
+            .filter(|intent_name| engine_intents.contains(&&***intent_name))
+            .cloned()
+            .collect()
+    })
+}
+
+fn parse_in_parallel(
+    engines: &[&(String, Arc<SnipsNluEngine>)],
+    input: &str,
+    intents_filters: Vec<Option<Vec<String>>>,
+) -> Result<Vec<IntentParserResult>> {
+    let handles = engines
+        .iter()
+        .zip(intents_filters.into_iter())
+        .map(|(&&(_, ref engine), intents_filter)| {
+            let engine = engine.clone();
+            let input = input.to_string();
+            thread::spawn(move || {
+                engine.parse(&input, intents_filter.as_ref().map(|intents| &**intents))
+            })
+        })
+        .collect::<Vec<_>>();
+    handles
+        .into_iter()
+        .map(|handle| {
+            handle
+                .join()
+                .map_err(|_| Error::from("An engine panicked while parsing"))?
+        })
+        .collect()
+}
+
+fn select_best_result(
+    input: &str,
+    engines: &[&(String, Arc<SnipsNluEngine>)],
+    language: Option<Language>,
+    results: Vec<IntentParserResult>,
+) -> RoutedParserResult {
+    let mut best_result: Option<(usize, IntentParserResult)> = None;
+    for (index, result) in results.into_iter().enumerate() {
+        let probability = match result.intent {
+            Some(ref intent) => intent.probability,
+            None => continue,
+        };
+        let is_better = match best_result {
+            Some((_, ref best)) => best.intent
+                .as_ref()
+                .map(|best_intent| probability > best_intent.probability)
+                .unwrap_or(true),
+            None => true,
+        };
+        if is_better {
+            best_result = Some((index, result));
+        }
+    }
+    if let Some((index, result)) = best_result {
+        RoutedParserResult {
+            assistant_id: Some(engines[index].0.clone()),
+            language,
+            result,
+        }
+    } else {
+        RoutedParserResult {
+            assistant_id: None,
+            language,
+            result: IntentParserResult {
+                input: input.to_string(),
+                intent: None,
+                slots: None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nlu_engine_builder::SnipsNluEngineBuilder;
+
+    fn test_router(parallel: bool) -> NluRouter {
+        let coffee_engine = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me [a] coffee"])
+            .build()
+            .unwrap();
+        let lights_engine = SnipsNluEngineBuilder::new()
+            .intent("TurnLightsOn", &["turn [the] lights on"])
+            .build()
+            .unwrap();
+        let mut router = NluRouter::new().parallel(parallel);
+        router.add_engine("coffee", Arc::new(coffee_engine));
+        router.add_engine("lights", Arc::new(lights_engine));
+        router
+    }
+
+    #[test]
+    fn parse_should_return_result_of_matching_assistant() {
+        for parallel in vec![false, true] {
+            // Given
+            let router = test_router(parallel);
+
+            // When
+            let result = router.parse("turn the lights on", None).unwrap();
+            let no_result = router.parse("what's the weather", None).unwrap();
+
+            // Then
+            assert_eq!(Some("lights".to_string()), result.assistant_id);
+            assert_eq!("TurnLightsOn", result.result.intent.unwrap().intent_name);
+            assert_eq!(None, no_result.assistant_id);
+            assert_eq!(None, no_result.result.intent);
+        }
+    }
+
+    #[test]
+    fn parse_should_filter_intents_of_each_assistant() {
+        for parallel in vec![false, true] {
+            // Given
+            let router = test_router(parallel);
+            let intents_filter = vec!["MakeCoffee".to_string()];
+            let unknown_intents_filter = vec!["TurnLightsOff".to_string()];
+
+            // When
+            let result = router
+                .parse("turn the lights on", Some(&intents_filter))
+                .unwrap();
+            let coffee_result = router
+                .parse("make me a coffee", Some(&intents_filter))
+                .unwrap();
+            let unknown_intents_result =
+                router.parse("turn the lights on", Some(&unknown_intents_filter));
+
+            // Then
+            assert_eq!(None, result.assistant_id);
+            assert_eq!(Some("coffee".to_string()), coffee_result.assistant_id);
+            assert!(unknown_intents_result.is_err());
+        }
+    }
+
+    #[test]
+    fn remove_engine_works() {
+        // Given
+        let mut router = test_router(false);
+
+        // When
+        let removed_engine = router.remove_engine("lights");
+        let result = router.parse("turn the lights on", None).unwrap();
+
+        // Then
+        assert!(removed_engine.is_some());
+        assert_eq!(vec!["coffee".to_string()], router.assistant_ids());
+        assert_eq!(None, result.assistant_id);
+    }
+
+    #[test]
+    fn parse_should_only_use_engines_of_identified_language() {
+        // Given
+        struct FrenchIdentifier;
+        impl LanguageIdentifier for FrenchIdentifier {
+            fn identify(&self, _input: &str, candidates: &[Language]) -> Option<Language> {
+                if candidates.contains(&Language::FR) {
+                    Some(Language::FR)
+                } else {
+                    None
+                }
+            }
+        }
+
+        let english_engine = SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me [a] coffee"])
+            .build()
+            .unwrap();
+        let french_engine = SnipsNluEngineBuilder::new()
+            .language("fr")
+            .intent("FaireUnCafe", &["make me [a] coffee"])
+            .build()
+            .unwrap();
+        let mut router = NluRouter::new().language_identifier(Box::new(FrenchIdentifier));
+        router.add_engine("english", Arc::new(english_engine));
+        router.add_engine("french", Arc::new(french_engine));
+
+        // When
+        let result = router.parse("make me a coffee", None).unwrap();
+
+        // Then
+        assert_eq!(Some("french".to_string()), result.assistant_id);
+        assert_eq!(Some(Language::FR), result.language);
+        assert_eq!("FaireUnCafe", result.result.intent.unwrap().intent_name);
+    }
+}
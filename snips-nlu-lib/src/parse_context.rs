@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use builtin_entity_parser::MultilingualBuiltinEntityParser;
+use snips_nlu_ontology::{BuiltinEntity, BuiltinEntityKind, Language};
+
+/// Maximum number of extractions of builtin entities kept by a context, the extractions being
+/// forgotten once it is reached so that a context reused across parses doesn't grow forever
+const MAX_CACHED_EXTRACTIONS: usize = 32;
+
+/// Builtin entities extracted by a parser, identified by its address, in a text with a filter of
+/// entity kinds
+type BuiltinEntitiesCache =
+    HashMap<(usize, String, Option<Vec<BuiltinEntityKind>>), Vec<BuiltinEntity>>;
+
+/// Builtin entity of the input which could fill several slots of the intent, and which was left
+/// out of the slots by the `ambiguous` disambiguation of the slot filler
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmbiguousBuiltinEntity {
+    pub entity: BuiltinEntity,
+    /// Names of the slots which the entity could fill
+    pub slot_names: Vec<String>,
+}
+
+/// Settings of a single parse, passed along the parse call chain from the engine down to the
+/// intent parsers, the slot fillers and their features, including on the threads of the pool
+/// which run the parsers concurrently
+#[derive(Clone, Default)]
+pub struct ParseContext {
+    /// Parser extracting the builtin entities of the input instead of the parsers of the
+    /// processing units, see `with_builtin_entities_languages`
+    builtin_entity_parser: Option<Arc<MultilingualBuiltinEntityParser>>,
+    /// Builtin entities already extracted during the parse, shared by the clones of the context
+    builtin_entities: Arc<Mutex<BuiltinEntitiesCache>>,
+    /// Builtin entities left out of the slots as they could fill several slots, which the engine
+    /// takes to explain the parse
+    ambiguous_builtin_entities: Arc<Mutex<Vec<AmbiguousBuiltinEntity>>>,
+}
+
+impl ParseContext {
+    /// Returns a context in which the builtin entities are extracted in the given languages
+    /// instead of the languages of the model
+    pub fn with_builtin_entities_languages(languages: &[Language]) -> Self {
+        Self {
+            builtin_entity_parser: Some(Arc::new(MultilingualBuiltinEntityParser::new(languages))),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the builtin entity parser to use in place of the parser of a processing unit,
+    /// processing units without builtin entity parser being left without one
+    pub fn builtin_entity_parser<'a>(
+        &'a self,
+        parser: Option<&'a Arc<MultilingualBuiltinEntityParser>>,
+    ) -> Option<&'a MultilingualBuiltinEntityParser> {
+        parser.map(|parser| self.builtin_entity_parser_or(parser))
+    }
+
+    /// Returns the builtin entity parser to use in place of the given parser
+    pub fn builtin_entity_parser_or<'a>(
+        &'a self,
+        parser: &'a MultilingualBuiltinEntityParser,
+    ) -> &'a MultilingualBuiltinEntityParser {
+        self.builtin_entity_parser
+            .as_ref()
+            .map(|parser| &**parser)
+            .unwrap_or(parser)
+    }
+
+    /// Extracts the builtin entities of the text with the parser, the entities extracted with
+    /// the same parser, text and filter earlier in the parse being reused
+    ///
+    /// The engine extracts the entities of the input to protect them from spell correction, and
+    /// the parsers then extract the same entities to format the input or to fill the slots.
+    pub fn extract_builtin_entities(
+        &self,
+        parser: &MultilingualBuiltinEntityParser,
+        text: &str,
+        filter_entity_kinds: Option<&[BuiltinEntityKind]>,
+    ) -> Vec<BuiltinEntity> {
+        let key = (
+            parser as *const MultilingualBuiltinEntityParser as usize,
+            text.to_string(),
+            filter_entity_kinds.map(|kinds| kinds.to_vec()),
+        );
+        if let Some(entities) = self.lock_builtin_entities().get(&key) {
+            return entities.clone();
+        }
+        let entities = parser.extract_entities(text, filter_entity_kinds);
+        let mut cache = self.lock_builtin_entities();
+        if cache.len() >= MAX_CACHED_EXTRACTIONS {
+            cache.clear();
+        }
+        cache.insert(key, entities.clone());
+        entities
+    }
+
+    /// Locks the extracted entities, which stay valid if a thread panicked while holding them
+    fn lock_builtin_entities(&self) -> MutexGuard<BuiltinEntitiesCache> {
+        lock(&self.builtin_entities)
+    }
+
+    /// Records a builtin entity which the slot filler left out of the slots
+    pub fn add_ambiguous_builtin_entity(&self, entity: AmbiguousBuiltinEntity) {
+        lock(&self.ambiguous_builtin_entities).push(entity);
+    }
+
+    /// Returns the ambiguous builtin entities recorded since the last call
+    pub fn take_ambiguous_builtin_entities(&self) -> Vec<AmbiguousBuiltinEntity> {
+        lock(&self.ambiguous_builtin_entities).drain(..).collect()
+    }
+}
+
+/// Locks the shared state of the context, which stays valid if a thread panicked while holding it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_ontology::{BuiltinEntityKind, NumberValue, SlotValue};
+
+    #[test]
+    fn with_builtin_entities_languages_should_override_the_parser() {
+        // Given
+        let parser = Arc::new(MultilingualBuiltinEntityParser::new(&[Language::EN]));
+        let context = ParseContext::with_builtin_entities_languages(&[Language::FR]);
+        let number_kinds = [BuiltinEntityKind::Number];
+
+        // When
+        let overridden_entities = context
+            .builtin_entity_parser(Some(&parser))
+            .unwrap()
+            .extract_entities("deux tasses", Some(&number_kinds));
+        let entities = ParseContext::default()
+            .builtin_entity_parser(Some(&parser))
+            .unwrap()
+            .extract_entities("deux tasses", Some(&number_kinds));
+
+        // Then
+        let values = overridden_entities
+            .into_iter()
+            .map(|e| e.entity)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![SlotValue::Number(NumberValue { value: 2.0 })], values);
+        assert!(entities.is_empty());
+        assert!(context.builtin_entity_parser(None).is_none());
+    }
+
+    #[test]
+    fn extract_builtin_entities_should_reuse_extracted_entities() {
+        // Given
+        let parser = MultilingualBuiltinEntityParser::new(&[Language::EN]);
+        let context = ParseContext::default();
+        let number_kinds = [BuiltinEntityKind::Number];
+
+        // When
+        let entities = context.extract_builtin_entities(&parser, "two cups", Some(&number_kinds));
+        let reused_entities = context
+            .clone()
+            .extract_builtin_entities(&parser, "two cups", Some(&number_kinds));
+
+        // Then
+        assert_eq!(1, entities.len());
+        assert_eq!(entities, reused_entities);
+        assert_eq!(1, context.lock_builtin_entities().len());
+    }
+}
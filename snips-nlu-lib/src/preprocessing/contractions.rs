@@ -0,0 +1,197 @@
+use std::ops::Range;
+
+use preprocessing::{find_words, is_apostrophe, with_case, PreprocessedInput};
+use snips_nlu_ontology::Language;
+
+/// Contractions of a language, written in lowercase with straight apostrophes
+struct ContractionRules {
+    /// Contracted words, which are replaced as a whole
+    words: &'static [(&'static str, &'static str)],
+    /// Elided words found at the beginning of words, such as French articles
+    prefixes: &'static [(&'static str, &'static str)],
+    /// Contracted words found at the end of words, such as English negations
+    suffixes: &'static [(&'static str, &'static str)],
+}
+
+const ENGLISH_RULES: ContractionRules = ContractionRules {
+    words: &[
+        ("can't", "can not"),
+        ("won't", "will not"),
+        ("shan't", "shall not"),
+        ("let's", "let us"),
+        ("what's", "what is"),
+        ("where's", "where is"),
+        ("when's", "when is"),
+        ("who's", "who is"),
+        ("how's", "how is"),
+        ("it's", "it is"),
+        ("that's", "that is"),
+        ("there's", "there is"),
+        ("here's", "here is"),
+        ("he's", "he is"),
+        ("she's", "she is"),
+    ],
+    prefixes: &[],
+    suffixes: &[
+        ("n't", " not"),
+        ("'re", " are"),
+        ("'m", " am"),
+        ("'ll", " will"),
+        ("'ve", " have"),
+        ("'d", " would"),
+    ],
+};
+
+const FRENCH_RULES: ContractionRules = ContractionRules {
+    words: &[],
+    prefixes: &[
+        ("jusqu'", "jusque "),
+        ("qu'", "que "),
+        ("j'", "je "),
+        ("l'", "le "),
+        ("d'", "de "),
+        ("m'", "me "),
+        ("t'", "te "),
+        ("s'", "se "),
+        ("n'", "ne "),
+        ("c'", "ce "),
+    ],
+    suffixes: &[],
+};
+
+const SPANISH_RULES: ContractionRules = ContractionRules {
+    words: &[("al", "a el"), ("del", "de el")],
+    prefixes: &[],
+    suffixes: &[],
+};
+
+const GERMAN_RULES: ContractionRules = ContractionRules {
+    words: &[
+        ("am", "an dem"),
+        ("ans", "an das"),
+        ("beim", "bei dem"),
+        ("im", "in dem"),
+        ("ins", "in das"),
+        ("vom", "von dem"),
+        ("zum", "zu dem"),
+        ("zur", "zu der"),
+    ],
+    prefixes: &[],
+    suffixes: &[],
+};
+
+fn get_contraction_rules(language: Language) -> Option<&'static ContractionRules> {
+    match language {
+        Language::EN => Some(&ENGLISH_RULES),
+        Language::FR => Some(&FRENCH_RULES),
+        Language::ES => Some(&SPANISH_RULES),
+        Language::DE => Some(&GERMAN_RULES),
+        _ => None,
+    }
+}
+
+impl PreprocessedInput {
+    /// Expands the contractions of the language, such as "what's" or "j'aime", only the
+    /// contracted part of the words being replaced so that the rest of the words keep their
+    /// original ranges
+    pub fn expand_contractions(self, language: Language) -> Self {
+        let rules = if let Some(rules) = get_contraction_rules(language) {
+            rules
+        } else {
+            return self;
+        };
+        let chars: Vec<char> = self.text().chars().collect();
+        let replacements = find_words(&chars)
+            .into_iter()
+            .filter_map(|word_range| expand_word(&chars, word_range, rules))
+            .collect();
+        self.replace(replacements)
+    }
+}
+
+fn expand_word(
+    chars: &[char],
+    word_range: Range<usize>,
+    rules: &ContractionRules,
+) -> Option<(Range<usize>, String)> {
+    let word = chars[word_range.clone()]
+        .iter()
+        .map(|c| if is_apostrophe(*c) { '\'' } else { *c })
+        .collect::<String>()
+        .to_lowercase();
+    // Only the chars of the contraction are replaced, which requires the lowercased word to
+    // have the same chars as the original one
+    if word.chars().count() != word_range.end - word_range.start {
+        return None;
+    }
+    let capitalized = chars[word_range.start].is_uppercase();
+    if let Some(&(_, expansion)) = rules.words.iter().find(|&&(w, _)| w == word) {
+        return Some((word_range, with_case(expansion, capitalized)));
+    }
+    if let Some(&(prefix, expansion)) = rules
+        .prefixes
+        .iter()
+        .find(|&&(prefix, _)| word.starts_with(prefix) && word.len() > prefix.len())
+    {
+        let prefix_end = word_range.start + prefix.chars().count();
+        return Some((word_range.start..prefix_end, with_case(expansion, capitalized)));
+    }
+    if let Some(&(suffix, expansion)) = rules
+        .suffixes
+        .iter()
+        .find(|&&(suffix, _)| word.ends_with(suffix) && word.len() > suffix.len())
+    {
+        let suffix_start = word_range.end - suffix.chars().count();
+        return Some((suffix_start..word_range.end, expansion.to_string()));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_contractions_should_expand_english_contractions() {
+        // Given
+        let input = PreprocessedInput::new("What's the weather? I don't know, they’re out");
+
+        // When
+        let preprocessed_input = input.expand_contractions(Language::EN);
+
+        // Then
+        assert_eq!(
+            "What is the weather? I do not know, they are out",
+            preprocessed_input.text()
+        );
+        assert_eq!(0..6, preprocessed_input.original_range(&(0..7)));
+        assert_eq!(22..24, preprocessed_input.original_range(&(23..25)));
+        assert_eq!(24..27, preprocessed_input.original_range(&(26..29)));
+    }
+
+    #[test]
+    fn expand_contractions_should_expand_french_elisions() {
+        // Given
+        let input = PreprocessedInput::new("J'aime l'eau d'Évian");
+
+        // When
+        let preprocessed_input = input.expand_contractions(Language::FR);
+
+        // Then
+        assert_eq!("Je aime le eau de Évian", preprocessed_input.text());
+        assert_eq!(2..6, preprocessed_input.original_range(&(3..7)));
+        assert_eq!(15..20, preprocessed_input.original_range(&(18..23)));
+    }
+
+    #[test]
+    fn expand_contractions_should_not_change_unsupported_languages() {
+        // Given
+        let input = PreprocessedInput::new("what's up");
+
+        // When
+        let preprocessed_input = input.expand_contractions(Language::JA);
+
+        // Then
+        assert_eq!("what's up", preprocessed_input.text());
+    }
+}
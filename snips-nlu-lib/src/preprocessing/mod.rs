@@ -0,0 +1,321 @@
+mod contractions;
+mod pipeline;
+mod spelling;
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::UnicodeNormalization as UnicodeNormalizationIterator;
+
+use configurations::UnicodeNormalization;
+use nlu_utils::token::Token;
+use snips_nlu_ontology::Slot;
+use tokenizer::{find_emojis, strip_emoji_modifiers};
+use utils::{grapheme_aligned_range, substring_with_char_range};
+
+pub use self::pipeline::PreprocessingPipeline;
+pub use self::spelling::SpellCorrector;
+
+/// Input transformed before being parsed, which keeps track of the chars of the original input
+/// each char of the transformed text comes from
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreprocessedInput {
+    original: String,
+    text: String,
+    /// Char range in the original input of each char of the text
+    original_ranges: Vec<Range<usize>>,
+}
+
+impl PreprocessedInput {
+    pub fn new(input: &str) -> Self {
+        Self {
+            original: input.to_string(),
+            text: input.to_string(),
+            original_ranges: (0..input.chars().count()).map(|i| i..i + 1).collect(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces non overlapping char ranges of the text, sorted by increasing position, the chars
+    /// of each replacement coming from all the original chars of the range it replaces
+    pub fn replace(self, replacements: Vec<(Range<usize>, String)>) -> Self {
+        if replacements.is_empty() {
+            return self;
+        }
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut text = String::with_capacity(self.text.len());
+        let mut original_ranges = Vec::with_capacity(self.original_ranges.len());
+        let mut position = 0;
+        for (range, replacement) in replacements {
+            text.extend(&chars[position..range.start]);
+            original_ranges.extend_from_slice(&self.original_ranges[position..range.start]);
+            let original_range = self.original_range(&range);
+            for c in replacement.chars() {
+                text.push(c);
+                original_ranges.push(original_range.clone());
+            }
+            position = range.end;
+        }
+        text.extend(&chars[position..]);
+        original_ranges.extend_from_slice(&self.original_ranges[position..]);
+        Self {
+            original: self.original,
+            text,
+            original_ranges,
+        }
+    }
+
+    /// Normalizes the text with the given Unicode normalization form
+    ///
+    /// Each char is normalized along with the combining chars which follow it, so that the chars
+    /// of the normalized text can be traced back to the original input.
+    pub fn normalize_unicode(self, form: UnicodeNormalization) -> Self {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut replacements = vec![];
+        let mut start = 0;
+        for end in 1..=chars.len() {
+            if end < chars.len() && canonical_combining_class(chars[end]) != 0 {
+                continue;
+            }
+            let cluster: String = chars[start..end].iter().collect();
+            let normalized: String = match form {
+                UnicodeNormalization::Nfc => cluster.nfc().collect(),
+                UnicodeNormalization::Nfkc => cluster.nfkc().collect(),
+            };
+            if normalized != cluster {
+                replacements.push((start..end, normalized));
+            }
+            start = end;
+        }
+        self.replace(replacements)
+    }
+
+    /// Replaces the emojis of the text by their aliases, emojis with skin tones or variation
+    /// selectors being looked up without them when they have no alias of their own
+    ///
+    /// Aliases are separated by spaces from the words they are glued to, so that they are
+    /// tokenized as words of their own.
+    pub fn replace_emojis(self, aliases: &HashMap<String, String>) -> Self {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut replacements: Vec<(Range<usize>, String)> = vec![];
+        for emoji_range in find_emojis(&self.text) {
+            let emoji: String = chars[emoji_range.clone()].iter().collect();
+            let alias = aliases
+                .get(&emoji)
+                .or_else(|| aliases.get(&strip_emoji_modifiers(&emoji)));
+            if let Some(alias) = alias {
+                let mut replacement = String::new();
+                let follows_replacement = replacements
+                    .last()
+                    .map_or(false, |&(ref range, _)| range.end == emoji_range.start);
+                if emoji_range.start > 0
+                    && !chars[emoji_range.start - 1].is_whitespace()
+                    && !follows_replacement
+                {
+                    replacement.push(' ');
+                }
+                replacement.push_str(alias);
+                if emoji_range.end < chars.len() && !chars[emoji_range.end].is_whitespace() {
+                    replacement.push(' ');
+                }
+                replacements.push((emoji_range, replacement));
+            }
+        }
+        self.replace(replacements)
+    }
+
+    /// Maps a char range of the text to the char range of the original input it comes from
+    pub fn original_range(&self, range: &Range<usize>) -> Range<usize> {
+        if range.start < range.end {
+            self.original_ranges[range.start].start..self.original_ranges[range.end - 1].end
+        } else {
+            let position = self.original_ranges
+                .get(range.start)
+                .map(|original_range| original_range.start)
+                .unwrap_or_else(|| self.original.chars().count());
+            position..position
+        }
+    }
+
+    /// Maps the range of a slot found in the text to the original input, the raw value of the
+    /// slot becoming the original chars it was found in
+    ///
+    /// The range is widened so that it does not split the grapheme clusters of the original input.
+    pub fn restore_slot(&self, slot: Slot) -> Slot {
+        let range = if let Some(range) = slot.range.clone() {
+            range
+        } else {
+            return slot;
+        };
+        let original_range = if self.text == self.original {
+            range.clone()
+        } else {
+            self.original_range(&range)
+        };
+        let original_range = grapheme_aligned_range(&self.original, &original_range);
+        if self.text == self.original && original_range == range {
+            return slot;
+        }
+        Slot {
+            raw_value: substring_with_char_range(&self.original, &original_range).to_string(),
+            range: Some(original_range),
+            ..slot
+        }
+    }
+
+    /// Maps the byte and char ranges of tokens of the text to the original input
+    pub fn restore_tokens(&self, tokens: Vec<Token>) -> Vec<Token> {
+        if self.text == self.original {
+            return tokens;
+        }
+        // Byte offsets of the original chars, followed by the length of the original input
+        let byte_offsets: Vec<usize> = self.original
+            .char_indices()
+            .map(|(byte_offset, _)| byte_offset)
+            .chain(Some(self.original.len()))
+            .collect();
+        tokens
+            .into_iter()
+            .map(|token| {
+                let char_range = self.original_range(&token.char_range);
+                Token {
+                    range: byte_offsets[char_range.start]..byte_offsets[char_range.end],
+                    char_range,
+                    ..token
+                }
+            })
+            .collect()
+    }
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+/// Returns the char ranges of the words, which may contain apostrophes
+fn find_words(chars: &[char]) -> Vec<Range<usize>> {
+    let mut words: Vec<Range<usize>> = vec![];
+    for (index, c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() && !is_apostrophe(*c) {
+            continue;
+        }
+        if let Some(word) = words.last_mut() {
+            if word.end == index {
+                word.end = index + 1;
+                continue;
+            }
+        }
+        words.push(index..index + 1);
+    }
+    words
+}
+
+fn with_case(expansion: &str, capitalized: bool) -> String {
+    if !capitalized {
+        return expansion.to_string();
+    }
+    let mut chars = expansion.chars();
+    chars
+        .next()
+        .map(|first_char| first_char.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use snips_nlu_ontology::SlotValue;
+
+    #[test]
+    fn normalize_unicode_should_compose_accents() {
+        // Given
+        let input = PreprocessedInput::new("un cafe\u{301} noir");
+
+        // When
+        let preprocessed_input = input.normalize_unicode(UnicodeNormalization::Nfc);
+
+        // Then
+        assert_eq!("un café noir", preprocessed_input.text());
+        assert_eq!(3..8, preprocessed_input.original_range(&(3..7)));
+        assert_eq!(9..13, preprocessed_input.original_range(&(8..12)));
+    }
+
+    #[test]
+    fn normalize_unicode_should_replace_compatibility_chars() {
+        // Given
+        let input = PreprocessedInput::new("ﬁve ２ cups");
+
+        // When
+        let preprocessed_input = input.normalize_unicode(UnicodeNormalization::Nfkc);
+
+        // Then
+        assert_eq!("five 2 cups", preprocessed_input.text());
+        assert_eq!(0..3, preprocessed_input.original_range(&(0..4)));
+        assert_eq!(4..5, preprocessed_input.original_range(&(5..6)));
+    }
+
+    #[test]
+    fn replace_emojis_works() {
+        // Given
+        let aliases = hashmap! {
+            "👍".to_string() => ":thumbsup:".to_string(),
+            "☕".to_string() => ":coffee:".to_string(),
+        };
+        let input = PreprocessedInput::new("great👍🏽👍 a ☕\u{FE0F} 🙂");
+
+        // When
+        let preprocessed_input = input.replace_emojis(&aliases);
+
+        // Then
+        assert_eq!("great :thumbsup: :thumbsup: a :coffee: 🙂", preprocessed_input.text());
+        assert_eq!(5..7, preprocessed_input.original_range(&(6..16)));
+        assert_eq!(11..13, preprocessed_input.original_range(&(30..38)));
+    }
+
+    #[test]
+    fn restore_slot_works() {
+        // Given
+        let preprocessed_input =
+            PreprocessedInput::new("un cafe\u{301}").normalize_unicode(UnicodeNormalization::Nfc);
+        let slot = Slot {
+            raw_value: "café".to_string(),
+            value: SlotValue::Custom("café".to_string().into()),
+            range: Some(3..7),
+            entity: "beverage".to_string(),
+            slot_name: "beverage".to_string(),
+        };
+
+        // When
+        let restored_slot = preprocessed_input.restore_slot(slot);
+
+        // Then
+        assert_eq!("cafe\u{301}", restored_slot.raw_value);
+        assert_eq!(Some(3..8), restored_slot.range);
+        assert_eq!(SlotValue::Custom("café".to_string().into()), restored_slot.value);
+    }
+
+    #[test]
+    fn restore_slot_should_not_split_grapheme_clusters() {
+        // Given
+        let preprocessed_input = PreprocessedInput::new("un cafe\u{301}");
+        let slot = Slot {
+            raw_value: "cafe".to_string(),
+            value: SlotValue::Custom("cafe".to_string().into()),
+            range: Some(3..7),
+            entity: "beverage".to_string(),
+            slot_name: "beverage".to_string(),
+        };
+
+        // When
+        let restored_slot = preprocessed_input.restore_slot(slot);
+
+        // Then
+        assert_eq!("cafe\u{301}", restored_slot.raw_value);
+        assert_eq!(Some(3..8), restored_slot.range);
+    }
+}
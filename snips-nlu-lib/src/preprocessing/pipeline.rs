@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::mem;
+use std::ops::Range;
+
+use regex::Regex;
+use snips_nlu_ontology::Language;
+use unicode_normalization::char::{canonical_combining_class, decompose_canonical};
+
+use configurations::{PreprocessingStep, SpellCorrectionConfiguration, UnicodeNormalization};
+use errors::*;
+use memory_footprint::HeapSize;
+use nlu_utils::string::convert_to_char_range;
+use preprocessing::{PreprocessedInput, SpellCorrector};
+
+/// Preprocessing steps of the configuration, with their regexes compiled and their spell
+/// correctors built
+pub struct PreprocessingPipeline {
+    steps: Vec<CompiledStep>,
+}
+
+enum CompiledStep {
+    NormalizeUnicode(UnicodeNormalization),
+    Lowercase,
+    StripAccents,
+    CollapseWhitespace,
+    RegexReplacement { regex: Regex, replacement: String },
+    ExpandContractions(Language),
+    CorrectSpelling(SpellCorrector),
+    ReplaceEmojis(HashMap<String, String>),
+}
+
+impl PreprocessingPipeline {
+    /// Compiles the steps for inputs in the given language, the spell correctors being built by
+    /// `build_spell_corrector`
+    pub fn new<F>(
+        steps: &[PreprocessingStep],
+        language: Language,
+        build_spell_corrector: F,
+    ) -> Result<Self>
+    where
+        F: Fn(&SpellCorrectionConfiguration) -> Result<SpellCorrector>,
+    {
+        let steps = steps
+            .iter()
+            .map(|step| {
+                Ok(match *step {
+                    PreprocessingStep::NormalizeUnicode(form) => {
+                        CompiledStep::NormalizeUnicode(form)
+                    }
+                    PreprocessingStep::Lowercase => CompiledStep::Lowercase,
+                    PreprocessingStep::StripAccents => CompiledStep::StripAccents,
+                    PreprocessingStep::CollapseWhitespace => CompiledStep::CollapseWhitespace,
+                    PreprocessingStep::RegexReplacement {
+                        ref pattern,
+                        ref replacement,
+                    } => CompiledStep::RegexReplacement {
+                        regex: Regex::new(pattern)
+                            .chain_err(|| format!("Invalid preprocessing regex: '{}'", pattern))?,
+                        replacement: replacement.clone(),
+                    },
+                    PreprocessingStep::ExpandContractions => {
+                        CompiledStep::ExpandContractions(language)
+                    }
+                    PreprocessingStep::CorrectSpelling(ref config) => {
+                        CompiledStep::CorrectSpelling(build_spell_corrector(config)?)
+                    }
+                    PreprocessingStep::ReplaceEmojis(ref aliases) => {
+                        CompiledStep::ReplaceEmojis(aliases.clone())
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { steps })
+    }
+
+    /// Applies the steps in order, the spell correction leaving unchanged the words which
+    /// overlap the ranges returned by `protected_ranges` for the text to correct, such as the
+    /// ranges of its builtin entities
+    pub fn apply<F>(&self, input: PreprocessedInput, protected_ranges: F) -> PreprocessedInput
+    where
+        F: Fn(&str) -> Vec<Range<usize>>,
+    {
+        self.steps
+            .iter()
+            .fold(input, |preprocessed_input, step| match *step {
+                CompiledStep::NormalizeUnicode(form) => preprocessed_input.normalize_unicode(form),
+                CompiledStep::Lowercase => preprocessed_input.lowercase(),
+                CompiledStep::StripAccents => preprocessed_input.strip_accents(),
+                CompiledStep::CollapseWhitespace => preprocessed_input.collapse_whitespace(),
+                CompiledStep::RegexReplacement {
+                    ref regex,
+                    ref replacement,
+                } => preprocessed_input.replace_regex(regex, replacement),
+                CompiledStep::ExpandContractions(language) => {
+                    preprocessed_input.expand_contractions(language)
+                }
+                CompiledStep::CorrectSpelling(ref corrector) => {
+                    let ranges = protected_ranges(preprocessed_input.text());
+                    preprocessed_input.correct_spelling(corrector, &ranges)
+                }
+                CompiledStep::ReplaceEmojis(ref aliases) => {
+                    preprocessed_input.replace_emojis(aliases)
+                }
+            })
+    }
+}
+
+/// The compiled regexes are counted by their patterns, as the regex crate doesn't expose their
+/// size
+impl HeapSize for PreprocessingPipeline {
+    fn heap_size(&self) -> usize {
+        self.steps.capacity() * mem::size_of::<CompiledStep>()
+            + self.steps
+                .iter()
+                .map(|step| match *step {
+                    CompiledStep::RegexReplacement {
+                        ref regex,
+                        ref replacement,
+                    } => regex.as_str().len() + replacement.heap_size(),
+                    CompiledStep::CorrectSpelling(ref corrector) => corrector.heap_size(),
+                    CompiledStep::ReplaceEmojis(ref aliases) => aliases.heap_size(),
+                    _ => 0,
+                })
+                .sum::<usize>()
+    }
+}
+
+impl PreprocessedInput {
+    pub fn lowercase(self) -> Self {
+        let replacements = self.text()
+            .chars()
+            .enumerate()
+            .filter(|&(_, c)| c.to_lowercase().ne(Some(c)))
+            .map(|(index, c)| (index..index + 1, c.to_lowercase().collect()))
+            .collect();
+        self.replace(replacements)
+    }
+
+    /// Removes the diacritics of the text, letters being decomposed so that their accents are
+    /// dropped along with the combining accents of the text
+    pub fn strip_accents(self) -> Self {
+        let replacements = self.text()
+            .chars()
+            .enumerate()
+            .filter_map(|(index, c)| {
+                let mut stripped = String::new();
+                decompose_canonical(c, |decomposed_char| {
+                    if canonical_combining_class(decomposed_char) == 0 {
+                        stripped.push(decomposed_char);
+                    }
+                });
+                if stripped.chars().eq(Some(c)) {
+                    None
+                } else {
+                    Some((index..index + 1, stripped))
+                }
+            })
+            .collect();
+        self.replace(replacements)
+    }
+
+    /// Replaces each sequence of whitespaces by a single space, the leading and trailing
+    /// whitespaces being removed
+    pub fn collapse_whitespace(self) -> Self {
+        let chars: Vec<char> = self.text().chars().collect();
+        let mut replacements = vec![];
+        let mut index = 0;
+        while index < chars.len() {
+            if !chars[index].is_whitespace() {
+                index += 1;
+                continue;
+            }
+            let start = index;
+            while index < chars.len() && chars[index].is_whitespace() {
+                index += 1;
+            }
+            let replacement = if start == 0 || index == chars.len() { "" } else { " " };
+            if chars[start..index].iter().collect::<String>() != replacement {
+                replacements.push((start..index, replacement.to_string()));
+            }
+        }
+        self.replace(replacements)
+    }
+
+    /// Replaces the matches of the regex, the replacement possibly referring to the groups of the
+    /// regex such as `$1`
+    pub fn replace_regex(self, regex: &Regex, replacement: &str) -> Self {
+        let replacements = regex
+            .captures_iter(self.text())
+            .filter_map(|captures| {
+                let matched = captures.get(0)?;
+                if matched.start() == matched.end() {
+                    return None;
+                }
+                let mut expanded_replacement = String::new();
+                captures.expand(replacement, &mut expanded_replacement);
+                let char_range =
+                    convert_to_char_range(self.text(), &(matched.start()..matched.end()));
+                Some((char_range, expanded_replacement))
+            })
+            .collect();
+        self.replace(replacements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline(steps: &[PreprocessingStep]) -> Result<PreprocessingPipeline> {
+        PreprocessingPipeline::new(steps, Language::EN, |config| {
+            let words = vec!["coffee".to_string(), "please".to_string()];
+            Ok(SpellCorrector::new(words, config))
+        })
+    }
+
+    #[test]
+    fn pipeline_should_apply_steps_in_order() {
+        // Given
+        let steps = vec![
+            PreprocessingStep::Lowercase,
+            PreprocessingStep::StripAccents,
+            PreprocessingStep::CollapseWhitespace,
+            PreprocessingStep::RegexReplacement {
+                pattern: r"\bpls\b".to_string(),
+                replacement: "please".to_string(),
+            },
+        ];
+        let pipeline = pipeline(&steps).unwrap();
+        let input = PreprocessedInput::new("  Un  CAFÉ  pls ");
+
+        // When
+        let preprocessed_input = pipeline.apply(input, |_| vec![]);
+
+        // Then
+        assert_eq!("un cafe please", preprocessed_input.text());
+        assert_eq!(6..10, preprocessed_input.original_range(&(3..7)));
+        assert_eq!(12..15, preprocessed_input.original_range(&(8..14)));
+    }
+
+    #[test]
+    fn pipeline_should_apply_language_steps() {
+        // Given
+        let steps = vec![
+            PreprocessingStep::NormalizeUnicode(UnicodeNormalization::Nfkc),
+            PreprocessingStep::ExpandContractions,
+            PreprocessingStep::CorrectSpelling(SpellCorrectionConfiguration {
+                max_edit_distance: 1,
+                min_word_length: 4,
+            }),
+            PreprocessingStep::ReplaceEmojis(hashmap! {
+                "☕".to_string() => ":coffee:".to_string(),
+            }),
+        ];
+        let pipeline = pipeline(&steps).unwrap();
+        let input = PreprocessedInput::new("ｃｏｆｅｅ ☕ what's cofee");
+
+        // When
+        let preprocessed_input = pipeline.apply(input, |_| vec![0..6]);
+
+        // Then
+        assert_eq!("cofee :coffee: what is coffee", preprocessed_input.text());
+        assert_eq!(6..7, preprocessed_input.original_range(&(6..14)));
+        assert_eq!(15..20, preprocessed_input.original_range(&(23..29)));
+    }
+
+    #[test]
+    fn strip_accents_should_remove_combining_accents() {
+        // Given
+        let input = PreprocessedInput::new("cafe\u{301} crème");
+
+        // When
+        let preprocessed_input = input.strip_accents();
+
+        // Then
+        assert_eq!("cafe creme", preprocessed_input.text());
+        assert_eq!(0..4, preprocessed_input.original_range(&(0..4)));
+        assert_eq!(6..11, preprocessed_input.original_range(&(5..10)));
+    }
+
+    #[test]
+    fn replace_regex_should_expand_groups() {
+        // Given
+        let regex = Regex::new(r"(\d+)h").unwrap();
+        let input = PreprocessedInput::new("wake me at 7h");
+
+        // When
+        let preprocessed_input = input.replace_regex(&regex, "$1 o'clock");
+
+        // Then
+        assert_eq!("wake me at 7 o'clock", preprocessed_input.text());
+    }
+
+    #[test]
+    fn pipeline_should_fail_with_invalid_regex() {
+        // Given
+        let steps = vec![
+            PreprocessingStep::RegexReplacement {
+                pattern: "(unclosed".to_string(),
+                replacement: "".to_string(),
+            },
+        ];
+
+        // When
+        let pipeline = pipeline(&steps);
+
+        // Then
+        assert!(pipeline.is_err());
+    }
+}
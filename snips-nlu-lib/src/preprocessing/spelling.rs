@@ -0,0 +1,325 @@
+use std::cmp::{max, min};
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::ops::Range;
+
+use configurations::SpellCorrectionConfiguration;
+use memory_footprint::HeapSize;
+use nlu_utils::range::ranges_overlap;
+use preprocessing::{find_words, with_case, PreprocessedInput};
+
+/// Corrects the misspelled words of the inputs with the closest words of a vocabulary
+///
+/// The candidate corrections are looked up in an index of the strings obtained by deleting up to
+/// `max_edit_distance` chars of the words of the vocabulary: a word within this edit distance of
+/// the input shares at least one of these strings with it, so that only a few candidates are
+/// compared to the input instead of the whole vocabulary.
+pub struct SpellCorrector {
+    vocabulary: HashSet<String>,
+    /// Words of the vocabulary which are long enough to be corrections, in alphabetical order so
+    /// that ties are broken in a deterministic way
+    words: Vec<Vec<char>>,
+    /// Indexes in `words` of the words giving each string once some of their chars are deleted
+    deletions: HashMap<String, Vec<usize>>,
+    max_edit_distance: usize,
+    min_word_length: usize,
+}
+
+impl SpellCorrector {
+    pub fn new<I>(words: I, config: &SpellCorrectionConfiguration) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let vocabulary: HashSet<String> = words
+            .into_iter()
+            .map(|word| word.to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+        let min_correction_length = config
+            .min_word_length
+            .saturating_sub(config.max_edit_distance);
+        let mut words: Vec<Vec<char>> = vocabulary
+            .iter()
+            .map(|word| word.chars().collect::<Vec<char>>())
+            .filter(|chars| chars.len() >= min_correction_length)
+            .collect();
+        words.sort();
+        let mut deletions: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, word) in words.iter().enumerate() {
+            for deletion in get_deletions(word, config.max_edit_distance) {
+                deletions.entry(deletion).or_insert_with(Vec::new).push(index);
+            }
+        }
+        Self {
+            vocabulary,
+            words,
+            deletions,
+            max_edit_distance: config.max_edit_distance,
+            min_word_length: config.min_word_length,
+        }
+    }
+
+    /// Returns the closest word of the vocabulary, or `None` when the word is known, too short
+    /// or too far from all the words of the vocabulary
+    pub fn correct(&self, word: &str) -> Option<String> {
+        let word = word.to_lowercase();
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() < self.min_word_length
+            || chars.iter().any(|c| !c.is_alphabetic())
+            || self.vocabulary.contains(&word)
+        {
+            return None;
+        }
+        let mut candidates: Vec<usize> = get_deletions(&chars, self.max_edit_distance)
+            .iter()
+            .flat_map(|deletion| self.deletions.get(deletion).into_iter().flatten().cloned())
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        candidates
+            .into_iter()
+            .filter_map(|index| {
+                bounded_edit_distance(&chars, &self.words[index], self.max_edit_distance)
+                    .map(|distance| (distance, index))
+            })
+            .min()
+            .map(|(_, index)| self.words[index].iter().collect())
+    }
+}
+
+impl HeapSize for SpellCorrector {
+    fn heap_size(&self) -> usize {
+        self.vocabulary.heap_size() + self.words.heap_size() + self.deletions.heap_size()
+    }
+}
+
+/// Returns the strings obtained by deleting up to `max_deletions` chars of the word, including
+/// the word itself
+fn get_deletions(word: &[char], max_deletions: usize) -> HashSet<String> {
+    let mut deletions: HashSet<Vec<char>> = HashSet::new();
+    deletions.insert(word.to_vec());
+    let mut last_deletions = vec![word.to_vec()];
+    for _ in 0..max_deletions {
+        let mut new_deletions = vec![];
+        for chars in &last_deletions {
+            for index in 0..chars.len() {
+                let mut deletion = chars.clone();
+                deletion.remove(index);
+                if deletions.insert(deletion.clone()) {
+                    new_deletions.push(deletion);
+                }
+            }
+        }
+        last_deletions = new_deletions;
+    }
+    deletions
+        .into_iter()
+        .map(|chars| chars.into_iter().collect())
+        .collect()
+}
+
+impl PreprocessedInput {
+    /// Replaces the misspelled words of the text by their corrections, the words overlapping the
+    /// protected ranges, such as the ones of builtin entities, being left unchanged
+    pub fn correct_spelling(
+        self,
+        corrector: &SpellCorrector,
+        protected_ranges: &[Range<usize>],
+    ) -> Self {
+        let chars: Vec<char> = self.text().chars().collect();
+        let replacements = find_words(&chars)
+            .into_iter()
+            .filter(|word_range| {
+                !protected_ranges
+                    .iter()
+                    .any(|range| ranges_overlap(range, word_range))
+            })
+            .filter_map(|word_range| {
+                let word: String = chars[word_range.clone()].iter().collect();
+                corrector.correct(&word).map(|correction| {
+                    let capitalized = chars[word_range.start].is_uppercase();
+                    (word_range, with_case(&correction, capitalized))
+                })
+            })
+            .collect();
+        self.replace(replacements)
+    }
+}
+
+/// Optimal string alignment distance, which counts insertions, deletions, substitutions and
+/// transpositions of adjacent chars, or `None` when it is above `max_distance`
+///
+/// Only the diagonal band of the cells which can be within `max_distance` is computed, and the
+/// computation stops as soon as a row of the band is entirely above `max_distance`, so that most
+/// of the candidates of a large vocabulary are discarded after a few chars.
+fn bounded_edit_distance(left: &[char], right: &[char], max_distance: usize) -> Option<usize> {
+    let length_difference = max(left.len(), right.len()) - min(left.len(), right.len());
+    if length_difference > max_distance {
+        return None;
+    }
+    // Distances are capped to `above_max`, which stands for any distance above `max_distance`
+    let above_max = max_distance + 1;
+    let mut previous_previous_row = vec![above_max; right.len() + 1];
+    let mut previous_row: Vec<usize> = (0..=right.len()).map(|j| min(j, above_max)).collect();
+    let mut row = vec![above_max; right.len() + 1];
+    for i in 1..=left.len() {
+        for distance in row.iter_mut() {
+            *distance = above_max;
+        }
+        row[0] = min(i, above_max);
+        let band_start = max(1, i.saturating_sub(max_distance));
+        let band_end = min(right.len(), i + max_distance);
+        let mut row_min = row[0];
+        for j in band_start..=band_end {
+            let cost = if left[i - 1] == right[j - 1] { 0 } else { 1 };
+            let mut distance = min(
+                min(previous_row[j] + 1, row[j - 1] + 1),
+                previous_row[j - 1] + cost,
+            );
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                distance = min(distance, previous_previous_row[j - 2] + 1);
+            }
+            row[j] = min(distance, above_max);
+            row_min = min(row_min, row[j]);
+        }
+        if row_min > max_distance {
+            return None;
+        }
+        mem::swap(&mut previous_previous_row, &mut previous_row);
+        mem::swap(&mut previous_row, &mut row);
+    }
+    Some(previous_row[right.len()]).filter(|distance| *distance <= max_distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corrector(words: &[&str]) -> SpellCorrector {
+        let config = SpellCorrectionConfiguration {
+            max_edit_distance: 1,
+            min_word_length: 4,
+        };
+        SpellCorrector::new(words.iter().map(|word| word.to_string()), &config)
+    }
+
+    #[test]
+    fn bounded_edit_distance_works() {
+        // Given
+        let pairs = vec![
+            ("coffee", "cofee"),
+            ("tea", "eat"),
+            ("please", "plaese"),
+            ("", "hot"),
+            ("espresso", "expresso"),
+            ("cappuccino", "capuccino"),
+            ("latte", "lattes"),
+            ("mocha", "macchiato"),
+        ];
+
+        // When
+        let distances: Vec<Option<usize>> = pairs
+            .iter()
+            .map(|&(left, right)| {
+                let left: Vec<char> = left.chars().collect();
+                let right: Vec<char> = right.chars().collect();
+                bounded_edit_distance(&left, &right, 3)
+            })
+            .collect();
+
+        // Then
+        let expected_distances = vec![
+            Some(1),
+            Some(2),
+            Some(1),
+            Some(3),
+            Some(1),
+            Some(1),
+            Some(1),
+            None,
+        ];
+        assert_eq!(expected_distances, distances);
+    }
+
+    #[test]
+    fn bounded_edit_distance_should_stop_above_max_distance() {
+        // Given
+        let left: Vec<char> = "americano".chars().collect();
+        let right: Vec<char> = "amaretto".chars().collect();
+
+        // When
+        let distances: Vec<Option<usize>> = (0..6)
+            .map(|max_distance| bounded_edit_distance(&left, &right, max_distance))
+            .collect();
+
+        // Then
+        assert_eq!(vec![None, None, None, None, None, Some(5)], distances);
+    }
+
+    #[test]
+    fn correct_works() {
+        // Given
+        let corrector = corrector(&["coffee", "coffees", "please", "tea"]);
+
+        // When
+        let corrections = vec![
+            corrector.correct("Cofee"),
+            corrector.correct("plese"),
+            corrector.correct("please"),
+            corrector.correct("tee"),
+            corrector.correct("cfoeef"),
+        ];
+
+        // Then
+        let expected_corrections = vec![
+            Some("coffee".to_string()),
+            Some("please".to_string()),
+            None,
+            None,
+            None,
+        ];
+        assert_eq!(expected_corrections, corrections);
+    }
+
+    #[test]
+    fn correct_should_use_several_edits() {
+        // Given
+        let config = SpellCorrectionConfiguration {
+            max_edit_distance: 2,
+            min_word_length: 4,
+        };
+        let words = vec!["coffee".to_string(), "cocoa".to_string(), "tea".to_string()];
+        let corrector = SpellCorrector::new(words, &config);
+
+        // When
+        let corrections = vec![
+            corrector.correct("cofe"),
+            corrector.correct("kocao"),
+            corrector.correct("teas"),
+            corrector.correct("cafe"),
+        ];
+
+        // Then
+        let expected_corrections = vec![
+            Some("coffee".to_string()),
+            Some("cocoa".to_string()),
+            Some("tea".to_string()),
+            None,
+        ];
+        assert_eq!(expected_corrections, corrections);
+    }
+
+    #[test]
+    fn correct_spelling_works() {
+        // Given
+        let corrector = corrector(&["make", "coffee", "three", "please"]);
+        let input = PreprocessedInput::new("Maek me there cofee plz");
+
+        // When
+        let preprocessed_input = input.correct_spelling(&corrector, &[8..13]);
+
+        // Then
+        assert_eq!("Make me there coffee plz", preprocessed_input.text());
+        assert_eq!(14..19, preprocessed_input.original_range(&(14..20)));
+    }
+}
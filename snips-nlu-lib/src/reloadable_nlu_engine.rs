@@ -0,0 +1,77 @@
+use std::sync::{Arc, RwLock};
+
+use errors::*;
+use nlu_engine::SnipsNluEngine;
+use snips_nlu_ontology::IntentParserResult;
+
+/// Wrapper around an engine which can be replaced by a newly loaded one while it is in use
+///
+/// Each parse runs on the engine which was current when it started, so that in-flight parses
+/// finish on the old engine while the new parses use the new one.
+pub struct ReloadableNluEngine {
+    engine: RwLock<Arc<SnipsNluEngine>>,
+}
+
+impl ReloadableNluEngine {
+    pub fn new(engine: SnipsNluEngine) -> Self {
+        Self {
+            engine: RwLock::new(Arc::new(engine)),
+        }
+    }
+
+    /// Returns the current engine
+    pub fn engine(&self) -> Result<Arc<SnipsNluEngine>> {
+        Ok(self.engine.read()?.clone())
+    }
+
+    /// Replaces the current engine, returning the previous one
+    pub fn reload(&self, engine: SnipsNluEngine) -> Result<Arc<SnipsNluEngine>> {
+        let mut current_engine = self.engine.write()?;
+        Ok(::std::mem::replace(&mut *current_engine, Arc::new(engine)))
+    }
+
+    pub fn parse(
+        &self,
+        input: &str,
+        intents_filter: Option<&[String]>,
+    ) -> Result<IntentParserResult> {
+        self.engine()?.parse(input, intents_filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use configurations::NluEngineConfiguration;
+    use testutils::parse_json;
+
+    fn test_engine() -> SnipsNluEngine {
+        let configuration: NluEngineConfiguration =
+            parse_json("tests/configurations/trained_assistant.json");
+        SnipsNluEngine::new(configuration).unwrap()
+    }
+
+    #[test]
+    fn reload_should_keep_previous_engine_usable() {
+        // Given
+        let reloadable_engine = ReloadableNluEngine::new(test_engine());
+        let previous_engine = reloadable_engine.engine().unwrap();
+
+        // When
+        let replaced_engine = reloadable_engine.reload(test_engine()).unwrap();
+        let result = reloadable_engine
+            .parse("Make me two cups of coffee please", None)
+            .unwrap();
+        let previous_result = previous_engine
+            .parse("Make me two cups of coffee please", None)
+            .unwrap();
+
+        // Then
+        assert!(Arc::ptr_eq(&previous_engine, &replaced_engine));
+        assert!(!Arc::ptr_eq(
+            &previous_engine,
+            &reloadable_engine.engine().unwrap()
+        ));
+        assert_eq!(previous_result, result);
+    }
+}
@@ -0,0 +1,115 @@
+use std::fs::{self, File};
+#[cfg(feature = "encryption")]
+use std::io::Cursor;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+#[cfg(feature = "encryption")]
+use std::sync::Arc;
+
+#[cfg(feature = "encryption")]
+use configurations::encryption::decrypt;
+use errors::*;
+
+/// Reads the files of a model and of its resources, decrypting them when the model is encrypted
+#[derive(Clone, Default)]
+pub struct ResourceReader {
+    /// Key with which the files are encrypted, if any
+    #[cfg(feature = "encryption")]
+    key: Option<Arc<Vec<u8>>>,
+}
+
+impl ResourceReader {
+    /// Returns a reader of files encrypted with AES-256-GCM using `key`
+    #[cfg(feature = "encryption")]
+    pub fn encrypted(key: &[u8]) -> Self {
+        Self {
+            key: Some(Arc::new(key.to_vec())),
+        }
+    }
+
+    /// Whether the files must be decrypted, in which case they can't be memory-mapped
+    #[cfg(feature = "encryption")]
+    pub fn is_encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    pub fn is_encrypted(&self) -> bool {
+        false
+    }
+
+    /// Returns the content of the file at `path`
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u8>> {
+        let bytes = fs::read(path.as_ref())
+            .chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", path.as_ref())))?;
+        self.decrypt(bytes)
+    }
+
+    /// Opens the file at `path`, which is read at once when it must be decrypted
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> Result<Box<BufRead>> {
+        #[cfg(feature = "encryption")]
+        {
+            if self.is_encrypted() {
+                return Ok(Box::new(Cursor::new(self.read(path)?)));
+            }
+        }
+        let file = File::open(path.as_ref())
+            .chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", path.as_ref())))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+
+    #[cfg(feature = "encryption")]
+    fn decrypt(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        match self.key {
+            Some(ref key) => decrypt(&bytes, key),
+            None => Ok(bytes),
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn decrypt(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use utils::file_path;
+
+    #[test]
+    fn read_should_return_plain_files() {
+        // Given
+        let path = file_path("tests/configurations/trained_assistant.json");
+        let reader = ResourceReader::default();
+
+        // When
+        let bytes = reader.read(&path).unwrap();
+        let mut opened_bytes = vec![];
+        reader
+            .open(&path)
+            .unwrap()
+            .read_to_end(&mut opened_bytes)
+            .unwrap();
+
+        // Then
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+        assert_eq!(bytes, opened_bytes);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn read_should_decrypt_encrypted_files() {
+        // Given
+        let path = file_path("tests/encrypted_resources/en/stems.txt");
+
+        // When
+        let bytes = ResourceReader::encrypted(&[42; 32]).read(&path);
+        let bytes_with_wrong_key = ResourceReader::encrypted(&[0; 32]).read(&path);
+
+        // Then
+        assert_eq!(b"cups\tcup\n".to_vec(), bytes.unwrap());
+        assert!(bytes_with_wrong_key.is_err());
+    }
+}
@@ -1,12 +1,15 @@
+#[cfg(test)]
 use std::collections::HashSet;
-use std::convert::From;
 #[cfg(test)]
 use std::io::prelude::Read;
+#[cfg(test)]
 use std::iter::FromIterator;
+use std::sync::Arc;
 
 use errors::*;
 #[cfg(test)]
 use serde_json;
+use resources::shared::{get_shared_resources, LanguageResources};
 use resources_packed::gazetteer_hits;
 use snips_nlu_ontology::Language;
 
@@ -17,28 +20,44 @@ pub trait Gazetteer {
 pub struct StaticMapGazetteer {
     name: String,
     language: Language,
+    /// Resources loaded with `load_resources`, used instead of the packed resources
+    shared_resources: Option<Arc<LanguageResources>>,
 }
 
 impl StaticMapGazetteer {
     pub fn new(gazetteer_name: &str, language: Language, use_stemming: bool) -> Result<Self> {
         let stemming_suffix = if use_stemming { "_stem" } else { "" };
         let full_gazetteer_name = format!("{}{}", gazetteer_name, stemming_suffix);
-        // Hack to check if gazetteer exists
-        gazetteer_hits(language, &full_gazetteer_name, "")?;
+        let shared_resources = get_shared_resources(language).and_then(|resources| {
+            if resources.gazetteers.contains_key(&full_gazetteer_name) {
+                Some(resources)
+            } else {
+                None
+            }
+        });
+        if shared_resources.is_none() {
+            // Hack to check if gazetteer exists
+            gazetteer_hits(language, &full_gazetteer_name, "")?;
+        }
         Ok(Self {
             name: full_gazetteer_name,
             language,
+            shared_resources,
         })
     }
 }
 
 impl Gazetteer for StaticMapGazetteer {
     fn contains(&self, value: &str) -> bool {
+        if let Some(resources) = self.shared_resources.as_ref() {
+            return resources.gazetteers[&self.name].contains(value);
+        }
         // checked during initialization
         gazetteer_hits(self.language, &self.name, value).unwrap()
     }
 }
 
+#[cfg(test)]
 pub struct HashSetGazetteer {
     values: HashSet<String>,
 }
@@ -55,6 +74,7 @@ impl HashSetGazetteer {
     }
 }
 
+#[cfg(test)]
 impl<I> From<I> for HashSetGazetteer
 where
     I: Iterator<Item = String>,
@@ -66,6 +86,7 @@ where
     }
 }
 
+#[cfg(test)]
 impl Gazetteer for HashSetGazetteer {
     fn contains(&self, value: &str) -> bool {
         self.values.contains(value)
@@ -1,3 +1,5 @@
 pub mod gazetteer;
+pub mod shared;
 pub mod stemmer;
 pub mod word_clusterer;
+pub mod word_embeddings;
@@ -0,0 +1,188 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use errors::*;
+use resource_reader::ResourceReader;
+use snips_nlu_ontology::Language;
+
+/// Resources of a language, loaded from a directory with the following layout:
+/// - `stop_words.txt` and `noise.txt`: one word per line
+/// - `stems.txt`: one `word<TAB>stem` pair per line
+/// - `word_clusters/<clusters name>.txt`: one `word<TAB>cluster` pair per line
+/// - `gazetteers/<gazetteer name>.txt`: one value per line
+///
+/// All the files are optional.
+#[derive(Debug, Default)]
+pub struct LanguageResources {
+    pub stop_words: HashSet<String>,
+    pub noise_words: HashSet<String>,
+    pub stems: HashMap<String, String>,
+    pub word_clusters: HashMap<String, HashMap<String, String>>,
+    pub gazetteers: HashMap<String, HashSet<String>>,
+}
+
+lazy_static! {
+    static ref SHARED_RESOURCES: RwLock<HashMap<String, Arc<LanguageResources>>> =
+        RwLock::new(HashMap::new());
+}
+
+/// Loads the resources of the languages found in `path`, which contains a directory named after
+/// the code of each language, and shares them with all the engines of the process
+///
+/// These resources are used instead of the resources packed in the library. The languages whose
+/// resources have already been loaded are not loaded again. The languages found are returned.
+pub fn load_resources<P: AsRef<Path>>(path: P) -> Result<Vec<Language>> {
+    load_resources_with_reader(path.as_ref(), &ResourceReader::default())
+}
+
+/// Loads resources, as `load_resources`, whose files have been encrypted with AES-256-GCM using
+/// `key`
+#[cfg(feature = "encryption")]
+pub fn load_encrypted_resources<P: AsRef<Path>>(path: P, key: &[u8]) -> Result<Vec<Language>> {
+    load_resources_with_reader(path.as_ref(), &ResourceReader::encrypted(key))
+}
+
+fn load_resources_with_reader(path: &Path, reader: &ResourceReader) -> Result<Vec<Language>> {
+    let mut languages = vec![];
+    for entry in fs::read_dir(path)? {
+        let language_dir = entry?.path();
+        if !language_dir.is_dir() {
+            continue;
+        }
+        let language_code = language_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let language = Language::from_str(&language_code)
+            .chain_err(|| format!("Unsupported language resources: {:?}", language_dir))?;
+        if !SHARED_RESOURCES.read()?.contains_key(&language_code) {
+            let resources = load_language_resources(&language_dir, reader)?;
+            SHARED_RESOURCES
+                .write()?
+                .entry(language_code)
+                .or_insert_with(|| Arc::new(resources));
+        }
+        languages.push(language);
+    }
+    Ok(languages)
+}
+
+/// Returns the resources of a language loaded with `load_resources`, if any
+pub fn get_shared_resources(language: Language) -> Option<Arc<LanguageResources>> {
+    SHARED_RESOURCES
+        .read()
+        .ok()
+        .and_then(|resources| resources.get(&language.to_string()).cloned())
+}
+
+fn load_language_resources(
+    language_dir: &Path,
+    reader: &ResourceReader,
+) -> Result<LanguageResources> {
+    Ok(LanguageResources {
+        stop_words: read_words(&language_dir.join("stop_words.txt"), reader)?,
+        noise_words: read_words(&language_dir.join("noise.txt"), reader)?,
+        stems: read_pairs(&language_dir.join("stems.txt"), reader)?,
+        word_clusters: read_named_files(&language_dir.join("word_clusters"), |path| {
+            read_pairs(path, reader)
+        })?,
+        gazetteers: read_named_files(&language_dir.join("gazetteers"), |path| {
+            read_words(path, reader)
+        })?,
+    })
+}
+
+fn read_lines(path: &Path, reader: &ResourceReader) -> Result<Vec<String>> {
+    if !path.is_file() {
+        return Ok(vec![]);
+    }
+    let content = String::from_utf8(reader.read(path)?)
+        .chain_err(|| ErrorKind::ConfigLoad(format!("{:?}", path)))?;
+    Ok(content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect())
+}
+
+fn read_words(path: &Path, reader: &ResourceReader) -> Result<HashSet<String>> {
+    Ok(read_lines(path, reader)?.into_iter().collect())
+}
+
+fn read_pairs(path: &Path, reader: &ResourceReader) -> Result<HashMap<String, String>> {
+    read_lines(path, reader)?
+        .into_iter()
+        .map(|line| {
+            let mut split = line.splitn(2, '\t');
+            match (split.next(), split.next()) {
+                (Some(key), Some(value)) => Ok((key.to_string(), value.trim().to_string())),
+                _ => bail!("Invalid line in {:?}: '{}'", path, line),
+            }
+        })
+        .collect()
+}
+
+/// Reads the `.txt` files of a directory, indexed by their name without extension
+fn read_named_files<T, F>(dir: &Path, read_file: F) -> Result<HashMap<String, T>>
+where
+    F: Fn(&Path) -> Result<T>,
+{
+    let mut files = HashMap::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().map(|extension| extension != "txt").unwrap_or(true) {
+            continue;
+        }
+        if let Some(name) = path.file_stem().map(|name| name.to_string_lossy().into_owned()) {
+            files.insert(name, read_file(&path)?);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::file_path;
+
+    #[test]
+    fn load_language_resources_works() {
+        // Given
+        let language_dir = file_path("tests/resources/en");
+        let reader = ResourceReader::default();
+
+        // When
+        let resources = load_language_resources(&language_dir, &reader).unwrap();
+
+        // Then
+        assert!(resources.stop_words.contains("the"));
+        assert!(resources.noise_words.is_empty());
+        assert_eq!(Some(&"cup".to_string()), resources.stems.get("cups"));
+        assert_eq!(
+            Some(&"0110".to_string()),
+            resources.word_clusters["brown_clusters"].get("coffee")
+        );
+        assert!(resources.gazetteers["top_10000_words"].contains("coffee"));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn load_language_resources_should_decrypt_encrypted_resources() {
+        // Given
+        let language_dir = file_path("tests/encrypted_resources/en");
+        let reader = ResourceReader::encrypted(&[42; 32]);
+
+        // When
+        let resources = load_language_resources(&language_dir, &reader).unwrap();
+
+        // Then
+        assert_eq!(Some(&"cup".to_string()), resources.stems.get("cups"));
+    }
+}
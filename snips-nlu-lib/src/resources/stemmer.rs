@@ -1,5 +1,8 @@
+use std::sync::Arc;
+
 use resources_packed::stem as resource_stem;
 use errors::*;
+use resources::shared::{get_shared_resources, LanguageResources};
 
 use snips_nlu_ontology::Language;
 
@@ -7,21 +10,42 @@ pub trait Stemmer: Send + Sync {
     fn stem(&self, value: &str) -> String;
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct StaticMapStemmer {
     language: Language,
+    /// Resources loaded with `load_resources`, used instead of the packed resources
+    shared_resources: Option<Arc<LanguageResources>>,
 }
 
 impl StaticMapStemmer {
     pub fn new(language: Language) -> Result<Self> {
-        // Hack to check if stemming is supported in this language
-        resource_stem(language, "")?;
-        Ok(Self { language })
+        let shared_resources = get_shared_resources(language).and_then(|resources| {
+            if resources.stems.is_empty() {
+                None
+            } else {
+                Some(resources)
+            }
+        });
+        if shared_resources.is_none() {
+            // Hack to check if stemming is supported in this language
+            resource_stem(language, "")?;
+        }
+        Ok(Self {
+            language,
+            shared_resources,
+        })
     }
 }
 
 impl Stemmer for StaticMapStemmer {
     fn stem(&self, value: &str) -> String {
+        if let Some(resources) = self.shared_resources.as_ref() {
+            return resources
+                .stems
+                .get(value)
+                .cloned()
+                .unwrap_or_else(|| value.to_string());
+        }
         // checked during initialization
         resource_stem(self.language, value).unwrap()
     }
@@ -1,4 +1,7 @@
+use std::sync::Arc;
+
 use errors::*;
+use resources::shared::{get_shared_resources, LanguageResources};
 use resources_packed::word_cluster;
 use snips_nlu_ontology::Language;
 
@@ -9,21 +12,36 @@ pub trait WordClusterer {
 pub struct StaticMapWordClusterer {
     language: Language,
     cluster_name: String,
+    /// Resources loaded with `load_resources`, used instead of the packed resources
+    shared_resources: Option<Arc<LanguageResources>>,
 }
 
 impl StaticMapWordClusterer {
     pub fn new(language: Language, cluster_name: String) -> Result<Self> {
-        // Hack to check that the word cluster exists
-        word_cluster(&cluster_name, language, "")?;
+        let shared_resources = get_shared_resources(language).and_then(|resources| {
+            if resources.word_clusters.contains_key(&cluster_name) {
+                Some(resources)
+            } else {
+                None
+            }
+        });
+        if shared_resources.is_none() {
+            // Hack to check that the word cluster exists
+            word_cluster(&cluster_name, language, "")?;
+        }
         Ok(Self {
             language,
             cluster_name,
+            shared_resources,
         })
     }
 }
 
 impl WordClusterer for StaticMapWordClusterer {
     fn get_cluster(&self, word: &str) -> Option<String> {
+        if let Some(resources) = self.shared_resources.as_ref() {
+            return resources.word_clusters[&self.cluster_name].get(word).cloned();
+        }
         // Checked during initialization
         word_cluster(&self.cluster_name, self.language, word).unwrap()
     }
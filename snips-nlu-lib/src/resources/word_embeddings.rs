@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use errors::*;
+
+/// Pretrained word vectors, read from a text file where each line contains a word followed by
+/// the components of its vector, separated by spaces
+pub struct WordEmbeddings {
+    dimension: usize,
+    vectors: HashMap<String, Vec<f32>>,
+}
+
+impl WordEmbeddings {
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let mut dimension: Option<usize> = None;
+        let mut vectors = HashMap::new();
+        for line in reader.lines() {
+            let line = line?;
+            let mut fields = line.split_whitespace();
+            let word = if let Some(word) = fields.next() {
+                word.to_string()
+            } else {
+                continue;
+            };
+            let vector = fields
+                .map(|component| {
+                    component
+                        .parse::<f32>()
+                        .chain_err(|| format!("Invalid component in vector of word '{}'", word))
+                })
+                .collect::<Result<Vec<f32>>>()?;
+            if *dimension.get_or_insert(vector.len()) != vector.len() {
+                bail!("Vector of word '{}' has an unexpected dimension", word);
+            }
+            vectors.insert(word, vector);
+        }
+        Ok(Self {
+            dimension: dimension.unwrap_or(0),
+            vectors,
+        })
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Returns the average vector of the known words, or a null vector if no word is known
+    pub fn average_vector(&self, words: &[String]) -> Vec<f32> {
+        let mut average = vec![0.; self.dimension];
+        let known_vectors = words
+            .iter()
+            .filter_map(|word| self.vectors.get(word))
+            .collect::<Vec<_>>();
+        for vector in &known_vectors {
+            for (average_component, component) in average.iter_mut().zip(vector.iter()) {
+                *average_component += *component;
+            }
+        }
+        if !known_vectors.is_empty() {
+            let nb_known_vectors = known_vectors.len() as f32;
+            for average_component in average.iter_mut() {
+                *average_component /= nb_known_vectors;
+            }
+        }
+        average
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutils::assert_epsilon_eq_vec;
+
+    #[test]
+    fn average_vector_works() {
+        // Given
+        let data = "coffee 1.0 2.0 3.0\ntea 3.0 0.0 -1.0\n";
+        let embeddings = WordEmbeddings::from_reader(data.as_bytes()).unwrap();
+        let words = vec!["coffee".to_string(), "or".to_string(), "tea".to_string()];
+
+        // When
+        let average = embeddings.average_vector(&words);
+
+        // Then
+        assert_eq!(3, embeddings.dimension());
+        assert_epsilon_eq_vec(&[2.0, 1.0, 1.0], &average, 1e-6);
+    }
+
+    #[test]
+    fn from_reader_should_fail_with_inconsistent_dimensions() {
+        // Given
+        let data = "coffee 1.0 2.0 3.0\ntea 3.0 0.0\n";
+
+        // When
+        let embeddings = WordEmbeddings::from_reader(data.as_bytes());
+
+        // Then
+        assert!(embeddings.is_err());
+    }
+}
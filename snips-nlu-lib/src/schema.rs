@@ -0,0 +1,603 @@
+//! Versioned JSON format of the parsing results, which is the contract of the bindings returning
+//! JSON results
+//!
+//! The format of version 1 is the following:
+//!
+//! ```json
+//! {
+//!   "resultFormatVersion": 1,
+//!   "input": "make me two cups of coffee",
+//!   "intent": {"intentName": "MakeCoffee", "probability": 0.84},
+//!   "slots": [
+//!     {
+//!       "rawValue": "two",
+//!       "value": {"kind": "Number", "value": 2.0},
+//!       "range": {"start": 8, "end": 11},
+//!       "entity": "snips/number",
+//!       "slotName": "number_of_cups"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! - `intent` is `null` when no intent is found, and `slots` is then `null` as well
+//! - `range` is the char range of the slot in the input
+//! - `value` is tagged with its `kind`: custom slots have a `Custom` kind with a string `value`,
+//! and builtin slots have the kind of their builtin entity, such as `Number`, `InstantTime` or
+//! `Temperature`, along with the fields of the builtin value
+//!
+//! The intents returned by `get_intents` are serialized as
+//! `{"resultFormatVersion": 1, "intents": [{"intentName": ..., "probability": ...}]}`, and the
+//! slots returned by `get_slots` as `{"resultFormatVersion": 1, "slots": [...]}`, with the slots
+//! in the format above.
+//!
+//! Adding fields doesn't change the version, while renaming, removing or changing the type of a
+//! field does.
+//!
+//! `result_to_python_json_string` serializes the results exactly like the Python library does,
+//! without the version field, so that mixed Python and Rust deployments produce identical
+//! payloads.
+
+use std::io;
+
+use serde::Serialize;
+use serde_json::ser::{CharEscape, Formatter, Serializer};
+use serde_json::{self, Map, Value};
+use snips_nlu_ontology::{IntentClassifierResult, IntentParserResult, Slot, SlotValue};
+
+use errors::*;
+
+/// Version of the JSON format of the parsing results
+pub const RESULT_FORMAT_VERSION: u32 = 1;
+
+const RESULT_FORMAT_VERSION_KEY: &str = "resultFormatVersion";
+
+/// Serializes the parsing result in the JSON format of `RESULT_FORMAT_VERSION`
+pub fn result_to_json(result: &IntentParserResult) -> Result<Value> {
+    let mut json_result = serde_json::to_value(result)?;
+    match json_result.as_object_mut() {
+        Some(object) => {
+            object.insert(
+                RESULT_FORMAT_VERSION_KEY.to_string(),
+                Value::from(RESULT_FORMAT_VERSION),
+            );
+        }
+        None => bail!("Parsing result is not serialized as a JSON object"),
+    }
+    Ok(json_result)
+}
+
+/// Serializes the parsing result to a JSON string in the format of `RESULT_FORMAT_VERSION`
+pub fn result_to_json_string(result: &IntentParserResult) -> Result<String> {
+    Ok(serde_json::to_string(&result_to_json(result)?)?)
+}
+
+/// Serializes the intents returned by `get_intents` in the JSON format of `RESULT_FORMAT_VERSION`
+pub fn intents_to_json(intents: &[IntentClassifierResult]) -> Result<Value> {
+    Ok(versioned_list("intents", serde_json::to_value(intents)?))
+}
+
+/// Serializes the slots returned by `get_slots` in the JSON format of `RESULT_FORMAT_VERSION`
+pub fn slots_to_json(slots: &[Slot]) -> Result<Value> {
+    Ok(versioned_list("slots", serde_json::to_value(slots)?))
+}
+
+fn versioned_list(key: &str, list: Value) -> Value {
+    let mut object = Map::new();
+    object.insert(
+        RESULT_FORMAT_VERSION_KEY.to_string(),
+        Value::from(RESULT_FORMAT_VERSION),
+    );
+    object.insert(key.to_string(), list);
+    Value::Object(object)
+}
+
+/// Serializes the parsing result like `json.dumps` does with the results of the Python library:
+/// keys in the order of the Python results, `", "` and `": "` separators, and non-ASCII chars
+/// escaped
+pub fn result_to_python_json_string(result: &IntentParserResult) -> Result<String> {
+    let python_result = PythonResult {
+        input: &result.input,
+        intent: result.intent.as_ref().map(|intent| PythonIntent {
+            intent_name: &intent.intent_name,
+            probability: intent.probability,
+        }),
+        slots: result.slots.as_ref().map(|slots| {
+            slots
+                .iter()
+                .map(|slot| PythonSlot {
+                    range: slot.range.as_ref().map(|range| PythonRange {
+                        start: range.start,
+                        end: range.end,
+                    }),
+                    raw_value: &slot.raw_value,
+                    value: &slot.value,
+                    entity: &slot.entity,
+                    slot_name: &slot.slot_name,
+                })
+                .collect()
+        }),
+    };
+    let mut json_result = vec![];
+    {
+        let mut serializer = Serializer::with_formatter(&mut json_result, PythonFormatter);
+        python_result.serialize(&mut serializer)?;
+    }
+    Ok(String::from_utf8(json_result)?)
+}
+
+#[derive(Serialize)]
+struct PythonResult<'a> {
+    input: &'a str,
+    intent: Option<PythonIntent<'a>>,
+    slots: Option<Vec<PythonSlot<'a>>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PythonIntent<'a> {
+    intent_name: &'a str,
+    probability: f32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PythonSlot<'a> {
+    range: Option<PythonRange>,
+    raw_value: &'a str,
+    value: &'a SlotValue,
+    entity: &'a str,
+    slot_name: &'a str,
+}
+
+#[derive(Serialize)]
+struct PythonRange {
+    start: usize,
+    end: usize,
+}
+
+/// Formatter reproducing the default output of `json.dumps`
+struct PythonFormatter;
+
+impl Formatter for PythonFormatter {
+    fn begin_array_value<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+
+    fn write_f32<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: f32) -> io::Result<()> {
+        writer.write_all(python_float_repr(&format!("{:e}", value)).as_bytes())
+    }
+
+    fn write_f64<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: f64) -> io::Result<()> {
+        writer.write_all(python_float_repr(&format!("{:e}", value)).as_bytes())
+    }
+
+    fn write_string_fragment<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()> {
+        for c in fragment.chars() {
+            if c.is_ascii() {
+                writer.write_all(&[c as u8])?;
+            } else {
+                let mut utf16_units = [0; 2];
+                for unit in c.encode_utf16(&mut utf16_units) {
+                    write!(writer, "\\u{:04x}", unit)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_char_escape<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        char_escape: CharEscape,
+    ) -> io::Result<()> {
+        match char_escape {
+            CharEscape::AsciiControl(byte) => write!(writer, "\\u{:04x}", byte),
+            CharEscape::Quote => writer.write_all(b"\\\""),
+            CharEscape::ReverseSolidus => writer.write_all(b"\\\\"),
+            CharEscape::Solidus => writer.write_all(b"/"),
+            CharEscape::Backspace => writer.write_all(b"\\b"),
+            CharEscape::FormFeed => writer.write_all(b"\\f"),
+            CharEscape::LineFeed => writer.write_all(b"\\n"),
+            CharEscape::CarriageReturn => writer.write_all(b"\\r"),
+            CharEscape::Tab => writer.write_all(b"\\t"),
+        }
+    }
+}
+
+/// Formats a float like `repr` does in Python, from the shortest digits reading back as the same
+/// float, as given in scientific notation by `LowerExp`
+///
+/// The notation is scientific, with a signed exponent of at least two digits, when the exponent
+/// is lower than -4 or at least 16, and positional with at least one fractional digit otherwise.
+/// The floats of 32 bits are formatted from their own shortest digits, so that `0.84f32` gives
+/// `0.84`.
+fn python_float_repr(exponential: &str) -> String {
+    let (sign, exponential) = if exponential.starts_with('-') {
+        ("-", &exponential[1..])
+    } else {
+        ("", exponential)
+    };
+    let mut parts = exponential.splitn(2, 'e');
+    let digits: String = parts
+        .next()
+        .unwrap_or("0")
+        .chars()
+        .filter(|c| *c != '.')
+        .collect();
+    let exponent: i32 = parts.next().and_then(|e| e.parse().ok()).unwrap_or(0);
+    // Position of the decimal point in the digits
+    let point = exponent + 1;
+    let formatted = if point <= -4 || point > 16 {
+        let (first_digit, other_digits) = digits.split_at(1);
+        let exponent_sign = if exponent < 0 { '-' } else { '+' };
+        if other_digits.is_empty() {
+            format!("{}e{}{:02}", first_digit, exponent_sign, exponent.abs())
+        } else {
+            format!(
+                "{}.{}e{}{:02}",
+                first_digit,
+                other_digits,
+                exponent_sign,
+                exponent.abs()
+            )
+        }
+    } else if point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else if point as usize >= digits.len() {
+        format!("{}{}.0", digits, "0".repeat(point as usize - digits.len()))
+    } else {
+        let (integer_digits, fractional_digits) = digits.split_at(point as usize);
+        format!("{}.{}", integer_digits, fractional_digits)
+    };
+    format!("{}{}", sign, formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_ontology::{AmountOfMoneyValue, Grain, InstantTimeValue, NumberValue, Precision,
+                             TimeIntervalValue};
+
+    fn keys(value: &Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|key| &**key)
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn result_to_json_should_have_the_shape_of_version_1() {
+        // Given
+        let result = IntentParserResult {
+            input: "make me two lattes".to_string(),
+            intent: Some(IntentClassifierResult {
+                intent_name: "MakeCoffee".to_string(),
+                probability: 0.5,
+            }),
+            slots: Some(vec![
+                Slot {
+                    raw_value: "two".to_string(),
+                    value: SlotValue::Number(NumberValue { value: 2.0 }),
+                    range: Some(8..11),
+                    entity: "snips/number".to_string(),
+                    slot_name: "number_of_cups".to_string(),
+                },
+                Slot {
+                    raw_value: "lattes".to_string(),
+                    value: SlotValue::Custom("latte".to_string().into()),
+                    range: Some(12..18),
+                    entity: "coffee_type".to_string(),
+                    slot_name: "coffee_type".to_string(),
+                },
+            ]),
+        };
+
+        // When
+        let json_result = result_to_json(&result).unwrap();
+
+        // Then
+        assert_eq!(
+            vec!["input", "intent", "resultFormatVersion", "slots"],
+            keys(&json_result)
+        );
+        assert_eq!(json!(1), json_result["resultFormatVersion"]);
+        assert_eq!(
+            json!({"intentName": "MakeCoffee", "probability": 0.5}),
+            json_result["intent"]
+        );
+        let builtin_slot = &json_result["slots"][0];
+        assert_eq!(
+            vec!["entity", "range", "rawValue", "slotName", "value"],
+            keys(builtin_slot)
+        );
+        assert_eq!(json!({"start": 8, "end": 11}), builtin_slot["range"]);
+        assert_eq!(json!({"kind": "Number", "value": 2.0}), builtin_slot["value"]);
+        assert_eq!(
+            json!({"kind": "Custom", "value": "latte"}),
+            json_result["slots"][1]["value"]
+        );
+    }
+
+    fn builtin_slot(value: SlotValue, entity: &str) -> Slot {
+        Slot {
+            raw_value: "raw".to_string(),
+            value,
+            range: Some(0..3),
+            entity: entity.to_string(),
+            slot_name: "slot".to_string(),
+        }
+    }
+
+    #[test]
+    fn result_to_json_should_encode_builtin_values() {
+        // Given
+        let result = IntentParserResult {
+            input: "raw".to_string(),
+            intent: Some(IntentClassifierResult {
+                intent_name: "BookFlight".to_string(),
+                probability: 0.5,
+            }),
+            slots: Some(vec![
+                builtin_slot(
+                    SlotValue::InstantTime(InstantTimeValue {
+                        value: "2018-03-27 09:00:00 +02:00".to_string(),
+                        grain: Grain::Hour,
+                        precision: Precision::Exact,
+                    }),
+                    "snips/datetime",
+                ),
+                builtin_slot(
+                    SlotValue::TimeInterval(TimeIntervalValue {
+                        from: Some("2018-03-27 09:00:00 +02:00".to_string()),
+                        to: None,
+                    }),
+                    "snips/datetime",
+                ),
+                builtin_slot(
+                    SlotValue::AmountOfMoney(AmountOfMoneyValue {
+                        value: 10.5,
+                        precision: Precision::Approximate,
+                        unit: Some("€".to_string()),
+                    }),
+                    "snips/amountOfMoney",
+                ),
+            ]),
+        };
+
+        // When
+        let json_result = result_to_json(&result).unwrap();
+
+        // Then
+        let expected_values = vec![
+            json!({
+                "kind": "InstantTime",
+                "value": "2018-03-27 09:00:00 +02:00",
+                "grain": "Hour",
+                "precision": "Exact"
+            }),
+            json!({
+                "kind": "TimeInterval",
+                "from": "2018-03-27 09:00:00 +02:00",
+                "to": null
+            }),
+            json!({
+                "kind": "AmountOfMoney",
+                "value": 10.5,
+                "precision": "Approximate",
+                "unit": "€"
+            }),
+        ];
+        let values: Vec<Value> = (0..3)
+            .map(|index| json_result["slots"][index]["value"].clone())
+            .collect();
+        assert_eq!(expected_values, values);
+    }
+
+    #[test]
+    fn intents_and_slots_to_json_should_be_versioned() {
+        // Given
+        let intents = vec![IntentClassifierResult {
+            intent_name: "MakeCoffee".to_string(),
+            probability: 0.5,
+        }];
+        let slots = vec![builtin_slot(
+            SlotValue::Number(NumberValue { value: 2.0 }),
+            "snips/number",
+        )];
+
+        // When
+        let json_intents = intents_to_json(&intents).unwrap();
+        let json_slots = slots_to_json(&slots).unwrap();
+
+        // Then
+        let expected_intents = json!({
+            "resultFormatVersion": 1,
+            "intents": [{"intentName": "MakeCoffee", "probability": 0.5}]
+        });
+        let expected_slots = json!({
+            "resultFormatVersion": 1,
+            "slots": [{
+                "rawValue": "raw",
+                "value": {"kind": "Number", "value": 2.0},
+                "range": {"start": 0, "end": 3},
+                "entity": "snips/number",
+                "slotName": "slot"
+            }]
+        });
+        assert_eq!(expected_intents, json_intents);
+        assert_eq!(expected_slots, json_slots);
+    }
+
+    #[test]
+    fn result_to_json_should_have_null_intent_and_slots_when_no_intent_is_found() {
+        // Given
+        let result = IntentParserResult {
+            input: "hello".to_string(),
+            intent: None,
+            slots: None,
+        };
+
+        // When
+        let json_result = result_to_json(&result).unwrap();
+
+        // Then
+        let expected_result = json!({
+            "resultFormatVersion": 1,
+            "input": "hello",
+            "intent": null,
+            "slots": null
+        });
+        assert_eq!(expected_result, json_result);
+    }
+
+    #[test]
+    fn result_to_python_json_string_should_match_json_dumps() {
+        // Given
+        let result = IntentParserResult {
+            input: "deux cafés \"allongés\"".to_string(),
+            intent: Some(IntentClassifierResult {
+                intent_name: "MakeCoffee".to_string(),
+                probability: 0.5,
+            }),
+            slots: Some(vec![
+                Slot {
+                    raw_value: "deux".to_string(),
+                    value: SlotValue::Number(NumberValue { value: 2.0 }),
+                    range: Some(0..4),
+                    entity: "snips/number".to_string(),
+                    slot_name: "number_of_cups".to_string(),
+                },
+            ]),
+        };
+
+        // When
+        let json_result = result_to_python_json_string(&result).unwrap();
+
+        // Then
+        let expected_result = concat!(
+            r#"{"input": "deux caf\u00e9s \"allong\u00e9s\"", "#,
+            r#""intent": {"intentName": "MakeCoffee", "probability": 0.5}, "#,
+            r#""slots": [{"range": {"start": 0, "end": 4}, "rawValue": "deux", "#,
+            r#""value": {"kind": "Number", "value": 2.0}, "#,
+            r#""entity": "snips/number", "slotName": "number_of_cups"}]}"#
+        );
+        assert_eq!(expected_result, json_result);
+    }
+
+    #[test]
+    fn result_to_python_json_string_should_order_builtin_values_like_json_dumps() {
+        // Given
+        let result = IntentParserResult {
+            input: "raw".to_string(),
+            intent: None,
+            slots: Some(vec![
+                builtin_slot(
+                    SlotValue::InstantTime(InstantTimeValue {
+                        value: "2018-03-27 09:00:00 +02:00".to_string(),
+                        grain: Grain::Hour,
+                        precision: Precision::Exact,
+                    }),
+                    "snips/datetime",
+                ),
+                builtin_slot(
+                    SlotValue::TimeInterval(TimeIntervalValue {
+                        from: Some("2018-03-27 09:00:00 +02:00".to_string()),
+                        to: None,
+                    }),
+                    "snips/datetime",
+                ),
+                builtin_slot(
+                    SlotValue::AmountOfMoney(AmountOfMoneyValue {
+                        value: 10.5,
+                        precision: Precision::Approximate,
+                        unit: Some("€".to_string()),
+                    }),
+                    "snips/amountOfMoney",
+                ),
+            ]),
+        };
+
+        // When
+        let json_result = result_to_python_json_string(&result).unwrap();
+
+        // Then
+        let expected_values = vec![
+            concat!(
+                r#""value": {"kind": "InstantTime", "value": "2018-03-27 09:00:00 +02:00", "#,
+                r#""grain": "Hour", "precision": "Exact"}"#
+            ),
+            concat!(
+                r#""value": {"kind": "TimeInterval", "from": "2018-03-27 09:00:00 +02:00", "#,
+                r#""to": null}"#
+            ),
+            concat!(
+                r#""value": {"kind": "AmountOfMoney", "value": 10.5, "#,
+                r#""precision": "Approximate", "unit": "\u20ac"}"#
+            ),
+        ];
+        for expected_value in expected_values {
+            assert!(json_result.contains(expected_value), "{}", json_result);
+        }
+    }
+
+    #[test]
+    fn python_float_repr_works() {
+        // Given
+        let floats = vec![2.0, 0.5, -0.25, 0.0001, 0.00001, 1e15, 1e16, 1.5e300, 0.84];
+
+        // When
+        let reprs: Vec<String> = floats
+            .into_iter()
+            .map(|value: f64| python_float_repr(&format!("{:e}", value)))
+            .collect();
+        let f32_repr = python_float_repr(&format!("{:e}", 0.84f32));
+
+        // Then
+        let expected_reprs = vec![
+            "2.0",
+            "0.5",
+            "-0.25",
+            "0.0001",
+            "1e-05",
+            "1000000000000000.0",
+            "1e+16",
+            "1.5e+300",
+            "0.84",
+        ];
+        assert_eq!(expected_reprs, reprs);
+        assert_eq!("0.84", f32_repr);
+    }
+}
@@ -2,31 +2,41 @@ use std::sync;
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
 use std::ops::Range;
-use std::str::FromStr;
 
-use crfsuite::Tagger as CRFSuiteTagger;
 use itertools::Itertools;
 
+use builtin_entity_parser::MultilingualBuiltinEntityParser;
 use errors::*;
-use configurations::SlotFillerConfiguration;
-use language::FromLanguage;
-use nlu_utils::language::Language as NluUtilsLanguage;
+use configurations::{BeamSearchConfiguration, BuiltinSlotsDisambiguation, OverlappingSlotsPolicy,
+                     SlotConstraint, SlotFillerConfiguration, WeightsQuantization};
+use intent_parser::TokenFeatures;
+use lazy_section::LazySection;
+use memory_footprint::HeapSize;
+use parse_context::{AmbiguousBuiltinEntity, ParseContext};
+use resource_reader::ResourceReader;
 use nlu_utils::range::ranges_overlap;
-use nlu_utils::token::{tokenize, Token};
+use nlu_utils::token::Token;
+use slot_filler::crf_tagger::Tagger;
 use slot_filler::crf_utils::*;
 use slot_filler::SlotFiller;
 use slot_filler::feature_processor::ProbabilisticFeatureProcessor;
+use slot_filler::feature_registry::FeatureExtractorRegistry;
 use slot_utils::*;
-use snips_nlu_ontology::{BuiltinEntity, BuiltinEntityKind, BuiltinEntityParser, Language, Slot};
+use symbols::Symbol;
+use snips_nlu_ontology::{BuiltinEntity, BuiltinEntityKind, Slot};
+use tokenizer::Tokenizer;
 
 pub struct CRFSlotFiller {
-    language: Language,
+    tokenizer: sync::Arc<Tokenizer>,
     tagging_scheme: TaggingScheme,
-    tagger: sync::Mutex<CRFSuiteTagger>,
+    tagger: LazySection<Tagger>,
     feature_processor: ProbabilisticFeatureProcessor,
-    slot_name_mapping: HashMap<String, String>,
-    builtin_entity_parser: Option<sync::Arc<BuiltinEntityParser>>,
+    slot_name_mapping: HashMap<Symbol, Symbol>,
+    builtin_entity_parser: Option<sync::Arc<MultilingualBuiltinEntityParser>>,
     exhaustive_permutations_threshold: usize,
+    overlapping_slots_policy: OverlappingSlotsPolicy,
+    builtin_slots_disambiguation: BuiltinSlotsDisambiguation,
+    beam_search: Option<BeamSearchConfiguration>,
 }
 
 impl SlotFiller for CRFSlotFiller {
@@ -34,18 +44,22 @@ impl SlotFiller for CRFSlotFiller {
         self.tagging_scheme
     }
 
-    fn get_slots(&self, text: &str) -> Result<Vec<Slot>> {
-        let tokens = tokenize(text, NluUtilsLanguage::from_language(self.language));
+    fn get_slots(&self, text: &str, context: &ParseContext) -> Result<Vec<Slot>> {
+        let tokens = self.tokenizer.tokenize(text);
         if tokens.is_empty() {
             return Ok(vec![]);
         }
-        let features = self.feature_processor.compute_features(&&*tokens);
-        let tags = self.tagger
-            .lock()?
-            .tag(&features)?
-            .into_iter()
-            .map(|tag| decode_tag(&*tag))
-            .collect::<Result<Vec<String>>>()?;
+        let features = self.feature_processor.compute_features(&&*tokens, context);
+        let tags = if let Some(beam_search) = self.beam_search.as_ref() {
+            self.tag_with_beam_search(&features, beam_search)?
+        } else {
+            self.tagger
+                .get()?
+                .tag(&features)?
+                .into_iter()
+                .map(|tag| decode_tag(&*tag))
+                .collect::<Result<Vec<String>>>()?
+        };
 
         let builtin_slot_names_iter = self.slot_name_mapping.iter().filter_map(
             |(slot_name, entity)| {
@@ -81,7 +95,7 @@ impl SlotFiller for CRFSlotFiller {
             .filter_map(|(slot_name, entity)| {
                 BuiltinEntityKind::from_identifier(entity)
                     .ok()
-                    .map(|kind| (slot_name.clone(), kind))
+                    .map(|kind| (slot_name.to_string(), kind))
             })
             .collect_vec();
 
@@ -91,9 +105,23 @@ impl SlotFiller for CRFSlotFiller {
             .unique()
             .collect_vec();
 
-        if let Some(builtin_entity_parser) = self.builtin_entity_parser.as_ref() {
-            let builtin_entities =
-                builtin_entity_parser.extract_entities(text, Some(&builtin_entity_kinds));
+        let builtin_entity_parser =
+            context.builtin_entity_parser(self.builtin_entity_parser.as_ref());
+        if let Some(builtin_entity_parser) = builtin_entity_parser {
+            let builtin_entities = context.extract_builtin_entities(
+                builtin_entity_parser,
+                text,
+                Some(&builtin_entity_kinds),
+            );
+            let (builtin_entities, updated_tags) = resolve_overlapping_slots(
+                self.overlapping_slots_policy,
+                builtin_entities,
+                &tokens,
+                updated_tags,
+                self,
+                &builtin_slots,
+                context,
+            )?;
             let augmented_slots = augment_slots(
                 text,
                 &tokens,
@@ -103,11 +131,14 @@ impl SlotFiller for CRFSlotFiller {
                 builtin_entities,
                 &builtin_slots,
                 self.exhaustive_permutations_threshold,
+                &self.builtin_slots_disambiguation,
+                self.slot_constraints(),
+                context,
             )?;
             Ok(resolve_builtin_slots(
                 text,
                 augmented_slots,
-                &*builtin_entity_parser,
+                builtin_entity_parser,
                 Some(&builtin_entity_kinds),
             ))
         } else {
@@ -118,12 +149,17 @@ impl SlotFiller for CRFSlotFiller {
         }
     }
 
-    fn get_sequence_probability(&self, tokens: &[Token], tags: Vec<String>) -> Result<f64> {
-        let features = self.feature_processor.compute_features(&tokens);
-        let tagger = self.tagger.lock()?;
+    fn get_sequence_probability(
+        &self,
+        tokens: &[Token],
+        tags: Vec<String>,
+        context: &ParseContext,
+    ) -> Result<f64> {
+        let features = self.feature_processor.compute_features(&tokens, context);
+        let tagger = self.tagger.get()?;
         let tagger_labels = tagger
-            .labels()?
-            .into_iter()
+            .labels()
+            .iter()
             .map(|label| decode_tag(&*label))
             .collect::<Result<Vec<String>>>()?;
         let tagger_labels_slice = tagger_labels.iter().map(|l| &**l).collect_vec();
@@ -138,32 +174,133 @@ impl SlotFiller for CRFSlotFiller {
             })
             .map(|t| encode_tag(&*t))
             .collect_vec();
-        tagger.set(&features)?;
-        Ok(tagger.probability(cleaned_tags)?)
+        tagger.probability(&features, &cleaned_tags)
+    }
+
+    fn get_features(&self, text: &str, context: &ParseContext) -> Result<Vec<TokenFeatures>> {
+        let tokens = self.tokenizer.tokenize(text);
+        let features = self.feature_processor.compute_features(&&*tokens, context);
+        Ok(tokens
+            .into_iter()
+            .zip(features.into_iter())
+            .map(|(token, features)| TokenFeatures {
+                token: token.value,
+                features,
+            })
+            .collect())
+    }
+
+    fn heap_size(&self) -> usize {
+        let tagger_size = if self.tagger.is_loaded() {
+            self.tagger.get().map(|tagger| tagger.heap_size()).unwrap_or(0)
+        } else {
+            0
+        };
+        tagger_size + self.slot_name_mapping.heap_size()
     }
 }
 
 impl CRFSlotFiller {
-    pub fn new(config: SlotFillerConfiguration) -> Result<CRFSlotFiller> {
-        let tagging_scheme = TaggingScheme::from_u8(config.config.tagging_scheme)?;
-        let slot_name_mapping = config.slot_name_mapping;
-        let feature_processor =
-            ProbabilisticFeatureProcessor::new(&config.config.feature_factory_configs)?;
-        let converted_data = ::base64::decode(&config.crf_model_data)?;
-        let tagger = CRFSuiteTagger::create_from_memory(converted_data)?;
-        let language = Language::from_str(&config.language_code)?;
-        let builtin_entity_parser = Some(BuiltinEntityParser::get(language));
+    pub fn new(
+        config: SlotFillerConfiguration,
+        builtin_entity_parser: Option<sync::Arc<MultilingualBuiltinEntityParser>>,
+        feature_registry: &FeatureExtractorRegistry,
+        weights_quantization: Option<WeightsQuantization>,
+        tokenizer: sync::Arc<Tokenizer>,
+        resource_reader: &ResourceReader,
+    ) -> Result<CRFSlotFiller> {
+        let slot_filler_config = config.config.ok_or_else(|| {
+            format!("Missing configuration of the slot filler of intent {}", config.intent)
+        })?;
+        let tagging_scheme = TaggingScheme::from_configuration(&slot_filler_config.tagging_scheme)?;
+        let slot_name_mapping = config
+            .slot_name_mapping
+            .into_iter()
+            .map(|(slot_name, entity)| (slot_name.into(), entity.into()))
+            .collect();
+        let feature_processor = ProbabilisticFeatureProcessor::new(
+            &slot_filler_config.feature_factory_configs,
+            feature_registry,
+        )?;
+        let tagger = if let Some(crf_model_file) = config.crf_model_file {
+            LazySection::mapped(crf_model_file, resource_reader, move |data| {
+                load_tagger(data, tagging_scheme, weights_quantization)
+            })?
+        } else {
+            let converted_data = ::base64::decode(&config.crf_model_data)?;
+            let tagger = load_tagger(&converted_data, tagging_scheme, weights_quantization)?;
+            LazySection::loaded(tagger)
+        };
 
         Ok(Self {
-            language,
+            tokenizer,
             tagging_scheme,
-            tagger: sync::Mutex::new(tagger),
+            tagger,
             feature_processor,
             slot_name_mapping,
             builtin_entity_parser,
-            exhaustive_permutations_threshold: config.config.exhaustive_permutations_threshold,
+            exhaustive_permutations_threshold: slot_filler_config.exhaustive_permutations_threshold,
+            overlapping_slots_policy: slot_filler_config.overlapping_slots_policy,
+            builtin_slots_disambiguation: slot_filler_config.builtin_slots_disambiguation,
+            beam_search: config.beam_search,
         })
     }
+
+    fn slot_constraints(&self) -> &[SlotConstraint] {
+        self.beam_search
+            .as_ref()
+            .map(|beam_search| &*beam_search.constraints)
+            .unwrap_or(&[])
+    }
+
+    /// Decodes the tags with a beam search which discards the sequences violating the slot
+    /// constraints, no slot being extracted when no valid sequence is found
+    fn tag_with_beam_search(
+        &self,
+        features: &[Vec<(String, String)>],
+        beam_search: &BeamSearchConfiguration,
+    ) -> Result<Vec<String>> {
+        let tagger = self.tagger.get()?;
+        let labels = tagger
+            .labels()
+            .iter()
+            .map(|label| decode_tag(&*label))
+            .collect::<Result<Vec<String>>>()?;
+        let is_valid = |path: &[usize], is_complete: bool| {
+            let tags = path.iter().map(|label| labels[*label].clone()).collect_vec();
+            satisfies_slot_constraints(
+                &tags,
+                self.tagging_scheme,
+                &beam_search.constraints,
+                is_complete,
+            )
+        };
+        let tags = tagger.tag_with_beam_search(features, beam_search.beam_size, is_valid)?;
+        if let Some(tags) = tags {
+            tags.into_iter().map(|tag| decode_tag(&*tag)).collect()
+        } else {
+            Ok(vec![OUTSIDE.to_string(); features.len()])
+        }
+    }
+}
+
+fn load_tagger(
+    data: &[u8],
+    tagging_scheme: TaggingScheme,
+    weights_quantization: Option<WeightsQuantization>,
+) -> Result<Tagger> {
+    let tagger = Tagger::create_from_memory(data)?;
+    let decoded_labels = tagger
+        .labels()
+        .iter()
+        .map(|label| decode_tag(&*label))
+        .collect::<Result<Vec<String>>>()?;
+    tagging_scheme.check_labels(&decoded_labels)?;
+    if let Some(quantization) = weights_quantization {
+        Ok(tagger.compress(quantization))
+    } else {
+        Ok(tagger)
+    }
 }
 
 // We need to use base64 encoding to ensure ascii encoding because of encoding issues in
@@ -174,7 +311,7 @@ fn decode_tag(tag: &str) -> Result<String> {
     Ok(String::from_utf8(bytes)?)
 }
 
-fn encode_tag(tag: &str) -> String {
+pub(crate) fn encode_tag(tag: &str) -> String {
     ::base64::encode(tag)
 }
 
@@ -195,23 +332,116 @@ fn filter_overlapping_builtins(
         .collect()
 }
 
+/// Resolves the overlaps between the custom slots tagged by the CRF and the builtin entities
+/// according to the policy, and returns the builtin entities which are kept along with the tags
+/// in which the custom slots overlapping them are replaced by `O`
+fn resolve_overlapping_slots(
+    policy: OverlappingSlotsPolicy,
+    builtin_entities: Vec<BuiltinEntity>,
+    tokens: &[Token],
+    tags: Vec<String>,
+    slot_filler: &SlotFiller,
+    builtin_slots: &[(String, BuiltinEntityKind)],
+    context: &ParseContext,
+) -> Result<(Vec<BuiltinEntity>, Vec<String>)> {
+    let tagging_scheme = slot_filler.get_tagging_scheme();
+    if policy == OverlappingSlotsPolicy::PreferCustom {
+        let builtin_entities =
+            filter_overlapping_builtins(builtin_entities, tokens, &tags, tagging_scheme);
+        return Ok((builtin_entities, tags));
+    }
+
+    let mut tags = tags;
+    let mut kept_entities = vec![];
+    for entity in builtin_entities {
+        let overlapping_slots = tags_to_slot_ranges(tokens, &tags, tagging_scheme)
+            .into_iter()
+            .filter(|slot| ranges_overlap(&entity.range, &slot.char_range))
+            .collect_vec();
+        if overlapping_slots.is_empty() {
+            kept_entities.push(entity);
+            continue;
+        }
+        let mut builtin_tags = tags.clone();
+        for slot in &overlapping_slots {
+            for index in slot.range.clone() {
+                builtin_tags[index] = OUTSIDE.to_string();
+            }
+        }
+        let keep_builtin = match policy {
+            OverlappingSlotsPolicy::PreferCustom => false,
+            OverlappingSlotsPolicy::LongestSpan => overlapping_slots
+                .iter()
+                .all(|slot| entity.range.len() > slot.char_range.len()),
+            OverlappingSlotsPolicy::HighestConfidence => {
+                let custom_probability =
+                    slot_filler.get_sequence_probability(tokens, tags.clone(), context)?;
+                let builtin_probability = get_builtin_probability(
+                    &entity,
+                    tokens,
+                    &builtin_tags,
+                    slot_filler,
+                    builtin_slots,
+                    context,
+                )?;
+                builtin_probability > custom_probability
+            }
+        };
+        if keep_builtin {
+            tags = builtin_tags;
+            kept_entities.push(entity);
+        }
+    }
+    Ok((kept_entities, tags))
+}
+
+/// Returns the highest probability of the tags when the builtin entity is tagged as one of the
+/// slots of its kind
+fn get_builtin_probability(
+    entity: &BuiltinEntity,
+    tokens: &[Token],
+    tags: &[String],
+    slot_filler: &SlotFiller,
+    builtin_slots: &[(String, BuiltinEntityKind)],
+    context: &ParseContext,
+) -> Result<f64> {
+    let tokens_indexes = spans_to_tokens_indexes(&[entity.range.clone()], tokens);
+    let indexes = &tokens_indexes[0];
+    let mut best_probability: f64 = -1.0;
+    for &(ref slot_name, kind) in builtin_slots {
+        if kind != entity.entity_kind {
+            continue;
+        }
+        let sub_tags_sequence =
+            positive_tagging(slot_filler.get_tagging_scheme(), slot_name, indexes.len());
+        let mut updated_tags = tags.to_vec();
+        for (index_position, index) in indexes.iter().enumerate() {
+            updated_tags[*index] = sub_tags_sequence[index_position].clone();
+        }
+        let probability = slot_filler.get_sequence_probability(tokens, updated_tags, context)?;
+        if probability > best_probability {
+            best_probability = probability;
+        }
+    }
+    Ok(best_probability)
+}
+
 fn augment_slots(
     text: &str,
     tokens: &[Token],
     tags: &[String],
     slot_filler: &SlotFiller,
-    intent_slots_mapping: &HashMap<String, String>,
+    intent_slots_mapping: &HashMap<Symbol, Symbol>,
     builtin_entities: Vec<BuiltinEntity>,
     missing_slots: &[(String, BuiltinEntityKind)],
     exhaustive_permutations_threshold: usize,
+    disambiguation: &BuiltinSlotsDisambiguation,
+    constraints: &[SlotConstraint],
+    context: &ParseContext,
 ) -> Result<Vec<InternalSlot>> {
+    let tagging_scheme = slot_filler.get_tagging_scheme();
     let mut grouped_entities: HashMap<BuiltinEntityKind, Vec<BuiltinEntity>> = HashMap::new();
-    for entity in filter_overlapping_builtins(
-        builtin_entities,
-        tokens,
-        tags,
-        slot_filler.get_tagging_scheme(),
-    ) {
+    for entity in builtin_entities {
         grouped_entities
             .entry(entity.entity_kind)
             .or_insert_with(|| vec![])
@@ -220,7 +450,7 @@ fn augment_slots(
 
     let mut augmented_tags: Vec<String> = tags.iter().map(|s| s.to_string()).collect();
     for (entity_kind, group) in grouped_entities {
-        let spans_ranges = group.into_iter().map(|e| e.range).collect_vec();
+        let spans_ranges = group.iter().map(|e| e.range.clone()).collect_vec();
         let num_detected_builtins = spans_ranges.len();
         let tokens_indexes = spans_to_tokens_indexes(&spans_ranges, tokens);
         let related_slots: Vec<&str> = missing_slots
@@ -235,6 +465,40 @@ fn augment_slots(
             })
             .collect_vec();
 
+        if related_slots.len() > 1 {
+            match *disambiguation {
+                BuiltinSlotsDisambiguation::MostProbable => (),
+                BuiltinSlotsDisambiguation::Positional { ref slot_names } => {
+                    let mut ordered_slots = related_slots.clone();
+                    ordered_slots.sort_by_key(|slot| {
+                        slot_names
+                            .iter()
+                            .position(|name| name == slot)
+                            .unwrap_or(slot_names.len())
+                    });
+                    let mut ordered_entities = (0..num_detected_builtins).collect_vec();
+                    ordered_entities.sort_by_key(|index| spans_ranges[*index].start);
+                    for (entity_index, slot) in ordered_entities.into_iter().zip(ordered_slots) {
+                        let indexes = &tokens_indexes[entity_index];
+                        let sub_tags = positive_tagging(tagging_scheme, slot, indexes.len());
+                        for (index_position, index) in indexes.iter().enumerate() {
+                            augmented_tags[*index] = sub_tags[index_position].clone();
+                        }
+                    }
+                    continue;
+                }
+                BuiltinSlotsDisambiguation::Ambiguous => {
+                    for entity in group {
+                        context.add_ambiguous_builtin_entity(AmbiguousBuiltinEntity {
+                            entity,
+                            slot_names: related_slots.iter().map(|slot| slot.to_string()).collect(),
+                        });
+                    }
+                    continue;
+                }
+            }
+        }
+
         let slots_permutations = generate_slots_permutations(
             num_detected_builtins,
             related_slots.as_slice(),
@@ -246,13 +510,16 @@ fn augment_slots(
             let mut updated_tags = augmented_tags.clone();
             for (slot_index, slot) in slots.iter().enumerate() {
                 let indexes = &tokens_indexes[slot_index];
-                let tagging_scheme = slot_filler.get_tagging_scheme();
                 let sub_tags_sequence = positive_tagging(tagging_scheme, slot, indexes.len());
                 for (index_position, index) in indexes.iter().enumerate() {
                     updated_tags[*index] = sub_tags_sequence[index_position].clone();
                 }
             }
-            let score = slot_filler.get_sequence_probability(tokens, updated_tags.clone())?;
+            if !satisfies_slot_constraints(&updated_tags, tagging_scheme, constraints, false) {
+                continue;
+            }
+            let score =
+                slot_filler.get_sequence_probability(tokens, updated_tags.clone(), context)?;
             if score > best_permutation_score {
                 best_updated_tags = updated_tags;
                 best_permutation_score = score;
@@ -261,11 +528,19 @@ fn augment_slots(
         augmented_tags = best_updated_tags;
     }
 
+    // The builtin slots are only kept when the final slots satisfy the constraints, which the
+    // decoding only enforced on the custom slots
+    let final_tags =
+        if satisfies_slot_constraints(&augmented_tags, tagging_scheme, constraints, true) {
+            &augmented_tags[..]
+        } else {
+            tags
+        };
     Ok(tags_to_slots(
         text,
         tokens,
-        &augmented_tags,
-        slot_filler.get_tagging_scheme(),
+        final_tags,
+        tagging_scheme,
         intent_slots_mapping,
     )?)
 }
@@ -293,7 +568,8 @@ fn spans_to_tokens_indexes(spans: &[Range<usize>], tokens: &[Token]) -> Vec<Vec<
 mod tests {
     use super::*;
     use nlu_utils::language::Language;
-    use snips_nlu_ontology::{Grain, InstantTimeValue, Precision, SlotValue};
+    use nlu_utils::token::tokenize;
+    use snips_nlu_ontology::{Grain, InstantTimeValue, NumberValue, Precision, SlotValue};
 
     struct TestSlotFiller {
         tags1: Vec<String>,
@@ -306,11 +582,16 @@ mod tests {
     }
 
     impl SlotFiller for TestSlotFiller {
-        fn get_slots(&self, _text: &str) -> Result<Vec<Slot>> {
+        fn get_slots(&self, _text: &str, _: &ParseContext) -> Result<Vec<Slot>> {
             Ok(vec![])
         }
 
-        fn get_sequence_probability(&self, _: &[Token], tags: Vec<String>) -> Result<f64> {
+        fn get_sequence_probability(
+            &self,
+            _: &[Token],
+            tags: Vec<String>,
+            _: &ParseContext,
+        ) -> Result<f64> {
             if tags == self.tags1 {
                 Ok(0.9)
             } else if tags == self.tags2 {
@@ -333,6 +614,10 @@ mod tests {
         fn get_tagging_scheme(&self) -> TaggingScheme {
             TaggingScheme::BIO
         }
+
+        fn get_features(&self, _text: &str, _: &ParseContext) -> Result<Vec<TokenFeatures>> {
+            Ok(vec![])
+        }
     }
 
     #[test]
@@ -401,6 +686,193 @@ mod tests {
         assert_eq!(filtered_entities, expected_entities)
     }
 
+    struct ProbabilitiesSlotFiller {
+        probabilities: Vec<(Vec<&'static str>, f64)>,
+    }
+
+    impl SlotFiller for ProbabilitiesSlotFiller {
+        fn get_slots(&self, _text: &str, _: &ParseContext) -> Result<Vec<Slot>> {
+            Ok(vec![])
+        }
+
+        fn get_sequence_probability(
+            &self,
+            _: &[Token],
+            tags: Vec<String>,
+            _: &ParseContext,
+        ) -> Result<f64> {
+            self.probabilities
+                .iter()
+                .find(|&&(ref probability_tags, _)| *probability_tags == tags)
+                .map(|&(_, probability)| probability)
+                .ok_or_else(|| format!("Unexpected tags: {:?}", tags).into())
+        }
+
+        fn get_tagging_scheme(&self) -> TaggingScheme {
+            TaggingScheme::BIO
+        }
+
+        fn get_features(&self, _text: &str, _: &ParseContext) -> Result<Vec<TokenFeatures>> {
+            Ok(vec![])
+        }
+    }
+
+    /// Input along with the builtin entities found in it and the builtin slots of its intent,
+    /// shared by the tests of the resolution of the slots overlapping builtin entities and of the
+    /// augmentation of the slots with builtin entities
+    struct BuiltinSlotsFixture {
+        text: &'static str,
+        tags: Vec<String>,
+        builtin_entities: Vec<BuiltinEntity>,
+        builtin_slots: Vec<(String, BuiltinEntityKind)>,
+        intent_slots_mapping: HashMap<Symbol, Symbol>,
+    }
+
+    impl BuiltinSlotsFixture {
+        /// A number overlapping a custom slot
+        fn temperature() -> Self {
+            Self {
+                text: "Set the temperature to twenty one degrees",
+                tags: vec!["O", "O", "O", "O", "O", "B-mood", "O"]
+                    .into_iter()
+                    .map(|tag| tag.to_string())
+                    .collect(),
+                builtin_entities: vec![
+                    BuiltinEntity {
+                        value: "twenty one".to_string(),
+                        range: 23..33,
+                        entity_kind: BuiltinEntityKind::Number,
+                        entity: SlotValue::Number(NumberValue { value: 21.0 }),
+                    },
+                ],
+                builtin_slots: vec![("temperature".to_string(), BuiltinEntityKind::Number)],
+                intent_slots_mapping: hashmap! {
+                    Symbol::from("mood") => Symbol::from("mood"),
+                    Symbol::from("temperature") => Symbol::from("snips/number"),
+                },
+            }
+        }
+
+        /// Two dates which can each fill the two date slots
+        fn dates() -> Self {
+            let time_entity = |value: &str, range: Range<usize>| BuiltinEntity {
+                value: value.to_string(),
+                range,
+                entity_kind: BuiltinEntityKind::Time,
+                entity: SlotValue::InstantTime(InstantTimeValue {
+                    value: value.to_string(),
+                    grain: Grain::Day,
+                    precision: Precision::Exact,
+                }),
+            };
+            Self {
+                text: "from today to tomorrow",
+                tags: vec![OUTSIDE.to_string(); 4],
+                builtin_entities: vec![
+                    time_entity("tomorrow", 14..22),
+                    time_entity("today", 5..10),
+                ],
+                builtin_slots: vec![
+                    ("end_date".to_string(), BuiltinEntityKind::Time),
+                    ("start_date".to_string(), BuiltinEntityKind::Time),
+                ],
+                intent_slots_mapping: hashmap! {
+                    Symbol::from("start_date") => Symbol::from("snips/datetime"),
+                    Symbol::from("end_date") => Symbol::from("snips/datetime"),
+                },
+            }
+        }
+
+        fn resolve_overlapping_slots(
+            self,
+            policy: OverlappingSlotsPolicy,
+            probabilities: Vec<(Vec<&'static str>, f64)>,
+        ) -> (Vec<BuiltinEntity>, Vec<String>) {
+            let tokens = tokenize(self.text, Language::EN);
+            resolve_overlapping_slots(
+                policy,
+                self.builtin_entities,
+                &*tokens,
+                self.tags,
+                &ProbabilitiesSlotFiller { probabilities },
+                &self.builtin_slots,
+                &ParseContext::default(),
+            ).unwrap()
+        }
+
+        fn augment_slots(
+            self,
+            disambiguation: BuiltinSlotsDisambiguation,
+            constraints: &[SlotConstraint],
+            context: &ParseContext,
+        ) -> Vec<InternalSlot> {
+            let tokens = tokenize(self.text, Language::EN);
+            augment_slots(
+                self.text,
+                &*tokens,
+                &self.tags,
+                &ProbabilitiesSlotFiller { probabilities: vec![] },
+                &self.intent_slots_mapping,
+                self.builtin_entities,
+                &self.builtin_slots,
+                1,
+                &disambiguation,
+                constraints,
+                context,
+            ).unwrap()
+        }
+    }
+
+    #[test]
+    fn resolve_overlapping_slots_should_prefer_custom_slots() {
+        // When
+        let (builtin_entities, tags) =
+            BuiltinSlotsFixture::temperature()
+            .resolve_overlapping_slots(OverlappingSlotsPolicy::PreferCustom, vec![]);
+
+        // Then
+        assert!(builtin_entities.is_empty());
+        assert_eq!(vec!["O", "O", "O", "O", "O", "B-mood", "O"], tags);
+    }
+
+    #[test]
+    fn resolve_overlapping_slots_should_keep_longest_span() {
+        // When
+        let (builtin_entities, tags) =
+            BuiltinSlotsFixture::temperature()
+            .resolve_overlapping_slots(OverlappingSlotsPolicy::LongestSpan, vec![]);
+
+        // Then
+        assert_eq!(1, builtin_entities.len());
+        assert_eq!(23..33, builtin_entities[0].range);
+        assert_eq!(vec!["O", "O", "O", "O", "O", "O", "O"], tags);
+    }
+
+    #[test]
+    fn resolve_overlapping_slots_should_keep_most_probable_slots() {
+        // Given
+        let custom_tags = vec!["O", "O", "O", "O", "O", "B-mood", "O"];
+        let builtin_tags = vec!["O", "O", "O", "O", "B-temperature", "I-temperature", "O"];
+
+        // When
+        let (builtin_entities, tags) = BuiltinSlotsFixture::temperature()
+            .resolve_overlapping_slots(
+                OverlappingSlotsPolicy::HighestConfidence,
+                vec![(custom_tags.clone(), 0.3), (builtin_tags.clone(), 0.6)],
+            );
+        let (other_builtin_entities, other_tags) = BuiltinSlotsFixture::temperature()
+            .resolve_overlapping_slots(
+                OverlappingSlotsPolicy::HighestConfidence,
+                vec![(custom_tags.clone(), 0.3), (builtin_tags, 0.2)],
+            );
+
+        // Then
+        assert_eq!(1, builtin_entities.len());
+        assert_eq!(vec!["O", "O", "O", "O", "O", "O", "O"], tags);
+        assert!(other_builtin_entities.is_empty());
+        assert_eq!(custom_tags, other_tags);
+    }
+
     #[test]
     fn augment_slots_works() {
         // Given
@@ -548,9 +1020,9 @@ mod tests {
             tags7,
         };
         let intent_slots_mapping = hashmap! {
-            "location".to_string() => "location_entity".to_string(),
-            "start_date".to_string() => "snips/datetime".to_string(),
-            "end_date".to_string() => "snips/datetime".to_string(),
+            Symbol::from("location") => Symbol::from("location_entity"),
+            Symbol::from("start_date") => Symbol::from("snips/datetime"),
+            Symbol::from("end_date") => Symbol::from("snips/datetime"),
         };
         let start_time = InstantTimeValue {
             value: "today at 9pm".to_string(),
@@ -591,6 +1063,9 @@ mod tests {
             builtin_entities,
             &missing_slots,
             exhaustive_permutations_threshold,
+            &BuiltinSlotsDisambiguation::MostProbable,
+            &[],
+            &ParseContext::default(),
         ).unwrap();
 
         // Then
@@ -617,6 +1092,85 @@ mod tests {
         assert_eq!(expected_slots, augmented_slots);
     }
 
+    #[test]
+    fn augment_slots_should_assign_slots_by_position() {
+        // Given
+        let disambiguation = BuiltinSlotsDisambiguation::Positional {
+            slot_names: vec!["start_date".to_string(), "end_date".to_string()],
+        };
+
+        // When
+        let augmented_slots = BuiltinSlotsFixture::dates().augment_slots(
+            disambiguation,
+            &[],
+            &ParseContext::default(),
+        );
+
+        // Then
+        let expected_slots = vec![
+            InternalSlot {
+                value: "today".to_string(),
+                char_range: 5..10,
+                entity: "snips/datetime".to_string(),
+                slot_name: "start_date".to_string(),
+            },
+            InternalSlot {
+                value: "tomorrow".to_string(),
+                char_range: 14..22,
+                entity: "snips/datetime".to_string(),
+                slot_name: "end_date".to_string(),
+            },
+        ];
+        assert_eq!(expected_slots, augmented_slots);
+    }
+
+    #[test]
+    fn augment_slots_should_leave_ambiguous_entities_out_of_slots() {
+        // Given
+        let fixture = BuiltinSlotsFixture::dates();
+        let builtin_entities = fixture.builtin_entities.clone();
+        let context = ParseContext::default();
+
+        // When
+        let augmented_slots =
+            fixture.augment_slots(BuiltinSlotsDisambiguation::Ambiguous, &[], &context);
+
+        // Then
+        assert!(augmented_slots.is_empty());
+        let slot_names = vec!["end_date".to_string(), "start_date".to_string()];
+        let expected_ambiguous_entities = builtin_entities
+            .into_iter()
+            .map(|entity| AmbiguousBuiltinEntity {
+                entity,
+                slot_names: slot_names.clone(),
+            })
+            .collect_vec();
+        assert_eq!(expected_ambiguous_entities, context.take_ambiguous_builtin_entities());
+        assert!(context.take_ambiguous_builtin_entities().is_empty());
+    }
+
+    #[test]
+    fn augment_slots_should_drop_builtin_slots_violating_constraints() {
+        // Given
+        let disambiguation = BuiltinSlotsDisambiguation::Positional {
+            slot_names: vec!["start_date".to_string(), "end_date".to_string()],
+        };
+        let constraints = vec![SlotConstraint::Requires {
+            slot_name: "end_date".to_string(),
+            required_slot_name: "location".to_string(),
+        }];
+
+        // When
+        let augmented_slots = BuiltinSlotsFixture::dates().augment_slots(
+            disambiguation,
+            &constraints,
+            &ParseContext::default(),
+        );
+
+        // Then
+        assert!(augmented_slots.is_empty());
+    }
+
     #[test]
     fn spans_to_tokens_indexes_works() {
         // Given
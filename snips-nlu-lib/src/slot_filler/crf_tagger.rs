@@ -0,0 +1,617 @@
+// Without the `crf` feature, only the writing of the models is used, by the training
+#![cfg_attr(not(feature = "crf"), allow(dead_code, unused_imports))]
+
+use std::collections::HashMap;
+use std::f64;
+use std::sync::Arc;
+
+use compression::{intern, Weights};
+use configurations::WeightsQuantization;
+use errors::*;
+use memory_footprint::HeapSize;
+use utils::decreasing_order;
+
+const MODEL_MAGIC: &[u8] = b"lCRF";
+const FEATURES_CHUNK_ID: &[u8] = b"FEAT";
+const CQDB_CHUNK_ID: &[u8] = b"CQDB";
+const HEADER_SIZE: usize = 48;
+const FEATURE_SIZE: usize = 20;
+pub(crate) const STATE_FEATURE: u32 = 0;
+pub(crate) const TRANSITION_FEATURE: u32 = 1;
+
+/// Linear-chain CRF tagger reading the binary models produced by CRFsuite
+///
+/// Attributes are built from the (key, value) pairs of each token as `key:value`, with a weight
+/// of 1, which is how python-crfsuite converts string features.
+pub struct Tagger {
+    labels: Vec<String>,
+    attributes: HashMap<Arc<str>, usize>,
+    /// The state features of attribute `i` are stored between `state_offsets[i]` and
+    /// `state_offsets[i + 1]` in `state_labels` and `state_weights`
+    state_offsets: Vec<u32>,
+    state_labels: Vec<u16>,
+    state_weights: Weights<f64>,
+    /// transition weights, indexed by the previous label and then by the current label
+    transition_weights: Weights<f64>,
+}
+
+impl HeapSize for Tagger {
+    fn heap_size(&self) -> usize {
+        self.labels.heap_size()
+            + self.attributes.heap_size()
+            + self.state_offsets.heap_size()
+            + self.state_labels.heap_size()
+            + self.state_weights.heap_size()
+            + self.transition_weights.heap_size()
+    }
+}
+
+impl Tagger {
+    pub fn create_from_memory(data: &[u8]) -> Result<Self> {
+        if data.len() < HEADER_SIZE || &data[0..4] != MODEL_MAGIC {
+            bail!("Invalid CRF model: unknown header");
+        }
+        let nb_labels = read_u32(data, 20)? as usize;
+        let nb_attributes = read_u32(data, 24)? as usize;
+        let features_offset = read_u32(data, 28)? as usize;
+        let labels_offset = read_u32(data, 32)? as usize;
+        let attributes_offset = read_u32(data, 36)? as usize;
+
+        if nb_labels > usize::from(u16::max_value()) {
+            bail!("Invalid CRF model: too many labels");
+        }
+
+        let labels = read_cqdb_strings(data, labels_offset, nb_labels)?;
+        let attributes = read_cqdb_strings(data, attributes_offset, nb_attributes)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, attribute)| (Arc::from(attribute.as_str()), index))
+            .collect();
+
+        let mut state_features = vec![vec![]; nb_attributes];
+        let mut transition_weights = vec![0.0; nb_labels * nb_labels];
+        check_chunk_id(data, features_offset, FEATURES_CHUNK_ID)?;
+        let nb_features = read_u32(data, features_offset + 8)? as usize;
+        for feature_index in 0..nb_features {
+            let offset = features_offset + 12 + feature_index * FEATURE_SIZE;
+            let feature_type = read_u32(data, offset)?;
+            let source = read_u32(data, offset + 4)? as usize;
+            let destination = read_u32(data, offset + 8)? as usize;
+            let weight = read_f64(data, offset + 12)?;
+            if destination >= nb_labels {
+                bail!("Invalid CRF model: unknown label in feature {}", feature_index);
+            }
+            match feature_type {
+                STATE_FEATURE if source < nb_attributes => {
+                    state_features[source].push((destination as u16, weight))
+                }
+                TRANSITION_FEATURE if source < nb_labels => {
+                    transition_weights[source * nb_labels + destination] = weight
+                }
+                _ => bail!("Invalid CRF model: malformed feature {}", feature_index),
+            }
+        }
+
+        let mut state_offsets = Vec::with_capacity(nb_attributes + 1);
+        let mut state_labels = vec![];
+        let mut state_weights = vec![];
+        state_offsets.push(0);
+        for attribute_features in state_features {
+            for (label, weight) in attribute_features {
+                state_labels.push(label);
+                state_weights.push(weight);
+            }
+            state_offsets.push(state_labels.len() as u32);
+        }
+
+        Ok(Self {
+            labels,
+            attributes,
+            state_offsets,
+            state_labels,
+            state_weights: Weights::Full(state_weights),
+            transition_weights: Weights::Full(transition_weights),
+        })
+    }
+
+    /// Returns the tagger with quantized weights and with attributes shared with the other
+    /// taggers
+    pub fn compress(self, quantization: WeightsQuantization) -> Self {
+        // The weights are scaled by attribute for the state features and by previous label for
+        // the transitions
+        let state_rows = self.state_offsets
+            .iter()
+            .map(|offset| *offset as usize)
+            .collect::<Vec<_>>();
+        let nb_labels = self.labels.len();
+        let transition_rows = (0..nb_labels + 1)
+            .map(|label| label * nb_labels)
+            .collect::<Vec<_>>();
+        Self {
+            labels: self.labels,
+            attributes: self.attributes
+                .into_iter()
+                .map(|(attribute, index)| (intern(&attribute), index))
+                .collect(),
+            state_offsets: self.state_offsets,
+            state_labels: self.state_labels,
+            state_weights: self.state_weights.quantize(quantization, &state_rows),
+            transition_weights: self.transition_weights
+                .quantize(quantization, &transition_rows),
+        }
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn transition_weight(&self, previous_label: usize, label: usize) -> f64 {
+        self.transition_weights
+            .get(previous_label, previous_label * self.labels.len() + label)
+    }
+
+    /// Returns the most likely sequence of labels, computed with the Viterbi algorithm
+    pub fn tag(&self, features: &[Vec<(String, String)>]) -> Result<Vec<String>> {
+        if features.is_empty() {
+            return Ok(vec![]);
+        }
+        if self.labels.is_empty() {
+            bail!("CRF model has no labels");
+        }
+        let state_scores = self.state_scores(features);
+        let mut scores = state_scores[0].clone();
+        let mut backpointers: Vec<Vec<usize>> = Vec::with_capacity(features.len() - 1);
+        for token_scores in &state_scores[1..] {
+            let (new_scores, token_backpointers): (Vec<f64>, Vec<usize>) = (0..self.labels.len())
+                .map(|label| {
+                    let (best_previous_label, best_score) = argmax(
+                        scores
+                            .iter()
+                            .enumerate()
+                            .map(|(previous_label, score)| {
+                                score + self.transition_weight(previous_label, label)
+                            }),
+                    );
+                    (best_score + token_scores[label], best_previous_label)
+                })
+                .unzip();
+            scores = new_scores;
+            backpointers.push(token_backpointers);
+        }
+
+        let mut label = argmax(scores.into_iter()).0;
+        let mut path = vec![label];
+        for token_backpointers in backpointers.iter().rev() {
+            label = token_backpointers[label];
+            path.push(label);
+        }
+        Ok(path.into_iter()
+            .rev()
+            .map(|label| self.labels[label].clone())
+            .collect())
+    }
+
+    /// Returns the most likely sequence of labels found by a beam search, among the sequences
+    /// accepted by `is_valid`
+    ///
+    /// `is_valid` is called with the indexes of the labels of each partial sequence, and a flag
+    /// telling whether the sequence is complete. `None` is returned when no valid sequence is
+    /// found.
+    pub fn tag_with_beam_search<F>(
+        &self,
+        features: &[Vec<(String, String)>],
+        beam_size: usize,
+        is_valid: F,
+    ) -> Result<Option<Vec<String>>>
+    where
+        F: Fn(&[usize], bool) -> bool,
+    {
+        if features.is_empty() {
+            return Ok(Some(vec![]));
+        }
+        if self.labels.is_empty() {
+            bail!("CRF model has no labels");
+        }
+        if beam_size == 0 {
+            bail!("Beam size must be positive");
+        }
+        let state_scores = self.state_scores(features);
+        let nb_tokens = features.len();
+        let mut beam: Vec<(Vec<usize>, f64)> = vec![(vec![], 0.0)];
+        for (token_index, token_scores) in state_scores.iter().enumerate() {
+            let is_complete = token_index + 1 == nb_tokens;
+            let mut candidates: Vec<(Vec<usize>, f64)> = vec![];
+            for &(ref path, score) in &beam {
+                for (label, label_score) in token_scores.iter().enumerate() {
+                    let transition_score = path.last()
+                        .map(|previous_label| self.transition_weight(*previous_label, label))
+                        .unwrap_or(0.0);
+                    let mut candidate_path = path.clone();
+                    candidate_path.push(label);
+                    if is_valid(&candidate_path, is_complete) {
+                        candidates.push((candidate_path, score + transition_score + label_score));
+                    }
+                }
+            }
+            candidates.sort_by(|a, b| decreasing_order(a.1, b.1));
+            candidates.truncate(beam_size);
+            if candidates.is_empty() {
+                return Ok(None);
+            }
+            beam = candidates;
+        }
+        Ok(beam.into_iter().next().map(|(path, _)| {
+            path.into_iter()
+                .map(|label| self.labels[label].clone())
+                .collect()
+        }))
+    }
+
+    /// Returns the probability of the given sequence of labels
+    pub fn probability(
+        &self,
+        features: &[Vec<(String, String)>],
+        tags: &[String],
+    ) -> Result<f64> {
+        if features.len() != tags.len() {
+            bail!("Expected {} tags but got {}", features.len(), tags.len());
+        }
+        if features.is_empty() {
+            return Ok(1.0);
+        }
+        let path = tags.iter()
+            .map(|tag| {
+                self.labels
+                    .iter()
+                    .position(|label| label == tag)
+                    .ok_or_else(|| format!("Unknown CRF label: {}", tag))
+            })
+            .collect::<::std::result::Result<Vec<usize>, String>>()?;
+
+        let state_scores = self.state_scores(features);
+        let path_score = path.iter()
+            .enumerate()
+            .map(|(index, label)| state_scores[index][*label])
+            .sum::<f64>()
+            + path.windows(2)
+                .map(|labels| self.transition_weight(labels[0], labels[1]))
+                .sum::<f64>();
+
+        let mut log_alphas = state_scores[0].clone();
+        for token_scores in &state_scores[1..] {
+            log_alphas = (0..self.labels.len())
+                .map(|label| {
+                    log_sum_exp(
+                        log_alphas
+                            .iter()
+                            .enumerate()
+                            .map(|(previous_label, log_alpha)| {
+                                log_alpha + self.transition_weight(previous_label, label)
+                            }),
+                    ) + token_scores[label]
+                })
+                .collect();
+        }
+        let log_partition = log_sum_exp(log_alphas.into_iter());
+
+        Ok((path_score - log_partition).exp())
+    }
+
+    fn state_scores(&self, features: &[Vec<(String, String)>]) -> Vec<Vec<f64>> {
+        features
+            .iter()
+            .map(|token_features| {
+                let mut scores = vec![0.0; self.labels.len()];
+                for &(ref key, ref value) in token_features {
+                    let attribute = format!("{}:{}", key, value);
+                    if let Some(attribute_index) = self.attributes.get(attribute.as_str()) {
+                        let start = self.state_offsets[*attribute_index] as usize;
+                        let end = self.state_offsets[*attribute_index + 1] as usize;
+                        for feature_index in start..end {
+                            let label = usize::from(self.state_labels[feature_index]);
+                            scores[label] +=
+                                self.state_weights.get(*attribute_index, feature_index);
+                        }
+                    }
+                }
+                scores
+            })
+            .collect()
+    }
+}
+
+/// Returns the index and the value of the first maximum
+fn argmax<I: Iterator<Item = f64>>(values: I) -> (usize, f64) {
+    values
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+}
+
+pub(crate) fn log_sum_exp<I: Iterator<Item = f64> + Clone>(values: I) -> f64 {
+    let max = values.clone().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + values.map(|value| (value - max).exp()).sum::<f64>().ln()
+}
+
+/// Reads the `nb_strings` first strings of a CQDB chunk, using its backward array which maps
+/// string identifiers to their records
+fn read_cqdb_strings(data: &[u8], offset: usize, nb_strings: usize) -> Result<Vec<String>> {
+    check_chunk_id(data, offset, CQDB_CHUNK_ID)?;
+    let backward_array_size = read_u32(data, offset + 16)? as usize;
+    let backward_array_offset = offset + read_u32(data, offset + 20)? as usize;
+    if nb_strings > backward_array_size {
+        bail!("Invalid CRF model: missing strings in CQDB chunk");
+    }
+    (0..nb_strings)
+        .map(|id| {
+            let record_offset = read_u32(data, backward_array_offset + 4 * id)? as usize;
+            if record_offset == 0 {
+                bail!("Invalid CRF model: missing string {} in CQDB chunk", id);
+            }
+            let string_size = read_u32(data, offset + record_offset + 4)? as usize;
+            let string_start = offset + record_offset + 8;
+            // The stored size includes the trailing null byte
+            let string_end = string_start + string_size.saturating_sub(1);
+            if string_end > data.len() {
+                bail!("Invalid CRF model: unexpected end of data");
+            }
+            Ok(String::from_utf8(data[string_start..string_end].to_vec())?)
+        })
+        .collect()
+}
+
+fn check_chunk_id(data: &[u8], offset: usize, chunk_id: &[u8]) -> Result<()> {
+    if data.len() < offset + chunk_id.len() || &data[offset..offset + chunk_id.len()] != chunk_id
+    {
+        bail!(
+            "Invalid CRF model: expected {} chunk",
+            String::from_utf8_lossy(chunk_id)
+        );
+    }
+    Ok(())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    if data.len() < offset + 4 {
+        bail!("Invalid CRF model: unexpected end of data");
+    }
+    Ok(data[offset..offset + 4]
+        .iter()
+        .rev()
+        .fold(0, |acc, byte| (acc << 8) | u32::from(*byte)))
+}
+
+fn read_f64(data: &[u8], offset: usize) -> Result<f64> {
+    if data.len() < offset + 8 {
+        bail!("Invalid CRF model: unexpected end of data");
+    }
+    let bits = data[offset..offset + 8]
+        .iter()
+        .rev()
+        .fold(0, |acc, byte| (acc << 8) | u64::from(*byte));
+    Ok(f64::from_bits(bits))
+}
+
+fn write_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend((0..4).map(|i| (value >> (8 * i)) as u8));
+}
+
+/// Writes a CQDB chunk storing `strings`, only the backward array which maps string identifiers
+/// to their records being filled
+fn write_cqdb(strings: &[&str]) -> Vec<u8> {
+    let mut records = vec![];
+    let mut record_offsets = vec![];
+    for (id, string) in strings.iter().enumerate() {
+        record_offsets.push(24 + records.len() as u32);
+        write_u32(&mut records, id as u32);
+        write_u32(&mut records, string.len() as u32 + 1);
+        records.extend(string.as_bytes());
+        records.push(0);
+    }
+    let mut chunk = CQDB_CHUNK_ID.to_vec();
+    let backward_array_offset = 24 + records.len() as u32;
+    write_u32(&mut chunk, backward_array_offset + 4 * strings.len() as u32);
+    write_u32(&mut chunk, 0);
+    write_u32(&mut chunk, 0x62445371);
+    write_u32(&mut chunk, strings.len() as u32);
+    write_u32(&mut chunk, backward_array_offset);
+    chunk.extend(records);
+    for record_offset in record_offsets {
+        write_u32(&mut chunk, record_offset);
+    }
+    chunk
+}
+
+/// Writes a binary CRFsuite model, each feature being given as a tuple (type, source,
+/// destination, weight)
+pub(crate) fn write_model(
+    labels: &[&str],
+    attributes: &[&str],
+    features: &[(u32, u32, u32, f64)],
+) -> Vec<u8> {
+    let mut features_chunk = FEATURES_CHUNK_ID.to_vec();
+    write_u32(&mut features_chunk, (12 + FEATURE_SIZE * features.len()) as u32);
+    write_u32(&mut features_chunk, features.len() as u32);
+    for &(feature_type, source, destination, weight) in features {
+        write_u32(&mut features_chunk, feature_type);
+        write_u32(&mut features_chunk, source);
+        write_u32(&mut features_chunk, destination);
+        let bits = weight.to_bits();
+        write_u32(&mut features_chunk, bits as u32);
+        write_u32(&mut features_chunk, (bits >> 32) as u32);
+    }
+    let labels_chunk = write_cqdb(labels);
+    let attributes_chunk = write_cqdb(attributes);
+
+    let features_offset = HEADER_SIZE;
+    let labels_offset = features_offset + features_chunk.len();
+    let attributes_offset = labels_offset + labels_chunk.len();
+    let total_size = attributes_offset + attributes_chunk.len();
+
+    let mut model = MODEL_MAGIC.to_vec();
+    write_u32(&mut model, total_size as u32);
+    model.extend(b"FOMC");
+    write_u32(&mut model, 100);
+    write_u32(&mut model, features.len() as u32);
+    write_u32(&mut model, labels.len() as u32);
+    write_u32(&mut model, attributes.len() as u32);
+    write_u32(&mut model, features_offset as u32);
+    write_u32(&mut model, labels_offset as u32);
+    write_u32(&mut model, attributes_offset as u32);
+    write_u32(&mut model, 0);
+    write_u32(&mut model, 0);
+    model.extend(features_chunk);
+    model.extend(labels_chunk);
+    model.extend(attributes_chunk);
+    model
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutils::epsilon_eq;
+
+    fn test_tagger() -> Tagger {
+        let labels = ["O", "B-beverage"];
+        let attributes = ["word:coffee", "word:a", "is_first:1"];
+        let features = [
+            (STATE_FEATURE, 0, 1, 3.0),
+            (STATE_FEATURE, 1, 0, 1.0),
+            (STATE_FEATURE, 2, 0, 0.5),
+            (TRANSITION_FEATURE, 0, 1, 0.5),
+            (TRANSITION_FEATURE, 1, 1, -2.0),
+        ];
+        Tagger::create_from_memory(&write_model(&labels, &attributes, &features)).unwrap()
+    }
+
+    fn test_features() -> Vec<Vec<(String, String)>> {
+        vec![
+            vec![
+                ("word".to_string(), "a".to_string()),
+                ("is_first".to_string(), "1".to_string()),
+            ],
+            vec![("word".to_string(), "coffee".to_string())],
+        ]
+    }
+
+    #[test]
+    fn create_from_memory_works() {
+        // When
+        let tagger = test_tagger();
+
+        // Then
+        assert_eq!(&["O".to_string(), "B-beverage".to_string()], tagger.labels());
+        assert_eq!(Weights::Full(vec![0.0, 0.5, 0.0, -2.0]), tagger.transition_weights);
+    }
+
+    #[test]
+    fn compressed_tagger_works() {
+        // Given
+        let tagger = test_tagger();
+        let tags = vec!["O".to_string(), "B-beverage".to_string()];
+        let probability = tagger.probability(&test_features(), &tags).unwrap();
+
+        for quantization in vec![WeightsQuantization::F16, WeightsQuantization::Int8] {
+            // When
+            let compressed_tagger = test_tagger().compress(quantization);
+            let compressed_tags = compressed_tagger.tag(&test_features()).unwrap();
+            let compressed_probability = compressed_tagger
+                .probability(&test_features(), &tags)
+                .unwrap();
+
+            // Then
+            assert_eq!(tags, compressed_tags);
+            assert!(epsilon_eq(probability as f32, compressed_probability as f32, 1e-2));
+        }
+    }
+
+    #[test]
+    fn create_from_memory_should_fail_with_invalid_data() {
+        // When
+        let result = Tagger::create_from_memory(b"not a crf model");
+
+        // Then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn tag_works() {
+        // Given
+        let tagger = test_tagger();
+
+        // When
+        let tags = tagger.tag(&test_features()).unwrap();
+
+        // Then
+        assert_eq!(vec!["O".to_string(), "B-beverage".to_string()], tags);
+    }
+
+    #[test]
+    fn tag_with_beam_search_works() {
+        // Given
+        let tagger = test_tagger();
+
+        // When
+        let tags = tagger
+            .tag_with_beam_search(&test_features(), 2, |_, _| true)
+            .unwrap();
+
+        // Then
+        assert_eq!(Some(vec!["O".to_string(), "B-beverage".to_string()]), tags);
+    }
+
+    #[test]
+    fn tag_with_beam_search_should_enforce_constraints() {
+        // Given
+        let tagger = test_tagger();
+
+        // When
+        let tags = tagger
+            .tag_with_beam_search(&test_features(), 2, |path, _| path.len() < 2 || path[1] == 0)
+            .unwrap();
+        let no_tags = tagger
+            .tag_with_beam_search(&test_features(), 2, |_, is_complete| !is_complete)
+            .unwrap();
+
+        // Then
+        assert_eq!(Some(vec!["O".to_string(), "O".to_string()]), tags);
+        assert_eq!(None, no_tags);
+    }
+
+    #[test]
+    fn probability_works() {
+        // Given
+        let tagger = test_tagger();
+        let tags = vec!["O".to_string(), "B-beverage".to_string()];
+
+        // When
+        let probability = tagger.probability(&test_features(), &tags).unwrap();
+
+        // Then
+        // Scores of the 4 possible sequences: O O -> 1.5, O B -> 5.0, B O -> 0.0, B B -> 1.0
+        let expected_probability = 5.0_f64.exp()
+            / (1.5_f64.exp() + 5.0_f64.exp() + 0.0_f64.exp() + 1.0_f64.exp());
+        assert!(epsilon_eq(expected_probability as f32, probability as f32, 1e-6));
+    }
+
+    #[test]
+    fn probability_should_fail_with_unknown_label() {
+        // Given
+        let tagger = test_tagger();
+        let tags = vec!["O".to_string(), "B-unknown".to_string()];
+
+        // When
+        let result = tagger.probability(&test_features(), &tags);
+
+        // Then
+        assert!(result.is_err());
+    }
+}
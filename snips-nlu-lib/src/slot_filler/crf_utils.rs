@@ -5,17 +5,19 @@ use std::iter::FromIterator;
 use itertools::{repeat_n, Itertools};
 use yolo::Yolo;
 
+use configurations::{SlotConstraint, TaggingSchemeConfiguration};
 use errors::*;
 use nlu_utils::string::suffix_from_char_index;
 use nlu_utils::token::Token;
 use slot_utils::InternalSlot;
+use symbols::Symbol;
 use utils::{permutations, product};
 
-const BEGINNING_PREFIX: &str = "B-";
-const INSIDE_PREFIX: &str = "I-";
+pub const BEGINNING_PREFIX: &str = "B-";
+pub const INSIDE_PREFIX: &str = "I-";
 const LAST_PREFIX: &str = "L-";
 const UNIT_PREFIX: &str = "U-";
-const OUTSIDE: &str = "O";
+pub const OUTSIDE: &str = "O";
 
 #[derive(Copy, Clone, Debug)]
 pub enum TaggingScheme {
@@ -33,6 +35,39 @@ impl TaggingScheme {
             _ => Err(format!("Unknown tagging scheme identifier: {}", i))?,
         }
     }
+
+    pub fn from_name(name: &str) -> Result<TaggingScheme> {
+        match &*name.to_lowercase() {
+            "io" => Ok(TaggingScheme::IO),
+            "bio" => Ok(TaggingScheme::BIO),
+            "bilou" => Ok(TaggingScheme::BILOU),
+            _ => Err(format!("Unknown tagging scheme name: {}", name))?,
+        }
+    }
+
+    pub fn from_configuration(configuration: &TaggingSchemeConfiguration) -> Result<TaggingScheme> {
+        match *configuration {
+            TaggingSchemeConfiguration::Identifier(i) => Self::from_u8(i),
+            TaggingSchemeConfiguration::Name(ref name) => Self::from_name(name),
+        }
+    }
+
+    /// Checks that the labels of a CRF model are all valid tags in this scheme, so that a
+    /// mismatch between the model and its configuration doesn't silently produce wrong slots
+    pub fn check_labels(&self, labels: &[String]) -> Result<()> {
+        let prefixes: &[&str] = match *self {
+            TaggingScheme::IO => &[INSIDE_PREFIX],
+            TaggingScheme::BIO => &[BEGINNING_PREFIX, INSIDE_PREFIX],
+            TaggingScheme::BILOU => &[BEGINNING_PREFIX, INSIDE_PREFIX, LAST_PREFIX, UNIT_PREFIX],
+        };
+        let invalid_label = labels.iter().find(|label| {
+            *label != OUTSIDE && !prefixes.iter().any(|prefix| label.starts_with(prefix))
+        });
+        if let Some(label) = invalid_label {
+            bail!("Label '{}' is not valid in the {:?} tagging scheme", label, self);
+        }
+        Ok(())
+    }
 }
 
 pub fn get_substitution_label(labels: &[&str]) -> String {
@@ -153,6 +188,41 @@ fn is_end_of_bilou_slot(tags: &[String], i: usize) -> bool {
     }
 }
 
+/// Checks whether the slots of a sequence of tags satisfy the constraints, the sequence being
+/// possibly partial in which case only the constraints which can't be fixed by the next tags are
+/// checked
+pub fn satisfies_slot_constraints(
+    tags: &[String],
+    tagging_scheme: TaggingScheme,
+    constraints: &[SlotConstraint],
+    is_complete: bool,
+) -> bool {
+    let is_start_of_slot: fn(&[String], usize) -> bool = match tagging_scheme {
+        TaggingScheme::IO => is_start_of_io_slot,
+        TaggingScheme::BIO => is_start_of_bio_slot,
+        TaggingScheme::BILOU => is_start_of_bilou_slot,
+    };
+    let mut slot_counts: HashMap<String, usize> = HashMap::new();
+    for (i, tag) in tags.iter().enumerate() {
+        if is_start_of_slot(tags, i) {
+            *slot_counts
+                .entry(tag_name_to_slot_name(tag.to_string()))
+                .or_insert(0) += 1;
+        }
+    }
+    let slot_count = |slot_name: &str| *slot_counts.get(slot_name).unwrap_or(&0);
+    constraints.iter().all(|constraint| match *constraint {
+        SlotConstraint::MaxCount {
+            ref slot_name,
+            max_count,
+        } => slot_count(slot_name) <= max_count,
+        SlotConstraint::Requires {
+            ref slot_name,
+            ref required_slot_name,
+        } => !is_complete || slot_count(slot_name) == 0 || slot_count(required_slot_name) > 0,
+    })
+}
+
 pub struct SlotRange {
     slot_name: String,
     pub range: Range<usize>,
@@ -209,7 +279,7 @@ pub fn tags_to_slots(
     tokens: &[Token],
     tags: &[String],
     tagging_scheme: TaggingScheme,
-    intent_slots_mapping: &HashMap<String, String>,
+    intent_slots_mapping: &HashMap<Symbol, Symbol>,
 ) -> Result<Vec<InternalSlot>> {
     tags_to_slot_ranges(tokens, tags, tagging_scheme)
         .into_iter()
@@ -217,7 +287,7 @@ pub fn tags_to_slots(
             Ok(InternalSlot {
                 value: text[s.range.clone()].to_string(),
                 entity: intent_slots_mapping
-                    .get(&s.slot_name)
+                    .get(&*s.slot_name)
                     .ok_or_else(|| {
                         format!(
                             "Missing slot to entity mapping for slot name: {}",
@@ -370,7 +440,7 @@ mod tests {
         // Given
         let language = Language::EN;
         let slot_name = "animal";
-        let intent_slots_mapping = hashmap!["animal".to_string() => "animal".to_string()];
+        let intent_slots_mapping = hashmap![Symbol::from("animal") => Symbol::from("animal")];
         let tags: Vec<Test> = vec![
             Test {
                 text: "".to_string(),
@@ -498,7 +568,7 @@ mod tests {
         // Given
         let language = Language::EN;
         let slot_name = "animal";
-        let intent_slots_mapping = hashmap!["animal".to_string() => "animal".to_string()];
+        let intent_slots_mapping = hashmap![Symbol::from("animal") => Symbol::from("animal")];
         let tags: Vec<Test> = vec![
             Test {
                 text: "".to_string(),
@@ -662,7 +732,7 @@ mod tests {
         // Given
         let language = Language::EN;
         let slot_name = "animal";
-        let intent_slots_mapping = hashmap!["animal".to_string() => "animal".to_string()];
+        let intent_slots_mapping = hashmap![Symbol::from("animal") => Symbol::from("animal")];
         let tags: Vec<Test> = vec![
             Test {
                 text: "".to_string(),
@@ -1005,6 +1075,101 @@ mod tests {
         assert_eq!(ends_of_bilou, expected_ends);
     }
 
+    #[test]
+    fn tagging_scheme_from_configuration_works() {
+        // Given
+        let identifier = TaggingSchemeConfiguration::Identifier(1);
+        let name = TaggingSchemeConfiguration::Name("BILOU".to_string());
+        let unknown_name = TaggingSchemeConfiguration::Name("BIOES".to_string());
+
+        // When
+        let scheme_from_identifier = TaggingScheme::from_configuration(&identifier).unwrap();
+        let scheme_from_name = TaggingScheme::from_configuration(&name).unwrap();
+        let scheme_from_unknown_name = TaggingScheme::from_configuration(&unknown_name);
+
+        // Then
+        assert!(match scheme_from_identifier {
+            TaggingScheme::BIO => true,
+            _ => false,
+        });
+        assert!(match scheme_from_name {
+            TaggingScheme::BILOU => true,
+            _ => false,
+        });
+        assert!(scheme_from_unknown_name.is_err());
+    }
+
+    #[test]
+    fn check_labels_works() {
+        // Given
+        let bio_labels = vec![
+            "O".to_string(),
+            "B-beverage".to_string(),
+            "I-beverage".to_string(),
+        ];
+        let bilou_labels = vec![
+            "O".to_string(),
+            "U-beverage".to_string(),
+            "L-beverage".to_string(),
+        ];
+
+        // Then
+        assert!(TaggingScheme::BIO.check_labels(&bio_labels).is_ok());
+        assert!(TaggingScheme::BILOU.check_labels(&bio_labels).is_ok());
+        assert!(TaggingScheme::BILOU.check_labels(&bilou_labels).is_ok());
+        assert!(TaggingScheme::BIO.check_labels(&bilou_labels).is_err());
+        assert!(TaggingScheme::IO.check_labels(&bio_labels).is_err());
+    }
+
+    #[test]
+    fn satisfies_slot_constraints_works() {
+        // Given
+        let tags = vec![
+            "B-beverage".to_string(),
+            "O".to_string(),
+            "B-beverage".to_string(),
+            "I-beverage".to_string(),
+        ];
+        let max_count_constraints = vec![
+            SlotConstraint::MaxCount {
+                slot_name: "beverage".to_string(),
+                max_count: 1,
+            },
+        ];
+        let requires_constraints = vec![
+            SlotConstraint::Requires {
+                slot_name: "beverage".to_string(),
+                required_slot_name: "number_of_cups".to_string(),
+            },
+        ];
+
+        // Then
+        assert!(satisfies_slot_constraints(
+            &tags[..2],
+            TaggingScheme::BIO,
+            &max_count_constraints,
+            false
+        ));
+        assert!(!satisfies_slot_constraints(
+            &tags,
+            TaggingScheme::BIO,
+            &max_count_constraints,
+            true
+        ));
+        assert!(satisfies_slot_constraints(
+            &tags,
+            TaggingScheme::BIO,
+            &requires_constraints,
+            false
+        ));
+        assert!(!satisfies_slot_constraints(
+            &tags,
+            TaggingScheme::BIO,
+            &requires_constraints,
+            true
+        ));
+    }
+
     #[test]
     fn get_scheme_prefix_works() {
         // Given
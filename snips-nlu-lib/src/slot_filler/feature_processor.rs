@@ -3,14 +3,18 @@ use std::collections::HashMap;
 use std::str::FromStr;
 
 use super::crf_utils::TaggingScheme;
+use super::feature_registry::FeatureExtractorRegistry;
 use super::features;
+use builtin_entity_parser::MultilingualBuiltinEntityParser;
 use configurations::FeatureFactory;
 use errors::*;
+use ngrams::NgramTrie;
 use nlu_utils::token::Token;
-use resources::gazetteer::{HashSetGazetteer, StaticMapGazetteer};
+use parse_context::ParseContext;
+use resources::gazetteer::StaticMapGazetteer;
 use resources::stemmer::StaticMapStemmer;
 use resources::word_clusterer::StaticMapWordClusterer;
-use snips_nlu_ontology::{BuiltinEntityKind, BuiltinEntityParser, Language};
+use snips_nlu_ontology::{BuiltinEntityKind, Language};
 
 pub struct ProbabilisticFeatureProcessor {
     functions: Vec<FeatureFunction>,
@@ -18,10 +22,13 @@ pub struct ProbabilisticFeatureProcessor {
 
 impl ProbabilisticFeatureProcessor {
     // TODO add a `GazetteerProvider` to this signature
-    pub fn new(features: &[FeatureFactory]) -> Result<ProbabilisticFeatureProcessor> {
+    pub fn new(
+        features: &[FeatureFactory],
+        feature_registry: &FeatureExtractorRegistry,
+    ) -> Result<ProbabilisticFeatureProcessor> {
         let functions = features
             .iter()
-            .map(|f| get_feature_function(f))
+            .map(|f| get_feature_function(f, feature_registry))
             .collect::<Result<Vec<Vec<_>>>>()?
             .into_iter()
             .flat_map(|fs| fs)
@@ -33,12 +40,16 @@ impl ProbabilisticFeatureProcessor {
 
 impl ProbabilisticFeatureProcessor {
     #[cfg_attr(rustfmt, rustfmt_skip)]
-    pub fn compute_features(&self, input: &&[Token]) -> Vec<Vec<(String, String)>> {
+    pub fn compute_features(
+        &self,
+        input: &&[Token],
+        context: &ParseContext,
+    ) -> Vec<Vec<(String, String)>> {
         self.functions
             .iter()
             .fold(vec![vec![]; input.len()], |mut acc, f| {
                 (0..input.len()).foreach(|i| {
-                    if let Some(value) = (f.function)(input, i) {
+                    if let Some(value) = (f.function)(input, i, context) {
                         f.offsets.iter().foreach(|&(offset, ref key)| {
                             if i as i32 - offset >= 0 && i as i32 - offset < input.len() as i32 {
                                 acc[(i as i32 - offset) as usize].push((key.clone(), value.clone()));
@@ -52,14 +63,14 @@ impl ProbabilisticFeatureProcessor {
 }
 
 struct FeatureFunction {
-    function: Box<Fn(&[Token], usize) -> Option<String> + Send + Sync>,
+    function: Box<Fn(&[Token], usize, &ParseContext) -> Option<String> + Send + Sync>,
     offsets: Vec<(i32, String)>,
 }
 
 impl FeatureFunction {
     fn new<T>(key: &str, offsets: Vec<i32>, function: T) -> FeatureFunction
     where
-        T: Fn(&[Token], usize) -> Option<String> + Send + Sync + 'static,
+        T: Fn(&[Token], usize, &ParseContext) -> Option<String> + Send + Sync + 'static,
     {
         let offsets = offsets
             .into_iter()
@@ -81,7 +92,10 @@ impl FeatureFunction {
     }
 }
 
-fn get_feature_function(f: &FeatureFactory) -> Result<Vec<FeatureFunction>> {
+fn get_feature_function(
+    f: &FeatureFactory,
+    feature_registry: &FeatureExtractorRegistry,
+) -> Result<Vec<FeatureFunction>> {
     let offsets = f.offsets.clone();
     match &*f.factory_name {
         "is_digit" => Ok(vec![is_digit_feature_function(offsets)?]),
@@ -94,24 +108,35 @@ fn get_feature_function(f: &FeatureFactory) -> Result<Vec<FeatureFunction>> {
         "entity_match" => entity_match_feature_function(&f.args, &offsets),
         "builtin_entity_match" => builtin_entity_match_feature_function(&f.args, &offsets),
         "word_cluster" => Ok(vec![word_cluster_feature_function(&f.args, offsets)?]),
-        _ => bail!("Feature {} not implemented", f.factory_name),
+        _ => {
+            if let Some(factory) = feature_registry.get(&f.factory_name) {
+                let extractor = factory.create(&f.args)?;
+                Ok(vec![FeatureFunction::new(
+                    &f.factory_name,
+                    offsets,
+                    move |tokens, token_index, _| extractor.compute(tokens, token_index),
+                )])
+            } else {
+                bail!("Feature {} not implemented", f.factory_name)
+            }
+        }
     }
 }
 
 fn is_digit_feature_function(offsets: Vec<i32>) -> Result<FeatureFunction> {
-    Ok(FeatureFunction::new("is_digit", offsets, |t, i| {
+    Ok(FeatureFunction::new("is_digit", offsets, |t, i, _| {
         features::is_digit(&t[i].value)
     }))
 }
 
 fn is_first_feature_function(offsets: Vec<i32>) -> Result<FeatureFunction> {
-    Ok(FeatureFunction::new("is_first", offsets, |_, i| {
+    Ok(FeatureFunction::new("is_first", offsets, |_, i, _| {
         features::is_first(i)
     }))
 }
 
 fn is_last_feature_function(offsets: Vec<i32>) -> Result<FeatureFunction> {
-    Ok(FeatureFunction::new("is_last", offsets, |t, i| {
+    Ok(FeatureFunction::new("is_last", offsets, |t, i, _| {
         features::is_last(t, i)
     }))
 }
@@ -133,7 +158,7 @@ fn ngram_feature_function(
     Ok(FeatureFunction::new(
         &format!("ngram_{}", n),
         offsets,
-        move |tokens, token_index| {
+        move |tokens, token_index, _| {
             features::ngram(
                 tokens,
                 token_index,
@@ -153,7 +178,7 @@ fn shape_ngram_feature_function(
     Ok(FeatureFunction::new(
         &format!("shape_ngram_{}", n),
         offsets,
-        move |t, i| features::shape(t, i, n),
+        move |t, i, _| features::shape(t, i, n),
     ))
 }
 
@@ -165,7 +190,7 @@ fn prefix_feature_function(
     Ok(FeatureFunction::new(
         &format!("prefix-{}", n),
         offsets,
-        move |t, i| features::prefix(&t[i].value, n),
+        move |t, i, _| features::prefix(&t[i].value, n),
     ))
 }
 
@@ -177,7 +202,7 @@ fn suffix_feature_function(
     Ok(FeatureFunction::new(
         &format!("suffix-{}", n),
         offsets,
-        move |t, i| features::suffix(&t[i].value, n),
+        move |t, i, _| features::suffix(&t[i].value, n),
     ))
 }
 
@@ -194,11 +219,13 @@ fn entity_match_feature_function(
     collections
         .into_iter()
         .map(|(entity_name, values)| {
-            let entity_gazetteer = HashSetGazetteer::from(values.into_iter());
+            let entity_gazetteer: NgramTrie<()> =
+                values.into_iter().map(|value| (value, ())).collect();
+            let stemmer = stemmer.clone();
             Ok(FeatureFunction::new(
                 &format!("entity_match_{}", entity_name),
                 offsets.to_vec(),
-                move |tokens, token_index| {
+                move |tokens, token_index, _| {
                     features::get_gazetteer_match(
                         tokens,
                         token_index,
@@ -225,19 +252,19 @@ fn builtin_entity_match_feature_function(
         .map(|label| {
             let builtin_parser = Language::from_str(&language_code)
                 .ok()
-                .map(BuiltinEntityParser::get);
+                .map(|language| MultilingualBuiltinEntityParser::new(&[language]));
             let builtin_entity_kind = BuiltinEntityKind::from_identifier(&label).ok();
             Ok(FeatureFunction::new(
                 &format!("builtin_entity_match_{}", &label),
                 offsets.to_vec(),
-                move |tokens, token_index| {
+                move |tokens, token_index, context| {
                     if let (Some(parser), Some(builtin_entity_kind)) =
                         (builtin_parser.as_ref(), builtin_entity_kind)
                     {
                         features::get_builtin_entity_match(
                             tokens,
                             token_index,
-                            &**parser,
+                            context.builtin_entity_parser_or(parser),
                             builtin_entity_kind,
                             tagging_scheme,
                         )
@@ -260,7 +287,9 @@ fn word_cluster_feature_function(
     Ok(FeatureFunction::new(
         &format!("word_cluster_{}", cluster_name),
         offsets,
-        move |tokens, token_index| features::get_word_cluster(tokens, token_index, &word_clusterer),
+        move |tokens, token_index, _| {
+            features::get_word_cluster(tokens, token_index, &word_clusterer)
+        },
     ))
 }
 
@@ -351,13 +380,14 @@ mod tests {
 
     use nlu_utils::language::Language;
     use nlu_utils::token::tokenize;
+    use snips_nlu_ontology::Language as OntologyLanguage;
 
     #[test]
     fn compute_features_works() {
         let language = Language::EN;
         let fp = ProbabilisticFeatureProcessor {
             functions: vec![
-                FeatureFunction::new("Toto", vec![0], |_, i| {
+                FeatureFunction::new("Toto", vec![0], |_, i, _| {
                     if i == 0 {
                         None
                     } else {
@@ -367,8 +397,8 @@ mod tests {
             ],
         };
 
-        let computed_features =
-            fp.compute_features(&tokenize("hello world how are you ?", language).as_slice());
+        let tokens = tokenize("hello world how are you ?", language);
+        let computed_features = fp.compute_features(&tokens.as_slice(), &ParseContext::default());
 
         assert_eq!(computed_features.len(), 6);
         assert_eq!(computed_features[0], vec![]);
@@ -385,14 +415,14 @@ mod tests {
         let language = Language::EN;
         let fp = ProbabilisticFeatureProcessor {
             functions: vec![
-                FeatureFunction::new("Toto", vec![-2, 0, 2, 4], |x, i| {
+                FeatureFunction::new("Toto", vec![-2, 0, 2, 4], |x, i, _| {
                     if i == 0 {
                         None
                     } else {
                         Some(x[i].value.clone())
                     }
                 }),
-                FeatureFunction::new("Tutu", vec![2], |_, i| {
+                FeatureFunction::new("Tutu", vec![2], |_, i, _| {
                     if i != 3 {
                         None
                     } else {
@@ -402,8 +432,8 @@ mod tests {
             ],
         };
 
-        let computed_features =
-            fp.compute_features(&tokenize("hello world how are you ?", language).as_slice());
+        let tokens = tokenize("hello world how are you ?", language);
+        let computed_features = fp.compute_features(&tokens.as_slice(), &ParseContext::default());
         assert_eq!(
             computed_features,
             vec![
@@ -437,4 +467,32 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn builtin_entity_match_should_use_the_parser_of_the_context() {
+        // Given
+        let args = hashmap! {
+            "entity_labels".to_string() => json!(["snips/number"]),
+            "language_code".to_string() => json!("en"),
+            "tagging_scheme_code".to_string() => json!(2),
+        };
+        let fp = ProbabilisticFeatureProcessor {
+            functions: builtin_entity_match_feature_function(&args, &[0]).unwrap(),
+        };
+        let tokens = tokenize("deux tasses", Language::FR);
+        let context = ParseContext::with_builtin_entities_languages(&[OntologyLanguage::FR]);
+
+        // When
+        let features = fp.compute_features(&tokens.as_slice(), &ParseContext::default());
+        let overridden_features = fp.compute_features(&tokens.as_slice(), &context);
+
+        // Then
+        let no_features: Vec<Vec<(String, String)>> = vec![vec![], vec![]];
+        assert_eq!(no_features, features);
+        let expected_features = vec![
+            vec![("builtin_entity_match_snips/number".to_string(), "U-".to_string())],
+            vec![],
+        ];
+        assert_eq!(expected_features, overridden_features);
+    }
 }
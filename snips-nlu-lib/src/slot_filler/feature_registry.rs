@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use errors::*;
+use nlu_utils::token::Token;
+
+/// Token-level feature, whose value is appended to the CRF features of the token when defined
+pub trait FeatureExtractor: Send + Sync {
+    fn compute(&self, tokens: &[Token], token_index: usize) -> Option<String>;
+}
+
+impl<F> FeatureExtractor for F
+where
+    F: Fn(&[Token], usize) -> Option<String> + Send + Sync,
+{
+    fn compute(&self, tokens: &[Token], token_index: usize) -> Option<String> {
+        self(tokens, token_index)
+    }
+}
+
+/// Builds a feature extractor out of the `args` of its feature factory configuration
+pub trait FeatureExtractorFactory: Send + Sync {
+    fn create(
+        &self,
+        args: &HashMap<String, ::serde_json::Value>,
+    ) -> Result<Box<FeatureExtractor>>;
+}
+
+impl<F> FeatureExtractorFactory for F
+where
+    F: Fn(&HashMap<String, ::serde_json::Value>) -> Result<Box<FeatureExtractor>> + Send + Sync,
+{
+    fn create(
+        &self,
+        args: &HashMap<String, ::serde_json::Value>,
+    ) -> Result<Box<FeatureExtractor>> {
+        self(args)
+    }
+}
+
+/// Factories of the custom slot filling features, indexed by the `factory_name` used in the
+/// feature factory configurations of the slot fillers
+#[derive(Default)]
+pub struct FeatureExtractorRegistry {
+    factories: HashMap<String, Box<FeatureExtractorFactory>>,
+}
+
+impl FeatureExtractorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<F>(&mut self, factory_name: &str, factory: F)
+    where
+        F: FeatureExtractorFactory + 'static,
+    {
+        self.factories
+            .insert(factory_name.to_string(), Box::new(factory));
+    }
+
+    pub fn get(&self, factory_name: &str) -> Option<&FeatureExtractorFactory> {
+        self.factories
+            .get(factory_name)
+            .map(|factory| &**factory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nlu_utils::language::Language;
+    use nlu_utils::token::tokenize;
+    use regex::Regex;
+
+    fn create_regex_extractor(
+        args: &HashMap<String, ::serde_json::Value>,
+    ) -> Result<Box<FeatureExtractor>> {
+        let pattern = args.get("pattern")
+            .and_then(|pattern| pattern.as_str())
+            .ok_or_else(|| "missing pattern")?;
+        let regex = Regex::new(pattern)?;
+        Ok(Box::new(move |tokens: &[Token], token_index: usize| {
+            if regex.is_match(&tokens[token_index].value) {
+                Some("1".to_string())
+            } else {
+                None
+            }
+        }))
+    }
+
+    #[test]
+    fn registry_should_create_registered_extractor() {
+        // Given
+        let mut registry = FeatureExtractorRegistry::new();
+        registry.register("is_flight_number", create_regex_extractor);
+        let args = hashmap![
+            "pattern".to_string() => ::serde_json::Value::String("^[A-Z]{2}[0-9]+$".to_string()),
+        ];
+        let tokens = tokenize("book flight AF123", Language::EN);
+
+        // When
+        let extractor = registry
+            .get("is_flight_number")
+            .unwrap()
+            .create(&args)
+            .unwrap();
+        let features = (0..tokens.len())
+            .map(|token_index| extractor.compute(&tokens, token_index))
+            .collect::<Vec<_>>();
+
+        // Then
+        assert_eq!(vec![None, None, Some("1".to_string())], features);
+    }
+
+    #[test]
+    fn registry_should_not_find_unknown_extractor() {
+        // Given
+        let registry = FeatureExtractorRegistry::new();
+
+        // When
+        let factory = registry.get("is_flight_number");
+
+        // Then
+        assert!(factory.is_none());
+    }
+}
@@ -1,6 +1,10 @@
+use std::cmp::min;
+use std::ops::Range;
+
 use itertools::Itertools;
 
-use nlu_utils::token::{compute_all_ngrams, Token};
+use ngrams::NgramTrie;
+use nlu_utils::token::Token;
 use resources::gazetteer::Gazetteer;
 #[cfg(test)]
 use resources::gazetteer::HashSetGazetteer;
@@ -10,7 +14,8 @@ use resources::stemmer::StaticMapStemmer;
 use resources::word_clusterer::WordClusterer;
 use super::crf_utils::{get_scheme_prefix, TaggingScheme};
 use super::features_utils::{get_shape, get_word_chunk, initial_string_from_tokens};
-use snips_nlu_ontology::{BuiltinEntityKind, BuiltinEntityParser};
+use builtin_entity_parser::MultilingualBuiltinEntityParser;
+use snips_nlu_ontology::BuiltinEntityKind;
 use nlu_utils::range::ranges_overlap;
 use nlu_utils::string::normalize;
 
@@ -95,27 +100,40 @@ pub fn ngram<S: Stemmer, G: Gazetteer>(
     }
 }
 
-pub fn get_gazetteer_match<S: Stemmer, G: Gazetteer>(
+/// Returns the tag of the token within the longest ngram of the gazetteer containing it, the
+/// earliest one being used when several ngrams have the same length
+pub fn get_gazetteer_match<S: Stemmer>(
     tokens: &[Token],
     token_index: usize,
-    gazetteer: &G,
+    gazetteer: &NgramTrie<()>,
     stemmer: Option<&S>,
     tagging_scheme: TaggingScheme,
 ) -> Option<String> {
-    let normalized_tokens = normalize_tokens(tokens, stemmer);
-    let normalized_tokens_ref = normalized_tokens.iter().map(|t| &**t).collect_vec();
-    let mut filtered_ngrams =
-        compute_all_ngrams(&*normalized_tokens_ref, normalized_tokens_ref.len())
-            .into_iter()
-            .filter(|ngram_indexes| ngram_indexes.1.iter().any(|index| *index == token_index))
-            .collect_vec();
-
-    filtered_ngrams.sort_by_key(|ngrams| -(ngrams.1.len() as i64));
-
-    filtered_ngrams
-        .iter()
-        .find(|ngrams| gazetteer.contains(&ngrams.0))
-        .map(|ngrams| get_scheme_prefix(token_index, &ngrams.1, tagging_scheme).to_string())
+    let max_ngram_length = gazetteer.max_depth();
+    if token_index >= tokens.len() || max_ngram_length == 0 {
+        return None;
+    }
+    // Only the tokens which can be part of an ngram containing the token are normalized
+    let window_start = (token_index + 1).saturating_sub(max_ngram_length);
+    let window_end = min(tokens.len(), token_index + max_ngram_length);
+    let normalized_tokens = normalize_tokens(&tokens[window_start..window_end], stemmer);
+    let window_index = token_index - window_start;
+
+    let mut best_match: Option<Range<usize>> = None;
+    for start in 0..=window_index {
+        for (end, _) in gazetteer.matches_from(&normalized_tokens, start) {
+            let is_longer = best_match
+                .as_ref()
+                .map_or(true, |range| end - start > range.end - range.start);
+            if end > window_index && is_longer {
+                best_match = Some(start..end);
+            }
+        }
+    }
+    best_match.map(|range| {
+        let indexes = (window_start + range.start..window_start + range.end).collect_vec();
+        get_scheme_prefix(token_index, &indexes, tagging_scheme).to_string()
+    })
 }
 
 pub fn get_word_cluster<C: WordClusterer>(
@@ -132,7 +150,7 @@ pub fn get_word_cluster<C: WordClusterer>(
 pub fn get_builtin_entity_match(
     tokens: &[Token],
     token_index: usize,
-    parser: &BuiltinEntityParser,
+    parser: &MultilingualBuiltinEntityParser,
     builtin_entity_kind: BuiltinEntityKind,
     tagging_scheme: TaggingScheme,
 ) -> Option<String> {
@@ -359,13 +377,13 @@ mod tests {
     fn get_gazetteer_match_works() {
         // Given
         let language = NluUtilsLanguage::EN;
-        let gazetteer = HashSetGazetteer::from(
-            vec![
-                "bird".to_string(),
-                "blue bird".to_string(),
-                "beautiful blue bird".to_string(),
-            ].into_iter(),
-        );
+        let gazetteer: NgramTrie<()> = vec![
+            "bird".to_string(),
+            "blue bird".to_string(),
+            "beautiful blue bird".to_string(),
+        ].into_iter()
+            .map(|value| (value, ()))
+            .collect();
         let tagging_scheme = TaggingScheme::BILOU;
         let tokens = tokenize("I love this beautiful blue Bird !", language);
         let token_index = 5;
@@ -399,13 +417,13 @@ mod tests {
 
         let language = NluUtilsLanguage::EN;
         let stemmer = TestStemmer {};
-        let gazetteer = HashSetGazetteer::from(
-            vec![
-                "bird".to_string(),
-                "blue bird".to_string(),
-                "beautiful blue bird".to_string(),
-            ].into_iter(),
-        );
+        let gazetteer: NgramTrie<()> = vec![
+            "bird".to_string(),
+            "blue bird".to_string(),
+            "beautiful blue bird".to_string(),
+        ].into_iter()
+            .map(|value| (value, ()))
+            .collect();
 
         let tagging_scheme = TaggingScheme::BILOU;
         let tokens = tokenize("I love Blue Birds !", language);
@@ -431,13 +449,13 @@ mod tests {
         let tokens = tokenize("Let's meet tomorrow at 9pm ok ?", language);
         let token_index = 5; // 9pm
         let tagging_scheme = TaggingScheme::BILOU;
-        let parser = BuiltinEntityParser::get(Language::EN);
+        let parser = MultilingualBuiltinEntityParser::new(&[Language::EN]);
 
         // When
         let actual_annotation = get_builtin_entity_match(
             &tokens,
             token_index,
-            &*parser,
+            &parser,
             BuiltinEntityKind::Time,
             tagging_scheme,
         );
@@ -1,18 +1,37 @@
+#[cfg(feature = "crf")]
 pub mod crf_slot_filler;
-mod crf_utils;
-mod feature_processor;
+pub(crate) mod crf_tagger;
+pub(crate) mod crf_utils;
+pub(crate) mod feature_processor;
+mod feature_registry;
 mod features;
 mod features_utils;
 
 use snips_nlu_ontology::Slot;
 use errors::*;
+use intent_parser::TokenFeatures;
+use parse_context::ParseContext;
 
+#[cfg(feature = "crf")]
 pub use self::crf_slot_filler::*;
+pub use self::feature_registry::{FeatureExtractor, FeatureExtractorFactory,
+                                 FeatureExtractorRegistry};
 use self::crf_utils::TaggingScheme;
 use nlu_utils::token::Token;
 
 pub trait SlotFiller: Send + Sync {
     fn get_tagging_scheme(&self) -> TaggingScheme;
-    fn get_slots(&self, text: &str) -> Result<Vec<Slot>>;
-    fn get_sequence_probability(&self, tokens: &[Token], tags: Vec<String>) -> Result<f64>;
+    fn get_slots(&self, text: &str, context: &ParseContext) -> Result<Vec<Slot>>;
+    fn get_sequence_probability(
+        &self,
+        tokens: &[Token],
+        tags: Vec<String>,
+        context: &ParseContext,
+    ) -> Result<f64>;
+    fn get_features(&self, text: &str, context: &ParseContext) -> Result<Vec<TokenFeatures>>;
+
+    /// Returns the estimated number of bytes taken by the loaded weights of the slot filler
+    fn heap_size(&self) -> usize {
+        0
+    }
 }
@@ -1,6 +1,7 @@
 use std::ops::Range;
 
-use snips_nlu_ontology::{BuiltinEntityKind, BuiltinEntityParser, Slot, SlotValue};
+use builtin_entity_parser::MultilingualBuiltinEntityParser;
+use snips_nlu_ontology::{BuiltinEntityKind, Slot, SlotValue};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct InternalSlot {
@@ -33,7 +34,7 @@ pub fn convert_to_builtin_slot(slot: InternalSlot, slot_value: SlotValue) -> Slo
 pub fn resolve_builtin_slots(
     text: &str,
     slots: Vec<InternalSlot>,
-    parser: &BuiltinEntityParser,
+    parser: &MultilingualBuiltinEntityParser,
     filter_entity_kinds: Option<&[BuiltinEntityKind]>,
 ) -> Vec<Slot> {
     let builtin_entities = parser.extract_entities(text, filter_entity_kinds);
@@ -46,8 +47,8 @@ pub fn resolve_builtin_slots(
                     .find(|entity| {
                         entity.entity_kind == entity_kind && entity.range == slot.char_range
                     })
-                    .map(|rustling_entity| Some(rustling_entity.entity.clone()))
-                    .unwrap_or({
+                    .map(|rustling_entity| rustling_entity.entity.clone())
+                    .or_else(|| {
                         parser
                             .extract_entities(&slot.value, Some(&[entity_kind]))
                             .into_iter()
@@ -65,7 +66,8 @@ pub fn resolve_builtin_slots(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use snips_nlu_ontology::{AmountOfMoneyValue, Language, OrdinalValue, Precision};
+    use snips_nlu_ontology::{AmountOfMoneyValue, DurationValue, Language, OrdinalValue,
+                             Precision};
 
     #[test]
     fn resolve_builtin_slots_works() {
@@ -85,12 +87,12 @@ mod tests {
                 slot_name: "ranking".to_string(),
             },
         ];
-        let parser = BuiltinEntityParser::get(Language::EN);
+        let parser = MultilingualBuiltinEntityParser::new(&[Language::EN]);
 
         // When
         let filter_entity_kinds = &[BuiltinEntityKind::AmountOfMoney, BuiltinEntityKind::Ordinal];
         let actual_results =
-            resolve_builtin_slots(text, slots, &*parser, Some(filter_entity_kinds));
+            resolve_builtin_slots(text, slots, &parser, Some(filter_entity_kinds));
 
         // Then
         let expected_results = vec![
@@ -115,4 +117,46 @@ mod tests {
         ];
         assert_eq!(expected_results, actual_results);
     }
+
+    #[test]
+    fn resolve_builtin_slots_works_with_durations() {
+        // Given
+        let text = "Set a timer for 3 hours";
+        let slots = vec![
+            InternalSlot {
+                value: "3 hours".to_string(),
+                char_range: 16..23,
+                entity: "snips/duration".to_string(),
+                slot_name: "timer_duration".to_string(),
+            },
+        ];
+        let parser = MultilingualBuiltinEntityParser::new(&[Language::EN]);
+
+        // When
+        let filter_entity_kinds = &[BuiltinEntityKind::Duration];
+        let actual_results =
+            resolve_builtin_slots(text, slots, &parser, Some(filter_entity_kinds));
+
+        // Then
+        let expected_results = vec![
+            Slot {
+                raw_value: "3 hours".to_string(),
+                value: SlotValue::Duration(DurationValue {
+                    years: 0,
+                    quarters: 0,
+                    months: 0,
+                    weeks: 0,
+                    days: 0,
+                    hours: 3,
+                    minutes: 0,
+                    seconds: 0,
+                    precision: Precision::Exact,
+                }),
+                range: Some(16..23),
+                entity: "snips/duration".to_string(),
+                slot_name: "timer_duration".to_string(),
+            },
+        ];
+        assert_eq!(expected_results, actual_results);
+    }
 }
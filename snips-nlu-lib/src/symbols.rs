@@ -0,0 +1,116 @@
+//! Interning of the intent, entity and slot names, which are repeated in the configurations of
+//! every intent parser and slot filler of an engine
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use compression::intern;
+use memory_footprint::HeapSize;
+
+/// Cheaply clonable name which is stored once, however many engines and processing units
+/// reference it, and which is released with the last of them
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    /// Returns the symbol of the name, allocating it only if it is not already interned
+    pub fn intern(name: &str) -> Self {
+        Symbol(intern(name))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a str> for Symbol {
+    fn from(name: &'a str) -> Self {
+        Symbol::intern(name)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(name: String) -> Self {
+        Symbol::intern(&name)
+    }
+}
+
+/// Interned names are counted once by `MemoryFootprint::measure`, whatever their number of owners
+impl HeapSize for Symbol {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size()
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern_should_share_equal_names() {
+        // When
+        let symbol = Symbol::intern("MakeCoffee");
+        let other_symbol = Symbol::from("MakeCoffee".to_string());
+        let different_symbol = Symbol::intern("MakeTea");
+
+        // Then
+        assert_eq!(symbol, other_symbol);
+        assert!(Arc::ptr_eq(&symbol.0, &other_symbol.0));
+        assert_ne!(symbol, different_symbol);
+        assert_eq!("MakeCoffee", symbol.as_str());
+    }
+
+    #[test]
+    fn intern_should_release_unreferenced_names() {
+        // Given
+        let symbol = Symbol::intern("symbols_test_referenced_name");
+        let unreferenced_symbol = Symbol::intern("symbols_test_unreferenced_name");
+        let referenced_name = Arc::downgrade(&symbol.0);
+        let unreferenced_name = Arc::downgrade(&unreferenced_symbol.0);
+
+        // When
+        drop(unreferenced_symbol);
+
+        // Then
+        assert!(referenced_name.upgrade().is_some());
+        assert!(unreferenced_name.upgrade().is_none());
+        assert!(Arc::ptr_eq(
+            &symbol.0,
+            &Symbol::intern("symbols_test_referenced_name").0
+        ));
+    }
+}
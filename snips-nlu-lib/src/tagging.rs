@@ -0,0 +1,346 @@
+//! Tagging of the tokens of an input with the slots found in it, such as the BIO tags printed by
+//! the command line tool, which data labeling tools can reuse
+//!
+//! A token is tagged with a slot when its char range is contained in the range of the slot, the
+//! tokens only partially covered by a slot being left outside of it. When slots are nested, a
+//! token is tagged with the innermost slot containing it, the first one when several slots have
+//! the same range. Slots without range are ignored.
+//!
+//! The confidence of the slots tells which of the slots tagged by a labeling tool need to be
+//! reviewed. It is not a probability estimated by the slot filler, such as the probability of
+//! the CRF tags, but a coarse class of provenance telling how the value of the slot was resolved,
+//! see `slot_confidences`.
+
+use std::ops::Range;
+use std::ptr;
+
+use nlu_engine::{ParseExplanation, SlotResolution};
+use nlu_utils::token::Token;
+use slot_filler::crf_utils::{BEGINNING_PREFIX, INSIDE_PREFIX, OUTSIDE};
+use snips_nlu_ontology::{IntentParserResult, Slot, SlotValue};
+
+/// Token of an input along with the slot it is tagged with, see `SnipsNluEngine::tag`
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaggedToken {
+    pub value: String,
+    /// Char range of the token in the input
+    pub range: Range<usize>,
+    /// Name of the slot the token is tagged with, if any
+    pub slot_name: Option<String>,
+    /// Confidence of the slot the token is tagged with, which is a class of provenance of the slot
+    /// rather than a probability, see `slot_confidences`
+    pub confidence: Option<f32>,
+}
+
+/// Tags the tokens of the input of the result with its slots and their confidence
+pub fn tag_result(
+    tokens: Vec<Token>,
+    result: &IntentParserResult,
+    explanation: &ParseExplanation,
+) -> Vec<TaggedToken> {
+    let confidences = slot_confidences(result, explanation);
+    let slots = result.slots.as_ref().map(|slots| &**slots).unwrap_or(&[]);
+    let tagged_slots = tag_tokens(&tokens, slots);
+    tokens
+        .into_iter()
+        .zip(tagged_slots)
+        .map(|(token, slot)| {
+            let slot_index = slot.and_then(|slot| slots.iter().position(|s| ptr::eq(s, slot)));
+            TaggedToken {
+                value: token.value,
+                range: token.char_range,
+                slot_name: slot.map(|slot| slot.slot_name.clone()),
+                confidence: slot_index.map(|index| confidences[index]),
+            }
+        })
+        .collect()
+}
+
+/// Returns the slot each token is tagged with, if any
+pub fn tag_tokens<'a>(tokens: &[Token], slots: &'a [Slot]) -> Vec<Option<&'a Slot>> {
+    tokens
+        .iter()
+        .map(|token| {
+            slots
+                .iter()
+                .filter_map(|slot| slot.range.as_ref().map(|range| (slot, range)))
+                .filter(|&(_, range)| {
+                    range.start <= token.char_range.start && token.char_range.end <= range.end
+                })
+                .min_by_key(|&(_, range)| range.end - range.start)
+                .map(|(slot, _)| slot)
+        })
+        .collect()
+}
+
+/// Returns the BIO tag of each token, `B-` and `I-` followed by the slot name marking the first
+/// and the next tokens of a slot, and `O` the tokens outside of the slots
+///
+/// Adjacent slots each start with a `B-` tag, even when they have the same slot name, and so does
+/// the rest of a slot interrupted by a nested slot.
+pub fn bio_tags(tokens: &[Token], slots: &[Slot]) -> Vec<String> {
+    let mut previous_slot: Option<&Slot> = None;
+    tag_tokens(tokens, slots)
+        .into_iter()
+        .map(|slot| {
+            let tag = match slot {
+                Some(slot) if previous_slot.map_or(false, |previous| ptr::eq(previous, slot)) => {
+                    format!("{}{}", INSIDE_PREFIX, slot.slot_name)
+                }
+                Some(slot) => format!("{}{}", BEGINNING_PREFIX, slot.slot_name),
+                None => OUTSIDE.to_string(),
+            };
+            previous_slot = slot;
+            tag
+        })
+        .collect()
+}
+
+/// Returns the slot name and the char range of each span of the BIO tags, which is the inverse of
+/// `bio_tags` for slots that are not nested
+///
+/// An `I-` tag which does not follow a tag of the same slot name starts a new span, like a `B-`
+/// tag, and unknown tags are considered outside of the slots.
+pub fn bio_tags_to_spans(tokens: &[Token], tags: &[String]) -> Vec<(String, Range<usize>)> {
+    let mut spans: Vec<(String, Range<usize>)> = vec![];
+    let mut previous_slot_name: Option<&str> = None;
+    for (token, tag) in tokens.iter().zip(tags) {
+        let slot_name = if tag.starts_with(BEGINNING_PREFIX) || tag.starts_with(INSIDE_PREFIX) {
+            Some(&tag[2..])
+        } else {
+            None
+        };
+        if let Some(slot_name) = slot_name {
+            let continues_span =
+                tag.starts_with(INSIDE_PREFIX) && previous_slot_name == Some(slot_name);
+            if continues_span {
+                if let Some(&mut (_, ref mut range)) = spans.last_mut() {
+                    range.end = token.char_range.end;
+                }
+            } else {
+                spans.push((slot_name.to_string(), token.char_range.clone()));
+            }
+        }
+        previous_slot_name = slot_name;
+    }
+    spans
+}
+
+/// Returns the confidence of each slot of the result, from 0 to 1, in the order of the slots
+///
+/// The confidence only depends on how the value of the slot was resolved, and not on the
+/// probability of the tags of the slot filler, so that slots of the same provenance share the same
+/// confidence for a given intent probability. The slots of builtin entities and the values found
+/// in the gazetteers are trusted the most, the synonyms of the gazetteer values a bit less, and the
+/// values which are not in the gazetteer of an automatically extensible entity the least.
+/// Confidences are scaled by the probability of the intent.
+pub fn slot_confidences(result: &IntentParserResult, explanation: &ParseExplanation) -> Vec<f32> {
+    let intent_probability = result.intent.as_ref().map_or(1.0, |intent| intent.probability);
+    result
+        .slots
+        .iter()
+        .flat_map(|slots| slots.iter())
+        .zip(&explanation.slots)
+        .map(|(slot, &resolution)| intent_probability * resolution_confidence(slot, resolution))
+        .collect()
+}
+
+/// Returns the confidence of a slot resolved in the given way, regardless of the intent
+pub fn resolution_confidence(slot: &Slot, resolution: SlotResolution) -> f32 {
+    match resolution {
+        SlotResolution::Builtin => 1.0,
+        SlotResolution::Gazetteer => match slot.value {
+            SlotValue::Custom(ref value)
+                if value.value.to_lowercase() == slot.raw_value.to_lowercase() =>
+            {
+                1.0
+            }
+            _ => 0.8,
+        },
+        SlotResolution::AutomaticallyExtensible => 0.5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_ontology::{IntentClassifierResult, NumberValue};
+
+    fn slot(slot_name: &str, range: Range<usize>) -> Slot {
+        Slot {
+            raw_value: slot_name.to_string(),
+            value: SlotValue::Custom(slot_name.to_string().into()),
+            range: Some(range),
+            entity: slot_name.to_string(),
+            slot_name: slot_name.to_string(),
+        }
+    }
+
+    fn tokens() -> Vec<Token> {
+        // "two iced lattes and one tea"
+        vec![
+            Token::new("two".to_string(), 0..3, 0..3),
+            Token::new("iced".to_string(), 4..8, 4..8),
+            Token::new("lattes".to_string(), 9..15, 9..15),
+            Token::new("and".to_string(), 16..19, 16..19),
+            Token::new("one".to_string(), 20..23, 20..23),
+            Token::new("tea".to_string(), 24..27, 24..27),
+        ]
+    }
+
+    #[test]
+    fn bio_tags_works() {
+        // Given
+        let slots = vec![slot("number", 0..3), slot("beverage", 4..15)];
+
+        // When
+        let tags = bio_tags(&tokens(), &slots);
+
+        // Then
+        let expected_tags = vec!["B-number", "B-beverage", "I-beverage", "O", "O", "O"];
+        assert_eq!(expected_tags, tags);
+    }
+
+    #[test]
+    fn bio_tags_should_separate_adjacent_slots() {
+        // Given
+        let slots = vec![slot("beverage", 4..8), slot("beverage", 9..15)];
+
+        // When
+        let tags = bio_tags(&tokens(), &slots);
+
+        // Then
+        let expected_tags = vec!["O", "B-beverage", "B-beverage", "O", "O", "O"];
+        assert_eq!(expected_tags, tags);
+    }
+
+    #[test]
+    fn bio_tags_should_use_innermost_nested_slots() {
+        // Given
+        let slots = vec![slot("order", 0..27), slot("beverage", 4..15)];
+
+        // When
+        let tags = bio_tags(&tokens(), &slots);
+
+        // Then
+        let expected_tags =
+            vec!["B-order", "B-beverage", "I-beverage", "B-order", "I-order", "I-order"];
+        assert_eq!(expected_tags, tags);
+    }
+
+    #[test]
+    fn tag_tokens_should_ignore_partially_covered_tokens() {
+        // Given
+        let slots = vec![slot("beverage", 6..15), Slot { range: None, ..slot("number", 0..3) }];
+
+        // When
+        let tagged_slots = tag_tokens(&tokens(), &slots);
+
+        // Then
+        let slot_names: Vec<Option<&str>> = tagged_slots
+            .iter()
+            .map(|slot| slot.map(|slot| &*slot.slot_name))
+            .collect();
+        assert_eq!(vec![None, None, Some("beverage"), None, None, None], slot_names);
+    }
+
+    #[test]
+    fn bio_tags_to_spans_works() {
+        // Given
+        let tags: Vec<String> = vec!["B-number", "B-beverage", "I-beverage", "O", "I-number", "X"]
+            .into_iter()
+            .map(|tag| tag.to_string())
+            .collect();
+
+        // When
+        let spans = bio_tags_to_spans(&tokens(), &tags);
+
+        // Then
+        let expected_spans = vec![
+            ("number".to_string(), 0..3),
+            ("beverage".to_string(), 4..15),
+            ("number".to_string(), 20..23),
+        ];
+        assert_eq!(expected_spans, spans);
+    }
+
+    #[test]
+    fn slot_confidences_works() {
+        // Given
+        let result = IntentParserResult {
+            input: "two iced lattes and one tea".to_string(),
+            intent: Some(IntentClassifierResult {
+                intent_name: "MakeCoffee".to_string(),
+                probability: 0.5,
+            }),
+            slots: Some(vec![
+                Slot {
+                    value: SlotValue::Number(NumberValue { value: 2.0 }),
+                    ..slot("number", 0..3)
+                },
+                Slot {
+                    value: SlotValue::Custom("Iced latte".to_string().into()),
+                    ..slot("iced lattes", 4..15)
+                },
+                slot("tea", 24..27),
+                slot("and", 16..19),
+            ]),
+        };
+        let explanation = ParseExplanation {
+            parser_index: Some(1),
+            intent: None,
+            slots: vec![
+                SlotResolution::Builtin,
+                SlotResolution::Gazetteer,
+                SlotResolution::Gazetteer,
+                SlotResolution::AutomaticallyExtensible,
+            ],
+            input_truncated: false,
+            ambiguous_builtin_entities: vec![],
+        };
+
+        // When
+        let confidences = slot_confidences(&result, &explanation);
+
+        // Then
+        assert_eq!(vec![0.5, 0.4, 0.5, 0.25], confidences);
+    }
+
+    #[test]
+    fn tag_result_works() {
+        // Given
+        let result = IntentParserResult {
+            input: "two iced lattes and one tea".to_string(),
+            intent: Some(IntentClassifierResult {
+                intent_name: "MakeCoffee".to_string(),
+                probability: 0.5,
+            }),
+            slots: Some(vec![slot("beverage", 4..15)]),
+        };
+        let explanation = ParseExplanation {
+            slots: vec![SlotResolution::AutomaticallyExtensible],
+            ..ParseExplanation::default()
+        };
+
+        // When
+        let tagged_tokens = tag_result(tokens(), &result, &explanation);
+
+        // Then
+        let tagged_slots: Vec<(Option<&str>, Option<f32>)> = tagged_tokens
+            .iter()
+            .map(|token| (token.slot_name.as_ref().map(|name| &**name), token.confidence))
+            .collect();
+        let expected_tagged_slots = vec![
+            (None, None),
+            (Some("beverage"), Some(0.25)),
+            (Some("beverage"), Some(0.25)),
+            (None, None),
+            (None, None),
+            (None, None),
+        ];
+        assert_eq!(expected_tagged_slots, tagged_slots);
+        assert_eq!("lattes", tagged_tokens[2].value);
+        assert_eq!(9..15, tagged_tokens[2].range);
+    }
+}
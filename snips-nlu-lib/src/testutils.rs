@@ -1,15 +1,14 @@
 use std::fs;
 
-use ndarray::prelude::*;
 use serde;
 use serde_json;
 
 use utils::file_path;
 
-pub fn assert_epsilon_eq_array1(a: &Array1<f32>, b: &Array1<f32>, epsilon: f32) {
-    assert_eq!(a.dim(), b.dim());
-    for (index, elem_a) in a.indexed_iter() {
-        assert!(epsilon_eq(*elem_a, b[index], epsilon))
+pub fn assert_epsilon_eq_vec(a: &[f32], b: &[f32], epsilon: f32) {
+    assert_eq!(a.len(), b.len());
+    for (elem_a, elem_b) in a.iter().zip(b.iter()) {
+        assert!(epsilon_eq(*elem_a, *elem_b, epsilon))
     }
 }
 
@@ -0,0 +1,258 @@
+use std::ops::Range;
+
+use nlu_utils::token::Token;
+use snips_nlu_ontology::Language;
+use tokenizer::Tokenizer;
+
+/// Japanese particles split from the beginning of hiragana sequences, longest particles first
+const JAPANESE_PARTICLES: &[&str] = &[
+    "から", "まで", "より", "には", "では", "へ", "を", "は", "が", "に", "で", "と", "の", "も",
+];
+
+/// Korean particles split from the end of words, longest particles first
+const KOREAN_PARTICLES: &[&str] = &[
+    "에서는", "으로는", "에게서", "에서", "에게", "으로", "까지", "부터", "한테", "께서", "처럼",
+    "이랑", "에는", "은", "는", "을", "를", "에", "로", "와", "과", "의",
+];
+
+/// Tokenizer segmenting the languages which are not written with spaces between words, based on
+/// the scripts of the characters
+///
+/// - Japanese is split at each change of script, particles being split from hiragana sequences
+/// - Korean words, which are separated by spaces, are split from their trailing particles
+/// - in other languages, each Chinese character is a token
+///
+/// Sequences of letters and digits of other scripts are tokenized as words, and punctuation
+/// characters are tokens of their own.
+pub struct CjkTokenizer {
+    language: Language,
+}
+
+impl CjkTokenizer {
+    pub fn new(language: Language) -> Self {
+        Self { language }
+    }
+}
+
+/// Script of a char, as far as the segmentation of Chinese, Japanese and Korean is concerned
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Script {
+    Han,
+    Hiragana,
+    Katakana,
+    Hangul,
+    Alphanumeric,
+    Punctuation,
+    Whitespace,
+}
+
+impl Script {
+    pub fn of(c: char) -> Self {
+        match c as u32 {
+            0x3040..=0x309F => Script::Hiragana,
+            0x30A0..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => Script::Katakana,
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF => Script::Han,
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+            _ if c.is_whitespace() => Script::Whitespace,
+            _ if c.is_alphanumeric() => Script::Alphanumeric,
+            _ => Script::Punctuation,
+        }
+    }
+}
+
+/// Sequence of characters of the same script, with its byte and char ranges in the input
+struct Segment<'a> {
+    value: &'a str,
+    script: Script,
+    range: Range<usize>,
+    char_range: Range<usize>,
+}
+
+impl Tokenizer for CjkTokenizer {
+    fn tokenize(&self, input: &str) -> Vec<Token> {
+        let span = pipeline_span!("tokenization");
+        let _entered = span.enter();
+        let mut tokens = vec![];
+        for segment in split_scripts(input) {
+            match (self.language, segment.script) {
+                (_, Script::Punctuation) => {
+                    tokens.extend(split_every_char(&segment));
+                }
+                (Language::JA, Script::Hiragana) => {
+                    let particle = find_particle(JAPANESE_PARTICLES, |p| {
+                        segment.value.starts_with(p)
+                    });
+                    let particle_len = particle.map_or(0, |p| p.len());
+                    tokens.extend(split_at(&segment, particle_len));
+                }
+                (Language::KO, Script::Hangul) => {
+                    let particle = find_particle(KOREAN_PARTICLES, |p| {
+                        if !segment.value.ends_with(p) {
+                            return false;
+                        }
+                        let stem = &segment.value[..segment.value.len() - p.len()];
+                        // Single syllable particles are often the last syllable of a word
+                        let min_stem_chars = if p.chars().count() == 1 { 2 } else { 1 };
+                        stem.chars().count() >= min_stem_chars
+                    });
+                    let stem_len = segment.value.len() - particle.map_or(0, |p| p.len());
+                    tokens.extend(split_at(&segment, stem_len));
+                }
+                (Language::JA, _) | (Language::KO, _) => {
+                    tokens.extend(split_at(&segment, 0));
+                }
+                (_, Script::Han) => {
+                    tokens.extend(split_every_char(&segment));
+                }
+                _ => {
+                    tokens.extend(split_at(&segment, 0));
+                }
+            }
+        }
+        tokens
+    }
+}
+
+fn find_particle<F>(particles: &[&'static str], matches: F) -> Option<&'static str>
+where
+    F: Fn(&str) -> bool,
+{
+    particles.iter().cloned().find(|particle| matches(particle))
+}
+
+/// Splits the input into sequences of characters of the same script, whitespaces being dropped
+fn split_scripts(input: &str) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = vec![];
+    for (char_index, (byte_index, c)) in input.char_indices().enumerate() {
+        let script = Script::of(c);
+        let byte_end = byte_index + c.len_utf8();
+        if script == Script::Whitespace {
+            continue;
+        }
+        if let Some(segment) = segments.last_mut() {
+            if segment.script == script && segment.char_range.end == char_index {
+                segment.range.end = byte_end;
+                segment.char_range.end = char_index + 1;
+                segment.value = &input[segment.range.clone()];
+                continue;
+            }
+        }
+        segments.push(Segment {
+            value: &input[byte_index..byte_end],
+            script,
+            range: byte_index..byte_end,
+            char_range: char_index..char_index + 1,
+        });
+    }
+    segments
+}
+
+/// Splits a segment in two tokens at a byte offset, empty tokens being skipped
+fn split_at(segment: &Segment, offset: usize) -> Vec<Token> {
+    let (head, tail) = segment.value.split_at(offset);
+    let head_chars = head.chars().count();
+    let split_byte = segment.range.start + offset;
+    let split_char = segment.char_range.start + head_chars;
+    let mut tokens = vec![];
+    if !head.is_empty() {
+        tokens.push(Token::new(
+            head.to_string(),
+            segment.range.start..split_byte,
+            segment.char_range.start..split_char,
+        ));
+    }
+    if !tail.is_empty() {
+        tokens.push(Token::new(
+            tail.to_string(),
+            split_byte..segment.range.end,
+            split_char..segment.char_range.end,
+        ));
+    }
+    tokens
+}
+
+fn split_every_char(segment: &Segment) -> Vec<Token> {
+    segment
+        .value
+        .char_indices()
+        .enumerate()
+        .map(|(char_offset, (byte_offset, c))| {
+            let start = segment.range.start + byte_offset;
+            let char_start = segment.char_range.start + char_offset;
+            Token::new(
+                c.to_string(),
+                start..start + c.len_utf8(),
+                char_start..char_start + 1,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn japanese_tokenization_works() {
+        // Given
+        let tokenizer = CjkTokenizer::new(Language::JA);
+
+        // When
+        let tokens = tokenizer.tokenize_light("東京から大阪までコーヒーを2杯ください。");
+
+        // Then
+        let expected_tokens = vec![
+            "東京", "から", "大阪", "まで", "コーヒー", "を", "2", "杯", "ください", "。",
+        ];
+        assert_eq!(expected_tokens, tokens);
+    }
+
+    #[test]
+    fn korean_tokenization_works() {
+        // Given
+        let tokenizer = CjkTokenizer::new(Language::KO);
+
+        // When
+        let tokens = tokenizer.tokenize_light("서울에서 부산까지 고양이를 데려가!");
+
+        // Then
+        let expected_tokens = vec!["서울", "에서", "부산", "까지", "고양이", "를", "데려가", "!"];
+        assert_eq!(expected_tokens, tokens);
+    }
+
+    #[test]
+    fn chinese_tokenization_works() {
+        // Given
+        let tokenizer = CjkTokenizer::new(Language::EN);
+
+        // When
+        let tokens = tokenizer.tokenize_light("我想喝咖啡 please");
+
+        // Then
+        let expected_tokens = vec!["我", "想", "喝", "咖", "啡", "please"];
+        assert_eq!(expected_tokens, tokens);
+    }
+
+    #[test]
+    fn tokenize_should_compute_ranges() {
+        // Given
+        let tokenizer = CjkTokenizer::new(Language::JA);
+
+        // When
+        let tokens = tokenizer.tokenize("お茶を hot で");
+
+        // Then
+        let ranges = tokens
+            .iter()
+            .map(|token| (token.value.as_str(), token.range.clone(), token.char_range.clone()))
+            .collect::<Vec<_>>();
+        let expected_ranges = vec![
+            ("お", 0..3, 0..1),
+            ("茶", 3..6, 1..2),
+            ("を", 6..9, 2..3),
+            ("hot", 10..13, 4..7),
+            ("で", 14..17, 8..9),
+        ];
+        assert_eq!(expected_ranges, ranges);
+    }
+}
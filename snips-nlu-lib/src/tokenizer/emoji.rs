@@ -0,0 +1,170 @@
+use std::ops::Range;
+
+use nlu_utils::token::Token;
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+/// Returns whether the char is an emoji or a pictographic symbol
+pub fn is_emoji(c: char) -> bool {
+    match c as u32 {
+        0x2190..=0x21FF | 0x2300..=0x23FF | 0x25A0..=0x27BF | 0x2900..=0x297F => true,
+        0x2B00..=0x2BFF | 0x3030 | 0x303D | 0x3297 | 0x3299 => true,
+        0x1F000..=0x1FAFF => true,
+        _ => false,
+    }
+}
+
+/// Returns whether the char modifies the emoji it follows: variation selectors, skin tones,
+/// keycaps and tags
+fn is_emoji_modifier(c: char) -> bool {
+    match c as u32 {
+        0xFE0E | 0xFE0F | 0x1F3FB..=0x1F3FF | 0x20E3 | 0xE0020..=0xE007F => true,
+        _ => false,
+    }
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    match c as u32 {
+        0x1F1E6..=0x1F1FF => true,
+        _ => false,
+    }
+}
+
+/// Returns the char ranges of the emojis of the text
+///
+/// An emoji is a pictographic char along with its modifiers, the emojis joined with zero width
+/// joiners, such as families, and the pairs of regional indicators, which are flags, being single
+/// emojis.
+pub fn find_emojis(text: &str) -> Vec<Range<usize>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut emoji_ranges = vec![];
+    let mut index = 0;
+    while index < chars.len() {
+        if !is_emoji(chars[index]) {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        index += 1;
+        if is_regional_indicator(chars[start])
+            && index < chars.len()
+            && is_regional_indicator(chars[index])
+        {
+            index += 1;
+        }
+        loop {
+            if index < chars.len() && is_emoji_modifier(chars[index]) {
+                index += 1;
+            } else if index + 1 < chars.len()
+                && chars[index] == ZERO_WIDTH_JOINER
+                && is_emoji(chars[index + 1])
+            {
+                index += 2;
+            } else {
+                break;
+            }
+        }
+        emoji_ranges.push(start..index);
+    }
+    emoji_ranges
+}
+
+/// Returns the emoji without its variation selectors and skin tones, which is the form under
+/// which emojis are usually given aliases
+pub fn strip_emoji_modifiers(emoji: &str) -> String {
+    emoji.chars().filter(|c| !is_emoji_modifier(*c)).collect()
+}
+
+/// Splits the emojis from the tokens they are glued to, each emoji becoming a token
+pub fn split_emojis(tokens: Vec<Token>) -> Vec<Token> {
+    let mut split_tokens = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let emoji_ranges = find_emojis(&token.value);
+        if emoji_ranges.is_empty() {
+            split_tokens.push(token);
+            continue;
+        }
+        // Byte offsets of the chars of the token, followed by the length of the token
+        let byte_offsets: Vec<usize> = token
+            .value
+            .char_indices()
+            .map(|(byte_offset, _)| byte_offset)
+            .chain(Some(token.value.len()))
+            .collect();
+        let nb_chars = byte_offsets.len() - 1;
+        let mut pieces: Vec<Range<usize>> = vec![];
+        let mut position = 0;
+        for emoji_range in emoji_ranges {
+            if position < emoji_range.start {
+                pieces.push(position..emoji_range.start);
+            }
+            position = emoji_range.end;
+            pieces.push(emoji_range);
+        }
+        if position < nb_chars {
+            pieces.push(position..nb_chars);
+        }
+        for piece in pieces {
+            let byte_range = byte_offsets[piece.start]..byte_offsets[piece.end];
+            split_tokens.push(Token::new(
+                token.value[byte_range.clone()].to_string(),
+                token.range.start + byte_range.start..token.range.start + byte_range.end,
+                token.char_range.start + piece.start..token.char_range.start + piece.end,
+            ));
+        }
+    }
+    split_tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_emojis_works() {
+        // Given
+        let text = "ok👍🏽 for 👨\u{200D}👩\u{200D}👧 in 🇫🇷!";
+
+        // When
+        let emoji_ranges = find_emojis(text);
+
+        // Then
+        assert_eq!(vec![2..4, 9..14, 18..20], emoji_ranges);
+    }
+
+    #[test]
+    fn split_emojis_works() {
+        // Given
+        let tokens = vec![
+            Token::new("great👍👍".to_string(), 0..13, 0..7),
+            Token::new("thanks".to_string(), 14..20, 8..14),
+        ];
+
+        // When
+        let split_tokens = split_emojis(tokens);
+
+        // Then
+        let expected_tokens = vec![
+            Token::new("great".to_string(), 0..5, 0..5),
+            Token::new("👍".to_string(), 5..9, 5..6),
+            Token::new("👍".to_string(), 9..13, 6..7),
+            Token::new("thanks".to_string(), 14..20, 8..14),
+        ];
+        assert_eq!(expected_tokens, split_tokens);
+    }
+
+    #[test]
+    fn strip_emoji_modifiers_works() {
+        // Given
+        let emojis = vec!["👍🏽", "❤\u{FE0F}", "🙂"];
+
+        // When
+        let stripped_emojis: Vec<String> = emojis
+            .into_iter()
+            .map(strip_emoji_modifiers)
+            .collect();
+
+        // Then
+        assert_eq!(vec!["👍", "❤", "🙂"], stripped_emojis);
+    }
+}
@@ -0,0 +1,262 @@
+mod cjk;
+mod emoji;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use errors::*;
+use language::FromLanguage;
+use nlu_utils::language::Language as NluUtilsLanguage;
+use nlu_utils::token::{tokenize, tokenize_light, Token};
+use snips_nlu_ontology::Language;
+
+pub use self::cjk::{CjkTokenizer, Script};
+pub use self::emoji::{find_emojis, strip_emoji_modifiers};
+
+use self::emoji::split_emojis;
+
+/// Name of the builtin tokenizer segmenting Chinese, Japanese and Korean into words
+pub const CJK_TOKENIZER: &str = "cjk";
+
+/// Splits inputs into tokens, the same tokenizer being shared by all the units of an engine
+pub trait Tokenizer: Send + Sync {
+    fn tokenize(&self, input: &str) -> Vec<Token>;
+
+    /// Returns the values of the tokens of the input
+    fn tokenize_light(&self, input: &str) -> Vec<String> {
+        self.tokenize(input)
+            .into_iter()
+            .map(|token| token.value)
+            .collect()
+    }
+}
+
+/// Tokenizer of `snips-nlu-utils`, which is used by the Python library at training time
+pub struct DefaultTokenizer {
+    language: NluUtilsLanguage,
+}
+
+impl DefaultTokenizer {
+    pub fn new(language: Language) -> Self {
+        Self {
+            language: NluUtilsLanguage::from_language(language),
+        }
+    }
+}
+
+impl Tokenizer for DefaultTokenizer {
+    fn tokenize(&self, input: &str) -> Vec<Token> {
+        let span = pipeline_span!("tokenization");
+        let _entered = span.enter();
+        tokenize(input, self.language)
+    }
+
+    fn tokenize_light(&self, input: &str) -> Vec<String> {
+        tokenize_light(input, self.language)
+    }
+}
+
+/// Tokenizer splitting the emojis glued to words from the tokens of another tokenizer, each
+/// emoji becoming a token of its own
+pub struct EmojiSplittingTokenizer {
+    tokenizer: Arc<Tokenizer>,
+}
+
+impl EmojiSplittingTokenizer {
+    pub fn new(tokenizer: Arc<Tokenizer>) -> Self {
+        Self { tokenizer }
+    }
+}
+
+impl Tokenizer for EmojiSplittingTokenizer {
+    fn tokenize(&self, input: &str) -> Vec<Token> {
+        split_emojis(self.tokenizer.tokenize(input))
+    }
+}
+
+/// Returns the tokenizer used when the configuration doesn't name a custom one
+pub fn default_tokenizer(language: Language) -> Arc<Tokenizer> {
+    Arc::new(DefaultTokenizer::new(language))
+}
+
+/// Builds a tokenizer for the language of the engine
+pub trait TokenizerFactory: Send + Sync {
+    fn create(&self, language: Language) -> Result<Box<Tokenizer>>;
+}
+
+impl<F> TokenizerFactory for F
+where
+    F: Fn(Language) -> Result<Box<Tokenizer>> + Send + Sync,
+{
+    fn create(&self, language: Language) -> Result<Box<Tokenizer>> {
+        self(language)
+    }
+}
+
+/// Factories of the tokenizers, indexed by the name used in the `tokenizer` field of the engine
+/// configuration, the builtin tokenizers being registered along with the custom ones
+pub struct TokenizerRegistry {
+    factories: HashMap<String, Box<TokenizerFactory>>,
+}
+
+impl Default for TokenizerRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            factories: HashMap::new(),
+        };
+        registry.register(CJK_TOKENIZER, create_cjk_tokenizer);
+        registry
+    }
+}
+
+impl TokenizerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<F>(&mut self, tokenizer_name: &str, factory: F)
+    where
+        F: TokenizerFactory + 'static,
+    {
+        self.factories
+            .insert(tokenizer_name.to_string(), Box::new(factory));
+    }
+
+    pub fn get(&self, tokenizer_name: &str) -> Option<&TokenizerFactory> {
+        self.factories
+            .get(tokenizer_name)
+            .map(|factory| &**factory)
+    }
+
+    /// Returns the tokenizer registered under `tokenizer_name` for the language, or the default
+    /// tokenizer of the language when no name is given
+    pub fn create(
+        &self,
+        tokenizer_name: Option<&str>,
+        language: Language,
+    ) -> Result<Arc<Tokenizer>> {
+        if let Some(tokenizer_name) = tokenizer_name {
+            let factory = self.get(tokenizer_name)
+                .ok_or_else(|| format!("Unknown tokenizer: {}", tokenizer_name))?;
+            Ok(Arc::from(factory.create(language)?))
+        } else {
+            Ok(default_tokenizer(language))
+        }
+    }
+
+    /// Returns the tokenizer of `create`, which also splits the emojis glued to words when
+    /// `split_emojis` is set, see the `split_emojis` field of the engine configuration
+    pub fn create_with_emoji_splitting(
+        &self,
+        tokenizer_name: Option<&str>,
+        language: Language,
+        split_emojis: bool,
+    ) -> Result<Arc<Tokenizer>> {
+        let tokenizer = self.create(tokenizer_name, language)?;
+        if split_emojis {
+            Ok(Arc::new(EmojiSplittingTokenizer::new(tokenizer)))
+        } else {
+            Ok(tokenizer)
+        }
+    }
+}
+
+fn create_cjk_tokenizer(language: Language) -> Result<Box<Tokenizer>> {
+    Ok(Box::new(CjkTokenizer::new(language)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Splits the words joined with underscores
+    struct UnderscoreTokenizer {
+        tokenizer: DefaultTokenizer,
+    }
+
+    impl Tokenizer for UnderscoreTokenizer {
+        fn tokenize(&self, input: &str) -> Vec<Token> {
+            self.tokenizer.tokenize(&input.replace("_", " "))
+        }
+    }
+
+    fn create_underscore_tokenizer(language: Language) -> Result<Box<Tokenizer>> {
+        Ok(Box::new(UnderscoreTokenizer {
+            tokenizer: DefaultTokenizer::new(language),
+        }))
+    }
+
+    #[test]
+    fn registry_should_create_registered_tokenizer() {
+        // Given
+        let mut registry = TokenizerRegistry::new();
+        registry.register("underscore", create_underscore_tokenizer);
+
+        // When
+        let tokenizer = registry.create(Some("underscore"), Language::EN).unwrap();
+        let tokens = tokenizer.tokenize_light("play some lo_fi music");
+
+        // Then
+        assert_eq!(vec!["play", "some", "lo", "fi", "music"], tokens);
+    }
+
+    #[test]
+    fn registry_should_create_default_tokenizer() {
+        // Given
+        let registry = TokenizerRegistry::new();
+
+        // When
+        let tokenizer = registry.create(None, Language::EN).unwrap();
+        let tokens = tokenizer.tokenize_light("play some lo_fi music");
+
+        // Then
+        let expected_tokens = tokenize_light("play some lo_fi music", NluUtilsLanguage::EN);
+        assert_eq!(expected_tokens, tokens);
+    }
+
+    #[test]
+    fn registry_should_create_builtin_tokenizer() {
+        // Given
+        let registry = TokenizerRegistry::new();
+
+        // When
+        let tokenizer = registry.create(Some(CJK_TOKENIZER), Language::JA).unwrap();
+        let tokens = tokenizer.tokenize_light("紅茶をください");
+
+        // Then
+        assert_eq!(vec!["紅茶", "を", "ください"], tokens);
+    }
+
+    #[test]
+    fn registry_should_split_emojis_when_enabled() {
+        // Given
+        let registry = TokenizerRegistry::new();
+        let input = "thanks👍 see you🙂";
+
+        // When
+        let tokens = registry
+            .create_with_emoji_splitting(None, Language::EN, true)
+            .unwrap()
+            .tokenize_light(input);
+        let default_tokens = registry
+            .create_with_emoji_splitting(None, Language::EN, false)
+            .unwrap()
+            .tokenize_light(input);
+
+        // Then
+        assert_eq!(vec!["thanks", "👍", "see", "you", "🙂"], tokens);
+        assert_eq!(tokenize_light(input, NluUtilsLanguage::EN), default_tokens);
+    }
+
+    #[test]
+    fn registry_should_fail_on_unknown_tokenizer() {
+        // Given
+        let registry = TokenizerRegistry::new();
+
+        // When
+        let tokenizer = registry.create(Some("underscore"), Language::EN);
+
+        // Then
+        assert!(tokenizer.is_err());
+    }
+}
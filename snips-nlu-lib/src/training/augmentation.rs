@@ -0,0 +1,253 @@
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use configurations::UtteranceChunk;
+use errors::*;
+use resources::shared::get_shared_resources;
+use snips_nlu_ontology::{BuiltinEntityKind, Language};
+use training::dataset::{Dataset, DatasetUtterance};
+use training::get_tokenizer;
+
+/// Settings of the data augmentation, which mirror those of the Python library
+#[derive(Debug, Clone, PartialEq)]
+pub struct AugmentationConfig {
+    /// Minimum number of utterances of each intent, utterances being generated for the intents
+    /// having fewer utterances
+    pub min_utterances: usize,
+    /// Number of noise utterances generated for the None intent, relative to the mean number of
+    /// utterances per intent, a positive factor requiring the noise words of the language to be
+    /// loaded with `load_resources`
+    pub noise_factor: f32,
+    /// Seed of the random generator, the same seed always generating the same utterances
+    pub seed: u64,
+}
+
+impl Default for AugmentationConfig {
+    fn default() -> Self {
+        Self {
+            min_utterances: 200,
+            noise_factor: 5.,
+            seed: 42,
+        }
+    }
+}
+
+impl Dataset {
+    /// Returns the dataset where each intent has at least `config.min_utterances` utterances
+    pub fn augment(&self, config: &AugmentationConfig) -> Dataset {
+        let mut augmented_dataset = self.clone();
+        for (intent_index, intent_name) in self.intent_names().into_iter().enumerate() {
+            let nb_utterances = self.intents[intent_name].utterances.len();
+            if nb_utterances >= config.min_utterances {
+                continue;
+            }
+            let generated_utterances = self.generate_utterances(
+                intent_name,
+                config.min_utterances - nb_utterances,
+                config.seed.wrapping_add(intent_index as u64),
+            );
+            if let Some(intent) = augmented_dataset.intents.get_mut(intent_name) {
+                intent.utterances.extend(generated_utterances);
+            }
+        }
+        augmented_dataset
+    }
+
+    /// Generates utterances of an intent from its utterances, whose slot values are replaced by
+    /// values of their entities sampled from the dataset
+    ///
+    /// The values of builtin entities are left unchanged. No utterance is generated for an
+    /// unknown intent or an intent without utterances.
+    pub fn generate_utterances(
+        &self,
+        intent_name: &str,
+        nb_utterances: usize,
+        seed: u64,
+    ) -> Vec<DatasetUtterance> {
+        let utterances = match self.intents.get(intent_name) {
+            Some(intent) if !intent.utterances.is_empty() => &intent.utterances,
+            _ => return vec![],
+        };
+        let mut rng = Rng::new(seed);
+        (0..nb_utterances)
+            .map(|index| {
+                let utterance = &utterances[index % utterances.len()];
+                let data = utterance
+                    .data
+                    .iter()
+                    .map(|chunk| UtteranceChunk {
+                        text: self.sample_chunk_text(chunk, &mut rng),
+                        entity: chunk.entity.clone(),
+                        slot_name: chunk.slot_name.clone(),
+                    })
+                    .collect();
+                DatasetUtterance { data }
+            })
+            .collect()
+    }
+
+    /// Generates utterances of the None intent made of the noise words of the language, whose
+    /// lengths are sampled from the utterances of the dataset
+    ///
+    /// Noise words are taken from the resources loaded with `load_resources`, an error being
+    /// returned when utterances are requested while there are none.
+    pub fn generate_noise(&self, nb_utterances: usize, seed: u64) -> Result<Vec<String>> {
+        let language = Language::from_str(&self.language)?;
+        let tokenizer = get_tokenizer(language)?;
+        let noise_words = get_shared_resources(language)
+            .map(|resources| {
+                let mut noise_words = resources.noise_words.iter().cloned().collect_vec();
+                noise_words.sort();
+                noise_words
+            })
+            .unwrap_or_else(Vec::new);
+        let utterances_lengths = self.intent_names()
+            .into_iter()
+            .flat_map(|intent_name| self.intents[intent_name].utterances.iter())
+            .map(|utterance| tokenizer.tokenize_light(&utterance.text()).len())
+            .filter(|length| *length > 0)
+            .collect_vec();
+        if nb_utterances == 0 || utterances_lengths.is_empty() {
+            return Ok(vec![]);
+        }
+        if noise_words.is_empty() {
+            bail!(
+                "No noise words available in language '{}', the resources containing them must \
+                 be loaded with `load_resources` before generating noise",
+                self.language
+            );
+        }
+        let mut rng = Rng::new(seed);
+        Ok((0..nb_utterances)
+            .map(|_| {
+                let length = *rng.choose(&utterances_lengths);
+                (0..length).map(|_| rng.choose(&noise_words).clone()).join(" ")
+            })
+            .collect())
+    }
+
+    fn sample_chunk_text(&self, chunk: &UtteranceChunk, rng: &mut Rng) -> String {
+        match chunk.entity {
+            Some(ref entity_name) if BuiltinEntityKind::from_identifier(entity_name).is_err() => {
+                let variants = self.entity_variants(entity_name);
+                if variants.is_empty() {
+                    chunk.text.clone()
+                } else {
+                    rng.choose(&variants).0.clone()
+                }
+            }
+            _ => chunk.text.clone(),
+        }
+    }
+}
+
+/// Xorshift pseudo-random generator, which makes the augmentation and the training reproducible
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // The state of a xorshift generator must not be zero
+        let state = seed ^ 0x9e37_79b9_7f4a_7c15;
+        Rng(if state == 0 { 1 } else { state })
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[(self.next_u64() % items.len() as u64) as usize]
+    }
+
+    /// Shuffles the items in place with the Fisher-Yates algorithm
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            let other_index = (self.next_u64() % (index as u64 + 1)) as usize;
+            items.swap(index, other_index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+    use utils::file_path;
+
+    fn beverage_dataset() -> Dataset {
+        let path = file_path("tests/datasets/beverage_dataset.json");
+        Dataset::from_reader(File::open(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn generate_utterances_works() {
+        // Given
+        let dataset = beverage_dataset();
+
+        // When
+        let utterances = dataset.generate_utterances("MakeTea", 20, 1);
+
+        // Then
+        assert_eq!(20, utterances.len());
+        let temperatures = vec!["hot", "warm", "boiling", "cold", "iced"];
+        for utterance in utterances {
+            for chunk in utterance.data {
+                if chunk.slot_name.is_some() {
+                    assert!(temperatures.contains(&&*chunk.text));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generate_utterances_should_be_reproducible() {
+        // Given
+        let dataset = beverage_dataset();
+
+        // When
+        let utterances = dataset.generate_utterances("MakeTea", 10, 3);
+        let other_utterances = dataset.generate_utterances("MakeTea", 10, 3);
+
+        // Then
+        let texts = utterances.iter().map(|u| u.text()).collect_vec();
+        let other_texts = other_utterances.iter().map(|u| u.text()).collect_vec();
+        assert_eq!(texts, other_texts);
+    }
+
+    #[test]
+    fn generate_noise_should_fail_without_noise_words() {
+        // Given
+        let dataset = beverage_dataset();
+
+        // When
+        let noise = dataset.generate_noise(10, 1);
+        let empty_noise = dataset.generate_noise(0, 1);
+
+        // Then
+        assert!(noise.is_err());
+        assert_eq!(Some(vec![]), empty_noise.ok());
+    }
+
+    #[test]
+    fn augment_works() {
+        // Given
+        let dataset = beverage_dataset();
+        let config = AugmentationConfig {
+            min_utterances: 12,
+            ..AugmentationConfig::default()
+        };
+
+        // When
+        let augmented_dataset = dataset.augment(&config);
+
+        // Then
+        assert_eq!(12, augmented_dataset.intents["MakeTea"].utterances.len());
+        assert_eq!(12, augmented_dataset.intents["MakeCoffee"].utterances.len());
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+use itertools::Itertools;
+
+use configurations::UtteranceChunk;
+use errors::*;
+use snips_nlu_ontology::BuiltinEntityKind;
+
+/// Training dataset, in the JSON format used by the Python library
+#[derive(Debug, Deserialize, Clone)]
+pub struct Dataset {
+    pub language: String,
+    pub intents: HashMap<String, IntentData>,
+    pub entities: HashMap<String, EntityData>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct IntentData {
+    pub utterances: Vec<DatasetUtterance>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DatasetUtterance {
+    pub data: Vec<UtteranceChunk>,
+}
+
+/// Custom entity, builtin entities being declared with an empty object
+#[derive(Debug, Deserialize, Clone)]
+pub struct EntityData {
+    #[serde(default)]
+    pub data: Vec<EntityValue>,
+    #[serde(default = "default_true")]
+    pub use_synonyms: bool,
+    #[serde(default = "default_true")]
+    pub automatically_extensible: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct EntityValue {
+    pub value: String,
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl DatasetUtterance {
+    pub fn text(&self) -> String {
+        self.data.iter().map(|chunk| &*chunk.text).collect()
+    }
+}
+
+impl Dataset {
+    /// Parses a dataset in the JSON format used by the Python library
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        Ok(::serde_json::from_reader(reader)?)
+    }
+
+    /// Returns the names of the intents, sorted alphabetically
+    pub fn intent_names(&self) -> Vec<&String> {
+        let mut intent_names = self.intents.keys().collect_vec();
+        intent_names.sort();
+        intent_names
+    }
+
+    /// Returns the names of the custom entities, sorted alphabetically
+    pub fn custom_entity_names(&self) -> Vec<&String> {
+        let mut entity_names = self.entities
+            .keys()
+            .filter(|entity| BuiltinEntityKind::from_identifier(entity).is_err())
+            .collect_vec();
+        entity_names.sort();
+        entity_names
+    }
+
+    /// Returns the entities of the slots of an intent, indexed by slot name
+    pub fn slot_name_mapping(&self, intent_name: &str) -> HashMap<String, String> {
+        self.intents
+            .get(intent_name)
+            .map(|intent| {
+                intent
+                    .utterances
+                    .iter()
+                    .flat_map(|utterance| utterance.data.iter())
+                    .filter_map(|chunk| match (&chunk.slot_name, &chunk.entity) {
+                        (&Some(ref slot_name), &Some(ref entity)) => {
+                            Some((slot_name.clone(), entity.clone()))
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_else(HashMap::new)
+    }
+
+    /// Returns the variants of a custom entity mapped to the value they resolve to, which are its
+    /// values, its synonyms when they are used, and the slot values found in the utterances
+    pub fn entity_variants(&self, entity_name: &str) -> Vec<(String, String)> {
+        let mut variants: Vec<(String, String)> = vec![];
+        if let Some(entity) = self.entities.get(entity_name) {
+            for entity_value in &entity.data {
+                variants.push((entity_value.value.clone(), entity_value.value.clone()));
+                if entity.use_synonyms {
+                    for synonym in &entity_value.synonyms {
+                        variants.push((synonym.clone(), entity_value.value.clone()));
+                    }
+                }
+            }
+        }
+        let utterance_values = self.intent_names()
+            .into_iter()
+            .flat_map(|intent_name| self.intents[intent_name].utterances.iter())
+            .flat_map(|utterance| utterance.data.iter())
+            .filter(|chunk| chunk.entity.as_ref().map_or(false, |e| e == entity_name))
+            .map(|chunk| chunk.text.clone())
+            .collect_vec();
+        for value in utterance_values {
+            if variants.iter().all(|&(ref variant, _)| *variant != value) {
+                variants.push((value.clone(), value));
+            }
+        }
+        variants
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::{BTreeSet, HashMap};
+
+use itertools::Itertools;
+
+use configurations::DeterministicParserConfiguration;
+use errors::*;
+use language::FromLanguage;
+use nlu_utils::language::Language as NluUtilsLanguage;
+use nlu_utils::token::tokenize_light;
+use snips_nlu_ontology::Language;
+use training::dataset::{Dataset, DatasetUtterance};
+
+/// Builds the rules of the deterministic intent parser from the utterances of the dataset
+///
+/// Each utterance gives a rule where its slots are replaced by placeholders, rules shared by
+/// several intents being discarded as they are ambiguous.
+pub fn train_deterministic_parser(
+    dataset: &Dataset,
+    language: Language,
+) -> Result<DeterministicParserConfiguration> {
+    let mut slot_names_to_entities: HashMap<String, String> = HashMap::new();
+    let mut rules: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for intent_name in dataset.intent_names() {
+        for (slot_name, entity) in dataset.slot_name_mapping(intent_name) {
+            if let Some(other_entity) = slot_names_to_entities.get(&slot_name) {
+                if *other_entity != entity {
+                    bail!(
+                        "Slot '{}' is used with entities '{}' and '{}'",
+                        slot_name,
+                        other_entity,
+                        entity
+                    );
+                }
+            }
+            slot_names_to_entities.insert(slot_name, entity);
+        }
+        let intent_rules = dataset.intents[intent_name]
+            .utterances
+            .iter()
+            .map(|utterance| utterance_to_rule(utterance, language))
+            .filter(|rule| !rule.is_empty())
+            .collect();
+        rules.insert(intent_name.clone(), intent_rules);
+    }
+
+    let mut intents_per_rule: HashMap<String, usize> = HashMap::new();
+    for rule in rules.values().flat_map(|intent_rules| intent_rules.iter()) {
+        *intents_per_rule.entry(rule.clone()).or_insert(0) += 1;
+    }
+
+    Ok(DeterministicParserConfiguration {
+        language_code: dataset.language.clone(),
+        patterns: HashMap::new(),
+        group_names_to_slot_names: HashMap::new(),
+        slot_names_to_entities,
+        rules: Some(
+            rules
+                .into_iter()
+                .map(|(intent_name, intent_rules)| {
+                    let unambiguous_rules = intent_rules
+                        .into_iter()
+                        .filter(|rule| intents_per_rule[rule] == 1)
+                        .collect();
+                    (intent_name, unambiguous_rules)
+                })
+                .collect(),
+        ),
+        tag_all_builtin_entities: false,
+    })
+}
+
+pub fn utterance_to_rule(utterance: &DatasetUtterance, language: Language) -> String {
+    utterance
+        .data
+        .iter()
+        .map(|chunk| {
+            if let Some(ref slot_name) = chunk.slot_name {
+                format!("{{{}}}", slot_name)
+            } else {
+                tokenize_light(&chunk.text, NluUtilsLanguage::from_language(language)).join(" ")
+            }
+        })
+        .filter(|item| !item.is_empty())
+        .join(" ")
+}
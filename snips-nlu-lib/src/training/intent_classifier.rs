@@ -0,0 +1,265 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use itertools::Itertools;
+
+use configurations::{FeaturizerConfigConfiguration, FeaturizerConfiguration,
+                     IntentClassifierConfiguration, TfIdfVectorizerConfiguration};
+use errors::*;
+use intent_classifier::Featurizer;
+use resources::stemmer::StaticMapStemmer;
+use snips_nlu_ontology::Language;
+use tokenizer::Tokenizer;
+use training::augmentation::Rng;
+use training::dataset::Dataset;
+use training::normalize_variant;
+
+const NB_EPOCHS: usize = 300;
+const BATCH_SIZE: usize = 16;
+const LEARNING_RATE: f32 = 1.0;
+const L2_REGULARIZATION: f32 = 1e-3;
+const MIN_WEIGHTS_SCALE: f32 = 1e-6;
+pub const TRAINING_SEED: u64 = 42;
+
+/// Non zero features of an example, as (index, value) pairs
+pub type SparseFeatures = Vec<(usize, f32)>;
+
+/// Trains a one-vs-rest logistic regression on the tf-idf features of the utterances
+///
+/// The noise utterances are examples of the None intent, along with empty feature vectors so that
+/// inputs made of words unknown to the model are not classified in any intent.
+pub fn train_intent_classifier(
+    dataset: &Dataset,
+    noise_utterances: &[String],
+    language: Language,
+    tokenizer: &Arc<Tokenizer>,
+) -> Result<IntentClassifierConfiguration> {
+    let intent_names = dataset.intent_names();
+    if intent_names.is_empty() {
+        bail!("Dataset has no intent");
+    }
+    let mut featurizer_config = FeaturizerConfiguration {
+        language_code: dataset.language.clone(),
+        tfidf_vectorizer: TfIdfVectorizerConfiguration {
+            idf_diag: vec![],
+            vocab: HashMap::new(),
+        },
+        config: FeaturizerConfigConfiguration {
+            sublinear_tf: false,
+        },
+        best_features: vec![],
+        entity_utterances_to_feature_names: get_entity_utterances_to_feature_names(
+            dataset,
+            language,
+            &**tokenizer,
+        ),
+    };
+
+    let mut texts: Vec<String> = vec![];
+    let mut labels: Vec<usize> = vec![];
+    for (intent_index, intent_name) in intent_names.iter().enumerate() {
+        for utterance in &dataset.intents[*intent_name].utterances {
+            texts.push(utterance.text());
+            labels.push(intent_index);
+        }
+    }
+    for noise_utterance in noise_utterances {
+        texts.push(noise_utterance.clone());
+        labels.push(intent_names.len());
+    }
+
+    let preprocessing_featurizer =
+        Featurizer::new_with_tokenizer(featurizer_config.clone(), tokenizer.clone());
+    let documents = texts
+        .iter()
+        .map(|text| {
+            preprocessing_featurizer
+                .preprocess_query(text)
+                .into_iter()
+                .collect::<HashSet<String>>()
+        })
+        .collect_vec();
+    let vocabulary = documents
+        .iter()
+        .flat_map(|tokens| tokens.iter())
+        .collect::<BTreeSet<_>>();
+    if vocabulary.is_empty() {
+        bail!("Dataset has no word to train the intent classifier with");
+    }
+    let nb_documents = documents.len() as f32;
+    featurizer_config.tfidf_vectorizer.idf_diag = vocabulary
+        .iter()
+        .map(|word| {
+            let document_frequency = documents.iter().filter(|d| d.contains(*word)).count();
+            ((1. + nb_documents) / (1. + document_frequency as f32)).ln() + 1.
+        })
+        .collect();
+    featurizer_config.tfidf_vectorizer.vocab = vocabulary
+        .iter()
+        .enumerate()
+        .map(|(index, word)| (word.to_string(), index))
+        .collect();
+    featurizer_config.best_features = (0..vocabulary.len()).collect();
+
+    let featurizer = Featurizer::new_with_tokenizer(featurizer_config.clone(), tokenizer.clone());
+    let mut features = texts
+        .iter()
+        .map(|text| Ok(to_sparse_features(&featurizer.transform(text)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let nb_intent_utterances = texts.len() - noise_utterances.len();
+    let nb_none_examples = (nb_intent_utterances + intent_names.len() - 1) / intent_names.len();
+    for _ in 0..nb_none_examples.max(1) {
+        features.push(vec![]);
+        labels.push(intent_names.len());
+    }
+
+    let (intercept, coeffs): (Vec<f32>, Vec<Vec<f32>>) = (0..intent_names.len() + 1)
+        .map(|class| {
+            let targets = labels
+                .iter()
+                .map(|label| if *label == class { 1. } else { 0. })
+                .collect_vec();
+            fit_binary_logistic_regression(
+                &features,
+                &targets,
+                (0., vec![0.; vocabulary.len()]),
+                NB_EPOCHS,
+                TRAINING_SEED,
+            )
+        })
+        .unzip();
+
+    let mut intent_list = intent_names
+        .into_iter()
+        .map(|intent_name| Some(intent_name.clone()))
+        .collect_vec();
+    intent_list.push(None);
+
+    Ok(IntentClassifierConfiguration {
+        featurizer: Some(featurizer_config),
+        intercept: Some(intercept),
+        coeffs: Some(coeffs),
+        intent_list,
+        calibration: None,
+    })
+}
+
+/// Maps the normalized variants of the custom entities to the names of the features they
+/// activate, in the way the featurizer looks them up
+fn get_entity_utterances_to_feature_names(
+    dataset: &Dataset,
+    language: Language,
+    tokenizer: &Tokenizer,
+) -> HashMap<String, Vec<String>> {
+    let stemmer = StaticMapStemmer::new(language).ok();
+    let mut entity_utterances_to_feature_names: HashMap<String, Vec<String>> = HashMap::new();
+    for entity_name in dataset.custom_entity_names() {
+        let feature_name = format!("entityfeature{}", entity_name);
+        for (variant, _) in dataset.entity_variants(entity_name) {
+            let normalized_variant = normalize_variant(&variant, tokenizer, stemmer.as_ref());
+            if normalized_variant.is_empty() {
+                continue;
+            }
+            let feature_names = entity_utterances_to_feature_names
+                .entry(normalized_variant)
+                .or_insert_with(Vec::new);
+            if !feature_names.contains(&feature_name) {
+                feature_names.push(feature_name.clone());
+            }
+        }
+    }
+    entity_utterances_to_feature_names
+}
+
+pub fn to_sparse_features(features: &[f32]) -> SparseFeatures {
+    features
+        .iter()
+        .enumerate()
+        .filter(|&(_, value)| *value != 0.)
+        .map(|(index, value)| (index, *value))
+        .collect()
+}
+
+/// Fits the intercept and the weights of an L2 regularized logistic regression with mini-batch
+/// stochastic gradient descent, starting from the given intercept and weights
+///
+/// The weights are stored as `scale * raw_weights`, so that a step only updates the weights of
+/// the features which are active in the batch while the regularization shrinks all of them.
+pub fn fit_binary_logistic_regression(
+    features: &[SparseFeatures],
+    targets: &[f32],
+    initial_parameters: (f32, Vec<f32>),
+    nb_epochs: usize,
+    seed: u64,
+) -> (f32, Vec<f32>) {
+    let (mut intercept, mut raw_weights) = initial_parameters;
+    let mut scale = 1.;
+    let mut order = (0..features.len()).collect_vec();
+    let mut rng = Rng::new(seed);
+    let mut weights_gradient: HashMap<usize, f32> = HashMap::new();
+    for _ in 0..nb_epochs {
+        rng.shuffle(&mut order);
+        for batch in order.chunks(BATCH_SIZE) {
+            let batch_size = batch.len() as f32;
+            let mut intercept_gradient = 0.;
+            weights_gradient.clear();
+            for example_index in batch {
+                let example_features = &features[*example_index];
+                let score = example_features
+                    .iter()
+                    .fold(intercept, |acc, &(index, value)| {
+                        acc + scale * raw_weights[index] * value
+                    });
+                let error = (1. / (1. + (-score).exp()) - targets[*example_index]) / batch_size;
+                intercept_gradient += error;
+                for &(index, value) in example_features {
+                    *weights_gradient.entry(index).or_insert(0.) += error * value;
+                }
+            }
+            intercept -= LEARNING_RATE * intercept_gradient;
+            scale *= 1. - LEARNING_RATE * L2_REGULARIZATION;
+            for (index, gradient) in &weights_gradient {
+                raw_weights[*index] -= LEARNING_RATE * gradient / scale;
+            }
+            if scale < MIN_WEIGHTS_SCALE {
+                for weight in &mut raw_weights {
+                    *weight *= scale;
+                }
+                scale = 1.;
+            }
+        }
+    }
+    let weights = raw_weights.into_iter().map(|weight| scale * weight).collect();
+    (intercept, weights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_binary_logistic_regression_should_separate_examples() {
+        // Given
+        let features = vec![
+            vec![(0, 1.)],
+            vec![(0, 1.), (2, 0.5)],
+            vec![(1, 1.)],
+            vec![(1, 1.), (2, 0.5)],
+        ];
+        let targets = vec![1., 1., 0., 0.];
+
+        // When
+        let (intercept, weights) = fit_binary_logistic_regression(
+            &features,
+            &targets,
+            (0., vec![0.; 3]),
+            NB_EPOCHS,
+            TRAINING_SEED,
+        );
+
+        // Then
+        assert!(intercept + weights[0] > 0.);
+        assert!(intercept + weights[1] < 0.);
+        assert_eq!(vec![(1, 2.)], to_sparse_features(&[0., 2., 0.]));
+    }
+}
@@ -0,0 +1,262 @@
+mod augmentation;
+mod dataset;
+mod deterministic_parser;
+mod intent_classifier;
+mod slot_filler;
+mod update;
+mod validation;
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use configurations::{DatasetMetadata, Entity, NluEngineConfiguration,
+                     ProbabilisticParserConfiguration};
+use errors::*;
+use itertools::Itertools;
+use nlu_engine::MODEL_VERSION;
+use nlu_utils::string::normalize;
+use resources::stemmer::{StaticMapStemmer, Stemmer};
+use snips_nlu_ontology::Language;
+use tokenizer::{Tokenizer, TokenizerRegistry, CJK_TOKENIZER};
+
+use self::deterministic_parser::train_deterministic_parser;
+use self::intent_classifier::train_intent_classifier;
+use self::slot_filler::train_slot_filler;
+
+pub use self::augmentation::AugmentationConfig;
+pub use self::dataset::{Dataset, DatasetUtterance, EntityData, EntityValue, IntentData};
+pub use self::update::add_utterances;
+pub use self::validation::DatasetDiagnostic;
+
+/// Trains an engine from a dataset in the JSON format used by the Python library
+///
+/// The engine is made of a deterministic intent parser, whose rules are built from the
+/// utterances, followed by a probabilistic intent parser combining a logistic regression intent
+/// classifier with a CRF slot filler for each intent.
+pub fn train_engine<R: Read>(dataset: R) -> Result<NluEngineConfiguration> {
+    Dataset::from_reader(dataset)?.train()
+}
+
+impl Dataset {
+    /// Trains an engine from the dataset, as done by `train_engine`
+    pub fn train(&self) -> Result<NluEngineConfiguration> {
+        self.train_with_noise(&[])
+    }
+
+    /// Trains an engine from the augmented dataset, the generated noise utterances being used
+    /// as examples of the None intent
+    pub fn train_with_augmentation(
+        &self,
+        config: &AugmentationConfig,
+    ) -> Result<NluEngineConfiguration> {
+        let mean_nb_utterances = self.intents
+            .values()
+            .map(|intent| intent.utterances.len().max(config.min_utterances))
+            .sum::<usize>() as f32 / self.intents.len().max(1) as f32;
+        let nb_noise_utterances = (config.noise_factor * mean_nb_utterances) as usize;
+        let noise_utterances = self.generate_noise(nb_noise_utterances, config.seed)?;
+        self.augment(config).train_with_noise(&noise_utterances)
+    }
+
+    fn train_with_noise(&self, noise_utterances: &[String]) -> Result<NluEngineConfiguration> {
+        let language = Language::from_str(&self.language)?;
+        let tokenizer = get_tokenizer(language)?;
+
+        let deterministic_parser = train_deterministic_parser(self, language)?;
+        let intent_classifier =
+            train_intent_classifier(self, noise_utterances, language, &tokenizer)?;
+        let slot_fillers = self
+            .intent_names()
+            .into_iter()
+            .map(|intent_name| {
+                let slot_filler = train_slot_filler(self, intent_name, &*tokenizer)?;
+                Ok((intent_name.clone(), slot_filler))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        let probabilistic_parser = ProbabilisticParserConfiguration {
+            intent_classifier: with_unit_name(
+                ::serde_json::to_value(intent_classifier)?,
+                "log_reg_intent_classifier",
+            ),
+            slot_fillers,
+            config: None,
+        };
+
+        Ok(NluEngineConfiguration {
+            dataset_metadata: get_dataset_metadata(self),
+            intent_parsers: vec![
+                with_unit_name(
+                    ::serde_json::to_value(deterministic_parser)?,
+                    "deterministic_intent_parser",
+                ),
+                with_unit_name(
+                    ::serde_json::to_value(probabilistic_parser)?,
+                    "probabilistic_intent_parser",
+                ),
+            ],
+            custom_parsers: None,
+            arbitration_strategy: None,
+            none_sensitivity: None,
+            intent_thresholds: None,
+            tokenizer: get_tokenizer_name(language).map(|name| name.to_string()),
+            split_emojis: None,
+            unicode_normalization: None,
+            preprocessing: None,
+            expand_contractions: None,
+            spell_correction: None,
+            emoji_aliases: None,
+            input_length_limit: None,
+            carry_over_slots: None,
+            unit_system: None,
+            model_version: MODEL_VERSION.to_string(),
+            training_package_version: env!("CARGO_PKG_VERSION").to_string(),
+        })
+    }
+}
+
+fn get_dataset_metadata(dataset: &Dataset) -> DatasetMetadata {
+    let entities = dataset
+        .custom_entity_names()
+        .into_iter()
+        .map(|entity_name| {
+            let mut utterances = HashMap::new();
+            for (variant, value) in dataset.entity_variants(entity_name) {
+                utterances.insert(normalize(&variant), value.clone());
+                utterances.insert(variant, value);
+            }
+            let entity = Entity {
+                automatically_extensible: dataset.entities[entity_name].automatically_extensible,
+                utterances,
+                utterances_file: None,
+                nb_values: None,
+            };
+            (entity_name.clone(), entity)
+        })
+        .collect();
+    let slot_name_mappings = dataset
+        .intent_names()
+        .into_iter()
+        .map(|intent_name| (intent_name.clone(), dataset.slot_name_mapping(intent_name)))
+        .collect();
+
+    DatasetMetadata {
+        language_code: dataset.language.clone(),
+        additional_builtin_entities_languages: None,
+        fallback_builtin_entities_languages: None,
+        entities,
+        slot_name_mappings,
+    }
+}
+
+/// Returns the name of the tokenizer of the trained engines, the languages which are written
+/// without spaces between words being segmented by the builtin CJK tokenizer
+fn get_tokenizer_name(language: Language) -> Option<&'static str> {
+    match language {
+        Language::JA | Language::KO => Some(CJK_TOKENIZER),
+        _ => None,
+    }
+}
+
+fn get_tokenizer(language: Language) -> Result<Arc<Tokenizer>> {
+    TokenizerRegistry::new().create(get_tokenizer_name(language), language)
+}
+
+/// Normalizes an entity value in the way its tokens are looked up by the entity features, the
+/// tokens being stemmed when a stemmer is given
+fn normalize_variant(
+    variant: &str,
+    tokenizer: &Tokenizer,
+    stemmer: Option<&StaticMapStemmer>,
+) -> String {
+    tokenizer
+        .tokenize_light(variant)
+        .iter()
+        .map(|token| {
+            let normalized_token = normalize(token);
+            stemmer.map_or(normalized_token.clone(), |s| s.stem(&normalized_token))
+        })
+        .join(" ")
+}
+
+fn with_unit_name(mut value: ::serde_json::Value, unit_name: &str) -> ::serde_json::Value {
+    if let ::serde_json::Value::Object(ref mut map) = value {
+        map.insert(
+            "unit_name".to_string(),
+            ::serde_json::Value::String(unit_name.to_string()),
+        );
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nlu_engine::SnipsNluEngine;
+    use snips_nlu_ontology::SlotValue;
+    use utils::file_path;
+
+    use std::fs::File;
+
+    #[test]
+    fn trained_engine_works() {
+        // Given
+        let dataset = File::open(file_path("tests/datasets/beverage_dataset.json")).unwrap();
+
+        // When
+        let configuration = train_engine(dataset).unwrap();
+        let engine = SnipsNluEngine::new(configuration).unwrap();
+        let rule_result = engine.parse("make me a warm cup of tea", None).unwrap();
+        let classifier_result = engine.parse("could you make me some coffee", None).unwrap();
+
+        // Then
+        assert_eq!("MakeTea", rule_result.intent.unwrap().intent_name);
+        let slots = rule_result.slots.unwrap();
+        assert_eq!(1, slots.len());
+        assert_eq!("beverage_temperature", slots[0].slot_name);
+        assert_eq!(SlotValue::Custom("hot".to_string().into()), slots[0].value);
+        assert_eq!("MakeCoffee", classifier_result.intent.unwrap().intent_name);
+    }
+
+    #[test]
+    fn train_engine_should_segment_japanese() {
+        // Given
+        let dataset = r#"{
+            "language": "ja",
+            "intents": {
+                "MakeTea": {"utterances": [
+                    {"data": [{"text": "紅茶をください"}]},
+                    {"data": [
+                        {"text": "ホット", "slot_name": "temperature", "entity": "Temperature"},
+                        {"text": "の紅茶をください"}
+                    ]}
+                ]},
+                "MakeCoffee": {"utterances": [{"data": [{"text": "コーヒーをください"}]}]}
+            },
+            "entities": {"Temperature": {"data": [{"value": "ホット", "synonyms": []}]}}
+        }"#;
+
+        // When
+        let configuration = train_engine(dataset.as_bytes()).unwrap();
+        let tokenizer_name = configuration.tokenizer.clone();
+        let engine = SnipsNluEngine::new(configuration).unwrap();
+        let result = engine.parse("コーヒーをください", None).unwrap();
+
+        // Then
+        assert_eq!(Some(CJK_TOKENIZER.to_string()), tokenizer_name);
+        assert_eq!("MakeCoffee", result.intent.unwrap().intent_name);
+    }
+
+    #[test]
+    fn train_engine_should_fail_with_unknown_language() {
+        // Given
+        let dataset = r#"{"language": "xx", "intents": {}, "entities": {}}"#;
+
+        // When
+        let result = train_engine(dataset.as_bytes());
+
+        // Then
+        assert!(result.is_err());
+    }
+}
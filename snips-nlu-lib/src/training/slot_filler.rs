@@ -0,0 +1,481 @@
+use std::collections::{BTreeSet, HashMap};
+
+use itertools::Itertools;
+
+use configurations::{BuiltinSlotsDisambiguation, FeatureFactory, OverlappingSlotsPolicy,
+                     SlotFillerConfigConfiguration, SlotFillerConfiguration,
+                     TaggingSchemeConfiguration};
+use errors::*;
+use nlu_utils::token::Token;
+use parse_context::ParseContext;
+use slot_filler::crf_tagger::{log_sum_exp, write_model, STATE_FEATURE, TRANSITION_FEATURE};
+use slot_filler::crf_utils::{positive_tagging, TaggingScheme, OUTSIDE};
+use slot_filler::encode_tag;
+use slot_filler::feature_processor::ProbabilisticFeatureProcessor;
+use slot_filler::FeatureExtractorRegistry;
+use snips_nlu_ontology::BuiltinEntityKind;
+use tokenizer::Tokenizer;
+use training::dataset::{Dataset, DatasetUtterance};
+use training::augmentation::Rng;
+use training::normalize_variant;
+
+const NB_EPOCHS: usize = 20;
+const INITIAL_LEARNING_RATE: f64 = 0.5;
+const L2_REGULARIZATION: f64 = 0.1;
+const MIN_WEIGHTS_SCALE: f64 = 1e-9;
+const TRAINING_SEED: u64 = 42;
+const BIO_TAGGING_SCHEME_CODE: u8 = 1;
+const EXHAUSTIVE_PERMUTATIONS_THRESHOLD: usize = 64;
+
+/// Trains the CRF slot filler of an intent by maximizing the log-likelihood of its utterances,
+/// using the BIO tagging scheme
+pub fn train_slot_filler(
+    dataset: &Dataset,
+    intent_name: &str,
+    tokenizer: &Tokenizer,
+) -> Result<SlotFillerConfiguration> {
+    let slot_name_mapping = dataset.slot_name_mapping(intent_name);
+    let feature_factory_configs = get_feature_factories(dataset, &slot_name_mapping, tokenizer);
+    let feature_processor = ProbabilisticFeatureProcessor::new(
+        &feature_factory_configs,
+        &FeatureExtractorRegistry::new(),
+    )?;
+
+    let mut labels: Vec<String> = vec![OUTSIDE.to_string()];
+    let mut attributes: HashMap<String, usize> = HashMap::new();
+    let mut sequences: Vec<(Vec<Vec<usize>>, Vec<usize>)> = vec![];
+    for utterance in &dataset.intents[intent_name].utterances {
+        let (tokens, tags) = get_tokens_and_tags(utterance, tokenizer);
+        if tokens.is_empty() {
+            continue;
+        }
+        let token_attributes = feature_processor
+            .compute_features(&&*tokens, &ParseContext::default())
+            .into_iter()
+            .map(|token_features| {
+                token_features
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let nb_attributes = attributes.len();
+                        *attributes
+                            .entry(format!("{}:{}", key, value))
+                            .or_insert(nb_attributes)
+                    })
+                    .collect()
+            })
+            .collect();
+        let token_labels = tags.into_iter()
+            .map(|tag| {
+                labels.iter().position(|label| *label == tag).unwrap_or_else(|| {
+                    labels.push(tag);
+                    labels.len() - 1
+                })
+            })
+            .collect();
+        sequences.push((token_attributes, token_labels));
+    }
+
+    let mut crf = LinearChainCrf::new(attributes.len(), labels.len());
+    crf.fit(&sequences, NB_EPOCHS, TRAINING_SEED);
+    let weights = crf.weights();
+
+    // Only the attributes having a non zero weight are kept in the model
+    let mut attributes_by_index: Vec<(usize, String)> = attributes
+        .into_iter()
+        .map(|(attribute, index)| (index, attribute))
+        .collect();
+    attributes_by_index.sort();
+    let mut model_attributes: Vec<String> = vec![];
+    let mut features: Vec<(u32, u32, u32, f64)> = vec![];
+    for (attribute_index, attribute) in attributes_by_index {
+        let attribute_features = (0..labels.len())
+            .map(|label| (label, weights[attribute_index * labels.len() + label]))
+            .filter(|&(_, weight)| weight != 0.)
+            .collect_vec();
+        if attribute_features.is_empty() {
+            continue;
+        }
+        for (label, weight) in attribute_features {
+            features.push((
+                STATE_FEATURE,
+                model_attributes.len() as u32,
+                label as u32,
+                weight,
+            ));
+        }
+        model_attributes.push(attribute);
+    }
+    let transitions_offset = crf.nb_attributes * labels.len();
+    for previous_label in 0..labels.len() {
+        for label in 0..labels.len() {
+            let weight = weights[transitions_offset + previous_label * labels.len() + label];
+            if weight != 0. {
+                features.push((TRANSITION_FEATURE, previous_label as u32, label as u32, weight));
+            }
+        }
+    }
+
+    let encoded_labels = labels.iter().map(|label| encode_tag(label)).collect_vec();
+    let model = write_model(
+        &encoded_labels.iter().map(|label| &**label).collect_vec(),
+        &model_attributes.iter().map(|attribute| &**attribute).collect_vec(),
+        &features,
+    );
+
+    Ok(SlotFillerConfiguration {
+        language_code: dataset.language.clone(),
+        intent: intent_name.to_string(),
+        slot_name_mapping,
+        crf_model_data: ::base64::encode(&model),
+        crf_model_file: None,
+        config: Some(SlotFillerConfigConfiguration {
+            tagging_scheme: TaggingSchemeConfiguration::Identifier(BIO_TAGGING_SCHEME_CODE),
+            exhaustive_permutations_threshold: EXHAUSTIVE_PERMUTATIONS_THRESHOLD,
+            feature_factory_configs,
+            overlapping_slots_policy: OverlappingSlotsPolicy::default(),
+            builtin_slots_disambiguation: BuiltinSlotsDisambiguation::default(),
+        }),
+        beam_search: None,
+    })
+}
+
+/// Returns the tokens of an utterance along with their tags, the tokens which are not fully
+/// contained in a slot being outside of any slot
+fn get_tokens_and_tags(
+    utterance: &DatasetUtterance,
+    tokenizer: &Tokenizer,
+) -> (Vec<Token>, Vec<String>) {
+    let text = utterance.text();
+    let tokens = tokenizer.tokenize(&text);
+    let mut tags: Vec<String> = vec![];
+    let mut chunk_start = 0;
+    let mut token_index = 0;
+    for chunk in &utterance.data {
+        let chunk_end = chunk_start + chunk.text.chars().count();
+        let nb_chunk_tokens = tokens[token_index..]
+            .iter()
+            .take_while(|token| token.char_range.end <= chunk_end)
+            .count();
+        let chunk_tokens = &tokens[token_index..token_index + nb_chunk_tokens];
+        let nb_slot_tokens = chunk_tokens
+            .iter()
+            .filter(|token| token.char_range.start >= chunk_start)
+            .count();
+        let nb_outside_tokens = nb_chunk_tokens - nb_slot_tokens;
+        tags.extend(vec![OUTSIDE.to_string(); nb_outside_tokens]);
+        if let Some(ref slot_name) = chunk.slot_name {
+            tags.extend(positive_tagging(TaggingScheme::BIO, slot_name, nb_slot_tokens));
+        } else {
+            tags.extend(vec![OUTSIDE.to_string(); nb_slot_tokens]);
+        }
+        token_index += nb_chunk_tokens;
+        chunk_start = chunk_end;
+    }
+    tags.extend(vec![OUTSIDE.to_string(); tokens.len() - token_index]);
+    (tokens, tags)
+}
+
+fn get_feature_factories(
+    dataset: &Dataset,
+    slot_name_mapping: &HashMap<String, String>,
+    tokenizer: &Tokenizer,
+) -> Vec<FeatureFactory> {
+    let language_code = ::serde_json::Value::from(dataset.language.clone());
+    let mut feature_factories = vec![
+        FeatureFactory {
+            factory_name: "ngram".to_string(),
+            offsets: vec![-2, -1, 0, 1, 2],
+            args: hashmap_args(vec![
+                ("n", ::serde_json::Value::from(1)),
+                ("use_stemming", ::serde_json::Value::from(false)),
+                ("common_words_gazetteer_name", ::serde_json::Value::Null),
+                ("language_code", language_code.clone()),
+            ]),
+        },
+        FeatureFactory {
+            factory_name: "shape_ngram".to_string(),
+            offsets: vec![0],
+            args: hashmap_args(vec![("n", ::serde_json::Value::from(1))]),
+        },
+        FeatureFactory {
+            factory_name: "is_digit".to_string(),
+            offsets: vec![-1, 0, 1],
+            args: HashMap::new(),
+        },
+        FeatureFactory {
+            factory_name: "is_first".to_string(),
+            offsets: vec![-2, -1, 0],
+            args: HashMap::new(),
+        },
+        FeatureFactory {
+            factory_name: "is_last".to_string(),
+            offsets: vec![0, 1, 2],
+            args: HashMap::new(),
+        },
+    ];
+
+    let entity_names: BTreeSet<&String> = slot_name_mapping
+        .values()
+        .filter(|entity| BuiltinEntityKind::from_identifier(entity).is_err())
+        .collect();
+    if !entity_names.is_empty() {
+        let collections = entity_names
+            .into_iter()
+            .map(|entity_name| {
+                let normalized_variants: BTreeSet<String> = dataset
+                    .entity_variants(entity_name)
+                    .into_iter()
+                    .map(|(variant, _)| normalize_variant(&variant, tokenizer, None))
+                    .filter(|variant| !variant.is_empty())
+                    .collect();
+                (
+                    entity_name.clone(),
+                    ::serde_json::Value::from(normalized_variants.into_iter().collect_vec()),
+                )
+            })
+            .collect();
+        feature_factories.push(FeatureFactory {
+            factory_name: "entity_match".to_string(),
+            offsets: vec![-2, -1, 0],
+            args: hashmap_args(vec![
+                ("collections", ::serde_json::Value::Object(collections)),
+                (
+                    "tagging_scheme_code",
+                    ::serde_json::Value::from(BIO_TAGGING_SCHEME_CODE),
+                ),
+                ("use_stemming", ::serde_json::Value::from(false)),
+                ("language_code", language_code),
+            ]),
+        });
+    }
+    feature_factories
+}
+
+fn hashmap_args(args: Vec<(&str, ::serde_json::Value)>) -> HashMap<String, ::serde_json::Value> {
+    args.into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+}
+
+/// Linear-chain CRF trained with stochastic gradient descent on its L2 regularized
+/// log-likelihood, the state weights being indexed by attribute and then by label, followed by
+/// the transition weights
+///
+/// The weights are stored as `scale * raw_weights`, so that the regularization, which shrinks
+/// every weight at each step, costs a single multiplication while the gradient updates only touch
+/// the weights of the attributes of the sequence.
+struct LinearChainCrf {
+    nb_attributes: usize,
+    nb_labels: usize,
+    raw_weights: Vec<f64>,
+    scale: f64,
+}
+
+impl LinearChainCrf {
+    fn new(nb_attributes: usize, nb_labels: usize) -> Self {
+        Self {
+            nb_attributes,
+            nb_labels,
+            raw_weights: vec![0.; (nb_attributes + nb_labels) * nb_labels],
+            scale: 1.,
+        }
+    }
+
+    fn state_index(&self, attribute: usize, label: usize) -> usize {
+        attribute * self.nb_labels + label
+    }
+
+    fn transition_index(&self, previous_label: usize, label: usize) -> usize {
+        (self.nb_attributes + previous_label) * self.nb_labels + label
+    }
+
+    fn weight(&self, index: usize) -> f64 {
+        self.scale * self.raw_weights[index]
+    }
+
+    fn add_to_weight(&mut self, index: usize, delta: f64) {
+        self.raw_weights[index] += delta / self.scale;
+    }
+
+    /// Maximizes the regularized log-likelihood of the sequences, made of the attributes of each
+    /// token along with their labels
+    fn fit(&mut self, sequences: &[(Vec<Vec<usize>>, Vec<usize>)], nb_epochs: usize, seed: u64) {
+        if sequences.is_empty() {
+            return;
+        }
+        let regularization = L2_REGULARIZATION / sequences.len() as f64;
+        let mut order = (0..sequences.len()).collect_vec();
+        let mut rng = Rng::new(seed);
+        for epoch in 0..nb_epochs {
+            let learning_rate = INITIAL_LEARNING_RATE / (1. + epoch as f64);
+            rng.shuffle(&mut order);
+            for sequence_index in &order {
+                let (ref token_attributes, ref labels) = sequences[*sequence_index];
+                self.scale *= 1. - learning_rate * regularization;
+                self.gradient_step(token_attributes, labels, learning_rate);
+                if self.scale < MIN_WEIGHTS_SCALE {
+                    self.rescale();
+                }
+            }
+        }
+    }
+
+    /// Moves the weights along the gradient of the log-likelihood of a sequence, which is the
+    /// difference between the observed feature counts and their expectation under the model
+    fn gradient_step(&mut self, token_attributes: &[Vec<usize>], labels: &[usize], step: f64) {
+        let nb_labels = self.nb_labels;
+        let state_scores = self.state_scores(token_attributes);
+        let (log_alphas, log_betas, log_partition) = self.forward_backward(&state_scores);
+        let transition_weights = (0..nb_labels * nb_labels)
+            .map(|index| self.weight(self.transition_index(index / nb_labels, index % nb_labels)))
+            .collect_vec();
+        for (token_index, attributes) in token_attributes.iter().enumerate() {
+            for label in 0..nb_labels {
+                let marginal = (log_alphas[token_index][label] + log_betas[token_index][label]
+                    - log_partition)
+                    .exp();
+                let observed = if labels[token_index] == label { 1. } else { 0. };
+                for attribute in attributes {
+                    let index = self.state_index(*attribute, label);
+                    self.add_to_weight(index, step * (observed - marginal));
+                }
+            }
+            if token_index == 0 {
+                continue;
+            }
+            for previous_label in 0..nb_labels {
+                for label in 0..nb_labels {
+                    let marginal = (log_alphas[token_index - 1][previous_label]
+                        + transition_weights[previous_label * nb_labels + label]
+                        + state_scores[token_index][label]
+                        + log_betas[token_index][label]
+                        - log_partition)
+                        .exp();
+                    let observed = if (labels[token_index - 1], labels[token_index])
+                        == (previous_label, label)
+                    {
+                        1.
+                    } else {
+                        0.
+                    };
+                    let index = self.transition_index(previous_label, label);
+                    self.add_to_weight(index, step * (observed - marginal));
+                }
+            }
+        }
+    }
+
+    fn state_scores(&self, token_attributes: &[Vec<usize>]) -> Vec<Vec<f64>> {
+        token_attributes
+            .iter()
+            .map(|attributes| {
+                (0..self.nb_labels)
+                    .map(|label| {
+                        attributes
+                            .iter()
+                            .map(|attribute| self.weight(self.state_index(*attribute, label)))
+                            .sum::<f64>()
+                    })
+                    .collect_vec()
+            })
+            .collect_vec()
+    }
+
+    /// Returns the forward and backward log scores of each token and label, along with the log
+    /// partition function
+    fn forward_backward(&self, state_scores: &[Vec<f64>]) -> (Vec<Vec<f64>>, Vec<Vec<f64>>, f64) {
+        let nb_tokens = state_scores.len();
+        let mut log_alphas = vec![state_scores[0].clone()];
+        for token_scores in &state_scores[1..] {
+            let token_log_alphas = (0..self.nb_labels)
+                .map(|label| {
+                    log_sum_exp(log_alphas[log_alphas.len() - 1].iter().enumerate().map(
+                        |(previous_label, log_alpha)| {
+                            log_alpha + self.weight(self.transition_index(previous_label, label))
+                        },
+                    )) + token_scores[label]
+                })
+                .collect_vec();
+            log_alphas.push(token_log_alphas);
+        }
+        let mut log_betas = vec![vec![0.; self.nb_labels]; nb_tokens];
+        for token_index in (0..nb_tokens - 1).rev() {
+            log_betas[token_index] = (0..self.nb_labels)
+                .map(|label| {
+                    log_sum_exp((0..self.nb_labels).map(|next_label| {
+                        self.weight(self.transition_index(label, next_label))
+                            + state_scores[token_index + 1][next_label]
+                            + log_betas[token_index + 1][next_label]
+                    }))
+                })
+                .collect();
+        }
+        let log_partition = log_sum_exp(log_alphas[nb_tokens - 1].iter().cloned());
+        (log_alphas, log_betas, log_partition)
+    }
+
+    /// Returns the probability of the labels of a sequence under the model
+    #[cfg(test)]
+    fn probability(&self, token_attributes: &[Vec<usize>], labels: &[usize]) -> f64 {
+        let state_scores = self.state_scores(token_attributes);
+        let (_, _, log_partition) = self.forward_backward(&state_scores);
+        let score = labels
+            .iter()
+            .enumerate()
+            .map(|(token_index, label)| state_scores[token_index][*label])
+            .sum::<f64>()
+            + labels
+                .windows(2)
+                .map(|labels| self.weight(self.transition_index(labels[0], labels[1])))
+                .sum::<f64>();
+        (score - log_partition).exp()
+    }
+
+    /// Applies the scale to the raw weights, before it becomes too small to be accurate
+    fn rescale(&mut self) {
+        let scale = self.scale;
+        for weight in &mut self.raw_weights {
+            *weight *= scale;
+        }
+        self.scale = 1.;
+    }
+
+    fn weights(&self) -> Vec<f64> {
+        self.raw_weights
+            .iter()
+            .map(|weight| self.scale * weight)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_chain_crf_should_fit_training_sequences() {
+        // Given
+        let (a, tea, coffee) = (0, 1, 2);
+        let (outside, beverage) = (0, 1);
+        let sequences = vec![
+            (vec![vec![a], vec![tea]], vec![outside, beverage]),
+            (vec![vec![a], vec![coffee]], vec![outside, beverage]),
+            (vec![vec![tea], vec![a]], vec![beverage, outside]),
+        ];
+        let mut crf = LinearChainCrf::new(3, 2);
+
+        // When
+        crf.fit(&sequences, NB_EPOCHS, TRAINING_SEED);
+
+        // Then
+        let tokens = vec![vec![a], vec![coffee]];
+        let probabilities = vec![
+            crf.probability(&tokens, &[outside, outside]),
+            crf.probability(&tokens, &[outside, beverage]),
+            crf.probability(&tokens, &[beverage, outside]),
+            crf.probability(&tokens, &[beverage, beverage]),
+        ];
+        assert!((probabilities.iter().sum::<f64>() - 1.).abs() < 1e-6);
+        assert!(probabilities[1] > 0.5);
+    }
+}
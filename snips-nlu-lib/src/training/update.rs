@@ -0,0 +1,535 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use itertools::Itertools;
+
+use configurations::{DeterministicParserConfiguration, FeatureFactory,
+                     IntentClassifierConfiguration, NluEngineConfiguration,
+                     ProbabilisticParserConfiguration};
+use errors::*;
+use intent_classifier::Featurizer;
+use nlu_utils::string::normalize;
+use resources::stemmer::StaticMapStemmer;
+use snips_nlu_ontology::{BuiltinEntityKind, Language};
+use tokenizer::{Tokenizer, TokenizerRegistry};
+use training::dataset::DatasetUtterance;
+use training::deterministic_parser::utterance_to_rule;
+use training::intent_classifier::{fit_binary_logistic_regression, to_sparse_features,
+                                  TRAINING_SEED};
+use training::{normalize_variant, with_unit_name};
+
+const NB_FINE_TUNING_EPOCHS: usize = 20;
+/// Maximum number of training utterances of each intent replayed when fine-tuning the classifier
+const NB_REPLAYED_UTTERANCES: usize = 20;
+
+/// Adds training utterances to an intent of a trained engine, without retraining it
+///
+/// The rules of the deterministic intent parser are extended with the new utterances, and the
+/// slot values they contain are added to the gazetteers of their entities as well as to the
+/// entity features of the probabilistic intent parser. The weights of the logistic regression
+/// intent classifier are updated with a few gradient steps on the new utterances when
+/// `fine_tune_classifier` is set, a sample of the training utterances recovered from the rules of
+/// the deterministic intent parser being replayed along with examples of the None intent, so that
+/// the classifier doesn't forget the other intents. The CRF slot fillers are left unchanged.
+/// Engines using a custom tokenizer can't be updated.
+///
+/// An error is returned when a new utterance gives a rule of the deterministic intent parser
+/// which already belongs to another intent.
+pub fn add_utterances(
+    mut configuration: NluEngineConfiguration,
+    intent_name: &str,
+    utterances: &[DatasetUtterance],
+    fine_tune_classifier: bool,
+) -> Result<NluEngineConfiguration> {
+    let language = Language::from_str(&configuration.dataset_metadata.language_code)?;
+    let tokenizer = TokenizerRegistry::new().create_with_emoji_splitting(
+        configuration.tokenizer.as_ref().map(|name| &**name),
+        language,
+        configuration.split_emojis.unwrap_or(false),
+    )?;
+    let new_slots = register_slots(&mut configuration, intent_name, utterances)?;
+    let new_entity_values = new_slots
+        .into_iter()
+        .filter(|&(_, ref entity_name, _)| {
+            BuiltinEntityKind::from_identifier(entity_name).is_err()
+        })
+        .map(|(_, entity_name, value)| (entity_name, value))
+        .collect::<Vec<_>>();
+
+    for &(ref entity_name, ref value) in &new_entity_values {
+        let entity = configuration
+            .dataset_metadata
+            .entities
+            .get_mut(entity_name)
+            .ok_or_else(|| format!("Unknown entity: {}", entity_name))?;
+        // Gazetteers stored in a separate file are left unchanged
+        if entity.utterances_file.is_none() {
+            entity
+                .utterances
+                .entry(value.clone())
+                .or_insert_with(|| value.clone());
+            entity
+                .utterances
+                .entry(normalize(value))
+                .or_insert_with(|| value.clone());
+        }
+    }
+
+    let slot_name_mapping = configuration.dataset_metadata.slot_name_mappings[intent_name]
+        .clone();
+    let replayed_utterances = get_replayed_utterances(&configuration.intent_parsers)?;
+    let mut intent_parsers = vec![];
+    for parser_config in configuration.intent_parsers {
+        let unit_name = parser_config["unit_name"].as_str().map(|name| name.to_string());
+        let updated_parser_config = match unit_name.as_ref().map(|name| &**name) {
+            Some("deterministic_intent_parser") => with_unit_name(
+                ::serde_json::to_value(update_deterministic_parser(
+                    ::serde_json::from_value(parser_config)?,
+                    intent_name,
+                    utterances,
+                    &slot_name_mapping,
+                    language,
+                )?)?,
+                "deterministic_intent_parser",
+            ),
+            Some("probabilistic_intent_parser") => with_unit_name(
+                ::serde_json::to_value(update_probabilistic_parser(
+                    ::serde_json::from_value(parser_config)?,
+                    intent_name,
+                    utterances,
+                    &slot_name_mapping,
+                    &new_entity_values,
+                    fine_tune_classifier,
+                    &replayed_utterances,
+                    language,
+                    &tokenizer,
+                )?)?,
+                "probabilistic_intent_parser",
+            ),
+            _ => parser_config,
+        };
+        intent_parsers.push(updated_parser_config);
+    }
+    configuration.intent_parsers = intent_parsers;
+    Ok(configuration)
+}
+
+/// Checks the slots of the utterances against the dataset metadata, registering the new slots of
+/// the intent, and returns the slot name, entity and value of each slot chunk
+fn register_slots(
+    configuration: &mut NluEngineConfiguration,
+    intent_name: &str,
+    utterances: &[DatasetUtterance],
+) -> Result<Vec<(String, String, String)>> {
+    let metadata = &mut configuration.dataset_metadata;
+    let entities = &metadata.entities;
+    let slot_name_mapping = metadata
+        .slot_name_mappings
+        .get_mut(intent_name)
+        .ok_or_else(|| format!("Unknown intent: {}", intent_name))?;
+    let mut slots = vec![];
+    for chunk in utterances.iter().flat_map(|utterance| utterance.data.iter()) {
+        let slot_name = if let Some(ref slot_name) = chunk.slot_name {
+            slot_name
+        } else {
+            continue;
+        };
+        let entity_name = match (chunk.entity.as_ref(), slot_name_mapping.get(slot_name)) {
+            (Some(entity_name), Some(mapped_entity_name)) if entity_name != mapped_entity_name => {
+                bail!(
+                    "Slot '{}' is used with entities '{}' and '{}'",
+                    slot_name,
+                    mapped_entity_name,
+                    entity_name
+                )
+            }
+            (Some(entity_name), _) | (None, Some(entity_name)) => entity_name.clone(),
+            (None, None) => bail!("Missing entity of slot '{}'", slot_name),
+        };
+        if BuiltinEntityKind::from_identifier(&entity_name).is_err()
+            && !entities.contains_key(&entity_name)
+        {
+            bail!("Unknown entity '{}' of slot '{}'", entity_name, slot_name);
+        }
+        slot_name_mapping.insert(slot_name.clone(), entity_name.clone());
+        slots.push((slot_name.clone(), entity_name, chunk.text.clone()));
+    }
+    Ok(slots)
+}
+
+fn update_deterministic_parser(
+    mut parser_config: DeterministicParserConfiguration,
+    intent_name: &str,
+    utterances: &[DatasetUtterance],
+    slot_name_mapping: &HashMap<String, String>,
+    language: Language,
+) -> Result<DeterministicParserConfiguration> {
+    for (slot_name, entity_name) in slot_name_mapping {
+        let previous_entity_name = parser_config
+            .slot_names_to_entities
+            .insert(slot_name.clone(), entity_name.clone());
+        if let Some(previous_entity_name) = previous_entity_name {
+            if previous_entity_name != *entity_name {
+                bail!(
+                    "Slot '{}' is used with entities '{}' and '{}'",
+                    slot_name,
+                    previous_entity_name,
+                    entity_name
+                );
+            }
+        }
+    }
+
+    let rules = parser_config.rules.get_or_insert_with(HashMap::new);
+    for utterance in utterances {
+        let rule = utterance_to_rule(utterance, language);
+        if rule.is_empty() {
+            continue;
+        }
+        if let Some((other_intent_name, _)) =
+            rules.iter().find(|&(other_intent_name, other_rules)| {
+                other_intent_name != intent_name && other_rules.contains(&rule)
+            }) {
+            bail!(
+                "Utterance '{}' of intent '{}' is ambiguous with a rule of intent '{}'",
+                utterance.text(),
+                intent_name,
+                other_intent_name
+            );
+        }
+        let intent_rules = rules
+            .entry(intent_name.to_string())
+            .or_insert_with(Vec::new);
+        if !intent_rules.contains(&rule) {
+            intent_rules.push(rule);
+        }
+    }
+    Ok(parser_config)
+}
+
+fn update_probabilistic_parser(
+    mut parser_config: ProbabilisticParserConfiguration,
+    intent_name: &str,
+    utterances: &[DatasetUtterance],
+    slot_name_mapping: &HashMap<String, String>,
+    new_entity_values: &[(String, String)],
+    fine_tune_classifier: bool,
+    replayed_utterances: &HashMap<String, Vec<String>>,
+    language: Language,
+    tokenizer: &Arc<Tokenizer>,
+) -> Result<ProbabilisticParserConfiguration> {
+    if let Some(slot_filler) = parser_config.slot_fillers.get_mut(intent_name) {
+        slot_filler.slot_name_mapping = slot_name_mapping.clone();
+        if let Some(slot_filler_config) = slot_filler.config.as_mut() {
+            for feature_factory in &mut slot_filler_config.feature_factory_configs {
+                add_entity_match_values(
+                    feature_factory,
+                    new_entity_values,
+                    language,
+                    &**tokenizer,
+                );
+            }
+        }
+    }
+
+    if parser_config.intent_classifier["unit_name"].as_str() == Some("log_reg_intent_classifier") {
+        let mut classifier_config: IntentClassifierConfiguration =
+            ::serde_json::from_value(parser_config.intent_classifier)?;
+        if let Some(featurizer_config) = classifier_config.featurizer.as_mut() {
+            let stemmer = StaticMapStemmer::new(language).ok();
+            for &(ref entity_name, ref value) in new_entity_values {
+                let normalized_value = normalize_variant(value, &**tokenizer, stemmer.as_ref());
+                let feature_name = format!("entityfeature{}", entity_name);
+                let feature_names = featurizer_config
+                    .entity_utterances_to_feature_names
+                    .entry(normalized_value)
+                    .or_insert_with(Vec::new);
+                if !feature_names.contains(&feature_name) {
+                    feature_names.push(feature_name);
+                }
+            }
+        }
+        if fine_tune_classifier {
+            fine_tune_intent_classifier(
+                &mut classifier_config,
+                intent_name,
+                utterances,
+                replayed_utterances,
+                tokenizer,
+            )?;
+        }
+        parser_config.intent_classifier = with_unit_name(
+            ::serde_json::to_value(classifier_config)?,
+            "log_reg_intent_classifier",
+        );
+    }
+    Ok(parser_config)
+}
+
+/// Adds the new values of the entities to the collections of an `entity_match` feature
+fn add_entity_match_values(
+    feature_factory: &mut FeatureFactory,
+    new_entity_values: &[(String, String)],
+    language: Language,
+    tokenizer: &Tokenizer,
+) {
+    if feature_factory.factory_name != "entity_match" {
+        return;
+    }
+    let use_stemming = feature_factory
+        .args
+        .get("use_stemming")
+        .and_then(|use_stemming| use_stemming.as_bool())
+        .unwrap_or(false);
+    let stemmer = if use_stemming {
+        StaticMapStemmer::new(language).ok()
+    } else {
+        None
+    };
+    if let Some(collections) = feature_factory
+        .args
+        .get_mut("collections")
+        .and_then(|collections| collections.as_object_mut())
+    {
+        for &(ref entity_name, ref value) in new_entity_values {
+            let normalized_value = ::serde_json::Value::String(normalize_variant(
+                value,
+                tokenizer,
+                stemmer.as_ref(),
+            ));
+            if let Some(values) = collections
+                .get_mut(entity_name)
+                .and_then(|values| values.as_array_mut())
+            {
+                if !values.contains(&normalized_value) {
+                    values.push(normalized_value);
+                }
+            }
+        }
+    }
+}
+
+/// Returns a sample of the training utterances of each intent, recovered from the rules of the
+/// deterministic intent parser without their slot placeholders
+fn get_replayed_utterances(
+    intent_parsers: &[::serde_json::Value],
+) -> Result<HashMap<String, Vec<String>>> {
+    let parser_config = intent_parsers
+        .iter()
+        .find(|config| config["unit_name"].as_str() == Some("deterministic_intent_parser"));
+    let rules = if let Some(parser_config) = parser_config {
+        ::serde_json::from_value::<DeterministicParserConfiguration>(parser_config.clone())?
+            .rules
+            .unwrap_or_else(HashMap::new)
+    } else {
+        return Ok(HashMap::new());
+    };
+    Ok(rules
+        .into_iter()
+        .map(|(intent_name, mut intent_rules)| {
+            intent_rules.sort();
+            let utterances = intent_rules
+                .iter()
+                .take(NB_REPLAYED_UTTERANCES)
+                .map(|rule| {
+                    rule.split_whitespace()
+                        .filter(|word| !(word.starts_with('{') && word.ends_with('}')))
+                        .join(" ")
+                })
+                .filter(|utterance| !utterance.is_empty())
+                .collect();
+            (intent_name, utterances)
+        })
+        .collect())
+}
+
+/// Updates the weights of the classifier with a few gradient steps on the new utterances, along
+/// with the replayed utterances of every intent and examples of the None intent
+fn fine_tune_intent_classifier(
+    classifier_config: &mut IntentClassifierConfiguration,
+    intent_name: &str,
+    utterances: &[DatasetUtterance],
+    replayed_utterances: &HashMap<String, Vec<String>>,
+    tokenizer: &Arc<Tokenizer>,
+) -> Result<()> {
+    let intent_list = classifier_config.intent_list.clone();
+    let intent_index = |name: &str| {
+        intent_list
+            .iter()
+            .position(|intent| intent.as_ref().map_or(false, |intent| intent == name))
+    };
+    let new_intent_index = intent_index(intent_name)
+        .ok_or_else(|| format!("Intent '{}' is unknown to the classifier", intent_name))?;
+    let none_index = intent_list.iter().position(|intent| intent.is_none());
+    let featurizer_config = if let Some(featurizer_config) = classifier_config.featurizer.as_ref()
+    {
+        featurizer_config.clone()
+    } else {
+        return Ok(());
+    };
+    let featurizer = Featurizer::new_with_tokenizer(featurizer_config, tokenizer.clone());
+
+    let mut texts = utterances
+        .iter()
+        .map(|utterance| (utterance.text(), new_intent_index))
+        .collect::<Vec<_>>();
+    let mut replayed_intent_names = replayed_utterances.keys().collect::<Vec<_>>();
+    replayed_intent_names.sort();
+    for replayed_intent_name in replayed_intent_names {
+        if let Some(index) = intent_index(replayed_intent_name) {
+            for utterance in &replayed_utterances[replayed_intent_name] {
+                texts.push((utterance.clone(), index));
+            }
+        }
+    }
+    let mut features = vec![];
+    let mut labels = vec![];
+    for (text, label) in texts {
+        features.push(to_sparse_features(&featurizer.transform(&text)?));
+        labels.push(label);
+    }
+    // Inputs without any known word are examples of the None intent, as in the training
+    if let Some(none_index) = none_index {
+        let nb_intents = intent_list.len().max(2) - 1;
+        let nb_none_examples = (features.len() + nb_intents - 1) / nb_intents;
+        for _ in 0..nb_none_examples.max(1) {
+            features.push(vec![]);
+            labels.push(none_index);
+        }
+    }
+
+    if let (Some(intercept), Some(coeffs)) = (
+        classifier_config.intercept.as_mut(),
+        classifier_config.coeffs.as_mut(),
+    ) {
+        for (class, (class_intercept, class_coeffs)) in
+            intercept.iter_mut().zip(coeffs.iter_mut()).enumerate()
+        {
+            let targets = labels
+                .iter()
+                .map(|label| if *label == class { 1. } else { 0. })
+                .collect::<Vec<_>>();
+            let (fine_tuned_intercept, fine_tuned_coeffs) = fit_binary_logistic_regression(
+                &features,
+                &targets,
+                (*class_intercept, class_coeffs.clone()),
+                NB_FINE_TUNING_EPOCHS,
+                TRAINING_SEED,
+            );
+            *class_intercept = fine_tuned_intercept;
+            *class_coeffs = fine_tuned_coeffs;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+    use nlu_engine::SnipsNluEngine;
+    use snips_nlu_ontology::SlotValue;
+    use training::train_engine;
+    use utils::file_path;
+
+    fn lukewarm_tea_utterance() -> DatasetUtterance {
+        ::serde_json::from_str(
+            r#"{"data": [
+                {"text": "brew me a "},
+                {"text": "lukewarm", "entity": "Temperature", "slot_name": "beverage_temperature"},
+                {"text": " tea"}
+            ]}"#,
+        ).unwrap()
+    }
+
+    fn trained_configuration() -> NluEngineConfiguration {
+        let dataset = File::open(file_path("tests/datasets/beverage_dataset.json")).unwrap();
+        train_engine(dataset).unwrap()
+    }
+
+    #[test]
+    fn add_utterances_works() {
+        // Given
+        let configuration = trained_configuration();
+
+        // When
+        let updated_configuration =
+            add_utterances(configuration, "MakeTea", &[lukewarm_tea_utterance()], true).unwrap();
+        let engine = SnipsNluEngine::new(updated_configuration.clone()).unwrap();
+        let result = engine.parse("brew me a lukewarm tea", None).unwrap();
+
+        // Then
+        let temperature_entity = &updated_configuration.dataset_metadata.entities["Temperature"];
+        assert_eq!(
+            Some(&"lukewarm".to_string()),
+            temperature_entity.utterances.get("lukewarm")
+        );
+        assert_eq!("MakeTea", result.intent.unwrap().intent_name);
+        let slots = result.slots.unwrap();
+        assert_eq!(1, slots.len());
+        assert_eq!(SlotValue::Custom("lukewarm".to_string().into()), slots[0].value);
+    }
+
+    #[test]
+    fn add_utterances_should_not_forget_other_intents() {
+        // Given
+        let configuration = trained_configuration();
+        let tea_utterances = vec![
+            lukewarm_tea_utterance(),
+            ::serde_json::from_str(r#"{"data": [{"text": "some green tea"}]}"#).unwrap(),
+        ];
+
+        // When
+        let updated_configuration =
+            add_utterances(configuration, "MakeTea", &tea_utterances, true).unwrap();
+        let engine = SnipsNluEngine::new(updated_configuration).unwrap();
+        let result = engine.parse("prepare me a coffee", None).unwrap();
+
+        // Then
+        assert_eq!("MakeCoffee", result.intent.unwrap().intent_name);
+    }
+
+    #[test]
+    fn add_utterances_should_fail_with_ambiguous_utterance() {
+        // Given
+        let configuration = trained_configuration();
+        let coffee_utterance: DatasetUtterance =
+            ::serde_json::from_str(r#"{"data": [{"text": "make me a coffee"}]}"#).unwrap();
+
+        // When
+        let result = add_utterances(configuration, "MakeTea", &[coffee_utterance], false);
+
+        // Then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn get_replayed_utterances_should_remove_slot_placeholders() {
+        // Given
+        let configuration = trained_configuration();
+
+        // When
+        let replayed_utterances = get_replayed_utterances(&configuration.intent_parsers).unwrap();
+
+        // Then
+        let tea_utterances = &replayed_utterances["MakeTea"];
+        assert_eq!(5, tea_utterances.len());
+        assert!(tea_utterances.contains(&"make me a cup of tea".to_string()));
+        assert!(tea_utterances.iter().all(|utterance| !utterance.contains('{')));
+    }
+
+    #[test]
+    fn add_utterances_should_fail_with_unknown_intent() {
+        // Given
+        let configuration = trained_configuration();
+
+        // When
+        let result = add_utterances(configuration, "MakeSoup", &[lukewarm_tea_utterance()], false);
+
+        // Then
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use itertools::Itertools;
+
+use nlu_utils::string::normalize;
+use snips_nlu_ontology::BuiltinEntityKind;
+use training::dataset::Dataset;
+
+/// Minimum number of utterances of an intent for its classifier and slot filler to generalize
+const MIN_NB_UTTERANCES: usize = 5;
+/// Maximum ratio between the numbers of utterances of the largest and the smallest intents
+const MAX_IMBALANCE_RATIO: usize = 10;
+
+/// Problem found in a dataset by `Dataset::validate`
+#[derive(Debug, Clone, PartialEq)]
+pub enum DatasetDiagnostic {
+    /// An intent has fewer utterances than recommended
+    TooFewUtterances {
+        intent_name: String,
+        nb_utterances: usize,
+    },
+    /// An intent has much fewer utterances than the largest intent, which biases the classifier
+    /// towards the largest intents
+    UnbalancedIntent {
+        intent_name: String,
+        nb_utterances: usize,
+        largest_intent_nb_utterances: usize,
+    },
+    /// The entity of a slot is neither a custom entity of the dataset nor a builtin entity
+    UnknownSlotEntity {
+        intent_name: String,
+        slot_name: String,
+        entity_name: String,
+    },
+    /// The same utterance, once normalized, is found in several intents
+    DuplicateUtterance {
+        utterance: String,
+        intent_names: Vec<String>,
+    },
+}
+
+impl fmt::Display for DatasetDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DatasetDiagnostic::TooFewUtterances {
+                ref intent_name,
+                nb_utterances,
+            } => write!(
+                f,
+                "Intent '{}' has {} utterances while at least {} are recommended",
+                intent_name, nb_utterances, MIN_NB_UTTERANCES
+            ),
+            DatasetDiagnostic::UnbalancedIntent {
+                ref intent_name,
+                nb_utterances,
+                largest_intent_nb_utterances,
+            } => write!(
+                f,
+                "Intent '{}' has {} utterances while the largest intent has {}",
+                intent_name, nb_utterances, largest_intent_nb_utterances
+            ),
+            DatasetDiagnostic::UnknownSlotEntity {
+                ref intent_name,
+                ref slot_name,
+                ref entity_name,
+            } => write!(
+                f,
+                "Unknown entity '{}' of slot '{}' in intent '{}'",
+                entity_name, slot_name, intent_name
+            ),
+            DatasetDiagnostic::DuplicateUtterance {
+                ref utterance,
+                ref intent_names,
+            } => write!(
+                f,
+                "Utterance '{}' is found in intents {}",
+                utterance,
+                intent_names.iter().map(|name| format!("'{}'", name)).join(", ")
+            ),
+        }
+    }
+}
+
+impl Dataset {
+    /// Checks the dataset before training, returning the problems which would make the trained
+    /// engine inaccurate
+    pub fn validate(&self) -> Vec<DatasetDiagnostic> {
+        let mut diagnostics = vec![];
+        let largest_intent_nb_utterances = self.intents
+            .values()
+            .map(|intent| intent.utterances.len())
+            .max()
+            .unwrap_or(0);
+        let mut intents_per_utterance: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for intent_name in self.intent_names() {
+            let utterances = &self.intents[intent_name].utterances;
+            let nb_utterances = utterances.len();
+            if nb_utterances < MIN_NB_UTTERANCES {
+                diagnostics.push(DatasetDiagnostic::TooFewUtterances {
+                    intent_name: intent_name.clone(),
+                    nb_utterances,
+                });
+            }
+            if nb_utterances * MAX_IMBALANCE_RATIO < largest_intent_nb_utterances {
+                diagnostics.push(DatasetDiagnostic::UnbalancedIntent {
+                    intent_name: intent_name.clone(),
+                    nb_utterances,
+                    largest_intent_nb_utterances,
+                });
+            }
+
+            let slot_name_mapping = self.slot_name_mapping(intent_name);
+            let mut slot_names = slot_name_mapping.keys().collect_vec();
+            slot_names.sort();
+            for slot_name in slot_names {
+                let entity_name = &slot_name_mapping[slot_name];
+                if BuiltinEntityKind::from_identifier(entity_name).is_err()
+                    && !self.entities.contains_key(entity_name)
+                {
+                    diagnostics.push(DatasetDiagnostic::UnknownSlotEntity {
+                        intent_name: intent_name.clone(),
+                        slot_name: slot_name.clone(),
+                        entity_name: entity_name.clone(),
+                    });
+                }
+            }
+
+            for utterance in utterances {
+                let normalized_utterance = normalize(&utterance.text())
+                    .split_whitespace()
+                    .join(" ");
+                intents_per_utterance
+                    .entry(normalized_utterance)
+                    .or_insert_with(BTreeSet::new)
+                    .insert(intent_name.clone());
+            }
+        }
+
+        for (utterance, intent_names) in intents_per_utterance {
+            if intent_names.len() > 1 {
+                diagnostics.push(DatasetDiagnostic::DuplicateUtterance {
+                    utterance,
+                    intent_names: intent_names.into_iter().collect(),
+                });
+            }
+        }
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+    use utils::file_path;
+
+    #[test]
+    fn validate_should_not_find_problems_in_valid_dataset() {
+        // Given
+        let path = file_path("tests/datasets/beverage_dataset.json");
+        let dataset = Dataset::from_reader(File::open(path).unwrap()).unwrap();
+
+        // When
+        let diagnostics = dataset.validate();
+
+        // Then
+        assert_eq!(Vec::<DatasetDiagnostic>::new(), diagnostics);
+    }
+
+    #[test]
+    fn validate_should_find_problems() {
+        // Given
+        let mut utterances = vec![
+            r#"{"data": [
+                {"text": "make me a "},
+                {"text": "hot", "slot_name": "temperature", "entity": "Temperature"},
+                {"text": " tea"}
+            ]}"#.to_string(),
+            r#"{"data": [{"text": "Make me a   coffee"}]}"#.to_string(),
+        ];
+        utterances.extend((0..20).map(|i| format!(r#"{{"data": [{{"text": "tea {}"}}]}}"#, i)));
+        let dataset_json = format!(
+            r#"{{
+                "language": "en",
+                "intents": {{
+                    "MakeCoffee": {{"utterances": [{{"data": [{{"text": "make me a coffee"}}]}}]}},
+                    "MakeTea": {{"utterances": [{}]}}
+                }},
+                "entities": {{}}
+            }}"#,
+            utterances.join(", ")
+        );
+        let dataset = Dataset::from_reader(dataset_json.as_bytes()).unwrap();
+
+        // When
+        let diagnostics = dataset.validate();
+
+        // Then
+        let expected_diagnostics = vec![
+            DatasetDiagnostic::TooFewUtterances {
+                intent_name: "MakeCoffee".to_string(),
+                nb_utterances: 1,
+            },
+            DatasetDiagnostic::UnbalancedIntent {
+                intent_name: "MakeCoffee".to_string(),
+                nb_utterances: 1,
+                largest_intent_nb_utterances: 22,
+            },
+            DatasetDiagnostic::UnknownSlotEntity {
+                intent_name: "MakeTea".to_string(),
+                slot_name: "temperature".to_string(),
+                entity_name: "Temperature".to_string(),
+            },
+            DatasetDiagnostic::DuplicateUtterance {
+                utterance: "make me a coffee".to_string(),
+                intent_names: vec!["MakeCoffee".to_string(), "MakeTea".to_string()],
+            },
+        ];
+        assert_eq!(expected_diagnostics, diagnostics);
+    }
+}
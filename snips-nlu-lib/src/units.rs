@@ -0,0 +1,120 @@
+use snips_nlu_ontology::{SlotValue, TemperatureValue};
+
+const CELSIUS: &str = "celsius";
+const FAHRENHEIT: &str = "fahrenheit";
+const KELVIN: &str = "kelvin";
+
+/// Unit system in which the measurement values of the builtin slots are returned
+///
+/// Temperatures are the only measurement values of the ontology, the amounts of money and the
+/// durations having no metric and imperial units, so they are the only converted values.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSystem {
+    Metric,
+    Imperial,
+}
+
+impl UnitSystem {
+    fn temperature_unit(&self) -> &'static str {
+        match *self {
+            UnitSystem::Metric => CELSIUS,
+            UnitSystem::Imperial => FAHRENHEIT,
+        }
+    }
+}
+
+/// Converts the temperature values to the unit of the unit system, the other values being
+/// returned unchanged
+pub fn convert_slot_value(slot_value: SlotValue, unit_system: UnitSystem) -> SlotValue {
+    match slot_value {
+        SlotValue::Temperature(temperature) => {
+            SlotValue::Temperature(convert_temperature(temperature, unit_system))
+        }
+        _ => slot_value,
+    }
+}
+
+fn convert_temperature(temperature: TemperatureValue, unit_system: UnitSystem) -> TemperatureValue {
+    let target_unit = unit_system.temperature_unit();
+    let celsius_value = match temperature.unit.as_ref().map(|unit| &**unit) {
+        Some(CELSIUS) => temperature.value,
+        Some(FAHRENHEIT) => (temperature.value - 32.) * 5. / 9.,
+        Some(KELVIN) => temperature.value - 273.15,
+        // Unspecified units such as plain degrees are left untouched
+        _ => return temperature,
+    };
+    let value = match target_unit {
+        FAHRENHEIT => celsius_value * 9. / 5. + 32.,
+        _ => celsius_value,
+    };
+    TemperatureValue {
+        value,
+        unit: Some(target_unit.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_ontology::NumberValue;
+    use testutils::epsilon_eq;
+
+    #[test]
+    fn convert_slot_value_works_with_fahrenheit() {
+        // Given
+        let slot_value = SlotValue::Temperature(TemperatureValue {
+            value: 212.,
+            unit: Some("fahrenheit".to_string()),
+        });
+
+        // When
+        let converted = convert_slot_value(slot_value, UnitSystem::Metric);
+
+        // Then
+        if let SlotValue::Temperature(temperature) = converted {
+            assert!(epsilon_eq(100., temperature.value, 1e-4));
+            assert_eq!(Some("celsius".to_string()), temperature.unit);
+        } else {
+            panic!("Expected a temperature value")
+        }
+    }
+
+    #[test]
+    fn convert_slot_value_works_with_celsius() {
+        // Given
+        let slot_value = SlotValue::Temperature(TemperatureValue {
+            value: 20.,
+            unit: Some("celsius".to_string()),
+        });
+
+        // When
+        let converted = convert_slot_value(slot_value, UnitSystem::Imperial);
+
+        // Then
+        if let SlotValue::Temperature(temperature) = converted {
+            assert!(epsilon_eq(68., temperature.value, 1e-4));
+            assert_eq!(Some("fahrenheit".to_string()), temperature.unit);
+        } else {
+            panic!("Expected a temperature value")
+        }
+    }
+
+    #[test]
+    fn convert_slot_value_should_not_convert_unspecified_units() {
+        // Given
+        let degrees = SlotValue::Temperature(TemperatureValue {
+            value: 25.,
+            unit: Some("degree".to_string()),
+        });
+        let number = SlotValue::Number(NumberValue { value: 25. });
+
+        // When
+        let converted_degrees = convert_slot_value(degrees.clone(), UnitSystem::Imperial);
+        let converted_number = convert_slot_value(number.clone(), UnitSystem::Imperial);
+
+        // Then
+        assert_eq!(degrees, converted_degrees);
+        assert_eq!(number, converted_number);
+    }
+}
@@ -1,9 +1,101 @@
+use std::cmp::{max, Ordering};
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
 pub fn file_path(filename: &str) -> ::std::path::PathBuf {
     ::dinghy_test::try_test_file_path("data")
         .unwrap_or("../data".into())
         .join(filename)
 }
 
+/// Returns the byte index of the char at `char_index`, or the length of the text when the index
+/// is past its last char
+fn byte_index(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(byte_index, _)| byte_index)
+}
+
+/// Returns the slice of the text covered by the char range, without copying it
+///
+/// The range is taken as is, ranges which may split grapheme clusters must first be aligned with
+/// `grapheme_aligned_range`.
+pub fn substring_with_char_range<'a>(text: &'a str, char_range: &Range<usize>) -> &'a str {
+    let start = byte_index(text, char_range.start);
+    let end = start + byte_index(&text[start..], char_range.end - char_range.start);
+    &text[start..end]
+}
+
+/// Char indexes of the starts of the grapheme clusters of the text, followed by its number of
+/// chars, which can be computed once to align several ranges of the same text with
+/// `align_on_grapheme_boundaries`
+pub fn grapheme_boundaries(text: &str) -> Vec<usize> {
+    let mut boundaries = vec![];
+    let mut nb_chars = 0;
+    for grapheme in text.graphemes(true) {
+        boundaries.push(nb_chars);
+        nb_chars += grapheme.chars().count();
+    }
+    boundaries.push(nb_chars);
+    boundaries
+}
+
+/// Returns the range of the indexes of the grapheme clusters overlapping the char range
+fn grapheme_indexes(boundaries: &[usize], char_range: &Range<usize>) -> Range<usize> {
+    let start = boundaries
+        .iter()
+        .rposition(|boundary| *boundary <= char_range.start)
+        .unwrap_or(0);
+    let end = boundaries
+        .iter()
+        .position(|boundary| *boundary >= char_range.end)
+        .unwrap_or(boundaries.len() - 1);
+    start..max(start, end)
+}
+
+/// Widens the char range so that it starts and ends on grapheme cluster boundaries, letters
+/// followed by combining accents or emoji ZWJ sequences never being split
+pub fn grapheme_aligned_range(text: &str, char_range: &Range<usize>) -> Range<usize> {
+    align_on_grapheme_boundaries(&grapheme_boundaries(text), char_range)
+}
+
+/// Widens the char range like `grapheme_aligned_range`, given the `grapheme_boundaries` of the
+/// text
+pub fn align_on_grapheme_boundaries(
+    boundaries: &[usize],
+    char_range: &Range<usize>,
+) -> Range<usize> {
+    let indexes = grapheme_indexes(boundaries, char_range);
+    boundaries[indexes.start]..boundaries[indexes.end]
+}
+
+/// Converts the char range to the range of the grapheme clusters of the text it overlaps, that
+/// is to a range of user-perceived characters
+pub fn char_range_to_grapheme_range(text: &str, char_range: &Range<usize>) -> Range<usize> {
+    grapheme_indexes(&grapheme_boundaries(text), char_range)
+}
+
+/// Returns the slice of the text starting at the char index, without copying it
+pub fn suffix_from_char_index(text: &str, char_index: usize) -> &str {
+    &text[byte_index(text, char_index)..]
+}
+
+/// Whether the text is empty or only made of whitespaces and control characters, from which
+/// nothing can be parsed
+pub fn is_blank(text: &str) -> bool {
+    text.chars().all(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Orders scores by decreasing value, NaN scores coming last, so that sorting scores never
+/// panics
+pub fn decreasing_order<T: Into<f64> + Copy>(score: T, other_score: T) -> Ordering {
+    let (score, other_score) = (score.into(), other_score.into());
+    other_score
+        .partial_cmp(&score)
+        .unwrap_or_else(|| score.is_nan().cmp(&other_score.is_nan()))
+}
+
 pub fn permutations<T: Copy>(v: &[T], permutation_length: i32) -> Vec<Vec<T>> {
     if permutation_length > v.len() as i32 {
         panic!("permutation_length must be greater than 0 and less than the length of v")
@@ -62,6 +154,87 @@ mod tests {
     use std::iter::FromIterator;
     use itertools::repeat_n;
 
+    #[test]
+    fn substring_with_char_range_works() {
+        // Given
+        let text = "un café crème";
+
+        // When
+        let substrings = vec![
+            substring_with_char_range(text, &(3..7)),
+            substring_with_char_range(text, &(8..13)),
+            substring_with_char_range(text, &(13..13)),
+            suffix_from_char_index(text, 8),
+        ];
+
+        // Then
+        assert_eq!(vec!["café", "crème", "", "crème"], substrings);
+    }
+
+    #[test]
+    fn substring_with_char_range_should_be_exact() {
+        // Given
+        let text = "un cafe\u{301} \u{1f469}\u{200d}\u{1f4bb}";
+
+        // When
+        let substrings = vec![
+            substring_with_char_range(text, &(3..7)),
+            substring_with_char_range(text, &grapheme_aligned_range(text, &(3..7))),
+            substring_with_char_range(text, &grapheme_aligned_range(text, &(9..10))),
+        ];
+
+        // Then
+        assert_eq!(
+            vec!["cafe", "cafe\u{301}", "\u{1f469}\u{200d}\u{1f4bb}"],
+            substrings
+        );
+    }
+
+    #[test]
+    fn grapheme_ranges_work() {
+        // Given
+        let text = "un cafe\u{301} \u{1f469}\u{200d}\u{1f4bb}";
+
+        // When
+        let aligned_ranges = vec![
+            grapheme_aligned_range(text, &(3..7)),
+            grapheme_aligned_range(text, &(10..11)),
+            grapheme_aligned_range(text, &(12..12)),
+        ];
+        let grapheme_ranges = vec![
+            char_range_to_grapheme_range(text, &(3..8)),
+            char_range_to_grapheme_range(text, &(9..12)),
+        ];
+
+        // Then
+        assert_eq!(vec![3..8, 9..12, 12..12], aligned_ranges);
+        assert_eq!(vec![3..7, 8..9], grapheme_ranges);
+    }
+
+    #[test]
+    fn is_blank_works() {
+        // When
+        let blank_texts = vec!["", " ", "\t\n\r", "\u{0}\u{7f} \u{a0}"];
+        let texts = vec!["a", " 2 ", "\u{0}é"];
+
+        // Then
+        assert!(blank_texts.into_iter().all(is_blank));
+        assert!(!texts.into_iter().any(is_blank));
+    }
+
+    #[test]
+    fn decreasing_order_should_put_nan_last() {
+        // Given
+        let mut scores = vec![0.2, ::std::f32::NAN, 0.7, 0.5, ::std::f32::NAN];
+
+        // When
+        scores.sort_by(|a, b| decreasing_order(*a, *b));
+
+        // Then
+        assert_eq!(vec![0.7, 0.5, 0.2], scores[..3].to_vec());
+        assert!(scores[3..].iter().all(|score| score.is_nan()));
+    }
+
     #[test]
     fn permutations_works() {
         // Given
@@ -0,0 +1,35 @@
+const { Engine } = require('../native');
+
+class NluEngine {
+  constructor(engine) {
+    this.engine = engine;
+  }
+
+  /**
+   * Parses the text on a thread of the libuv pool, the options being
+   * `intentsFilter`, a list of intent names, `builtinEntitiesLanguages`,
+   * a list of language codes, `parallelParsers`, a boolean, and
+   * `graphemeRanges`, a boolean giving the ranges of the slots in grapheme
+   * clusters
+   */
+  parse(text, options = {}) {
+    return new Promise((resolve, reject) => {
+      this.engine.parse(text, JSON.stringify(options), (error, resultJson) => {
+        if (error) {
+          reject(error);
+        } else {
+          resolve(JSON.parse(resultJson));
+        }
+      });
+    });
+  }
+}
+
+/**
+ * Loads the engine from the directory of a trained model
+ */
+function loadEngine(path) {
+  return new NluEngine(new Engine(path));
+}
+
+module.exports = { loadEngine };
@@ -0,0 +1,113 @@
+//! Node.js bindings of the engine, built with `neon` and wrapped by `lib/index.js`:
+//!
+//! ```js
+//! const engine = loadEngine("path/to/trained_engine");
+//! const options = { intentsFilter: ["MakeCoffee"] };
+//! const result = await engine.parse("Make me two cups of coffee", options);
+//! ```
+//!
+//! Parsing runs on the thread pool of libuv, so that the event loop is not blocked by the engine.
+
+#[macro_use]
+extern crate neon;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate snips_nlu_lib;
+extern crate snips_nlu_ontology;
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use neon::prelude::*;
+use snips_nlu_lib::{result_to_json_string, ParseOptions, SnipsNluEngine};
+use snips_nlu_ontology::Language;
+
+pub struct NluEngine {
+    engine: Arc<SnipsNluEngine>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsParseOptions {
+    intents_filter: Option<Vec<String>>,
+    builtin_entities_languages: Option<Vec<String>>,
+    parallel_parsers: Option<bool>,
+    grapheme_ranges: Option<bool>,
+}
+
+impl JsParseOptions {
+    fn into_parse_options(self) -> Result<ParseOptions, String> {
+        let builtin_entities_languages = match self.builtin_entities_languages {
+            Some(languages) => Some(languages
+                .iter()
+                .map(|language| Language::from_str(language).map_err(|e| e.to_string()))
+                .collect::<Result<Vec<_>, _>>()?),
+            None => None,
+        };
+        Ok(ParseOptions {
+            intents_filter: self.intents_filter,
+            builtin_entities_languages,
+            parallel_parsers: self.parallel_parsers.unwrap_or(false),
+            grapheme_ranges: self.grapheme_ranges.unwrap_or(false),
+        })
+    }
+}
+
+struct ParseTask {
+    engine: Arc<SnipsNluEngine>,
+    text: String,
+    options_json: String,
+}
+
+impl Task for ParseTask {
+    type Output = String;
+    type Error = String;
+    type JsEvent = JsString;
+
+    fn perform(&self) -> Result<String, String> {
+        let options: JsParseOptions =
+            serde_json::from_str(&self.options_json).map_err(|e| e.to_string())?;
+        let result = self.engine
+            .parse_with_options(&self.text, &options.into_parse_options()?)
+            .map_err(|e| e.to_string())?;
+        result_to_json_string(&result).map_err(|e| e.to_string())
+    }
+
+    fn complete(self, mut cx: TaskContext, result: Result<String, String>) -> JsResult<JsString> {
+        match result {
+            Ok(result_json) => Ok(cx.string(result_json)),
+            Err(error) => cx.throw_error(error),
+        }
+    }
+}
+
+declare_types! {
+    pub class JsEngine for NluEngine {
+        init(mut cx) {
+            let path = cx.argument::<JsString>(0)?.value();
+            let engine = match SnipsNluEngine::from_path(&path) {
+                Ok(engine) => engine,
+                Err(error) => return cx.throw_error(error.to_string()),
+            };
+            Ok(NluEngine { engine: Arc::new(engine) })
+        }
+
+        method parse(mut cx) {
+            let text = cx.argument::<JsString>(0)?.value();
+            let options_json = cx.argument::<JsString>(1)?.value();
+            let callback = cx.argument::<JsFunction>(2)?;
+            let this = cx.this();
+            let engine = {
+                let guard = cx.lock();
+                let nlu_engine = this.borrow(&guard);
+                nlu_engine.engine.clone()
+            };
+            ParseTask { engine, text, options_json }.schedule(callback);
+            Ok(cx.undefined().upcast())
+        }
+    }
+}
+
+register_module!(mut cx, { cx.export_class::<JsEngine>("Engine") });
@@ -0,0 +1,31 @@
+#[cfg(feature = "grpc")]
+extern crate protoc_grpcio;
+
+#[cfg(feature = "grpc")]
+use std::env;
+#[cfg(feature = "grpc")]
+use std::fs;
+#[cfg(feature = "grpc")]
+use std::path::PathBuf;
+
+#[cfg(not(feature = "grpc"))]
+fn main() {}
+
+#[cfg(feature = "grpc")]
+fn main() {
+    println!("cargo:rerun-if-changed=proto/snips_nlu.proto");
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is not set"));
+    protoc_grpcio::compile_grpc_protos(&["snips_nlu.proto"], &["proto"], &out_dir, None)
+        .expect("Failed to compile the protobuf definitions");
+
+    // The generated files start with inner attributes, so they are declared as modules rather
+    // than included in `src/proto/mod.rs`
+    let modules = ["snips_nlu", "snips_nlu_grpc"]
+        .iter()
+        .map(|module| {
+            let path = out_dir.join(format!("{}.rs", module));
+            format!("#[path = {:?}]\npub mod {};\n", path.display().to_string(), module)
+        })
+        .collect::<String>();
+    fs::write(out_dir.join("modules.rs"), modules).expect("Failed to write the proto modules");
+}
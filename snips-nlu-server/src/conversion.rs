@@ -0,0 +1,222 @@
+use protobuf::RepeatedField;
+use snips_nlu_lib::tagging::TaggedToken;
+use snips_nlu_ontology::{AmountOfMoneyValue, DurationValue, Grain, InstantTimeValue,
+                         IntentClassifierResult, IntentParserResult, Precision, Slot, SlotValue,
+                         TemperatureValue, TimeIntervalValue};
+
+use proto::snips_nlu;
+
+impl From<IntentParserResult> for snips_nlu::IntentParserResult {
+    fn from(result: IntentParserResult) -> Self {
+        let mut message = Self::new();
+        message.set_input(result.input);
+        if let Some(intent) = result.intent {
+            message.set_intent(intent.into());
+        }
+        message.set_slots(convert_slots(result.slots.unwrap_or_else(Vec::new)));
+        message
+    }
+}
+
+impl From<IntentClassifierResult> for snips_nlu::IntentClassifierResult {
+    fn from(intent: IntentClassifierResult) -> Self {
+        let mut message = Self::new();
+        message.set_intent_name(intent.intent_name);
+        message.set_probability(intent.probability);
+        message
+    }
+}
+
+impl From<Slot> for snips_nlu::Slot {
+    fn from(slot: Slot) -> Self {
+        let mut message = Self::new();
+        if let Some(range) = slot.range {
+            let mut range_message = snips_nlu::Range::new();
+            range_message.set_start(range.start as u32);
+            range_message.set_end(range.end as u32);
+            message.set_range(range_message);
+        }
+        message.set_raw_value(slot.raw_value);
+        message.set_value(slot.value.into());
+        message.set_entity(slot.entity);
+        message.set_slot_name(slot.slot_name);
+        message
+    }
+}
+
+impl From<SlotValue> for snips_nlu::SlotValue {
+    fn from(value: SlotValue) -> Self {
+        let mut message = Self::new();
+        match value {
+            SlotValue::Custom(value) => message.set_custom(value.value),
+            SlotValue::Number(value) => message.set_number(value.value),
+            SlotValue::Ordinal(value) => message.set_ordinal(value.value),
+            SlotValue::Percentage(value) => message.set_percentage(value.value),
+            SlotValue::InstantTime(value) => message.set_instant_time(value.into()),
+            SlotValue::TimeInterval(value) => message.set_time_interval(value.into()),
+            SlotValue::AmountOfMoney(value) => message.set_amount_of_money(value.into()),
+            SlotValue::Temperature(value) => message.set_temperature(value.into()),
+            SlotValue::Duration(value) => message.set_duration(value.into()),
+        }
+        message
+    }
+}
+
+impl From<InstantTimeValue> for snips_nlu::InstantTimeValue {
+    fn from(value: InstantTimeValue) -> Self {
+        let mut message = Self::new();
+        message.set_value(value.value);
+        message.set_grain(value.grain.into());
+        message.set_precision(value.precision.into());
+        message
+    }
+}
+
+impl From<TimeIntervalValue> for snips_nlu::TimeIntervalValue {
+    fn from(value: TimeIntervalValue) -> Self {
+        let mut message = Self::new();
+        message.set_from(value.from.unwrap_or_else(String::new));
+        message.set_to(value.to.unwrap_or_else(String::new));
+        message
+    }
+}
+
+impl From<AmountOfMoneyValue> for snips_nlu::AmountOfMoneyValue {
+    fn from(value: AmountOfMoneyValue) -> Self {
+        let mut message = Self::new();
+        message.set_value(value.value);
+        message.set_precision(value.precision.into());
+        message.set_unit(value.unit.unwrap_or_else(String::new));
+        message
+    }
+}
+
+impl From<TemperatureValue> for snips_nlu::TemperatureValue {
+    fn from(value: TemperatureValue) -> Self {
+        let mut message = Self::new();
+        message.set_value(value.value);
+        message.set_unit(value.unit.unwrap_or_else(String::new));
+        message
+    }
+}
+
+impl From<DurationValue> for snips_nlu::DurationValue {
+    fn from(value: DurationValue) -> Self {
+        let mut message = Self::new();
+        message.set_years(value.years);
+        message.set_quarters(value.quarters);
+        message.set_months(value.months);
+        message.set_weeks(value.weeks);
+        message.set_days(value.days);
+        message.set_hours(value.hours);
+        message.set_minutes(value.minutes);
+        message.set_seconds(value.seconds);
+        message.set_precision(value.precision.into());
+        message
+    }
+}
+
+impl From<Grain> for snips_nlu::Grain {
+    fn from(grain: Grain) -> Self {
+        match grain {
+            Grain::Year => snips_nlu::Grain::YEAR,
+            Grain::Quarter => snips_nlu::Grain::QUARTER,
+            Grain::Month => snips_nlu::Grain::MONTH,
+            Grain::Week => snips_nlu::Grain::WEEK,
+            Grain::Day => snips_nlu::Grain::DAY,
+            Grain::Hour => snips_nlu::Grain::HOUR,
+            Grain::Minute => snips_nlu::Grain::MINUTE,
+            Grain::Second => snips_nlu::Grain::SECOND,
+        }
+    }
+}
+
+impl From<Precision> for snips_nlu::Precision {
+    fn from(precision: Precision) -> Self {
+        match precision {
+            Precision::Approximate => snips_nlu::Precision::APPROXIMATE,
+            Precision::Exact => snips_nlu::Precision::EXACT,
+        }
+    }
+}
+
+pub fn convert_slots(slots: Vec<Slot>) -> RepeatedField<snips_nlu::Slot> {
+    slots.into_iter().map(|slot| slot.into()).collect()
+}
+
+/// Converts the tokens tagged with the slots of an input, see `SnipsNluEngine::tag`
+pub fn convert_tagged_tokens(
+    tagged_tokens: Vec<TaggedToken>,
+) -> RepeatedField<snips_nlu::TaggedToken> {
+    tagged_tokens
+        .into_iter()
+        .map(|token| {
+            let mut message = snips_nlu::TaggedToken::new();
+            message.set_range_start(token.range.start as u32);
+            message.set_range_end(token.range.end as u32);
+            message.set_value(token.value);
+            if let Some(slot_name) = token.slot_name {
+                message.set_slot_name(slot_name);
+            }
+            if let Some(confidence) = token.confidence {
+                message.set_confidence(confidence);
+            }
+            message
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_ontology::NumberValue;
+
+    #[test]
+    fn slot_conversion_works() {
+        // Given
+        let slot = Slot {
+            raw_value: "tomorrow at 8am".to_string(),
+            value: SlotValue::InstantTime(InstantTimeValue {
+                value: "2018-06-13 08:00:00 +02:00".to_string(),
+                grain: Grain::Hour,
+                precision: Precision::Exact,
+            }),
+            range: Some(10..25),
+            entity: "snips/datetime".to_string(),
+            slot_name: "time".to_string(),
+        };
+        let number_value = SlotValue::Number(NumberValue { value: 2.0 });
+
+        // When
+        let slot_message: snips_nlu::Slot = slot.into();
+        let number_message: snips_nlu::SlotValue = number_value.into();
+
+        // Then
+        let instant_time = slot_message.get_value().get_instant_time();
+        assert_eq!("2018-06-13 08:00:00 +02:00", instant_time.get_value());
+        assert_eq!(snips_nlu::Grain::HOUR, instant_time.get_grain());
+        assert_eq!(snips_nlu::Precision::EXACT, instant_time.get_precision());
+        assert_eq!(10, slot_message.get_range().get_start());
+        assert_eq!(25, slot_message.get_range().get_end());
+        assert!(number_message.has_number());
+        assert_eq!(2.0, number_message.get_number());
+    }
+
+    #[test]
+    fn slot_conversion_should_leave_missing_ranges_unset() {
+        // Given
+        let slot = Slot {
+            raw_value: "two".to_string(),
+            value: SlotValue::Number(NumberValue { value: 2.0 }),
+            range: None,
+            entity: "snips/number".to_string(),
+            slot_name: "number_of_cups".to_string(),
+        };
+
+        // When
+        let slot_message: snips_nlu::Slot = slot.into();
+
+        // Then
+        assert!(!slot_message.has_range());
+    }
+}
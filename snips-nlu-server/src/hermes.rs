@@ -0,0 +1,186 @@
+use rumqtt::{MqttClient, MqttOptions, Notification, QoS};
+use serde_json;
+use snips_nlu_lib::{Error, Result, SnipsNluEngine, RESULT_FORMAT_VERSION};
+use snips_nlu_ontology::{IntentClassifierResult, IntentParserResult, Slot};
+
+pub const QUERY_TOPIC: &str = "hermes/nlu/query";
+pub const INTENT_PARSED_TOPIC: &str = "hermes/nlu/intentParsed";
+pub const INTENT_NOT_RECOGNIZED_TOPIC: &str = "hermes/nlu/intentNotRecognized";
+pub const ERROR_TOPIC: &str = "hermes/error/nlu";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NluQueryMessage {
+    input: String,
+    intent_filter: Option<Vec<String>>,
+    id: Option<String>,
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NluIntentMessage {
+    result_format_version: u32,
+    id: Option<String>,
+    input: String,
+    intent: IntentClassifierResult,
+    slots: Vec<Slot>,
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NluIntentNotRecognizedMessage {
+    result_format_version: u32,
+    id: Option<String>,
+    input: String,
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NluErrorMessage {
+    session_id: Option<String>,
+    error: String,
+    context: Option<String>,
+}
+
+/// Subscribes to the NLU queries of the hermes protocol on the MQTT broker, and publishes the
+/// parsing result of each query on the `intentParsed` or `intentNotRecognized` topic
+pub fn serve(engine: SnipsNluEngine, broker_host: &str, broker_port: u16) -> Result<()> {
+    let mqtt_options = MqttOptions::new("snips-nlu-server", broker_host, broker_port);
+    let (mut client, notifications) = MqttClient::start(mqtt_options)
+        .map_err(|e| Error::from(format!("Cannot connect to the MQTT broker: {:?}", e)))?;
+    client
+        .subscribe(QUERY_TOPIC, QoS::AtLeastOnce)
+        .map_err(|e| Error::from(format!("Cannot subscribe to {}: {:?}", QUERY_TOPIC, e)))?;
+    println!("Listening on {} at {}:{}", QUERY_TOPIC, broker_host, broker_port);
+    for notification in notifications {
+        if let Notification::Publish(publish) = notification {
+            let (topic, payload) = handle_query(&engine, &publish.payload);
+            if let Err(e) = client.publish(topic, QoS::AtLeastOnce, false, payload) {
+                eprintln!("Failed to publish on {}: {:?}", topic, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the topic and the payload of the message answering the query
+fn handle_query(engine: &SnipsNluEngine, payload: &[u8]) -> (&'static str, Vec<u8>) {
+    let query: NluQueryMessage = match serde_json::from_slice(payload) {
+        Ok(query) => query,
+        Err(e) => {
+            let context = String::from_utf8_lossy(payload).into_owned();
+            return error_message(None, &Error::from(e), Some(context));
+        }
+    };
+    let intents_filter = query.intent_filter.as_ref().map(|intents| &**intents);
+    match engine
+        .parse(&query.input, intents_filter)
+        .and_then(|result| build_response(query.id, query.session_id.clone(), result))
+    {
+        Ok(response) => response,
+        Err(e) => error_message(query.session_id, &e, Some(query.input)),
+    }
+}
+
+fn build_response(
+    id: Option<String>,
+    session_id: Option<String>,
+    result: IntentParserResult,
+) -> Result<(&'static str, Vec<u8>)> {
+    Ok(match result.intent {
+        Some(intent) => {
+            let message = NluIntentMessage {
+                result_format_version: RESULT_FORMAT_VERSION,
+                id,
+                input: result.input,
+                intent,
+                slots: result.slots.unwrap_or_else(Vec::new),
+                session_id,
+            };
+            (INTENT_PARSED_TOPIC, serde_json::to_vec(&message)?)
+        }
+        None => {
+            let message = NluIntentNotRecognizedMessage {
+                result_format_version: RESULT_FORMAT_VERSION,
+                id,
+                input: result.input,
+                session_id,
+            };
+            (INTENT_NOT_RECOGNIZED_TOPIC, serde_json::to_vec(&message)?)
+        }
+    })
+}
+
+fn error_message(
+    session_id: Option<String>,
+    error: &Error,
+    context: Option<String>,
+) -> (&'static str, Vec<u8>) {
+    let message = NluErrorMessage {
+        session_id,
+        error: error.to_string(),
+        context,
+    };
+    let payload = serde_json::to_vec(&message).unwrap_or_else(|e| e.to_string().into_bytes());
+    (ERROR_TOPIC, payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use snips_nlu_lib::SnipsNluEngineBuilder;
+
+    fn test_engine() -> SnipsNluEngine {
+        SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me a coffee"])
+            .intent("MakeTea", &["make me a tea"])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn handle_query_should_publish_parsed_intent() {
+        // Given
+        let engine = test_engine();
+        let query = br#"{"input": "make me a tea", "id": "42", "sessionId": "abc"}"#;
+
+        // When
+        let (topic, payload) = handle_query(&engine, query);
+
+        // Then
+        let message: Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(INTENT_PARSED_TOPIC, topic);
+        assert_eq!("MakeTea", message["intent"]["intentName"]);
+        assert_eq!("42", message["id"]);
+        assert_eq!("abc", message["sessionId"]);
+        assert_eq!(1, message["resultFormatVersion"]);
+    }
+
+    #[test]
+    fn handle_query_should_publish_intent_not_recognized() {
+        // Given
+        let engine = test_engine();
+        let query = br#"{"input": "what is the weather"}"#;
+
+        // When
+        let (topic, payload) = handle_query(&engine, query);
+
+        // Then
+        let message: Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(INTENT_NOT_RECOGNIZED_TOPIC, topic);
+        assert_eq!("what is the weather", message["input"]);
+    }
+
+    #[test]
+    fn handle_query_should_publish_errors() {
+        // When
+        let (topic, _) = handle_query(&test_engine(), b"not json");
+
+        // Then
+        assert_eq!(ERROR_TOPIC, topic);
+    }
+}
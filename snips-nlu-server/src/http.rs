@@ -0,0 +1,154 @@
+use std::io::Read;
+use std::sync::Arc;
+use std::thread;
+
+use serde_json::{self, Value};
+use snips_nlu_lib::{result_to_json, Error, Result, SnipsNluEngine};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Maximum size in bytes of the body of a request, larger bodies being rejected with a 413 status
+const MAX_BODY: u64 = 1 << 20;
+
+#[derive(Deserialize)]
+struct ParseBody {
+    input: String,
+    intents_filter: Option<Vec<String>>,
+}
+
+/// Serves the engine over HTTP with the given number of threads, each thread handling one request
+/// at a time:
+///
+/// - `POST /parse` with a `{"input": ..., "intents_filter": [...]}` body returns the parsing
+/// result, in the versioned JSON format of the `schema` module of the library
+/// - `GET /model/info` returns the language, the model version and the intents of the engine
+/// - `GET /health` returns `{"status": "ok"}` once the engine is loaded
+pub fn serve(engine: SnipsNluEngine, host: &str, port: u16, threads: usize) -> Result<()> {
+    let server = Server::http((host, port))
+        .map_err(|e| Error::from(format!("Cannot start the HTTP server: {}", e)))?;
+    println!("Listening on http://{}:{}", host, port);
+    let server = Arc::new(server);
+    let engine = Arc::new(engine);
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let server = server.clone();
+            let engine = engine.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle(&engine, request);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle
+            .join()
+            .map_err(|_| Error::from("HTTP server thread panicked"))?;
+    }
+    Ok(())
+}
+
+fn handle(engine: &SnipsNluEngine, mut request: Request) {
+    let mut body = vec![];
+    // One more byte than the limit is read to tell apart the bodies which exceed it
+    let read_body = request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_end(&mut body);
+    let (status, response) = match read_body {
+        Ok(size) if size as u64 > MAX_BODY => (
+            413,
+            json!({ "error": format!("Body exceeds the maximum size of {} bytes", MAX_BODY) }),
+        ),
+        Ok(_) => match String::from_utf8(body) {
+            Ok(body) => route(engine, request.method(), request.url(), &body),
+            Err(e) => (400, json!({ "error": e.to_string() })),
+        },
+        Err(e) => (400, json!({ "error": e.to_string() })),
+    };
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    let response = Response::from_string(response.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        eprintln!("Failed to send the response: {}", e);
+    }
+}
+
+fn route(engine: &SnipsNluEngine, method: &Method, url: &str, body: &str) -> (u16, Value) {
+    match (method, url) {
+        (&Method::Post, "/parse") => {
+            let body: ParseBody = match serde_json::from_str(body) {
+                Ok(body) => body,
+                Err(e) => return (400, json!({ "error": e.to_string() })),
+            };
+            let intents_filter = body.intents_filter.as_ref().map(|intents| &**intents);
+            match engine
+                .parse(&body.input, intents_filter)
+                .and_then(|result| result_to_json(&result))
+            {
+                Ok(result) => (200, result),
+                Err(e) => (500, json!({ "error": e.to_string() })),
+            }
+        }
+        (&Method::Get, "/model/info") => (
+            200,
+            json!({
+                "language": engine.language().to_string(),
+                "model_version": SnipsNluEngine::model_version(),
+                "intents": engine.intents(),
+            }),
+        ),
+        (&Method::Get, "/health") => (200, json!({ "status": "ok" })),
+        _ => (404, json!({ "error": format!("Unknown route: {} {}", method, url) })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snips_nlu_lib::SnipsNluEngineBuilder;
+
+    fn test_engine() -> SnipsNluEngine {
+        SnipsNluEngineBuilder::new()
+            .intent("MakeCoffee", &["make me a coffee"])
+            .intent("MakeTea", &["make me a tea"])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn route_should_parse_inputs() {
+        // Given
+        let engine = test_engine();
+
+        // When
+        let (status, result) = route(
+            &engine,
+            &Method::Post,
+            "/parse",
+            r#"{"input": "make me a tea"}"#,
+        );
+        let (invalid_status, _) = route(&engine, &Method::Post, "/parse", "make me a tea");
+
+        // Then
+        assert_eq!(200, status);
+        assert_eq!(json!("MakeTea"), result["intent"]["intentName"]);
+        assert_eq!(json!(1), result["resultFormatVersion"]);
+        assert_eq!(400, invalid_status);
+    }
+
+    #[test]
+    fn route_should_return_model_info() {
+        // Given
+        let engine = test_engine();
+
+        // When
+        let (status, info) = route(&engine, &Method::Get, "/model/info", "");
+        let (unknown_status, _) = route(&engine, &Method::Get, "/model", "");
+
+        // Then
+        assert_eq!(200, status);
+        assert_eq!(json!(["MakeCoffee", "MakeTea"]), info["intents"]);
+        assert_eq!(404, unknown_status);
+    }
+}
@@ -0,0 +1,210 @@
+//! gRPC server exposing a trained engine, whose service is defined in `proto/snips_nlu.proto`:
+//!
+//! ```bash
+//! snips-nlu-server path/to/trained_engine --port 50051 --threads 4
+//! ```
+//!
+//! The engine is shared by the threads of the server, and can be replaced without downtime with
+//! the `Reload` call, the requests being handled when the reload starts finishing on the
+//! previous engine. The `Reload` call reloads the model given at startup, or a model of the
+//! directory given with `--models-dir`, the other paths being rejected.
+//!
+//! The gRPC server requires the `grpc` feature, which is enabled by default. When built with the
+//! `http` feature, the engine can be served over HTTP instead with the `--http` flag, see the
+//! `http` module for the routes. When built with the `hermes` feature, the
+//! engine can answer the NLU queries of the hermes protocol on an MQTT broker with the `--mqtt`
+//! option.
+
+extern crate clap;
+#[cfg(feature = "grpc")]
+extern crate futures;
+#[cfg(feature = "grpc")]
+extern crate grpcio;
+#[cfg(feature = "grpc")]
+extern crate protobuf;
+#[cfg(feature = "hermes")]
+extern crate rumqtt;
+#[cfg(any(feature = "http", feature = "hermes"))]
+extern crate serde;
+#[cfg(any(feature = "http", feature = "hermes"))]
+#[macro_use]
+extern crate serde_derive;
+#[cfg_attr(feature = "http", macro_use)]
+extern crate serde_json;
+extern crate snips_nlu_lib;
+extern crate snips_nlu_ontology;
+#[cfg(feature = "http")]
+extern crate tiny_http;
+
+#[cfg(feature = "grpc")]
+mod conversion;
+#[cfg(feature = "hermes")]
+mod hermes;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "grpc")]
+mod proto;
+#[cfg(feature = "grpc")]
+mod service;
+
+use std::path::{Path, PathBuf};
+use std::process;
+#[cfg(feature = "grpc")]
+use std::sync::Arc;
+#[cfg(feature = "grpc")]
+use std::thread;
+
+use clap::{App, Arg, ArgMatches};
+#[cfg(feature = "grpc")]
+use grpcio::{EnvBuilder, ServerBuilder};
+use snips_nlu_lib::{Result, ResultExt, SnipsNluEngine};
+
+#[cfg(feature = "grpc")]
+use proto::snips_nlu_grpc::create_snips_nlu;
+#[cfg(feature = "grpc")]
+use service::SnipsNluService;
+
+fn main() {
+    let app = App::new("snips-nlu-server")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Serves a trained Snips NLU engine over gRPC")
+        .arg(
+            Arg::with_name("MODEL")
+                .help("Directory or zip archive of the trained engine")
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("host")
+                .long("host")
+                .takes_value(true)
+                .default_value("127.0.0.1"),
+        )
+        .arg(
+            Arg::with_name("port")
+                .long("port")
+                .takes_value(true)
+                .default_value("50051"),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .long("threads")
+                .takes_value(true)
+                .default_value("4")
+                .help("Number of threads handling the requests"),
+        );
+    #[cfg(feature = "grpc")]
+    let app = app.arg(
+        Arg::with_name("models-dir")
+            .long("models-dir")
+            .takes_value(true)
+            .help("Directory of the models which the clients can reload by path"),
+    );
+    #[cfg(feature = "http")]
+    let app = app.arg(
+        Arg::with_name("http")
+            .long("http")
+            .help("Serves the engine over HTTP instead of gRPC"),
+    );
+    #[cfg(feature = "hermes")]
+    let app = app.arg(
+        Arg::with_name("mqtt")
+            .long("mqtt")
+            .takes_value(true)
+            .value_name("HOST:PORT")
+            .help("Answers the hermes NLU queries of the MQTT broker instead of serving gRPC"),
+    );
+    let matches = app.get_matches();
+
+    if let Err(error) = run(&matches) {
+        eprintln!("Error: {}", error);
+        for cause in error.iter().skip(1) {
+            eprintln!("Caused by: {}", cause);
+        }
+        process::exit(1);
+    }
+}
+
+fn run(matches: &ArgMatches) -> Result<()> {
+    let model_path = PathBuf::from(matches.value_of("MODEL").unwrap());
+    let host = matches.value_of("host").unwrap();
+    let port: u16 = matches
+        .value_of("port")
+        .unwrap()
+        .parse()
+        .chain_err(|| "Invalid port")?;
+    let threads: usize = matches
+        .value_of("threads")
+        .unwrap()
+        .parse()
+        .chain_err(|| "Invalid number of threads")?;
+
+    let engine = load_engine(&model_path)?;
+    #[cfg(feature = "http")]
+    {
+        if matches.is_present("http") {
+            return http::serve(engine, host, port, threads);
+        }
+    }
+    #[cfg(feature = "hermes")]
+    {
+        if let Some(broker) = matches.value_of("mqtt") {
+            let (broker_host, broker_port) = parse_broker_address(broker)?;
+            return hermes::serve(engine, broker_host, broker_port);
+        }
+    }
+    serve_grpc(engine, model_path, matches, host, port, threads)
+}
+
+/// Loads the engine from the directory or the zip archive of a trained model
+pub fn load_engine<P: AsRef<Path>>(model_path: P) -> Result<SnipsNluEngine> {
+    SnipsNluEngine::from_model_path(&model_path)
+        .chain_err(|| format!("Cannot load the engine from {:?}", model_path.as_ref()))
+}
+
+#[cfg(feature = "grpc")]
+fn serve_grpc(
+    engine: SnipsNluEngine,
+    model_path: PathBuf,
+    matches: &ArgMatches,
+    host: &str,
+    port: u16,
+    threads: usize,
+) -> Result<()> {
+    let models_dir = matches.value_of("models-dir").map(PathBuf::from);
+    let service = create_snips_nlu(SnipsNluService::new(engine, model_path, models_dir));
+    let env = Arc::new(EnvBuilder::new().cq_count(threads).build());
+    let mut server = ServerBuilder::new(env)
+        .register_service(service)
+        .bind(host, port)
+        .build()
+        .chain_err(|| "Cannot build the server")?;
+    server.start();
+    for &(ref bound_host, bound_port) in server.bind_addrs() {
+        println!("Listening on {}:{}", bound_host, bound_port);
+    }
+    loop {
+        thread::park();
+    }
+}
+
+#[cfg(not(feature = "grpc"))]
+fn serve_grpc(
+    _engine: SnipsNluEngine,
+    _model_path: PathBuf,
+    _matches: &ArgMatches,
+    _host: &str,
+    _port: u16,
+    _threads: usize,
+) -> Result<()> {
+    Err("The server was built without the 'grpc' feature, see --help for the other modes".into())
+}
+
+#[cfg(feature = "hermes")]
+fn parse_broker_address(address: &str) -> Result<(&str, u16)> {
+    let mut parts = address.rsplitn(2, ':');
+    let port = parts.next().unwrap();
+    match parts.next() {
+        Some(host) => Ok((host, port.parse().chain_err(|| "Invalid MQTT broker port")?)),
+        None => Ok((address, 1883)),
+    }
+}
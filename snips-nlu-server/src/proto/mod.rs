@@ -0,0 +1,3 @@
+//! Messages and service generated by the build script from `proto/snips_nlu.proto`
+
+include!(concat!(env!("OUT_DIR"), "/modules.rs"));
@@ -0,0 +1,167 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+
+use futures::sync::oneshot;
+use futures::Future;
+use grpcio::{RpcContext, RpcStatus, RpcStatusCode, UnarySink, UnarySinkResult};
+use snips_nlu_lib::{Error, ErrorKind, ReloadableNluEngine, Result, SnipsNluEngine};
+
+use conversion::{convert_slots, convert_tagged_tokens};
+use load_engine;
+use proto::snips_nlu::{GetSlotsRequest, IntentParserResult, ParseRequest, ReloadRequest,
+                       ReloadResponse, SlotList, TaggedTokenList};
+use proto::snips_nlu_grpc::SnipsNlu;
+
+/// Service sharing the same engine between the threads of the server
+#[derive(Clone)]
+pub struct SnipsNluService {
+    engine: Arc<ReloadableNluEngine>,
+    model_path: PathBuf,
+    /// Directory of the models which the clients can reload by path, the clients being only
+    /// able to reload the model loaded at startup when it is `None`
+    models_dir: Option<PathBuf>,
+}
+
+impl SnipsNluService {
+    pub fn new(engine: SnipsNluEngine, model_path: PathBuf, models_dir: Option<PathBuf>) -> Self {
+        Self {
+            engine: Arc::new(ReloadableNluEngine::new(engine)),
+            model_path,
+            models_dir,
+        }
+    }
+
+    fn parse_request(&self, request: &ParseRequest) -> Result<IntentParserResult> {
+        let result = self.engine
+            .engine()?
+            .parse(request.get_input(), intents_filter(request))?;
+        Ok(result.into())
+    }
+
+    fn get_slots_request(&self, request: &GetSlotsRequest) -> Result<SlotList> {
+        let slots = self.engine
+            .engine()?
+            .get_slots(request.get_input(), request.get_intent())?;
+        let mut slot_list = SlotList::new();
+        slot_list.set_slots(convert_slots(slots));
+        Ok(slot_list)
+    }
+
+    fn tag_request(&self, request: &ParseRequest) -> Result<TaggedTokenList> {
+        let tagged_tokens = self.engine
+            .engine()?
+            .tag(request.get_input(), intents_filter(request))?;
+        let mut tagged_token_list = TaggedTokenList::new();
+        tagged_token_list.set_tokens(convert_tagged_tokens(tagged_tokens));
+        Ok(tagged_token_list)
+    }
+
+    fn reload_request(&self, model_path: &Path) -> Result<ReloadResponse> {
+        self.engine.reload(load_engine(model_path)?)?;
+        Ok(ReloadResponse::new())
+    }
+
+    /// Returns the model to reload, which is either the model loaded at startup or a model of the
+    /// models directory, so that the clients can't make the server read arbitrary paths
+    fn reload_path(&self, request: &ReloadRequest) -> ::std::result::Result<PathBuf, RpcStatus> {
+        let requested_path = request.get_model_path();
+        if requested_path.is_empty() {
+            return Ok(self.model_path.clone());
+        }
+        let models_dir = self.models_dir.as_ref().ok_or_else(|| {
+            permission_denied("Models can't be reloaded by path without a models directory")
+        })?;
+        let models_dir = models_dir.canonicalize().map_err(|error| {
+            RpcStatus::new(RpcStatusCode::Internal, Some(error.to_string()))
+        })?;
+        if let Ok(path) = models_dir.join(requested_path).canonicalize() {
+            if path.starts_with(&models_dir) && path != models_dir {
+                return Ok(path);
+            }
+        }
+        Err(permission_denied(&format!(
+            "Model {:?} not found in the models directory",
+            requested_path
+        )))
+    }
+}
+
+impl SnipsNlu for SnipsNluService {
+    fn parse(&mut self, ctx: RpcContext, req: ParseRequest, sink: UnarySink<IntentParserResult>) {
+        reply(ctx, sink, self.parse_request(&req))
+    }
+
+    fn get_slots(&mut self, ctx: RpcContext, req: GetSlotsRequest, sink: UnarySink<SlotList>) {
+        reply(ctx, sink, self.get_slots_request(&req))
+    }
+
+    fn tag(&mut self, ctx: RpcContext, req: ParseRequest, sink: UnarySink<TaggedTokenList>) {
+        reply(ctx, sink, self.tag_request(&req))
+    }
+
+    /// Loads the model on a separate thread, so that the completion queue of the request keeps
+    /// serving the other requests in the meantime
+    fn reload(&mut self, ctx: RpcContext, req: ReloadRequest, sink: UnarySink<ReloadResponse>) {
+        let model_path = match self.reload_path(&req) {
+            Ok(model_path) => model_path,
+            Err(status) => return ctx.spawn(sink.fail(status).map_err(log_send_error)),
+        };
+        let service = self.clone();
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            // The receiver is only dropped when the request is cancelled
+            let _ = sender.send(service.reload_request(&model_path));
+        });
+        let response = receiver.then(move |received| {
+            let result = received.unwrap_or_else(|_| Err("The reload thread panicked".into()));
+            respond(sink, result)
+        });
+        ctx.spawn(response.map_err(log_send_error))
+    }
+}
+
+fn intents_filter(request: &ParseRequest) -> Option<&[String]> {
+    if request.get_intents_filter().is_empty() {
+        None
+    } else {
+        Some(request.get_intents_filter())
+    }
+}
+
+fn reply<T>(ctx: RpcContext, sink: UnarySink<T>, result: Result<T>) {
+    ctx.spawn(respond(sink, result).map_err(log_send_error))
+}
+
+fn respond<T>(sink: UnarySink<T>, result: Result<T>) -> UnarySinkResult {
+    match result {
+        Ok(message) => sink.success(message),
+        Err(error) => {
+            let message = error
+                .iter()
+                .map(|cause| cause.to_string())
+                .collect::<Vec<_>>()
+                .join(": ");
+            sink.fail(RpcStatus::new(status_code(&error), Some(message)))
+        }
+    }
+}
+
+/// Errors caused by the request are reported as invalid arguments, the other ones as internal
+/// errors
+fn status_code(error: &Error) -> RpcStatusCode {
+    match *error.kind() {
+        ErrorKind::UnknownIntents(_) | ErrorKind::InputTooLong(_, _) => {
+            RpcStatusCode::InvalidArgument
+        }
+        _ => RpcStatusCode::Internal,
+    }
+}
+
+fn permission_denied(message: &str) -> RpcStatus {
+    RpcStatus::new(RpcStatusCode::PermissionDenied, Some(message.to_string()))
+}
+
+fn log_send_error(error: ::grpcio::Error) {
+    eprintln!("Failed to send the response: {:?}", error)
+}
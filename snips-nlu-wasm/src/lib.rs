@@ -0,0 +1,66 @@
+//! WebAssembly bindings of the engine, to be built with `wasm-pack` and used from JavaScript:
+//!
+//! ```js
+//! const engine = new Engine(configJson);
+//! const result = engine.parse("Make me two cups of coffee");
+//! ```
+//!
+//! The engine is built from bytes only, either the JSON configuration of a trained model or its
+//! zip archive with `Engine.fromZip(bytes)`, as there is no file system in the browser.
+//!
+//! The library is built without its default features: the parsers run one after the other, no
+//! builtin entity is extracted, and models with CRF slot fillers fail to load.
+
+extern crate serde_json;
+extern crate snips_nlu_lib;
+extern crate wasm_bindgen;
+
+use std::io::Cursor;
+
+use snips_nlu_lib::{result_to_json, slots_to_json, NluEngineConfiguration, SnipsNluEngine};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct Engine {
+    engine: SnipsNluEngine,
+}
+
+#[wasm_bindgen]
+impl Engine {
+    #[wasm_bindgen(constructor)]
+    pub fn new(config_json: &str) -> Result<Engine, JsValue> {
+        let configuration: NluEngineConfiguration =
+            serde_json::from_str(config_json).map_err(to_js_error)?;
+        let engine = SnipsNluEngine::new(configuration).map_err(to_js_error)?;
+        Ok(Engine { engine })
+    }
+
+    /// Builds the engine from the bytes of the zip archive of a trained model
+    #[wasm_bindgen(js_name = fromZip)]
+    pub fn from_zip(zip_bytes: &[u8]) -> Result<Engine, JsValue> {
+        let engine =
+            SnipsNluEngine::from_zip(Cursor::new(zip_bytes.to_vec())).map_err(to_js_error)?;
+        Ok(Engine { engine })
+    }
+
+    /// Returns the parsing result as an object in the versioned format of the JSON results of the
+    /// other bindings
+    pub fn parse(&self, text: &str) -> Result<JsValue, JsValue> {
+        let result = self.engine.parse(text, None).map_err(to_js_error)?;
+        let json_result = result_to_json(&result).map_err(to_js_error)?;
+        JsValue::from_serde(&json_result).map_err(to_js_error)
+    }
+
+    /// Returns the slots of the intent in the versioned format of the JSON slots of the other
+    /// bindings
+    #[wasm_bindgen(js_name = getSlots)]
+    pub fn get_slots(&self, text: &str, intent: &str) -> Result<JsValue, JsValue> {
+        let slots = self.engine.get_slots(text, intent).map_err(to_js_error)?;
+        let json_slots = slots_to_json(&slots).map_err(to_js_error)?;
+        JsValue::from_serde(&json_slots).map_err(to_js_error)
+    }
+}
+
+fn to_js_error<E: ::std::fmt::Display>(error: E) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}